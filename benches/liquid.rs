@@ -31,3 +31,54 @@ fn bench_render_text(b: &mut test::Bencher) {
     template.render(&data).unwrap();
     b.iter(|| template.render(&data));
 }
+
+/// A page that is mostly markup, like most site templates.
+fn text_heavy() -> String {
+    let section = r#"<section class="product">
+  <header class="product__header">
+    <h2 class="product__title">{{ product.title | escape }}</h2>
+    <p class="product__vendor">Sold by our partners, shipped from the nearest warehouse.</p>
+  </header>
+  <div class="product__body">
+    <p>Every order is checked by hand before it leaves, and arrives within five work days.</p>
+    {% if product.available %}<button class="button button--primary">Add to cart</button>{% endif %}
+  </div>
+</section>
+"#;
+    section.repeat(50)
+}
+
+/// A page that is mostly tags and outputs.
+fn tag_heavy() -> String {
+    let section = r#"{% for item in items %}{% if item.visible %}{{ item.title | upcase }}{% assign total = total | plus: item.price %}{% else %}{{ item.id }}{% endif %}{% endfor %}
+"#;
+    section.repeat(50)
+}
+
+#[bench]
+fn bench_parse_text_heavy(b: &mut test::Bencher) {
+    let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    let text = text_heavy();
+    b.iter(|| parser.parse(&text));
+}
+
+#[bench]
+fn bench_parse_compiled_text_heavy(b: &mut test::Bencher) {
+    let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    let (_, compiled) = parser.compile(&text_heavy()).unwrap();
+    b.iter(|| parser.parse_compiled(&compiled));
+}
+
+#[bench]
+fn bench_parse_tag_heavy(b: &mut test::Bencher) {
+    let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    let text = tag_heavy();
+    b.iter(|| parser.parse(&text));
+}
+
+#[bench]
+fn bench_parse_compiled_tag_heavy(b: &mut test::Bencher) {
+    let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    let (_, compiled) = parser.compile(&tag_heavy()).unwrap();
+    b.iter(|| parser.parse_compiled(&compiled));
+}
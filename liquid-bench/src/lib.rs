@@ -0,0 +1,287 @@
+//! Representative workloads for measuring the performance of Liquid templates and plugins.
+//!
+//! Each `Workload` is a template, its partials, and the globals it renders with.  Measure them
+//! against the plugins you use by giving a `ParserBuilder`:
+//!
+//! ```rust
+//! let measurements = liquid_bench::measure_all(
+//!     || liquid::ParserBuilder::with_liquid().extra_filters(),
+//!     2,
+//! ).unwrap();
+//! assert_eq!(measurements.len(), liquid_bench::workloads().len());
+//! ```
+
+use std::fmt;
+use std::time;
+
+use liquid::error::Result;
+use liquid::partials::{EagerCompiler, InMemorySource};
+use liquid::value::{Array, Object, Value};
+use liquid::{Parser, ParserBuilder};
+
+/// A template, with its partials and globals, exercising one part of Liquid.
+#[derive(Clone, Debug)]
+pub struct Workload {
+    name: &'static str,
+    template: String,
+    partials: Vec<(String, String)>,
+    globals: Object,
+}
+
+impl Workload {
+    /// Create a workload rendering `template` with `globals`.
+    pub fn new<S: Into<String>>(name: &'static str, template: S, globals: Object) -> Self {
+        Self {
+            name,
+            template: template.into(),
+            partials: Vec::new(),
+            globals,
+        }
+    }
+
+    /// Add a partial the template includes.
+    pub fn with_partial<N: Into<String>, S: Into<String>>(mut self, name: N, source: S) -> Self {
+        self.partials.push((name.into(), source.into()));
+        self
+    }
+
+    /// What the workload is called in reports.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The source of the template.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// The globals the template is rendered with.
+    pub fn globals(&self) -> &Object {
+        &self.globals
+    }
+
+    /// Build a parser out of `builder` with the partials of the workload.
+    pub fn parser(&self, builder: ParserBuilder) -> Result<Parser> {
+        let mut source = InMemorySource::new();
+        for (name, partial) in &self.partials {
+            source.add(name.as_str(), partial.as_str());
+        }
+        builder.partials(EagerCompiler::new(source)).build()
+    }
+
+    /// Parse and render the template `iterations` times each with `parser`.
+    pub fn measure(&self, parser: &Parser, iterations: usize) -> Result<Measurement> {
+        let iterations = iterations.max(1);
+
+        let start = time::Instant::now();
+        for _ in 1..iterations {
+            parser.parse(&self.template)?;
+        }
+        let template = parser.parse(&self.template)?;
+        let parse = start.elapsed();
+
+        let start = time::Instant::now();
+        for _ in 1..iterations {
+            template.render(&self.globals)?;
+        }
+        let output = template.render(&self.globals)?;
+        let render = start.elapsed();
+
+        Ok(Measurement {
+            name: self.name,
+            iterations,
+            parse,
+            render,
+            output_len: output.len(),
+        })
+    }
+}
+
+/// How long a workload took to parse and render.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    /// The name of the workload.
+    pub name: &'static str,
+    /// How many times the template was parsed, and rendered.
+    pub iterations: usize,
+    /// The total time spent parsing.
+    pub parse: time::Duration,
+    /// The total time spent rendering.
+    pub render: time::Duration,
+    /// The length of the output, in bytes.
+    pub output_len: usize,
+}
+
+impl Measurement {
+    /// The average time to parse the template once.
+    pub fn parse_per_iteration(&self) -> time::Duration {
+        self.parse / self.iterations as u32
+    }
+
+    /// The average time to render the template once.
+    pub fn render_per_iteration(&self) -> time::Duration {
+        self.render / self.iterations as u32
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<16} parse {:>10.1?}  render {:>10.1?}  ({} bytes, {} iterations)",
+            self.name,
+            self.parse_per_iteration(),
+            self.render_per_iteration(),
+            self.output_len,
+            self.iterations
+        )
+    }
+}
+
+/// Measure every workload with the parsers `builder` creates.
+pub fn measure_all<F>(builder: F, iterations: usize) -> Result<Vec<Measurement>>
+where
+    F: Fn() -> ParserBuilder,
+{
+    workloads()
+        .iter()
+        .map(|workload| {
+            let parser = workload.parser(builder())?;
+            workload.measure(&parser, iterations)
+        })
+        .collect()
+}
+
+/// The standard workloads.
+pub fn workloads() -> Vec<Workload> {
+    vec![big_loop(), heavy_filters(), deep_includes(), escape_heavy()]
+}
+
+fn object(entries: Vec<(&'static str, Value)>) -> Object {
+    entries
+        .into_iter()
+        .map(|(key, value)| (key.into(), value))
+        .collect()
+}
+
+/// A loop over many items, with conditions and `forloop` lookups.
+pub fn big_loop() -> Workload {
+    let items: Array = (0..1000)
+        .map(|i| {
+            Value::Object(object(vec![
+                ("name", Value::scalar(format!("Item {}", i))),
+                ("price", Value::scalar(i * 3 % 97)),
+                ("active", Value::scalar(i % 3 != 0)),
+            ]))
+        })
+        .collect();
+    Workload::new(
+        "big_loop",
+        "{% for item in items %}\
+         {{ forloop.index }}. {{ item.name }} \
+         {% if item.active and item.price > 10 %}{{ item.price }}{% else %}-{% endif %}\
+         {% unless forloop.last %}, {% endunless %}\
+         {% endfor %}",
+        object(vec![("items", Value::Array(items))]),
+    )
+}
+
+/// Long filter chains on strings and arrays.
+pub fn heavy_filters() -> Workload {
+    let words: Array = (0..300)
+        .map(|i| Value::scalar(format!("word number {} of the alphabet", i)))
+        .collect();
+    Workload::new(
+        "heavy_filters",
+        "{% for word in words %}\
+         {{ word | upcase | replace: 'O', '0' | append: '!' | truncate: 24 \
+            | split: ' ' | reverse | join: '-' | downcase | capitalize }}\
+         {% endfor %}\
+         {{ words | sort | uniq | first | size }} \
+         {{ words | map: 'size' | join: ',' | size }}",
+        object(vec![("words", Value::Array(words))]),
+    )
+}
+
+/// Partials including partials, ten deep, included many times.
+pub fn deep_includes() -> Workload {
+    let mut workload = Workload::new(
+        "deep_includes",
+        "{% for section in sections %}{% include 'level0' %}{% endfor %}",
+        object(vec![(
+            "sections",
+            Value::Array(
+                (0..50)
+                    .map(|i| Value::scalar(format!("Section {}", i)))
+                    .collect(),
+            ),
+        )]),
+    );
+    for level in 0..10 {
+        workload = workload.with_partial(
+            format!("level{}", level),
+            format!(
+                "<div class=\"level{}\">{{% include 'level{}' %}}</div>",
+                level,
+                level + 1
+            ),
+        );
+    }
+    workload.with_partial("level10", "<p>{{ section | escape }}</p>")
+}
+
+/// HTML output where most values need escaping.
+pub fn escape_heavy() -> Workload {
+    let comments: Array = (0..500)
+        .map(|i| {
+            Value::Object(object(vec![
+                (
+                    "author",
+                    Value::scalar(format!("<user {}> & \"friends\"", i)),
+                ),
+                (
+                    "body",
+                    Value::scalar(
+                        "<script>alert('x')</script> 1 < 2 && 3 > 2 \"quoted\" 'single'".repeat(4),
+                    ),
+                ),
+            ]))
+        })
+        .collect();
+    Workload::new(
+        "escape_heavy",
+        "{% for comment in comments %}\
+         <p title=\"{{ comment.author | escape }}\">{{ comment.body | escape_once }}</p>\
+         {{ comment.body | strip_html | escape }}\
+         {% endfor %}",
+        object(vec![("comments", Value::Array(comments))]),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn workloads_render() {
+        for workload in workloads() {
+            let parser = workload
+                .parser(ParserBuilder::with_liquid().extra_filters())
+                .unwrap();
+            let measurement = workload.measure(&parser, 1).unwrap();
+            assert!(measurement.output_len > 0, "{}", workload.name());
+        }
+    }
+
+    #[test]
+    fn deep_includes_reach_the_bottom() {
+        let workload = deep_includes();
+        let parser = workload.parser(ParserBuilder::with_liquid()).unwrap();
+        let output = parser
+            .parse(workload.template())
+            .unwrap()
+            .render(workload.globals())
+            .unwrap();
+        assert!(output.contains("<div class=\"level9\"><p>Section 49</p></div>"));
+    }
+}
@@ -0,0 +1,31 @@
+use std::env;
+use std::process;
+
+fn main() {
+    let iterations = match env::args().nth(1) {
+        Some(arg) => match arg.parse() {
+            Ok(iterations) => iterations,
+            Err(_) => {
+                eprintln!("usage: liquid-bench [ITERATIONS]");
+                process::exit(2);
+            }
+        },
+        None => 100,
+    };
+
+    let measurements = liquid_bench::measure_all(
+        || liquid::ParserBuilder::with_liquid().extra_filters(),
+        iterations,
+    );
+    match measurements {
+        Ok(measurements) => {
+            for measurement in measurements {
+                println!("{}", measurement);
+            }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -8,7 +8,7 @@ use serde_yaml;
 
 use std::ffi;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Read, Write};
 use std::path;
 
 use structopt::StructOpt;
@@ -33,9 +33,9 @@ fn load_json(path: &path::Path) -> Result<liquid::value::Value, Box<dyn std::err
 
 fn build_context(path: &path::Path) -> Result<liquid::value::Object, Box<dyn std::error::Error>> {
     let extension = path.extension().unwrap_or_else(|| ffi::OsStr::new(""));
-    let value = if extension == ffi::OsStr::new("yaml") {
+    let value = if extension == ffi::OsStr::new("yaml") || extension == ffi::OsStr::new("yml") {
         load_yaml(path)
-    } else if extension == ffi::OsStr::new("yaml") {
+    } else if extension == ffi::OsStr::new("json") {
         load_json(path)
     } else {
         Err(Error::new("Unsupported file type"))?
@@ -48,34 +48,115 @@ fn build_context(path: &path::Path) -> Result<liquid::value::Object, Box<dyn std
     Ok(value)
 }
 
+/// Set the global `assignment` is of, e.g. `user.name=Ada`.
+///
+/// The value is read as JSON, e.g. `3` or `[1, 2]`, falling back to a string.  Dots in the key
+/// set the entries of objects, which are created as needed.
+fn set_global(
+    globals: &mut liquid::value::Object,
+    assignment: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut split = assignment.splitn(2, '=');
+    let key = split.next().unwrap_or_default();
+    let value = split
+        .next()
+        .ok_or_else(|| Error::new("`--set` takes `key=value`"))?;
+    if key.is_empty() {
+        Err(Error::new("`--set` takes `key=value`"))?;
+    }
+    let value = serde_json::from_str(value)
+        .unwrap_or_else(|_| liquid::value::Value::scalar(value.to_owned()));
+
+    let mut keys: Vec<_> = key.split('.').collect();
+    let last = keys.pop().expect("split returns at least one key");
+    let mut object = globals;
+    for key in keys {
+        let entry = object
+            .entry(liquid::value::Key::from(key.to_owned()))
+            .or_insert_with(|| liquid::value::Value::Object(liquid::value::Object::new()));
+        if entry.as_object().is_none() {
+            *entry = liquid::value::Value::Object(liquid::value::Object::new());
+        }
+        object = entry.as_object_mut().expect("the entry was made an object");
+    }
+    object.insert(liquid::value::Key::from(last.to_owned()), value);
+    Ok(())
+}
+
 #[derive(StructOpt)]
+#[structopt(name = "liquid", about = "Render a liquid template")]
 struct Args {
+    /// The template, read from stdin when left out
     #[structopt(long, parse(from_os_str))]
-    input: std::path::PathBuf,
+    input: Option<std::path::PathBuf>,
 
+    /// Where to write the output, stdout when left out
     #[structopt(long, parse(from_os_str))]
     output: Option<std::path::PathBuf>,
 
+    /// A JSON or YAML file of globals, later files overriding earlier ones
+    #[structopt(long, parse(from_os_str), number_of_values = 1)]
+    context: Vec<std::path::PathBuf>,
+
+    /// A global, as `key=value`, overriding those of files; `value` is JSON or else a string
+    #[structopt(long, number_of_values = 1)]
+    set: Vec<String>,
+
+    /// The directory partials are included from, with or without their `.liquid` extension
     #[structopt(long, parse(from_os_str))]
-    context: Option<std::path::PathBuf>,
+    include_dir: Option<std::path::PathBuf>,
+
+    /// Only parse the template, reporting all of its errors
+    #[structopt(long)]
+    check: bool,
 }
 
 fn run() -> Result<i32, Box<dyn std::error::Error>> {
     let args = Args::from_args();
 
-    let parser = liquid::ParserBuilder::with_liquid()
+    let builder = liquid::ParserBuilder::with_liquid()
         .extra_filters()
-        .jekyll_filters()
-        .build()
-        .expect("should succeed without partials");
-    let template = parser.parse_file(&args.input)?;
-
-    let data = args
-        .context
-        .as_ref()
-        .map(|p| build_context(p.as_path()))
-        .map_or(Ok(None), |r| r.map(Some))?
-        .unwrap_or_else(liquid::value::Object::new);
+        .jekyll_filters();
+    let parser = match args.include_dir {
+        Some(ref dir) => {
+            let source = liquid::partials::FsSource::new(dir.as_path()).with_extension("liquid");
+            builder
+                .partials(liquid::partials::LazyCompiler::new(source))
+                .build()?
+        }
+        None => builder.build()?,
+    };
+
+    let mut text = String::new();
+    match args.input {
+        Some(ref path) => {
+            fs::File::open(path)?.read_to_string(&mut text)?;
+        }
+        None => {
+            io::stdin().read_to_string(&mut text)?;
+        }
+    }
+
+    if args.check {
+        return match parser.parse_all_errors(&text) {
+            Ok(_) => Ok(0),
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("{}", error);
+                }
+                Ok(1)
+            }
+        };
+    }
+    let template = parser.parse(&text)?;
+
+    let mut data = liquid::value::Object::new();
+    for path in &args.context {
+        data.extend(build_context(path.as_path())?);
+    }
+    for assignment in &args.set {
+        set_global(&mut data, assignment)?;
+    }
     let output = template.render(&data)?;
     match args.output {
         Some(path) => {
@@ -83,7 +164,7 @@ fn run() -> Result<i32, Box<dyn std::error::Error>> {
             out.write_all(output.as_bytes())?;
         }
         None => {
-            println!("{}", output);
+            io::stdout().write_all(output.as_bytes())?;
         }
     }
 
@@ -91,6 +172,33 @@ fn run() -> Result<i32, Box<dyn std::error::Error>> {
 }
 
 fn main() {
-    let code = run().unwrap();
+    let code = run().unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        1
+    });
     std::process::exit(code);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid::value::Value;
+
+    #[test]
+    fn set_globals() {
+        let mut globals = liquid::value::Object::new();
+        set_global(&mut globals, "title=Home=page").unwrap();
+        set_global(&mut globals, "count=3").unwrap();
+        set_global(&mut globals, "user.name=Ada").unwrap();
+        set_global(&mut globals, "user.tags=[\"a\"]").unwrap();
+        assert_eq!(globals["title"], Value::scalar("Home=page"));
+        assert_eq!(globals["count"], Value::scalar(3));
+        let user = globals["user"].as_object().unwrap();
+        assert_eq!(user["name"], Value::scalar("Ada"));
+        assert_eq!(user["tags"], Value::array(vec![Value::scalar("a")]));
+
+        assert!(set_global(&mut globals, "title").is_err());
+        assert!(set_global(&mut globals, "=1").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use liquid_error::Result;
 use liquid_interpreter::Renderable;
 
+use super::Deprecation;
 use super::Language;
 use super::TagBlock;
 use super::TagTokenIter;
@@ -19,6 +20,11 @@ pub trait BlockReflection {
     fn spec(&self) -> Option<&'static str> {
         None
     }
+
+    /// Whether the plugin should no longer be used.
+    fn deprecated(&self) -> Option<Deprecation> {
+        None
+    }
 }
 
 /// A trait for creating custom custom block-size tags (`{% if something %}{% endif %}`).
@@ -67,3 +73,51 @@ where
         Box::new(filter)
     }
 }
+
+/// A trait for creating custom blocks whose content isn't liquid, like the `raw` block, e.g.
+/// `{% highlight rust %}fn main() {}{% endhighlight %}`.
+///
+/// The block is handed the text between its start and end tags as is.  Blocks of the same
+/// name nest, so `{% endhighlight %}` closes the innermost open `{% highlight %}`.
+///
+/// Register it wrapped in a [`RawBody`](struct.RawBody.html).
+pub trait ParseRawBlock: Send + Sync + Clone {
+    fn parse(
+        &self,
+        arguments: TagTokenIter,
+        body: &str,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>>;
+
+    fn reflection(&self) -> &dyn BlockReflection;
+}
+
+/// Turns a [`ParseRawBlock`](trait.ParseRawBlock.html) into a `ParseBlock`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RawBody<B>(pub B);
+
+impl<B> RawBody<B> {
+    pub fn new(block: B) -> Self {
+        RawBody(block)
+    }
+}
+
+impl<B> ParseBlock for RawBody<B>
+where
+    B: 'static + ParseRawBlock,
+{
+    fn parse(
+        &self,
+        arguments: TagTokenIter,
+        mut block: TagBlock,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let body = block.escape_liquid(true)?;
+        block.assert_empty();
+        self.0.parse(arguments, body, options)
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self.0.reflection()
+    }
+}
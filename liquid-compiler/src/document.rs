@@ -0,0 +1,493 @@
+use std::cmp;
+use std::ops::Range;
+use std::sync::Arc;
+
+use liquid_error::Error;
+
+use super::parser::{lex, Lexeme};
+use super::{collect_warnings, parse_all_errors};
+use super::{Language, NodeKind, Warning};
+
+/// A node of the outline of a template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    pub kind: NodeKind,
+    /// Where the node is in the source, in bytes, up to the end tag of blocks.
+    pub span: Range<usize>,
+    /// The nodes inside a block, including its `else`-like tags.
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn new(kind: NodeKind, span: Range<usize>) -> Self {
+        Self {
+            kind,
+            span,
+            children: Vec::new(),
+        }
+    }
+
+    fn shift(&mut self, delta: isize) {
+        self.span = shift(self.span.start, delta)..shift(self.span.end, delta);
+        for child in &mut self.children {
+            child.shift(delta);
+        }
+    }
+}
+
+/// How bad a `Diagnostic` is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The template can't be parsed.
+    Error,
+    /// The template can be parsed, see `Warning`.
+    Warning,
+}
+
+/// A problem with a `Document`, located in its source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Where the problem is, in bytes.
+    pub span: Range<usize>,
+    /// What the problem is, in short.
+    pub message: String,
+    /// The problem with its context, as it would be reported when parsing.
+    pub detail: String,
+}
+
+/// What a `Completion` is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompletionKind {
+    Tag,
+    /// The start tag of a block.
+    Block,
+    /// The end tag of the block the cursor is in.
+    EndTag,
+    Filter,
+}
+
+/// A name that may be typed at a cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// The description of the tag, block or filter.
+    pub detail: String,
+    /// The part of the name already typed, that the completion replaces.
+    pub span: Range<usize>,
+}
+
+/// A template being edited, e.g. by an editor through a language server.
+///
+/// A document has an outline of its source, of the text, outputs, tags and blocks in it, and the
+/// `Diagnostic`s of parsing it.  Editing it only parses again the top-level nodes of the outline
+/// that the edit may change.  Each top-level node is diagnosed on its own, so problems spanning
+/// several of them, like a loop variable shadowing an earlier `assign`, are not reported.
+///
+/// Offsets are in bytes from the start of the source; lines and columns start at 1, columns
+/// counting characters.
+#[derive(Clone)]
+pub struct Document {
+    source: String,
+    language: Arc<Language>,
+    nodes: Vec<Node>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Document {
+    /// Parse `source` with `language`.
+    pub fn new<S: Into<String>>(source: S, language: Arc<Language>) -> Self {
+        let mut document = Self {
+            source: source.into(),
+            language,
+            nodes: Vec::new(),
+            diagnostics: Vec::new(),
+        };
+        document.reparse();
+        document
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The top-level nodes of the outline.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// The problems with the document, in the order of the source.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Replace the text at `range` by `text`, parsing again what it changes.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is not within the source, or not on character boundaries.
+    pub fn edit(&mut self, range: Range<usize>, text: &str) {
+        let delta = text.len() as isize - range.len() as isize;
+        let old = self.source.clone();
+        self.source.replace_range(range.clone(), text);
+
+        // The nodes the edit touches, widened until the nodes around them are lexed the same
+        // whatever is between them: tags and outputs that don't trim whitespace.
+        let first = self.nodes.iter().position(|n| n.span.end >= range.start);
+        let last = self.nodes.iter().rposition(|n| n.span.start <= range.end);
+        let (mut first, mut last) = match (first, last) {
+            (Some(first), Some(last)) if first <= last => (first, last),
+            _ => return self.reparse(),
+        };
+        while first > 0 && !ends_cleanly(&old, &self.nodes[first - 1]) {
+            first -= 1;
+        }
+        while last + 1 < self.nodes.len() && !starts_cleanly(&old, &self.nodes[last + 1]) {
+            last += 1;
+        }
+
+        let old_region = self.nodes[first].span.start..self.nodes[last].span.end;
+        let region = old_region.start..shift(old_region.end, delta);
+        let lexemes = lex(&self.source[region.clone()], self.language.arithmetic());
+        if let Some(Lexeme::Text { invalid: true, .. }) = lexemes.last() {
+            // Invalid markup may be valid once followed by what is after it.
+            return self.reparse();
+        }
+        let (nodes, balanced) = outline(&lexemes, region.start, region.end, &self.language);
+        if !balanced {
+            return self.reparse();
+        }
+
+        let diagnostics: Vec<_> = nodes
+            .iter()
+            .flat_map(|node| diagnose(&self.source, node, &self.language))
+            .collect();
+        let mut kept = Vec::with_capacity(self.diagnostics.len() + diagnostics.len());
+        for mut diagnostic in self.diagnostics.drain(..) {
+            if diagnostic.span.start < old_region.start {
+                kept.push(diagnostic);
+            } else if diagnostic.span.start >= old_region.end {
+                diagnostic.span =
+                    shift(diagnostic.span.start, delta)..shift(diagnostic.span.end, delta);
+                kept.push(diagnostic);
+            }
+        }
+        let at = kept
+            .iter()
+            .position(|d| d.span.start >= region.end)
+            .unwrap_or(kept.len());
+        kept.splice(at..at, diagnostics);
+        self.diagnostics = kept;
+
+        for node in &mut self.nodes[last + 1..] {
+            node.shift(delta);
+        }
+        self.nodes.splice(first..=last, nodes);
+    }
+
+    /// The nodes at `offset`, from the top-level one to the innermost.
+    pub fn nodes_at(&self, offset: usize) -> Vec<&Node> {
+        let mut path = Vec::new();
+        let mut nodes = &self.nodes;
+        while let Some(node) = nodes
+            .iter()
+            .find(|n| n.span.start <= offset && offset < n.span.end)
+        {
+            path.push(node);
+            nodes = &node.children;
+        }
+        path
+    }
+
+    /// The innermost node at `offset`.
+    pub fn node_at(&self, offset: usize) -> Option<&Node> {
+        self.nodes_at(offset).pop()
+    }
+
+    /// The names that may be typed at `offset`: tags after `{%`, and filters after `|`.
+    pub fn completions(&self, offset: usize) -> Vec<Completion> {
+        let before = &self.source[..offset];
+        let open = cmp::max(before.rfind("{{"), before.rfind("{%"));
+        let close = cmp::max(before.rfind("}}"), before.rfind("%}"));
+        let open = match open {
+            Some(open) if close.is_none_or(|close| close < open) => open,
+            _ => return Vec::new(),
+        };
+        let is_tag = before[open..].starts_with("{%");
+        let markup = before[open + 2..].trim_start_matches('-');
+        let typed = markup
+            .chars()
+            .rev()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .map(char::len_utf8)
+            .sum::<usize>();
+        let prefix = &before[offset - typed..];
+        let span = offset - typed..offset;
+        let head = markup[..markup.len() - typed].trim();
+
+        let mut completions = Vec::new();
+        if head.ends_with('|') {
+            for filter in self.language.filters.plugins() {
+                let filter = filter.reflection();
+                completions.push((CompletionKind::Filter, filter.name(), filter.description()));
+            }
+        } else if is_tag && head.is_empty() {
+            for block in self.language.blocks.plugins() {
+                let block = block.reflection();
+                completions.push((
+                    CompletionKind::Block,
+                    block.start_tag(),
+                    block.description(),
+                ));
+            }
+            for tag in self.language.tags.plugins() {
+                let tag = tag.reflection();
+                completions.push((CompletionKind::Tag, tag.tag(), tag.description()));
+            }
+        }
+        completions.sort_by_key(|c| c.1);
+        if is_tag && head.is_empty() {
+            if let Some(end_tag) = self.end_tag_at(offset) {
+                completions.insert(0, (CompletionKind::EndTag, end_tag, ""));
+            }
+        }
+        completions
+            .into_iter()
+            .filter(|(_, name, _)| name.starts_with(prefix))
+            .map(|(kind, label, detail)| Completion {
+                label: label.to_owned(),
+                kind,
+                detail: detail.to_owned(),
+                span: span.clone(),
+            })
+            .collect()
+    }
+
+    /// The end tag of the innermost block still open at `offset`.
+    fn end_tag_at(&self, offset: usize) -> Option<&'static str> {
+        let mut end_tag = None;
+        let mut nodes = &self.nodes;
+        // A block not closed runs to the end of the source.
+        while let Some(node) = nodes.iter().find(|n| {
+            n.span.start < offset && (offset < n.span.end || n.span.end == self.source.len())
+        }) {
+            if let NodeKind::Tag(ref name) = node.kind {
+                if let Some(block) = self.language.blocks.get(name) {
+                    end_tag = Some(block.reflection().end_tag());
+                }
+            }
+            nodes = &node.children;
+        }
+        end_tag
+    }
+
+    /// The line and column of `offset`.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line = before.matches('\n').count() + 1;
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    /// The offset of `line` and `column`, if the source has them.
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        offset(&self.source, line, column)
+    }
+
+    fn reparse(&mut self) {
+        let lexemes = lex(&self.source, self.language.arithmetic());
+        let (nodes, _) = outline(&lexemes, 0, self.source.len(), &self.language);
+        self.diagnostics = nodes
+            .iter()
+            .flat_map(|node| diagnose(&self.source, node, &self.language))
+            .collect();
+        self.nodes = nodes;
+    }
+}
+
+fn shift(offset: usize, delta: isize) -> usize {
+    (offset as isize + delta) as usize
+}
+
+fn offset(text: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = if line == 1 {
+        0
+    } else {
+        text.match_indices('\n').nth(line.checked_sub(2)?)?.0 + 1
+    };
+    let line_text = text[line_start..].split('\n').next().unwrap_or_default();
+    let column = column.checked_sub(1)?;
+    line_text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(Some(line_text.len()))
+        .nth(column)
+        .map(|i| line_start + i)
+}
+
+/// Whether what follows `node` can't change how it is lexed.
+fn ends_cleanly(source: &str, node: &Node) -> bool {
+    let text = &source[node.span.clone()];
+    node.kind != NodeKind::Text && !text.ends_with("-}}") && !text.ends_with("-%}")
+}
+
+/// Whether what precedes `node` can't change how it is lexed.
+fn starts_cleanly(source: &str, node: &Node) -> bool {
+    node.kind != NodeKind::Text && source[node.span.clone()].starts_with('{')
+}
+
+/// Whether the content of the block `name` is not liquid.
+fn is_verbatim(name: &str) -> bool {
+    name == "raw" || name == "comment"
+}
+
+/// Adds `node` to `nodes`, joining text to the text before it.
+fn push(nodes: &mut Vec<Node>, node: Node) {
+    if let Some(last) = nodes.last_mut() {
+        if last.kind == NodeKind::Text && node.kind == NodeKind::Text {
+            last.span.end = node.span.end;
+            return;
+        }
+    }
+    nodes.push(node);
+}
+
+/// Nests the lexemes of the source from `start` to `end` into blocks.
+///
+/// The outline is not balanced if a block is not closed.
+fn outline(
+    lexemes: &[Lexeme<'_>],
+    start: usize,
+    end: usize,
+    language: &Language,
+) -> (Vec<Node>, bool) {
+    let mut balanced = true;
+    let mut nodes = Vec::new();
+    // The blocks still open, with their end tag.
+    let mut open: Vec<(Node, &str)> = Vec::new();
+
+    fn close(open: &mut Vec<(Node, &str)>, nodes: &mut Vec<Node>, end: usize) {
+        let (mut block, _) = open.pop().expect("There is a block to close.");
+        block.span.end = end;
+        match open.last_mut() {
+            Some((parent, _)) => push(&mut parent.children, block),
+            None => push(nodes, block),
+        }
+    }
+
+    for lexeme in lexemes {
+        let (kind, span, name) = match *lexeme {
+            Lexeme::Text { ref span, .. } => (NodeKind::Text, span, None),
+            Lexeme::Output { ref span } => (NodeKind::Output, span, None),
+            Lexeme::Tag { name, ref span } => (NodeKind::Tag(name.to_owned()), span, Some(name)),
+        };
+        let span = start + span.start..start + span.end;
+        let verbatim = open.last().and_then(|(block, _)| match block.kind {
+            NodeKind::Tag(ref name) if is_verbatim(name) => Some(name.as_str()),
+            _ => None,
+        });
+
+        // Within a block that is not liquid, only its own end tag ends it.
+        let ends = name
+            .and_then(|name| open.iter().rposition(|&(_, end)| end == name))
+            .filter(|&ends| verbatim.is_none() || ends + 1 == open.len());
+        // Only comments nest in blocks that are not liquid.
+        let starts = name
+            .filter(|&name| {
+                verbatim.is_none() || (verbatim == Some("comment") && name == "comment")
+            })
+            .and_then(|name| language.blocks.get(name));
+        if let Some(ends) = ends {
+            balanced &= ends + 1 == open.len();
+            while open.len() > ends + 1 {
+                close(&mut open, &mut nodes, span.start);
+            }
+            close(&mut open, &mut nodes, span.end);
+        } else if let Some(block) = starts {
+            open.push((Node::new(kind, span), block.reflection().end_tag()));
+        } else {
+            let kind = if verbatim.is_some() {
+                NodeKind::Text
+            } else {
+                kind
+            };
+            match open.last_mut() {
+                Some((parent, _)) => push(&mut parent.children, Node::new(kind, span)),
+                None => push(&mut nodes, Node::new(kind, span)),
+            }
+        }
+    }
+
+    balanced &= open.is_empty();
+    while !open.is_empty() {
+        close(&mut open, &mut nodes, end);
+    }
+    (nodes, balanced)
+}
+
+/// The problems with a top-level node of the outline.
+fn diagnose(source: &str, node: &Node, language: &Language) -> Vec<Diagnostic> {
+    let text = &source[node.span.clone()];
+    if node.kind == NodeKind::Text && !text.contains('{') {
+        return Vec::new();
+    }
+
+    let (result, warnings) = collect_warnings(|| parse_all_errors(text, language));
+    let errors = result.err().unwrap_or_default();
+    let mut diagnostics: Vec<_> = errors
+        .into_iter()
+        .map(|error| error_diagnostic(text, node, error))
+        .collect();
+    diagnostics.extend(
+        warnings
+            .into_iter()
+            .map(|warning| warning_diagnostic(node, &warning)),
+    );
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+/// Locates `error` in `node`, from where it is to the end of the innermost node there, or of
+/// the line for the tag of a block.
+fn error_diagnostic(text: &str, node: &Node, error: Error) -> Diagnostic {
+    let start = match (error.line(), error.column()) {
+        (Some(line), Some(column)) => offset(text, line, column),
+        _ => None,
+    };
+    let span = match start {
+        Some(start) => {
+            let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+            let start = node.span.start + start;
+            let mut end = node.span.start + line_end;
+            let mut nodes = &node.children;
+            while let Some(node) = nodes
+                .iter()
+                .find(|n| n.span.start <= start && start < n.span.end)
+            {
+                end = node.span.end;
+                nodes = &node.children;
+            }
+            if node.children.is_empty() {
+                end = node.span.end;
+            }
+            start..end
+        }
+        None => node.span.clone(),
+    };
+    Diagnostic {
+        severity: Severity::Error,
+        span,
+        message: error.message().to_owned(),
+        detail: error.to_string(),
+    }
+}
+
+fn warning_diagnostic(node: &Node, warning: &Warning) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Warning,
+        span: node.span.clone(),
+        message: warning.message().to_owned(),
+        detail: warning.to_string(),
+    }
+}
@@ -2,7 +2,10 @@ use std::fmt::{Debug, Display};
 
 use liquid_error::Result;
 use liquid_interpreter::{Context, Expression};
-use liquid_value::Value;
+use liquid_value::{Array, Value};
+
+use super::Deprecation;
+use super::Language;
 
 /// A structure that holds the information of a single parameter in a filter.
 /// This includes its name, description and whether it is optional or required.
@@ -42,6 +45,11 @@ pub trait FilterReflection {
 
     fn positional_parameters(&self) -> &'static [ParameterReflection];
     fn keyword_parameters(&self) -> &'static [ParameterReflection];
+
+    /// Whether the filter should no longer be used.
+    fn deprecated(&self) -> Option<Deprecation> {
+        None
+    }
 }
 
 /// A trait that declares and holds the parameters of a filter.
@@ -121,28 +129,109 @@ pub struct FilterArguments<'a> {
 ///
 /// Filter for a configurable filter:
 /// ```ignore
-/// #[derive(Debug, Display_filter)]
+/// #[derive(Debug, FromFilterParameters, Display_filter)]
 /// #[name = "example"] // The name of the filter for `Display`
-/// // Because construction happens manually (without derive) in `FilterParser`
-/// // no need to derive neither `Default` nor `FromFilterParameters`.
 /// struct ExampleFilter {
-///     #[parameters] // Mark the FilterParameters struct for `Display`
+///     #[parameters] // Mark the FilterParameters struct for derives
 ///     args: ExampleArgs, // A struct that implements `FilterParameters`
-///     state: i32, // See `ParseFilter` example for context
+///     #[state] // Mark the configuration for `FromFilterParameters`
+///     config: ExampleParser, // See `ParseFilter` example for context
 /// }
 ///
-/// impl Filter for AtLeastFilter {
+/// impl Filter for ExampleFilter {
 ///     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
 ///         // Evaluate the `FilterParameters`
 ///         let args = self.args.evaluate(context)?;
 ///
-///         // Implementation of the filter here
+///         // Implementation of the filter here, using `self.config.mode`
 ///     }
 /// }
 /// ```
 pub trait Filter: Send + Sync + Debug + Display {
     // This will evaluate the expressions and evaluate the filter.
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value>;
+
+    /// Whether the filter takes ranges as they are.
+    ///
+    /// Other filters are given the array of a range's numbers instead.
+    fn takes_ranges(&self) -> bool {
+        false
+    }
+
+    /// Whether the filter takes arrays, including lazy arrays, as `Filtered` values, see
+    /// `evaluate_elements`.
+    ///
+    /// Other filters are given the array of a lazy array's elements instead.
+    fn takes_elements(&self) -> bool {
+        false
+    }
+
+    /// Evaluate the filter on an array, reading its elements only as they are needed.
+    ///
+    /// Returning `Filtered::Elements` lets the next filter of the chain read the elements as
+    /// they are computed, so a chain like `where | map | first` doesn't build an array at every
+    /// filter.  The elements are only collected into an array once a filter that doesn't take
+    /// elements, or the end of the chain, needs them.
+    ///
+    /// Elements may be computed while a later filter of the chain runs, so errors of the
+    /// elements it doesn't read are not reported, and their work is spent from its
+    /// `FilterBudget`.
+    ///
+    /// By default the elements are collected for `evaluate`.
+    fn evaluate_elements<'a>(
+        &'a self,
+        input: Filtered<'a>,
+        context: &'a Context<'_>,
+    ) -> Result<Filtered<'a>> {
+        let input = input.into_value()?;
+        self.evaluate(&input, context).map(Filtered::Value)
+    }
+}
+
+/// The elements of an array computed as they are read, see `Filter::evaluate_elements`.
+pub type Elements<'a> = Box<dyn Iterator<Item = Result<Value>> + 'a>;
+
+/// A value evaluated by a filter, see `Filter::evaluate_elements`.
+pub enum Filtered<'a> {
+    /// A value.
+    Value(Value),
+    /// The elements of an array, computed as they are read.
+    Elements(Elements<'a>),
+}
+
+impl<'a> Filtered<'a> {
+    /// The elements of an array, `None` for other values.
+    ///
+    /// The elements of an array value are cloned one at a time as they are read, and those of a
+    /// lazy array are generated as they are read.
+    pub fn into_elements(self) -> Option<Elements<'a>> {
+        match self {
+            Filtered::Value(Value::Array(array)) => {
+                let elements = (0..array.len()).map(move |i| Ok(array[i].clone()));
+                Some(Box::new(elements))
+            }
+            Filtered::Value(Value::Lazy(array)) => Some(Box::new(array.iter().map(Ok))),
+            Filtered::Value(_) => None,
+            Filtered::Elements(elements) => Some(elements),
+        }
+    }
+
+    /// Compute the value, collecting the elements into an array.
+    pub fn into_value(self) -> Result<Value> {
+        match self {
+            Filtered::Value(value) => Ok(value),
+            Filtered::Elements(elements) => elements.collect::<Result<Array>>().map(Value::Array),
+        }
+    }
+}
+
+impl<'a> Debug for Filtered<'a> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self {
+            Filtered::Value(value) => f.debug_tuple("Value").field(value).finish(),
+            Filtered::Elements(_) => f.debug_tuple("Elements").finish(),
+        }
+    }
 }
 
 /// A trait to register a new filter in the `liquid::Parser`.
@@ -192,29 +281,51 @@ pub trait Filter: Send + Sync + Debug + Display {
 ///
 /// ParseFilter for a configurable filter:
 /// ```ignore
-/// #[derive(Clone, FilterReflection)]
+/// #[derive(Clone, Debug, ParseFilter, FilterReflection)]
 /// #[filter(
 ///     name = "example",
 ///     description = "This filter exists for example purposes.",
-///     parameters(ExampleArgs) // A struct that implements `FilterParameters`
+///     parameters(ExampleArgs), // A struct that implements `FilterParameters`
+///     parsed(ExampleFilter), // Must implement `From<(ExampleParser, ExampleArgs)>`
+///     stateful // Build `ExampleFilter` from a clone of `ExampleParser` too
 /// )]
 /// pub struct ExampleParser {
 ///     // You can add as many fields as you find necessary to configure the filter
 ///     // before registering it.
 ///     pub mode: i32,
 /// }
+/// ```
+///
+/// # Configuration
+///
+/// A `ParseFilter` is registered as a value, so it can hold configuration, like a default
+/// format, a currency table or a CDN base URL:
 ///
-/// // For configurable filters, there is no default implementation of `ParseFilter`
+/// ```ignore
+/// let parser = ParserBuilder::with_liquid()
+///     .filter(ExampleParser { mode: 2 })
+///     .build()?;
+/// ```
+///
+/// The configuration flows to `Filter::evaluate` through the `Filter` built by `parse`, once
+/// for every use of the filter in a template: with `stateful`, the derived `parse` builds it
+/// from a clone of the `ParseFilter`, so keep configuration that is costly to clone in an
+/// `Arc`.  Implement `parse` by hand to copy only part of the configuration, or to read the
+/// arguments differently:
+///
+/// ```ignore
 /// impl ParseFilter for ExampleParser {
 ///     fn parse(&self, arguments: FilterArguments) -> Result<Box<Filter>> {
 ///         // Create the `FilterParameters` struct from the given `arguments`
 ///         let args = ExampleArgs::from_args(arguments)?;
-///         // Use the configuration state of the `ParseFilter`
-///         let state = self.state;
 ///
 ///         // Create the `Filter` struct and return it, passing the information
 ///         // about the arguments and the configuration of the `ParseFilter`.
-///         Ok(Box::new(ExampleFilter { args, state }))
+///         Ok(Box::new(ExampleFilter { args, config: self.clone() }))
+///     }
+///
+///     fn reflection(&self) -> &FilterReflection {
+///         self
 ///     }
 /// }
 /// ```
@@ -222,6 +333,18 @@ pub trait ParseFilter: Send + Sync + ParseFilterClone {
     /// Filter `input` based on `arguments`.
     fn parse(&self, arguments: FilterArguments) -> Result<Box<dyn Filter>>;
 
+    /// Like `parse`, for filters whose arguments are themselves Liquid, parsed with the
+    /// registered tags and filters.
+    ///
+    /// Defaults to `parse`.
+    fn parse_with_language(
+        &self,
+        arguments: FilterArguments,
+        _language: &Language,
+    ) -> Result<Box<dyn Filter>> {
+        self.parse(arguments)
+    }
+
     fn reflection(&self) -> &dyn FilterReflection;
 }
 
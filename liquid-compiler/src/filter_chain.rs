@@ -1,45 +1,134 @@
 use std::fmt;
-use std::io::Write;
 
 use itertools;
 
-use super::Filter;
+use super::{Filter, Filtered};
 use liquid_error::{Result, ResultLiquidExt, ResultLiquidReplaceExt};
 use liquid_interpreter::Context;
 use liquid_interpreter::Expression;
+use liquid_interpreter::Output;
+use liquid_interpreter::References;
 use liquid_interpreter::Renderable;
 use liquid_value::Value;
 
+/// A filter, as called in a template.
+#[derive(Debug)]
+pub(crate) struct FilterCall {
+    name: String,
+    line_col: Option<(usize, usize)>,
+    filter: Box<dyn Filter>,
+    // What the filter was called with, for `Renderable::references`.
+    arguments: Vec<Expression>,
+}
+
+impl FilterCall {
+    /// The call of the filter `name` at `line_col` with `arguments`.
+    pub(crate) fn new(
+        name: String,
+        line_col: (usize, usize),
+        filter: Box<dyn Filter>,
+        arguments: Vec<Expression>,
+    ) -> Self {
+        Self {
+            name,
+            line_col: Some(line_col),
+            filter,
+            arguments,
+        }
+    }
+}
+
+impl FilterCall {
+    /// Add the position and the filter to an error of the filter.
+    fn trace<T>(&self, result: Result<T>) -> Result<T> {
+        result
+            .map_err(|error| match self.line_col {
+                Some((line, column)) => error.with_position(line, column),
+                None => error,
+            })
+            .trace("Filter error")
+            .context_key("filter")
+            .value_with(|| format!("{}", self.filter).into())
+    }
+}
+
 /// A `Value` expression.
 #[derive(Debug)]
 pub struct FilterChain {
     entry: Expression,
-    filters: Vec<Box<dyn Filter>>,
+    filters: Vec<FilterCall>,
 }
 
 impl FilterChain {
     /// Create a new expression.
     pub fn new(entry: Expression, filters: Vec<Box<dyn Filter>>) -> Self {
+        let filters = filters
+            .into_iter()
+            .map(|filter| {
+                let display = filter.to_string();
+                let name = display
+                    .split(|c: char| c == ':' || c.is_whitespace())
+                    .next()
+                    .unwrap_or_default()
+                    .to_owned();
+                FilterCall {
+                    name,
+                    line_col: None,
+                    filter,
+                    arguments: Vec::new(),
+                }
+            })
+            .collect();
+        Self { entry, filters }
+    }
+
+    /// Create a new expression, from filters as called in the template.
+    pub(crate) fn with_calls(entry: Expression, filters: Vec<FilterCall>) -> Self {
         Self { entry, filters }
     }
 
     /// Process `Value` expression within `context`'s stack.
     pub fn evaluate(&self, context: &Context) -> Result<Value> {
         // take either the provided value or the value from the provided variable
-        let mut entry = self.entry.evaluate(context)?.to_owned();
+        let mut entry = Filtered::Value(self.entry.evaluate(context)?.to_owned());
 
         // apply all specified filters
-        for filter in &self.filters {
-            entry = filter
-                .evaluate(&entry, context)
-                .trace("Filter error")
-                .context_key("filter")
-                .value_with(|| format!("{}", filter).into())
+        for call in &self.filters {
+            let filter = &call.filter;
+            let takes_elements = filter.takes_elements()
+                && match entry {
+                    Filtered::Value(ref value) => value.is_array() || value.is_lazy(),
+                    Filtered::Elements(_) => true,
+                };
+            if takes_elements {
+                let result = context.run_filter(&call.name, call.line_col, || {
+                    filter.evaluate_elements(entry, context)
+                });
+                entry = match call.trace(result)? {
+                    // Errors of the elements computed later are the errors of this filter too.
+                    Filtered::Elements(elements) => {
+                        Filtered::Elements(Box::new(elements.map(move |e| call.trace(e))))
+                    }
+                    value => value,
+                };
+                continue;
+            }
+
+            let mut value = entry.into_value()?;
+            if (value.is_range() && !filter.takes_ranges()) || value.is_lazy() {
+                value = value.into_materialized();
+            }
+            let result = context.run_filter(&call.name, call.line_col, || {
+                filter.evaluate(&value, context)
+            });
+            let value = call
+                .trace(result)
                 .context_key("input")
-                .value_with(|| format!("{}", entry.source()).into())?;
+                .value_with(|| format!("{}", value.source()).into())?;
+            entry = Filtered::Value(value);
         }
 
-        Ok(entry)
+        entry.into_value()
     }
 }
 
@@ -49,15 +138,42 @@ impl fmt::Display for FilterChain {
             f,
             "{} | {}",
             self.entry,
-            itertools::join(&self.filters, " | ")
+            itertools::join(self.filters.iter().map(|call| &call.filter), " | ")
         )
     }
 }
 
 impl Renderable for FilterChain {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let redaction = context.environment().redaction();
+        match self.entry {
+            Expression::Variable(ref variable) if !redaction.is_empty() => {
+                let path = variable.try_evaluate(context);
+                if let Some(path) = path.filter(|path| redaction.covers(path)) {
+                    redaction.report(&path);
+                    writer
+                        .write_value(redaction.placeholder(), false)
+                        .replace("Failed to render")?;
+                    return Ok(());
+                }
+            }
+            _ => (),
+        }
+
         let entry = self.evaluate(context)?;
-        write!(writer, "{}", entry.to_str()).replace("Failed to render")?;
+        let (rendered, safe) = context.environment().render_output(&entry)?;
+        writer
+            .write_value(&rendered, safe)
+            .replace("Failed to render")?;
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        references.expression(&self.entry);
+        for call in &self.filters {
+            for argument in &call.arguments {
+                references.expression(argument);
+            }
+        }
+    }
 }
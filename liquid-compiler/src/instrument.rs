@@ -0,0 +1,50 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use liquid_interpreter::Renderable;
+
+/// What a node of a template is.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// Text outside of tags and outputs.
+    Text,
+    /// An output, e.g. `{{ name }}`.
+    Output,
+    /// A tag or block, by name.
+    Tag(String),
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NodeKind::Text => write!(f, "text"),
+            NodeKind::Output => write!(f, "output"),
+            NodeKind::Tag(ref name) => write!(f, "{{% {} %}}", name),
+        }
+    }
+}
+
+/// Where a node is in the template source, and what it is.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeInfo {
+    pub line: usize,
+    pub column: usize,
+    /// The bytes of the source the node is, only the start tag of blocks.
+    pub span: Range<usize>,
+    pub kind: NodeKind,
+}
+
+/// Observes the nodes of templates as they are parsed, e.g. to find which are rendered.
+pub trait Instrument: Send + Sync {
+    /// A template is about to be parsed.
+    fn template(&self, source: &str) {
+        let _ = source;
+    }
+
+    /// Wrap a parsed node, typically in a `Renderable` recording its use before rendering it.
+    fn instrument(&self, node: NodeInfo, renderable: Box<dyn Renderable>) -> Box<dyn Renderable>;
+}
+
+/// Shares an `Instrument` between the parsers of a `Language`.
+pub type InstrumentHandle = Arc<dyn Instrument>;
@@ -1,13 +1,53 @@
+use super::BlockReflection;
+use super::FilterReflection;
 use super::ParseBlock;
 use super::ParseFilter;
 use super::ParseTag;
 use super::PluginRegistry;
+use super::TagReflection;
+use liquid_interpreter::Renderable;
+
+use super::{InstrumentHandle, NodeInfo};
+use super::{LoopShadowing, Recovery, Warning, WarningHandler};
+
+/// Which partials an `include` may render when its name is computed, e.g.
+/// `{% include "cards/" | append: card.type %}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum DynamicPartials {
+    /// Partial names are taken literally, e.g. `{% include name %}` renders the partial `name`.
+    #[default]
+    Deny,
+    /// A name may be computed and render any partial.
+    ///
+    /// A variable that is not defined is still taken as a literal name, so
+    /// `{% include footer.html %}` keeps working.
+    Allow,
+    /// A name may be computed but only render one of these partials.
+    Allowlist(Vec<String>),
+}
+
+impl DynamicPartials {
+    /// Whether a computed name may render the partial `name`.
+    pub fn allows(&self, name: &str) -> bool {
+        match *self {
+            DynamicPartials::Deny => false,
+            DynamicPartials::Allow => true,
+            DynamicPartials::Allowlist(ref names) => names.iter().any(|n| n == name),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Language {
     pub blocks: PluginRegistry<Box<dyn ParseBlock>>,
     pub tags: PluginRegistry<Box<dyn ParseTag>>,
     pub filters: PluginRegistry<Box<dyn ParseFilter>>,
+    warning_handler: Option<WarningHandler>,
+    loop_shadowing: LoopShadowing,
+    recovery: Recovery,
+    dynamic_partials: DynamicPartials,
+    arithmetic: bool,
+    instrument: Option<InstrumentHandle>,
     non_exhaustive: (),
 }
 
@@ -15,6 +55,102 @@ impl Language {
     pub fn empty() -> Self {
         Default::default()
     }
+
+    /// The blocks of the language, sorted by their start tag, e.g. to document them.
+    pub fn blocks(&self) -> impl Iterator<Item = &dyn BlockReflection> {
+        let mut blocks: Vec<_> = self.blocks.plugins().map(|p| p.reflection()).collect();
+        blocks.sort_by_key(|b| b.start_tag());
+        blocks.into_iter()
+    }
+
+    /// The tags of the language, sorted by name.
+    pub fn tags(&self) -> impl Iterator<Item = &dyn TagReflection> {
+        let mut tags: Vec<_> = self.tags.plugins().map(|p| p.reflection()).collect();
+        tags.sort_by_key(|t| t.tag());
+        tags.into_iter()
+    }
+
+    /// The filters of the language, sorted by name, with their parameters.
+    pub fn filters(&self) -> impl Iterator<Item = &dyn FilterReflection> {
+        let mut filters: Vec<_> = self.filters.plugins().map(|p| p.reflection()).collect();
+        filters.sort_by_key(|f| f.name());
+        filters.into_iter()
+    }
+
+    /// Receive the `Warning`s found while parsing.
+    pub fn set_warning_handler(&mut self, handler: Option<WarningHandler>) {
+        self.warning_handler = handler;
+    }
+
+    /// Report a problem that does not stop the template from being parsed.
+    pub fn warn(&self, warning: Warning) {
+        super::warning::collect(&warning);
+        if let Some(ref handler) = self.warning_handler {
+            handler(warning);
+        }
+    }
+
+    /// Check whether loop variables shadow other variables of the template.
+    pub fn set_loop_shadowing(&mut self, policy: LoopShadowing) {
+        self.loop_shadowing = policy;
+    }
+
+    pub fn loop_shadowing(&self) -> LoopShadowing {
+        self.loop_shadowing
+    }
+
+    /// Recover from markup that can't be parsed instead of failing the parse.
+    pub fn set_recovery(&mut self, recovery: Recovery) {
+        self.recovery = recovery;
+    }
+
+    pub fn recovery(&self) -> Recovery {
+        self.recovery
+    }
+
+    /// Allow `include` names to be computed, like `{% include "cards/" | append: card.type %}`.
+    pub fn set_dynamic_partials(&mut self, policy: DynamicPartials) {
+        self.dynamic_partials = policy;
+    }
+
+    pub fn dynamic_partials(&self) -> &DynamicPartials {
+        &self.dynamic_partials
+    }
+
+    /// Allow arithmetic in values, like `{{ (width + 10) * 2 }}` or
+    /// `{% if price * quantity > 100 %}`, and parentheses grouping conditions, like
+    /// `{% if (a or b) and c %}`.
+    ///
+    /// This goes beyond the Liquid of Shopify, so it is off by default.  Operators are surrounded
+    /// by spaces, as `a-b` is the variable named so.
+    pub fn set_arithmetic(&mut self, arithmetic: bool) {
+        self.arithmetic = arithmetic;
+    }
+
+    pub fn arithmetic(&self) -> bool {
+        self.arithmetic
+    }
+
+    /// Observe the nodes of the templates parsed with this language.
+    pub fn set_instrument(&mut self, instrument: Option<InstrumentHandle>) {
+        self.instrument = instrument;
+    }
+
+    pub fn instrument(&self) -> Option<&InstrumentHandle> {
+        self.instrument.as_ref()
+    }
+
+    /// Pass a parsed node to the `Instrument`, if any.
+    pub(crate) fn instrumented(
+        &self,
+        node: impl FnOnce() -> NodeInfo,
+        renderable: Box<dyn Renderable>,
+    ) -> Box<dyn Renderable> {
+        match self.instrument {
+            Some(ref instrument) => instrument.instrument(node(), renderable),
+            None => renderable,
+        }
+    }
 }
 
 impl Default for Language {
@@ -23,6 +159,12 @@ impl Default for Language {
             blocks: Default::default(),
             tags: Default::default(),
             filters: Default::default(),
+            warning_handler: None,
+            loop_shadowing: Default::default(),
+            recovery: Default::default(),
+            dynamic_partials: Default::default(),
+            arithmetic: false,
+            instrument: None,
             non_exhaustive: Default::default(),
         }
     }
@@ -7,20 +7,30 @@ extern crate pest;
 extern crate pest_derive;
 
 mod block;
+mod document;
 mod filter;
 mod filter_chain;
+mod instrument;
 mod lang;
+mod located;
 mod parser;
 mod registry;
+mod stdlib;
 mod tag;
 mod text;
+mod warning;
 
 pub use crate::block::*;
+pub use crate::document::*;
 pub use crate::filter::*;
 pub use crate::filter_chain::*;
+pub use crate::instrument::*;
 pub use crate::lang::*;
 pub use crate::parser::*;
 pub use crate::registry::*;
+pub use crate::stdlib::*;
 pub use crate::tag::*;
+pub use crate::warning::*;
 
+use crate::located::Located;
 use crate::text::Text;
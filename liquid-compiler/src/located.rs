@@ -0,0 +1,40 @@
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Output;
+use liquid_interpreter::References;
+use liquid_interpreter::Renderable;
+use liquid_value::Object;
+
+/// A tag or output, locating the errors it renders at where it is in the template.
+#[derive(Debug)]
+pub(crate) struct Located {
+    line: usize,
+    column: usize,
+    renderable: Box<dyn Renderable>,
+}
+
+impl Located {
+    pub(crate) fn new(line: usize, column: usize, renderable: Box<dyn Renderable>) -> Self {
+        Self {
+            line,
+            column,
+            renderable,
+        }
+    }
+}
+
+impl Renderable for Located {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        self.renderable
+            .render_to(writer, context)
+            .map_err(|error| error.with_position(self.line, self.column))
+    }
+
+    fn metadata(&self) -> Option<&Object> {
+        self.renderable.metadata()
+    }
+
+    fn references(&self, references: &mut References) {
+        self.renderable.references(references)
+    }
+}
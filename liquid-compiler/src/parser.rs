@@ -4,17 +4,27 @@
 //! but should be ignored for simple usage.
 
 use std;
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::ptr;
+use std::sync::Arc;
 
 use itertools;
 use liquid_error::{Error, Result, ResultLiquidExt};
+use liquid_interpreter::ArithmeticOperator;
 use liquid_interpreter::Expression;
 use liquid_interpreter::Renderable;
 use liquid_interpreter::Variable;
-use liquid_value::Value;
+use liquid_value::{Key, Scalar, Value};
 
 use super::Language;
+use super::Located;
+use super::LoopShadowing;
+use super::Recovery;
 use super::Text;
-use super::{Filter, FilterArguments, FilterChain};
+use super::Warning;
+use super::{FilterArguments, FilterCall, FilterChain};
+use super::{NodeInfo, NodeKind};
 
 use pest::Parser;
 
@@ -42,9 +52,19 @@ fn convert_pest_error(err: ::pest::error::Error<Rule>) -> Error {
         Rule::Assign => "\"=\"".to_string(),
         Rule::Comma => "\",\"".to_string(),
         Rule::Colon => "\":\"".to_string(),
+        Rule::OpenParen => "\"(\"".to_string(),
+        Rule::CloseParen => "\")\"".to_string(),
+        // Arithmetic and values are told apart after parsing
+        Rule::Arithmetic | Rule::PositionalFilterArgument => "Value".to_string(),
         other => format!("{:?}", other),
     });
-    Error::with_msg(err.to_string())
+    let (line, column) = match err.line_col {
+        ::pest::error::LineColLocation::Pos(start) => start,
+        ::pest::error::LineColLocation::Span(start, _) => start,
+    };
+    Error::with_msg(err.variant.message().into_owned())
+        .with_position(line, column)
+        .with_excerpt(err.line())
 }
 
 /// Generates a `liquid::Error` with the given message pointing to
@@ -57,13 +77,285 @@ fn error_from_pair(pair: Pair, msg: String) -> Error {
     convert_pest_error(pest_error)
 }
 
-/// Parses the provided &str into a number of Renderable items.
-pub fn parse(text: &str, options: &Language) -> Result<Vec<Box<dyn Renderable>>> {
-    let mut liquid = LiquidParser::parse(Rule::LaxLiquidFile, text)
+thread_local! {
+    static ERRORS: RefCell<Option<Vec<Error>>> = const { RefCell::new(None) };
+    static START: Cell<Start> = const { Cell::new(Start::TEMPLATE) };
+    static ARITHMETIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Where the text being parsed starts in its template, for parsing a `Chunk` by itself.
+#[derive(Copy, Clone)]
+struct Start {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Start {
+    const TEMPLATE: Start = Start {
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+}
+
+/// Runs `parse` on text starting at `start` in its template, written in the language `options`.
+fn parse_at<T, F: FnOnce() -> T>(start: Start, options: &Language, parse: F) -> T {
+    let outer = START.with(|cell| cell.replace(start));
+    let result = with_arithmetic(options.arithmetic(), parse);
+    START.with(|cell| cell.set(outer));
+    result
+}
+
+/// Runs `parse` on text with arithmetic, or without.
+fn with_arithmetic<T, F: FnOnce() -> T>(arithmetic: bool, parse: F) -> T {
+    let outer = ARITHMETIC.with(|cell| cell.replace(arithmetic));
+    let result = parse();
+    ARITHMETIC.with(|cell| cell.set(outer));
+    result
+}
+
+/// Parses `text` into its elements, invalid liquid being `InvalidLiquid` tokens.
+///
+/// Arithmetic is only parsed when `arithmetic` is set, as otherwise e.g. `a and -1` would be
+/// tokenized as `a` and `and - 1`.
+fn parse_lax(text: &str, arithmetic: bool) -> Pairs<'_> {
+    let rule = if arithmetic {
+        Rule::LaxArithmeticLiquidFile
+    } else {
+        Rule::LaxLiquidFile
+    };
+    LiquidParser::parse(rule, text)
         .expect("Parsing with Rule::LaxLiquidFile should not raise errors, but InvalidLiquid tokens instead.")
         .next()
         .expect("Unwrapping LiquidFile to access the elements.")
-        .into_inner();
+        .into_inner()
+}
+
+/// The line and column of `position` in the template.
+fn line_col(position: ::pest::Position) -> (usize, usize) {
+    let (line, column) = position.line_col();
+    let start = START.with(Cell::get);
+    if line == 1 {
+        (start.line, start.column + column - 1)
+    } else {
+        (start.line + line - 1, column)
+    }
+}
+
+/// The bytes of the template `span`, of the text being parsed, is.
+fn template_span(span: &::pest::Span) -> Range<usize> {
+    let offset = START.with(Cell::get).offset;
+    offset + span.start()..offset + span.end()
+}
+
+/// Whether `parse_all_errors` is running, recovering from every error it can.
+fn collecting_errors() -> bool {
+    ERRORS.with(|errors| errors.borrow().is_some())
+}
+
+/// Keeps `error` for `parse_all_errors`, if it is running, and gives it back otherwise.
+fn collect_error(error: Error) -> Option<Error> {
+    ERRORS.with(|errors| match *errors.borrow_mut() {
+        Some(ref mut errors) => {
+            errors.push(error);
+            None
+        }
+        None => Some(error),
+    })
+}
+
+/// Applies the `Recovery` policy to `markup` that failed to parse with `error`.
+///
+/// `problem` says what is wrong with the markup in the comment replacing it.
+fn recover(
+    markup: &str,
+    line: usize,
+    problem: &str,
+    error: Error,
+    options: &Language,
+) -> Result<Box<dyn Renderable>> {
+    let error = match collect_error(error) {
+        Some(error) => error,
+        None => return Ok(Box::new(Text::new(markup))),
+    };
+    let text = match options.recovery() {
+        Recovery::Fail => return Err(error),
+        Recovery::Text => markup.to_owned(),
+        // `--` would end the comment early.
+        Recovery::Comment => format!(
+            "<!-- liquid: {} on line {} -->",
+            problem.replace("--", "- -"),
+            line
+        ),
+    };
+    options.warn(
+        Warning::with_msg("Recovered from markup that can't be parsed")
+            .context("markup", markup)
+            .context("line", line.to_string())
+            .context("cause", error.to_string()),
+    );
+    Ok(Box::new(Text::new(text)))
+}
+
+/// Parses the provided &str into a number of Renderable items.
+pub fn parse(text: &str, options: &Language) -> Result<Vec<Box<dyn Renderable>>> {
+    parse_template(text, options, None)
+}
+
+/// A top level piece of a template: text, or an output, tag or block with everything in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    /// Where the chunk is in the template.
+    pub span: Range<usize>,
+    /// The line the chunk starts on, from 1.
+    pub line: usize,
+    /// The column the chunk starts at, from 1.
+    pub column: usize,
+    /// Whether the chunk is text.
+    pub text: bool,
+}
+
+/// Parses the provided &str like `parse`, also splitting it into its `Chunk`s, pushed onto
+/// `chunks`.
+pub fn parse_chunked(
+    text: &str,
+    options: &Language,
+    chunks: &mut Vec<Chunk>,
+) -> Result<Vec<Box<dyn Renderable>>> {
+    parse_template(text, options, Some(chunks))
+}
+
+/// Parses the template `source` from the `Chunk`s `parse_chunked` split it into, each by itself.
+///
+/// The text between outputs and tags isn't parsed again, and parsing small chunks is faster than
+/// parsing the whole template at once.  Only a template `parse_chunked` parsed with the same
+/// tags, blocks and filters is parsed the same.
+pub fn parse_chunks(
+    source: &Arc<str>,
+    chunks: &[Chunk],
+    options: &Language,
+) -> Result<Vec<Box<dyn Renderable>>> {
+    if options.loop_shadowing() != LoopShadowing::Allow {
+        check_loop_shadowing(source, options).map_err(|error| error.with_source(source))?;
+    }
+    if let Some(instrument) = options.instrument() {
+        instrument.template(source);
+    }
+
+    let mut renderables = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        if source.get(chunk.span.clone()).is_none() {
+            return Error::with_msg("Chunk out of the template")
+                .context("span", format!("{:?}", chunk.span))
+                .into_err();
+        }
+        if chunk.text {
+            let line_col = (chunk.line, chunk.column);
+            renderables.push(text(source, chunk.span.clone(), line_col, options));
+            continue;
+        }
+        let start = Start {
+            offset: chunk.span.start,
+            line: chunk.line,
+            column: chunk.column,
+        };
+        let parsed = parse_at(start, options, || {
+            parse_elements(source, chunk.span.clone(), options, None)
+        })
+        .map_err(|error| error.with_source(source))?;
+        renderables.extend(parsed);
+    }
+    Ok(renderables)
+}
+
+fn parse_template(
+    text: &str,
+    options: &Language,
+    chunks: Option<&mut Vec<Chunk>>,
+) -> Result<Vec<Box<dyn Renderable>>> {
+    if options.loop_shadowing() != LoopShadowing::Allow {
+        check_loop_shadowing(text, options)?;
+    }
+    if let Some(instrument) = options.instrument() {
+        instrument.template(text);
+    }
+
+    // Text is rendered from slices of one shared copy of the source, instead of a copy each.
+    // Only the text between outputs and tags is: the contents of `raw` blocks, string literals,
+    // and identifiers are still copied out of it.
+    let source: Arc<str> = Arc::from(text);
+    parse_at(Start::TEMPLATE, options, || {
+        parse_elements(&source, 0..text.len(), options, chunks)
+    })
+    .map_err(|error| error.with_source(text))
+}
+
+/// Parses the provided &str, carrying on after the errors a `Recovery` policy would recover
+/// from, to report every one of them, e.g. for a template editor.
+///
+/// The errors are in the order of the template.  Those a `Recovery` policy can't recover from
+/// end the parse, and are last.
+pub fn parse_all_errors(
+    text: &str,
+    options: &Language,
+) -> std::result::Result<Vec<Box<dyn Renderable>>, Vec<Error>> {
+    let outer = ERRORS.with(|errors| errors.replace(Some(Vec::new())));
+    let result = parse(text, options);
+    let errors = ERRORS.with(|errors| errors.replace(outer));
+    let mut errors: Vec<_> = errors
+        .unwrap_or_default()
+        .into_iter()
+        .map(|error| error.with_source(text))
+        .collect();
+    match result {
+        Ok(renderables) if errors.is_empty() => Ok(renderables),
+        Ok(_) => Err(errors),
+        Err(error) => {
+            errors.push(error);
+            Err(errors)
+        }
+    }
+}
+
+/// Parses the statements of a `{% liquid %}` tag, one tag per line without its `{% %}`, e.g.
+/// `assign x = 1`, into a number of Renderable items.
+pub fn parse_statements(text: &str, options: &Language) -> Result<Vec<Box<dyn Renderable>>> {
+    // Each line becomes a tag on a line of its own, so errors point at the right line, and
+    // whitespace control drops the newlines between them.
+    let mut statements = String::with_capacity(text.len() + 8 * text.lines().count());
+    for line in text.lines() {
+        let line = line.trim();
+        if !line.is_empty() {
+            statements.push_str("{%- ");
+            statements.push_str(line);
+            statements.push_str(" -%}");
+        }
+        statements.push('\n');
+    }
+    if statements.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if options.loop_shadowing() != LoopShadowing::Allow {
+        check_loop_shadowing(&statements, options)?;
+    }
+    let source: Arc<str> = Arc::from(statements.as_str());
+    parse_at(Start::TEMPLATE, options, || {
+        parse_elements(&source, 0..source.len(), options, None)
+    })
+    .map_err(|error| error.with_source(&statements))
+}
+
+/// Parses the `span` of `source`, which starts at `START`, listing its `Chunk`s in `chunks`.
+fn parse_elements(
+    source: &Arc<str>,
+    span: Range<usize>,
+    options: &Language,
+    mut chunks: Option<&mut Vec<Chunk>>,
+) -> Result<Vec<Box<dyn Renderable>>> {
+    let text = &source[span.clone()];
+    let mut liquid = parse_lax(text, options.arithmetic());
 
     let mut renderables = Vec::new();
 
@@ -72,15 +364,260 @@ pub fn parse(text: &str, options: &Language) -> Result<Vec<Box<dyn Renderable>>>
             break;
         }
 
+        let start = element.as_span().start();
+        let (line, column) = line_col(element.as_span().start_pos());
+        let is_text = element.as_rule() == Rule::Raw;
         renderables.push(BlockElement::parse_pair(
             element.into(),
             &mut liquid,
+            source,
             options,
         )?);
+        if let Some(ref mut chunks) = chunks {
+            // A block consumes the elements up to its end tag.
+            let end = liquid
+                .peek()
+                .map_or(text.len(), |next| next.as_span().start());
+            chunks.push(Chunk {
+                span: span.start + start..span.start + end,
+                line,
+                column,
+                text: is_text,
+            });
+        }
     }
     Ok(renderables)
 }
 
+/// Whether a `PluginUse` is of a tag or filter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PluginKind {
+    /// A tag, including the start and end tags of blocks.
+    Tag,
+    Filter,
+}
+
+/// A tag or filter used by a template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PluginUse {
+    pub kind: PluginKind,
+    pub name: String,
+    pub line: usize,
+}
+
+fn scan_filters(pair: Pair, uses: &mut Vec<PluginUse>) {
+    for pair in pair.into_inner().flatten() {
+        if pair.as_rule() == Rule::Filter {
+            let (line, _) = pair.as_span().start_pos().line_col();
+            let name = pair
+                .into_inner()
+                .next()
+                .expect("A filter always has a name.");
+            uses.push(PluginUse {
+                kind: PluginKind::Filter,
+                name: name.as_str().to_owned(),
+                line,
+            });
+        }
+    }
+}
+
+/// An element met by `scan`.
+enum Scanned<'a> {
+    /// A tag with its name, line and `TagInner` pair.
+    Tag(&'a str, usize, Pair<'a>),
+    Expression(Pair<'a>),
+}
+
+/// Visits the tags and expressions of a template, skipping the content of `raw` and `comment`
+/// blocks.
+///
+/// Invalid liquid is an error unless `recover` is set, when it is skipped.
+fn scan<'a, F>(text: &'a str, arithmetic: bool, recover: bool, mut visit: F) -> Result<()>
+where
+    F: FnMut(Scanned<'a>) -> Result<()>,
+{
+    let liquid = parse_lax(text, arithmetic);
+
+    let mut in_raw = false;
+    let mut comment_depth = 0;
+    for element in liquid {
+        match element.as_rule() {
+            Rule::EOI => break,
+            Rule::Tag => {
+                let (line, _) = element.as_span().start_pos().line_col();
+                let inner = element
+                    .into_inner()
+                    .next()
+                    .expect("Tags always have an inner rule.");
+                let name = inner
+                    .clone()
+                    .into_inner()
+                    .next()
+                    .expect("Tags start by their identifier.")
+                    .as_str();
+
+                if in_raw {
+                    in_raw = name != "endraw";
+                    if in_raw {
+                        continue;
+                    }
+                } else if name == "comment" {
+                    comment_depth += 1;
+                } else if name == "endcomment" && comment_depth > 0 {
+                    comment_depth -= 1;
+                } else if comment_depth > 0 {
+                    continue;
+                } else if name == "raw" {
+                    in_raw = true;
+                }
+
+                visit(Scanned::Tag(name, line, inner))?;
+            }
+            Rule::Expression if !in_raw && comment_depth == 0 => {
+                visit(Scanned::Expression(element))?;
+            }
+            Rule::InvalidLiquid if !recover && !in_raw && comment_depth == 0 => {
+                let token = InvalidLiquidToken::from(element);
+                return Err(with_arithmetic(arithmetic, || token.error()));
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// A piece of a template, as lexed by the grammar.
+pub(crate) enum Lexeme<'a> {
+    /// Text, or markup that can't be parsed when `invalid`.
+    Text {
+        span: Range<usize>,
+        invalid: bool,
+    },
+    Output {
+        span: Range<usize>,
+    },
+    /// A tag with its name.
+    Tag {
+        name: &'a str,
+        span: Range<usize>,
+    },
+}
+
+/// Splits a template into text, outputs and tags, without parsing what is in them.
+pub(crate) fn lex(text: &str, arithmetic: bool) -> Vec<Lexeme<'_>> {
+    parse_lax(text, arithmetic)
+        .filter_map(|element| {
+            let span = element.as_span().start()..element.as_span().end();
+            match element.as_rule() {
+                Rule::Raw => Some(Lexeme::Text {
+                    span,
+                    invalid: false,
+                }),
+                Rule::InvalidLiquid => Some(Lexeme::Text {
+                    span,
+                    invalid: true,
+                }),
+                Rule::Expression => Some(Lexeme::Output { span }),
+                Rule::Tag => {
+                    let name = element
+                        .into_inner()
+                        .next()
+                        .expect("Tags always have an inner rule.")
+                        .into_inner()
+                        .next()
+                        .expect("Tags start by their identifier.")
+                        .as_str();
+                    Some(Lexeme::Tag { name, span })
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Lists the tags and filters a template uses, without needing them to be registered.
+///
+/// This checks the syntax the grammar knows about, arithmetic included, not the arguments of
+/// tags.  The content of `raw` and `comment` blocks is skipped.
+pub fn scan_plugins(text: &str) -> Result<Vec<PluginUse>> {
+    let mut uses = Vec::new();
+    scan(text, true, false, |element| {
+        match element {
+            Scanned::Tag(name, line, inner) => {
+                uses.push(PluginUse {
+                    kind: PluginKind::Tag,
+                    name: name.to_owned(),
+                    line,
+                });
+                if name != "comment" {
+                    scan_filters(inner, &mut uses);
+                }
+            }
+            Scanned::Expression(element) => scan_filters(element, &mut uses),
+        }
+        Ok(())
+    })?;
+    Ok(uses)
+}
+
+/// Applies the `LoopShadowing` policy to the loops of a template.
+///
+/// Liquid's `assign` and `capture` set variables for the rest of the template, so any earlier
+/// one is shadowed, as are the variables of enclosing loops.
+fn check_loop_shadowing(text: &str, options: &Language) -> Result<()> {
+    let mut assigned: Vec<&str> = Vec::new();
+    let mut loops: Vec<&str> = Vec::new();
+    let recover = options.recovery() != Recovery::Fail || collecting_errors();
+    scan(text, options.arithmetic(), recover, |element| {
+        let (name, line, inner) = match element {
+            Scanned::Tag(name, line, inner) => (name, line, inner),
+            Scanned::Expression(_) => return Ok(()),
+        };
+        let variable = inner
+            .into_inner()
+            .nth(1)
+            .map(|token| token.as_str().trim())
+            .unwrap_or_default();
+        match name {
+            "assign" | "capture" => assigned.push(variable),
+            "for" | "tablerow" => {
+                let shadowed = if loops.contains(&variable) {
+                    Some("enclosing loop")
+                } else if assigned.contains(&variable) {
+                    Some("earlier assignment")
+                } else {
+                    None
+                };
+                if let Some(shadowed) = shadowed {
+                    let msg = "Loop variable shadows another variable";
+                    match options.loop_shadowing() {
+                        LoopShadowing::Allow => (),
+                        LoopShadowing::Warn => options.warn(
+                            Warning::with_msg(msg)
+                                .context("variable", variable)
+                                .context("shadowed", shadowed)
+                                .context("line", line.to_string()),
+                        ),
+                        LoopShadowing::Error => {
+                            return Err(Error::with_msg(msg)
+                                .context("variable", variable.to_owned())
+                                .context("shadowed", shadowed)
+                                .context("line", line.to_string()));
+                        }
+                    }
+                }
+                loops.push(variable);
+            }
+            "endfor" | "endtablerow" => {
+                loops.pop();
+            }
+            _ => (),
+        }
+        Ok(())
+    })
+}
+
 /// Parses a `Scalar` from a `Pair` with a literal value.
 /// This `Pair` must be `Rule::Literal`.
 fn parse_literal(literal: Pair) -> Value {
@@ -101,7 +638,7 @@ fn parse_literal(literal: Pair) -> Value {
             let literal = literal.as_str();
             let trim_quotes = &literal[1..literal.len() - 1];
 
-            Value::scalar(trim_quotes.to_owned())
+            Value::Scalar(Scalar::shared_str(trim_quotes))
         }
         Rule::IntegerLiteral => Value::scalar(
             literal
@@ -134,30 +671,73 @@ fn parse_variable(variable: Pair) -> Variable {
 
     let mut indexes = variable.into_inner();
 
+    // Identifiers are interned, sharing their strings with equal object keys and identifiers.
     let first_identifier = indexes
         .next()
         .expect("A variable starts with an identifier.")
-        .as_str()
-        .to_owned();
-    let mut variable = Variable::with_literal(first_identifier);
+        .as_str();
+    let mut variable = Variable::with_literal(Key::intern(first_identifier));
+    variable.extend(indexes.map(parse_index));
+    variable
+}
 
-    let indexes = indexes.map(|index| match index.as_rule() {
-        Rule::Identifier => Expression::with_literal(index.as_str().to_owned()),
+/// Parses an index of a `Variable`, an identifier or a value.
+fn parse_index(index: Pair) -> Expression {
+    match index.as_rule() {
+        Rule::Identifier => Expression::with_literal(Key::intern(index.as_str())),
         Rule::Value => parse_value(index),
         _ => unreachable!(),
-    });
+    }
+}
 
-    variable.extend(indexes);
-    variable
+/// Parses a `Variable` used as a value.
+///
+/// Identifiers may start with hyphens, but when arithmetic is enabled those are read as unary
+/// minus instead, `-a` being `0 - a`.
+fn parse_variable_value(variable: Pair) -> Expression {
+    let first_identifier = variable
+        .clone()
+        .into_inner()
+        .next()
+        .expect("A variable starts with an identifier.")
+        .as_str();
+    let name = first_identifier.trim_start_matches('-');
+    if !ARITHMETIC.with(Cell::get) || name.is_empty() || name.len() == first_identifier.len() {
+        return Expression::Variable(parse_variable(variable));
+    }
+
+    let mut indexes = variable.into_inner();
+    indexes.next();
+    let mut negated = Variable::with_literal(Key::intern(name));
+    negated.extend(indexes.map(parse_index));
+
+    let mut expression = Expression::Variable(negated);
+    for _ in name.len()..first_identifier.len() {
+        expression = negate(expression);
+    }
+    expression
+}
+
+/// Negates `expression` as `0 - expression`.
+fn negate(expression: Expression) -> Expression {
+    Expression::Arithmetic(
+        Box::new(Expression::Literal(Value::scalar(0))),
+        ArithmeticOperator::Minus,
+        Box::new(expression),
+    )
 }
 
 /// Parses an `Expression` from a `Pair` with a value.
 ///
 /// Do not confuse this value with `liquid-value`'s `Value`.
-/// In this context, value refers to either a literal value or a variable.
+/// In this context, value refers to either a literal value, a variable, a range, an array or
+/// arithmetic.
 ///
-/// This `Pair` must be `Rule::Value`.
+/// This `Pair` must be `Rule::Value` or `Rule::Arithmetic`.
 fn parse_value(value: Pair) -> Expression {
+    if value.as_rule() == Rule::Arithmetic {
+        return parse_arithmetic(value);
+    }
     if value.as_rule() != Rule::Value {
         panic!("Expected value.");
     }
@@ -166,19 +746,98 @@ fn parse_value(value: Pair) -> Expression {
 
     match value.as_rule() {
         Rule::Literal => Expression::Literal(parse_literal(value)),
-        Rule::Variable => Expression::Variable(parse_variable(value)),
+        Rule::Variable => parse_variable_value(value),
+        Rule::Range => {
+            let (start, end) = parse_range(value);
+            Expression::Range(Box::new(start), Box::new(end))
+        }
+        Rule::Array => Expression::Array(value.into_inner().map(parse_value).collect()),
         _ => unreachable!(),
     }
 }
 
+/// Parses arithmetic, or a group within it, into an `Expression`, operators binding their
+/// operands according to their precedence.
+///
+/// Operators are silent in the grammar, so they are read from the text between the operands.
+fn parse_arithmetic(arithmetic: Pair) -> Expression {
+    let text = arithmetic.as_str();
+    let offset = arithmetic.as_span().start();
+    let mut pairs = arithmetic.into_inner();
+
+    let first = pairs.next().expect("Arithmetic has an operand.");
+    let mut end = first.as_span().end();
+    let mut operands = vec![parse_operand(first)];
+    let mut operators: Vec<ArithmeticOperator> = Vec::new();
+
+    fn reduce(operands: &mut Vec<Expression>, operator: ArithmeticOperator) {
+        let rhs = operands.pop().expect("Operators have a right operand.");
+        let lhs = operands.pop().expect("Operators have a left operand.");
+        operands.push(Expression::Arithmetic(
+            Box::new(lhs),
+            operator,
+            Box::new(rhs),
+        ));
+    }
+
+    for operand in pairs {
+        let symbol = &text[end - offset..operand.as_span().start() - offset];
+        let operator = ArithmeticOperator::from_symbol(symbol.trim())
+            .expect("Grammar ensures operators are known.");
+        while let Some(&last) = operators.last() {
+            if last.precedence() < operator.precedence() {
+                break;
+            }
+            operators.pop();
+            reduce(&mut operands, last);
+        }
+        operators.push(operator);
+        end = operand.as_span().end();
+        operands.push(parse_operand(operand));
+    }
+    while let Some(operator) = operators.pop() {
+        reduce(&mut operands, operator);
+    }
+    operands.pop().expect("Arithmetic has an operand.")
+}
+
+/// Parses a value or a parenthesized group of arithmetic, possibly negated.
+fn parse_operand(operand: Pair) -> Expression {
+    match operand.as_rule() {
+        Rule::Group => parse_arithmetic(operand),
+        Rule::Negation => negate(parse_arithmetic(
+            operand
+                .into_inner()
+                .next()
+                .expect("A negation has a group."),
+        )),
+        _ => parse_value(operand),
+    }
+}
+
+/// Parses the start and end of a `Range` from a `Pair` with a range.
+/// This `Pair` must be `Rule::Range`.
+fn parse_range(range: Pair) -> (Expression, Expression) {
+    if range.as_rule() != Rule::Range {
+        panic!("Expected range.");
+    }
+
+    let mut range = range.into_inner();
+    (
+        parse_value(range.next().expect("A range has a start.")),
+        parse_value(range.next().expect("A range has an end.")),
+    )
+}
+
 /// Parses a `FilterCall` from a `Pair` with a filter.
 /// This `Pair` must be `Rule::Filter`.
-fn parse_filter(filter: Pair, options: &Language) -> Result<Box<dyn Filter>> {
+fn parse_filter(filter: Pair, options: &Language) -> Result<FilterCall> {
     if filter.as_rule() != Rule::Filter {
         panic!("Expected a filter.");
     }
 
     let filter_str = filter.as_str();
+    let (line, column) = line_col(filter.as_span().start_pos());
     let mut filter = filter.into_inner();
     let name = filter.next().expect("A filter always has a name.").as_str();
 
@@ -203,6 +862,11 @@ fn parse_filter(filter: Pair, options: &Language) -> Result<Box<dyn Filter>> {
         }
     }
 
+    let arguments = positional_args
+        .iter()
+        .chain(keyword_args.iter().map(|(_, value)| value))
+        .cloned()
+        .collect();
     let args = FilterArguments {
         positional: Box::new(positional_args.into_iter()),
         keyword: Box::new(keyword_args.into_iter()),
@@ -215,15 +879,28 @@ fn parse_filter(filter: Pair, options: &Language) -> Result<Box<dyn Filter>> {
         Error::with_msg("Unknown filter")
             .context("requested filter", name.to_owned())
             .context("available filters", available)
+            .with_position(line, column)
     })?;
 
+    if let Some(deprecation) = f.reflection().deprecated() {
+        options.warn(
+            Warning::deprecated("filter", name, deprecation).context("line", line.to_string()),
+        );
+    }
+
     let f = f
-        .parse(args)
+        .parse_with_language(args, options)
+        .map_err(|error| error.with_position(line, column))
         .trace("Filter parsing error")
         .context_key("filter")
         .value_with(|| filter_str.to_string().into())?;
 
-    Ok(f)
+    Ok(FilterCall::new(
+        name.to_owned(),
+        (line, column),
+        f,
+        arguments,
+    ))
 }
 
 /// Parses a `FilterChain` from a `Pair` with a filter chain.
@@ -234,15 +911,14 @@ fn parse_filter_chain(chain: Pair, options: &Language) -> Result<FilterChain> {
     }
 
     let mut chain = chain.into_inner();
-    let entry = parse_value(
-        chain
-            .next()
-            .expect("A filterchain always has starts by a value."),
-    );
+    let entry = chain
+        .next()
+        .expect("A filterchain always has starts by a value.");
+    let entry = parse_value(entry);
     let filters: Result<Vec<_>> = chain.map(|f| parse_filter(f, options)).collect();
     let filters = filters?;
 
-    let filters = FilterChain::new(entry, filters);
+    let filters = FilterChain::with_calls(entry, filters);
     Ok(filters)
 }
 
@@ -250,14 +926,20 @@ fn parse_filter_chain(chain: Pair, options: &Language) -> Result<FilterChain> {
 pub struct TagBlock<'a: 'b, 'b> {
     name: &'b str,
     iter: &'b mut dyn Iterator<Item = Pair<'a>>,
+    source: &'b Arc<str>,
     closed: bool,
 }
 
 impl<'a, 'b> TagBlock<'a, 'b> {
-    fn new(name: &'b str, next_elements: &'b mut dyn Iterator<Item = Pair<'a>>) -> Self {
+    fn new(
+        name: &'b str,
+        next_elements: &'b mut dyn Iterator<Item = Pair<'a>>,
+        source: &'b Arc<str>,
+    ) -> Self {
         TagBlock {
             name,
             iter: next_elements,
+            source,
             closed: false,
         }
     }
@@ -284,6 +966,7 @@ impl<'a, 'b> TagBlock<'a, 'b> {
         // Tags are treated separately so as to check for a possible `{% endtag %}`
         if element.as_rule() == Rule::Tag {
             let as_str = element.as_str();
+            let start = element.as_span().start();
             let mut tag = element
                 .into_inner()
                 .next()
@@ -310,6 +993,7 @@ impl<'a, 'b> TagBlock<'a, 'b> {
                     name,
                     tokens,
                     as_str,
+                    start,
                 })));
             }
         }
@@ -429,6 +1113,8 @@ impl<'a, 'b> TagBlock<'a, 'b> {
 /// An element that is raw text.
 pub struct Raw<'a> {
     text: &'a str,
+    start: usize,
+    line_col: (usize, usize),
 }
 impl<'a> From<Pair<'a>> for Raw<'a> {
     fn from(element: Pair<'a>) -> Self {
@@ -437,6 +1123,8 @@ impl<'a> From<Pair<'a>> for Raw<'a> {
         }
         Raw {
             text: element.as_str(),
+            start: element.as_span().start(),
+            line_col: line_col(element.as_span().start_pos()),
         }
     }
 }
@@ -455,6 +1143,50 @@ impl<'a> Raw<'a> {
     pub fn as_str(&self) -> &'a str {
         self.text
     }
+
+    fn parse(self, source: &Arc<str>, options: &Language) -> Box<dyn Renderable> {
+        let start = START.with(Cell::get).offset + self.start;
+        let range = start..start + self.text.len();
+        // Tags made with `Tag::new` parse a source of their own, so check the text is this very
+        // slice of `source` rather than comparing what it says.
+        let shared = source
+            .get(range.clone())
+            .is_some_and(|shared| ptr::eq(shared.as_ptr(), self.text.as_ptr()));
+        if shared {
+            text(source, range, self.line_col, options)
+        } else {
+            let (line, column) = self.line_col;
+            options.instrumented(
+                || NodeInfo {
+                    line,
+                    column,
+                    span: range,
+                    kind: NodeKind::Text,
+                },
+                self.to_renderable(),
+            )
+        }
+    }
+}
+
+/// The text at `range` of `source`, which starts at `line_col`.
+fn text(
+    source: &Arc<str>,
+    range: Range<usize>,
+    line_col: (usize, usize),
+    options: &Language,
+) -> Box<dyn Renderable> {
+    let (line, column) = line_col;
+    let span = range.clone();
+    options.instrumented(
+        || NodeInfo {
+            line,
+            column,
+            span,
+            kind: NodeKind::Text,
+        },
+        Box::new(Text::with_source(source.clone(), range)),
+    )
 }
 
 /// An element that is a tag.
@@ -462,6 +1194,7 @@ pub struct Tag<'a> {
     name: Pair<'a>,
     tokens: TagTokenIter<'a>,
     as_str: &'a str,
+    start: usize,
 }
 
 impl<'a> From<Pair<'a>> for Tag<'a> {
@@ -470,6 +1203,7 @@ impl<'a> From<Pair<'a>> for Tag<'a> {
             panic!("Only rule Tag can be converted to Tag.");
         }
         let as_str = element.as_str();
+        let start = element.as_span().start();
         let mut tag = element
             .into_inner()
             .next()
@@ -482,6 +1216,7 @@ impl<'a> From<Pair<'a>> for Tag<'a> {
             name,
             tokens,
             as_str,
+            start,
         }
     }
 }
@@ -525,25 +1260,60 @@ impl<'a> Tag<'a> {
         tag_block: &mut TagBlock,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
-        self.parse_pair(&mut tag_block.iter, options)
+        self.parse_pair(&mut tag_block.iter, tag_block.source, options)
     }
 
     /// The same as `parse`, but directly takes an iterator over `Pair`s instead of a TagBlock.
     fn parse_pair(
         self,
         next_elements: &mut dyn Iterator<Item = Pair>,
+        source: &Arc<str>,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
-        let (name, tokens) = (self.name, self.tokens);
+        let (name, tokens, as_str) = (self.name, self.tokens, self.as_str);
         let position = name.as_span();
         let name = name.as_str();
 
+        let (line, column) = line_col(position.start_pos());
+        let offset = START.with(Cell::get).offset + self.start;
+        let node = || NodeInfo {
+            line,
+            column,
+            span: offset..offset + as_str.len(),
+            kind: NodeKind::Tag(name.to_owned()),
+        };
         if let Some(plugin) = options.tags.get(name) {
-            plugin.parse(tokens, options)
+            if let Some(deprecation) = plugin.reflection().deprecated() {
+                options.warn(
+                    Warning::deprecated("tag", name, deprecation).context("line", line.to_string()),
+                );
+            }
+            match plugin.parse(tokens, options) {
+                Ok(renderable) => {
+                    let renderable = Box::new(Located::new(line, column, renderable));
+                    Ok(options.instrumented(node, renderable))
+                }
+                Err(error) => recover(
+                    as_str,
+                    line,
+                    &format!("Invalid tag `{}`", name),
+                    error.with_position(line, column),
+                    options,
+                ),
+            }
         } else if let Some(plugin) = options.blocks.get(name) {
-            let block = TagBlock::new(name, next_elements);
-            let renderables = plugin.parse(tokens, block, options)?;
-            Ok(renderables)
+            if let Some(deprecation) = plugin.reflection().deprecated() {
+                options.warn(
+                    Warning::deprecated("block", name, deprecation)
+                        .context("line", line.to_string()),
+                );
+            }
+            let block = TagBlock::new(name, next_elements, source);
+            let renderable = plugin
+                .parse(tokens, block, options)
+                .map_err(|error| error.with_position(line, column))?;
+            let renderable = Box::new(Located::new(line, column, renderable));
+            Ok(options.instrumented(node, renderable))
         } else {
             let pest_error = ::pest::error::Error::new_from_span(
                 ::pest::error::ErrorVariant::CustomError {
@@ -561,7 +1331,13 @@ impl<'a> Tag<'a> {
                 .context("requested", name.to_owned())
                 .context("available tags", all_tags)
                 .context("available blocks", all_blocks);
-            Err(error)
+            recover(
+                as_str,
+                line,
+                &format!("Unknown tag `{}`", name),
+                error,
+                options,
+            )
         }
     }
 }
@@ -583,6 +1359,9 @@ impl<'a> From<Pair<'a>> for Exp<'a> {
 impl<'a> Exp<'a> {
     /// Parses the expression just as if it weren't inside any block.
     pub fn parse(self, options: &Language) -> Result<Box<dyn Renderable>> {
+        let (line, column) = line_col(self.element.as_span().start_pos());
+        let span = template_span(&self.element.as_span());
+        let as_str = self.element.as_str();
         let filter_chain = self
             .element
             .into_inner()
@@ -592,8 +1371,22 @@ impl<'a> Exp<'a> {
             .next()
             .expect("An expression consists of one filterchain.");
 
-        let filter_chain = parse_filter_chain(filter_chain, options)?;
-        Ok(Box::new(filter_chain))
+        let filter_chain = match parse_filter_chain(filter_chain, options) {
+            Ok(filter_chain) => filter_chain,
+            Err(error) => {
+                let error = error.with_position(line, column);
+                return recover(as_str, line, "Invalid output", error, options);
+            }
+        };
+        Ok(options.instrumented(
+            || NodeInfo {
+                line,
+                column,
+                span,
+                kind: NodeKind::Output,
+            },
+            Box::new(Located::new(line, column, Box::new(filter_chain))),
+        ))
     }
 
     /// Returns the expression as a str.
@@ -614,18 +1407,18 @@ impl<'a> InvalidLiquidToken<'a> {
         self.element.as_str()
     }
 
-    /// Tries to parse this as valid liquid, which will inevitably raise an error.
+    /// Tries to parse this as valid liquid, which will inevitably raise an error, unless the
+    /// `Recovery` policy keeps it.
     /// This is needed in order to raise the right error message.
-    pub fn parse(self, tag_block: &mut TagBlock) -> Result<Box<dyn Renderable>> {
-        self.parse_pair(&mut tag_block.iter)
+    pub fn parse(self, options: &Language) -> Result<Box<dyn Renderable>> {
+        let error = self.error();
+        let (line, _) = line_col(self.element.as_span().start_pos());
+        recover(self.as_str(), line, "Invalid markup", error, options)
     }
 
     /// Tries to parse this as valid liquid, which will inevitably raise an error.
     /// This is needed in order to raise the correct error message.
-    fn parse_pair(
-        self,
-        next_elements: &mut dyn Iterator<Item = Pair>,
-    ) -> Result<Box<dyn Renderable>> {
+    fn error(&self) -> Error {
         use pest::error::LineColLocation;
 
         let invalid_token_span = self.element.as_span();
@@ -634,17 +1427,21 @@ impl<'a> InvalidLiquidToken<'a> {
         let offset_l = offset_l - 1;
         let offset_c = offset_c - 1;
 
-        let end_position = match next_elements.last() {
-            Some(element) => element.as_span().end_pos(),
-            None => invalid_token_span.end_pos(),
-        };
+        let input = invalid_token_span.get_input();
+        let end_position = ::pest::Position::new(input, input.len())
+            .expect("The end of the input is a valid position.");
 
         let mut text = String::from(&invalid_token_position.line_of()[..offset_c]);
         text.push_str(invalid_token_position.span(&end_position).as_str());
 
         // Reparses from the line where invalid liquid started, in order
         // to raise the error.
-        let mut error = match LiquidParser::parse(Rule::LiquidFile, &text) {
+        let rule = if ARITHMETIC.with(Cell::get) {
+            Rule::ArithmeticLiquidFile
+        } else {
+            Rule::LiquidFile
+        };
+        let mut error = match LiquidParser::parse(rule, &text) {
             Ok(_) => panic!("`LiquidParser::parse` should fail in InvalidLiquidTokens."),
             Err(error) => error,
         };
@@ -659,7 +1456,7 @@ impl<'a> InvalidLiquidToken<'a> {
             LineColLocation::Pos((ls, cs)) => LineColLocation::Pos((ls + offset_l, cs)),
         };
 
-        Err(convert_pest_error(error))
+        convert_pest_error(error)
     }
 }
 impl<'a> From<Pair<'a>> for InvalidLiquidToken<'a> {
@@ -703,10 +1500,10 @@ impl<'a> BlockElement<'a> {
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
         match self {
-            BlockElement::Raw(raw) => Ok(raw.to_renderable()),
+            BlockElement::Raw(raw) => Ok(raw.parse(block.source, options)),
             BlockElement::Tag(tag) => tag.parse(block, options),
             BlockElement::Expression(exp) => exp.parse(options),
-            BlockElement::Invalid(invalid) => invalid.parse(block),
+            BlockElement::Invalid(invalid) => invalid.parse(options),
         }
     }
 
@@ -714,13 +1511,14 @@ impl<'a> BlockElement<'a> {
     fn parse_pair(
         self,
         next_elements: &mut dyn Iterator<Item = Pair>,
+        source: &Arc<str>,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
         match self {
-            BlockElement::Raw(raw) => Ok(raw.to_renderable()),
-            BlockElement::Tag(tag) => tag.parse_pair(next_elements, options),
+            BlockElement::Raw(raw) => Ok(raw.parse(source, options)),
+            BlockElement::Tag(tag) => tag.parse_pair(next_elements, source, options),
             BlockElement::Expression(exp) => exp.parse(options),
-            BlockElement::Invalid(invalid) => invalid.parse_pair(next_elements),
+            BlockElement::Invalid(invalid) => invalid.parse(options),
         }
     }
 
@@ -759,6 +1557,18 @@ impl<'a> TagTokenIter<'a> {
         }
     }
 
+    /// The line of the template the tag is on, starting at 1.
+    pub fn line(&self) -> usize {
+        line_col(self.position).0
+    }
+
+    /// The source of the remaining tokens, consuming them.
+    pub fn remaining_source(&mut self) -> &'a str {
+        let start = self.position;
+        while self.next().is_some() {}
+        start.span(&self.position).as_str()
+    }
+
     /// Creates an error with the given message pointing at the current
     /// position of the iterator.
     pub fn raise_error(&mut self, error_msg: &str) -> Error {
@@ -879,7 +1689,6 @@ impl<'a> TagToken<'a> {
             // There are filters: it can't be a value
             return Err(());
         }
-
         Ok(value)
     }
 
@@ -928,6 +1737,21 @@ impl<'a> TagToken<'a> {
         Ok(literal)
     }
 
+    fn unwrap_range(&mut self) -> std::result::Result<Pair<'a>, ()> {
+        let value = self.unwrap_value()?;
+
+        let range = value
+            .into_inner()
+            .next()
+            .expect("A value is made of one token.");
+
+        if range.as_rule() != Rule::Range {
+            return Err(());
+        }
+
+        Ok(range)
+    }
+
     /// Tries to obtain a `FilterChain` from this token.
     pub fn expect_filter_chain(mut self, options: &Language) -> TryMatchToken<'a, FilterChain> {
         match self.expect_filter_chain_err(options) {
@@ -950,7 +1774,7 @@ impl<'a> TagToken<'a> {
     /// Tries to obtain a value from this token.
     ///
     /// Do not confuse this value with `liquid-value`'s `Value`.
-    /// In this context, value refers to either a literal value or a variable.
+    /// In this context, value refers to either a literal value, a variable or a range.
     pub fn expect_value(mut self) -> TryMatchToken<'a, Expression> {
         match self.unwrap_value() {
             Ok(t) => TryMatchToken::Matches(parse_value(t)),
@@ -1001,18 +1825,13 @@ impl<'a> TagToken<'a> {
     ///
     /// The range is returned as a pair `(Expression, Expression)`.
     pub fn expect_range(mut self) -> TryMatchToken<'a, (Expression, Expression)> {
-        let token = self.token.clone();
-
-        if token.as_rule() != Rule::Range {
-            self.expected.push(Rule::Range);
-            return TryMatchToken::Fails(self);
+        match self.unwrap_range() {
+            Ok(t) => TryMatchToken::Matches(parse_range(t)),
+            Err(_) => {
+                self.expected.push(Rule::Range);
+                TryMatchToken::Fails(self)
+            }
         }
-
-        let mut range = token.into_inner();
-        TryMatchToken::Matches((
-            parse_value(range.next().expect("start")),
-            parse_value(range.next().expect("end")),
-        ))
     }
 
     /// Returns `Ok` if and only if the tokens' str is equal to the given str.
@@ -1158,4 +1977,34 @@ mod test {
 
         assert_eq!(output, "5");
     }
+
+    #[test]
+    fn test_scan_plugins() {
+        let text = "{{ a | upcase | append: b }}\n{% if x | size > 1 %}{% raw %}{{ c | hidden }}{% endraw %}{% endif %}\n{% comment %}{% bogus %}{% endcomment %}";
+        let uses: Vec<_> = scan_plugins(text)
+            .unwrap()
+            .into_iter()
+            .map(|u| (u.kind, u.name, u.line))
+            .collect();
+        assert_eq!(
+            uses,
+            vec![
+                (PluginKind::Filter, "upcase".to_owned(), 1),
+                (PluginKind::Filter, "append".to_owned(), 1),
+                (PluginKind::Tag, "if".to_owned(), 2),
+                (PluginKind::Filter, "size".to_owned(), 2),
+                (PluginKind::Tag, "raw".to_owned(), 2),
+                (PluginKind::Tag, "endraw".to_owned(), 2),
+                (PluginKind::Tag, "endif".to_owned(), 2),
+                (PluginKind::Tag, "comment".to_owned(), 3),
+                (PluginKind::Tag, "endcomment".to_owned(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_plugins_invalid() {
+        assert!(scan_plugins("{{ a | }}").is_err());
+        assert!(scan_plugins("{% %}").is_err());
+    }
 }
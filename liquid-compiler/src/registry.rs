@@ -26,6 +26,13 @@ impl<P> PluginRegistry<P> {
         old.is_some()
     }
 
+    /// Unregister a plugin
+    ///
+    /// Returns the plugin, if it was registered.
+    pub fn remove(&mut self, name: &str) -> Option<P> {
+        self.plugins.remove(name)
+    }
+
     /// Look up an existing plugin.
     ///
     /// Generally this is used for running plugins.
@@ -0,0 +1,98 @@
+//! The tags, blocks, and filters of the Liquid language.
+//!
+//! These are the names `liquid::ParserBuilder::with_liquid` registers, for tools checking
+//! templates without a parser, like the `template!` macro.
+
+/// The tags registered by `liquid::ParserBuilder::liquid_tags`.
+pub const LIQUID_TAGS: &[&str] = &[
+    "assign",
+    "break",
+    "continue",
+    "cycle",
+    "decrement",
+    "echo",
+    "include",
+    "increment",
+    "liquid",
+    "render",
+];
+
+/// The blocks registered by `liquid::ParserBuilder::liquid_blocks`.
+pub const LIQUID_BLOCKS: &[&str] = &[
+    "capture",
+    "case",
+    "comment",
+    "for",
+    "if",
+    "ifchanged",
+    "raw",
+    "tablerow",
+    "unless",
+];
+
+/// The filters registered by `liquid::ParserBuilder::liquid_filters`, with `markdownify` when
+/// the `markdown-filters` feature is on.
+pub const LIQUID_FILTERS: &[&str] = &[
+    "abs",
+    "append",
+    "at_least",
+    "at_most",
+    "base64_decode",
+    "base64_encode",
+    "base64_url_safe_decode",
+    "base64_url_safe_encode",
+    "capitalize",
+    "ceil",
+    "compact",
+    "concat",
+    "date",
+    "default",
+    "divided_by",
+    "downcase",
+    "escape",
+    "escape_once",
+    "find",
+    "find_index",
+    "first",
+    "floor",
+    "group_by",
+    "join",
+    "last",
+    "lstrip",
+    "map",
+    #[cfg(feature = "markdown-filters")]
+    "markdownify",
+    "minus",
+    "modulo",
+    "newline_to_br",
+    "normalize_whitespace",
+    "plus",
+    "prepend",
+    "raw",
+    "remove",
+    "remove_first",
+    "replace",
+    "replace_first",
+    "reverse",
+    "round",
+    "rstrip",
+    "safe",
+    "size",
+    "slice",
+    "slugify",
+    "sort",
+    "sort_natural",
+    "split",
+    "strip",
+    "strip_html",
+    "strip_newlines",
+    "sum",
+    "times",
+    "truncate",
+    "truncatewords",
+    "uniq",
+    "upcase",
+    "url_decode",
+    "url_encode",
+    "where",
+];
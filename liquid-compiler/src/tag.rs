@@ -1,6 +1,7 @@
 use liquid_error::Result;
 use liquid_interpreter::Renderable;
 
+use super::Deprecation;
 use super::Language;
 use super::TagTokenIter;
 
@@ -16,6 +17,11 @@ pub trait TagReflection {
     fn spec(&self) -> Option<&'static str> {
         None
     }
+
+    /// Whether the plugin should no longer be used.
+    fn deprecated(&self) -> Option<Deprecation> {
+        None
+    }
 }
 
 /// A trait for creating custom tags. This is a simple type alias for a function.
@@ -1,25 +1,59 @@
-use std::io::Write;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
 
 use liquid_error::{Result, ResultLiquidReplaceExt};
 use liquid_interpreter::Context;
+use liquid_interpreter::Output;
 use liquid_interpreter::Renderable;
 
 /// A raw template expression.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone)]
 pub(crate) struct Text {
-    text: String,
+    source: Arc<str>,
+    range: Range<usize>,
 }
 
 impl Text {
     /// Create a raw template expression.
     pub(crate) fn new<S: Into<String>>(text: S) -> Text {
-        Text { text: text.into() }
+        let text = text.into();
+        let range = 0..text.len();
+        Text {
+            source: text.into(),
+            range,
+        }
+    }
+
+    /// Create a raw template expression out of `range` of the template's source.
+    pub(crate) fn with_source(source: Arc<str>, range: Range<usize>) -> Text {
+        Text { source, range }
+    }
+
+    fn as_str(&self) -> &str {
+        &self.source[self.range.clone()]
+    }
+}
+
+impl PartialEq for Text {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Text {}
+
+impl fmt::Debug for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Text")
+            .field("text", &self.as_str())
+            .finish()
     }
 }
 
 impl Renderable for Text {
-    fn render_to(&self, writer: &mut dyn Write, _context: &mut Context) -> Result<()> {
-        write!(writer, "{}", &self.text).replace("Failed to render")?;
+    fn render_to(&self, writer: &mut dyn Output, _context: &mut Context) -> Result<()> {
+        write!(writer, "{}", self.as_str()).replace("Failed to render")?;
         Ok(())
     }
 }
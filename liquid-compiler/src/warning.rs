@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::Arc;
+
+/// Marks a plugin as kept for compatibility only.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Deprecation {
+    /// Why the plugin is deprecated.
+    pub message: &'static str,
+    /// What to use instead, if anything.
+    pub replacement: Option<&'static str>,
+}
+
+/// What the parser does when a `for` or `tablerow` variable shadows a variable of an enclosing
+/// loop or one assigned earlier in the template.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LoopShadowing {
+    /// Accept the template silently.
+    #[default]
+    Allow,
+    /// Report a `Warning`.
+    Warn,
+    /// Fail the parse, for strict template sets.
+    Error,
+}
+
+/// What the parser does with markup it can't parse, like unknown tags and malformed outputs,
+/// e.g. for templates written by the users of a CMS.
+///
+/// Recovering reports a `Warning` with the error as its `cause`.  Markup that can't be told apart
+/// from text, like an unclosed `{{`, is recovered one character at a time, so the rest of it is
+/// kept as text.  Blocks whose own tag can't be parsed, and unclosed blocks, still fail the
+/// parse.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Recovery {
+    /// Fail the parse.
+    #[default]
+    Fail,
+    /// Keep the markup as text.
+    Text,
+    /// Replace the markup with an HTML comment saying what is wrong with it.
+    Comment,
+}
+
+/// A problem with a template that does not stop it from being parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    message: String,
+    context: Vec<(String, String)>,
+}
+
+impl Warning {
+    pub fn with_msg<S: Into<String>>(message: S) -> Self {
+        Self {
+            message: message.into(),
+            context: Vec::new(),
+        }
+    }
+
+    /// A deprecated filter, tag or block was used.
+    pub fn deprecated(kind: &str, name: &str, deprecation: Deprecation) -> Self {
+        let warning = Self::with_msg(format!("Deprecated {}", kind))
+            .context(kind, name)
+            .context("cause", deprecation.message);
+        match deprecation.replacement {
+            Some(replacement) => warning.context("replacement", replacement),
+            None => warning,
+        }
+    }
+
+    /// Add context about the warning.
+    pub fn context<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.context.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The context about the warning, in the order it was added.
+    pub fn contexts(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.context.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Look up context by key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.context
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "liquid: {}", self.message)?;
+        if !self.context.is_empty() {
+            writeln!(f, "  with:")?;
+        }
+        for (key, value) in &self.context {
+            writeln!(f, "    {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Receives the `Warning`s found while parsing.
+pub type WarningHandler = Arc<dyn Fn(Warning) + Send + Sync>;
+
+thread_local! {
+    static COLLECTED: RefCell<Option<Vec<Warning>>> = const { RefCell::new(None) };
+}
+
+/// Executes the supplied function, collecting the `Warning`s reported on this thread while it
+/// runs, in addition to passing them to the `WarningHandler`.
+///
+/// Collections nest: the warnings are collected by the enclosing collection too.
+pub fn collect_warnings<RvalT, FnT>(f: FnT) -> (RvalT, Vec<Warning>)
+where
+    FnT: FnOnce() -> RvalT,
+{
+    let outer = COLLECTED.with(|collected| collected.replace(Some(Vec::new())));
+    let result = f();
+    let warnings = COLLECTED
+        .with(|collected| collected.replace(outer))
+        .unwrap_or_default();
+    COLLECTED.with(|collected| {
+        if let Some(ref mut outer) = *collected.borrow_mut() {
+            outer.extend(warnings.iter().cloned());
+        }
+    });
+    (result, warnings)
+}
+
+/// Collect `warning`, if `collect_warnings` is running.
+pub(crate) fn collect(warning: &Warning) {
+    COLLECTED.with(|collected| {
+        if let Some(ref mut warnings) = *collected.borrow_mut() {
+            warnings.push(warning.clone());
+        }
+    });
+}
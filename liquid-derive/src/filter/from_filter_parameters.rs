@@ -8,6 +8,8 @@ use syn::*;
 struct FilterStruct<'a> {
     name: &'a Ident,
     parameters_struct_name: &'a Type,
+    /// The type of the field marked with `#[state]`, if any.
+    state_struct_name: Option<&'a Type>,
     fields: Vec<FilterStructField<'a>>,
     ty: StructFieldsType,
     generics: &'a Generics,
@@ -51,6 +53,7 @@ impl<'a> FilterStruct<'a> {
             ..
         } = &input;
         let mut parameters_struct_name = AssignOnce::Unset;
+        let mut state_struct_name = AssignOnce::Unset;
         let mut filter_fields = Vec::new();
 
         let fields = match data {
@@ -79,6 +82,12 @@ impl<'a> FilterStruct<'a> {
                     "A previous field was already marked as `parameters`. Only one field can be marked as so.",
                 ))?;
             }
+            if filter_field.is_state() {
+                state_struct_name.set(&field.ty, || Error::new_spanned(
+                    field,
+                    "A previous field was already marked as `state`. Only one field can be marked as so.",
+                ))?;
+            }
             filter_fields.push(filter_field);
         }
 
@@ -93,6 +102,7 @@ impl<'a> FilterStruct<'a> {
             name,
             fields,
             parameters_struct_name,
+            state_struct_name: state_struct_name.to_option(),
             ty,
             generics,
         })
@@ -100,9 +110,10 @@ impl<'a> FilterStruct<'a> {
 }
 
 /// A field in `FilterStruct`. It can either be a regular one or the one marked
-/// with `#[parameters]`.
+/// with `#[parameters]` or `#[state]`.
 enum FilterStructField<'a> {
     FilterParameters(FilterField<'a>),
+    State(FilterField<'a>),
     RegularField(FilterField<'a>),
 }
 
@@ -117,6 +128,8 @@ impl<'a> FilterStructField<'a> {
 
         if attrs.iter().any(|attr| attr.path.is_ident("parameters")) {
             FilterStructField::FilterParameters(FilterField { ident, ty })
+        } else if attrs.iter().any(|attr| attr.path.is_ident("state")) {
+            FilterStructField::State(FilterField { ident, ty })
         } else {
             FilterStructField::RegularField(FilterField { ident, ty })
         }
@@ -138,6 +151,18 @@ impl<'a> FilterStructField<'a> {
                     }
                 }
             }
+            FilterStructField::State(field) => {
+                let FilterField { ident, .. } = field;
+                if let Some(ident) = ident {
+                    quote! {
+                        #ident: state,
+                    }
+                } else {
+                    quote! {
+                        state,
+                    }
+                }
+            }
             FilterStructField::RegularField(field) => {
                 let FilterField { ident, ty } = field;
                 if let Some(ident) = ident {
@@ -160,6 +185,11 @@ impl<'a> FilterStructField<'a> {
             _ => false,
         }
     }
+
+    /// Returns whether this is a field marked with `#[state]`
+    fn is_state(&self) -> bool {
+        matches!(self, FilterStructField::State(_))
+    }
 }
 
 /// A field in `FilterStruct`.
@@ -168,22 +198,31 @@ struct FilterField<'a> {
     ty: &'a Type,
 }
 
-/// Generates implementation of `From<FilterParameters>`.
+/// Generates implementation of `From<FilterParameters>`, or of `From<(State, FilterParameters)>`
+/// when a field is marked with `#[state]`.
 fn generate_from_filter_parameters(filter: &FilterStruct) -> TokenStream {
     let FilterStruct {
         parameters_struct_name,
+        state_struct_name,
         fields,
         ty,
         ..
     } = &filter;
     let fields = fields.iter().map(|field| field.generate_field_value());
 
-    let impl_from = filter.generate_impl(quote! { ::std::convert::From<#parameters_struct_name> });
+    let (from_type, from_pattern) = match state_struct_name {
+        Some(state_struct_name) => (
+            quote! { (#state_struct_name, #parameters_struct_name) },
+            quote! { (state, parameters) },
+        ),
+        None => (quote! { #parameters_struct_name }, quote! { parameters }),
+    };
+    let impl_from = filter.generate_impl(quote! { ::std::convert::From<#from_type> });
 
     match ty {
         StructFieldsType::Named => quote! {
             #impl_from {
-                fn from(parameters: #parameters_struct_name) -> Self {
+                fn from(#from_pattern: #from_type) -> Self {
                     Self {
                         #(#fields)*
                     }
@@ -192,7 +231,7 @@ fn generate_from_filter_parameters(filter: &FilterStruct) -> TokenStream {
         },
         StructFieldsType::Unnamed => quote! {
             #impl_from {
-                fn from(parameters: #parameters_struct_name) -> Self {
+                fn from(#from_pattern: #from_type) -> Self {
                     Self (
                         #(#fields)*
                     )
@@ -201,7 +240,7 @@ fn generate_from_filter_parameters(filter: &FilterStruct) -> TokenStream {
         },
         StructFieldsType::Unit => quote! {
             #impl_from {
-                fn from(parameters: #parameters_struct_name) -> Self {
+                fn from(#from_pattern: #from_type) -> Self {
                     Self;
                 }
             }
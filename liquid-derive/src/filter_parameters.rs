@@ -274,9 +274,9 @@ impl<'a> FilterParameter<'a> {
         !self.is_optional
     }
 
-    /// Returns whether this is a positional field.
+    /// Returns whether this is a positional field, that may also be named.
     fn is_positional(&self) -> bool {
-        self.meta.mode == FilterParameterMode::Positional
+        self.meta.mode != FilterParameterMode::Keyword
     }
 
     /// Returns whether this is a keyword field.
@@ -284,6 +284,11 @@ impl<'a> FilterParameter<'a> {
         self.meta.mode == FilterParameterMode::Keyword
     }
 
+    /// Returns whether this field may be given as a keyword argument.
+    fn is_named(&self) -> bool {
+        self.meta.mode != FilterParameterMode::Positional
+    }
+
     /// Returns the name of this parameter in liquid.
     ///
     /// That is, by default, the name of the field as a string. However,
@@ -302,11 +307,12 @@ impl<'a> ToTokens for FilterParameter<'a> {
     }
 }
 
-/// Whether `FilterParameter` is `Keyword` or `Positional`.
+/// Whether `FilterParameter` is `Keyword`, `Positional` or both.
 #[derive(PartialEq)]
 enum FilterParameterMode {
     Keyword,
     Positional,
+    PositionalOrKeyword,
 }
 
 impl FromStr for FilterParameterMode {
@@ -315,8 +321,9 @@ impl FromStr for FilterParameterMode {
         match s {
             "keyword" => Ok(FilterParameterMode::Keyword),
             "positional" => Ok(FilterParameterMode::Positional),
+            "positional_or_keyword" => Ok(FilterParameterMode::PositionalOrKeyword),
             s => Err(format!(
-                "Expected either \"keyword\" or \"positional\". Found \"{}\".",
+                "Expected either \"keyword\", \"positional\" or \"positional_or_keyword\". Found \"{}\".",
                 s
             )),
         }
@@ -452,7 +459,11 @@ impl FilterParameterMeta {
 fn generate_construct_positional_field(field: &FilterParameter, required: usize) -> TokenStream {
     let name = &field.name;
 
-    if field.is_optional() {
+    if field.is_named() {
+        quote! {
+            let mut #name = args.positional.next();
+        }
+    } else if field.is_optional() {
         quote! {
             let #name = args.positional.next();
         }
@@ -599,16 +610,20 @@ fn generate_impl_filter_parameters(filter_parameters: &FilterParameters) -> Toke
     let match_keyword_parameters_arms = fields
         .parameters
         .iter()
-        .filter(|parameter| parameter.is_keyword())
+        .filter(|parameter| parameter.is_named())
         .map(|field| generate_keyword_match_arm(&field));
 
     let unwrap_required_keyword_fields = fields
         .parameters
         .iter()
-        .filter(|parameter| parameter.is_keyword() && parameter.is_required())
+        .filter(|parameter| parameter.is_named() && parameter.is_required())
         .map(|field| {
             let liquid_name = field.liquid_name();
-            quote!{ let #field = #field.ok_or_else(|| ::liquid::error::Error::with_msg(concat!("Expected named argument `", #liquid_name, "`")))?; }
+            if field.is_keyword() {
+                quote!{ let #field = #field.ok_or_else(|| ::liquid::error::Error::with_msg(concat!("Expected named argument `", #liquid_name, "`")))?; }
+            } else {
+                quote!{ let #field = #field.ok_or_else(|| ::liquid::error::Error::with_msg(concat!("Expected argument `", #liquid_name, "`")))?; }
+            }
         });
 
     quote! {
@@ -726,14 +741,20 @@ fn generate_impl_reflection(filter_parameters: &FilterParameters) -> TokenStream
 /// Helper function for `generate_impl_display`
 fn generate_access_positional_field_for_display(field: &FilterParameter) -> TokenStream {
     let rust_name = &field.name;
+    let liquid_name = if field.is_named() {
+        let liquid_name = field.liquid_name();
+        quote! { ::std::option::Option::Some(#liquid_name) }
+    } else {
+        quote! { ::std::option::Option::None }
+    };
 
     if field.is_optional() {
         quote! {
-            self.#rust_name.as_ref()
+            (#liquid_name, self.#rust_name.as_ref())
         }
     } else {
         quote! {
-            ::std::option::Option::Some(&self.#rust_name)
+            (#liquid_name, ::std::option::Option::Some(&self.#rust_name))
         }
     }
 }
@@ -776,10 +797,18 @@ fn generate_impl_display(filter_parameters: &FilterParameters) -> TokenStream {
                 let positional = [#(#positional_fields ,)*];
                 let keyword = [#(#keyword_fields ,)*];
 
+                // Parameters after one that is left out are named, when they may be.
+                let mut skipped = false;
                 let positional = positional
                     .iter()
-                    .filter_map(|p: &::std::option::Option<&::liquid::interpreter::Expression>| p.as_ref())
-                    .map(|p| p.to_string());
+                    .filter_map(|p: &(::std::option::Option<&str>, ::std::option::Option<&::liquid::interpreter::Expression>)| match (p.0, p.1) {
+                        (_, ::std::option::Option::None) => {
+                            skipped = true;
+                            ::std::option::Option::None
+                        }
+                        (::std::option::Option::Some(name), ::std::option::Option::Some(p1)) if skipped => ::std::option::Option::Some(format!("{}: {}", name, p1)),
+                        (_, ::std::option::Option::Some(p1)) => ::std::option::Option::Some(p1.to_string()),
+                    });
                 let keyword = keyword.iter().filter_map(|p: &(&str, ::std::option::Option<&::liquid::interpreter::Expression>)| match p.1 {
                     ::std::option::Option::Some(p1) => ::std::option::Option::Some(format!("{}: {}", p.0, p1)),
                     ::std::option::Option::None => ::std::option::Option::None,
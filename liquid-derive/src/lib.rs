@@ -1,4 +1,4 @@
-//! Derive macros to aid in filter creation.
+//! Derive macros to aid in filter creation, and compile-time checked templates.
 
 extern crate liquid_compiler;
 extern crate liquid_error;
@@ -10,7 +10,9 @@ extern crate syn;
 mod filter;
 mod filter_parameters;
 pub(crate) mod helpers;
+mod object_view;
 mod parse_filter;
+mod template;
 
 use proc_macro::TokenStream;
 
@@ -40,7 +42,9 @@ use proc_macro::TokenStream;
 /// `FilterReflection`
 ///     - `rename` -> overrides `NAME` as the liquid name of the parameter
 /// (to avoid collisions with rust keywords)
-///     - `mode` -> either "keyword" or "positional" (defaults to "positional")
+///     - `mode` -> either "keyword", "positional" or "positional_or_keyword" (defaults to
+/// "positional").  A "positional_or_keyword" parameter is positional but may also be named,
+/// e.g. `{{ img | thumbnail: width: 100, height: 50 }}`.
 ///     - `arg_type` -> a shortcut to unwrap the content of a value while evaluating
 /// the argument (defaults to "any"). See below for more information.
 ///
@@ -143,15 +147,19 @@ pub fn derive_filter_parameters(item: TokenStream) -> TokenStream {
 
 /// Implements `ParseFilter`.
 ///
-/// This is only useful for stateless filters (the most common case). For
-/// filters with configurable state, you will need to implement this trait
-/// manually.
-///
 /// Requires the `#[filter(...)]` attribute to define the filter, with the
 /// following information:
 ///     - `parameters` -> (OPTIONAL) only required if the filter has parameters,
 /// the `FilterParameters` struct
 ///     - `parsed` -> the `Filter` struct
+///     - `stateful` -> (OPTIONAL) build the `Filter` from a clone of the
+/// `ParseFilter` too, so the configuration it was registered with reaches
+/// `Filter::evaluate`
+///
+/// The `Filter` is built with `Default`, or `From<Parameters>` when the filter
+/// has parameters.  With `stateful`, it is built with `From<Self>`, or
+/// `From<(Self, Parameters)>` when the filter has parameters, which
+/// `#[derive(FromFilterParameters)]` implements for a field marked `#[state]`.
 ///
 /// # Example
 ///
@@ -165,6 +173,23 @@ pub fn derive_filter_parameters(item: TokenStream) -> TokenStream {
 /// )]
 /// pub struct Slice;
 /// ```
+///
+/// A filter configured when it is registered, e.g. with
+/// `.filter(AssetUrl { cdn: "https://cdn.example.com".into() })`:
+///
+/// ```ignore
+/// #[derive(Clone, ParseFilter, FilterReflection)]
+/// #[filter(
+///     name = "asset_url",
+///     description = "Returns the URL of an asset on the CDN.",
+///     parameters(AssetUrlArgs),
+///     parsed(AssetUrlFilter),
+///     stateful // `AssetUrlFilter` implements `From<(AssetUrl, AssetUrlArgs)>`
+/// )]
+/// pub struct AssetUrl {
+///     cdn: String, // Keep large configuration, like tables, in an `Arc`
+/// }
+/// ```
 #[proc_macro_derive(ParseFilter, attributes(filter))]
 pub fn derive_parse_filter(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as syn::DeriveInput);
@@ -180,6 +205,8 @@ pub fn derive_parse_filter(item: TokenStream) -> TokenStream {
 ///     - `description` -> the description of the filter
 ///     - `parameters` -> (OPTIONAL) only required if the filter has parameters,
 /// the `FilterParameters` struct
+///     - `deprecated` -> (OPTIONAL) why the filter should no longer be used
+///     - `replacement` -> (OPTIONAL) what to use instead of a deprecated filter
 ///
 /// # Example
 ///
@@ -203,6 +230,9 @@ pub fn derive_filter_reflection(item: TokenStream) -> TokenStream {
 /// intends to implement the `Filter` trait.
 ///
 /// The field that holds the parameters must be marked with `#[parameters]`.
+/// The field marked with `#[state]`, if any, holds the `ParseFilter` of a
+/// `stateful` filter: `From<(S, T)>` is implemented instead, with `S` the type
+/// of that field.  Other fields are set to their `Default`.
 ///
 /// # Example
 ///
@@ -217,8 +247,25 @@ pub fn derive_filter_reflection(item: TokenStream) -> TokenStream {
 /// impl Filter for AtLeastFilter {
 ///     // ...
 /// }
+///
+/// #[derive(Debug, FromFilterParameters, Display_filter)]
+/// #[name = "asset_url"]
+/// struct AssetUrlFilter {
+///     #[parameters]
+///     args: AssetUrlArgs,
+///     #[state] // The `AssetUrl` the filter was registered as
+///     config: AssetUrl,
+/// }
+///
+/// impl Filter for AssetUrlFilter {
+///     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+///         let args = self.args.evaluate(context)?;
+///         let url = format!("{}/{}", self.config.cdn, input.to_str());
+///         // ...
+///     }
+/// }
 /// ```
-#[proc_macro_derive(FromFilterParameters, attributes(parameters))]
+#[proc_macro_derive(FromFilterParameters, attributes(parameters, state))]
 pub fn derive_from_filter_parameters(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as syn::DeriveInput);
     filter::from_filter_parameters::derive(&input).into()
@@ -252,3 +299,69 @@ pub fn derive_display_filter(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as syn::DeriveInput);
     filter::display::derive(&input).into()
 }
+
+/// Implements `ObjectView` and `ValueView` for a struct with named fields, so it can be read
+/// by templates without being converted into a `Value`.
+///
+/// Every field must implement `ValueView` and is an entry of the object, under the field's
+/// name.  The `#[object_view(...)]` attribute of a field may:
+///     - `rename = "..."` -> use another name for the entry
+///     - `skip` -> leave the field out of the object
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Debug, ObjectView)]
+/// struct Product {
+///     title: String,
+///     tags: Vec<String>,
+///     #[object_view(rename = "type")]
+///     kind: String,
+///     #[object_view(skip)]
+///     cost: f64,
+/// }
+///
+/// let globals = liquid::interpreter::ViewStore::new(&product);
+/// let output = template.render(&globals)?;
+/// ```
+#[proc_macro_derive(ObjectView, attributes(object_view))]
+pub fn derive_object_view(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    object_view::derive(&input).into()
+}
+
+/// Embeds a template file, checking it at compile time.
+///
+/// The path is relative to the crate's `CARGO_MANIFEST_DIR`.  Syntax errors and tags or
+/// filters that are neither standard liquid nor declared become compile errors.  Expands to a
+/// `liquid::EmbeddedTemplate`, to be parsed at runtime with a `Parser` that has every declared
+/// tag, block and filter registered.
+///
+/// # Example
+///
+/// ```ignore
+/// let email = liquid::template!(
+///     "templates/email.liquid",
+///     blocks = ["section"],
+///     filters = ["pluralize"]
+/// );
+/// let template = email.parse(&parser)?;
+/// ```
+#[proc_macro]
+pub fn template(input: TokenStream) -> TokenStream {
+    template::expand(input.into(), template::Source::File).into()
+}
+
+/// Embeds a template given as a string, checking it at compile time.
+///
+/// Accepts the same declarations as `template!`.
+///
+/// # Example
+///
+/// ```ignore
+/// let greeting = liquid::template_str!("Hello {{ name | shout }}!", filters = ["shout"]);
+/// ```
+#[proc_macro]
+pub fn template_str(input: TokenStream) -> TokenStream {
+    template::expand(input.into(), template::Source::Inline).into()
+}
@@ -0,0 +1,198 @@
+use helpers::*;
+use proc_macro2::*;
+use proc_quote::*;
+use syn::*;
+
+/// Struct that contains information about the struct to generate the
+/// necessary code for `ObjectView`.
+struct ObjectViewStruct<'a> {
+    name: &'a Ident,
+    generics: &'a Generics,
+    fields: Vec<ObjectViewField<'a>>,
+}
+
+/// A field read as an entry of the object.
+struct ObjectViewField<'a> {
+    ident: &'a Ident,
+    key: String,
+}
+
+impl<'a> ObjectViewStruct<'a> {
+    /// Tries to create a new `ObjectViewStruct` from the given `DeriveInput`
+    fn from_input(input: &'a DeriveInput) -> Result<Self> {
+        let DeriveInput {
+            ident,
+            generics,
+            data,
+            ..
+        } = &input;
+
+        let fields = match data {
+            Data::Struct(data) => match &data.fields {
+                Fields::Named(fields) => &fields.named,
+                fields => {
+                    return Err(Error::new_spanned(
+                        fields,
+                        "ObjectView can only be derived for structs with named fields.",
+                    ))
+                }
+            },
+            Data::Enum(data) => {
+                return Err(Error::new_spanned(
+                    data.enum_token,
+                    "Enums cannot be ObjectView.",
+                ))
+            }
+            Data::Union(data) => {
+                return Err(Error::new_spanned(
+                    data.union_token,
+                    "Unions cannot be ObjectView.",
+                ))
+            }
+        };
+
+        let mut view_fields = Vec::new();
+        for field in fields {
+            let ident = field
+                .ident
+                .as_ref()
+                .expect("Fields are named, so they have an ident.");
+            let meta = ObjectViewFieldMeta::from_field(field)?;
+            if meta.skip {
+                continue;
+            }
+            let key = meta
+                .rename
+                .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_owned());
+            view_fields.push(ObjectViewField { ident, key });
+        }
+
+        Ok(ObjectViewStruct {
+            name: ident,
+            generics,
+            fields: view_fields,
+        })
+    }
+
+    /// Generates `impl` declaration of the given trait for the structure
+    /// represented by `self`.
+    fn generate_impl(&self, trait_name: TokenStream) -> TokenStream {
+        let name = &self.name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        quote! {
+            impl #impl_generics #trait_name for #name #ty_generics #where_clause
+        }
+    }
+}
+
+/// The information about a field parsed from its `#[object_view(...)]` attribute.
+struct ObjectViewFieldMeta {
+    rename: Option<String>,
+    skip: bool,
+}
+
+impl ObjectViewFieldMeta {
+    /// Tries to create a new `ObjectViewFieldMeta` from the given field.
+    fn from_field(field: &Field) -> Result<Self> {
+        let mut rename = AssignOnce::Unset;
+        let mut skip = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("object_view"))
+        {
+            let meta = attr.parse_meta().map_err(|err| {
+                Error::new(
+                    err.span(),
+                    format!("Could not parse `object_view` attribute: {}", err),
+                )
+            })?;
+            let meta = match meta {
+                Meta::List(meta) => meta,
+                meta => {
+                    return Err(Error::new_spanned(
+                        meta,
+                        "Couldn't parse object_view attribute. Have you tried `#[object_view(rename = \"...\")]`?",
+                    ))
+                }
+            };
+
+            for meta in meta.nested.into_iter() {
+                match meta {
+                    NestedMeta::Meta(Meta::NameValue(ref meta)) if meta.ident == "rename" => {
+                        assign_str_value(&mut rename, &meta.ident, &meta.lit)?
+                    }
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "skip" => skip = true,
+                    meta => {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "Unknown element in object_view attribute. Expected `rename = \"...\"` or `skip`.",
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(ObjectViewFieldMeta {
+            rename: rename.to_option(),
+            skip,
+        })
+    }
+}
+
+fn generate_impl_object_view(object: &ObjectViewStruct) -> TokenStream {
+    let impl_object_view = object.generate_impl(quote! { ::liquid::value::ObjectView });
+    let size = object.fields.len();
+    let keys = object.fields.iter().map(|field| &field.key);
+    let arms = object.fields.iter().map(|field| {
+        let key = &field.key;
+        let ident = field.ident;
+        quote! { #key => ::std::option::Option::Some(&self.#ident) }
+    });
+
+    quote! {
+        #impl_object_view {
+            fn size(&self) -> usize {
+                #size
+            }
+
+            fn keys(&self) -> ::std::boxed::Box<dyn (::std::iter::Iterator<Item = &str>) + '_> {
+                const KEYS: &[&str] = &[#(#keys),*];
+                ::std::boxed::Box::new(KEYS.iter().cloned())
+            }
+
+            fn get(&self, key: &str) -> ::std::option::Option<&dyn (::liquid::value::ValueView)> {
+                match key {
+                    #(#arms,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    }
+}
+
+fn generate_impl_value_view(object: &ObjectViewStruct) -> TokenStream {
+    let impl_value_view = object.generate_impl(quote! { ::liquid::value::ValueView });
+
+    quote! {
+        #impl_value_view {
+            fn as_object(&self) -> ::std::option::Option<&dyn (::liquid::value::ObjectView)> {
+                ::std::option::Option::Some(self)
+            }
+        }
+    }
+}
+
+pub fn derive(input: &DeriveInput) -> TokenStream {
+    let object = match ObjectViewStruct::from_input(input) {
+        Ok(object) => object,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    let mut output = TokenStream::new();
+    output.extend(generate_impl_object_view(&object));
+    output.extend(generate_impl_value_view(&object));
+
+    output
+}
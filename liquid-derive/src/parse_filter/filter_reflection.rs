@@ -6,6 +6,8 @@ fn generate_reflection(filter_parser: &ParseFilter) -> Result<TokenStream> {
         filter_name,
         filter_description,
         parameters_struct_name,
+        deprecated,
+        replacement,
         ..
     } = &filter_parser.meta;
 
@@ -26,6 +28,21 @@ fn generate_reflection(filter_parser: &ParseFilter) -> Result<TokenStream> {
         (quote! { &[] }, quote! { &[] })
     };
 
+    let deprecated = deprecated.as_ref().map(|message| {
+        let replacement = match replacement {
+            Some(replacement) => quote! { Some(#replacement) },
+            None => quote! { None },
+        };
+        quote! {
+            fn deprecated(&self) -> Option<::liquid::compiler::Deprecation> {
+                Some(::liquid::compiler::Deprecation {
+                    message: #message,
+                    replacement: #replacement,
+                })
+            }
+        }
+    });
+
     Ok(quote! {
         #impl_filter_reflection {
             fn name(&self) -> &'static str {
@@ -42,6 +59,8 @@ fn generate_reflection(filter_parser: &ParseFilter) -> Result<TokenStream> {
             fn keyword_parameters(&self) -> &'static [::liquid::compiler::ParameterReflection] {
                 #keyword_parameters
             }
+
+            #deprecated
         }
     })
 }
@@ -85,6 +85,10 @@ struct ParseFilterMeta {
     filter_description: Result<String>,
     parameters_struct_name: Option<Ident>,
     filter_struct_name: Result<Ident>,
+    deprecated: Option<String>,
+    replacement: Option<String>,
+    /// Whether the `Filter` is built from the `ParseFilter` too, with `stateful`.
+    stateful: bool,
 }
 
 impl ParseFilterMeta {
@@ -113,6 +117,9 @@ impl ParseFilterMeta {
         let mut description = AssignOnce::Unset;
         let mut parameters = AssignOnce::Unset;
         let mut parsed = AssignOnce::Unset;
+        let mut deprecated = AssignOnce::Unset;
+        let mut replacement = AssignOnce::Unset;
+        let mut stateful = AssignOnce::Unset;
 
         for meta in meta.nested.into_iter() {
             match meta {
@@ -123,6 +130,8 @@ impl ParseFilterMeta {
                     match key.to_string().as_str() {
                         "name" => assign_str_value(&mut name, key, value)?,
                         "description" => assign_str_value(&mut description, key, value)?,
+                        "deprecated" => assign_str_value(&mut deprecated, key, value)?,
+                        "replacement" => assign_str_value(&mut replacement, key, value)?,
                         "parameters" => {
                             Err(Error::new_spanned(key, "Did you mean `parameters(...)`."))?
                         }
@@ -134,6 +143,18 @@ impl ParseFilterMeta {
                     }
                 }
 
+                NestedMeta::Meta(Meta::Word(word)) => match word.to_string().as_str() {
+                    "stateful" => stateful.set(true, || {
+                        Error::new_spanned(&word, "Found multiple definitions for `stateful`.")
+                    })?,
+                    _ => {
+                        return Err(Error::new_spanned(
+                            word,
+                            "Unknown element in filter attribute.",
+                        ));
+                    }
+                },
+
                 NestedMeta::Meta(Meta::List(meta)) => {
                     let attr = &meta.ident;
 
@@ -191,6 +212,14 @@ impl ParseFilterMeta {
             "FilterReflection does not have a description. Have you tried `#[filter(name=\"...\", description=\"...\", parameters(...), parsed(...))]`?",
         ));
         let parameters_struct_name = parameters.to_option();
+        let deprecated = deprecated.to_option();
+        let replacement = replacement.to_option();
+        if deprecated.is_none() && replacement.is_some() {
+            return Err(Error::new_spanned(
+                attr,
+                "Found `replacement` without `deprecated`. Have you tried `#[filter(..., deprecated=\"...\", replacement=\"...\")]`?",
+            ));
+        }
         let filter_struct_name = parsed.unwrap_or_err(|| Error::new_spanned(
             attr,
             "ParseFilter does not have a Filter to return. Have you tried `#[filter(name=\"...\", description=\"...\", parameters(...), parsed(...))]`?",
@@ -201,6 +230,9 @@ impl ParseFilterMeta {
             filter_description,
             parameters_struct_name,
             filter_struct_name,
+            deprecated,
+            replacement,
+            stateful: stateful.default_to(false),
         })
     }
 }
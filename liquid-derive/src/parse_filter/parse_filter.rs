@@ -5,6 +5,7 @@ fn generate_parse_filter(filter_parser: &ParseFilter) -> Result<TokenStream> {
     let ParseFilterMeta {
         parameters_struct_name,
         filter_struct_name,
+        stateful,
         ..
     } = &filter_parser.meta;
 
@@ -17,8 +18,14 @@ fn generate_parse_filter(filter_parser: &ParseFilter) -> Result<TokenStream> {
             let args = <#parameters_struct_name as ::liquid::compiler::FilterParameters>::from_args(args)?;
         };
 
-        let return_expr = quote_spanned! {filter_struct_name.span()=>
-            Ok(::std::boxed::Box::new(<#filter_struct_name as ::std::convert::From<#parameters_struct_name>>::from(args)))
+        let return_expr = if *stateful {
+            quote_spanned! {filter_struct_name.span()=>
+                Ok(::std::boxed::Box::new(<#filter_struct_name as ::std::convert::From<(Self, #parameters_struct_name)>>::from((::std::clone::Clone::clone(self), args))))
+            }
+        } else {
+            quote_spanned! {filter_struct_name.span()=>
+                Ok(::std::boxed::Box::new(<#filter_struct_name as ::std::convert::From<#parameters_struct_name>>::from(args)))
+            }
         };
 
         Ok(quote! {
@@ -34,8 +41,14 @@ fn generate_parse_filter(filter_parser: &ParseFilter) -> Result<TokenStream> {
             }
         })
     } else {
-        let return_expr = quote_spanned! {filter_struct_name.span()=>
-            ::std::result::Result::Ok(::std::boxed::Box::new(<#filter_struct_name as ::std::default::Default>::default()))
+        let return_expr = if *stateful {
+            quote_spanned! {filter_struct_name.span()=>
+                ::std::result::Result::Ok(::std::boxed::Box::new(<#filter_struct_name as ::std::convert::From<Self>>::from(::std::clone::Clone::clone(self))))
+            }
+        } else {
+            quote_spanned! {filter_struct_name.span()=>
+                ::std::result::Result::Ok(::std::boxed::Box::new(<#filter_struct_name as ::std::default::Default>::default()))
+            }
         };
         Ok(quote! {
             #impl_parse_filter {
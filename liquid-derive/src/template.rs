@@ -0,0 +1,151 @@
+//! Compile-time checked templates.
+
+use liquid_compiler::{scan_plugins, PluginKind, LIQUID_BLOCKS, LIQUID_FILTERS, LIQUID_TAGS};
+use proc_macro2::*;
+use proc_quote::*;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::*;
+
+/// The tags that separate the sections of liquid blocks.
+const LIQUID_BLOCK_TAGS: &[&str] = &["else", "elsif", "when"];
+
+/// Where the template source comes from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// A path relative to `CARGO_MANIFEST_DIR`.
+    File,
+    /// The template itself.
+    Inline,
+}
+
+/// The arguments of `template!` and `template_str!`.
+struct TemplateInput {
+    source: LitStr,
+    tags: Vec<String>,
+    blocks: Vec<String>,
+    filters: Vec<String>,
+}
+
+impl Parse for TemplateInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let source = input.parse()?;
+        let mut tags = Vec::new();
+        let mut blocks = Vec::new();
+        let mut filters = Vec::new();
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let content;
+            bracketed!(content in input);
+            let names: Punctuated<LitStr, Token![,]> =
+                content.parse_terminated(<LitStr as Parse>::parse)?;
+            let names = names.into_iter().map(|name| name.value());
+
+            if key == "tags" {
+                tags.extend(names);
+            } else if key == "blocks" {
+                blocks.extend(names);
+            } else if key == "filters" {
+                filters.extend(names);
+            } else {
+                return Err(Error::new(
+                    key.span(),
+                    "Unknown argument. Expected one of `tags`, `blocks` or `filters`.",
+                ));
+            }
+        }
+
+        Ok(Self {
+            source,
+            tags,
+            blocks,
+            filters,
+        })
+    }
+}
+
+impl TemplateInput {
+    fn is_tag(&self, name: &str) -> bool {
+        let is_block =
+            |block: &str| block == name || name.strip_prefix("end").is_some_and(|n| n == block);
+        LIQUID_TAGS.contains(&name)
+            || LIQUID_BLOCK_TAGS.contains(&name)
+            || LIQUID_BLOCKS.iter().any(|b| is_block(b))
+            || self.tags.iter().any(|t| t == name)
+            || self.blocks.iter().any(|b| is_block(b))
+    }
+
+    fn is_filter(&self, name: &str) -> bool {
+        LIQUID_FILTERS.contains(&name) || self.filters.iter().any(|f| f == name)
+    }
+
+    /// Checks `text` only uses known tags and filters.
+    fn validate(&self, text: &str) -> Result<()> {
+        let span = self.source.span();
+        let uses = scan_plugins(text).map_err(|e| Error::new(span, e.to_string()))?;
+        for plugin in uses {
+            let (known, kind, declare) = match plugin.kind {
+                PluginKind::Tag => (self.is_tag(&plugin.name), "tag", "tags` or `blocks"),
+                PluginKind::Filter => (self.is_filter(&plugin.name), "filter", "filters"),
+            };
+            if !known {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "Unknown {} `{}` on line {}. Declare it with `{} = [...]`.",
+                        kind, plugin.name, plugin.line, declare
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn generate(input: &TemplateInput, source: Source) -> Result<TokenStream> {
+    let literal = &input.source;
+    match source {
+        Source::Inline => {
+            input.validate(&literal.value())?;
+            Ok(quote! {
+                ::liquid::EmbeddedTemplate::new(#literal)
+            })
+        }
+        Source::File => {
+            let dir = env::var("CARGO_MANIFEST_DIR")
+                .map_err(|_| Error::new(literal.span(), "CARGO_MANIFEST_DIR is not set."))?;
+            let path: PathBuf = [dir, literal.value()].iter().collect();
+            let text = fs::read_to_string(&path).map_err(|e| {
+                Error::new(
+                    literal.span(),
+                    format!("Cannot read `{}`: {}", path.display(), e),
+                )
+            })?;
+            input.validate(&text)?;
+
+            // `include_str!` makes cargo rebuild when the template changes.
+            Ok(quote! {
+                ::liquid::EmbeddedTemplate::new(
+                    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #literal))
+                )
+            })
+        }
+    }
+}
+
+pub fn expand(input: TokenStream, source: Source) -> TokenStream {
+    match parse2::<TemplateInput>(input).and_then(|input| generate(&input, source)) {
+        Ok(output) => output,
+        Err(err) => err.to_compile_error(),
+    }
+}
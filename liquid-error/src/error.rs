@@ -11,6 +11,15 @@ pub type Result<T> = result::Result<T, Error>;
 
 type BoxedError = Box<dyn ErrorClone>;
 
+/// What kind of problem an `Error` is, for callers that handle some differently.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Any problem without a kind of its own, e.g. a template that can't be parsed.
+    Other,
+    /// Rendering went over one of the limits it was given, e.g. on the size of the output.
+    LimitExceeded,
+}
+
 /// Compiler error
 #[derive(Debug, Clone)]
 pub struct Error {
@@ -24,8 +33,11 @@ pub struct Error {
 #[derive(Debug, Clone)]
 struct InnerError {
     msg: borrow::Cow<'static, str>,
+    kind: ErrorKind,
     user_backtrace: Vec<Trace>,
     cause: Option<BoxedError>,
+    position: Option<(usize, usize)>,
+    excerpt: Option<String>,
 }
 
 impl Error {
@@ -37,14 +49,23 @@ impl Error {
     fn with_msg_cow(msg: borrow::Cow<'static, str>) -> Self {
         let error = InnerError {
             msg,
+            kind: ErrorKind::Other,
             user_backtrace: vec![Trace::empty()],
             cause: None,
+            position: None,
+            excerpt: None,
         };
         Self {
             inner: Box::new(error),
         }
     }
 
+    /// Mark the error as being of `kind`.
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.inner.kind = kind;
+        self
+    }
+
     /// Add a new call to the user-visible backtrace
     pub fn trace<T>(self, trace: T) -> Self
     where
@@ -95,6 +116,67 @@ impl Error {
         self
     }
 
+    /// Locate the error at `line` and `column` of the template, both starting at 1.
+    ///
+    /// The innermost position is the most precise, so an error keeps the first one it is given.
+    pub fn with_position(mut self, line: usize, column: usize) -> Self {
+        if self.inner.position.is_none() {
+            self.inner.position = Some((line, column));
+        }
+        self
+    }
+
+    /// Show the line of `source` the error is at, pointing at its column.
+    ///
+    /// Does nothing for errors without a position or that already have an excerpt.
+    pub fn with_source(self, source: &str) -> Self {
+        match self.inner.position {
+            Some((line, _)) if self.inner.excerpt.is_none() => {
+                let text = source
+                    .lines()
+                    .nth(line.saturating_sub(1))
+                    .unwrap_or_default();
+                self.with_excerpt(text)
+            }
+            _ => self,
+        }
+    }
+
+    /// Show `text`, the line the error is at, pointing at its column.
+    ///
+    /// Does nothing for errors without a position or that already have an excerpt.
+    pub fn with_excerpt(mut self, text: &str) -> Self {
+        if let (Some((line, column)), None) = (self.inner.position, &self.inner.excerpt) {
+            self.inner.excerpt = Some(render_excerpt(line, column, text));
+        }
+        self
+    }
+
+    /// What kind of problem the error is.
+    pub fn kind(&self) -> ErrorKind {
+        self.inner.kind
+    }
+
+    /// What went wrong, without where or the context of it.
+    pub fn message(&self) -> &str {
+        &self.inner.msg
+    }
+
+    /// The line of the template the error is at, starting at 1.
+    pub fn line(&self) -> Option<usize> {
+        self.inner.position.map(|(line, _)| line)
+    }
+
+    /// The column of the template the error is at, starting at 1.
+    pub fn column(&self) -> Option<usize> {
+        self.inner.position.map(|(_, column)| column)
+    }
+
+    /// The line of the template the error is at, with a caret under its column.
+    pub fn excerpt(&self) -> Option<&str> {
+        self.inner.excerpt.as_deref()
+    }
+
     /// Simplify returning early with an error.
     pub fn into_err<T, E>(self) -> ::std::result::Result<T, E>
     where
@@ -105,11 +187,34 @@ impl Error {
     }
 }
 
+/// Renders `text`, line `line` of a template, with a caret under `column`.
+fn render_excerpt(line: usize, column: usize, text: &str) -> String {
+    let text = text.trim_end_matches(['\n', '\r']);
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    // Tabs are kept so the caret lines up however wide they are shown.
+    let indent: String = text
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    format!(
+        "{} |\n{} | {}\n{} | {}^",
+        gutter, number, text, gutter, indent
+    )
+}
+
 const ERROR_DESCRIPTION: &str = "liquid";
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}: {}", ERROR_DESCRIPTION, self.inner.msg)?;
+        if let Some((line, column)) = self.inner.position {
+            writeln!(f, " --> {}:{}", line, column)?;
+        }
+        if let Some(ref excerpt) = self.inner.excerpt {
+            writeln!(f, "{}", excerpt)?;
+        }
         for trace in &self.inner.user_backtrace {
             if let Some(trace) = trace.get_trace() {
                 writeln!(f, "from: {}", trace)?;
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use liquid_error::{Error, Result};
+use liquid_value::{Number, Value};
+
+use super::Coercion;
+use super::Context;
+
+/// An operator of arithmetic expressions, e.g. `+` in `{{ width + 10 }}`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArithmeticOperator {
+    /// `+`, like `plus`.
+    Plus,
+    /// `-`, like `minus`.
+    Minus,
+    /// `*`, like `times`.
+    Times,
+    /// `/`, like `divided_by`.
+    DividedBy,
+    /// `%`, like `modulo`.
+    Modulo,
+}
+
+impl ArithmeticOperator {
+    /// The operator written `symbol`, e.g. `+`.
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "+" => Some(ArithmeticOperator::Plus),
+            "-" => Some(ArithmeticOperator::Minus),
+            "*" => Some(ArithmeticOperator::Times),
+            "/" => Some(ArithmeticOperator::DividedBy),
+            "%" => Some(ArithmeticOperator::Modulo),
+            _ => None,
+        }
+    }
+
+    /// How the operator is written, e.g. `+`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ArithmeticOperator::Plus => "+",
+            ArithmeticOperator::Minus => "-",
+            ArithmeticOperator::Times => "*",
+            ArithmeticOperator::DividedBy => "/",
+            ArithmeticOperator::Modulo => "%",
+        }
+    }
+
+    /// How tightly the operator binds its operands: `*`, `/` and `%` bind tighter than `+` and
+    /// `-`.
+    pub fn precedence(self) -> u8 {
+        match self {
+            ArithmeticOperator::Plus | ArithmeticOperator::Minus => 1,
+            ArithmeticOperator::Times
+            | ArithmeticOperator::DividedBy
+            | ArithmeticOperator::Modulo => 2,
+        }
+    }
+
+    /// Apply the operator to `lhs` and `rhs`, like the filter doing the same, e.g. `plus` for
+    /// `+`.
+    ///
+    /// Whole numbers make a whole number, following the `NumericPolicy` of the environment, and
+    /// any fractional number makes a fractional number.
+    pub fn apply(self, lhs: &Value, rhs: &Value, context: &Context<'_>) -> Result<Value> {
+        let lhs = to_number(lhs, "left", context)?;
+        let rhs = to_number(rhs, "right", context)?;
+        let policy = context.environment().numeric_policy();
+        let result = match self {
+            ArithmeticOperator::Plus => lhs.plus(rhs, policy),
+            ArithmeticOperator::Minus => lhs.minus(rhs, policy),
+            ArithmeticOperator::Times => lhs.times(rhs, policy),
+            ArithmeticOperator::DividedBy => lhs.divided_by(rhs, policy),
+            ArithmeticOperator::Modulo => lhs.modulo(rhs, policy),
+        };
+        result
+            .map(Value::scalar)
+            .map_err(|error| error.context("operator", self.symbol()))
+    }
+}
+
+impl fmt::Display for ArithmeticOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// The operand on the `side` of an operator as a number.  Strings of numbers are numbers too,
+/// unless coercion is strict.
+fn to_number(value: &Value, side: &'static str, context: &Context<'_>) -> Result<Number> {
+    let number = value.as_scalar().and_then(|scalar| {
+        if context.environment().coercion() == Coercion::Strict {
+            scalar.as_number()
+        } else {
+            scalar.to_number()
+        }
+    });
+    number.ok_or_else(|| {
+        Error::with_msg("Number expected")
+            .context("operand", side)
+            .context("value", value.source().to_string())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use super::super::{ContextBuilder, Environment, IntegerDivision, OverflowPolicy};
+
+    fn apply(operator: &str, lhs: Value, rhs: Value) -> Result<Value> {
+        let context = Context::new();
+        ArithmeticOperator::from_symbol(operator)
+            .unwrap()
+            .apply(&lhs, &rhs, &context)
+    }
+
+    #[test]
+    fn whole_numbers_stay_whole() {
+        let seven = Value::scalar(7);
+        let two = Value::scalar(2);
+        assert_eq!(
+            apply("+", seven.clone(), two.clone()).unwrap(),
+            Value::scalar(9)
+        );
+        assert_eq!(
+            apply("-", seven.clone(), two.clone()).unwrap(),
+            Value::scalar(5)
+        );
+        assert_eq!(
+            apply("*", seven.clone(), two.clone()).unwrap(),
+            Value::scalar(14)
+        );
+        assert_eq!(
+            apply("/", seven.clone(), two.clone()).unwrap(),
+            Value::scalar(3)
+        );
+        assert_eq!(apply("%", seven.clone(), two).unwrap(), Value::scalar(1));
+        assert_eq!(
+            apply("/", seven, Value::scalar(2.0)).unwrap(),
+            Value::scalar(3.5)
+        );
+        assert_eq!(
+            apply("+", Value::scalar("3"), Value::scalar(1)).unwrap(),
+            Value::scalar(4)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(apply("/", Value::scalar(1), Value::scalar(0)).is_err());
+        assert!(apply("%", Value::scalar(1), Value::scalar(0.0)).is_err());
+        assert!(apply("+", Value::scalar("a"), Value::scalar(1)).is_err());
+        assert!(apply("+", Value::scalar(i32::MAX), Value::scalar(1)).is_err());
+    }
+
+    #[test]
+    fn environment_policies() {
+        let env = Environment::new()
+            .with_overflow_policy(OverflowPolicy::Saturate)
+            .with_integer_division(IntegerDivision::Float);
+        let context = ContextBuilder::new().set_environment(env).build();
+        let max = Value::scalar(i32::MAX);
+        assert_eq!(
+            ArithmeticOperator::Plus
+                .apply(&max, &Value::scalar(1), &context)
+                .unwrap(),
+            max
+        );
+        assert_eq!(
+            ArithmeticOperator::DividedBy
+                .apply(&Value::scalar(7), &Value::scalar(2), &context)
+                .unwrap(),
+            Value::scalar(3.5)
+        );
+    }
+}
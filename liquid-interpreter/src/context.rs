@@ -1,13 +1,26 @@
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::ops;
 use std::sync;
+use std::time::{Duration, Instant};
 
 use anymap;
+use itertools;
 use liquid_error::Error;
-use liquid_error::Result;
+use liquid_error::ErrorKind;
+use liquid_error::{Result, ResultLiquidReplaceExt};
+use liquid_value::{Date, Value};
 
+use super::Dependencies;
+use super::Environment;
+use super::Output;
 use super::PartialStore;
 use super::Renderable;
+use super::SharedGlobals;
 use super::Stack;
+use super::Usage;
 use super::ValueStore;
+use super::{limit_exceeded, value_size};
 
 /// Block processing interrupt state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -68,10 +81,52 @@ impl PartialStore for NullPartials {
     }
 }
 
+/// The budget of the filter call being run.
+#[derive(Debug)]
+struct FilterMeter {
+    name: String,
+    line_col: Option<(usize, usize)>,
+    started: Instant,
+    spent: u64,
+    time: Option<Duration>,
+    iterations: Option<u64>,
+}
+
+impl FilterMeter {
+    fn check(&self) -> Result<()> {
+        if let Some(limit) = self.iterations {
+            if self.spent > limit {
+                return Err(self.exceeded(format!("{} iterations", limit)));
+            }
+        }
+        if let Some(limit) = self.time {
+            if self.started.elapsed() > limit {
+                return Err(self.exceeded(format!("{:?}", limit)));
+            }
+        }
+        Ok(())
+    }
+
+    fn exceeded(&self, budget: String) -> Error {
+        let error = Error::with_msg("Filter exceeded its budget")
+            .context("filter", self.name.clone())
+            .context("budget", budget);
+        match self.line_col {
+            Some((line, column)) => error
+                .context("line", line.to_string())
+                .context("column", column.to_string()),
+            None => error,
+        }
+    }
+}
+
 /// Create processing context for a template.
 pub struct ContextBuilder<'g> {
     globals: Option<&'g dyn ValueStore>,
+    shared_globals: Option<SharedGlobals>,
     partials: Option<&'g dyn PartialStore>,
+    environment: sync::Arc<Environment>,
+    registers: anymap::AnyMap,
 }
 
 impl<'g> ContextBuilder<'g> {
@@ -79,7 +134,10 @@ impl<'g> ContextBuilder<'g> {
     pub fn new() -> Self {
         Self {
             globals: None,
+            shared_globals: None,
             partials: None,
+            environment: Default::default(),
+            registers: anymap::AnyMap::new(),
         }
     }
 
@@ -89,24 +147,63 @@ impl<'g> ContextBuilder<'g> {
         self
     }
 
+    /// Initialize the stack with read-only globals shared across renders, e.g. site data.
+    ///
+    /// The globals from `set_globals` take precedence over these.
+    pub fn set_shared_globals(mut self, values: SharedGlobals) -> Self {
+        self.shared_globals = Some(values);
+        self
+    }
+
     /// Initialize partial-templates availible for including.
     pub fn set_partials(mut self, values: &'g dyn PartialStore) -> Self {
         self.partials = Some(values);
         self
     }
 
+    /// Set the locale, timezone, and clock to render with.
+    ///
+    /// Pass an `Arc` to share the environment with other renders instead of moving it in.
+    pub fn set_environment<E: Into<sync::Arc<Environment>>>(mut self, environment: E) -> Self {
+        self.environment = environment.into();
+        self
+    }
+
+    /// Start a plugin's state off with `value`, e.g. to seed a random number generator.
+    ///
+    /// See `Context::get_register_mut`.
+    pub fn set_register<T: anymap::any::IntoBox<dyn anymap::any::Any>>(mut self, value: T) -> Self {
+        self.registers.insert(value);
+        self
+    }
+
     /// Create the `Context`.
     pub fn build(self) -> Context<'g> {
-        let stack = match self.globals {
+        let mut stack = match self.globals {
             Some(globals) => Stack::with_globals(globals),
             None => Stack::empty(),
         };
+        if let Some(shared) = self.shared_globals {
+            stack.set_shared_globals(shared);
+        }
         let partials = self.partials.unwrap_or(&NullPartials);
+        let environment = self.environment;
+        let now = environment.now();
+        stack.set_builtin("liquid", environment.liquid(now));
+        stack.set_strict_variables(environment.strict_variables());
         Context {
             stack,
             partials,
-            registers: anymap::AnyMap::new(),
+            environment,
+            now,
+            registers: self.registers,
             interrupt: InterruptState::default(),
+            filter_meter: RefCell::new(None),
+            partials_rendering: Vec::new(),
+            started: Instant::now(),
+            loop_iterations: Cell::new(0),
+            assigned_bytes: Cell::new(0),
+            suppressed_errors: Vec::new(),
         }
     }
 }
@@ -118,12 +215,45 @@ impl<'g> Default for ContextBuilder<'g> {
 }
 
 /// Processing context for a template.
+///
+/// Custom tags and blocks render against this: they scope their variables with `enter_scope`
+/// or `run_in_scope`, set them with `stack_mut`, keep state across renders of the template in
+/// registers, and `break` or `continue` loops through `interrupt_mut`.
+///
+/// ```
+/// use liquid_interpreter::{Context, Interrupt};
+/// use liquid_value::{Scalar, Value};
+///
+/// let mut context = Context::new();
+/// {
+///     let mut scope = context.enter_scope();
+///     scope.stack_mut().set("item", Value::scalar(1));
+///     assert!(scope.stack().try_get(&[Scalar::new("item")]).is_some());
+///     scope.interrupt_mut().set_interrupt(Interrupt::Break);
+/// }
+/// // The scope's variables are gone once it is dropped.
+/// assert!(context.stack().try_get(&[Scalar::new("item")]).is_none());
+/// assert_eq!(context.interrupt_mut().pop_interrupt(), Some(Interrupt::Break));
+/// ```
 pub struct Context<'g> {
     stack: Stack<'g>,
     partials: &'g dyn PartialStore,
+    environment: sync::Arc<Environment>,
+    now: Date,
 
     registers: anymap::AnyMap,
     interrupt: InterruptState,
+    filter_meter: RefCell<Option<FilterMeter>>,
+    // The names of the partials being rendered, innermost last.
+    partials_rendering: Vec<String>,
+
+    // What the render spent of the environment's `Limits`.
+    started: Instant,
+    loop_iterations: Cell<u64>,
+    assigned_bytes: Cell<usize>,
+
+    // The errors the environment's `ErrorPolicy` rendered a placeholder for.
+    suppressed_errors: Vec<Error>,
 }
 
 impl<'g> Context<'g> {
@@ -149,6 +279,128 @@ impl<'g> Context<'g> {
         self.partials
     }
 
+    /// Locale, timezone, and clock being rendered with.
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// The time rendering started, in the environment's timezone.
+    pub fn now(&self) -> Date {
+        self.now
+    }
+
+    /// Run `filter`, the call of the filter `name` at `line_col` of the template, within the
+    /// environment's `FilterBudget`.
+    pub fn run_filter<F, R>(
+        &self,
+        name: &str,
+        line_col: Option<(usize, usize)>,
+        filter: F,
+    ) -> Result<R>
+    where
+        F: FnOnce() -> Result<R>,
+    {
+        self.stack.record_filter(name);
+        let budget = self.environment.filter_budget().for_filter(name);
+        if budget.time().is_none() && budget.iterations().is_none() {
+            return filter();
+        }
+
+        let meter = FilterMeter {
+            name: name.to_owned(),
+            line_col,
+            started: Instant::now(),
+            spent: 0,
+            time: budget.time(),
+            iterations: budget.iterations(),
+        };
+        let outer = self.filter_meter.replace(Some(meter));
+        let result = filter();
+        let meter = self
+            .filter_meter
+            .replace(outer)
+            .expect("the meter is only taken here");
+        let value = result?;
+        meter.check()?;
+        Ok(value)
+    }
+
+    /// Spend `iterations` of the budget of the running filter, failing once it is exceeded.
+    ///
+    /// Filters whose work grows with their input call this as they go, so they stop early.
+    pub fn spend_filter_budget(&self, iterations: u64) -> Result<()> {
+        match *self.filter_meter.borrow_mut() {
+            Some(ref mut meter) => {
+                meter.spent = meter.spent.saturating_add(iterations);
+                meter.check()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Spend an iteration of a loop, failing once the render ran more than the environment's
+    /// `Limits` allow or took too long.
+    pub fn spend_loop_iteration(&self) -> Result<()> {
+        let iterations = self.loop_iterations.get() + 1;
+        self.loop_iterations.set(iterations);
+        match self.environment.limits().loop_iterations() {
+            Some(limit) if iterations > limit => {
+                Err(limit_exceeded("loop iterations", limit.to_string()))
+            }
+            _ => self.check_render_time(),
+        }
+    }
+
+    /// Spend the size of `value`, set by `assign`, `capture` or the like, failing once the
+    /// values set are larger than the environment's `Limits` allow.
+    pub fn spend_assigned(&self, value: &Value) -> Result<()> {
+        let limit = match self.environment.limits().assigned_bytes() {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let bytes = self.assigned_bytes.get().saturating_add(value_size(value));
+        self.assigned_bytes.set(bytes);
+        if bytes > limit {
+            return Err(limit_exceeded("assigned bytes", limit.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Fail once the render took longer than the environment's `Limits` allow.
+    pub fn check_render_time(&self) -> Result<()> {
+        match self.environment.limits().render_time() {
+            Some(limit) if self.started.elapsed() > limit => {
+                Err(limit_exceeded("render time", format!("{:?}", limit)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Carry on after `error`, the error of rendering an element, if the environment's
+    /// `ErrorPolicy` says to, writing its placeholder to `writer`.
+    ///
+    /// The error is kept in `suppressed_errors`.  Otherwise it is returned, to fail the render.
+    pub fn recover(&mut self, error: Error, writer: &mut dyn Output) -> Result<()> {
+        let placeholder = match self.environment.error_policy().handle(&error) {
+            Some(placeholder) => placeholder,
+            None => return Err(error),
+        };
+        self.suppressed_errors.push(error);
+        write!(writer, "{}", placeholder).replace("Failed to render")
+    }
+
+    /// The errors rendered so far that the environment's `ErrorPolicy` carried on after, in
+    /// the order they happened.
+    pub fn suppressed_errors(&self) -> &[Error] {
+        &self.suppressed_errors
+    }
+
+    /// Take the errors rendered so far that the environment's `ErrorPolicy` carried on after,
+    /// e.g. to log them once the render is done.
+    pub fn take_suppressed_errors(&mut self) -> Vec<Error> {
+        mem::take(&mut self.suppressed_errors)
+    }
+
     /// Data store for stateful tags/blocks.
     ///
     /// If a plugin needs state, it creates a `struct State : Default` and accesses it via
@@ -159,6 +411,26 @@ impl<'g> Context<'g> {
         self.registers.entry::<T>().or_insert_with(Default::default)
     }
 
+    /// Data store for stateful tags/blocks, if the state has been created.
+    ///
+    /// Filters only have shared access to the `Context`, so this is how they read state.
+    pub fn get_register<T: anymap::any::IntoBox<dyn anymap::any::Any>>(&self) -> Option<&T> {
+        self.registers.get::<T>()
+    }
+
+    /// Set the data store of type `T`, returning the one it replaces.
+    pub fn set_register<T: anymap::any::IntoBox<dyn anymap::any::Any>>(
+        &mut self,
+        value: T,
+    ) -> Option<T> {
+        self.registers.insert(value)
+    }
+
+    /// Remove the data store of type `T`, returning it.
+    pub fn remove_register<T: anymap::any::IntoBox<dyn anymap::any::Any>>(&mut self) -> Option<T> {
+        self.registers.remove::<T>()
+    }
+
     /// Access the current `Stack`.
     pub fn stack(&self) -> &Stack<'_> {
         &self.stack
@@ -172,6 +444,24 @@ impl<'g> Context<'g> {
         &mut self.stack
     }
 
+    /// Sets up a new stack frame, torn down when the returned guard is dropped.
+    ///
+    /// The guard derefs to the `Context`, so variables set through it are local to the frame,
+    /// like those of `run_in_scope`.
+    pub fn enter_scope(&mut self) -> ScopeGuard<'_, 'g> {
+        self.stack.push_frame();
+        ScopeGuard { context: self }
+    }
+
+    /// Sets up a new stack frame for the template `name`, torn down when the returned guard is
+    /// dropped.
+    ///
+    /// See `enter_scope`.
+    pub fn enter_named_scope<S: Into<String>>(&mut self, name: S) -> ScopeGuard<'_, 'g> {
+        self.stack.push_named_frame(name);
+        ScopeGuard { context: self }
+    }
+
     /// Sets up a new stack frame, executes the supplied function and then
     /// tears the stack frame down before returning the function's result
     /// to the caller.
@@ -197,16 +487,124 @@ impl<'g> Context<'g> {
         self.stack.pop_frame();
         result
     }
+
+    /// Executes the supplied function with only the globals in scope, as the template `name`.
+    ///
+    /// Variables set by the function are discarded and those of the caller are left untouched,
+    /// e.g. for `render`.
+    pub fn run_in_isolated_scope<RvalT, S: Into<String>, FnT>(&mut self, name: S, f: FnT) -> RvalT
+    where
+        FnT: FnOnce(&mut Context<'_>) -> RvalT,
+    {
+        let isolated = self.stack.isolated(name);
+        let outer = mem::replace(&mut self.stack, isolated);
+        let result = f(self);
+        let isolated = mem::replace(&mut self.stack, outer);
+        self.stack.resume(isolated);
+        result
+    }
+
+    /// Executes the supplied function as rendering the partial `name`, e.g. for `include`.
+    ///
+    /// Fails instead when partials are already nested as deep as the environment allows, e.g.
+    /// when a partial includes itself.  The error names the partials including each other.
+    pub fn run_in_partial<RvalT, FnT>(&mut self, name: &str, f: FnT) -> Result<RvalT>
+    where
+        FnT: FnOnce(&mut Context<'_>) -> Result<RvalT>,
+    {
+        let depth = self.environment.partial_depth();
+        if self.partials_rendering.len() >= depth {
+            // Name only the cycle, when the partial is in one.
+            let start = self
+                .partials_rendering
+                .iter()
+                .rposition(|partial| partial == name)
+                .unwrap_or(0);
+            let chain = itertools::join(
+                self.partials_rendering[start..]
+                    .iter()
+                    .map(String::as_str)
+                    .chain(Some(name)),
+                " > ",
+            );
+            return Error::with_msg("Include depth exceeded")
+                .context("partials", chain)
+                .context("maximum", depth.to_string())
+                .with_kind(ErrorKind::LimitExceeded)
+                .into_err();
+        }
+        self.partials_rendering.push(name.to_owned());
+        let result = f(self);
+        self.partials_rendering.pop();
+        result
+    }
+
+    /// Executes the supplied function, recording the globals it reads and the partials it
+    /// renders, e.g. for invalidating cached output when they change.
+    ///
+    /// Recordings nest: what the function reads is recorded by the enclosing recording too.
+    pub fn record_dependencies<RvalT, FnT>(&mut self, f: FnT) -> (RvalT, Dependencies)
+    where
+        FnT: FnOnce(&mut Context<'_>) -> RvalT,
+    {
+        self.stack.start_recording();
+        let result = f(self);
+        (result, self.stack.stop_recording())
+    }
+
+    /// Record the output depending on `dependencies`, as if they had been read, e.g. when
+    /// reusing cached output.
+    pub fn add_dependencies(&self, dependencies: &Dependencies) {
+        self.stack.record(dependencies);
+    }
+
+    /// Executes the supplied function, recording the filters it runs, the partials it renders
+    /// and the variables it finds undefined, e.g. to report on a render.
+    pub fn record_usage<RvalT, FnT>(&mut self, f: FnT) -> (RvalT, Usage)
+    where
+        FnT: FnOnce(&mut Context<'_>) -> RvalT,
+    {
+        let outer = self.stack.start_usage();
+        let result = f(self);
+        (result, self.stack.stop_usage(outer))
+    }
+
+    /// Record rendering the partial `name`, for `record_dependencies` and `record_usage`.
+    pub fn add_partial_dependency(&self, name: &str) {
+        self.stack.record_partial(name);
+    }
 }
 
 impl<'g> Default for Context<'g> {
     fn default() -> Self {
-        Self {
-            stack: Stack::empty(),
-            partials: &NullPartials,
-            registers: anymap::AnyMap::new(),
-            interrupt: InterruptState::default(),
-        }
+        ContextBuilder::new().build()
+    }
+}
+
+/// A stack frame of a `Context`, torn down when dropped.
+///
+/// See `Context::enter_scope`.
+pub struct ScopeGuard<'a, 'g> {
+    context: &'a mut Context<'g>,
+}
+
+impl<'a, 'g> ops::Deref for ScopeGuard<'a, 'g> {
+    type Target = Context<'g>;
+
+    fn deref(&self) -> &Context<'g> {
+        self.context
+    }
+}
+
+impl<'a, 'g> ops::DerefMut for ScopeGuard<'a, 'g> {
+    fn deref_mut(&mut self) -> &mut Context<'g> {
+        self.context
+    }
+}
+
+impl<'a, 'g> Drop for ScopeGuard<'a, 'g> {
+    fn drop(&mut self) {
+        self.context.stack.pop_frame();
     }
 }
 
@@ -215,7 +613,6 @@ mod test {
     use super::*;
 
     use liquid_value::Scalar;
-    use liquid_value::Value;
 
     #[test]
     fn scoped_variables() {
@@ -253,4 +650,101 @@ mod test {
             &Value::scalar("some value")
         );
     }
+
+    #[test]
+    fn scope_guards() {
+        let test_path = [Scalar::new("test")];
+
+        let mut ctx = Context::new();
+        ctx.stack_mut().set_global("test", Value::scalar(1));
+        {
+            let mut outer = ctx.enter_named_scope("outer");
+            outer.stack_mut().set("test", Value::scalar(2));
+            {
+                let mut inner = outer.enter_scope();
+                inner.stack_mut().set("test", Value::scalar(3));
+                assert_eq!(inner.stack().get(&test_path).unwrap(), &Value::scalar(3));
+                assert_eq!(inner.stack().frame_name(), Some("outer"));
+            }
+            assert_eq!(outer.stack().get(&test_path).unwrap(), &Value::scalar(2));
+        }
+        assert_eq!(ctx.stack().get(&test_path).unwrap(), &Value::scalar(1));
+        assert_eq!(ctx.stack().frame_name(), None);
+    }
+
+    #[test]
+    fn registers() {
+        #[derive(Debug, Default, PartialEq)]
+        struct Counter(usize);
+
+        let mut ctx = Context::new();
+        assert_eq!(ctx.get_register::<Counter>(), None);
+        ctx.get_register_mut::<Counter>().0 += 1;
+        assert_eq!(ctx.set_register(Counter(5)), Some(Counter(1)));
+        assert_eq!(ctx.get_register::<Counter>(), Some(&Counter(5)));
+        assert_eq!(ctx.remove_register::<Counter>(), Some(Counter(5)));
+        assert_eq!(ctx.get_register::<Counter>(), None);
+    }
+
+    #[test]
+    fn environment_variables() {
+        let locale_path = [Scalar::new("liquid"), Scalar::new("locale")];
+        let timezone_path = [Scalar::new("liquid"), Scalar::new("timezone")];
+        let now_path = [Scalar::new("liquid"), Scalar::new("now")];
+
+        let environment = Environment::new()
+            .with_locale("nl-NL")
+            .with_timezone(chrono::FixedOffset::east_opt(3600).unwrap());
+        let mut ctx = ContextBuilder::new().set_environment(environment).build();
+        assert_eq!(ctx.environment().locale(), "nl-NL");
+        assert_eq!(
+            ctx.stack().get(&locale_path).unwrap(),
+            &Value::scalar("nl-NL")
+        );
+        assert_eq!(
+            ctx.stack().get(&timezone_path).unwrap(),
+            &Value::scalar("+01:00")
+        );
+        assert_eq!(
+            ctx.stack().get(&now_path).unwrap(),
+            &Value::scalar(ctx.now())
+        );
+
+        // user-defined variables take precedence
+        ctx.stack_mut().set_global("liquid", Value::scalar("drop"));
+        assert_eq!(
+            ctx.stack().get(&[Scalar::new("liquid")]).unwrap(),
+            &Value::scalar("drop")
+        );
+    }
+
+    #[test]
+    fn record_dependencies() {
+        let mut globals = liquid_value::Object::new();
+        let mut product = liquid_value::Object::new();
+        product.insert("title".into(), Value::scalar("Shirt"));
+        globals.insert("product".into(), Value::Object(product));
+        let mut ctx = ContextBuilder::new().set_globals(&globals).build();
+        ctx.stack_mut().set_global("assigned", Value::Nil);
+
+        let ((), outer) = ctx.record_dependencies(|ctx| {
+            let ((), inner) = ctx.record_dependencies(|ctx| {
+                let path = [Scalar::new("product"), Scalar::new("title")];
+                ctx.stack().get(&path).unwrap();
+                ctx.stack().get(&[Scalar::new("assigned")]).unwrap();
+                ctx.stack().get(&[Scalar::new("liquid")]).unwrap();
+            });
+            assert_eq!(inner.globals().collect::<Vec<_>>(), vec!["product.title"]);
+
+            ctx.run_in_isolated_scope("partial", |ctx| {
+                ctx.add_partial_dependency("partial");
+                ctx.stack().get(&[Scalar::new("missing")]).unwrap();
+            });
+        });
+        assert_eq!(
+            outer.globals().collect::<Vec<_>>(),
+            vec!["missing", "product.title"]
+        );
+        assert_eq!(outer.partials().collect::<Vec<_>>(), vec!["partial"]);
+    }
 }
@@ -0,0 +1,91 @@
+use std::collections::BTreeSet;
+
+/// The globals and partials a render read, to know what content to render again when they
+/// change.
+///
+/// Globals are recorded by the path they were read at, e.g. `product.title`.  See
+/// `Context::record_dependencies`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dependencies {
+    globals: BTreeSet<String>,
+    partials: BTreeSet<String>,
+}
+
+impl Dependencies {
+    /// Create an empty set of dependencies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether nothing was read.
+    pub fn is_empty(&self) -> bool {
+        self.globals.is_empty() && self.partials.is_empty()
+    }
+
+    /// The paths of the globals read, in order.
+    pub fn globals(&self) -> impl Iterator<Item = &str> {
+        self.globals.iter().map(|s| s.as_str())
+    }
+
+    /// The names of the partials rendered, in order.
+    pub fn partials(&self) -> impl Iterator<Item = &str> {
+        self.partials.iter().map(|s| s.as_str())
+    }
+
+    /// Record reading the global at `path`, e.g. `product.title`.
+    pub fn add_global<S: Into<String>>(&mut self, path: S) {
+        self.globals.insert(path.into());
+    }
+
+    /// Record rendering the partial `name`.
+    pub fn add_partial<S: Into<String>>(&mut self, name: S) {
+        self.partials.insert(name.into());
+    }
+
+    /// Record everything `other` did.
+    pub fn extend(&mut self, other: &Dependencies) {
+        self.globals.extend(other.globals.iter().cloned());
+        self.partials.extend(other.partials.iter().cloned());
+    }
+
+    /// Whether a change to the global at `path` may change the output.
+    ///
+    /// That is when a path read is within `path`, e.g. `product.title` for `product`, or
+    /// contains it, e.g. `products` for `products.42`.
+    pub fn depends_on_global(&self, path: &str) -> bool {
+        self.globals
+            .iter()
+            .any(|read| is_within(read, path) || is_within(path, read))
+    }
+
+    /// Whether a change to the partial `name` may change the output.
+    pub fn depends_on_partial(&self, name: &str) -> bool {
+        self.partials.contains(name)
+    }
+}
+
+/// Whether `path` is `parent` or one of its indexes.
+fn is_within(path: &str, parent: &str) -> bool {
+    match path.strip_prefix(parent) {
+        Some(rest) => rest.is_empty() || rest.starts_with('.'),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn depends_on_global() {
+        let mut dependencies = Dependencies::new();
+        dependencies.add_global("product.title");
+        dependencies.add_global("collections");
+        assert!(dependencies.depends_on_global("product"));
+        assert!(dependencies.depends_on_global("product.title"));
+        assert!(!dependencies.depends_on_global("product.price"));
+        assert!(!dependencies.depends_on_global("products"));
+        assert!(dependencies.depends_on_global("collections.42.title"));
+        assert!(!dependencies.depends_on_global("user"));
+    }
+}
@@ -0,0 +1,775 @@
+use std::borrow;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use liquid_error::{Error, Result};
+use liquid_value::{Date, NumericPolicy, Object, Value};
+
+pub use liquid_value::{IntegerDivision, OverflowPolicy};
+
+use super::ErrorPolicy;
+use super::Limits;
+use super::Redaction;
+
+/// How fractional numbers are rendered.
+///
+/// By default floats render with as many digits as it takes to be read back exactly, e.g.
+/// `0.30000000000000004` for `0.1 | plus: 0.2`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FloatFormat {
+    significant_digits: Option<usize>,
+    decimal_separator: char,
+    scientific_threshold: Option<u32>,
+}
+
+impl FloatFormat {
+    /// Create the default format.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Round to at most `digits` significant digits, e.g. `15` renders `0.1 + 0.2` as `0.3`.
+    pub fn with_significant_digits(mut self, digits: usize) -> Self {
+        self.significant_digits = Some(digits.max(1));
+        self
+    }
+
+    /// Set the decimal separator, e.g. `,` for many European locales.
+    pub fn with_decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Use scientific notation, e.g. `1.5e-7`, when the exponent is at least `exponent` in
+    /// magnitude.
+    pub fn with_scientific_threshold(mut self, exponent: u32) -> Self {
+        self.scientific_threshold = Some(exponent);
+        self
+    }
+
+    /// Render `value`.
+    pub fn format(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        let value = match self.significant_digits {
+            Some(digits) => format!("{:.*e}", digits - 1, value)
+                .parse()
+                .expect("formatted floats parse"),
+            None => value,
+        };
+
+        let exponent = if value == 0.0 {
+            0
+        } else {
+            value.abs().log10().floor() as i32
+        };
+        let scientific = self
+            .scientific_threshold
+            .is_some_and(|threshold| exponent.unsigned_abs() >= threshold);
+        let formatted = if scientific {
+            format!("{:e}", value)
+        } else {
+            value.to_string()
+        };
+
+        if self.decimal_separator == '.' {
+            formatted
+        } else {
+            formatted.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        Self {
+            significant_digits: None,
+            decimal_separator: '.',
+            scientific_threshold: None,
+        }
+    }
+}
+
+/// How `number_with_delimiter` and the `money` filters format numbers, see their `with_format`.
+///
+/// ```rust
+/// use liquid_interpreter::NumberFormat;
+///
+/// let format = NumberFormat::for_locale("de-DE").with_currency("EUR");
+/// assert_eq!(format.format(1234567.5, Some(2)), "1.234.567,50");
+/// assert_eq!(format.currency(), "EUR");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimal_separator: String,
+    thousands_separator: String,
+    currency: String,
+}
+
+impl NumberFormat {
+    /// Create the `en-US` format, with `USD` as the currency.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the usual separators of `locale`, an IETF language tag, with `USD` as the
+    /// currency.
+    ///
+    /// Unknown locales get the `en-US` separators.
+    pub fn for_locale(locale: &str) -> Self {
+        let mut subtags = locale.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+        let region = subtags.next();
+        let (thousands, decimal) = match (language.as_str(), region) {
+            ("de", Some("CH")) | ("it", Some("CH")) | ("fr", Some("CH")) => ("'", "."),
+            ("de", _)
+            | ("es", _)
+            | ("it", _)
+            | ("nl", _)
+            | ("pt", _)
+            | ("id", _)
+            | ("tr", _)
+            | ("da", _)
+            | ("el", _) => (".", ","),
+            ("fr", _)
+            | ("ru", _)
+            | ("sv", _)
+            | ("nb", _)
+            | ("fi", _)
+            | ("pl", _)
+            | ("cs", _)
+            | ("uk", _) => ("\u{a0}", ","),
+            _ => (",", "."),
+        };
+        Self::new()
+            .with_thousands_separator(thousands)
+            .with_decimal_separator(decimal)
+    }
+
+    /// Set the decimal separator, `.` by default.
+    pub fn with_decimal_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.decimal_separator = separator.into();
+        self
+    }
+
+    /// Set what separates groups of thousands, `,` by default.  Empty to not group them.
+    pub fn with_thousands_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.thousands_separator = separator.into();
+        self
+    }
+
+    /// Set the currency of `money` when none is given, as an ISO 4217 code like `EUR`.
+    pub fn with_currency<S: Into<String>>(mut self, currency: S) -> Self {
+        self.currency = currency.into();
+        self
+    }
+
+    /// The decimal separator.
+    pub fn decimal_separator(&self) -> &str {
+        &self.decimal_separator
+    }
+
+    /// What separates groups of thousands.
+    pub fn thousands_separator(&self) -> &str {
+        &self.thousands_separator
+    }
+
+    /// The currency of `money` when none is given.
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// Render `value` with `decimals` fractional digits, or as many as it takes to be read back
+    /// exactly.
+    pub fn format(&self, value: f64, decimals: Option<usize>) -> String {
+        let formatted = match decimals {
+            Some(decimals) => format!("{:.*}", decimals, value),
+            None => value.to_string(),
+        };
+        self.delimit(&formatted)
+    }
+
+    /// Regroup `number`, a whole or fractional number as written in Rust, e.g. `-1234.5`.
+    ///
+    /// Anything else is returned as is.
+    pub fn delimit(&self, number: &str) -> String {
+        let (sign, number) = match number.strip_prefix('-') {
+            Some(number) => ("-", number),
+            None => ("", number),
+        };
+        let (whole, fraction) = match number.find('.') {
+            Some(point) => (&number[..point], Some(&number[point + 1..])),
+            None => (number, None),
+        };
+        if !whole.chars().all(|c| c.is_ascii_digit()) {
+            return format!("{}{}", sign, number);
+        }
+
+        let mut result = String::with_capacity(number.len() + number.len() / 3);
+        result.push_str(sign);
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                result.push_str(&self.thousands_separator);
+            }
+            result.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            result.push_str(&self.decimal_separator);
+            result.push_str(fraction);
+        }
+        result
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: ".".to_owned(),
+            thousands_separator: ",".to_owned(),
+            currency: "USD".to_owned(),
+        }
+    }
+}
+
+/// How filters turn values into the type they need, e.g. the string `"5"` into a number.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Coercion {
+    /// Convert what can be converted, like Ruby Liquid: strings of numbers are numbers, numbers
+    /// are strings, and every value is true except `false` and `nil`.
+    #[default]
+    Lax,
+    /// Only accept values that already are of the type, e.g. `"5" | plus: 1` fails.
+    Strict,
+}
+
+/// How `NaN` and infinite numbers are rendered.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NonFinitePolicy {
+    /// Render as `NaN`, `inf` or `-inf`.
+    #[default]
+    Literal,
+    /// Render nothing.
+    Empty,
+    /// Fail the render.
+    Error,
+}
+
+/// How long a filter call may take, protecting renders from pathological filter input.
+///
+/// Time is checked once the filter returns, and whenever it spends iterations.  Filters whose
+/// work grows with their input, like `strip_html` or `sort`, spend an iteration per element or
+/// character, so they stop early once over budget.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use liquid_interpreter::FilterBudget;
+///
+/// let budget = FilterBudget::new()
+///     .with_time(Duration::from_millis(50))
+///     .with_filter("strip_html", FilterBudget::new().with_iterations(100_000));
+/// assert_eq!(budget.for_filter("strip_html").iterations(), Some(100_000));
+/// assert_eq!(budget.for_filter("upcase").time(), Some(Duration::from_millis(50)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilterBudget {
+    time: Option<Duration>,
+    iterations: Option<u64>,
+    filters: HashMap<String, FilterBudget>,
+}
+
+impl FilterBudget {
+    /// Create a budget without limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit how long each filter call may take.
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Limit how many iterations each filter call may spend.
+    pub fn with_iterations(mut self, iterations: u64) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    /// Give calls to the filter `name` a budget of their own, instead of this one.
+    pub fn with_filter<S: Into<String>>(mut self, name: S, budget: FilterBudget) -> Self {
+        self.filters.insert(name.into(), budget);
+        self
+    }
+
+    /// How long each filter call may take.
+    pub fn time(&self) -> Option<Duration> {
+        self.time
+    }
+
+    /// How many iterations each filter call may spend.
+    pub fn iterations(&self) -> Option<u64> {
+        self.iterations
+    }
+
+    /// The budget calls to the filter `name` get.
+    pub fn for_filter(&self, name: &str) -> &FilterBudget {
+        self.filters.get(name).unwrap_or(self)
+    }
+
+    /// Whether no filter is limited.
+    pub fn is_unlimited(&self) -> bool {
+        self.time.is_none()
+            && self.iterations.is_none()
+            && self.filters.values().all(FilterBudget::is_unlimited)
+    }
+}
+
+/// Locale, timezone, clock, and policies a template is rendered with.
+///
+/// The locale, timezone, and clock are available to templates through the `liquid` variable:
+/// - `liquid.locale`, e.g. `en-US`
+/// - `liquid.timezone`, e.g. `+02:00`
+/// - `liquid.now`, the time rendering started
+///
+/// A `liquid` global provided by the host takes precedence.
+///
+/// Settings of a single filter, like the site `url` of `absolute_url`, are configured on the
+/// filter when registering it instead.
+///
+/// Renders share the environment of their template rather than copying it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Environment {
+    locale: String,
+    timezone: chrono::FixedOffset,
+    now: Option<Date>,
+    // The `liquid` variable, less `now` unless it is pinned.
+    liquid: Object,
+    float_format: FloatFormat,
+    numeric_policy: NumericPolicy,
+    coercion: Coercion,
+    non_finite_policy: NonFinitePolicy,
+    filter_budget: FilterBudget,
+    limits: Limits,
+    partial_depth: usize,
+    redaction: Redaction,
+    error_policy: ErrorPolicy,
+    strict_variables: bool,
+    auto_escape: bool,
+}
+
+impl Environment {
+    /// Create an `en-US`, UTC environment using the system clock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the locale, as an IETF language tag.
+    pub fn with_locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.locale = locale.into();
+        self.with_liquid()
+    }
+
+    /// Set the timezone dates are presented in.
+    pub fn with_timezone(mut self, timezone: chrono::FixedOffset) -> Self {
+        self.timezone = timezone;
+        self.with_liquid()
+    }
+
+    /// Pin "now" to a specific time, e.g. for reproducible output.
+    pub fn with_now(mut self, now: Date) -> Self {
+        self.now = Some(now);
+        self.with_liquid()
+    }
+
+    /// Set how fractional numbers are rendered.
+    pub fn with_float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Set how arithmetic filters, like `plus`, and arithmetic expressions compute whole
+    /// numbers.
+    pub fn with_numeric_policy(mut self, policy: NumericPolicy) -> Self {
+        self.numeric_policy = policy;
+        self
+    }
+
+    /// Set what arithmetic does when a whole number result is out of range.
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.numeric_policy = self.numeric_policy.with_overflow(policy);
+        self
+    }
+
+    /// Set how `divided_by` divides a whole number by a whole number.
+    pub fn with_integer_division(mut self, division: IntegerDivision) -> Self {
+        self.numeric_policy = self.numeric_policy.with_integer_division(division);
+        self
+    }
+
+    /// Set how filters turn values into the type they need.
+    pub fn with_coercion(mut self, coercion: Coercion) -> Self {
+        self.coercion = coercion;
+        self
+    }
+
+    /// Set how `NaN` and infinite numbers are rendered.
+    pub fn with_non_finite_policy(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite_policy = policy;
+        self
+    }
+
+    /// Set how long filter calls may take.
+    pub fn with_filter_budget(mut self, budget: FilterBudget) -> Self {
+        self.filter_budget = budget;
+        self
+    }
+
+    /// Set how much a render may do, e.g. how large its output may be.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set how deep partials may include or render other partials, 32 by default.
+    ///
+    /// This stops partials that include themselves, directly or not, from rendering forever.
+    pub fn with_partial_depth(mut self, depth: usize) -> Self {
+        self.partial_depth = depth;
+        self
+    }
+
+    /// Set the global paths whose values may not be output.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Set what a render does when an element of the template fails, e.g. render a placeholder
+    /// and carry on.
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Fail to render variables that don't exist, e.g. a misspelled `{{ prodcut.title }}`,
+    /// instead of them being `nil`.
+    ///
+    /// Indexes that don't exist, like `{{ product.titel }}`, fail either way.  Filters that
+    /// don't exist fail when parsing.
+    pub fn with_strict_variables(mut self, strict: bool) -> Self {
+        self.strict_variables = strict;
+        self
+    }
+
+    /// Escape the values output with `{{ }}` for HTML, unless they are strings marked safe, see
+    /// `Scalar::safe_str`.
+    ///
+    /// The `safe` filter marks its input safe, and filters that make HTML, like `escape` and
+    /// `markdownify`, mark their output safe.  What `capture` captures is safe, its values
+    /// having been escaped already.
+    pub fn with_auto_escape(mut self, auto_escape: bool) -> Self {
+        self.auto_escape = auto_escape;
+        self
+    }
+
+    /// The locale, as an IETF language tag.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// The timezone dates are presented in.
+    pub fn timezone(&self) -> chrono::FixedOffset {
+        self.timezone
+    }
+
+    /// The current time, in `timezone`.
+    ///
+    /// Within a render, use `Context::now`, which is fixed to when rendering started.
+    pub fn now(&self) -> Date {
+        match self.now {
+            Some(now) => now.with_timezone(&self.timezone),
+            None => chrono::Utc::now().with_timezone(&self.timezone),
+        }
+    }
+
+    /// How fractional numbers are rendered.
+    pub fn float_format(&self) -> &FloatFormat {
+        &self.float_format
+    }
+
+    /// How arithmetic filters and arithmetic expressions compute whole numbers.
+    pub fn numeric_policy(&self) -> NumericPolicy {
+        self.numeric_policy
+    }
+
+    /// What arithmetic does when a whole number result is out of range.
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.numeric_policy.overflow()
+    }
+
+    /// How `divided_by` divides a whole number by a whole number.
+    pub fn integer_division(&self) -> IntegerDivision {
+        self.numeric_policy.integer_division()
+    }
+
+    /// How filters turn values into the type they need.
+    pub fn coercion(&self) -> Coercion {
+        self.coercion
+    }
+
+    /// How `NaN` and infinite numbers are rendered.
+    pub fn non_finite_policy(&self) -> NonFinitePolicy {
+        self.non_finite_policy
+    }
+
+    /// How long filter calls may take.
+    pub fn filter_budget(&self) -> &FilterBudget {
+        &self.filter_budget
+    }
+
+    /// How much a render may do.
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// How deep partials may include or render other partials.
+    pub fn partial_depth(&self) -> usize {
+        self.partial_depth
+    }
+
+    /// The global paths whose values may not be output.
+    pub fn redaction(&self) -> &Redaction {
+        &self.redaction
+    }
+
+    /// What a render does when an element of the template fails.
+    pub fn error_policy(&self) -> &ErrorPolicy {
+        &self.error_policy
+    }
+
+    /// Whether rendering variables that don't exist fails.
+    pub fn strict_variables(&self) -> bool {
+        self.strict_variables
+    }
+
+    /// Whether the values output with `{{ }}` are escaped for HTML unless marked safe.
+    pub fn auto_escape(&self) -> bool {
+        self.auto_escape
+    }
+
+    /// Render `value` for `{{ }}` like `render_value`, escaping it for HTML when auto-escaping
+    /// unless it is marked safe.
+    ///
+    /// Returns the text with whether it is safe to output as it is, see `Output::write_value`.
+    pub fn render_output<'v>(&self, value: &'v Value) -> Result<(borrow::Cow<'v, str>, bool)> {
+        let rendered = self.render_value(value)?;
+        if value.as_scalar().is_some_and(|s| s.is_safe()) {
+            return Ok((rendered, true));
+        }
+        if !self.auto_escape {
+            return Ok((rendered, false));
+        }
+        Ok((escape_html(rendered), true))
+    }
+
+    /// Render `value` for output, applying the `float_format` and `non_finite_policy`.
+    pub fn render_value<'v>(&self, value: &'v Value) -> Result<borrow::Cow<'v, str>> {
+        let x = match value.as_scalar().and_then(|s| s.as_float()) {
+            Some(x) => x,
+            None => return Ok(value.to_str()),
+        };
+        if x.is_finite() {
+            return Ok(borrow::Cow::Owned(self.float_format.format(x)));
+        }
+        match self.non_finite_policy {
+            NonFinitePolicy::Literal => Ok(borrow::Cow::Owned(self.float_format.format(x))),
+            NonFinitePolicy::Empty => Ok(borrow::Cow::Borrowed("")),
+            NonFinitePolicy::Error => Err(Error::with_msg("Cannot render a non-finite number")
+                .context("value", x.to_string())),
+        }
+    }
+
+    /// The `liquid` variable of a render started at `now`.
+    pub(crate) fn liquid(&self, now: Date) -> Value {
+        if self.now.is_some() {
+            return Value::Object(self.liquid.clone());
+        }
+        let mut liquid = self.liquid.clone();
+        liquid.insert("now".into(), Value::scalar(now));
+        Value::Object(liquid)
+    }
+
+    fn with_liquid(mut self) -> Self {
+        let mut liquid = Object::new();
+        liquid.insert("locale".into(), Value::scalar(self.locale.clone()));
+        liquid.insert("timezone".into(), Value::scalar(self.timezone.to_string()));
+        if self.now.is_some() {
+            liquid.insert("now".into(), Value::scalar(self.now()));
+        }
+        self.liquid = liquid;
+        self
+    }
+}
+
+/// Escapes the characters of `text` that are special in HTML.
+fn escape_html(text: borrow::Cow<'_, str>) -> borrow::Cow<'_, str> {
+    if !text.contains(['<', '>', '&', '"', '\'']) {
+        return text;
+    }
+    let mut escaped = String::with_capacity(text.len() + text.len() / 8);
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    borrow::Cow::Owned(escaped)
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            locale: "en-US".to_owned(),
+            timezone: chrono::FixedOffset::east_opt(0).unwrap(),
+            now: None,
+            liquid: Object::new(),
+            float_format: FloatFormat::default(),
+            numeric_policy: NumericPolicy::default(),
+            coercion: Coercion::default(),
+            non_finite_policy: NonFinitePolicy::default(),
+            filter_budget: FilterBudget::default(),
+            limits: Limits::default(),
+            partial_depth: 32,
+            redaction: Redaction::default(),
+            error_policy: ErrorPolicy::default(),
+            strict_variables: false,
+            auto_escape: false,
+        }
+        .with_liquid()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn now_uses_timezone() {
+        let now =
+            Date::parse_from_str("2019-01-01 12:00:00 +0000", "%Y-%m-%d %H:%M:%S %z").unwrap();
+        let env = Environment::new()
+            .with_timezone(chrono::FixedOffset::east_opt(2 * 3600).unwrap())
+            .with_now(now);
+        assert_eq!(env.now(), now);
+        assert_eq!(env.now().to_string(), "2019-01-01 14:00:00 +02:00");
+    }
+
+    #[test]
+    fn float_format() {
+        assert_eq!(FloatFormat::new().format(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(FloatFormat::new().format(2.0), "2");
+
+        let rounded = FloatFormat::new().with_significant_digits(15);
+        assert_eq!(rounded.format(0.1 + 0.2), "0.3");
+        assert_eq!(rounded.format(-1234.5), "-1234.5");
+        assert_eq!(
+            FloatFormat::new()
+                .with_significant_digits(3)
+                .format(3.14159),
+            "3.14"
+        );
+
+        let comma = FloatFormat::new().with_decimal_separator(',');
+        assert_eq!(comma.format(2.5), "2,5");
+
+        let scientific = FloatFormat::new().with_scientific_threshold(6);
+        assert_eq!(scientific.format(1.5e-7), "1.5e-7");
+        assert_eq!(scientific.format(2.5e10), "2.5e10");
+        assert_eq!(scientific.format(12345.5), "12345.5");
+        assert_eq!(scientific.format(0.0), "0");
+    }
+
+    #[test]
+    fn number_format() {
+        let format = NumberFormat::new();
+        assert_eq!(format.format(1234567.0, None), "1,234,567");
+        assert_eq!(format.format(-1234567.891, None), "-1,234,567.891");
+        assert_eq!(format.format(999.5, Some(2)), "999.50");
+        assert_eq!(format.format(f64::NAN, None), "NaN");
+        assert_eq!(format.delimit("12345"), "12,345");
+        assert_eq!(format.delimit("abc"), "abc");
+
+        let swiss = NumberFormat::for_locale("de-CH");
+        assert_eq!(swiss.format(1234.5, Some(2)), "1'234.50");
+        let french = NumberFormat::for_locale("fr_FR");
+        assert_eq!(french.format(1234.5, Some(2)), "1\u{a0}234,50");
+        let ungrouped = NumberFormat::new().with_thousands_separator("");
+        assert_eq!(ungrouped.format(1234.5, Some(1)), "1234.5");
+    }
+
+    #[test]
+    fn render_value() {
+        let env =
+            Environment::new().with_float_format(FloatFormat::new().with_decimal_separator(','));
+        assert_eq!(env.render_value(&Value::scalar(1.5)).unwrap(), "1,5");
+        assert_eq!(env.render_value(&Value::scalar("1.5")).unwrap(), "1.5");
+        assert_eq!(env.render_value(&Value::scalar(15)).unwrap(), "15");
+    }
+
+    #[test]
+    fn render_non_finite() {
+        let nan = Value::scalar(f64::NAN);
+        let inf = Value::scalar(f64::NEG_INFINITY);
+        let env = Environment::new();
+        assert_eq!(env.render_value(&nan).unwrap(), "NaN");
+        assert_eq!(env.render_value(&inf).unwrap(), "-inf");
+
+        let env = Environment::new().with_non_finite_policy(NonFinitePolicy::Empty);
+        assert_eq!(env.render_value(&nan).unwrap(), "");
+        assert_eq!(env.render_value(&Value::scalar(1.5)).unwrap(), "1.5");
+
+        let env = Environment::new().with_non_finite_policy(NonFinitePolicy::Error);
+        assert!(env.render_value(&inf).is_err());
+        assert!(env.render_value(&Value::scalar(1.5)).is_ok());
+    }
+
+    #[test]
+    fn render_output() {
+        let html = Value::scalar("<b>Tom & \"Jerry's\"</b>");
+        let safe = Value::Scalar(liquid_value::Scalar::safe_str("<b>safe</b>"));
+        let env = Environment::new();
+        assert_eq!(env.render_output(&html).unwrap(), (html.to_str(), false));
+        assert_eq!(env.render_output(&safe).unwrap(), (safe.to_str(), true));
+
+        let env = Environment::new().with_auto_escape(true);
+        let escaped = "&lt;b&gt;Tom &amp; &quot;Jerry&#39;s&quot;&lt;/b&gt;".into();
+        assert_eq!(env.render_output(&html).unwrap(), (escaped, true));
+        assert_eq!(env.render_output(&safe).unwrap(), (safe.to_str(), true));
+        assert_eq!(
+            env.render_output(&Value::scalar(1)).unwrap(),
+            ("1".into(), true)
+        );
+    }
+
+    #[test]
+    fn liquid() {
+        let now =
+            Date::parse_from_str("2019-01-01 12:00:00 +0000", "%Y-%m-%d %H:%M:%S %z").unwrap();
+        let later =
+            Date::parse_from_str("2019-01-02 12:00:00 +0000", "%Y-%m-%d %H:%M:%S %z").unwrap();
+        let env = Environment::new().with_locale("nl-NL");
+        let liquid = env.liquid(later);
+        let liquid = liquid.as_object().unwrap();
+        assert_eq!(liquid["locale"], Value::scalar("nl-NL"));
+        assert_eq!(liquid["now"], Value::scalar(later));
+
+        let pinned = env.with_now(now).liquid(later);
+        assert_eq!(pinned.as_object().unwrap()["now"], Value::scalar(now));
+    }
+}
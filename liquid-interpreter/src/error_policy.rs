@@ -0,0 +1,102 @@
+use std::fmt;
+use std::sync::Arc;
+
+use liquid_error::{Error, ErrorKind};
+
+/// Renders what replaces an element of a template that failed to render.
+pub type ErrorHandler = Arc<dyn Fn(&Error) -> String + Send + Sync>;
+
+/// What a render does when an element of the template fails, e.g. an output whose filter was
+/// given the wrong kind of argument.
+///
+/// By default the render fails.  Otherwise the element is replaced, e.g. by an HTML comment,
+/// and the render carries on, keeping the error in `Context::suppressed_errors`:
+///
+/// ```rust
+/// use liquid_error::Error;
+/// use liquid_interpreter::ErrorPolicy;
+///
+/// let policy = ErrorPolicy::placeholder(|error| format!("<!-- {} -->", error.message()));
+/// let error = Error::with_msg("Invalid argument");
+/// assert_eq!(
+///     policy.handle(&error),
+///     Some("<!-- Invalid argument -->".to_owned())
+/// );
+/// assert_eq!(ErrorPolicy::fail().handle(&error), None);
+/// ```
+///
+/// Going over the environment's `Limits` always fails the render.
+#[derive(Clone, Default)]
+pub struct ErrorPolicy {
+    handler: Option<ErrorHandler>,
+}
+
+impl ErrorPolicy {
+    /// Fail the render.
+    pub fn fail() -> Self {
+        Self::default()
+    }
+
+    /// Render nothing in place of the failed element.
+    pub fn skip() -> Self {
+        Self::placeholder(|_| String::new())
+    }
+
+    /// Render what `handler` returns for the error in place of the failed element.
+    pub fn placeholder<F>(handler: F) -> Self
+    where
+        F: Fn(&Error) -> String + Send + Sync + 'static,
+    {
+        Self {
+            handler: Some(Arc::new(handler)),
+        }
+    }
+
+    /// Whether errors fail the render.
+    pub fn is_fail(&self) -> bool {
+        self.handler.is_none()
+    }
+
+    /// What replaces the element that failed with `error`, or `None` when the render fails.
+    pub fn handle(&self, error: &Error) -> Option<String> {
+        if error.kind() == ErrorKind::LimitExceeded {
+            return None;
+        }
+        self.handler.as_ref().map(|handler| handler(error))
+    }
+}
+
+impl fmt::Debug for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorPolicy")
+            .field("handler", &self.handler.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl PartialEq for ErrorPolicy {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.handler, &other.handler) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ErrorPolicy {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn limits_fail() {
+        let error = Error::with_msg("Render limit exceeded").with_kind(ErrorKind::LimitExceeded);
+        assert_eq!(ErrorPolicy::skip().handle(&error), None);
+        assert_eq!(
+            ErrorPolicy::skip().handle(&Error::with_msg("Oops")),
+            Some(String::new())
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt;
 
-use liquid_error::Result;
+use liquid_error::{Error, Result};
+use liquid_value::Range;
 use liquid_value::Scalar;
 use liquid_value::Value;
 
+use super::ArithmeticOperator;
 use super::Context;
 use crate::variable::Variable;
 
@@ -14,6 +16,12 @@ pub enum Expression {
     Variable(Variable),
     /// Evaluated.
     Literal(Value),
+    /// The whole numbers from a start to an end, like `(1..5)`.
+    Range(Box<Expression>, Box<Expression>),
+    /// The values of expressions, like `["a", b]`.
+    Array(Vec<Expression>),
+    /// Arithmetic on two values, like `width * 2`.
+    Arithmetic(Box<Expression>, ArithmeticOperator, Box<Expression>),
 }
 
 impl Expression {
@@ -26,14 +34,20 @@ impl Expression {
     pub fn into_literal(self) -> Option<Value> {
         match self {
             Expression::Literal(x) => Some(x),
-            Expression::Variable(_) => None,
+            Expression::Variable(_)
+            | Expression::Range(..)
+            | Expression::Array(_)
+            | Expression::Arithmetic(..) => None,
         }
     }
 
     /// Convert into a variable, if possible.
     pub fn into_variable(self) -> Option<Variable> {
         match self {
-            Expression::Literal(_) => None,
+            Expression::Literal(_)
+            | Expression::Range(..)
+            | Expression::Array(_)
+            | Expression::Arithmetic(..) => None,
             Expression::Variable(x) => Some(x),
         }
     }
@@ -46,6 +60,24 @@ impl Expression {
                 let path = x.try_evaluate(context)?;
                 context.stack().try_get(&path)?
             }
+            Expression::Range(ref start, ref end) => {
+                let start = start.try_evaluate(context)?.as_scalar()?.to_integer()?;
+                let end = end.try_evaluate(context)?.as_scalar()?.to_integer()?;
+                context.stack().keep(Value::Range(Range::new(start, end)))
+            }
+            Expression::Array(ref items) => {
+                let items: Option<Vec<_>> = items
+                    .iter()
+                    .map(|item| item.try_evaluate(context).cloned())
+                    .collect();
+                context.stack().keep(Value::array(items?))
+            }
+            Expression::Arithmetic(ref lhs, operator, ref rhs) => {
+                let lhs = lhs.try_evaluate(context)?;
+                let rhs = rhs.try_evaluate(context)?;
+                let value = operator.apply(lhs, rhs, context).ok()?;
+                context.stack().keep(value)
+            }
         };
         Some(val)
     }
@@ -58,16 +90,70 @@ impl Expression {
                 let path = x.evaluate(context)?;
                 context.stack().get(&path)?
             }
+            Expression::Range(ref start, ref end) => {
+                let start = range_bound(start, context, "start")?;
+                let end = range_bound(end, context, "end")?;
+                context.stack().keep(Value::Range(Range::new(start, end)))
+            }
+            Expression::Array(ref items) => {
+                let items: Result<Vec<_>> = items
+                    .iter()
+                    .map(|item| item.evaluate(context).cloned())
+                    .collect();
+                context.stack().keep(Value::array(items?))
+            }
+            Expression::Arithmetic(ref lhs, operator, ref rhs) => {
+                let lhs = lhs.evaluate(context)?;
+                let rhs = rhs.evaluate(context)?;
+                let value = operator.apply(lhs, rhs, context)?;
+                context.stack().keep(value)
+            }
         };
         Ok(val)
     }
 }
 
+fn range_bound(bound: &Expression, context: &Context<'_>, name: &'static str) -> Result<i32> {
+    let value = bound.evaluate(context)?;
+    value
+        .as_scalar()
+        .and_then(Scalar::to_integer)
+        .ok_or_else(|| {
+            Error::with_msg("Whole number expected")
+                .context("range", name)
+                .context("value", value.source().to_string())
+        })
+}
+
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Expression::Literal(ref x) => write!(f, "{}", x.source()),
             Expression::Variable(ref x) => write!(f, "{}", x),
+            Expression::Range(ref start, ref end) => write!(f, "({}..{})", start, end),
+            Expression::Array(ref items) => write!(f, "[{}]", itertools::join(items, ", ")),
+            Expression::Arithmetic(ref lhs, operator, ref rhs) => {
+                // Operands binding looser than the operator are grouped, as are those on the
+                // right binding just as tightly, e.g. `a - (b - c)`.
+                let groups = |operand: &Expression, right: bool| match *operand {
+                    Expression::Arithmetic(_, inner, _) => {
+                        inner.precedence() < operator.precedence()
+                            || (right && inner.precedence() == operator.precedence())
+                    }
+                    _ => false,
+                };
+                write_operand(f, lhs, groups(lhs, false))?;
+                write!(f, " {} ", operator)?;
+                write_operand(f, rhs, groups(rhs, true))
+            }
         }
     }
 }
+
+fn write_operand(f: &mut fmt::Formatter<'_>, operand: &Expression, group: bool) -> fmt::Result {
+    if group {
+        write!(f, "({})", operand)
+    } else {
+        write!(f, "{}", operand)
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::pin::Pin;
+
+use liquid_error::Result;
+use liquid_value::{Object, Value};
+
+use super::Usage;
+use super::ValueStore;
+
+/// A boxed future that can be awaited from another thread, as `AsyncGlobalsProvider` returns.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Computes globals by name without blocking, e.g. by querying a database, see
+/// `render_fetching`.
+///
+/// This is the asynchronous counterpart of `GlobalsProvider`.  A global that is itself an object
+/// with asynchronously computed properties, like a drop, awaits the properties it has when it is
+/// computed.
+pub trait AsyncGlobalsProvider: Sync {
+    /// The names of the globals, without computing them.
+    fn names(&self) -> Vec<&str>;
+
+    /// Compute the global `name`, `None` when there is no such global.
+    fn get<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Value>>>;
+}
+
+/// Globals fetched from an `AsyncGlobalsProvider` before rendering a template reads them.
+///
+/// Each global is fetched at most once.
+pub struct FetchedGlobals<'p> {
+    provider: &'p dyn AsyncGlobalsProvider,
+    names: BTreeSet<String>,
+    values: Object,
+}
+
+impl<'p> FetchedGlobals<'p> {
+    /// Fetch globals from `provider`.
+    pub fn new(provider: &'p dyn AsyncGlobalsProvider) -> Self {
+        let names = provider.names().into_iter().map(str::to_owned).collect();
+        Self {
+            provider,
+            names,
+            values: Object::new(),
+        }
+    }
+
+    /// Fetch the globals among `names` that haven't been yet, one after the other.
+    ///
+    /// Returns whether any global was fetched.  Names the provider doesn't have are ignored.
+    pub async fn fetch<I, S>(&mut self, names: I) -> Result<bool>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut fetched = false;
+        for name in names {
+            let name = match self.names.take(name.as_ref()) {
+                Some(name) => name,
+                None => continue,
+            };
+            if let Some(value) = self.provider.get(&name).await? {
+                self.values.insert(name.into(), value);
+            }
+            fetched = true;
+        }
+        Ok(fetched)
+    }
+
+    /// The globals fetched so far.
+    pub fn values(&self) -> &Object {
+        &self.values
+    }
+}
+
+/// Render with the globals of `provider` fetched ahead of time, so the render itself doesn't
+/// wait on them.
+///
+/// The globals `names` are fetched first, usually the roots of the variables the template and its
+/// partials refer to.  `render` then renders with the globals fetched, recording its usage.
+/// Globals it read without them being fetched, like those of partials whose names are computed,
+/// are fetched and it renders again, until it reads none.
+pub async fn render_fetching<I, S, F>(
+    provider: &dyn AsyncGlobalsProvider,
+    names: I,
+    mut render: F,
+) -> Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+    F: FnMut(&dyn ValueStore) -> (Result<String>, Usage),
+{
+    let mut globals = FetchedGlobals::new(provider);
+    globals.fetch(names).await?;
+    loop {
+        let (result, usage) = render(globals.values());
+        let undefined: Vec<_> = usage.undefined().map(root_name).collect();
+        if !globals.fetch(undefined).await? {
+            return result;
+        }
+    }
+}
+
+/// The name of the global at the root of the variable `path`, e.g. `user` for `user.name`.
+fn root_name(path: &str) -> &str {
+    path.split('.').next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+
+    /// Wakes nothing, `block_on` polling regardless.
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Runs `future` on this thread, polling it until it is ready.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(Noop));
+        let mut context = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    /// A future ready the second time it is polled.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[derive(Default)]
+    struct Provider {
+        fetched: Mutex<Vec<String>>,
+    }
+
+    impl AsyncGlobalsProvider for Provider {
+        fn names(&self) -> Vec<&str> {
+            vec!["user", "cart", "missing"]
+        }
+
+        fn get<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Value>>> {
+            Box::pin(async move {
+                YieldOnce(false).await;
+                self.fetched.lock().unwrap().push(name.to_owned());
+                match name {
+                    "user" => Ok(Some(Value::scalar("Ada"))),
+                    "cart" => Ok(Some(Value::array(vec![]))),
+                    _ => Ok(None),
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn globals_are_fetched_before_rendering() {
+        let provider = Provider::default();
+        let mut renders = 0;
+        let output = block_on(render_fetching(
+            &provider,
+            vec!["user", "other"],
+            |globals| {
+                renders += 1;
+                let mut usage = Usage::new();
+                if !globals.contains_root("cart") {
+                    usage.add_undefined("cart.size");
+                }
+                if !globals.contains_root("missing") {
+                    usage.add_undefined("missing");
+                }
+                let mut roots = globals.roots();
+                roots.sort();
+                let output = roots.join(",");
+                (Ok(output), usage)
+            },
+        ))
+        .unwrap();
+        assert_eq!(output, "cart,user");
+        assert_eq!(renders, 2);
+        assert_eq!(
+            *provider.fetched.lock().unwrap(),
+            vec!["user", "cart", "missing"]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use itertools;
+use liquid_error::{Error, Result};
+use liquid_value::{PathRef, ScalarCow, Value};
+
+use super::ValueStore;
+
+/// Computes globals by name, see `LazyGlobals`.
+pub trait GlobalsProvider {
+    /// The names of the globals, without computing them.
+    fn names(&self) -> Vec<&str>;
+
+    /// Compute the global `name`, `None` when there is no such global.
+    fn get(&self, name: &str) -> Option<Value>;
+}
+
+/// Globals computed by a `GlobalsProvider`, only once a template reads them.
+///
+/// Each global is computed at most once, however often it is read.
+///
+/// ```rust
+/// use liquid_interpreter::{GlobalsProvider, LazyGlobals, ValueStore};
+/// use liquid_value::{Scalar, Value};
+///
+/// struct Site;
+///
+/// impl GlobalsProvider for Site {
+///     fn names(&self) -> Vec<&str> {
+///         vec!["title", "pages"]
+///     }
+///
+///     fn get(&self, name: &str) -> Option<Value> {
+///         match name {
+///             "title" => Some(Value::scalar("Blog")),
+///             "pages" => unimplemented!("expensive"),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let site = Site;
+/// let globals = LazyGlobals::new(&site);
+/// let title = [Scalar::new("title")];
+/// assert_eq!(globals.get_variable(&title).unwrap(), &Value::scalar("Blog"));
+/// ```
+pub struct LazyGlobals<'p> {
+    provider: &'p dyn GlobalsProvider,
+    values: BTreeMap<String, OnceCell<Option<Value>>>,
+}
+
+impl<'p> LazyGlobals<'p> {
+    /// Read the globals of `provider`.
+    pub fn new(provider: &'p dyn GlobalsProvider) -> Self {
+        let values = provider
+            .names()
+            .into_iter()
+            .map(|name| (name.to_owned(), OnceCell::new()))
+            .collect();
+        Self { provider, values }
+    }
+
+    /// The global `name`, computed on the first read.
+    fn root(&self, name: &str) -> Option<&Value> {
+        let (name, value) = self.values.get_key_value(name)?;
+        value.get_or_init(|| self.provider.get(name)).as_ref()
+    }
+}
+
+impl<'p> fmt::Debug for LazyGlobals<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyGlobals")
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl<'p> ValueStore for LazyGlobals<'p> {
+    fn contains_root(&self, name: &str) -> bool {
+        self.root(name).is_some()
+    }
+
+    fn roots(&self) -> Vec<&str> {
+        self.values.keys().map(String::as_str).collect()
+    }
+
+    fn contains_variable(&self, path: PathRef<'_, '_>) -> bool {
+        self.try_get_variable(path).is_some()
+    }
+
+    fn try_get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Option<&'a Value> {
+        let (root, indexes) = path.split_first()?;
+        let root = self.root(root.to_str().as_ref())?;
+        indexes
+            .iter()
+            .try_fold(root, |value, index| value.get(index))
+    }
+
+    fn get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Result<&'a Value> {
+        let (root, indexes) = match path.split_first() {
+            Some(split) => split,
+            None => return Ok(&Value::Nil),
+        };
+        let mut value = match self.root(root.to_str().as_ref()) {
+            Some(value) => value,
+            None => return Ok(&Value::Nil),
+        };
+        for (position, index) in indexes.iter().enumerate() {
+            value = match value.get(index) {
+                Some(value) => value,
+                None => {
+                    let variable =
+                        itertools::join(path[..=position].iter().map(ScalarCow::render), ".");
+                    let available = itertools::join(value.keys().map(|k| k.into_string()), ", ");
+                    return Error::with_msg("Unknown index")
+                        .context("variable", variable)
+                        .context("requested index", format!("{}", index.render()))
+                        .context("available indexes", available)
+                        .into_err();
+                }
+            };
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cell::RefCell;
+
+    use liquid_value::{Object, Scalar};
+
+    #[derive(Default)]
+    struct Provider {
+        computed: RefCell<Vec<String>>,
+    }
+
+    impl GlobalsProvider for Provider {
+        fn names(&self) -> Vec<&str> {
+            vec!["user", "cart", "missing"]
+        }
+
+        fn get(&self, name: &str) -> Option<Value> {
+            self.computed.borrow_mut().push(name.to_owned());
+            match name {
+                "user" => {
+                    let mut user = Object::new();
+                    user.insert("name".into(), Value::scalar("Ada"));
+                    Some(Value::Object(user))
+                }
+                "cart" => Some(Value::array(vec![])),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn globals_are_computed_once_when_read() {
+        let provider = Provider::default();
+        let globals = LazyGlobals::new(&provider);
+        assert_eq!(globals.roots(), vec!["cart", "missing", "user"]);
+        assert!(provider.computed.borrow().is_empty());
+
+        let name = [Scalar::new("user"), Scalar::new("name")];
+        assert_eq!(globals.get_variable(&name).unwrap(), &Value::scalar("Ada"));
+        assert_eq!(globals.get_variable(&name).unwrap(), &Value::scalar("Ada"));
+        assert_eq!(*provider.computed.borrow(), vec!["user"]);
+
+        assert!(!globals.contains_root("missing"));
+        assert!(!globals.contains_root("other"));
+        let email = [Scalar::new("user"), Scalar::new("email")];
+        let error = globals.get_variable(&email).unwrap_err().to_string();
+        assert!(error.contains("available indexes=name"));
+        assert_eq!(*provider.computed.borrow(), vec!["user", "missing"]);
+    }
+}
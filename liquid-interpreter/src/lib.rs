@@ -3,20 +3,46 @@
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 
+mod arithmetic;
 mod context;
+mod dependencies;
+mod environment;
+mod error_policy;
 mod expression;
+#[cfg(feature = "async")]
+mod fetched;
+mod lazy;
+mod limits;
+mod output;
 mod partials;
+mod redaction;
+mod references;
 mod renderable;
+mod source;
 mod stack;
 mod store;
 mod template;
+mod usage;
 mod variable;
 
+pub use self::arithmetic::*;
 pub use self::context::*;
+pub use self::dependencies::*;
+pub use self::environment::*;
+pub use self::error_policy::*;
 pub use self::expression::*;
+#[cfg(feature = "async")]
+pub use self::fetched::*;
+pub use self::lazy::*;
+pub use self::limits::*;
+pub use self::output::*;
 pub use self::partials::*;
+pub use self::redaction::*;
+pub use self::references::*;
 pub use self::renderable::*;
+pub use self::source::*;
 pub use self::stack::*;
 pub use self::store::*;
 pub use self::template::*;
+pub use self::usage::*;
 pub use self::variable::*;
@@ -0,0 +1,180 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use liquid_error::{Error, ErrorKind, Result};
+use liquid_value::Value;
+
+use super::Output;
+
+/// How much a render may do, e.g. to render templates from untrusted users.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use liquid_interpreter::Limits;
+///
+/// let limits = Limits::new()
+///     .with_output_bytes(1 << 20)
+///     .with_loop_iterations(10_000)
+///     .with_render_time(Duration::from_millis(100));
+/// assert_eq!(limits.loop_iterations(), Some(10_000));
+/// assert_eq!(limits.assigned_bytes(), None);
+/// ```
+///
+/// A render going over a limit fails with an error of kind `ErrorKind::LimitExceeded`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    output_bytes: Option<usize>,
+    loop_iterations: Option<u64>,
+    assigned_bytes: Option<usize>,
+    render_time: Option<Duration>,
+}
+
+impl Limits {
+    /// Create limits that don't limit anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the size of the output.
+    pub fn with_output_bytes(mut self, bytes: usize) -> Self {
+        self.output_bytes = Some(bytes);
+        self
+    }
+
+    /// Limit the iterations of all the `for` and `tablerow` loops of a render together.
+    pub fn with_loop_iterations(mut self, iterations: u64) -> Self {
+        self.loop_iterations = Some(iterations);
+        self
+    }
+
+    /// Limit the size of all the values set with `assign` and `capture`, roughly in bytes.
+    pub fn with_assigned_bytes(mut self, bytes: usize) -> Self {
+        self.assigned_bytes = Some(bytes);
+        self
+    }
+
+    /// Limit how long a render may take.
+    pub fn with_render_time(mut self, time: Duration) -> Self {
+        self.render_time = Some(time);
+        self
+    }
+
+    /// The size the output may have.
+    pub fn output_bytes(&self) -> Option<usize> {
+        self.output_bytes
+    }
+
+    /// How many loop iterations a render may run.
+    pub fn loop_iterations(&self) -> Option<u64> {
+        self.loop_iterations
+    }
+
+    /// The size the values set with `assign` and `capture` may have together.
+    pub fn assigned_bytes(&self) -> Option<usize> {
+        self.assigned_bytes
+    }
+
+    /// How long a render may take.
+    pub fn render_time(&self) -> Option<Duration> {
+        self.render_time
+    }
+}
+
+/// The error of going over the `limit` of a render, e.g. `output bytes`.
+pub(crate) fn limit_exceeded(limit: &'static str, maximum: String) -> Error {
+    Error::with_msg("Render limit exceeded")
+        .context("limit", limit)
+        .context("maximum", maximum)
+        .with_kind(ErrorKind::LimitExceeded)
+}
+
+/// Roughly how many bytes `value` takes.
+pub(crate) fn value_size(value: &Value) -> usize {
+    match *value {
+        Value::Scalar(ref x) => x.to_str().len(),
+        Value::Array(ref x) => x.iter().map(value_size).sum::<usize>() + x.len(),
+        Value::Object(ref x) => x.iter().map(|(k, v)| k.len() + value_size(v)).sum(),
+        _ => 8,
+    }
+}
+
+/// Forwards to `writer`, failing once the output is larger than `Limits::output_bytes`.
+///
+/// Renderables replace the errors of writing with their own, so check the `LimitedOutput` for
+/// the error of going over the limit once the render fails.
+pub struct LimitedOutput<'w> {
+    writer: &'w mut dyn Output,
+    limit: Option<usize>,
+    written: usize,
+    exceeded: bool,
+}
+
+impl<'w> LimitedOutput<'w> {
+    /// Limit the output written to `writer` by `limits`.
+    pub fn new(writer: &'w mut dyn Output, limits: &Limits) -> Self {
+        Self {
+            writer,
+            limit: limits.output_bytes(),
+            written: 0,
+            exceeded: false,
+        }
+    }
+
+    /// The error of going over the limit, if the output did.
+    pub fn check(&self) -> Result<()> {
+        match self.limit {
+            Some(limit) if self.exceeded => Err(limit_exceeded("output bytes", limit.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    fn spend(&mut self, bytes: usize) -> io::Result<()> {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        self.written = self.written.saturating_add(bytes);
+        if self.written > limit {
+            self.exceeded = true;
+            return Err(io::Error::other("Render limit exceeded"));
+        }
+        Ok(())
+    }
+}
+
+impl<'w> Write for LimitedOutput<'w> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.spend(buf.len())?;
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<'w> Output for LimitedOutput<'w> {
+    fn write_value(&mut self, value: &str, safe: bool) -> io::Result<()> {
+        self.spend(value.len())?;
+        self.writer.write_value(value, safe)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn limited_output() {
+        let mut buffer = Vec::new();
+        {
+            let limits = Limits::new().with_output_bytes(5);
+            let mut output = LimitedOutput::new(&mut buffer, &limits);
+            output.write_all(b"abc").unwrap();
+            assert!(output.check().is_ok());
+            assert!(output.write_value("def", false).is_err());
+            assert_eq!(output.check().unwrap_err().kind(), ErrorKind::LimitExceeded);
+        }
+        assert_eq!(buffer, b"abc");
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::{self, Write};
+
+/// Where a template is rendered to.
+///
+/// Renderables write template text through `Write` and the values of expressions through
+/// `write_value`, so a wrapper can treat them differently, e.g. escape values, minify text or
+/// record where output came from.
+pub trait Output: Write {
+    /// Writes the rendered value of an expression, e.g. `{{ page.title }}`.
+    ///
+    /// `safe` marks values that are already escaped and should be written as they are.
+    fn write_value(&mut self, value: &str, safe: bool) -> io::Result<()> {
+        let _ = safe;
+        self.write_all(value.as_bytes())
+    }
+}
+
+impl Output for Vec<u8> {}
+
+impl<O: Output + ?Sized> Output for &mut O {
+    fn write_value(&mut self, value: &str, safe: bool) -> io::Result<()> {
+        (**self).write_value(value, safe)
+    }
+}
+
+/// Adapts a `Write` into an `Output` that writes values as they are.
+#[derive(Debug)]
+pub struct WriteOutput<W>(pub W);
+
+impl<W: Write> Write for WriteOutput<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<W: Write> Output for WriteOutput<W> {}
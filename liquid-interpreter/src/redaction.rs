@@ -0,0 +1,148 @@
+use std::fmt;
+use std::sync::Arc;
+
+use liquid_value::ScalarCow;
+
+/// Called with the path of each output that was redacted, e.g. to log it.
+pub type RedactionHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Global paths whose values templates may use but not output.
+///
+/// Outputting a sensitive value, or an object or array holding one, renders a placeholder
+/// instead, while conditions and filters still see the value:
+///
+/// ```rust
+/// use liquid_interpreter::Redaction;
+///
+/// let redaction = Redaction::new()
+///     .path("user.password")
+///     .path("cards.*.number")
+///     .with_placeholder("***");
+/// assert_eq!(redaction.placeholder(), "***");
+/// ```
+///
+/// Only outputs of a path through the globals are redacted.  Copies made with `assign` or the
+/// variable of a `for` loop are not.
+#[derive(Clone)]
+pub struct Redaction {
+    paths: Vec<Vec<String>>,
+    placeholder: String,
+    handler: Option<RedactionHandler>,
+}
+
+impl Redaction {
+    /// Create a redaction of no paths.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `path` as sensitive, as keys and indexes separated by `.`, with `*` for any one of
+    /// them, e.g. `users.*.email`.
+    pub fn path(mut self, path: &str) -> Self {
+        self.paths
+            .push(path.split('.').map(|part| part.to_owned()).collect());
+        self
+    }
+
+    /// Set what sensitive outputs render as, `[REDACTED]` by default.
+    pub fn with_placeholder<S: Into<String>>(mut self, placeholder: S) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Call `handler` with the path of each redacted output.
+    pub fn with_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// What sensitive outputs render as.
+    pub fn placeholder(&self) -> &str {
+        &self.placeholder
+    }
+
+    /// Whether no path is sensitive.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Whether outputting `path` would reveal a sensitive value, because the value is sensitive
+    /// or holds one.
+    pub fn covers(&self, path: &[ScalarCow<'_>]) -> bool {
+        self.paths.iter().any(|sensitive| {
+            sensitive
+                .iter()
+                .zip(path)
+                .all(|(part, index)| part == "*" || *part == index.to_str())
+        })
+    }
+
+    /// Report that the output of `path` was redacted.
+    pub fn report(&self, path: &[ScalarCow<'_>]) {
+        if let Some(ref handler) = self.handler {
+            let path = itertools::join(path.iter().map(|index| index.to_str()), ".");
+            handler(&path);
+        }
+    }
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            placeholder: "[REDACTED]".to_owned(),
+            handler: None,
+        }
+    }
+}
+
+impl fmt::Debug for Redaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redaction")
+            .field("paths", &self.paths)
+            .field("placeholder", &self.placeholder)
+            .field("handler", &self.handler.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl PartialEq for Redaction {
+    fn eq(&self, other: &Self) -> bool {
+        let same_handler = match (&self.handler, &other.handler) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        self.paths == other.paths && self.placeholder == other.placeholder && same_handler
+    }
+}
+
+impl Eq for Redaction {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_value::Scalar;
+
+    fn path(parts: &[&'static str]) -> Vec<ScalarCow<'static>> {
+        parts.iter().map(|p| Scalar::new(*p)).collect()
+    }
+
+    #[test]
+    fn covers() {
+        let redaction = Redaction::new()
+            .path("user.password")
+            .path("cards.*.number");
+        assert!(redaction.covers(&path(&["user", "password"])));
+        assert!(redaction.covers(&path(&["user", "password", "size"])));
+        assert!(redaction.covers(&path(&["user"])));
+        assert!(!redaction.covers(&path(&["user", "name"])));
+        assert!(redaction.covers(&path(&["cards", "0", "number"])));
+        assert!(!redaction.covers(&path(&["cards", "0", "expiry"])));
+        assert!(!Redaction::new().covers(&path(&["user"])));
+    }
+}
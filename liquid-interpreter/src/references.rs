@@ -0,0 +1,179 @@
+use super::Expression;
+use super::Variable;
+
+/// The globals and partials a template refers to, found without rendering it, e.g. to know
+/// which variables to ask a user for.
+///
+/// Globals are recorded by their path up to the first index that isn't a literal, e.g.
+/// `user.address.city` for `{{ user.address.city }}` and `products` for
+/// `{{ products[i].title }}`, in the order the template first refers to them.  Variables set by
+/// the template, like those of `assign` or the item of a `for` loop, are not globals from where
+/// they are set.  See `Renderable::references`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct References {
+    variables: Vec<String>,
+    partials: Vec<String>,
+    // Variables set by the template, innermost scope last.
+    locals: Vec<Vec<String>>,
+}
+
+impl References {
+    /// Create an empty set of references.
+    pub fn new() -> Self {
+        Self {
+            variables: Vec::new(),
+            partials: Vec::new(),
+            locals: vec![Vec::new()],
+        }
+    }
+
+    /// The paths of the globals referred to, in order.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.variables.iter().map(|s| s.as_str())
+    }
+
+    /// The names of the partials referred to, in order.
+    pub fn partials(&self) -> impl Iterator<Item = &str> {
+        self.partials.iter().map(|s| s.as_str())
+    }
+
+    /// Record the globals `expression` refers to.
+    pub fn expression(&mut self, expression: &Expression) {
+        match *expression {
+            Expression::Literal(_) => (),
+            Expression::Variable(ref variable) => self.variable(variable),
+            Expression::Range(ref start, ref end) => {
+                self.expression(start);
+                self.expression(end);
+            }
+            Expression::Array(ref items) => {
+                for item in items {
+                    self.expression(item);
+                }
+            }
+            Expression::Arithmetic(ref lhs, _, ref rhs) => {
+                self.expression(lhs);
+                self.expression(rhs);
+            }
+        }
+    }
+
+    /// Record the global `variable` refers to, and those of its indexes.
+    pub fn variable(&mut self, variable: &Variable) {
+        let root = variable.root().to_str();
+        let mut path = root.clone().into_owned();
+        let mut literal = true;
+        for index in variable.indexes() {
+            match *index {
+                Expression::Literal(ref value) if literal => match value.as_scalar() {
+                    Some(index) => {
+                        path.push('.');
+                        path.push_str(&index.to_str());
+                    }
+                    None => literal = false,
+                },
+                _ => {
+                    literal = false;
+                    self.expression(index);
+                }
+            }
+        }
+        if !self.is_local(&root) && !self.variables.contains(&path) {
+            self.variables.push(path);
+        }
+    }
+
+    /// Record the partial `name`.
+    pub fn partial<S: Into<String>>(&mut self, name: S) {
+        let name = name.into();
+        if !self.partials.contains(&name) {
+            self.partials.push(name);
+        }
+    }
+
+    /// Record that the template sets the variable `name`, e.g. with `assign`, so it is not a
+    /// global from here on.
+    pub fn assign<S: Into<String>>(&mut self, name: S) {
+        self.locals
+            .last_mut()
+            .expect("There is always a scope.")
+            .push(name.into());
+    }
+
+    /// Record that the template sets `variable`, e.g. with `assign`, and the globals its indexes
+    /// refer to.
+    pub fn set(&mut self, variable: &Variable) {
+        for index in variable.indexes() {
+            self.expression(index);
+        }
+        self.assign(variable.root().to_str().into_owned());
+    }
+
+    /// Record the references of `f` in a scope where the variables `locals` are set, e.g. the
+    /// item of a `for` loop.
+    pub fn scope<F>(&mut self, locals: &[&str], f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.locals
+            .push(locals.iter().map(|name| (*name).to_owned()).collect());
+        f(self);
+        let scope = self.locals.pop().expect("The scope was pushed.");
+        // `assign` sets variables beyond the scope it is in.
+        self.locals
+            .last_mut()
+            .expect("There is always a scope.")
+            .extend(scope.into_iter().skip(locals.len()));
+    }
+
+    fn is_local(&self, name: &str) -> bool {
+        self.locals.iter().flatten().any(|local| local == name)
+    }
+}
+
+impl Default for References {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_value::Value;
+
+    #[test]
+    fn references_variables() {
+        let mut references = References::new();
+        let city = Variable::with_literal("user")
+            .push_literal("address")
+            .push_literal("city");
+        references.variable(&city);
+        let mut product = Variable::with_literal("products");
+        product.extend(vec![
+            Expression::Variable(Variable::with_literal("i")),
+            Expression::with_literal("title"),
+        ]);
+        references.variable(&product);
+        references.expression(&Expression::Literal(Value::scalar(1)));
+        references.variable(&city);
+
+        let variables: Vec<_> = references.variables().collect();
+        assert_eq!(variables, vec!["user.address.city", "i", "products"]);
+    }
+
+    #[test]
+    fn references_locals() {
+        let mut references = References::new();
+        references.scope(&["item"], |references| {
+            references.variable(&Variable::with_literal("item").push_literal("name"));
+            references.assign("total");
+        });
+        references.variable(&Variable::with_literal("item"));
+        references.variable(&Variable::with_literal("total"));
+
+        let variables: Vec<_> = references.variables().collect();
+        assert_eq!(variables, vec!["item"]);
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt::Debug;
-use std::io::Write;
 
 use liquid_error::Result;
+use liquid_value::Object;
 
 use super::Context;
+use super::Output;
+use super::References;
 
 /// Any object (tag/block) that can be rendered by liquid must implement this trait.
 pub trait Renderable: Send + Sync + Debug {
@@ -15,5 +17,16 @@ pub trait Renderable: Send + Sync + Debug {
     }
 
     /// Renders the Renderable instance given a Liquid context.
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context<'_>) -> Result<()>;
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context<'_>) -> Result<()>;
+
+    /// What this element declares about its template for the host, like `{% meta %}` does.
+    fn metadata(&self) -> Option<&Object> {
+        None
+    }
+
+    /// Record the globals and partials this element refers to, including those of the elements
+    /// inside it.
+    fn references(&self, references: &mut References) {
+        let _ = references;
+    }
 }
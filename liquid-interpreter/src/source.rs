@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use itertools;
+use liquid_error::{Error, Result, ResultLiquidExt};
+use liquid_value::{Object, PathRef, ScalarCow, Value};
+use typed_arena::Arena;
+
+use super::ValueStore;
+
+/// An object whose properties are computed when a template reads them, like the drops of
+/// Ruby's Liquid.
+///
+/// Properties are computed each time they are read, so a source whose properties are expensive
+/// to compute, e.g. by querying a database, should keep them once computed.
+pub trait ObjectSource: fmt::Debug {
+    /// The names of the properties.
+    fn keys(&self) -> Vec<&str>;
+
+    /// Compute the property `key`, `None` when the object doesn't have it.
+    ///
+    /// Errors fail the render.
+    fn get(&self, key: &str) -> Result<Option<Value>>;
+}
+
+/// Globals some of which are `ObjectSource`s, computing their properties as templates read
+/// them.
+///
+/// ```rust
+/// use liquid_error::Result;
+/// use liquid_interpreter::{ObjectSource, SourceStore, ValueStore};
+/// use liquid_value::{Object, Scalar, Value};
+///
+/// #[derive(Debug)]
+/// struct ProductDrop;
+///
+/// impl ObjectSource for ProductDrop {
+///     fn keys(&self) -> Vec<&str> {
+///         vec!["title"]
+///     }
+///
+///     fn get(&self, key: &str) -> Result<Option<Value>> {
+///         match key {
+///             "title" => Ok(Some(Value::scalar("Hat"))),
+///             _ => Ok(None),
+///         }
+///     }
+/// }
+///
+/// let product = ProductDrop;
+/// let store = SourceStore::new(Object::new()).with_source("product", &product);
+/// let path = [Scalar::new("product"), Scalar::new("title")];
+/// assert_eq!(store.get_variable(&path).unwrap(), &Value::scalar("Hat"));
+/// ```
+pub struct SourceStore<'s> {
+    values: Object,
+    sources: BTreeMap<String, &'s dyn ObjectSource>,
+    computed: Arena<Value>,
+}
+
+impl<'s> SourceStore<'s> {
+    /// Create globals of `values`, without any sources.
+    pub fn new(values: Object) -> Self {
+        Self {
+            values,
+            sources: BTreeMap::new(),
+            computed: Arena::new(),
+        }
+    }
+
+    /// Add the global `name`, computing its properties with `source`.
+    ///
+    /// It shadows the value of the same name.
+    pub fn with_source<S: Into<String>>(mut self, name: S, source: &'s dyn ObjectSource) -> Self {
+        self.sources.insert(name.into(), source);
+        self
+    }
+
+    fn source(&self, path: PathRef<'_, '_>) -> Option<(&'s dyn ObjectSource, String)> {
+        let root = path.first()?.to_str();
+        let source = *self.sources.get(root.as_ref())?;
+        Some((source, root.into_owned()))
+    }
+
+    /// Read `path` from `source`, computing only the property it is in.
+    fn get_from_source(
+        &self,
+        source: &dyn ObjectSource,
+        root: &str,
+        path: PathRef<'_, '_>,
+    ) -> Result<&Value> {
+        let key = match path.get(1) {
+            Some(key) => key.to_str(),
+            None => return Ok(self.computed.alloc(compute_all(source, root)?)),
+        };
+        let property = source
+            .get(key.as_ref())
+            .context_key("property")
+            .value_with(|| format!("{}.{}", root, key).into())?;
+        let mut value: &Value = match property {
+            Some(property) => self.computed.alloc(property),
+            None => {
+                return Error::with_msg("Unknown index")
+                    .context("variable", root.to_owned())
+                    .context("requested index", key.into_owned())
+                    .context("available indexes", itertools::join(source.keys(), ", "))
+                    .into_err();
+            }
+        };
+
+        for (position, index) in path.iter().enumerate().skip(2) {
+            value = match value.get(index) {
+                Some(next) => next,
+                None => match value.property(&index.to_str()) {
+                    Some(property) => self.computed.alloc(property),
+                    None => {
+                        let variable =
+                            itertools::join(path[..position].iter().map(ScalarCow::render), ".");
+                        let available =
+                            itertools::join(value.keys().map(|k| k.into_string()), ", ");
+                        return Error::with_msg("Unknown index")
+                            .context("variable", variable)
+                            .context("requested index", format!("{}", index.render()))
+                            .context("available indexes", available)
+                            .into_err();
+                    }
+                },
+            };
+        }
+        Ok(value)
+    }
+}
+
+/// Compute every property of `source`, e.g. when a template outputs the whole object.
+fn compute_all(source: &dyn ObjectSource, root: &str) -> Result<Value> {
+    let mut object = Object::new();
+    for key in source.keys() {
+        let property = source
+            .get(key)
+            .context_key("property")
+            .value_with(|| format!("{}.{}", root, key).into())?;
+        if let Some(property) = property {
+            object.insert(key.to_owned().into(), property);
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+impl<'s> fmt::Debug for SourceStore<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourceStore")
+            .field("values", &self.values)
+            .field("sources", &self.sources)
+            .finish()
+    }
+}
+
+impl<'s> ValueStore for SourceStore<'s> {
+    fn contains_root(&self, name: &str) -> bool {
+        self.sources.contains_key(name) || self.values.contains_root(name)
+    }
+
+    fn roots(&self) -> Vec<&str> {
+        let mut roots = self.values.roots();
+        roots.extend(self.sources.keys().map(String::as_str));
+        roots
+    }
+
+    fn contains_variable(&self, path: PathRef<'_, '_>) -> bool {
+        self.try_get_variable(path).is_some()
+    }
+
+    fn try_get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Option<&'a Value> {
+        match self.source(path) {
+            Some((source, root)) => self.get_from_source(source, &root, path).ok(),
+            None => self.values.try_get_variable(path),
+        }
+    }
+
+    fn get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Result<&'a Value> {
+        match self.source(path) {
+            Some((source, root)) => self.get_from_source(source, &root, path),
+            None => self.values.get_variable(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use liquid_value::Scalar;
+
+    #[derive(Debug, Default)]
+    struct ProductDrop {
+        queries: Cell<usize>,
+    }
+
+    impl ObjectSource for ProductDrop {
+        fn keys(&self) -> Vec<&str> {
+            vec!["title", "related"]
+        }
+
+        fn get(&self, key: &str) -> Result<Option<Value>> {
+            match key {
+                "title" => Ok(Some(Value::scalar("Hat"))),
+                "related" => {
+                    self.queries.set(self.queries.get() + 1);
+                    Ok(Some(Value::array(vec![Value::scalar("Cap")])))
+                }
+                "broken" => Error::with_msg("Database is down").into_err(),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    #[test]
+    fn properties_are_computed_when_read() {
+        let product = ProductDrop::default();
+        let mut values = Object::new();
+        values.insert("shop".into(), Value::scalar("Hats"));
+        let store = SourceStore::new(values).with_source("product", &product);
+        assert_eq!(store.roots(), vec!["shop", "product"]);
+
+        let title = [Scalar::new("product"), Scalar::new("title")];
+        assert_eq!(store.get_variable(&title).unwrap(), &Value::scalar("Hat"));
+        assert_eq!(product.queries.get(), 0);
+
+        let size = [
+            Scalar::new("product"),
+            Scalar::new("related"),
+            Scalar::new("size"),
+        ];
+        assert_eq!(store.get_variable(&size).unwrap(), &Value::scalar(1));
+        assert_eq!(product.queries.get(), 1);
+
+        let shop = [Scalar::new("shop")];
+        assert_eq!(store.get_variable(&shop).unwrap(), &Value::scalar("Hats"));
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let product = ProductDrop::default();
+        let store = SourceStore::new(Object::new()).with_source("product", &product);
+
+        let broken = [Scalar::new("product"), Scalar::new("broken")];
+        let error = store.get_variable(&broken).unwrap_err().to_string();
+        assert!(error.contains("Database is down"));
+        assert!(error.contains("property=product.broken"));
+        assert!(store.try_get_variable(&broken).is_none());
+
+        let missing = [Scalar::new("product"), Scalar::new("price")];
+        let error = store.get_variable(&missing).unwrap_err().to_string();
+        assert!(error.contains("available indexes=title, related"));
+    }
+}
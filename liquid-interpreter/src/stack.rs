@@ -1,10 +1,12 @@
-use std::borrow;
+use std::cell::RefCell;
+use std::fmt;
 
 use itertools;
 use liquid_error::{Error, Result};
-use liquid_value::{Object, PathRef, Scalar, Value};
+use liquid_value::{Key, Object, PathRef, ScalarCow, Value};
+use typed_arena::Arena;
 
-use super::ValueStore;
+use super::{Dependencies, SharedGlobals, Usage, ValueStore};
 
 #[derive(Clone, Default, Debug)]
 struct Frame {
@@ -25,13 +27,43 @@ impl Frame {
     }
 }
 
+/// The values computed while rendering, like the `size`, `first` and `last` properties of
+/// variables and ranges, kept for the rest of the render so lookups can return references to them.
+#[derive(Default)]
+struct Computed(Arena<Value>);
+
+impl Clone for Computed {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Computed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Computed").field(&self.0.len()).finish()
+    }
+}
+
 /// Stack of variables.
+///
+/// Variables are looked up, in order, in the frames written by `assign`, `capture` and the
+/// like, the per-render globals, and the globals shared across renders.  The globals are never
+/// written to: setting a global shadows it for the rest of the render instead.
 #[derive(Debug, Clone)]
 pub struct Stack<'g> {
     globals: Option<&'g dyn ValueStore>,
+    shared: Option<SharedGlobals>,
     stack: Vec<Frame>,
     // State of variables created through increment or decrement tags.
     indexes: Object,
+    // Variables provided by the interpreter, e.g. `liquid`.
+    builtins: Object,
+    // What is being read, innermost recording last.
+    recordings: RefCell<Vec<Dependencies>>,
+    // What the render used, when it is recorded.
+    usage: RefCell<Option<Usage>>,
+    strict_variables: bool,
+    computed: Computed,
 }
 
 impl<'g> Stack<'g> {
@@ -39,7 +71,13 @@ impl<'g> Stack<'g> {
     pub fn empty() -> Self {
         Self {
             globals: None,
+            shared: None,
             indexes: Object::new(),
+            builtins: Object::new(),
+            recordings: RefCell::default(),
+            usage: RefCell::default(),
+            strict_variables: false,
+            computed: Computed::default(),
             // Mutable frame for globals.
             stack: vec![Frame::new()],
         }
@@ -52,6 +90,120 @@ impl<'g> Stack<'g> {
         stack
     }
 
+    /// Add globals shared across renders, shadowed by every other variable.
+    ///
+    /// Sharing is a reference count, so a stack can be set up per render at no cost.
+    pub fn set_shared_globals(&mut self, shared: SharedGlobals) {
+        self.shared = Some(shared);
+    }
+
+    /// A stack of only the globals and builtins, for rendering the template `name` without
+    /// access to the variables of this one.
+    pub(crate) fn isolated<S: Into<String>>(&self, name: S) -> Self {
+        Self {
+            globals: self.globals,
+            shared: self.shared.clone(),
+            stack: vec![Frame::with_name(name)],
+            indexes: Object::new(),
+            builtins: self.builtins.clone(),
+            // What the template reads is read by this one.
+            recordings: RefCell::new(self.recordings.take()),
+            usage: RefCell::new(self.usage.take()),
+            strict_variables: self.strict_variables,
+            computed: Computed::default(),
+        }
+    }
+
+    /// Fail to `get` variables that don't exist, instead of them being `nil`.
+    pub fn set_strict_variables(&mut self, strict: bool) {
+        self.strict_variables = strict;
+    }
+
+    /// Carry on with this stack after rendering with the one from `isolated`.
+    pub(crate) fn resume(&mut self, isolated: Self) {
+        self.recordings = isolated.recordings;
+        self.usage = isolated.usage;
+    }
+
+    /// Start recording the globals read and partials rendered.
+    pub(crate) fn start_recording(&self) {
+        self.recordings.borrow_mut().push(Dependencies::new());
+    }
+
+    /// What was read since the matching `start_recording`.
+    ///
+    /// That was read by the enclosing recording too.
+    pub(crate) fn stop_recording(&self) -> Dependencies {
+        let mut recordings = self.recordings.borrow_mut();
+        let dependencies = recordings.pop().unwrap_or_default();
+        if let Some(outer) = recordings.last_mut() {
+            outer.extend(&dependencies);
+        }
+        dependencies
+    }
+
+    /// Record depending on `dependencies`, if recording.
+    pub(crate) fn record(&self, dependencies: &Dependencies) {
+        if let Some(recording) = self.recordings.borrow_mut().last_mut() {
+            recording.extend(dependencies);
+        }
+    }
+
+    /// Record the partial `name` being rendered, if recording.
+    pub(crate) fn record_partial(&self, name: &str) {
+        if let Some(recording) = self.recordings.borrow_mut().last_mut() {
+            recording.add_partial(name);
+        }
+        if let Some(ref mut usage) = *self.usage.borrow_mut() {
+            usage.add_partial(name);
+        }
+    }
+
+    /// Start recording what the render uses.
+    pub(crate) fn start_usage(&self) -> Option<Usage> {
+        self.usage.replace(Some(Usage::new()))
+    }
+
+    /// What the render used since `start_usage`, carrying on with the recording it returned.
+    pub(crate) fn stop_usage(&self, outer: Option<Usage>) -> Usage {
+        self.usage.replace(outer).unwrap_or_default()
+    }
+
+    /// Record running the filter `name`, if recording usage.
+    pub(crate) fn record_filter(&self, name: &str) {
+        if let Some(ref mut usage) = *self.usage.borrow_mut() {
+            usage.add_filter(name);
+        }
+    }
+
+    /// Record reading `path`, if recording and it is a global.
+    ///
+    /// Variables that don't exist are globals that might be added.
+    fn record_global(&self, path: PathRef<'_, '_>) {
+        let mut recordings = self.recordings.borrow_mut();
+        let recording = match recordings.last_mut() {
+            Some(recording) => recording,
+            None => return,
+        };
+        let root = match path.first() {
+            Some(root) => root.to_str(),
+            None => return,
+        };
+        let root = root.as_ref();
+        if self
+            .stack
+            .iter()
+            .any(|frame| frame.data.contains_root(root))
+        {
+            return;
+        }
+        let is_global = self.globals.is_some_and(|g| g.contains_root(root))
+            || self.shared.as_ref().is_some_and(|g| g.contains_root(root));
+        if is_global || !(self.indexes.contains_root(root) || self.builtins.contains_root(root)) {
+            recording.add_global(itertools::join(path.iter().map(ScalarCow::render), "."));
+        }
+    }
+
     /// Creates a new variable scope chained to a parent scope.
     pub(crate) fn push_frame(&mut self) {
         self.stack.push(Frame::new());
@@ -84,23 +236,109 @@ impl<'g> Stack<'g> {
             .find_map(|f| f.name.as_ref().map(|s| s.as_str()))
     }
 
+    /// The variables of each scope, outermost first, with the name of the template that opened
+    /// it, if any, e.g. for debugging.
+    ///
+    /// The outermost scope holds the variables the template sets at its top level, e.g. with
+    /// `assign`.
+    pub fn scopes(&self) -> impl Iterator<Item = (Option<&str>, &Object)> {
+        self.stack
+            .iter()
+            .map(|frame| (frame.name.as_deref(), &frame.data))
+    }
+
+    /// The globals, per-render ones shadowing those shared across renders, e.g. for debugging.
+    ///
+    /// Reading them isn't recorded as depending on them.
+    pub fn global_values(&self) -> Object {
+        let mut values = Object::new();
+        let stores = self
+            .shared
+            .as_ref()
+            .map(|shared| shared.as_ref() as &dyn ValueStore)
+            .into_iter()
+            .chain(self.globals);
+        for store in stores {
+            for root in store.roots() {
+                if let Some(value) = store.try_get_variable(&[ScalarCow::new(root)]) {
+                    values.insert(Key::from(root.to_owned()), value.clone());
+                }
+            }
+        }
+        values
+    }
+
     /// Recursively index into the stack.
+    ///
+    /// Indexes that values don't have resolve to their `size`, `first` and `last` properties, see
+    /// `Value::property`.
     pub fn try_get(&self, path: PathRef<'_, '_>) -> Option<&Value> {
+        self.record_global(path);
         let frame = self.find_path_frame(path)?;
 
-        frame.try_get_variable(path)
+        frame
+            .try_get_variable(path)
+            .or_else(|| self.try_get_property(frame, path))
     }
 
     /// Recursively index into the stack.
+    ///
+    /// Indexes that values don't have resolve to their `size`, `first` and `last` properties, see
+    /// `Value::property`.
     pub fn get(&self, path: PathRef<'_, '_>) -> Result<&Value> {
+        self.record_global(path);
         match self.find_path_frame(path) {
-            Some(value) => value.get_variable(path),
-            None => Ok(&Value::Nil)
+            Some(frame) => frame
+                .get_variable(path)
+                .or_else(|error| self.try_get_property(frame, path).ok_or(error)),
+            None if self.strict_variables && !path.is_empty() => {
+                if let Some(ref mut usage) = *self.usage.borrow_mut() {
+                    usage.add_undefined(itertools::join(path.iter().map(ScalarCow::render), "."));
+                }
+                let available = itertools::join(self.globals(), ", ");
+                Error::with_msg("Unknown variable")
+                    .context("requested variable", path[0].to_str().into_owned())
+                    .context("available variables", available)
+                    .into_err()
+            }
+            None => {
+                if let Some(ref mut usage) = *self.usage.borrow_mut() {
+                    usage.add_undefined(itertools::join(path.iter().map(ScalarCow::render), "."));
+                }
+                Ok(&Value::Nil)
+            }
         }
     }
 
+    /// Keeps a value computed while rendering for the rest of the render.
+    pub(crate) fn keep(&self, value: Value) -> &Value {
+        self.computed.0.alloc(value)
+    }
+
+    /// Looks `path` up in `frame` from the longest part of it that exists, computing properties
+    /// for the indexes after it.
+    fn try_get_property<'a>(
+        &'a self,
+        frame: &'a dyn ValueStore,
+        path: PathRef<'_, '_>,
+    ) -> Option<&'a Value> {
+        let (end, mut value) = (1..path.len())
+            .rev()
+            .find_map(|end| frame.try_get_variable(&path[..end]).map(|v| (end, v)))?;
+        for index in &path[end..] {
+            value = match value.get(index) {
+                Some(value) => value,
+                None => self.keep(value.property(&index.to_str())?),
+            };
+        }
+        Some(value)
+    }
+
     fn globals(&self) -> Vec<&str> {
         let mut globals = self.globals.map(|g| g.roots()).unwrap_or_default();
+        if let Some(ref shared) = self.shared {
+            globals.extend(shared.roots());
+        }
         for frame in self.stack.iter() {
             globals.extend(frame.data.roots());
         }
@@ -126,17 +364,27 @@ impl<'g> Stack<'g> {
             return self.globals;
         }
 
+        if let Some(ref shared) = self.shared {
+            if shared.contains_root(name) {
+                return Some(shared.as_ref());
+            }
+        }
+
         if self.indexes.contains_root(name) {
             return Some(&self.indexes);
         }
 
+        if self.builtins.contains_root(name) {
+            return Some(&self.builtins);
+        }
+
         None
     }
 
     /// Used by increment and decrement tags
     pub fn set_index<S>(&mut self, name: S, val: Value) -> Option<Value>
     where
-        S: Into<borrow::Cow<'static, str>>,
+        S: Into<Key>,
     {
         self.indexes.insert(name.into(), val)
     }
@@ -146,14 +394,45 @@ impl<'g> Stack<'g> {
         self.indexes.get(name)
     }
 
+    /// Sets a variable provided by the interpreter, shadowed by any user-defined variable.
+    pub(crate) fn set_builtin<S>(&mut self, name: S, val: Value) -> Option<Value>
+    where
+        S: Into<Key>,
+    {
+        self.builtins.insert(name.into(), val)
+    }
+
     /// Sets a value in the global context.
+    ///
+    /// This writes to the per-render layer, shadowing any read-only global of the same name.
     pub fn set_global<S>(&mut self, name: S, val: Value) -> Option<Value>
     where
-        S: Into<borrow::Cow<'static, str>>,
+        S: Into<Key>,
     {
         self.global_frame().insert(name.into(), val)
     }
 
+    /// Sets a value at `path` in the global context, e.g. `user.profile.theme`.
+    ///
+    /// The root variable is copied into the per-render layer, if read-only, and missing objects
+    /// along the path are created.
+    pub fn set_global_path(&mut self, path: PathRef<'_, '_>, val: Value) -> Result<()> {
+        let (root, indexes) = match path.split_first() {
+            Some(split) => split,
+            None => return Error::with_msg("Empty path").into_err(),
+        };
+        let root = root.to_str().into_owned();
+        if indexes.is_empty() {
+            self.set_global(root, val);
+            return Ok(());
+        }
+
+        let mut value = self.try_get(&path[..1]).cloned().unwrap_or(Value::Nil);
+        set_index(&mut value, indexes, val).map_err(|e| e.context("variable", root.clone()))?;
+        self.set_global(root, value);
+        Ok(())
+    }
+
     /// Sets a value to the rendering context.
     /// Note that it needs to be wrapped in a liquid::Value.
     ///
@@ -164,7 +443,7 @@ impl<'g> Stack<'g> {
     /// this should never happen in a well-formed program.
     pub fn set<S>(&mut self, name: S, val: Value) -> Option<Value>
     where
-        S: Into<borrow::Cow<'static, str>>,
+        S: Into<Key>,
     {
         self.current_frame().insert(name.into(), val)
     }
@@ -184,16 +463,80 @@ impl<'g> Stack<'g> {
     }
 }
 
+fn set_index(value: &mut Value, path: PathRef<'_, '_>, val: Value) -> Result<()> {
+    let (index, rest) = match path.split_first() {
+        Some(split) => split,
+        None => {
+            *value = val;
+            return Ok(());
+        }
+    };
+
+    if value.is_nil() {
+        *value = Value::Object(Object::new());
+    }
+    let slot = match *value {
+        Value::Object(ref mut object) => object
+            .entry(index.to_str().into_owned())
+            .or_insert(Value::Nil),
+        Value::Array(ref mut array) => {
+            let len = array.len() as isize;
+            let position = index
+                .to_integer()
+                .map(|i| if i < 0 { len + i as isize } else { i as isize })
+                .filter(|i| 0 <= *i && *i < len)
+                .ok_or_else(|| {
+                    Error::with_msg("Index out of bounds")
+                        .context("index", index.to_str().into_owned())
+                        .context("size", len.to_string())
+                })?;
+            &mut array[position as usize]
+        }
+        _ => {
+            return Error::with_msg("Cannot assign to an index of this value")
+                .context("value", value.source().to_string())
+                .context("index", index.to_str().into_owned())
+                .into_err();
+        }
+    };
+    set_index(slot, rest, val)
+}
+
 impl<'g> Default for Stack<'g> {
     fn default() -> Self {
         Self::empty()
     }
 }
 
+/// Read-only view of every variable in scope, e.g. to be the globals of a nested `Context`.
+impl<'g> ValueStore for Stack<'g> {
+    fn contains_root(&self, name: &str) -> bool {
+        self.find_frame(name).is_some()
+    }
+
+    fn roots(&self) -> Vec<&str> {
+        self.globals()
+    }
+
+    fn contains_variable(&self, path: PathRef<'_, '_>) -> bool {
+        self.try_get(path).is_some()
+    }
+
+    fn try_get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Option<&'a Value> {
+        self.try_get(path)
+    }
+
+    fn get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Result<&'a Value> {
+        self.get(path)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    use liquid_value::Scalar;
+
     #[test]
     fn stack_find_frame() {
         let mut stack = Stack::empty();
@@ -210,6 +553,30 @@ mod test {
         assert!(stack.find_frame("post.number").is_none());
     }
 
+    #[test]
+    fn stack_scopes() {
+        let mut globals = Object::new();
+        globals.insert("site".into(), Value::scalar("blog"));
+        let mut shared = Object::new();
+        shared.insert("site".into(), Value::scalar("shared"));
+        shared.insert("year".into(), Value::scalar(2019));
+        let mut stack = Stack::with_globals(&globals);
+        stack.set_shared_globals(std::sync::Arc::new(shared));
+        stack.set_global("title", Value::scalar("Home"));
+        stack.push_named_frame("card");
+        stack.set("item", Value::scalar(1));
+
+        let scopes: Vec<_> = stack
+            .scopes()
+            .map(|(name, variables)| (name, variables.keys().count()))
+            .collect();
+        assert_eq!(scopes, vec![(None, 1), (Some("card"), 1)]);
+        let values = stack.global_values();
+        assert_eq!(values["site"], Value::scalar("blog"));
+        assert_eq!(values["year"], Value::scalar(2019));
+        assert_eq!(values.len(), 2);
+    }
+
     #[test]
     fn stack_get() {
         let mut stack = Stack::empty();
@@ -220,4 +587,126 @@ mod test {
         assert_eq!(stack.get(&indexes).unwrap(), &Value::scalar(42f64));
     }
 
+    #[test]
+    fn stack_get_strict() {
+        let mut stack = Stack::empty();
+        stack.set_global("post", Value::Object(Object::new()));
+        let missing = [Scalar::new("missing")];
+        assert_eq!(stack.get(&missing).unwrap(), &Value::Nil);
+        assert!(stack.try_get(&missing).is_none());
+
+        stack.set_strict_variables(true);
+        assert!(stack.get(&missing).is_err());
+        assert!(stack.try_get(&missing).is_none());
+        assert_eq!(
+            stack.get(&[Scalar::new("post")]).unwrap(),
+            &Value::Object(Object::new())
+        );
+    }
+
+    #[test]
+    fn stack_get_properties() {
+        let mut stack = Stack::empty();
+        let mut product = Object::new();
+        product.insert("title".into(), Value::scalar("Hat"));
+        let mut collection = Object::new();
+        collection.insert(
+            "products".into(),
+            Value::array(vec![Value::Object(product)]),
+        );
+        stack.set_global("collection", Value::Object(collection));
+        stack.set_global("name", Value::scalar("héllo"));
+        stack.set_global("empty", Value::array(vec![]));
+
+        let get = |path: &[&str]| {
+            let path: Vec<_> = path.iter().map(|s| Scalar::new(s.to_string())).collect();
+            stack.get(&path).map(Value::clone)
+        };
+        assert_eq!(
+            get(&["collection", "products", "first", "title"]).unwrap(),
+            Value::scalar("Hat")
+        );
+        assert_eq!(
+            get(&["collection", "products", "size"]).unwrap(),
+            Value::scalar(1)
+        );
+        assert_eq!(get(&["collection", "size"]).unwrap(), Value::scalar(1));
+        assert_eq!(
+            get(&["collection", "first", "first"]).unwrap(),
+            Value::scalar("products")
+        );
+        assert_eq!(get(&["name", "size"]).unwrap(), Value::scalar(5));
+        assert_eq!(get(&["name", "last"]).unwrap(), Value::scalar("o"));
+        assert_eq!(
+            get(&["name", "size", "size"])
+                .unwrap_err()
+                .to_string()
+                .lines()
+                .next(),
+            Some("liquid: Unknown index")
+        );
+        assert!(get(&["empty", "last"]).is_err());
+        assert_eq!(get(&["empty", "size"]).unwrap(), Value::scalar(0));
+    }
+
+    #[test]
+    fn stack_set_global_path() {
+        let mut site = Object::new();
+        site.insert("title".into(), Value::scalar("Site"));
+        let mut globals = Object::new();
+        globals.insert("site".into(), Value::Object(site));
+        globals.insert(
+            "tags".into(),
+            Value::array(vec![Value::scalar("a"), Value::scalar("b")]),
+        );
+        let mut stack = Stack::with_globals(&globals);
+
+        let theme = [
+            Scalar::new("user"),
+            Scalar::new("profile"),
+            Scalar::new("theme"),
+        ];
+        stack
+            .set_global_path(&theme, Value::scalar("dark"))
+            .unwrap();
+        assert_eq!(stack.get(&theme).unwrap(), &Value::scalar("dark"));
+
+        let lang = [Scalar::new("site"), Scalar::new("lang")];
+        stack.set_global_path(&lang, Value::scalar("en")).unwrap();
+        assert_eq!(stack.get(&lang).unwrap(), &Value::scalar("en"));
+        let title = [Scalar::new("site"), Scalar::new("title")];
+        assert_eq!(stack.get(&title).unwrap(), &Value::scalar("Site"));
+        assert!(globals["site"].get(&Scalar::new("lang")).is_none());
+
+        let last = [Scalar::new("tags"), Scalar::new(-1)];
+        stack.set_global_path(&last, Value::scalar("z")).unwrap();
+        assert_eq!(stack.get(&last).unwrap(), &Value::scalar("z"));
+        let missing = [Scalar::new("tags"), Scalar::new(5)];
+        assert!(stack.set_global_path(&missing, Value::nil()).is_err());
+        let scalar = [Scalar::new("site"), Scalar::new("title"), Scalar::new("x")];
+        assert!(stack.set_global_path(&scalar, Value::nil()).is_err());
+    }
+
+    #[test]
+    fn stack_shared_globals() {
+        let mut site = Object::new();
+        site.insert("title".into(), Value::scalar("Site"));
+        site.insert("author".into(), Value::scalar("Site author"));
+        let site = std::sync::Arc::new(site);
+
+        let mut page = Object::new();
+        page.insert("author".into(), Value::scalar("Page author"));
+
+        let mut stack = Stack::with_globals(&page);
+        stack.set_shared_globals(site.clone());
+        let title = [Scalar::new("title")];
+        let author = [Scalar::new("author")];
+        assert_eq!(stack.get(&title).unwrap(), &Value::scalar("Site"));
+        assert_eq!(stack.get(&author).unwrap(), &Value::scalar("Page author"));
+        assert_eq!(stack.roots(), vec!["author", "title"]);
+
+        stack.set_global("title", Value::scalar("Assigned"));
+        assert_eq!(stack.get(&title).unwrap(), &Value::scalar("Assigned"));
+        assert_eq!(site.get("title"), Some(&Value::scalar("Site")));
+    }
 }
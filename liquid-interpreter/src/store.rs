@@ -1,11 +1,18 @@
 use std::fmt;
+use std::sync;
 
 use itertools;
 use liquid_error::{Error, Result};
 use liquid_value::Object;
+use liquid_value::ObjectView;
 use liquid_value::PathRef;
 use liquid_value::ScalarCow;
 use liquid_value::Value;
+use liquid_value::ValueView;
+use typed_arena::Arena;
+
+/// Read-only globals owned by more than one render, e.g. site-wide data.
+pub type SharedGlobals = sync::Arc<dyn ValueStore + Send + Sync>;
 
 /// Immutable view into a template's global variables.
 pub trait ValueStore: fmt::Debug {
@@ -90,3 +97,194 @@ fn get_variable_option<'o>(obj: &'o Object, path: PathRef<'_, '_>) -> Option<&'o
         value.get(index)
     })
 }
+
+/// Globals read through an `ObjectView`, e.g. a struct deriving it, instead of an `Object`.
+///
+/// The globals are borrowed: only the values a template reads are copied into `Value`s, when
+/// it reads them.
+///
+/// ```rust
+/// use liquid_interpreter::{ValueStore, ViewStore};
+/// use liquid_value::{Scalar, Value};
+/// use std::collections::HashMap;
+///
+/// let mut globals = HashMap::new();
+/// globals.insert("tags".to_owned(), vec!["a", "b"]);
+/// let store = ViewStore::new(&globals);
+/// let path = [Scalar::new("tags"), Scalar::new("last")];
+/// assert_eq!(store.try_get_variable(&path), Some(&Value::scalar("b")));
+/// ```
+pub struct ViewStore<'o> {
+    view: &'o dyn ObjectView,
+    read: Arena<Value>,
+}
+
+impl<'o> ViewStore<'o> {
+    /// Read the globals from `view`.
+    pub fn new(view: &'o dyn ObjectView) -> Self {
+        Self {
+            view,
+            read: Arena::new(),
+        }
+    }
+}
+
+impl<'o> fmt::Debug for ViewStore<'o> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewStore")
+            .field("view", &self.view)
+            .finish()
+    }
+}
+
+impl<'o> ValueStore for ViewStore<'o> {
+    fn contains_root(&self, name: &str) -> bool {
+        self.view.contains_key(name)
+    }
+
+    fn roots(&self) -> Vec<&str> {
+        self.view.keys().collect()
+    }
+
+    fn contains_variable(&self, path: PathRef<'_, '_>) -> bool {
+        get_view(self.view, path).is_some()
+    }
+
+    fn try_get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Option<&'a Value> {
+        if let Some(view) = get_view(self.view, path) {
+            return Some(self.read.alloc(view.to_value()));
+        }
+        // Don't copy whole arrays and objects for their size.
+        let (last, parent) = path.split_last()?;
+        if last.to_str() != "size" {
+            return None;
+        }
+        let parent = get_view(self.view, parent)?;
+        let size = match (parent.as_array(), parent.as_object()) {
+            (Some(array), _) => array.size(),
+            (None, Some(object)) => object.size(),
+            (None, None) => return None,
+        };
+        Some(self.read.alloc(Value::scalar(size as i32)))
+    }
+
+    fn get_variable<'a>(&'a self, path: PathRef<'_, '_>) -> Result<&'a Value> {
+        if let Some(res) = self.try_get_variable(path) {
+            return Ok(res);
+        }
+        for end in (1..path.len()).rev() {
+            let subpath = &path[..end];
+            if let Some(parent) = get_view(self.view, subpath) {
+                let subpath = itertools::join(subpath.iter().map(ScalarCow::render), ".");
+                return Error::with_msg("Unknown index")
+                    .context("variable", subpath)
+                    .context("requested index", format!("{}", path[end].render()))
+                    .context("available indexes", view_indexes(parent))
+                    .into_err();
+            }
+        }
+        Ok(&Value::Nil)
+    }
+}
+
+fn get_view<'o>(view: &'o dyn ObjectView, path: PathRef<'_, '_>) -> Option<&'o dyn ValueView> {
+    let (root, indexes) = path.split_first()?;
+    let value = view.get(root.to_str().as_ref())?;
+    indexes
+        .iter()
+        .try_fold(value, |value, index| get_index(value, index))
+}
+
+/// Index into `value` the way `Value::get` does.
+fn get_index<'v>(value: &'v dyn ValueView, index: &ScalarCow<'_>) -> Option<&'v dyn ValueView> {
+    if let Some(array) = value.as_array() {
+        let size = array.size() as isize;
+        let position = match index.to_integer() {
+            Some(i) if i < 0 => size + i as isize,
+            Some(i) => i as isize,
+            None => match &*index.to_str() {
+                "first" => 0,
+                "last" => size - 1,
+                _ => return None,
+            },
+        };
+        if position < 0 {
+            return None;
+        }
+        array.get(position as usize)
+    } else if let Some(object) = value.as_object() {
+        object.get(index.to_str().as_ref())
+    } else {
+        None
+    }
+}
+
+fn view_indexes(value: &dyn ValueView) -> String {
+    if let Some(array) = value.as_array() {
+        let indexes = (0..array.size())
+            .map(|i| i.to_string())
+            .chain(vec!["first".to_owned(), "last".to_owned()]);
+        itertools::join(indexes, ", ")
+    } else if let Some(object) = value.as_object() {
+        itertools::join(object.keys(), ", ")
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_value::Scalar;
+
+    #[derive(Debug)]
+    struct Page {
+        title: String,
+        tags: Vec<String>,
+    }
+
+    impl ObjectView for Page {
+        fn size(&self) -> usize {
+            2
+        }
+
+        fn keys(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+            Box::new(vec!["title", "tags"].into_iter())
+        }
+
+        fn get(&self, key: &str) -> Option<&dyn ValueView> {
+            match key {
+                "title" => Some(&self.title),
+                "tags" => Some(&self.tags),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn view_store() {
+        let page = Page {
+            title: "Home".to_owned(),
+            tags: vec!["a".to_owned(), "b".to_owned()],
+        };
+        let store = ViewStore::new(&page);
+        assert_eq!(store.roots(), vec!["title", "tags"]);
+
+        let title = [Scalar::new("title")];
+        assert_eq!(store.get_variable(&title).unwrap(), &Value::scalar("Home"));
+        let tag = [Scalar::new("tags"), Scalar::new(-1)];
+        assert_eq!(store.get_variable(&tag).unwrap(), &Value::scalar("b"));
+        let size = [Scalar::new("tags"), Scalar::new("size")];
+        assert_eq!(store.get_variable(&size).unwrap(), &Value::scalar(2));
+
+        let missing = [Scalar::new("tags"), Scalar::new(2)];
+        assert!(store.try_get_variable(&missing).is_none());
+        let error = store.get_variable(&missing).unwrap_err().to_string();
+        assert!(error.contains("available indexes=0, 1, first, last"));
+        assert_eq!(
+            store.get_variable(&[Scalar::new("author")]).unwrap(),
+            &Value::Nil
+        );
+    }
+}
@@ -1,27 +1,58 @@
-use std::io::Write;
+use std::sync::Arc;
 
 use liquid_error::Result;
+use liquid_value::Object;
 
 use super::Context;
+use super::Output;
+use super::References;
 use super::Renderable;
 
 /// An executable template block.
 #[derive(Debug)]
 pub struct Template {
     elements: Vec<Box<dyn Renderable>>,
+    source: Option<Arc<str>>,
 }
 
 impl Template {
     /// Create an executable template block.
     pub fn new(elements: Vec<Box<dyn Renderable>>) -> Template {
-        Template { elements }
+        Template {
+            elements,
+            source: None,
+        }
+    }
+
+    /// Show the line of `source`, the template's source, that render errors are at.
+    pub fn with_source(mut self, source: &str) -> Template {
+        self.source = Some(Arc::from(source));
+        self
+    }
+
+    /// The metadata declared by the top-level elements, later declarations of a key winning.
+    pub fn metadata(&self) -> Object {
+        let mut metadata = Object::new();
+        for el in &self.elements {
+            if let Some(declared) = el.metadata() {
+                metadata.extend(declared.clone());
+            }
+        }
+        metadata
     }
 }
 
 impl Renderable for Template {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context<'_>) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context<'_>) -> Result<()> {
         for el in &self.elements {
-            el.render_to(writer, context)?;
+            context.check_render_time()?;
+            if let Err(error) = el.render_to(writer, context) {
+                let error = match self.source {
+                    Some(ref source) => error.with_source(source),
+                    None => error,
+                };
+                context.recover(error, writer)?;
+            }
 
             // Did the last element we processed set an interrupt? If so, we
             // need to abandon the rest of our child elements and just
@@ -33,4 +64,10 @@ impl Renderable for Template {
         }
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        for el in &self.elements {
+            el.references(references);
+        }
+    }
 }
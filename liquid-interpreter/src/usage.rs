@@ -0,0 +1,67 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What a render used: the filters it ran, the partials it rendered and the variables it found
+/// undefined.
+///
+/// Everything is sorted, so the usage of renders of the same template with the same globals is
+/// the same.  See `Context::record_usage`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    undefined: BTreeSet<String>,
+    filters: BTreeMap<String, usize>,
+    partials: BTreeSet<String>,
+}
+
+impl Usage {
+    /// Create an empty usage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The paths of the variables that were read but not defined, e.g. `user.name`.
+    pub fn undefined(&self) -> impl Iterator<Item = &str> {
+        self.undefined.iter().map(|s| s.as_str())
+    }
+
+    /// The filters run, with how many times each was.
+    pub fn filters(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.filters
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+    }
+
+    /// The names of the partials rendered.
+    pub fn partials(&self) -> impl Iterator<Item = &str> {
+        self.partials.iter().map(|s| s.as_str())
+    }
+
+    /// Record reading the undefined variable at `path`.
+    pub fn add_undefined<S: Into<String>>(&mut self, path: S) {
+        self.undefined.insert(path.into());
+    }
+
+    /// Record running the filter `name`.
+    pub fn add_filter(&mut self, name: &str) {
+        *self.filters.entry(name.to_owned()).or_insert(0) += 1;
+    }
+
+    /// Record rendering the partial `name`.
+    pub fn add_partial<S: Into<String>>(&mut self, name: S) {
+        self.partials.insert(name.into());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn usage_counts_filters() {
+        let mut usage = Usage::new();
+        usage.add_filter("upcase");
+        usage.add_filter("append");
+        usage.add_filter("upcase");
+        let filters: Vec<_> = usage.filters().collect();
+        assert_eq!(filters, vec![("append", 1), ("upcase", 2)]);
+    }
+}
@@ -29,6 +29,16 @@ impl Variable {
         self
     }
 
+    /// The name of the variable.
+    pub(crate) fn root(&self) -> &Scalar {
+        &self.variable
+    }
+
+    /// The indexes into the variable, in order.
+    pub(crate) fn indexes(&self) -> &[Expression] {
+        &self.indexes
+    }
+
     /// Convert to a `Path`.
     pub fn try_evaluate<'c>(&'c self, context: &'c Context<'_>) -> Option<Path<'c>> {
         let mut path = Path::with_index(self.variable.as_ref());
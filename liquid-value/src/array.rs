@@ -0,0 +1,157 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops;
+use std::slice;
+use std::sync::Arc;
+use std::vec;
+
+use serde::{de, ser};
+
+use super::Value;
+
+/// Type representing a Liquid array, payload of the `Value::Array` variant
+///
+/// Arrays share their elements when cloned, copying them only once a clone is modified, so
+/// cloning arrays, e.g. in filters sorting or filtering them, is cheap.  An array is used as the
+/// `Vec` of its elements.
+#[derive(Clone, Default, PartialEq)]
+pub struct Array(Arc<Vec<Value>>);
+
+impl Array {
+    /// Create an empty array.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The elements, copied unless this is the only clone of the array.
+    pub fn into_vec(self) -> Vec<Value> {
+        Arc::try_unwrap(self.0).unwrap_or_else(|values| (*values).clone())
+    }
+
+    /// Whether both arrays are clones of the same array, sharing their elements.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl ops::Deref for Array {
+    type Target = Vec<Value>;
+
+    #[inline]
+    fn deref(&self) -> &Vec<Value> {
+        &self.0
+    }
+}
+
+/// Copies the elements if they are shared with other clones.
+impl ops::DerefMut for Array {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Vec<Value> {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+impl fmt::Debug for Array {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq<Vec<Value>> for Array {
+    fn eq(&self, other: &Vec<Value>) -> bool {
+        *self.0 == *other
+    }
+}
+
+impl From<Vec<Value>> for Array {
+    #[inline]
+    fn from(values: Vec<Value>) -> Self {
+        Array(Arc::new(values))
+    }
+}
+
+impl From<Array> for Vec<Value> {
+    #[inline]
+    fn from(array: Array) -> Self {
+        array.into_vec()
+    }
+}
+
+impl FromIterator<Value> for Array {
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        Array(Arc::new(iter.into_iter().collect()))
+    }
+}
+
+impl Extend<Value> for Array {
+    fn extend<T: IntoIterator<Item = Value>>(&mut self, iter: T) {
+        Arc::make_mut(&mut self.0).extend(iter)
+    }
+}
+
+impl IntoIterator for Array {
+    type Item = Value;
+    type IntoIter = vec::IntoIter<Value>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Array {
+    type Item = &'a Value;
+    type IntoIter = slice::Iter<'a, Value>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Array {
+    type Item = &'a mut Value;
+    type IntoIter = slice::IterMut<'a, Value>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        Arc::make_mut(&mut self.0).iter_mut()
+    }
+}
+
+impl ser::Serialize for Array {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Array {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer).map(Array::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clones_share_until_modified() {
+        let array: Array = vec![Value::scalar(1), Value::scalar(2)].into();
+        let mut clone = array.clone();
+        assert!(clone.ptr_eq(&array));
+
+        clone.push(Value::scalar(3));
+        assert!(!clone.ptr_eq(&array));
+        assert_eq!(array.len(), 2);
+        assert_eq!(clone.len(), 3);
+    }
+}
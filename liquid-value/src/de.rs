@@ -0,0 +1,342 @@
+#[cfg(feature = "json")]
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+
+use super::Value;
+use crate::error;
+
+/// Convert a `liquid_value::Value` into a `T`.
+///
+/// # Examples
+///
+/// ```rust
+/// #[derive(serde::Deserialize)]
+/// struct Product {
+///     title: String,
+///     price: f64,
+/// }
+///
+/// let value = liquid_value::liquid_value!({"title": "Hat", "price": 9.5});
+/// let product: Product = liquid_value::from_value(value).unwrap();
+/// assert_eq!(product.title, "Hat");
+/// assert_eq!(product.price, 9.5);
+///
+/// let value = liquid_value::liquid_value!({"title": "Hat", "price": "cheap"});
+/// assert!(liquid_value::from_value::<Product>(value).is_err());
+/// ```
+///
+/// Ranges and lazy arrays are read as the arrays of their elements.  Dates are read as the
+/// strings they serialize as.
+pub fn from_value<T>(value: Value) -> Result<T, error::Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(value).map_err(|e| e.0)
+}
+
+/// The error of deserializing a `Value`, see `from_value`.
+#[derive(Debug)]
+pub struct DeError(error::Error);
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.0)
+    }
+}
+
+impl ::std::error::Error for DeError {
+    fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
+        ::std::error::Error::source(&self.0)
+    }
+}
+
+impl de::Error for DeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        DeError(error::Error::with_msg(format!("{}", msg)))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = DeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.into_materialized() {
+            Value::Scalar(scalar) => {
+                if let Some(number) = scalar.as_number() {
+                    match number {
+                        super::Number::Integer(x) => visitor.visit_i32(x),
+                        super::Number::Float(x) => visitor.visit_f64(x),
+                    }
+                } else if let Some(x) = scalar.to_bool() {
+                    visitor.visit_bool(x)
+                } else if scalar.as_str().is_some() {
+                    visitor.visit_string(scalar.into_string())
+                } else {
+                    // Dates, which deserialize from the strings they serialize as.
+                    crate::to_value(&scalar)
+                        .map_err(DeError)?
+                        .deserialize_any(visitor)
+                }
+            }
+            Value::Array(array) => {
+                let mut seq = de::value::SeqDeserializer::new(array.into_vec().into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Object(object) => {
+                let entries = object
+                    .into_iter()
+                    .map(|(key, value)| (key.as_str().to_owned(), value));
+                let mut map = de::value::MapDeserializer::new(entries);
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            Value::Nil | Value::Empty | Value::Blank => visitor.visit_unit(),
+            Value::Range(_) | Value::Lazy(_) => unreachable!("materialized above"),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil | Value::Empty | Value::Blank => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        // Unit variants are strings, others objects of a single variant.
+        let (variant, value) = match self {
+            Value::Scalar(scalar) if scalar.as_str().is_some() => (scalar.into_string(), None),
+            Value::Object(object) if object.len() == 1 => {
+                let (variant, value) = object.into_iter().next().expect("one entry");
+                (variant.as_str().to_owned(), Some(value))
+            }
+            other => {
+                return Err(de::Error::invalid_type(
+                    de::Unexpected::Other(other.type_name()),
+                    &"a string or an object of a single entry",
+                ));
+            }
+        };
+        visitor.visit_enum(EnumDeserializer { variant, value })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<Value>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = DeError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, VariantDeserializer), DeError>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<Value>,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        match self.value {
+            None => Ok(()),
+            Some(value) => de::Deserialize::deserialize(value),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, DeError>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(value) => de::Deserializer::deserialize_any(value, visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(value) => de::Deserializer::deserialize_any(value, visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+/// Whole numbers that fit an `i32` become whole numbers, other numbers fractional ones.
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Nil,
+            serde_json::Value::Bool(x) => Value::scalar(x),
+            serde_json::Value::Number(x) => match x.as_i64().map(i32::try_from) {
+                Some(Ok(x)) => Value::scalar(x),
+                _ => Value::scalar(x.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(x) => Value::scalar(x),
+            serde_json::Value::Array(x) => Value::array(x.into_iter().map(Value::from)),
+            serde_json::Value::Object(x) => Value::Object(
+                x.into_iter()
+                    .map(|(key, value)| (key.into(), Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Converts values like serializing them: dates become strings, non-finite numbers `null`.
+#[cfg(feature = "json")]
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        serde_json::to_value(value).expect("keys of objects are strings")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Kind {
+        Simple,
+        Sized(i32),
+        Named { name: String },
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Item {
+        title: String,
+        count: u8,
+        tags: Vec<String>,
+        discount: Option<f64>,
+        kinds: Vec<Kind>,
+        extra: HashMap<String, bool>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let item = Item {
+            title: "Hat".to_owned(),
+            count: 3,
+            tags: vec!["a".to_owned(), "b".to_owned()],
+            discount: None,
+            kinds: vec![
+                Kind::Simple,
+                Kind::Sized(2),
+                Kind::Named {
+                    name: "x".to_owned(),
+                },
+            ],
+            extra: vec![("sale".to_owned(), true)].into_iter().collect(),
+        };
+        let value = crate::to_value(&item).unwrap();
+        assert_eq!(value.as_object().unwrap()["count"], Value::scalar(3));
+        assert_eq!(from_value::<Item>(value).unwrap(), item);
+    }
+
+    #[test]
+    fn materialized() {
+        let value = Value::Range(crate::Range::new(1, 3));
+        assert_eq!(from_value::<Vec<i64>>(value).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn errors() {
+        let value = liquid_value!({"title": "Hat", "count": 300});
+        let error = from_value::<Item>(value).unwrap_err();
+        assert!(error.to_string().contains("300"), "{}", error);
+        assert!(from_value::<Kind>(Value::scalar("Unknown")).is_err());
+        assert!(from_value::<Kind>(Value::scalar(1)).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        let json = serde_json::json!({
+            "a": [1, 2.5, 3_000_000_000u64, null, true],
+            "b": {"c": "d"},
+        });
+        let value = Value::from(json);
+        assert_eq!(
+            value,
+            liquid_value!({"a": [1, 2.5, 3_000_000_000f64, nil, true], "b": {"c": "d"}})
+        );
+        assert_eq!(
+            serde_json::Value::from(value),
+            serde_json::json!({"a": [1, 2.5, 3_000_000_000f64, null, true], "b": {"c": "d"}})
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::{de, ser};
+
+use super::Scalar;
+
+/// The key of an entry of an `Object`.
+///
+/// Keys are cheap to clone.  Keys made with `Key::intern`, like the names a template reads, share
+/// their string with every equal interned key, so comparing equal keys compares pointers.
+///
+/// Interning takes a lock shared by all threads, so it is meant for parsing templates, not for
+/// the keys of data read or written at every render: serializing and deserializing keys doesn't
+/// intern them.
+#[derive(Clone)]
+pub struct Key(KeyRepr);
+
+#[derive(Clone)]
+enum KeyRepr {
+    Static(&'static str),
+    Shared(Arc<str>),
+}
+
+impl Key {
+    /// Create a key from a string that lives for the whole program.
+    pub fn from_static(value: &'static str) -> Self {
+        Key(KeyRepr::Static(value))
+    }
+
+    /// Create a key sharing its string with the other interned keys equal to `value`.
+    ///
+    /// Strings no key uses anymore are eventually dropped from the interned strings.
+    pub fn intern(value: &str) -> Self {
+        Key(KeyRepr::Shared(interned(value)))
+    }
+
+    /// The key as a string.
+    pub fn as_str(&self) -> &str {
+        match self.0 {
+            KeyRepr::Static(x) => x,
+            KeyRepr::Shared(ref x) => x.as_ref(),
+        }
+    }
+
+    /// Convert to a string.
+    pub fn into_string(self) -> String {
+        self.as_str().to_owned()
+    }
+}
+
+/// Interned strings are dropped once only the interner holds them, checked when their number
+/// doubles.
+struct Interner {
+    strings: HashSet<Arc<str>>,
+    purge_at: usize,
+}
+
+const MIN_PURGE_AT: usize = 1024;
+
+fn interned(value: &str) -> Arc<str> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    let interner = INTERNER.get_or_init(|| {
+        Mutex::new(Interner {
+            strings: HashSet::new(),
+            purge_at: MIN_PURGE_AT,
+        })
+    });
+    let mut interner = interner.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(string) = interner.strings.get(value) {
+        return string.clone();
+    }
+    if interner.purge_at <= interner.strings.len() {
+        interner.strings.retain(|s| 1 < Arc::strong_count(s));
+        interner.purge_at = MIN_PURGE_AT.max(interner.strings.len() * 2);
+    }
+    let string: Arc<str> = Arc::from(value);
+    interner.strings.insert(string.clone());
+    string
+}
+
+impl PartialEq for Key {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        let (lhs, rhs) = (self.as_str(), other.as_str());
+        (lhs.as_ptr() == rhs.as_ptr() && lhs.len() == rhs.len()) || lhs == rhs
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialEq<str> for Key {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'s> PartialEq<&'s str> for Key {
+    #[inline]
+    fn eq(&self, other: &&'s str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for Key {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for Key {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl ops::Deref for Key {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Key {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Key {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl From<&'static str> for Key {
+    fn from(value: &'static str) -> Self {
+        Key::from_static(value)
+    }
+}
+
+impl From<String> for Key {
+    fn from(value: String) -> Self {
+        Key(KeyRepr::Shared(Arc::from(value)))
+    }
+}
+
+impl From<Cow<'static, str>> for Key {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(x) => Key::from_static(x),
+            Cow::Owned(x) => Key::from(x),
+        }
+    }
+}
+
+impl From<Arc<str>> for Key {
+    fn from(value: Arc<str>) -> Self {
+        Key(KeyRepr::Shared(value))
+    }
+}
+
+impl From<Key> for Scalar {
+    /// The scalar shares the string of the key.
+    fn from(value: Key) -> Self {
+        match value.0 {
+            KeyRepr::Static(x) => Scalar::new(x),
+            KeyRepr::Shared(x) => Scalar::shared_str(x),
+        }
+    }
+}
+
+impl From<Key> for String {
+    fn from(value: Key) -> Self {
+        value.into_string()
+    }
+}
+
+impl ser::Serialize for Key {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Key {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Key;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            #[inline]
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Key::from(value))
+            }
+
+            #[inline]
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Key::from(value.to_owned()))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn interned_keys_share_their_string() {
+        let title = Key::intern("title");
+        let other = Key::intern(&String::from("title"));
+        assert_eq!(title.as_ptr(), other.as_ptr());
+        assert_eq!(title, Key::from("title"));
+        assert_eq!(title, "title");
+        assert_ne!(title, Key::intern("price"));
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde::ser::SerializeSeq;
+
+use super::Array;
+use super::Value;
+
+type ElementsFn = dyn Fn() -> Box<dyn Iterator<Item = Value>> + Send + Sync;
+
+/// An array whose elements are generated each time it is read, like the rows of a query, so they
+/// are never all in memory.
+///
+/// `for` loops, `size`, `first` and `last`, and the filters that take their input element by
+/// element, like `map` and `where`, read the elements one at a time.  Every other use of the
+/// array, like filters that need all of its elements at once (e.g. `sort` or `reverse`),
+/// comparing it or outputting it, first collects its elements into an array, as for ranges.
+///
+/// ```rust
+/// use liquid_value::{LazyArray, Value};
+///
+/// let rows = LazyArray::new(|| (1..=3).map(Value::scalar));
+/// assert_eq!(rows.len(), 3);
+/// assert_eq!(rows.last(), Some(Value::scalar(3)));
+/// ```
+#[derive(Clone)]
+pub struct LazyArray {
+    elements: Arc<ElementsFn>,
+    len: Option<usize>,
+}
+
+impl LazyArray {
+    /// Create the array of the elements `elements` generates, each time it is read.
+    pub fn new<F, I>(elements: F) -> Self
+    where
+        F: Fn() -> I + Send + Sync + 'static,
+        I: IntoIterator<Item = Value>,
+        I::IntoIter: 'static,
+    {
+        let elements = move || Box::new(elements().into_iter()) as Box<dyn Iterator<Item = Value>>;
+        Self {
+            elements: Arc::new(elements),
+            len: None,
+        }
+    }
+
+    /// Set how many elements there are, so it isn't counted by generating them.
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    /// Generate the elements.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = Value>> {
+        (self.elements)()
+    }
+
+    /// How many elements there are, counted by generating them unless set with `with_len`.
+    pub fn len(&self) -> usize {
+        self.len.unwrap_or_else(|| self.iter().count())
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        match self.len {
+            Some(len) => len == 0,
+            None => self.iter().next().is_none(),
+        }
+    }
+
+    /// The first element, if any.
+    pub fn first(&self) -> Option<Value> {
+        self.iter().next()
+    }
+
+    /// The last element, if any.
+    pub fn last(&self) -> Option<Value> {
+        self.iter().last()
+    }
+
+    /// The element at `index`, counting from the end for negative indexes, as for arrays.
+    pub fn get(&self, index: i32) -> Option<Value> {
+        let index = if index < 0 {
+            self.len() as i64 + i64::from(index)
+        } else {
+            i64::from(index)
+        };
+        if index < 0 {
+            return None;
+        }
+        self.iter().nth(index as usize)
+    }
+
+    /// The elements, as an array.
+    pub fn to_array(&self) -> Array {
+        self.iter().collect()
+    }
+}
+
+impl fmt::Debug for LazyArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyArray").field("len", &self.len).finish()
+    }
+}
+
+/// Serialized as the array of its elements.
+impl serde::Serialize for LazyArray {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut seq = serializer.serialize_seq(self.len)?;
+        for element in self.iter() {
+            seq.serialize_element(&element)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn elements_are_generated_when_read() {
+        let generated = Arc::new(AtomicUsize::new(0));
+        let counter = generated.clone();
+        let array = LazyArray::new(move || {
+            let counter = counter.clone();
+            (1..=1_000_000).map(move |i| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Value::scalar(i)
+            })
+        });
+        assert_eq!(array.first(), Some(Value::scalar(1)));
+        assert_eq!(array.get(9), Some(Value::scalar(10)));
+        assert_eq!(generated.load(Ordering::SeqCst), 11);
+
+        assert_eq!(array.get(-1), Some(Value::scalar(1_000_000)));
+        assert_eq!(array.clone().with_len(1_000_000).len(), 1_000_000);
+        assert!(!array.is_empty());
+        assert!(LazyArray::new(Vec::new).is_empty());
+    }
+}
@@ -10,18 +10,32 @@ extern crate serde;
 #[macro_use]
 mod macros;
 
+mod array;
+mod de;
+mod key;
+mod lazy_array;
 pub mod map;
+mod number;
 mod path;
+mod range;
 mod scalar;
 mod ser;
 mod values;
+mod view;
 
 /// Liquid Processing Errors.
 pub mod error {
     pub use liquid_error::*;
 }
 
+pub use crate::array::*;
+pub use crate::de::*;
+pub use crate::key::*;
+pub use crate::lazy_array::*;
+pub use crate::number::*;
 pub use crate::path::*;
+pub use crate::range::*;
 pub use crate::scalar::*;
 pub use crate::ser::*;
 pub use crate::values::*;
+pub use crate::view::*;
@@ -203,11 +203,11 @@ macro_rules! value_internal {
     };
 
     ([]) => {
-        $crate::Value::Array(value_internal_vec![])
+        $crate::Value::array(value_internal_vec![])
     };
 
     ([ $($tt:tt)+ ]) => {
-        $crate::Value::Array(value_internal!(@array [] $($tt)+))
+        $crate::Value::array(value_internal!(@array [] $($tt)+))
     };
 
     ({}) => {
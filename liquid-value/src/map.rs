@@ -2,25 +2,28 @@
 //!
 //! This is to abstract the choice of map from the user so it can be changed without breaking
 //! compatibility.
+//!
+//! Maps share their entries when cloned, copying them only once a clone is modified, so cloning
+//! objects, e.g. in filters passing them through, is cheap.
 
-use std::borrow::{Borrow, Cow};
+use std::borrow::Borrow;
 use std::collections::hash_map;
 use std::fmt::{self, Debug};
 use std::hash::Hash;
 use std::iter::FromIterator;
 use std::ops;
+use std::sync::Arc;
 
 use serde::{de, ser};
 
+use super::Key;
 use super::Value;
 
 /// Type representing a Liquid object, payload of the `Value::Map` variant
 pub struct Map {
-    map: MapImpl<Key, Value>,
+    map: Arc<MapImpl<Key, Value>>,
 }
 
-type Key = Cow<'static, str>;
-
 type MapImpl<K, V> = hash_map::HashMap<K, V>;
 type VacantEntryImpl<'a> = hash_map::VacantEntry<'a, Key, Value>;
 type OccupiedEntryImpl<'a> = hash_map::OccupiedEntry<'a, Key, Value>;
@@ -36,14 +39,14 @@ impl Map {
     #[inline]
     pub fn new() -> Self {
         Map {
-            map: MapImpl::new(),
+            map: Arc::new(MapImpl::new()),
         }
     }
 
     /// Clears the map, removing all values.
     #[inline]
     pub fn clear(&mut self) {
-        self.map.clear()
+        Arc::make_mut(&mut self.map).clear()
     }
 
     /// Returns a reference to the value corresponding to the key.
@@ -82,7 +85,7 @@ impl Map {
         Key: Borrow<Q>,
         Q: Ord + Eq + Hash,
     {
-        self.map.get_mut(key)
+        Arc::make_mut(&mut self.map).get_mut(key)
     }
 
     /// Inserts a key-value pair into the map.
@@ -93,7 +96,7 @@ impl Map {
     /// value is returned.
     #[inline]
     pub fn insert(&mut self, k: Key, v: Value) -> Option<Value> {
-        self.map.insert(k, v)
+        Arc::make_mut(&mut self.map).insert(k, v)
     }
 
     /// Removes a key from the map, returning the value at the key if the key
@@ -107,7 +110,7 @@ impl Map {
         Key: Borrow<Q>,
         Q: Ord + Eq + Hash,
     {
-        self.map.remove(key)
+        Arc::make_mut(&mut self.map).remove(key)
     }
 
     /// Gets the given key's corresponding entry in the map for in-place
@@ -117,7 +120,7 @@ impl Map {
         S: Into<Key>,
     {
         use std::collections::hash_map::Entry as EntryImpl;
-        match self.map.entry(key.into()) {
+        match Arc::make_mut(&mut self.map).entry(key.into()) {
             EntryImpl::Vacant(vacant) => Entry::Vacant(VacantEntry { vacant }),
             EntryImpl::Occupied(occupied) => Entry::Occupied(OccupiedEntry { occupied }),
         }
@@ -147,7 +150,7 @@ impl Map {
     #[inline]
     pub fn iter_mut(&mut self) -> IterMut<'_> {
         IterMut {
-            iter: self.map.iter_mut(),
+            iter: Arc::make_mut(&mut self.map).iter_mut(),
         }
     }
 
@@ -171,7 +174,7 @@ impl Map {
     #[inline]
     pub fn values_mut(&mut self) -> ValuesMut<'_> {
         ValuesMut {
-            iter: self.map.values_mut(),
+            iter: Arc::make_mut(&mut self.map).values_mut(),
         }
     }
 }
@@ -180,7 +183,7 @@ impl Default for Map {
     #[inline]
     fn default() -> Self {
         Map {
-            map: MapImpl::new(),
+            map: Arc::new(MapImpl::new()),
         }
     }
 }
@@ -197,7 +200,7 @@ impl Clone for Map {
 impl PartialEq for Map {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.map.eq(&other.map)
+        Arc::ptr_eq(&self.map, &other.map) || self.map.eq(&other.map)
     }
 }
 
@@ -251,7 +254,9 @@ where
     Q: Ord + Eq + Hash,
 {
     fn index_mut(&mut self, index: &Q) -> &mut Value {
-        self.map.get_mut(index).expect("no entry found for key")
+        Arc::make_mut(&mut self.map)
+            .get_mut(index)
+            .expect("no entry found for key")
     }
 }
 
@@ -326,7 +331,7 @@ impl FromIterator<(Key, Value)> for Map {
         T: IntoIterator<Item = (Key, Value)>,
     {
         Map {
-            map: FromIterator::from_iter(iter),
+            map: Arc::new(FromIterator::from_iter(iter)),
         }
     }
 }
@@ -336,7 +341,7 @@ impl Extend<(Key, Value)> for Map {
     where
         T: IntoIterator<Item = (Key, Value)>,
     {
-        self.map.extend(iter);
+        Arc::make_mut(&mut self.map).extend(iter);
     }
 }
 
@@ -711,7 +716,7 @@ impl<'a> IntoIterator for &'a mut Map {
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
         IterMut {
-            iter: self.map.iter_mut(),
+            iter: Arc::make_mut(&mut self.map).iter_mut(),
         }
     }
 }
@@ -732,7 +737,9 @@ impl IntoIterator for Map {
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
         IntoIter {
-            iter: self.map.into_iter(),
+            iter: Arc::try_unwrap(self.map)
+                .unwrap_or_else(|map| (*map).clone())
+                .into_iter(),
         }
     }
 }
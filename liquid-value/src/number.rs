@@ -0,0 +1,323 @@
+use liquid_error::{Error, Result};
+
+/// What arithmetic does when a whole number result is out of range.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Fail the render.
+    #[default]
+    Error,
+    /// Wrap around the range of whole numbers, e.g. the largest one plus one is the smallest.
+    Wrap,
+    /// Clamp the result to the nearest whole number in range.
+    Saturate,
+    /// Compute the result as a fractional number instead.
+    Promote,
+}
+
+/// How arithmetic divides a whole number by a whole number.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum IntegerDivision {
+    /// Round towards zero to a whole number, like Ruby Liquid, e.g. `7 | divided_by: 2` is `3`.
+    #[default]
+    Truncate,
+    /// Keep the fractional part, e.g. `7 | divided_by: 2` is `3.5`.
+    Float,
+}
+
+/// How arithmetic on whole numbers is done, shared by the math filters and arithmetic
+/// expressions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NumericPolicy {
+    overflow: OverflowPolicy,
+    integer_division: IntegerDivision,
+}
+
+impl NumericPolicy {
+    /// Error on overflow and truncate division, like Ruby Liquid.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set what is done when a whole number result is out of range.
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Set how a whole number is divided by a whole number.
+    pub fn with_integer_division(mut self, division: IntegerDivision) -> Self {
+        self.integer_division = division;
+        self
+    }
+
+    /// What is done when a whole number result is out of range.
+    pub fn overflow(&self) -> OverflowPolicy {
+        self.overflow
+    }
+
+    /// How a whole number is divided by a whole number.
+    pub fn integer_division(&self) -> IntegerDivision {
+        self.integer_division
+    }
+}
+
+/// A whole or fractional number, e.g. an operand of arithmetic.
+///
+/// Whole numbers make whole numbers, following a `NumericPolicy`, and any fractional number
+/// makes a fractional number.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Number {
+    /// A whole number.
+    Integer(i32),
+    /// A fractional number.
+    Float(f64),
+}
+
+type IntegerOp = fn(i32, i32) -> Option<i32>;
+type WrappingOp = fn(i32, i32) -> i32;
+type FloatOp = fn(f64, f64) -> f64;
+
+impl Number {
+    /// The number as a fractional number.
+    pub fn to_float(self) -> f64 {
+        match self {
+            Number::Integer(x) => f64::from(x),
+            Number::Float(x) => x,
+        }
+    }
+
+    /// `self + rhs`.
+    pub fn plus(self, rhs: Number, policy: NumericPolicy) -> Result<Number> {
+        self.apply(
+            rhs,
+            policy,
+            (i32::checked_add, i32::wrapping_add, i32::saturating_add),
+            |a, b| a + b,
+        )
+    }
+
+    /// `self - rhs`.
+    pub fn minus(self, rhs: Number, policy: NumericPolicy) -> Result<Number> {
+        self.apply(
+            rhs,
+            policy,
+            (i32::checked_sub, i32::wrapping_sub, i32::saturating_sub),
+            |a, b| a - b,
+        )
+    }
+
+    /// `self * rhs`.
+    pub fn times(self, rhs: Number, policy: NumericPolicy) -> Result<Number> {
+        self.apply(
+            rhs,
+            policy,
+            (i32::checked_mul, i32::wrapping_mul, i32::saturating_mul),
+            |a, b| a * b,
+        )
+    }
+
+    /// `self / rhs`, dividing whole numbers according to the policy's `IntegerDivision`.
+    pub fn divided_by(self, rhs: Number, policy: NumericPolicy) -> Result<Number> {
+        check_divisor(rhs)?;
+        if policy.integer_division() == IntegerDivision::Float {
+            return Ok(Number::Float(self.to_float() / rhs.to_float()));
+        }
+        self.apply(
+            rhs,
+            policy,
+            (i32::checked_div, i32::wrapping_div, i32::saturating_div),
+            |a, b| a / b,
+        )
+    }
+
+    /// The remainder of `self / rhs`, with the sign of `self`.
+    pub fn modulo(self, rhs: Number, _policy: NumericPolicy) -> Result<Number> {
+        check_divisor(rhs)?;
+        match (self, rhs) {
+            // Only the smallest whole number modulo -1 overflows, and its remainder is 0 anyway.
+            (Number::Integer(lhs), Number::Integer(rhs)) => {
+                Ok(Number::Integer(lhs.wrapping_rem(rhs)))
+            }
+            (lhs, rhs) => Ok(Number::Float(lhs.to_float() % rhs.to_float())),
+        }
+    }
+
+    /// The absolute value of `self`.
+    pub fn abs(self, policy: NumericPolicy) -> Result<Number> {
+        let x = match self {
+            Number::Integer(x) => x,
+            Number::Float(x) => return Ok(Number::Float(x.abs())),
+        };
+        if let Some(result) = x.checked_abs() {
+            return Ok(Number::Integer(result));
+        }
+        match policy.overflow() {
+            OverflowPolicy::Error => Error::with_msg("Integer overflow")
+                .context("input", x.to_string())
+                .into_err(),
+            OverflowPolicy::Wrap => Ok(Number::Integer(x.wrapping_abs())),
+            OverflowPolicy::Saturate => Ok(Number::Integer(x.saturating_abs())),
+            OverflowPolicy::Promote => Ok(Number::Float(f64::from(x).abs())),
+        }
+    }
+
+    /// A fractional number that has been rounded, e.g. by `floor`, as a whole number.
+    pub fn whole(x: f64, policy: NumericPolicy) -> Result<Number> {
+        if x.is_finite() && f64::from(i32::MIN) <= x && x <= f64::from(i32::MAX) {
+            return Ok(Number::Integer(x as i32));
+        }
+        match policy.overflow() {
+            OverflowPolicy::Wrap if x.is_finite() => {
+                // Keep the low 32 bits, like wrapping whole number arithmetic does.
+                Ok(Number::Integer(x.rem_euclid(4_294_967_296.0) as u32 as i32))
+            }
+            OverflowPolicy::Saturate => Ok(Number::Integer(x as i32)),
+            OverflowPolicy::Promote => Ok(Number::Float(x)),
+            OverflowPolicy::Error | OverflowPolicy::Wrap => Error::with_msg("Integer overflow")
+                .context("input", x.to_string())
+                .into_err(),
+        }
+    }
+
+    fn apply(
+        self,
+        rhs: Number,
+        policy: NumericPolicy,
+        (checked, wrapping, saturating): (IntegerOp, WrappingOp, WrappingOp),
+        float: FloatOp,
+    ) -> Result<Number> {
+        let (lhs, rhs) = match (self, rhs) {
+            (Number::Integer(lhs), Number::Integer(rhs)) => (lhs, rhs),
+            (lhs, rhs) => return Ok(Number::Float(float(lhs.to_float(), rhs.to_float()))),
+        };
+        if let Some(result) = checked(lhs, rhs) {
+            return Ok(Number::Integer(result));
+        }
+        match policy.overflow() {
+            OverflowPolicy::Error => Error::with_msg("Integer overflow")
+                .context("lhs", lhs.to_string())
+                .context("rhs", rhs.to_string())
+                .into_err(),
+            OverflowPolicy::Wrap => Ok(Number::Integer(wrapping(lhs, rhs))),
+            OverflowPolicy::Saturate => Ok(Number::Integer(saturating(lhs, rhs))),
+            OverflowPolicy::Promote => Ok(Number::Float(float(f64::from(lhs), f64::from(rhs)))),
+        }
+    }
+}
+
+fn check_divisor(rhs: Number) -> Result<()> {
+    if rhs.to_float() == 0.0 {
+        return Error::with_msg("Division by zero").into_err();
+    }
+    Ok(())
+}
+
+impl From<i32> for Number {
+    fn from(x: i32) -> Self {
+        Number::Integer(x)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(x: f64) -> Self {
+        Number::Float(x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn whole_numbers_stay_whole() {
+        let policy = NumericPolicy::new();
+        let seven = Number::Integer(7);
+        let two = Number::Integer(2);
+        assert_eq!(seven.plus(two, policy).unwrap(), Number::Integer(9));
+        assert_eq!(seven.minus(two, policy).unwrap(), Number::Integer(5));
+        assert_eq!(seven.times(two, policy).unwrap(), Number::Integer(14));
+        assert_eq!(seven.divided_by(two, policy).unwrap(), Number::Integer(3));
+        assert_eq!(seven.modulo(two, policy).unwrap(), Number::Integer(1));
+        assert_eq!(
+            seven.divided_by(Number::Float(2.0), policy).unwrap(),
+            Number::Float(3.5)
+        );
+        assert!(seven.divided_by(Number::Integer(0), policy).is_err());
+        assert!(seven.modulo(Number::Float(0.0), policy).is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        let max = Number::Integer(i32::MAX);
+        let one = Number::Integer(1);
+        let policy = NumericPolicy::new();
+        assert!(max.plus(one, policy).is_err());
+
+        let policy = policy.with_overflow(OverflowPolicy::Wrap);
+        assert_eq!(max.plus(one, policy).unwrap(), Number::Integer(i32::MIN));
+
+        let policy = policy.with_overflow(OverflowPolicy::Saturate);
+        assert_eq!(max.times(max, policy).unwrap(), max);
+
+        let policy = policy.with_overflow(OverflowPolicy::Promote);
+        assert_eq!(
+            max.plus(one, policy).unwrap(),
+            Number::Float(f64::from(i32::MAX) + 1.0)
+        );
+    }
+
+    #[test]
+    fn abs_overflow() {
+        let min = Number::Integer(i32::MIN);
+        let policy = NumericPolicy::new();
+        assert_eq!(Number::Integer(-7).abs(policy).unwrap(), Number::Integer(7));
+        assert!(min.abs(policy).is_err());
+
+        let policy = policy.with_overflow(OverflowPolicy::Wrap);
+        assert_eq!(min.abs(policy).unwrap(), min);
+
+        let policy = policy.with_overflow(OverflowPolicy::Saturate);
+        assert_eq!(min.abs(policy).unwrap(), Number::Integer(i32::MAX));
+
+        let policy = policy.with_overflow(OverflowPolicy::Promote);
+        assert_eq!(
+            min.abs(policy).unwrap(),
+            Number::Float(-f64::from(i32::MIN))
+        );
+    }
+
+    #[test]
+    fn whole_overflow() {
+        let policy = NumericPolicy::new();
+        assert_eq!(Number::whole(-3.0, policy).unwrap(), Number::Integer(-3));
+        assert!(Number::whole(3e9, policy).is_err());
+        assert!(Number::whole(f64::NAN, policy).is_err());
+
+        let policy = policy.with_overflow(OverflowPolicy::Wrap);
+        assert_eq!(
+            Number::whole(2_147_483_648.0, policy).unwrap(),
+            Number::Integer(i32::MIN)
+        );
+        assert!(Number::whole(f64::INFINITY, policy).is_err());
+
+        let policy = policy.with_overflow(OverflowPolicy::Saturate);
+        assert_eq!(
+            Number::whole(3e9, policy).unwrap(),
+            Number::Integer(i32::MAX)
+        );
+
+        let policy = policy.with_overflow(OverflowPolicy::Promote);
+        assert_eq!(Number::whole(3e9, policy).unwrap(), Number::Float(3e9));
+    }
+
+    #[test]
+    fn float_division() {
+        let policy = NumericPolicy::new().with_integer_division(IntegerDivision::Float);
+        assert_eq!(
+            Number::Integer(7)
+                .divided_by(Number::Integer(2), policy)
+                .unwrap(),
+            Number::Float(3.5)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use serde::ser::SerializeSeq;
+
+use super::Value;
+
+/// The whole numbers from a start to an end, inclusive, like `(1..5)`.
+///
+/// A range is not materialized into an array of its numbers unless asked to, so large ones are
+/// cheap to loop over and to measure.  A range whose end is before its start is empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Range {
+    start: i32,
+    end: i32,
+}
+
+impl Range {
+    /// Create the range from `start` to `end`, inclusive.
+    pub fn new(start: i32, end: i32) -> Self {
+        Self { start, end }
+    }
+
+    /// Where the range starts.
+    pub fn start(&self) -> i32 {
+        self.start
+    }
+
+    /// Where the range ends, inclusive.
+    pub fn end(&self) -> i32 {
+        self.end
+    }
+
+    /// How many numbers the range has.
+    pub fn len(&self) -> usize {
+        if self.end < self.start {
+            0
+        } else {
+            (i64::from(self.end) - i64::from(self.start) + 1) as usize
+        }
+    }
+
+    /// Whether the range has no numbers.
+    pub fn is_empty(&self) -> bool {
+        self.end < self.start
+    }
+
+    /// The first number, if any.
+    pub fn first(&self) -> Option<i32> {
+        self.get(0)
+    }
+
+    /// The last number, if any.
+    pub fn last(&self) -> Option<i32> {
+        self.get(-1)
+    }
+
+    /// The number at `index`, counting from the end for negative indexes, as for arrays.
+    pub fn get(&self, index: i32) -> Option<i32> {
+        let len = self.len() as i64;
+        let index = i64::from(index);
+        let index = if index < 0 { len + index } else { index };
+        if 0 <= index && index < len {
+            Some((i64::from(self.start) + index) as i32)
+        } else {
+            None
+        }
+    }
+
+    /// The numbers from `offset`, at most `limit` of them.
+    pub fn slice(&self, offset: usize, limit: Option<usize>) -> Self {
+        let len = self.len();
+        let offset = ::std::cmp::min(offset, len);
+        let limit = limit.map_or(len - offset, |limit| ::std::cmp::min(limit, len - offset));
+        let start = i64::from(self.start) + offset as i64;
+        Self {
+            start: start as i32,
+            end: (start + limit as i64 - 1) as i32,
+        }
+    }
+
+    /// Iterate over the numbers.
+    pub fn iter(&self) -> RangeInclusive<i32> {
+        self.start..=self.end
+    }
+
+    /// The numbers, as an array.
+    pub fn to_array(&self) -> Vec<Value> {
+        self.iter().map(Value::scalar).collect()
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// Serialized as the array of its numbers.
+impl serde::Serialize for Range {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for number in self.iter() {
+            seq.serialize_element(&number)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn range_len() {
+        assert_eq!(Range::new(1, 5).len(), 5);
+        assert_eq!(Range::new(3, 3).len(), 1);
+        assert_eq!(Range::new(5, 1).len(), 0);
+        assert!(Range::new(5, 1).is_empty());
+        assert_eq!(
+            Range::new(i32::min_value(), i32::max_value()).len(),
+            1 << 32
+        );
+    }
+
+    #[test]
+    fn range_get() {
+        let range = Range::new(1, 1_000_000);
+        assert_eq!(range.first(), Some(1));
+        assert_eq!(range.last(), Some(1_000_000));
+        assert_eq!(range.get(9), Some(10));
+        assert_eq!(range.get(-2), Some(999_999));
+        assert_eq!(range.get(1_000_000), None);
+        assert_eq!(Range::new(5, 1).first(), None);
+    }
+
+    #[test]
+    fn range_slice() {
+        let range = Range::new(1, 10);
+        assert_eq!(range.slice(2, Some(3)), Range::new(3, 5));
+        assert_eq!(range.slice(8, Some(5)), Range::new(9, 10));
+        assert_eq!(range.slice(2, None), Range::new(3, 10));
+        assert!(range.slice(20, None).is_empty());
+    }
+}
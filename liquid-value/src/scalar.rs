@@ -1,9 +1,12 @@
 use std::borrow;
 use std::cmp::Ordering;
 use std::fmt;
+use std::sync;
 
 use chrono;
 
+use super::Number;
+
 /// Liquid's native date/time type.
 pub type Date = chrono::DateTime<chrono::FixedOffset>;
 
@@ -24,6 +27,22 @@ enum ScalarCowEnum<'s> {
     #[serde(with = "friendly_date")]
     Date(Date),
     Str(borrow::Cow<'s, str>),
+    /// A string shared with other values, so cloning it doesn't copy it.
+    #[serde(serialize_with = "shared_str::serialize", skip_deserializing)]
+    Shared(sync::Arc<str>),
+    /// A string that is safe to output as it is, see `ScalarCow::safe_str`.
+    #[serde(serialize_with = "shared_str::serialize", skip_deserializing)]
+    Safe(sync::Arc<str>),
+}
+
+impl<'s> ScalarCowEnum<'s> {
+    fn as_str(&self) -> Option<&str> {
+        match *self {
+            ScalarCowEnum::Str(ref x) => Some(x.as_ref()),
+            ScalarCowEnum::Shared(ref x) | ScalarCowEnum::Safe(ref x) => Some(x.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 impl<'s> ScalarCow<'s> {
@@ -32,6 +51,43 @@ impl<'s> ScalarCow<'s> {
         value.into()
     }
 
+    /// Create a string that is shared with the other values made from `value`.
+    ///
+    /// Cloning the scalar, e.g. when a filter returns its input unchanged, doesn't copy the
+    /// string.
+    pub fn shared_str<S: Into<sync::Arc<str>>>(value: S) -> Self {
+        ScalarCow {
+            0: ScalarCowEnum::Shared(value.into()),
+        }
+    }
+
+    /// Create a string that is safe to output as it is, e.g. HTML a filter made, so templates
+    /// escaping their output automatically don't escape it.
+    ///
+    /// Like `shared_str`, cloning the scalar doesn't copy the string.  Filters that return their
+    /// input unchanged keep it safe, while those making a new string, like `append`, don't.
+    pub fn safe_str<S: Into<sync::Arc<str>>>(value: S) -> Self {
+        ScalarCow {
+            0: ScalarCowEnum::Safe(value.into()),
+        }
+    }
+
+    /// Whether this is a string that is safe to output as it is, see `ScalarCow::safe_str`.
+    pub fn is_safe(&self) -> bool {
+        match self.0 {
+            ScalarCowEnum::Safe(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether this is a string shared with other values, see `ScalarCow::shared_str`.
+    pub fn is_shared_str(&self) -> bool {
+        match self.0 {
+            ScalarCowEnum::Shared(_) => true,
+            _ => false,
+        }
+    }
+
     /// A `Display` for a `Scalar` as source code.
     pub fn source(&self) -> ScalarSource<'_> {
         ScalarSource(&self.0)
@@ -43,10 +99,15 @@ impl<'s> ScalarCow<'s> {
     }
 
     /// Create an owned version of the value.
-    pub fn into_owned(self) -> Self {
+    pub fn into_owned(self) -> Scalar {
         match self.0 {
+            ScalarCowEnum::Integer(x) => Scalar::new(x),
+            ScalarCowEnum::Float(x) => Scalar::new(x),
+            ScalarCowEnum::Bool(x) => Scalar::new(x),
+            ScalarCowEnum::Date(x) => Scalar::new(x),
             ScalarCowEnum::Str(x) => Scalar::new(x.into_owned()),
-            _ => self,
+            ScalarCowEnum::Shared(x) => Scalar::shared_str(x),
+            ScalarCowEnum::Safe(x) => Scalar::safe_str(x),
         }
     }
 
@@ -58,6 +119,8 @@ impl<'s> ScalarCow<'s> {
             ScalarCowEnum::Bool(x) => ScalarCow::new(x),
             ScalarCowEnum::Date(x) => ScalarCow::new(x),
             ScalarCowEnum::Str(ref x) => ScalarCow::new(x.as_ref()),
+            ScalarCowEnum::Shared(ref x) => ScalarCow::new(x.as_ref()),
+            ScalarCowEnum::Safe(ref x) => ScalarCow::safe_str(x.clone()),
         }
     }
 
@@ -69,6 +132,9 @@ impl<'s> ScalarCow<'s> {
             ScalarCowEnum::Bool(ref x) => borrow::Cow::Owned(x.to_string()),
             ScalarCowEnum::Date(ref x) => borrow::Cow::Owned(x.format(DATE_FORMAT).to_string()),
             ScalarCowEnum::Str(ref x) => borrow::Cow::Borrowed(x.as_ref()),
+            ScalarCowEnum::Shared(ref x) | ScalarCowEnum::Safe(ref x) => {
+                borrow::Cow::Borrowed(x.as_ref())
+            }
         }
     }
 
@@ -80,6 +146,7 @@ impl<'s> ScalarCow<'s> {
             ScalarCowEnum::Bool(x) => x.to_string(),
             ScalarCowEnum::Date(x) => x.to_string(),
             ScalarCowEnum::Str(x) => x.into_owned(),
+            ScalarCowEnum::Shared(x) | ScalarCowEnum::Safe(x) => x.as_ref().to_owned(),
         }
     }
 
@@ -87,8 +154,7 @@ impl<'s> ScalarCow<'s> {
     pub fn to_integer(&self) -> Option<i32> {
         match self.0 {
             ScalarCowEnum::Integer(ref x) => Some(*x),
-            ScalarCowEnum::Str(ref x) => x.parse::<i32>().ok(),
-            _ => None,
+            _ => self.0.as_str().and_then(|x| x.parse::<i32>().ok()),
         }
     }
 
@@ -97,7 +163,30 @@ impl<'s> ScalarCow<'s> {
         match self.0 {
             ScalarCowEnum::Integer(ref x) => Some(f64::from(*x)),
             ScalarCowEnum::Float(ref x) => Some(*x),
-            ScalarCowEnum::Str(ref x) => x.parse::<f64>().ok(),
+            _ => self.0.as_str().and_then(|x| x.parse::<f64>().ok()),
+        }
+    }
+
+    /// Interpret as a number, if possible, e.g. the string `"5"` as `5`.
+    pub fn to_number(&self) -> Option<Number> {
+        self.to_integer()
+            .map(Number::Integer)
+            .or_else(|| self.to_float().map(Number::Float))
+    }
+
+    /// The value, if this is a whole or fractional number.
+    pub fn as_number(&self) -> Option<Number> {
+        match self.0 {
+            ScalarCowEnum::Integer(x) => Some(Number::Integer(x)),
+            ScalarCowEnum::Float(x) => Some(Number::Float(x)),
+            _ => None,
+        }
+    }
+
+    /// The value, if this is a fractional number.
+    pub fn as_float(&self) -> Option<f64> {
+        match self.0 {
+            ScalarCowEnum::Float(ref x) => Some(*x),
             _ => None,
         }
     }
@@ -114,8 +203,7 @@ impl<'s> ScalarCow<'s> {
     pub fn to_date(&self) -> Option<Date> {
         match self.0 {
             ScalarCowEnum::Date(ref x) => Some(*x),
-            ScalarCowEnum::Str(ref x) => parse_date(x.as_ref()),
-            _ => None,
+            _ => self.0.as_str().and_then(parse_date),
         }
     }
 
@@ -133,11 +221,15 @@ impl<'s> ScalarCow<'s> {
         // encode Ruby truthiness: all values except false and nil are true
         match self.0 {
             ScalarCowEnum::Bool(ref x) => !*x,
-            ScalarCowEnum::Str(ref x) => x.is_empty(),
-            _ => false,
+            _ => self.0.as_str().is_some_and(str::is_empty),
         }
     }
 
+    /// The string, if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_str()
+    }
+
     /// Report the data type (generally for error reporting).
     pub fn type_name(&self) -> &'static str {
         match self.0 {
@@ -145,7 +237,7 @@ impl<'s> ScalarCow<'s> {
             ScalarCowEnum::Float(_) => "fractional number",
             ScalarCowEnum::Bool(_) => "boolean",
             ScalarCowEnum::Date(_) => "date",
-            ScalarCowEnum::Str(_) => "string",
+            ScalarCowEnum::Str(_) | ScalarCowEnum::Shared(_) | ScalarCowEnum::Safe(_) => "string",
         }
     }
 }
@@ -166,6 +258,15 @@ impl<'s> From<f64> for ScalarCow<'s> {
     }
 }
 
+impl<'s> From<Number> for ScalarCow<'s> {
+    fn from(s: Number) -> Self {
+        match s {
+            Number::Integer(x) => x.into(),
+            Number::Float(x) => x.into(),
+        }
+    }
+}
+
 impl<'s> From<bool> for ScalarCow<'s> {
     fn from(s: bool) -> Self {
         ScalarCow {
@@ -310,6 +411,7 @@ impl<'s> fmt::Display for ScalarSource<'s> {
             ScalarCowEnum::Bool(ref x) => write!(f, "{}", x),
             ScalarCowEnum::Date(ref x) => write!(f, "{}", x.format(DATE_FORMAT)),
             ScalarCowEnum::Str(ref x) => write!(f, r#""{}""#, x),
+            ScalarCowEnum::Shared(ref x) | ScalarCowEnum::Safe(ref x) => write!(f, r#""{}""#, x),
         }
     }
 }
@@ -327,6 +429,7 @@ impl<'s> fmt::Display for ScalarRendered<'s> {
             ScalarCowEnum::Bool(ref x) => write!(f, "{}", x),
             ScalarCowEnum::Date(ref x) => write!(f, "{}", x.format(DATE_FORMAT)),
             ScalarCowEnum::Str(ref x) => write!(f, "{}", x),
+            ScalarCowEnum::Shared(ref x) | ScalarCowEnum::Safe(ref x) => write!(f, "{}", x),
         }
     }
 }
@@ -334,33 +437,72 @@ impl<'s> fmt::Display for ScalarRendered<'s> {
 fn scalar_eq<'s>(lhs: &ScalarCow<'s>, rhs: &ScalarCow<'s>) -> bool {
     match (&lhs.0, &rhs.0) {
         (&ScalarCowEnum::Integer(x), &ScalarCowEnum::Integer(y)) => x == y,
-        (&ScalarCowEnum::Integer(x), &ScalarCowEnum::Float(y)) => (f64::from(x)) == y,
-        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Integer(y)) => x == (f64::from(y)),
-        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Float(y)) => x == y,
+        (&ScalarCowEnum::Integer(x), &ScalarCowEnum::Float(y)) => float_eq(f64::from(x), y),
+        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Integer(y)) => float_eq(x, f64::from(y)),
+        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Float(y)) => float_eq(x, y),
         (&ScalarCowEnum::Bool(x), &ScalarCowEnum::Bool(y)) => x == y,
         (&ScalarCowEnum::Date(x), &ScalarCowEnum::Date(y)) => x == y,
-        (&ScalarCowEnum::Str(ref x), &ScalarCowEnum::Str(ref y)) => x == y,
         // encode Ruby truthiness: all values except false and nil are true
         (_, &ScalarCowEnum::Bool(b)) | (&ScalarCowEnum::Bool(b), _) => b,
-        _ => false,
+        (&ScalarCowEnum::Date(x), y) => y.as_str().and_then(parse_date) == Some(x),
+        (x, &ScalarCowEnum::Date(y)) => x.as_str().and_then(parse_date) == Some(y),
+        (x, y) => match (x.as_str(), y.as_str()) {
+            (Some(x), Some(y)) => x == y,
+            _ => false,
+        },
     }
 }
 
+// `NaN` equals itself and sorts after every other number, keeping `Eq` and sorting consistent.
+fn float_eq(x: f64, y: f64) -> bool {
+    x == y || (x.is_nan() && y.is_nan())
+}
+
+fn float_cmp(x: f64, y: f64) -> Ordering {
+    x.partial_cmp(&y)
+        .unwrap_or_else(|| x.is_nan().cmp(&y.is_nan()))
+}
+
 fn scalar_cmp<'s>(lhs: &ScalarCow<'s>, rhs: &ScalarCow<'s>) -> Option<Ordering> {
     match (&lhs.0, &rhs.0) {
         (&ScalarCowEnum::Integer(x), &ScalarCowEnum::Integer(y)) => x.partial_cmp(&y),
-        (&ScalarCowEnum::Integer(x), &ScalarCowEnum::Float(y)) => (f64::from(x)).partial_cmp(&y),
-        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Integer(y)) => x.partial_cmp(&(f64::from(y))),
-        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Float(y)) => x.partial_cmp(&y),
+        (&ScalarCowEnum::Integer(x), &ScalarCowEnum::Float(y)) => Some(float_cmp(f64::from(x), y)),
+        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Integer(y)) => Some(float_cmp(x, f64::from(y))),
+        (&ScalarCowEnum::Float(x), &ScalarCowEnum::Float(y)) => Some(float_cmp(x, y)),
         (&ScalarCowEnum::Bool(x), &ScalarCowEnum::Bool(y)) => x.partial_cmp(&y),
         (&ScalarCowEnum::Date(x), &ScalarCowEnum::Date(y)) => x.partial_cmp(&y),
-        (&ScalarCowEnum::Str(ref x), &ScalarCowEnum::Str(ref y)) => x.partial_cmp(y),
-        _ => None,
+        (&ScalarCowEnum::Date(x), y) => y
+            .as_str()
+            .and_then(parse_date)
+            .and_then(|y| x.partial_cmp(&y)),
+        (x, &ScalarCowEnum::Date(y)) => x
+            .as_str()
+            .and_then(parse_date)
+            .and_then(|x| x.partial_cmp(&y)),
+        (x, y) => match (x.as_str(), y.as_str()) {
+            (Some(x), Some(y)) => x.partial_cmp(y),
+            _ => None,
+        },
     }
 }
 
 const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
 
+/// Like `DATE_FORMAT` but keeping fractions of a second, so serializing a date is lossless.
+const PRECISE_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f %z";
+
+mod shared_str {
+    use super::*;
+    use serde::Serializer;
+
+    pub(crate) fn serialize<S>(s: &sync::Arc<str>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(s)
+    }
+}
+
 mod friendly_date {
     use super::*;
     use serde::{self, Deserialize, Deserializer, Serializer};
@@ -369,7 +511,7 @@ mod friendly_date {
     where
         S: Serializer,
     {
-        let s = date.format(DATE_FORMAT).to_string();
+        let s = date.format(PRECISE_DATE_FORMAT).to_string();
         serializer.serialize_str(&s)
     }
 
@@ -378,7 +520,7 @@ mod friendly_date {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        Date::parse_from_str(&s, DATE_FORMAT).map_err(serde::de::Error::custom)
+        Date::parse_from_str(&s, PRECISE_DATE_FORMAT).map_err(serde::de::Error::custom)
     }
 }
 
@@ -391,11 +533,12 @@ fn parse_date(s: &str) -> Option<Date> {
             Some(now)
         }
         _ => {
-            let formats = ["%d %B %Y %H:%M:%S %z", "%Y-%m-%d %H:%M:%S %z"];
+            let formats = ["%d %B %Y %H:%M:%S%.f %z", PRECISE_DATE_FORMAT];
             formats
                 .iter()
                 .filter_map(|f| Date::parse_from_str(s, f).ok())
                 .next()
+                .or_else(|| Date::parse_from_rfc3339(s).ok())
         }
     }
 }
@@ -633,4 +776,58 @@ mod test {
     fn parse_date_today() {
         assert!(parse_date("today").is_some());
     }
+
+    #[test]
+    fn parse_date_precise() {
+        let date = parse_date("2016-06-13 02:30:00.25 +0300").unwrap();
+        assert_eq!(date.timestamp_subsec_millis(), 250);
+        assert_eq!(parse_date("2016-06-13T02:30:00.25+03:00"), Some(date));
+        assert!(parse_date("13 June 2016 02:30:00 +0300").is_some());
+    }
+
+    #[test]
+    fn date_compares_with_str() {
+        let date: ScalarCow<'_> = parse_date("2016-06-13 02:30:00 +0300").unwrap().into();
+        let same: ScalarCow<'_> = "2016-06-12T23:30:00Z".into();
+        let later: ScalarCow<'_> = "2016-06-13 02:30:00 +0000".into();
+        let text: ScalarCow<'_> = "June".into();
+        assert_eq!(date, same);
+        assert_eq!(same, date);
+        assert_eq!(date.partial_cmp(&later), Some(Ordering::Less));
+        assert_eq!(later.partial_cmp(&date), Some(Ordering::Greater));
+        assert_ne!(date, text);
+        assert_eq!(date.partial_cmp(&text), None);
+    }
+
+    #[test]
+    fn shared_str() {
+        let shared = Scalar::shared_str("Hello");
+        assert!(shared.is_shared_str());
+        assert!(shared.clone().is_shared_str());
+        assert!(shared.clone().into_owned().is_shared_str());
+        assert!(!Scalar::new("Hello").is_shared_str());
+        assert_eq!(shared, Scalar::new("Hello"));
+        assert_eq!(shared.type_name(), "string");
+        assert_eq!(shared.to_str(), "Hello");
+        assert_eq!(Scalar::shared_str("42").to_integer(), Some(42));
+        assert_eq!(
+            Scalar::shared_str("2016-06-13 02:30:00 +0300").to_date(),
+            parse_date("2016-06-13 02:30:00 +0300")
+        );
+        assert!(Scalar::shared_str("").is_default());
+        assert_eq!(shared.source().to_string(), r#""Hello""#);
+    }
+
+    #[test]
+    fn nan_is_consistent() {
+        let nan: ScalarCow<'_> = f64::NAN.into();
+        let one: ScalarCow<'_> = 1i32.into();
+        let inf: ScalarCow<'_> = f64::INFINITY.into();
+        assert_eq!(nan, nan);
+        assert_ne!(nan, one);
+        assert_eq!(nan.partial_cmp(&one), Some(Ordering::Greater));
+        assert_eq!(one.partial_cmp(&nan), Some(Ordering::Less));
+        assert_eq!(nan.partial_cmp(&inf), Some(Ordering::Greater));
+        assert_eq!(nan.partial_cmp(&nan), Some(Ordering::Equal));
+    }
 }
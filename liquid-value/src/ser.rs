@@ -4,7 +4,9 @@ use num_traits;
 use serde::ser::Impossible;
 use serde::{self, Serialize};
 
+use super::Key;
 use super::Object;
+use super::Scalar;
 use super::Value;
 use crate::error;
 
@@ -136,9 +138,10 @@ impl serde::Serializer for Serializer {
         self.serialize_str(&s)
     }
 
+    /// Strings are shared, so the data is cheap to clone while rendering.
     #[inline]
     fn serialize_str(self, value: &str) -> Result<Value, SerError> {
-        Ok(Value::scalar(value.to_owned()))
+        Ok(Value::Scalar(Scalar::shared_str(value)))
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<Value, SerError> {
@@ -189,7 +192,7 @@ impl serde::Serializer for Serializer {
         T: Serialize,
     {
         let mut values = Object::new();
-        values.insert(String::from(variant).into(), value.serialize(Serializer)?);
+        values.insert(Key::from_static(variant), value.serialize(Serializer)?);
         Ok(Value::Object(values))
     }
 
@@ -302,7 +305,7 @@ impl serde::ser::SerializeSeq for SerializeVec {
     }
 
     fn end(self) -> Result<Value, SerError> {
-        Ok(Value::Array(self.vec))
+        Ok(Value::Array(self.vec.into()))
     }
 }
 
@@ -353,7 +356,7 @@ impl serde::ser::SerializeTupleVariant for SerializeTupleVariant {
     fn end(self) -> Result<Value, SerError> {
         let mut object = Object::new();
 
-        object.insert(self.name.into(), Value::Array(self.vec));
+        object.insert(self.name.into(), Value::Array(self.vec.into()));
 
         Ok(Value::Object(object))
     }
@@ -618,7 +621,7 @@ impl serde::ser::SerializeStructVariant for SerializeStructVariant {
         T: Serialize,
     {
         self.map
-            .insert(String::from(key).into(), value.serialize(Serializer)?);
+            .insert(Key::from_static(key), value.serialize(Serializer)?);
         Ok(())
     }
 
@@ -5,6 +5,10 @@ use std::fmt;
 use itertools;
 
 use super::map;
+use super::Array;
+use super::Key;
+use super::LazyArray;
+use super::Range;
 use super::Scalar;
 use super::ScalarCow;
 
@@ -24,11 +28,14 @@ pub enum Value {
     Empty,
     /// Evaluates to empty string.
     Blank,
+    /// The whole numbers from a start to an end, without an array of them.
+    #[serde(skip_deserializing)]
+    Range(Range),
+    /// An array whose elements are generated as they are read, see `LazyArray`.
+    #[serde(skip_deserializing)]
+    Lazy(LazyArray),
 }
 
-/// Type representing a Liquid array, payload of the `Value::Array` variant
-pub type Array = Vec<Value>;
-
 /// Type representing a Liquid object, payload of the `Value::Object` variant
 pub type Object = map::Map;
 
@@ -75,6 +82,10 @@ impl Value {
                 borrow::Cow::Owned(itertools::join(arr, ""))
             }
             Value::Nil | Value::Empty | Value::Blank => borrow::Cow::Borrowed(""),
+            Value::Range(ref x) => borrow::Cow::Owned(x.to_string()),
+            Value::Lazy(ref x) => {
+                borrow::Cow::Owned(Value::Array(x.to_array()).to_str().into_owned())
+            }
         }
     }
 
@@ -128,6 +139,42 @@ impl Value {
         self.as_array().is_some()
     }
 
+    /// Extracts the range value if it is a range.
+    pub fn as_range(&self) -> Option<Range> {
+        match *self {
+            Value::Range(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Tests whether this value is a range
+    pub fn is_range(&self) -> bool {
+        self.as_range().is_some()
+    }
+
+    /// Extracts the lazy array if it is one.
+    pub fn as_lazy(&self) -> Option<&LazyArray> {
+        match *self {
+            Value::Lazy(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Tests whether this value is a lazy array
+    pub fn is_lazy(&self) -> bool {
+        self.as_lazy().is_some()
+    }
+
+    /// Turns a range into the array of its numbers, and a lazy array into the array of its
+    /// elements, leaving other values as they are.
+    pub fn into_materialized(self) -> Self {
+        match self {
+            Value::Range(x) => Value::Array(x.to_array().into()),
+            Value::Lazy(x) => Value::Array(x.to_array()),
+            _ => self,
+        }
+    }
+
     /// Extracts the object value if it is a object.
     pub fn as_object(&self) -> Option<&Object> {
         match *self {
@@ -224,6 +271,8 @@ impl Value {
             Value::Blank => true,
             Value::Array(ref x) => x.is_empty(),
             Value::Object(ref x) => x.is_empty(),
+            Value::Range(ref x) => x.is_empty(),
+            Value::Lazy(ref x) => x.is_empty(),
         }
     }
 
@@ -236,6 +285,8 @@ impl Value {
             Value::Blank => "blank",
             Value::Array(_) => "array",
             Value::Object(_) => "object",
+            Value::Range(_) => "range",
+            Value::Lazy(_) => "array",
         }
     }
 
@@ -269,13 +320,7 @@ impl Value {
                 keys.push(Scalar::new("last"));
                 keys
             }
-            Value::Object(ref x) => x
-                .keys()
-                .map(|s| match *s {
-                    borrow::Cow::Borrowed(s) => Scalar::new(s),
-                    borrow::Cow::Owned(ref s) => Scalar::new(s.to_owned()),
-                })
-                .collect(),
+            Value::Object(ref x) => x.keys().map(|s| Scalar::from(s.clone())).collect(),
             _ => vec![],
         };
         Keys(v.into_iter())
@@ -290,8 +335,8 @@ impl Value {
                     x.get(index as usize)
                 } else {
                     match &*index.to_str() {
-                        "first" => x.get(0),
-                        "last" => x.get(x.len() - 1),
+                        "first" => x.first(),
+                        "last" => x.last(),
                         _ => None,
                     }
                 }
@@ -300,6 +345,44 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Compute the `size`, `first` or `last` property, for when it isn't an index of the value.
+    ///
+    /// These are the length, and the first and last elements, of arrays and ranges, the
+    /// characters of strings, and the `[key, value]` entries of objects.  Ranges are indexed by
+    /// position, like arrays.
+    pub fn property(&self, name: &str) -> Option<Self> {
+        match (self, name) {
+            (&Value::Range(ref x), "size") => Some(Value::scalar(x.len() as i32)),
+            (&Value::Range(ref x), "first") => x.first().map(Value::scalar),
+            (&Value::Range(ref x), "last") => x.last().map(Value::scalar),
+            (&Value::Range(ref x), _) => x.get(name.parse().ok()?).map(Value::scalar),
+            (&Value::Lazy(ref x), "size") => Some(Value::scalar(x.len() as i32)),
+            (&Value::Lazy(ref x), "first") => x.first(),
+            (&Value::Lazy(ref x), "last") => x.last(),
+            (&Value::Lazy(ref x), _) => x.get(name.parse().ok()?),
+            (&Value::Array(ref x), "size") => Some(Value::scalar(x.len() as i32)),
+            (&Value::Array(ref x), "first") => x.first().cloned(),
+            (&Value::Array(ref x), "last") => x.last().cloned(),
+            (&Value::Object(ref x), "size") => Some(Value::scalar(x.len() as i32)),
+            (&Value::Object(ref x), "first") => x.iter().next().map(entry),
+            (&Value::Object(ref x), "last") => x.iter().last().map(entry),
+            (&Value::Scalar(ref x), _) => {
+                let x = x.as_str()?;
+                match name {
+                    "size" => Some(Value::scalar(x.chars().count() as i32)),
+                    "first" => x.chars().next().map(|c| Value::scalar(c.to_string())),
+                    "last" => x.chars().last().map(|c| Value::scalar(c.to_string())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+fn entry((key, value): (&Key, &Value)) -> Value {
+    Value::array(vec![Value::scalar(key.clone()), value.clone()])
 }
 
 /// Iterator over a `Value`s keys.
@@ -384,6 +467,8 @@ impl<'s> fmt::Display for ValueSource<'s> {
             Value::Nil => write!(f, "nil")?,
             Value::Empty => write!(f, "empty")?,
             Value::Blank => write!(f, "blank")?,
+            Value::Range(ref x) => write!(f, "({})", x)?,
+            Value::Lazy(ref x) => write!(f, "{}", Value::Array(x.to_array()).source())?,
         }
         Ok(())
     }
@@ -409,6 +494,8 @@ impl<'s> fmt::Display for ValueRendered<'s> {
                 }
             }
             Value::Nil | Value::Empty | Value::Blank => (),
+            Value::Range(ref x) => write!(f, "{}", x)?,
+            Value::Lazy(ref x) => write!(f, "{}", Value::Array(x.to_array()).render())?,
         }
         Ok(())
     }
@@ -416,9 +503,17 @@ impl<'s> fmt::Display for ValueRendered<'s> {
 
 fn value_eq(lhs: &Value, rhs: &Value) -> bool {
     match (lhs, rhs) {
+        (&Value::Lazy(ref x), y) | (y, &Value::Lazy(ref x)) => {
+            value_eq(&Value::Array(x.to_array()), y)
+        }
         (&Value::Scalar(ref x), &Value::Scalar(ref y)) => x == y,
         (&Value::Array(ref x), &Value::Array(ref y)) => x == y,
         (&Value::Object(ref x), &Value::Object(ref y)) => x == y,
+        (&Value::Range(ref x), &Value::Range(ref y)) => x == y || (x.is_empty() && y.is_empty()),
+        (&Value::Range(ref x), &Value::Array(ref y))
+        | (&Value::Array(ref y), &Value::Range(ref x)) => {
+            x.len() == y.len() && x.iter().map(Value::scalar).eq(y.iter().cloned())
+        }
         (&Value::Nil, &Value::Nil)
         | (&Value::Empty, &Value::Empty)
         | (&Value::Blank, &Value::Blank)
@@ -449,6 +544,10 @@ fn value_eq(lhs: &Value, rhs: &Value) -> bool {
         (&Value::Blank, &Value::Object(ref s)) | (&Value::Object(ref s), &Value::Blank) => {
             s.is_empty()
         }
+        (&Value::Empty, &Value::Range(ref s))
+        | (&Value::Range(ref s), &Value::Empty)
+        | (&Value::Blank, &Value::Range(ref s))
+        | (&Value::Range(ref s), &Value::Blank) => s.is_empty(),
 
         // encode Ruby truthiness: all values except false and nil are true
         (&Value::Nil, &Value::Scalar(ref b)) | (&Value::Scalar(ref b), &Value::Nil) => {
@@ -462,9 +561,12 @@ fn value_eq(lhs: &Value, rhs: &Value) -> bool {
 
 fn value_cmp(lhs: &Value, rhs: &Value) -> Option<Ordering> {
     match (lhs, rhs) {
+        (&Value::Lazy(ref x), y) => value_cmp(&Value::Array(x.to_array()), y),
+        (x, &Value::Lazy(ref y)) => value_cmp(x, &Value::Array(y.to_array())),
         (&Value::Scalar(ref x), &Value::Scalar(ref y)) => x.partial_cmp(y),
         (&Value::Array(ref x), &Value::Array(ref y)) => x.iter().partial_cmp(y.iter()),
         (&Value::Object(ref x), &Value::Object(ref y)) => x.iter().partial_cmp(y.iter()),
+        (&Value::Range(ref x), &Value::Range(ref y)) => x.iter().partial_cmp(y.iter()),
         _ => None,
     }
 }
@@ -482,7 +584,7 @@ mod test {
 
     #[test]
     fn test_to_string_array() {
-        let val = Value::Array(vec![
+        let val = Value::array(vec![
             Value::scalar(3f64),
             Value::scalar("test"),
             Value::scalar(5.3),
@@ -520,8 +622,8 @@ mod test {
 
     #[test]
     fn array_equality() {
-        let a = Value::Array(vec![Value::scalar("one"), Value::scalar("two")]);
-        let b = Value::Array(vec![Value::scalar("alpha"), Value::scalar("beta")]);
+        let a = Value::array(vec![Value::scalar("one"), Value::scalar("two")]);
+        let b = Value::array(vec![Value::scalar("alpha"), Value::scalar("beta")]);
 
         assert_eq!(a, a);
         assert!(a != b);
@@ -530,8 +632,8 @@ mod test {
 
     #[test]
     fn arrays_have_ruby_truthiness() {
-        assert_eq!(Value::scalar(true), Value::Array(Vec::new()));
-        assert!(Value::Array(Vec::new()).is_truthy());
+        assert_eq!(Value::scalar(true), Value::Array(Array::new()));
+        assert!(Value::Array(Array::new()).is_truthy());
     }
 
     #[test]
@@ -548,7 +650,7 @@ mod test {
         let b: Object = [
             ("alpha".into(), Value::scalar("1")),
             ("beta".into(), Value::scalar(2f64)),
-            ("gamma".into(), Value::Array(vec![])),
+            ("gamma".into(), Value::array(vec![])),
         ]
         .into_iter()
         .cloned()
@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::map;
+use super::Array;
+use super::Date;
+use super::Object;
+use super::ScalarCow;
+use super::Value;
+
+/// A read-only view of a value, for reading data without converting it into a `Value` first.
+///
+/// Only the accessors for what the value is have to be implemented; a value that is neither a
+/// scalar, an array nor an object is `nil`.
+pub trait ValueView: fmt::Debug {
+    /// The value as a scalar, if it is one.
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        None
+    }
+
+    /// The value as an array, if it is one.
+    fn as_array(&self) -> Option<&dyn ArrayView> {
+        None
+    }
+
+    /// The value as an object, if it is one.
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        None
+    }
+
+    /// Whether the value is `nil`.
+    fn is_nil(&self) -> bool {
+        self.as_scalar().is_none() && self.as_array().is_none() && self.as_object().is_none()
+    }
+
+    /// Copy the value into a `Value`.
+    fn to_value(&self) -> Value {
+        if let Some(scalar) = self.as_scalar() {
+            Value::Scalar(scalar.into_owned())
+        } else if let Some(array) = self.as_array() {
+            Value::array(array.values().map(|v| v.to_value()))
+        } else if let Some(object) = self.as_object() {
+            Value::Object(
+                object
+                    .iter()
+                    .map(|(k, v)| (k.to_owned().into(), v.to_value()))
+                    .collect(),
+            )
+        } else {
+            Value::Nil
+        }
+    }
+}
+
+/// A read-only view of an array, see `ValueView`.
+pub trait ArrayView: fmt::Debug {
+    /// The number of elements.
+    fn size(&self) -> usize;
+
+    /// The element at `index`, counting from 0.
+    fn get(&self, index: usize) -> Option<&dyn ValueView>;
+
+    /// The elements, in order.
+    fn values(&self) -> Box<dyn Iterator<Item = &dyn ValueView> + '_>;
+}
+
+/// A read-only view of an object, see `ValueView`.
+///
+/// Structs can derive it with `#[derive(ObjectView)]`, to be read field by field.
+pub trait ObjectView: fmt::Debug {
+    /// The number of entries.
+    fn size(&self) -> usize;
+
+    /// The keys, in the order the entries are iterated.
+    fn keys(&self) -> Box<dyn Iterator<Item = &str> + '_>;
+
+    /// The value of `key`.
+    fn get(&self, key: &str) -> Option<&dyn ValueView>;
+
+    /// Whether there is a value for `key`.
+    fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The entries, in the order of `keys`.
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &dyn ValueView)> + '_> {
+        Box::new(self.keys().filter_map(move |k| self.get(k).map(|v| (k, v))))
+    }
+}
+
+impl ValueView for Value {
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        match *self {
+            Value::Scalar(ref x) => Some(x.as_ref()),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&dyn ArrayView> {
+        match *self {
+            Value::Array(ref x) => Some(x),
+            _ => None,
+        }
+    }
+
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        match *self {
+            Value::Object(ref x) => Some(x),
+            _ => None,
+        }
+    }
+
+    fn is_nil(&self) -> bool {
+        Value::is_nil(self)
+    }
+
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl ObjectView for Object {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(map::Map::keys(self).map(|k| k.as_ref()))
+    }
+
+    fn get(&self, key: &str) -> Option<&dyn ValueView> {
+        map::Map::get(self, key).map(|v| v as &dyn ValueView)
+    }
+
+    fn contains_key(&self, key: &str) -> bool {
+        map::Map::contains_key(self, key)
+    }
+}
+
+impl ValueView for Object {
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        Some(self)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Object(self.clone())
+    }
+}
+
+impl ArrayView for Array {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&dyn ValueView> {
+        self.as_slice().get(index).map(|v| v as &dyn ValueView)
+    }
+
+    fn values(&self) -> Box<dyn Iterator<Item = &dyn ValueView> + '_> {
+        Box::new(self.iter().map(|v| v as &dyn ValueView))
+    }
+}
+
+impl ValueView for Array {
+    fn as_array(&self) -> Option<&dyn ArrayView> {
+        Some(self)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Array(self.clone())
+    }
+}
+
+impl<T: ValueView> ArrayView for Vec<T> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&dyn ValueView> {
+        self.as_slice().get(index).map(|v| v as &dyn ValueView)
+    }
+
+    fn values(&self) -> Box<dyn Iterator<Item = &dyn ValueView> + '_> {
+        Box::new(self.iter().map(|v| v as &dyn ValueView))
+    }
+}
+
+impl<T: ValueView> ValueView for Vec<T> {
+    fn as_array(&self) -> Option<&dyn ArrayView> {
+        Some(self)
+    }
+}
+
+impl<T: ValueView, S: BuildHasher + fmt::Debug> ObjectView for HashMap<String, T, S> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(HashMap::keys(self).map(String::as_str))
+    }
+
+    fn get(&self, key: &str) -> Option<&dyn ValueView> {
+        HashMap::get(self, key).map(|v| v as &dyn ValueView)
+    }
+}
+
+impl<T: ValueView, S: BuildHasher + fmt::Debug> ValueView for HashMap<String, T, S> {
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        Some(self)
+    }
+}
+
+impl<T: ValueView> ObjectView for BTreeMap<String, T> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(BTreeMap::keys(self).map(String::as_str))
+    }
+
+    fn get(&self, key: &str) -> Option<&dyn ValueView> {
+        BTreeMap::get(self, key).map(|v| v as &dyn ValueView)
+    }
+}
+
+impl<T: ValueView> ValueView for BTreeMap<String, T> {
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        Some(self)
+    }
+}
+
+impl<T: ValueView> ValueView for Option<T> {
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        self.as_ref()?.as_scalar()
+    }
+
+    fn as_array(&self) -> Option<&dyn ArrayView> {
+        self.as_ref()?.as_array()
+    }
+
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        self.as_ref()?.as_object()
+    }
+
+    fn is_nil(&self) -> bool {
+        self.as_ref().map(|v| v.is_nil()).unwrap_or(true)
+    }
+
+    fn to_value(&self) -> Value {
+        self.as_ref().map(|v| v.to_value()).unwrap_or(Value::Nil)
+    }
+}
+
+macro_rules! deref_value_view {
+    ($($pointer:ty),*) => {
+        $(
+            impl<T: ValueView + ?Sized> ValueView for $pointer {
+                fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+                    (**self).as_scalar()
+                }
+
+                fn as_array(&self) -> Option<&dyn ArrayView> {
+                    (**self).as_array()
+                }
+
+                fn as_object(&self) -> Option<&dyn ObjectView> {
+                    (**self).as_object()
+                }
+
+                fn is_nil(&self) -> bool {
+                    (**self).is_nil()
+                }
+
+                fn to_value(&self) -> Value {
+                    (**self).to_value()
+                }
+            }
+        )*
+    };
+}
+
+deref_value_view!(&T, Box<T>, Rc<T>, Arc<T>);
+
+impl<'s> ValueView for ScalarCow<'s> {
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        Some(self.as_ref())
+    }
+}
+
+impl ValueView for str {
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        Some(ScalarCow::new(self))
+    }
+}
+
+impl ValueView for String {
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        Some(ScalarCow::new(self.as_str()))
+    }
+}
+
+macro_rules! scalar_value_view {
+    ($($t:ty => $convert:expr),* $(,)?) => {
+        $(
+            impl ValueView for $t {
+                fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+                    let convert: fn($t) -> ScalarCow<'static> = $convert;
+                    Some(convert(*self))
+                }
+            }
+        )*
+    };
+}
+
+/// Integers too large for a scalar are floats.
+fn wide_integer(x: i128) -> ScalarCow<'static> {
+    match i32::try_from(x) {
+        Ok(x) => ScalarCow::new(x),
+        Err(_) => ScalarCow::new(x as f64),
+    }
+}
+
+scalar_value_view! {
+    bool => ScalarCow::new,
+    i8 => |x| ScalarCow::new(i32::from(x)),
+    i16 => |x| ScalarCow::new(i32::from(x)),
+    i32 => ScalarCow::new,
+    i64 => |x| wide_integer(i128::from(x)),
+    isize => |x| wide_integer(x as i128),
+    u8 => |x| ScalarCow::new(i32::from(x)),
+    u16 => |x| ScalarCow::new(i32::from(x)),
+    u32 => |x| wide_integer(i128::from(x)),
+    u64 => |x| wide_integer(i128::from(x)),
+    usize => |x| wide_integer(x as i128),
+    f32 => |x| ScalarCow::new(f64::from(x)),
+    f64 => ScalarCow::new,
+}
+
+impl ValueView for Date {
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        Some(ScalarCow::new(*self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Page {
+        title: String,
+        tags: Vec<&'static str>,
+    }
+
+    impl ObjectView for Page {
+        fn size(&self) -> usize {
+            2
+        }
+
+        fn keys(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+            Box::new(vec!["title", "tags"].into_iter())
+        }
+
+        fn get(&self, key: &str) -> Option<&dyn ValueView> {
+            match key {
+                "title" => Some(&self.title),
+                "tags" => Some(&self.tags),
+                _ => None,
+            }
+        }
+    }
+
+    impl ValueView for Page {
+        fn as_object(&self) -> Option<&dyn ObjectView> {
+            Some(self)
+        }
+    }
+
+    #[test]
+    fn view_to_value() {
+        let page = Page {
+            title: "Home".to_owned(),
+            tags: vec!["a", "b"],
+        };
+        let mut expected = Object::new();
+        expected.insert("title".into(), Value::scalar("Home"));
+        expected.insert(
+            "tags".into(),
+            Value::array(vec![Value::scalar("a"), Value::scalar("b")]),
+        );
+        assert_eq!(page.to_value(), Value::Object(expected));
+
+        let tags = page.as_object().unwrap().get("tags").unwrap();
+        assert_eq!(tags.as_array().unwrap().size(), 2);
+        assert_eq!(None::<i32>.to_value(), Value::Nil);
+        assert_eq!(3_000_000_000_u32.to_value(), Value::scalar(3e9));
+    }
+
+    #[test]
+    fn value_views_itself() {
+        let value = Value::array(vec![Value::scalar(1), Value::Nil]);
+        let array = ValueView::as_array(&value).unwrap();
+        assert_eq!(array.get(0).unwrap().as_scalar(), Some(ScalarCow::new(1)));
+        assert!(ValueView::is_nil(array.get(1).unwrap()));
+        assert_eq!(ValueView::to_value(&value), value);
+    }
+}
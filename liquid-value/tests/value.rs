@@ -81,7 +81,7 @@ pub fn deserialize_nil() {
     let actual: liquid_value::Value = serde_yaml::from_str("---\n- ").unwrap();
     assert_eq!(
         actual,
-        liquid_value::Value::Array(vec![liquid_value::Value::Nil])
+        liquid_value::Value::array(vec![liquid_value::Value::Nil])
     );
 }
 
@@ -119,7 +119,7 @@ pub fn serialize_array() {
         liquid_value::Value::scalar(true),
         liquid_value::Value::scalar("true"),
     ];
-    let actual = liquid_value::Value::Array(actual);
+    let actual = liquid_value::Value::array(actual);
     let actual = serde_yaml::to_string(&actual).unwrap();
     assert_diff!(&actual, "---\n- 1.0\n- true\n- \"true\"", "", 0);
 }
@@ -132,7 +132,7 @@ pub fn deserialize_array() {
         liquid_value::Value::scalar(true),
         liquid_value::Value::scalar("true"),
     ];
-    let expected = liquid_value::Value::Array(expected);
+    let expected = liquid_value::Value::array(expected);
     assert_eq!(actual, expected);
 }
 
@@ -156,3 +156,20 @@ pub fn deserialize_object() {
     let expected = liquid_value::Value::Object(expected);
     assert_eq!(actual, expected);
 }
+
+#[test]
+pub fn date_round_trips() {
+    let date = liquid_value::Scalar::new("2016-06-13 02:30:00.125 +0300")
+        .to_date()
+        .unwrap();
+    let expected = liquid_value::Value::scalar(date);
+    let serialized = serde_yaml::to_string(&expected).unwrap();
+    let actual: liquid_value::Value = serde_yaml::from_str(&serialized).unwrap();
+    assert_eq!(actual, expected);
+    assert_eq!(
+        actual.as_scalar().and_then(|s| s.to_date()),
+        Some(date),
+        "{}",
+        serialized
+    );
+}
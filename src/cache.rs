@@ -0,0 +1,327 @@
+//! Stores for rendered content that is expensive to produce.
+//!
+//! One `CacheStore` can back both the `cache` block and `CachedSource`, each in a namespace of
+//! its own.  Hosts plug in shared stores, like Redis or memcached, by implementing the trait.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! let store = Arc::new(liquid::cache::MemoryStore::new());
+//! let parser = liquid::ParserBuilder::with_liquid()
+//!     .block(liquid::tags::CacheBlock::new(store))
+//!     .build()
+//!     .unwrap();
+//! let template = parser
+//!     .parse("{% cache 'footer', ttl: 60 %}{{ 'expensive' | upcase }}{% endcache %}")
+//!     .unwrap();
+//! let output = template.render(&liquid::value::Object::new()).unwrap();
+//! assert_eq!(output, "EXPENSIVE");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path;
+use std::sync;
+use std::time;
+
+use interpreter::Dependencies;
+
+/// A store of strings by key, within namespaces, that may expire.
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    /// The value of `key`, unless it is missing or has expired.
+    fn get(&self, namespace: &str, key: &str) -> Option<String>;
+
+    /// Store `value` as `key`, for as long as `ttl` if given.
+    fn set(&self, namespace: &str, key: &str, value: String, ttl: Option<time::Duration>);
+
+    /// Forget `key`.  Returns whether it was stored.
+    fn remove(&self, namespace: &str, key: &str) -> bool;
+
+    /// Forget every key of `namespace`.
+    fn clear(&self, namespace: &str);
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    value: String,
+    expires: Option<time::SystemTime>,
+}
+
+impl Entry {
+    fn new(value: String, ttl: Option<time::Duration>) -> Self {
+        Self {
+            value,
+            expires: ttl.map(|ttl| time::SystemTime::now() + ttl),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires
+            .is_some_and(|expires| expires <= time::SystemTime::now())
+    }
+}
+
+/// A `CacheStore` in memory, for a single process.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: sync::Mutex<HashMap<String, HashMap<String, Entry>>>,
+}
+
+impl MemoryStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> sync::MutexGuard<'_, HashMap<String, HashMap<String, Entry>>> {
+        self.entries.lock().expect("not to be poisoned and reused")
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, namespace: &str, key: &str) -> Option<String> {
+        let mut entries = self.lock();
+        let namespace = entries.get_mut(namespace)?;
+        if namespace.get(key)?.is_expired() {
+            namespace.remove(key);
+            return None;
+        }
+        namespace.get(key).map(|entry| entry.value.clone())
+    }
+
+    fn set(&self, namespace: &str, key: &str, value: String, ttl: Option<time::Duration>) {
+        self.lock()
+            .entry(namespace.to_owned())
+            .or_default()
+            .insert(key.to_owned(), Entry::new(value, ttl));
+    }
+
+    fn remove(&self, namespace: &str, key: &str) -> bool {
+        self.lock()
+            .get_mut(namespace)
+            .is_some_and(|namespace| namespace.remove(key).is_some())
+    }
+
+    fn clear(&self, namespace: &str) {
+        self.lock().remove(namespace);
+    }
+}
+
+/// A `CacheStore` of files in a directory, surviving restarts and shared between processes.
+///
+/// Each namespace is a subdirectory, and each key a file.
+#[derive(Clone, Debug)]
+pub struct DiskStore {
+    root: path::PathBuf,
+}
+
+impl DiskStore {
+    /// Create a store of the files in `root`, which is created when needed.
+    pub fn new<P: Into<path::PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The directory of `namespace`.
+    fn directory(&self, namespace: &str) -> path::PathBuf {
+        self.root.join(file_name(namespace))
+    }
+
+    fn path(&self, namespace: &str, key: &str) -> path::PathBuf {
+        self.directory(namespace).join(file_name(key))
+    }
+}
+
+/// A name that is safe on every file system, whatever `name` contains.
+fn file_name(name: &str) -> String {
+    name.bytes().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl CacheStore for DiskStore {
+    fn get(&self, namespace: &str, key: &str) -> Option<String> {
+        let path = self.path(namespace, key);
+        let content = fs::read_to_string(&path).ok()?;
+        // The first line is when the entry expires, in seconds since the epoch, if ever.
+        let (expires, value) = content.split_once('\n')?;
+        let expires = match expires {
+            "" => None,
+            expires => Some(time::UNIX_EPOCH + time::Duration::from_secs(expires.parse().ok()?)),
+        };
+        let entry = Entry {
+            value: value.to_owned(),
+            expires,
+        };
+        if entry.is_expired() {
+            let _ = fs::remove_file(path);
+            return None;
+        }
+        Some(entry.value)
+    }
+
+    fn set(&self, namespace: &str, key: &str, value: String, ttl: Option<time::Duration>) {
+        let entry = Entry::new(value, ttl);
+        let expires = entry
+            .expires
+            .and_then(|expires| expires.duration_since(time::UNIX_EPOCH).ok())
+            .map(|expires| expires.as_secs().to_string())
+            .unwrap_or_default();
+        // A cache that can't be written is only slower, so failures are ignored.
+        if fs::create_dir_all(self.directory(namespace)).is_ok() {
+            let _ = fs::write(
+                self.path(namespace, key),
+                format!("{}\n{}", expires, entry.value),
+            );
+        }
+    }
+
+    fn remove(&self, namespace: &str, key: &str) -> bool {
+        fs::remove_file(self.path(namespace, key)).is_ok()
+    }
+
+    fn clear(&self, namespace: &str) {
+        let _ = fs::remove_dir_all(self.directory(namespace));
+    }
+}
+
+/// What the entries of a namespace were rendered from, to remove those a change affects.
+///
+/// Only the entries rendered by this process are known, so a store shared with other processes
+/// is best cleared when an index is lost, e.g. on restart.
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// let store = Arc::new(liquid::cache::MemoryStore::new());
+/// let block = liquid::tags::CacheBlock::new(store);
+/// let dependencies = block.dependencies();
+/// let parser = liquid::ParserBuilder::with_liquid().block(block).build().unwrap();
+/// let template = parser
+///     .parse("{% cache 'product' %}{{ product | upcase }}{% endcache %}")
+///     .unwrap();
+/// let mut globals = liquid::value::Object::new();
+/// globals.insert("product".into(), liquid::value::Value::scalar("shirt"));
+/// template.render(&globals).unwrap();
+///
+/// assert_eq!(dependencies.invalidate_global("product"), vec!["product".to_owned()]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DependencyIndex {
+    store: sync::Arc<dyn CacheStore>,
+    namespace: String,
+    entries: sync::Arc<sync::Mutex<HashMap<String, Dependencies>>>,
+}
+
+impl DependencyIndex {
+    /// Create an empty index of the entries of `namespace` in `store`.
+    pub fn new<S: Into<String>>(store: sync::Arc<dyn CacheStore>, namespace: S) -> Self {
+        Self {
+            store,
+            namespace: namespace.into(),
+            entries: Default::default(),
+        }
+    }
+
+    fn lock(&self) -> sync::MutexGuard<'_, HashMap<String, Dependencies>> {
+        self.entries.lock().expect("not to be poisoned and reused")
+    }
+
+    /// What `key` was last rendered from.
+    pub fn get(&self, key: &str) -> Option<Dependencies> {
+        self.lock().get(key).cloned()
+    }
+
+    /// Record what `key` was rendered from.
+    pub fn insert<S: Into<String>>(&self, key: S, dependencies: Dependencies) {
+        self.lock().insert(key.into(), dependencies);
+    }
+
+    /// The keys that a change to the global at `path`, e.g. `product`, may affect, in order.
+    pub fn keys_depending_on_global(&self, path: &str) -> Vec<String> {
+        self.keys(|dependencies| dependencies.depends_on_global(path))
+    }
+
+    /// The keys that a change to the partial `name` may affect, in order.
+    pub fn keys_depending_on_partial(&self, name: &str) -> Vec<String> {
+        self.keys(|dependencies| dependencies.depends_on_partial(name))
+    }
+
+    /// Remove the entries that a change to the global at `path` may affect, returning their
+    /// keys.
+    pub fn invalidate_global(&self, path: &str) -> Vec<String> {
+        self.invalidate(self.keys_depending_on_global(path))
+    }
+
+    /// Remove the entries that a change to the partial `name` may affect, returning their keys.
+    pub fn invalidate_partial(&self, name: &str) -> Vec<String> {
+        self.invalidate(self.keys_depending_on_partial(name))
+    }
+
+    fn keys<F: Fn(&Dependencies) -> bool>(&self, depends: F) -> Vec<String> {
+        let mut keys: Vec<_> = self
+            .lock()
+            .iter()
+            .filter(|(_, dependencies)| depends(dependencies))
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn invalidate(&self, keys: Vec<String>) -> Vec<String> {
+        let mut entries = self.lock();
+        for key in keys.iter() {
+            entries.remove(key);
+            self.store.remove(&self.namespace, key);
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check_store(store: &dyn CacheStore) {
+        assert_eq!(store.get("a", "key"), None);
+        store.set("a", "key", "value\nwith lines".to_owned(), None);
+        store.set("b", "key", "other".to_owned(), None);
+        assert_eq!(store.get("a", "key"), Some("value\nwith lines".to_owned()));
+        assert_eq!(store.get("b", "key"), Some("other".to_owned()));
+
+        store.set(
+            "a",
+            "expired",
+            "gone".to_owned(),
+            Some(time::Duration::from_secs(0)),
+        );
+        assert_eq!(store.get("a", "expired"), None);
+        store.set(
+            "a",
+            "kept",
+            "here".to_owned(),
+            Some(time::Duration::from_secs(60)),
+        );
+        assert_eq!(store.get("a", "kept"), Some("here".to_owned()));
+
+        assert!(store.remove("a", "kept"));
+        assert!(!store.remove("a", "kept"));
+        store.clear("a");
+        assert_eq!(store.get("a", "key"), None);
+        assert_eq!(store.get("b", "key"), Some("other".to_owned()));
+    }
+
+    #[test]
+    fn memory_store() {
+        check_store(&MemoryStore::new());
+    }
+
+    #[test]
+    fn disk_store() {
+        let root = ::std::env::temp_dir().join(format!("liquid-cache-{}", ::std::process::id()));
+        let store = DiskStore::new(&root);
+        check_store(&store);
+        store.set("../escape", "../../key", "safe".to_owned(), None);
+        assert_eq!(store.get("../escape", "../../key"), Some("safe".to_owned()));
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,130 @@
+//! The format of compiled templates, see `Parser::compile`.
+//!
+//! A compiled template is the template source with the `Chunk`s parsing it split it into, after
+//! a header naming the version of liquid that compiled it.  Integers are little-endian `u32`s
+//! and strings are their length followed by their UTF-8 bytes.
+
+use std::str;
+
+use liquid_compiler::Chunk;
+use liquid_error::{Error, Result};
+
+const MAGIC: &[u8] = b"LIQUIDC\0";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Encode the template `source`, split into `chunks`.
+pub(crate) fn encode(source: &str, chunks: &[Chunk]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MAGIC.len() + source.len() + 17 * chunks.len() + 32);
+    bytes.extend_from_slice(MAGIC);
+    put_str(&mut bytes, VERSION);
+    put_str(&mut bytes, source);
+    put_u32(&mut bytes, chunks.len());
+    for chunk in chunks {
+        put_u32(&mut bytes, chunk.span.start);
+        put_u32(&mut bytes, chunk.span.end);
+        put_u32(&mut bytes, chunk.line);
+        put_u32(&mut bytes, chunk.column);
+        bytes.push(chunk.text as u8);
+    }
+    bytes
+}
+
+/// Decode a template compiled by this version of liquid into its source and chunks.
+pub(crate) fn decode(bytes: &[u8]) -> Result<(&str, Vec<Chunk>)> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Error::with_msg("Not a compiled template").into_err();
+    }
+    let version = reader.str()?;
+    if version != VERSION {
+        return Error::with_msg("Template compiled by another version of liquid")
+            .context("compiled by", version.to_owned())
+            .context("expected", VERSION)
+            .into_err();
+    }
+    let source = reader.str()?;
+    let count = reader.u32()?;
+    let mut chunks = Vec::with_capacity(count.min(reader.bytes.len() / 17));
+    for _ in 0..count {
+        let start = reader.u32()?;
+        let end = reader.u32()?;
+        chunks.push(Chunk {
+            span: start..end,
+            line: reader.u32()?,
+            column: reader.u32()?,
+            text: reader.take(1)?[0] != 0,
+        });
+    }
+    if !reader.bytes.is_empty() {
+        return corrupted();
+    }
+    Ok((source, chunks))
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: usize) {
+    let value = value as u32;
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(bytes: &mut Vec<u8>, value: &str) {
+    put_u32(bytes, value.len());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8]> {
+        if self.bytes.len() < len {
+            return corrupted();
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<usize> {
+        let mut value = [0; 4];
+        value.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(value) as usize)
+    }
+
+    fn str(&mut self) -> Result<&'b str> {
+        let len = self.u32()?;
+        str::from_utf8(self.take(len)?).or_else(|_| corrupted())
+    }
+}
+
+fn corrupted<T>() -> Result<T> {
+    Error::with_msg("Corrupted compiled template").into_err()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let chunks = vec![
+            Chunk {
+                span: 0..3,
+                line: 1,
+                column: 1,
+                text: true,
+            },
+            Chunk {
+                span: 3..10,
+                line: 1,
+                column: 4,
+                text: false,
+            },
+        ];
+        let bytes = encode("Hi {{ a }}", &chunks);
+        assert_eq!(decode(&bytes).unwrap(), ("Hi {{ a }}", chunks));
+
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(b"nope").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Find the parts of templates a set of renders never reaches, like dead branches.
+//!
+//! Templates parsed by a parser instrumented with a `Coverage` count how often each of their
+//! nodes, that is text, outputs and tags, renders:
+//!
+//! ```rust
+//! use liquid::coverage::Coverage;
+//!
+//! let coverage = Coverage::new();
+//! let parser = liquid::ParserBuilder::with_liquid()
+//!     .instrument(coverage.clone())
+//!     .build()
+//!     .unwrap();
+//! let template = parser
+//!     .parse("{% if admin %}Hello, boss{% else %}Hello{% endif %}")
+//!     .unwrap();
+//! template.render(&liquid::value::Object::new()).unwrap();
+//!
+//! let report = coverage.report();
+//! let uncovered: Vec<_> = report.templates[0].uncovered().collect();
+//! assert_eq!(uncovered.len(), 1);
+//! assert_eq!(uncovered[0].node.column, 15);
+//! ```
+//!
+//! Parse templates one at a time while collecting coverage, so their nodes are told apart.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use liquid_compiler::{Instrument, NodeInfo};
+use liquid_error::Result;
+use liquid_interpreter::{Context, Output, References, Renderable};
+use liquid_value::Object;
+
+#[derive(Debug, Default)]
+struct State {
+    templates: Vec<TemplateState>,
+}
+
+#[derive(Debug, Default)]
+struct TemplateState {
+    name: Option<String>,
+    nodes: Vec<(NodeInfo, usize)>,
+}
+
+/// Collects which nodes of templates are rendered.
+///
+/// Clones share their counts, so one can be given to a parser and another kept for the report.
+#[derive(Clone, Debug, Default)]
+pub struct Coverage {
+    state: Arc<Mutex<State>>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often each node of the instrumented templates has rendered so far.
+    pub fn report(&self) -> CoverageReport {
+        let state = self.state.lock().expect("coverage is never poisoned");
+        let templates = state
+            .templates
+            .iter()
+            .map(|template| {
+                let mut nodes: Vec<_> = template
+                    .nodes
+                    .iter()
+                    .map(|(node, hits)| NodeCoverage {
+                        node: node.clone(),
+                        hits: *hits,
+                    })
+                    .collect();
+                nodes.sort_by_key(|n| (n.node.line, n.node.column));
+                TemplateCoverage {
+                    name: template.name.clone(),
+                    nodes,
+                }
+            })
+            .collect();
+        CoverageReport { templates }
+    }
+
+    /// Forget the renders so far, keeping the instrumented templates.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().expect("coverage is never poisoned");
+        for template in &mut state.templates {
+            for node in &mut template.nodes {
+                node.1 = 0;
+            }
+        }
+    }
+}
+
+impl Instrument for Coverage {
+    fn template(&self, _source: &str) {
+        let mut state = self.state.lock().expect("coverage is never poisoned");
+        state.templates.push(TemplateState::default());
+    }
+
+    fn instrument(&self, node: NodeInfo, renderable: Box<dyn Renderable>) -> Box<dyn Renderable> {
+        let mut state = self.state.lock().expect("coverage is never poisoned");
+        if state.templates.is_empty() {
+            state.templates.push(TemplateState::default());
+        }
+        let template = state.templates.len() - 1;
+        let nodes = &mut state.templates[template].nodes;
+        nodes.push((node, 0));
+        Box::new(Covered {
+            state: self.state.clone(),
+            template,
+            node: nodes.len() - 1,
+            renderable,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Covered {
+    state: Arc<Mutex<State>>,
+    template: usize,
+    node: usize,
+    renderable: Box<dyn Renderable>,
+}
+
+impl Renderable for Covered {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        {
+            let mut state = self.state.lock().expect("coverage is never poisoned");
+            let template = &mut state.templates[self.template];
+            template.nodes[self.node].1 += 1;
+            if template.name.is_none() {
+                template.name = context.stack().frame_name().map(str::to_owned);
+            }
+        }
+        self.renderable.render_to(writer, context)
+    }
+
+    fn metadata(&self) -> Option<&Object> {
+        self.renderable.metadata()
+    }
+
+    fn references(&self, references: &mut References) {
+        self.renderable.references(references)
+    }
+}
+
+/// The coverage of every instrumented template, in the order they were parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub templates: Vec<TemplateCoverage>,
+}
+
+/// The coverage of a template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateCoverage {
+    /// The name of a partial, once it was rendered by `include`.
+    pub name: Option<String>,
+    /// The nodes of the template, in source order.
+    pub nodes: Vec<NodeCoverage>,
+}
+
+impl TemplateCoverage {
+    /// The nodes that never rendered.
+    pub fn uncovered(&self) -> impl Iterator<Item = &NodeCoverage> {
+        self.nodes.iter().filter(|n| n.hits == 0)
+    }
+
+    /// The number of nodes that rendered at least once.
+    pub fn covered(&self) -> usize {
+        self.nodes.iter().filter(|n| n.hits > 0).count()
+    }
+}
+
+/// How often a node rendered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeCoverage {
+    pub node: NodeInfo,
+    pub hits: usize,
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for template in &self.templates {
+            writeln!(
+                f,
+                "{}: {}/{} nodes rendered",
+                template.name.as_deref().unwrap_or("template"),
+                template.covered(),
+                template.nodes.len()
+            )?;
+            for node in template.uncovered() {
+                writeln!(
+                    f,
+                    "  {}:{} {} never rendered",
+                    node.node.line, node.node.column, node.node.kind
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_compiler::NodeKind;
+    use liquid_value::{Object, Value};
+    use parser::ParserBuilder;
+    use partials::{InMemorySource, LazyCompiler};
+
+    #[test]
+    fn branches() {
+        let coverage = Coverage::new();
+        let parser = ParserBuilder::with_liquid()
+            .instrument(coverage.clone())
+            .build()
+            .unwrap();
+        let template = parser
+            .parse("{% for i in items %}{{ i }}{% else %}none{% endfor %}\n{% if x %}x{% endif %}")
+            .unwrap();
+
+        let mut globals = Object::new();
+        globals.insert("items".into(), Value::array(vec![Value::scalar(1)]));
+        template.render(&globals).unwrap();
+        template.render(&globals).unwrap();
+
+        let report = coverage.report();
+        assert_eq!(report.templates.len(), 1);
+        let hits: Vec<_> = report.templates[0]
+            .nodes
+            .iter()
+            .map(|n| (n.node.kind.to_string(), n.hits))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![
+                ("{% for %}".to_owned(), 2),
+                ("output".to_owned(), 2),
+                ("text".to_owned(), 0),
+                ("text".to_owned(), 2),
+                ("{% if %}".to_owned(), 2),
+                ("text".to_owned(), 0),
+            ]
+        );
+        assert_eq!(
+            report.to_string(),
+            "template: 4/6 nodes rendered\n  1:38 text never rendered\n  2:11 text never rendered\n"
+        );
+
+        coverage.reset();
+        assert_eq!(coverage.report().templates[0].covered(), 0);
+    }
+
+    #[test]
+    fn partials() {
+        let mut source = InMemorySource::new();
+        source.add("card", "{{ card }}");
+        source.add("unused", "never");
+        let coverage = Coverage::new();
+        let parser = ParserBuilder::with_liquid()
+            .instrument(coverage.clone())
+            .partials(LazyCompiler::new(source))
+            .build()
+            .unwrap();
+        let template = parser.parse("{% include 'card' %}").unwrap();
+        template.render(&Object::new()).unwrap();
+
+        let report = coverage.report();
+        let card = report
+            .templates
+            .iter()
+            .find(|t| t.name.as_deref() == Some("card"))
+            .unwrap();
+        assert_eq!(card.nodes[0].node.kind, NodeKind::Output);
+        assert_eq!(card.nodes[0].hits, 1);
+    }
+}
@@ -0,0 +1,375 @@
+//! Compare a render against the output of another Liquid implementation.
+//!
+//! When migrating from Ruby Liquid, render the same source and globals here and compare with
+//! the output Ruby produced.  The first divergence is traced back to the node of the template
+//! that wrote it:
+//!
+//! ```rust
+//! use liquid::differential::render_diff;
+//!
+//! let mut globals = liquid::value::Object::new();
+//! globals.insert("price".into(), liquid::value::Value::scalar(7));
+//! let source = "Total:\n{{ price | divided_by: 2.0 }} EUR";
+//! let diff = render_diff(liquid::ParserBuilder::with_liquid(), source, &globals, "Total:\n3,5 EUR")
+//!     .unwrap();
+//!
+//! let divergence = diff.divergence.unwrap();
+//! assert_eq!((divergence.line, divergence.column), (2, 2));
+//! let node = divergence.node.unwrap();
+//! assert_eq!((node.line, node.column), (2, 1));
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use liquid_compiler::{Instrument, NodeInfo};
+use liquid_error::Result;
+use liquid_interpreter::{Context, Output, References, Renderable, ValueStore};
+use liquid_value::Object;
+
+use parser::ParserBuilder;
+use partials::PartialCompiler;
+
+/// How far to show the output around a divergence.
+const EXCERPT: usize = 20;
+
+/// Render `source` with `globals` and compare the output with `expected`.
+///
+/// The parser is built from `builder`, with partials parsed the same way as `source`.
+pub fn render_diff<P: PartialCompiler>(
+    builder: ParserBuilder<P>,
+    source: &str,
+    globals: &dyn ValueStore,
+    expected: &str,
+) -> Result<RenderDiff> {
+    let trace = Trace::default();
+    let parser = builder.instrument(trace.clone()).build()?;
+    let template = parser.parse(source)?;
+    let output = template.render(globals)?;
+
+    let divergence = first_difference(&output, expected).map(|offset| {
+        let spans = trace.spans.lock().expect("trace is never poisoned");
+        let span = innermost(&spans, offset, output.len());
+        let (line, column) = line_col(&output, offset);
+        Divergence {
+            offset,
+            line,
+            column,
+            actual: excerpt(&output, offset),
+            expected: excerpt(expected, offset),
+            node: span.map(|s| s.node.clone()),
+            partial: span.and_then(|s| s.partial.clone()),
+        }
+    });
+
+    Ok(RenderDiff { output, divergence })
+}
+
+/// The result of `render_diff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderDiff {
+    /// The output of the render.
+    pub output: String,
+    /// Where the output first differs from the expected one, if it does.
+    pub divergence: Option<Divergence>,
+}
+
+impl RenderDiff {
+    /// Whether the output is the expected one.
+    pub fn matches(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Where a render first differs from the expected output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The byte offset in both outputs.
+    pub offset: usize,
+    /// The line of the output, from 1.
+    pub line: usize,
+    /// The column of the output, in characters from 1.
+    pub column: usize,
+    /// The actual output from the divergence on, shortened.
+    pub actual: String,
+    /// The expected output from the divergence on, shortened.
+    pub expected: String,
+    /// The node of the template that wrote the differing output, if any did.
+    pub node: Option<NodeInfo>,
+    /// The partial holding `node`, when it is not part of the rendered template itself.
+    pub partial: Option<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "output differs at {}:{}: expected {:?}, got {:?}",
+            self.line, self.column, self.expected, self.actual
+        )?;
+        if let Some(ref node) = self.node {
+            write!(
+                f,
+                ", written by {} at {}:{}",
+                node.kind, node.line, node.column
+            )?;
+            if let Some(ref partial) = self.partial {
+                write!(f, " of {}", partial)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The output a node wrote.
+#[derive(Clone, Debug)]
+struct Span {
+    node: NodeInfo,
+    partial: Option<String>,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Default)]
+struct Position {
+    /// How deep in nested nodes rendering is.
+    depth: usize,
+    /// The bytes written so far.
+    offset: usize,
+}
+
+/// Records the spans of output written by each node.
+#[derive(Clone, Debug, Default)]
+struct Trace {
+    spans: Arc<Mutex<Vec<Span>>>,
+    position: Arc<Mutex<Position>>,
+}
+
+impl Instrument for Trace {
+    fn instrument(&self, node: NodeInfo, renderable: Box<dyn Renderable>) -> Box<dyn Renderable> {
+        Box::new(Traced {
+            trace: self.clone(),
+            node,
+            renderable,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Traced {
+    trace: Trace,
+    node: NodeInfo,
+    renderable: Box<dyn Renderable>,
+}
+
+impl Renderable for Traced {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let (start, outermost) = {
+            let mut position = self.trace.position.lock().expect("trace is never poisoned");
+            position.depth += 1;
+            (position.offset, position.depth == 1)
+        };
+
+        // Only the outermost node counts the bytes, nested ones write through it.
+        let result = if outermost {
+            let mut counting = Counting {
+                writer,
+                position: &self.trace.position,
+            };
+            self.renderable.render_to(&mut counting, context)
+        } else {
+            self.renderable.render_to(writer, context)
+        };
+
+        let end = {
+            let mut position = self.trace.position.lock().expect("trace is never poisoned");
+            position.depth -= 1;
+            position.offset
+        };
+        self.trace
+            .spans
+            .lock()
+            .expect("trace is never poisoned")
+            .push(Span {
+                node: self.node.clone(),
+                partial: context.stack().frame_name().map(str::to_owned),
+                start,
+                end,
+            });
+        result
+    }
+
+    fn metadata(&self) -> Option<&Object> {
+        self.renderable.metadata()
+    }
+
+    fn references(&self, references: &mut References) {
+        self.renderable.references(references)
+    }
+}
+
+/// Forwards to `writer`, counting the bytes written.
+struct Counting<'w> {
+    writer: &'w mut dyn Output,
+    position: &'w Mutex<Position>,
+}
+
+impl<'w> Counting<'w> {
+    fn count(&self, written: usize) {
+        self.position
+            .lock()
+            .expect("trace is never poisoned")
+            .offset += written;
+    }
+}
+
+impl<'w> Write for Counting<'w> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.count(written);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<'w> Output for Counting<'w> {
+    fn write_value(&mut self, value: &str, safe: bool) -> io::Result<()> {
+        self.writer.write_value(value, safe)?;
+        self.count(value.len());
+        Ok(())
+    }
+}
+
+/// The byte offset of the first character that differs, if the strings differ.
+fn first_difference(actual: &str, expected: &str) -> Option<usize> {
+    let mut expected_chars = expected.char_indices();
+    for (offset, c) in actual.char_indices() {
+        match expected_chars.next() {
+            Some((_, e)) if e == c => (),
+            _ => return Some(offset),
+        }
+    }
+    if actual.len() == expected.len() {
+        None
+    } else {
+        Some(actual.len())
+    }
+}
+
+/// The deepest span covering `offset`, or the last one ending at it when the output stops there.
+fn innermost(spans: &[Span], offset: usize, len: usize) -> Option<&Span> {
+    // Nested spans are recorded before those containing them.
+    let covering = spans.iter().find(|s| s.start <= offset && offset < s.end);
+    if covering.is_some() || offset < len {
+        return covering;
+    }
+    spans
+        .iter()
+        .filter(|s| s.end == offset && s.start < s.end)
+        .max_by_key(|s| s.start)
+}
+
+fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+fn excerpt(text: &str, offset: usize) -> String {
+    text.get(offset..)
+        .unwrap_or_default()
+        .chars()
+        .take(EXCERPT)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_compiler::NodeKind;
+    use liquid_value::{Object, Value};
+    use partials::{InMemorySource, LazyCompiler};
+
+    fn globals() -> Object {
+        let mut globals = Object::new();
+        globals.insert(
+            "items".into(),
+            Value::array(vec![Value::scalar("a"), Value::scalar("b")]),
+        );
+        globals
+    }
+
+    #[test]
+    fn matching() {
+        let diff = render_diff(
+            ParserBuilder::with_liquid(),
+            "{% for i in items %}{{ i }},{% endfor %}",
+            &globals(),
+            "a,b,",
+        )
+        .unwrap();
+        assert!(diff.matches());
+        assert_eq!(diff.output, "a,b,");
+    }
+
+    #[test]
+    fn inside_block() {
+        let diff = render_diff(
+            ParserBuilder::with_liquid(),
+            "{% for i in items %}{{ i | upcase }}, {% endfor %}",
+            &globals(),
+            "A, b, ",
+        )
+        .unwrap();
+        let divergence = diff.divergence.unwrap();
+        assert_eq!(divergence.offset, 3);
+        assert_eq!(divergence.actual, "B, ");
+        assert_eq!(divergence.expected, "b, ");
+        let node = divergence.node.clone().unwrap();
+        assert_eq!(node.kind, NodeKind::Output);
+        assert_eq!((node.line, node.column), (1, 21));
+        assert_eq!(
+            divergence.to_string(),
+            "output differs at 1:4: expected \"b, \", got \"B, \", written by output at 1:21"
+        );
+    }
+
+    #[test]
+    fn shorter_output() {
+        let diff = render_diff(
+            ParserBuilder::with_liquid(),
+            "a{% if false %}b{% endif %}",
+            &Object::new(),
+            "ab",
+        )
+        .unwrap();
+        let divergence = diff.divergence.unwrap();
+        assert_eq!(divergence.offset, 1);
+        assert_eq!(divergence.actual, "");
+        assert_eq!(divergence.node.unwrap().kind, NodeKind::Text);
+    }
+
+    #[test]
+    fn in_partial() {
+        let mut source = InMemorySource::new();
+        source.add("row", "<td>{{ i }}</td>");
+        let builder = ParserBuilder::with_liquid().partials(LazyCompiler::new(source));
+        let diff = render_diff(
+            builder,
+            "{% for i in items %}{% include 'row' %}{% endfor %}",
+            &globals(),
+            "<td>a</td><td>b</td >",
+        )
+        .unwrap();
+        let divergence = diff.divergence.unwrap();
+        assert_eq!(divergence.partial.as_deref(), Some("row"));
+        let node = divergence.node.unwrap();
+        assert_eq!((node.kind, node.column), (NodeKind::Text, 12));
+    }
+}
@@ -0,0 +1,77 @@
+//! The XML escaping shared by the extra and Jekyll filters.
+
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::Value;
+
+pub(crate) fn xml_escape(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "xml_escape",
+    description = "Escapes a string for use in XML text or attribute values.",
+    parsed(XmlEscapeFilter)
+)]
+pub struct XmlEscape;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "xml_escape"]
+struct XmlEscapeFilter;
+
+impl Filter for XmlEscapeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(xml_escape(&input.to_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_xml_escape() {
+        assert_eq!(
+            unit!(XmlEscape, tos!("<p class=\"a\">Tom & Jerry's</p>")),
+            tos!("&lt;p class=&quot;a&quot;&gt;Tom &amp; Jerry's&lt;/p&gt;")
+        );
+        assert_eq!(unit!(XmlEscape, Value::scalar(5)), tos!("5"));
+        assert_eq!(unit!(XmlEscape, Value::Nil), tos!(""));
+    }
+}
@@ -0,0 +1,458 @@
+use std::fmt;
+
+use filters::helpers::{argument_in_range, literal_str};
+use filters::invalid_input;
+use liquid_compiler::{
+    Filter, FilterArguments, FilterParameters, FilterReflection, Language, ParseFilter,
+};
+use liquid_derive::*;
+use liquid_error::{Result, ResultLiquidExt};
+use liquid_interpreter::{Context, ContextBuilder, Expression};
+use liquid_value::{Array, Object, Value};
+use tags::{parse_condition_str, Condition};
+
+// liquid-rust proprietary
+
+#[derive(Debug, FilterParameters)]
+struct PartitionArgs {
+    #[parameter(description = "The property being matched", arg_type = "str")]
+    property: Expression,
+    #[parameter(
+        description = "The value the property is matched with",
+        arg_type = "any"
+    )]
+    target_value: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "partition",
+    description = "Split an array into the elements that match a certain property value and those that don't, \
+                   returned as a two-element array. By default the target is any truthy value.",
+    parameters(PartitionArgs),
+    parsed(PartitionFilter)
+)]
+pub struct Partition;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "partition"]
+struct PartitionFilter {
+    #[parameters]
+    args: PartitionArgs,
+}
+
+impl Filter for PartitionFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let property: &str = &args.property;
+        let target_value: Option<&Value> = args.target_value;
+
+        let array = match input {
+            Value::Array(array) => array.clone(),
+            Value::Object(_) => vec![input.clone()].into(),
+            Value::Nil => Array::new(),
+            _ => {
+                return Err(invalid_input(
+                    "Array of objects or a single object expected",
+                ));
+            }
+        };
+
+        let (matching, rest): (Vec<_>, Vec<_>) = array.into_iter().partition(|value| {
+            let value = value.as_object().and_then(|object| object.get(property));
+            match (value, target_value) {
+                (Some(value), None) => value.is_truthy(),
+                (Some(value), Some(target_value)) => value == target_value,
+                (None, _) => false,
+            }
+        });
+
+        Ok(Value::array(vec![
+            Value::array(matching),
+            Value::array(rest),
+        ]))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct PartitionExpArgs {
+    #[parameter(
+        description = "The name each element is bound to in the expression.",
+        arg_type = "str"
+    )]
+    variable: Expression,
+    #[parameter(
+        description = "The condition an element must meet, as in an `if` tag.",
+        arg_type = "str"
+    )]
+    expression: Expression,
+}
+
+#[derive(Clone, FilterReflection)]
+#[filter(
+    name = "partition_exp",
+    description = "Split an array into the elements for which an expression is true and those for which it isn't, \
+                   returned as a two-element array, e.g. `{{ items | partition_exp: \"item\", \"item.price > 20\" }}`.",
+    parameters(PartitionExpArgs)
+)]
+pub struct PartitionExp;
+
+impl ParseFilter for PartitionExp {
+    fn parse(&self, arguments: FilterArguments) -> Result<Box<dyn Filter>> {
+        self.parse_with_language(arguments, &Language::empty())
+    }
+
+    fn parse_with_language(
+        &self,
+        arguments: FilterArguments,
+        language: &Language,
+    ) -> Result<Box<dyn Filter>> {
+        let args = PartitionExpArgs::from_args(arguments)?;
+        let variable = literal_str("variable", args.variable)?;
+        let expression = literal_str("expression", args.expression)?;
+        let condition = parse_condition_str(&expression, language)
+            .context_key("expression")
+            .value_with(|| expression.clone().into())?;
+
+        Ok(Box::new(PartitionExpFilter {
+            variable,
+            expression,
+            condition,
+        }))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct PartitionExpFilter {
+    variable: String,
+    expression: String,
+    condition: Condition,
+}
+
+impl fmt::Display for PartitionExpFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "partition_exp: \"{}\", \"{}\"",
+            self.variable, self.expression
+        )
+    }
+}
+
+impl Filter for PartitionExpFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let array = match input {
+            Value::Array(array) => array.clone(),
+            Value::Object(_) => vec![input.clone()].into(),
+            Value::Nil => Array::new(),
+            _ => {
+                return Err(invalid_input(
+                    "Array of objects or a single object expected",
+                ));
+            }
+        };
+
+        // Evaluate the condition in a scope of its own, seeing every variable of the template.
+        let mut scope = ContextBuilder::new()
+            .set_globals(context.stack())
+            .set_environment(context.environment().clone())
+            .build();
+
+        let mut matching = Vec::new();
+        let mut rest = Vec::new();
+        for item in array {
+            let keep = scope.run_in_scope(|scope| {
+                scope.stack_mut().set(self.variable.clone(), item.clone());
+                self.condition.evaluate(scope)
+            })?;
+            if keep {
+                matching.push(item);
+            } else {
+                rest.push(item);
+            }
+        }
+
+        Ok(Value::array(vec![
+            Value::array(matching),
+            Value::array(rest),
+        ]))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct SlicePagesArgs {
+    #[parameter(
+        description = "The page to show, counting from 1.",
+        arg_type = "integer"
+    )]
+    page: Expression,
+    #[parameter(
+        description = "How many elements are on each page. Defaults to 10.",
+        arg_type = "integer",
+        mode = "keyword"
+    )]
+    per_page: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "slice_pages",
+    description = "Split an array into pages, returning an object with the `items` of one page, its `page` number, \
+                   `per_page`, `total_pages`, `total_items`, and the `previous_page` and `next_page` numbers, \
+                   which are nil on the first and last page.",
+    parameters(SlicePagesArgs),
+    parsed(SlicePagesFilter)
+)]
+pub struct SlicePages;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "slice_pages"]
+struct SlicePagesFilter {
+    #[parameters]
+    args: SlicePagesArgs,
+}
+
+impl Filter for SlicePagesFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let page = argument_in_range("page", args.page, 1..)?;
+        let per_page = argument_in_range("per_page", args.per_page.unwrap_or(10), 1..)?;
+
+        let array: &[Value] = match input {
+            Value::Array(array) => array,
+            Value::Nil => &[],
+            _ => return Err(invalid_input("Array expected")),
+        };
+
+        // An empty array still has a first page, with nothing on it.
+        let total_pages = array.len().div_ceil(per_page as usize).max(1);
+        let items = array
+            .chunks(per_page as usize)
+            .nth(page as usize - 1)
+            .unwrap_or(&[]);
+        let page_number = |page: usize| {
+            if 1 <= page && page <= total_pages {
+                Value::scalar(page as i32)
+            } else {
+                Value::Nil
+            }
+        };
+
+        let mut pagination = Object::new();
+        pagination.insert("items".into(), Value::array(items.to_vec()));
+        pagination.insert("page".into(), Value::scalar(page));
+        pagination.insert("per_page".into(), Value::scalar(per_page));
+        pagination.insert("total_pages".into(), Value::scalar(total_pages as i32));
+        pagination.insert("total_items".into(), Value::scalar(array.len() as i32));
+        pagination.insert("previous_page".into(), page_number(page as usize - 1));
+        pagination.insert("next_page".into(), page_number(page as usize + 1));
+        Ok(Value::Object(pagination))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use liquid_value::liquid_value;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:ident, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+                .unwrap_err()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_partition_truthy() {
+        let input = liquid_value!([
+            {"handle": "alpha", "ok": true},
+            {"handle": "beta", "ok": false},
+            {"handle": "gamma"},
+            {"handle": "delta", "ok": true},
+        ]);
+        let desired_result = liquid_value!([
+            [
+                {"handle": "alpha", "ok": true},
+                {"handle": "delta", "ok": true},
+            ],
+            [
+                {"handle": "beta", "ok": false},
+                {"handle": "gamma"},
+            ],
+        ]);
+        assert_eq!(unit!(Partition, input, tos!("ok")), desired_result);
+    }
+
+    #[test]
+    fn unit_partition_target() {
+        let input = liquid_value!([
+            {"handle": "alpha", "type": "kitchen"},
+            {"handle": "beta", "type": "garden"},
+            {"handle": "gamma", "type": "kitchen"},
+        ]);
+        let desired_result = liquid_value!([
+            [
+                {"handle": "alpha", "type": "kitchen"},
+                {"handle": "gamma", "type": "kitchen"},
+            ],
+            [
+                {"handle": "beta", "type": "garden"},
+            ],
+        ]);
+        assert_eq!(
+            unit!(Partition, input, tos!("type"), tos!("kitchen")),
+            desired_result
+        );
+    }
+
+    #[test]
+    fn unit_partition_non_objects() {
+        let input = liquid_value!([1, {"ok": true}, "a"]);
+        let desired_result = liquid_value!([[{"ok": true}], [1, "a"]]);
+        assert_eq!(unit!(Partition, input, tos!("ok")), desired_result);
+    }
+
+    #[test]
+    fn unit_partition_nil() {
+        let desired_result = liquid_value!([[], []]);
+        assert_eq!(unit!(Partition, Value::Nil, tos!("ok")), desired_result);
+    }
+
+    #[test]
+    fn unit_partition_invalid_input() {
+        failed!(Partition, tos!("abc"), tos!("ok"));
+    }
+
+    #[test]
+    fn unit_partition_no_args() {
+        failed!(Partition, liquid_value!([]));
+    }
+
+    #[test]
+    fn unit_partition_exp() {
+        let input = liquid_value!([
+            {"handle": "alpha", "price": 10},
+            {"handle": "beta", "price": 30},
+            {"handle": "gamma", "price": 40},
+        ]);
+        let desired_result = liquid_value!([
+            [
+                {"handle": "beta", "price": 30},
+                {"handle": "gamma", "price": 40},
+            ],
+            [
+                {"handle": "alpha", "price": 10},
+            ],
+        ]);
+        assert_eq!(
+            unit!(PartitionExp, input, tos!("item"), tos!("item.price > 20")),
+            desired_result
+        );
+    }
+
+    #[test]
+    fn unit_partition_exp_invalid() {
+        failed!(PartitionExp, liquid_value!([]), tos!("item"));
+        failed!(
+            PartitionExp,
+            liquid_value!([]),
+            tos!("item"),
+            tos!("item >")
+        );
+        failed!(PartitionExp, tos!("abc"), tos!("item"), tos!("item"));
+    }
+
+    #[test]
+    fn unit_slice_pages() {
+        let input = liquid_value!([1, 2, 3, 4, 5]);
+        let desired_result = liquid_value!({
+            "items": [1, 2, 3, 4, 5],
+            "page": 1,
+            "per_page": 10,
+            "total_pages": 1,
+            "total_items": 5,
+            "previous_page": nil,
+            "next_page": nil,
+        });
+        assert_eq!(unit!(SlicePages, input, Value::scalar(1)), desired_result);
+    }
+
+    #[test]
+    fn unit_slice_pages_per_page() {
+        let mut globals = ::liquid::value::Object::new();
+        globals.insert("posts".into(), liquid_value!([1, 2, 3, 4, 5]));
+        let render = |text: &str| {
+            ::liquid::ParserBuilder::with_liquid()
+                .extra_filters()
+                .build()
+                .unwrap()
+                .parse(text)
+                .unwrap()
+                .render(&globals)
+                .unwrap()
+        };
+        let text = concat!(
+            "{% assign pages = posts | slice_pages: 3, per_page: 2 %}",
+            "{{ pages.items | join: ',' }} {{ pages.page }}/{{ pages.total_pages }} ",
+            "{{ pages.previous_page }}<>{{ pages.next_page }}"
+        );
+        assert_eq!(render(text), "5 3/3 2<>");
+        let text = concat!(
+            "{% assign pages = posts | slice_pages: 4, per_page: 2 %}",
+            "[{{ pages.items | join: ',' }}] {{ pages.previous_page }}<>{{ pages.next_page }}"
+        );
+        assert_eq!(render(text), "[] 3<>");
+    }
+
+    #[test]
+    fn unit_slice_pages_empty() {
+        let pagination = unit!(SlicePages, Value::Nil, Value::scalar(1));
+        let pagination = pagination.as_object().unwrap();
+        assert_eq!(pagination["items"], liquid_value!([]));
+        assert_eq!(pagination["total_pages"], Value::scalar(1));
+    }
+
+    #[test]
+    fn unit_slice_pages_invalid() {
+        failed!(SlicePages, liquid_value!([1]), Value::scalar(0));
+        failed!(SlicePages, tos!("abc"), Value::scalar(1));
+        failed!(SlicePages, liquid_value!([1]));
+    }
+}
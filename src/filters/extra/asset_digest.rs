@@ -0,0 +1,161 @@
+use std::fmt;
+use std::sync::Arc;
+
+use filters::invalid_input;
+use liquid_compiler::{Filter, FilterArguments, FilterReflection, ParseFilter};
+use liquid_derive::*;
+use liquid_error::{Error, Result};
+use liquid_interpreter::Context;
+use liquid_value::Value;
+
+// liquid-rust proprietary
+
+type DigestFn = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+/// Fingerprints an asset URL for cache busting, e.g. `{{ "app.css" | asset_digest }}`
+/// renders `app.css?v=3f2a9c`.
+///
+/// The host supplies the digest of each asset, so this is not registered by
+/// `extra_filters`.
+#[derive(Clone, FilterReflection)]
+#[filter(
+    name = "asset_digest",
+    description = "Appends the content digest of an asset to its URL."
+)]
+pub struct AssetDigest {
+    digest: Arc<DigestFn>,
+}
+
+impl AssetDigest {
+    /// Create the filter from a function mapping asset names to their digest, returning `None`
+    /// for unknown assets.
+    pub fn new<F>(digest: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            digest: Arc::new(digest),
+        }
+    }
+}
+
+impl fmt::Debug for AssetDigest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AssetDigest").finish()
+    }
+}
+
+impl ParseFilter for AssetDigest {
+    fn parse(&self, mut arguments: FilterArguments) -> Result<Box<dyn Filter>> {
+        if arguments.positional.next().is_some() {
+            return Error::with_msg("Invalid number of positional arguments")
+                .context("cause", "expected at most 0 positional arguments")
+                .into_err();
+        }
+        if let Some((name, _)) = arguments.keyword.next() {
+            return Error::with_msg(format!("Unexpected named argument `{}`", name)).into_err();
+        }
+
+        Ok(Box::new(AssetDigestFilter {
+            digest: self.digest.clone(),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+#[derive(Display_filter)]
+#[name = "asset_digest"]
+struct AssetDigestFilter {
+    digest: Arc<DigestFn>,
+}
+
+impl fmt::Debug for AssetDigestFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AssetDigestFilter").finish()
+    }
+}
+
+impl Filter for AssetDigestFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let name = input
+            .as_scalar()
+            .ok_or_else(|| invalid_input("String expected"))?
+            .to_str();
+
+        // Look up the asset without any query or fragment already on the URL.
+        let (path, fragment) = match name.find('#') {
+            Some(index) => name.split_at(index),
+            None => (name.as_ref(), ""),
+        };
+        let asset = path.split('?').next().unwrap_or(path);
+        let digest = (self.digest)(asset)
+            .ok_or_else(|| Error::with_msg("Unknown asset").context("asset", asset.to_owned()))?;
+
+        let separator = if path.contains('?') { '&' } else { '?' };
+        Ok(Value::scalar(format!(
+            "{}{}v={}{}",
+            path, separator, digest, fragment
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:expr, $b:expr) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments {
+                positional,
+                keyword,
+            };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn filter() -> AssetDigest {
+        AssetDigest::new(|name| match name {
+            "app.css" => Some("3f2a9c".to_owned()),
+            "img/logo.svg" => Some("77b0e1".to_owned()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn unit_asset_digest() {
+        assert_eq!(
+            unit!(filter(), tos!("app.css")).unwrap(),
+            tos!("app.css?v=3f2a9c")
+        );
+    }
+
+    #[test]
+    fn unit_asset_digest_existing_query() {
+        assert_eq!(
+            unit!(filter(), tos!("img/logo.svg?size=2#mark")).unwrap(),
+            tos!("img/logo.svg?size=2&v=77b0e1#mark")
+        );
+    }
+
+    #[test]
+    fn unit_asset_digest_unknown() {
+        assert!(unit!(filter(), tos!("missing.js")).is_err());
+        assert!(unit!(filter(), Value::Nil).is_err());
+    }
+}
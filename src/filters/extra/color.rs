@@ -0,0 +1,504 @@
+use filters::{invalid_argument, invalid_input};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+
+// shopify
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Format {
+    Hex,
+    Rgb,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Color {
+    red: f64,
+    green: f64,
+    blue: f64,
+    alpha: f64,
+    format: Format,
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok().map(f64::from);
+    let (red, green, blue) = match hex.len() {
+        3 => {
+            let double = |i: usize| channel(&hex[i..=i].repeat(2));
+            (double(0)?, double(1)?, double(2)?)
+        }
+        6 => (
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        ),
+        _ => return None,
+    };
+    Some(Color {
+        red,
+        green,
+        blue,
+        alpha: 1.0,
+        format: Format::Hex,
+    })
+}
+
+fn parse_rgb(args: &str, with_alpha: bool) -> Option<Color> {
+    let parts: Vec<_> = args.split(',').map(str::trim).collect();
+    let expected = if with_alpha { 4 } else { 3 };
+    if parts.len() != expected {
+        return None;
+    }
+    let channel = |s: &str| s.parse::<u8>().ok().map(f64::from);
+    let alpha = if with_alpha {
+        parts[3]
+            .parse::<f64>()
+            .ok()
+            .filter(|a| (0.0..=1.0).contains(a))?
+    } else {
+        1.0
+    };
+    Some(Color {
+        red: channel(parts[0])?,
+        green: channel(parts[1])?,
+        blue: channel(parts[2])?,
+        alpha,
+        format: Format::Rgb,
+    })
+}
+
+fn parse_color(input: &str) -> Option<Color> {
+    let input = input.trim();
+    if let Some(hex) = input.strip_prefix('#') {
+        parse_hex(hex)
+    } else if let Some(args) = input.strip_prefix("rgba(") {
+        parse_rgb(args.strip_suffix(')')?, true)
+    } else if let Some(args) = input.strip_prefix("rgb(") {
+        parse_rgb(args.strip_suffix(')')?, false)
+    } else {
+        None
+    }
+}
+
+fn input_color(input: &Value) -> Result<Color> {
+    parse_color(&input.to_str()).ok_or_else(|| invalid_input("Hex or rgb color expected"))
+}
+
+fn argument_color(argument: &'static str, value: &str) -> Result<Color> {
+    parse_color(value).ok_or_else(|| invalid_argument(argument, "Hex or rgb color expected"))
+}
+
+fn percentage(argument: &'static str, value: i32) -> Result<f64> {
+    if (0..=100).contains(&value) {
+        Ok(f64::from(value) / 100.0)
+    } else {
+        Err(invalid_argument(
+            argument,
+            "Percentage between 0 and 100 expected",
+        ))
+    }
+}
+
+impl Color {
+    fn to_rgb_string(self) -> String {
+        let (red, green, blue) = self.channels();
+        if self.alpha < 1.0 {
+            format!("rgba({}, {}, {}, {})", red, green, blue, self.alpha)
+        } else {
+            format!("rgb({}, {}, {})", red, green, blue)
+        }
+    }
+
+    fn to_format_string(self) -> String {
+        match self.format {
+            Format::Hex if self.alpha >= 1.0 => {
+                let (red, green, blue) = self.channels();
+                format!("#{:02x}{:02x}{:02x}", red, green, blue)
+            }
+            _ => self.to_rgb_string(),
+        }
+    }
+
+    fn channels(self) -> (u8, u8, u8) {
+        let round = |c: f64| c.round().clamp(0.0, 255.0) as u8;
+        (round(self.red), round(self.green), round(self.blue))
+    }
+
+    /// Hue in `[0, 1)`, saturation and lightness in `[0, 1]`.
+    fn to_hsl(self) -> (f64, f64, f64) {
+        let (r, g, b) = (self.red / 255.0, self.green / 255.0, self.blue / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        let delta = max - min;
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = if lightness > 0.5 {
+            delta / (2.0 - max - min)
+        } else {
+            delta / (max + min)
+        };
+        let hue = if max == r {
+            (g - b) / delta + if g < b { 6.0 } else { 0.0 }
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        (hue / 6.0, saturation, lightness)
+    }
+
+    fn with_hsl(self, hue: f64, saturation: f64, lightness: f64) -> Color {
+        fn channel(p: f64, q: f64, t: f64) -> f64 {
+            let t = if t < 0.0 {
+                t + 1.0
+            } else if t > 1.0 {
+                t - 1.0
+            } else {
+                t
+            };
+            if t < 1.0 / 6.0 {
+                p + (q - p) * 6.0 * t
+            } else if t < 1.0 / 2.0 {
+                q
+            } else if t < 2.0 / 3.0 {
+                p + (q - p) * (2.0 / 3.0 - t) * 6.0
+            } else {
+                p
+            }
+        }
+
+        let (red, green, blue) = if saturation == 0.0 {
+            (lightness, lightness, lightness)
+        } else {
+            let q = if lightness < 0.5 {
+                lightness * (1.0 + saturation)
+            } else {
+                lightness + saturation - lightness * saturation
+            };
+            let p = 2.0 * lightness - q;
+            (
+                channel(p, q, hue + 1.0 / 3.0),
+                channel(p, q, hue),
+                channel(p, q, hue - 1.0 / 3.0),
+            )
+        };
+        Color {
+            red: red * 255.0,
+            green: green * 255.0,
+            blue: blue * 255.0,
+            ..self
+        }
+    }
+
+    fn adjust_lightness(self, amount: f64) -> Color {
+        let (hue, saturation, lightness) = self.to_hsl();
+        let lightness = (lightness + amount).clamp(0.0, 1.0);
+        self.with_hsl(hue, saturation, lightness)
+    }
+
+    /// WCAG 2.0 relative luminance.
+    fn luminance(self) -> f64 {
+        let linear = |c: f64| {
+            let c = c / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "color_to_rgb",
+    description = "Converts a color to its `rgb()` (or `rgba()`) representation.",
+    parsed(ColorToRgbFilter)
+)]
+pub struct ColorToRgb;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "color_to_rgb"]
+struct ColorToRgbFilter;
+
+impl Filter for ColorToRgbFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(input_color(input)?.to_rgb_string()))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct ColorAdjustArgs {
+    #[parameter(
+        description = "The percentage to adjust the lightness by, from 0 to 100.",
+        arg_type = "integer"
+    )]
+    amount: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "color_lighten",
+    description = "Lightens a color by a percentage.",
+    parameters(ColorAdjustArgs),
+    parsed(ColorLightenFilter)
+)]
+pub struct ColorLighten;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "color_lighten"]
+struct ColorLightenFilter {
+    #[parameters]
+    args: ColorAdjustArgs,
+}
+
+impl Filter for ColorLightenFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let amount = percentage("amount", args.amount)?;
+
+        let color = input_color(input)?.adjust_lightness(amount);
+        Ok(Value::scalar(color.to_format_string()))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "color_darken",
+    description = "Darkens a color by a percentage.",
+    parameters(ColorAdjustArgs),
+    parsed(ColorDarkenFilter)
+)]
+pub struct ColorDarken;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "color_darken"]
+struct ColorDarkenFilter {
+    #[parameters]
+    args: ColorAdjustArgs,
+}
+
+impl Filter for ColorDarkenFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let amount = percentage("amount", args.amount)?;
+
+        let color = input_color(input)?.adjust_lightness(-amount);
+        Ok(Value::scalar(color.to_format_string()))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct ColorMixArgs {
+    #[parameter(description = "The color to mix in.", arg_type = "str")]
+    color: Expression,
+    #[parameter(
+        description = "The percentage of the input color in the mix, from 0 to 100.",
+        arg_type = "integer"
+    )]
+    weight: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "color_mix",
+    description = "Blends two colors together.",
+    parameters(ColorMixArgs),
+    parsed(ColorMixFilter)
+)]
+pub struct ColorMix;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "color_mix"]
+struct ColorMixFilter {
+    #[parameters]
+    args: ColorMixArgs,
+}
+
+impl Filter for ColorMixFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let other = argument_color("color", &args.color)?;
+        let weight = percentage("weight", args.weight)?;
+
+        let color = input_color(input)?;
+        let mix = |a: f64, b: f64| a * weight + b * (1.0 - weight);
+        let mixed = Color {
+            red: mix(color.red, other.red),
+            green: mix(color.green, other.green),
+            blue: mix(color.blue, other.blue),
+            alpha: mix(color.alpha, other.alpha),
+            format: color.format,
+        };
+        Ok(Value::scalar(mixed.to_format_string()))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct ColorContrastArgs {
+    #[parameter(description = "The color to compare against.", arg_type = "str")]
+    color: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "color_contrast",
+    description = "Calculates the WCAG contrast ratio between two colors, e.g. `4.5`.",
+    parameters(ColorContrastArgs),
+    parsed(ColorContrastFilter)
+)]
+pub struct ColorContrast;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "color_contrast"]
+struct ColorContrastFilter {
+    #[parameters]
+    args: ColorContrastArgs,
+}
+
+impl Filter for ColorContrastFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let other = argument_color("color", &args.color)?.luminance();
+
+        let color = input_color(input)?.luminance();
+        let ratio = (color.max(other) + 0.05) / (color.min(other) + 0.05);
+        Ok(Value::scalar((ratio * 10.0).round() / 10.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:ident, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+                .unwrap_err()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_color_to_rgb() {
+        assert_eq!(
+            unit!(ColorToRgb, tos!("#7ab55c")),
+            tos!("rgb(122, 181, 92)")
+        );
+        assert_eq!(unit!(ColorToRgb, tos!("#fff")), tos!("rgb(255, 255, 255)"));
+        assert_eq!(
+            unit!(ColorToRgb, tos!("rgba(122, 181, 92, 0.5)")),
+            tos!("rgba(122, 181, 92, 0.5)")
+        );
+    }
+
+    #[test]
+    fn unit_color_invalid() {
+        failed!(ColorToRgb, tos!("7ab55c"));
+        failed!(ColorToRgb, tos!("#7ab55"));
+        failed!(ColorToRgb, tos!("rgb(256, 0, 0)"));
+        failed!(ColorToRgb, tos!("rgba(0, 0, 0, 2)"));
+    }
+
+    #[test]
+    fn unit_color_lighten() {
+        assert_eq!(
+            unit!(ColorLighten, tos!("#7ab55c"), Value::scalar(30)),
+            tos!("#d0e5c5")
+        );
+        assert_eq!(
+            unit!(ColorLighten, tos!("rgb(0, 0, 0)"), Value::scalar(100)),
+            tos!("rgb(255, 255, 255)")
+        );
+        failed!(ColorLighten, tos!("#7ab55c"), Value::scalar(101));
+    }
+
+    #[test]
+    fn unit_color_darken() {
+        assert_eq!(
+            unit!(ColorDarken, tos!("#7ab55c"), Value::scalar(30)),
+            tos!("#355325")
+        );
+        assert_eq!(
+            unit!(ColorDarken, tos!("#fff"), Value::scalar(100)),
+            tos!("#000000")
+        );
+    }
+
+    #[test]
+    fn unit_color_mix() {
+        assert_eq!(
+            unit!(
+                ColorMix,
+                tos!("#7ab55c"),
+                tos!("#ffc0cb"),
+                Value::scalar(50)
+            ),
+            tos!("#bdbb94")
+        );
+        assert_eq!(
+            unit!(
+                ColorMix,
+                tos!("rgba(0, 0, 0, 1)"),
+                tos!("#ffffff"),
+                Value::scalar(100)
+            ),
+            tos!("rgb(0, 0, 0)")
+        );
+        failed!(ColorMix, tos!("#7ab55c"), tos!("pink"), Value::scalar(50));
+    }
+
+    #[test]
+    fn unit_color_contrast() {
+        assert_eq!(
+            unit!(ColorContrast, tos!("#495859"), tos!("#fffffb")),
+            Value::scalar(7.4)
+        );
+        assert_eq!(
+            unit!(ColorContrast, tos!("#000"), tos!("#fff")),
+            Value::scalar(21.0)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use filters::{invalid_argument, invalid_input};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::{Scalar, Value};
+
+// liquid-rust proprietary
+
+fn is_number(scalar: &Scalar) -> bool {
+    matches!(scalar.type_name(), "whole number" | "fractional number")
+}
+
+/// Structural comparison, unlike `==` which follows Ruby's truthiness rules
+/// (e.g. any object is `==` to `true`).
+fn deep_eq(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Scalar(x), Value::Scalar(y)) => {
+            if is_number(x) && is_number(y) {
+                x.to_float() == y.to_float()
+            } else {
+                x.type_name() == y.type_name() && x == y
+            }
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y.iter()).all(|(x, y)| deep_eq(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(key, x)| y.get(key.as_ref()).is_some_and(|y| deep_eq(x, y)))
+        }
+        (Value::Nil, Value::Nil) | (Value::Empty, Value::Empty) | (Value::Blank, Value::Blank) => {
+            true
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct EqualsArgs {
+    #[parameter(description = "The value to compare with.")]
+    other: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "equals",
+    description = "Structurally compares the input with the argument, recursing into arrays and objects.",
+    parameters(EqualsArgs),
+    parsed(EqualsFilter)
+)]
+pub struct Equals;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "equals"]
+struct EqualsFilter {
+    #[parameters]
+    args: EqualsArgs,
+}
+
+impl Filter for EqualsFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        Ok(Value::scalar(deep_eq(input, args.other)))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct DiffArgs {
+    #[parameter(description = "The object to compare with.")]
+    other: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "diff",
+    description = "Returns the sorted keys whose values differ between the input object and the argument, \
+                   including keys only present in one of them.",
+    parameters(DiffArgs),
+    parsed(DiffFilter)
+)]
+pub struct Diff;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "diff"]
+struct DiffFilter {
+    #[parameters]
+    args: DiffArgs,
+}
+
+impl Filter for DiffFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let lhs = input
+            .as_object()
+            .ok_or_else(|| invalid_input("Object expected"))?;
+        let rhs = args
+            .other
+            .as_object()
+            .ok_or_else(|| invalid_argument("other", "Object expected"))?;
+
+        let mut keys: Vec<_> = lhs
+            .iter()
+            .filter(|(key, x)| rhs.get(key.as_ref()).is_none_or(|y| !deep_eq(x, y)))
+            .map(|(key, _)| key)
+            .chain(rhs.keys().filter(|key| !lhs.contains_key(key.as_ref())))
+            .map(|key| key.to_string())
+            .collect();
+        keys.sort();
+
+        Ok(Value::array(keys.into_iter().map(Value::scalar)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use liquid_value::liquid_value;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:ident, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+                .unwrap_err()
+        }};
+    }
+
+    #[test]
+    fn unit_equals_scalars() {
+        assert_eq!(
+            unit!(Equals, liquid_value!(1), liquid_value!(1.0)),
+            liquid_value!(true)
+        );
+        assert_eq!(
+            unit!(Equals, liquid_value!("1"), liquid_value!(1)),
+            liquid_value!(false)
+        );
+        assert_eq!(
+            unit!(Equals, liquid_value!("a"), liquid_value!(true)),
+            liquid_value!(false)
+        );
+        assert_eq!(
+            unit!(Equals, liquid_value!(nil), liquid_value!(false)),
+            liquid_value!(false)
+        );
+    }
+
+    #[test]
+    fn unit_equals_objects() {
+        let input = liquid_value!({"a": 1, "b": [1, {"c": "d"}]});
+        assert_eq!(
+            unit!(Equals, input, liquid_value!({"b": [1, {"c": "d"}], "a": 1})),
+            liquid_value!(true)
+        );
+        assert_eq!(
+            unit!(Equals, input, liquid_value!({"a": 1, "b": [1, {"c": "e"}]})),
+            liquid_value!(false)
+        );
+        assert_eq!(
+            unit!(Equals, input, liquid_value!({"a": 1})),
+            liquid_value!(false)
+        );
+        assert_eq!(
+            unit!(Equals, input, liquid_value!(true)),
+            liquid_value!(false)
+        );
+    }
+
+    #[test]
+    fn unit_equals_no_args() {
+        failed!(Equals, liquid_value!(1));
+    }
+
+    #[test]
+    fn unit_diff() {
+        let input = liquid_value!({"a": 1, "b": [1, 2], "c": "x", "d": nil});
+        let other = liquid_value!({"a": 1.0, "b": [1, 3], "d": nil, "e": true});
+        assert_eq!(unit!(Diff, input, other), liquid_value!(["b", "c", "e"]));
+    }
+
+    #[test]
+    fn unit_diff_same() {
+        let input = liquid_value!({"a": {"b": 1}});
+        assert_eq!(unit!(Diff, input, input.clone()), liquid_value!([]));
+    }
+
+    #[test]
+    fn unit_diff_not_objects() {
+        failed!(Diff, liquid_value!([1]), liquid_value!({}));
+        failed!(Diff, liquid_value!({}), liquid_value!([1]));
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Write;
+
+use hmac::{Hmac, Mac};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+use sha2::Digest;
+
+// Shopify Liquid
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac =
+        Hmac::<::sha2::Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(digest: &[u8]) -> Value {
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        write!(hex, "{:02x}", byte).expect("writing to a String");
+    }
+    Value::scalar(hex)
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "md5",
+    description = "Returns the MD5 digest of a string, in hexadecimal.",
+    parsed(Md5Filter)
+)]
+pub struct Md5;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "md5"]
+struct Md5Filter;
+
+impl Filter for Md5Filter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(to_hex(&::md5::Md5::digest(input.to_str().as_bytes())))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "sha1",
+    description = "Returns the SHA-1 digest of a string, in hexadecimal.",
+    parsed(Sha1Filter)
+)]
+pub struct Sha1;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "sha1"]
+struct Sha1Filter;
+
+impl Filter for Sha1Filter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(to_hex(&::sha1::Sha1::digest(input.to_str().as_bytes())))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "sha256",
+    description = "Returns the SHA-256 digest of a string, in hexadecimal.",
+    parsed(Sha256Filter)
+)]
+pub struct Sha256;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "sha256"]
+struct Sha256Filter;
+
+impl Filter for Sha256Filter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(to_hex(&::sha2::Sha256::digest(input.to_str().as_bytes())))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct HmacSha256Args {
+    #[parameter(description = "The secret key.", arg_type = "str")]
+    key: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "hmac_sha256",
+    description = "Returns the HMAC-SHA256 signature of a string with a key, in hexadecimal.",
+    parameters(HmacSha256Args),
+    parsed(HmacSha256Filter)
+)]
+pub struct HmacSha256;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "hmac_sha256"]
+struct HmacSha256Filter {
+    #[parameters]
+    args: HmacSha256Args,
+}
+
+impl Filter for HmacSha256Filter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let signature = hmac_sha256(args.key.as_bytes(), input.to_str().as_bytes());
+        Ok(to_hex(&signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    const FOX: &str = "The quick brown fox jumps over the lazy dog";
+    const TWO_BLOCKS: &str = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    #[test]
+    fn unit_md5() {
+        assert_eq!(
+            unit!(Md5, tos!("")),
+            tos!("d41d8cd98f00b204e9800998ecf8427e")
+        );
+        assert_eq!(
+            unit!(Md5, tos!(FOX)),
+            tos!("9e107d9d372bb6826bd81d3542a419d6")
+        );
+        assert_eq!(
+            unit!(Md5, tos!(TWO_BLOCKS)),
+            tos!("8215ef0796a20bcaaae116d3876c664a")
+        );
+    }
+
+    #[test]
+    fn unit_sha1() {
+        assert_eq!(
+            unit!(Sha1, tos!("")),
+            tos!("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+        );
+        assert_eq!(
+            unit!(Sha1, tos!(FOX)),
+            tos!("2fd4e1c67a2d28fced849ee1bb76e7391b93eb12")
+        );
+        assert_eq!(
+            unit!(Sha1, tos!(TWO_BLOCKS)),
+            tos!("84983e441c3bd26ebaae4aa1f95129e5e54670f1")
+        );
+    }
+
+    #[test]
+    fn unit_sha256() {
+        assert_eq!(
+            unit!(Sha256, tos!("")),
+            tos!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(
+            unit!(Sha256, tos!("abc")),
+            tos!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            unit!(Sha256, tos!(TWO_BLOCKS)),
+            tos!("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+        );
+        assert_eq!(unit!(Sha256, Value::scalar(42)), unit!(Sha256, tos!("42")));
+    }
+
+    #[test]
+    fn unit_hmac_sha256() {
+        assert_eq!(
+            unit!(HmacSha256, tos!(FOX), tos!("key")),
+            tos!("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8")
+        );
+        assert_eq!(
+            unit!(HmacSha256, tos!(""), tos!("")),
+            tos!("b613679a0814d9ec772f95d778c35fc5ff1697c493715653c6c712144292c5ad")
+        );
+        // Keys longer than a block are hashed first.
+        assert_eq!(
+            unit!(HmacSha256, tos!("long key"), tos!("k".repeat(100))),
+            tos!("5903eedefe0bba3f15b658715e41d71f1848739bddf19e497e968df5723dcd56")
+        );
+    }
+}
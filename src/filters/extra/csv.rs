@@ -0,0 +1,402 @@
+use std::collections::BTreeSet;
+
+use filters::{invalid_argument, invalid_input};
+use itertools;
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::{Object, Value};
+
+// liquid-rust proprietary
+
+fn single_char(argument: &'static str, value: Option<&str>, default: char) -> Result<char> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(default),
+    };
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '"' && c != '\r' && c != '\n' => Ok(c),
+        _ => Err(invalid_argument(
+            argument,
+            "A single character other than a quote or newline expected",
+        )),
+    }
+}
+
+/// Split RFC 4180-style CSV into rows of fields.
+fn parse_rows(input: &str, delimiter: char) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    // Whether anything was read since the last row ended.
+    let mut pending = false;
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        pending = true;
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                c => field.push(c),
+            }
+        } else {
+            match c {
+                '"' if field.is_empty() => in_quotes = true,
+                c if c == delimiter => row.push(std::mem::take(&mut field)),
+                '\r' if chars.peek() == Some(&'\n') => (),
+                '\n' => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                    pending = false;
+                }
+                c => field.push(c),
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(invalid_input("Unterminated quoted field"));
+    }
+    if pending {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[derive(Debug, FilterParameters)]
+struct ParseCsvArgs {
+    #[parameter(
+        description = "The character separating fields. Defaults to `,`.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    delimiter: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "parse_csv",
+    description = "Parses CSV into an array of objects, keyed by the header row.",
+    parameters(ParseCsvArgs),
+    parsed(ParseCsvFilter)
+)]
+pub struct ParseCsv;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "parse_csv"]
+struct ParseCsvFilter {
+    #[parameters]
+    args: ParseCsvArgs,
+}
+
+impl Filter for ParseCsvFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let delimiter = single_char(
+            "delimiter",
+            args.delimiter.as_ref().map(|d| d.as_ref()),
+            ',',
+        )?;
+
+        let input = input
+            .as_scalar()
+            .ok_or_else(|| invalid_input("String expected"))?
+            .to_str();
+
+        let mut rows = parse_rows(&input, delimiter)?.into_iter();
+        let header = match rows.next() {
+            Some(header) => header,
+            None => return Ok(Value::array(vec![])),
+        };
+
+        let objects = rows.map(|row| {
+            let mut fields = row.into_iter();
+            let object: Object = header
+                .iter()
+                .map(|key| {
+                    let value = fields.next().map(Value::scalar).unwrap_or(Value::Nil);
+                    (key.clone().into(), value)
+                })
+                .collect();
+            Value::Object(object)
+        });
+        Ok(Value::array(objects))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct ToCsvArgs {
+    #[parameter(
+        description = "The character separating fields. Defaults to `,`.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    delimiter: Option<Expression>,
+    #[parameter(
+        description = "When to quote fields: `necessary` (the default) or `always`.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    quote: Option<Expression>,
+    #[parameter(
+        description = "The columns to emit, in order. Defaults to all keys, sorted.",
+        arg_type = "any",
+        mode = "keyword"
+    )]
+    headers: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "to_csv",
+    description = "Formats an array of objects, or an array of arrays, as CSV.",
+    parameters(ToCsvArgs),
+    parsed(ToCsvFilter)
+)]
+pub struct ToCsv;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "to_csv"]
+struct ToCsvFilter {
+    #[parameters]
+    args: ToCsvArgs,
+}
+
+fn write_row<I, S>(output: &mut String, row: I, delimiter: char, always_quote: bool)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let fields = row.into_iter().map(|field| {
+        let field = field.as_ref();
+        let needs_quotes = always_quote
+            || field.contains(delimiter)
+            || field.contains('"')
+            || field.contains('\n')
+            || field.contains('\r');
+        if needs_quotes {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    });
+    output.push_str(&itertools::join(fields, &delimiter.to_string()));
+    output.push_str("\r\n");
+}
+
+impl Filter for ToCsvFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let delimiter = single_char(
+            "delimiter",
+            args.delimiter.as_ref().map(|d| d.as_ref()),
+            ',',
+        )?;
+        let always_quote = match args.quote.as_ref().map(|q| q.as_ref()) {
+            None | Some("necessary") => false,
+            Some("always") => true,
+            Some(_) => {
+                return Err(invalid_argument(
+                    "quote",
+                    "`necessary` or `always` expected",
+                ))
+            }
+        };
+
+        let rows = input
+            .as_array()
+            .ok_or_else(|| invalid_input("Array of objects or array of arrays expected"))?;
+
+        let mut output = String::new();
+        if rows.iter().all(Value::is_array) {
+            for row in rows {
+                let row = row.as_array().expect("checked above");
+                write_row(
+                    &mut output,
+                    row.iter().map(|v| v.to_str()),
+                    delimiter,
+                    always_quote,
+                );
+            }
+        } else if rows.iter().all(Value::is_object) {
+            let headers: Vec<String> = match args.headers {
+                Some(headers) => headers
+                    .as_array()
+                    .ok_or_else(|| invalid_argument("headers", "Array expected"))?
+                    .iter()
+                    .map(|h| h.to_str().into_owned())
+                    .collect(),
+                None => {
+                    let headers: BTreeSet<_> = rows
+                        .iter()
+                        .filter_map(Value::as_object)
+                        .flat_map(|o| o.keys())
+                        .map(|k| k.to_string())
+                        .collect();
+                    headers.into_iter().collect()
+                }
+            };
+            write_row(&mut output, &headers, delimiter, always_quote);
+            for row in rows.iter().filter_map(Value::as_object) {
+                let fields = headers
+                    .iter()
+                    .map(|h| row.get(h.as_str()).map(|v| v.to_str()).unwrap_or_default());
+                write_row(&mut output, fields, delimiter, always_quote);
+            }
+        } else {
+            return Err(invalid_input(
+                "Array of objects or array of arrays expected",
+            ));
+        }
+
+        Ok(Value::scalar(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use liquid_value::liquid_value;
+    use parser::ParserBuilder;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments {
+                positional,
+                keyword,
+            };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn render(text: &str, globals: Value) -> String {
+        let template = ParserBuilder::with_liquid()
+            .extra_filters()
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap();
+        template.render(globals.as_object().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn unit_parse_csv() {
+        let input = tos!("name,price\r\nwidget,5\r\n\"big, \"\"blue\"\" gadget\",10\n");
+        let desired_result = liquid_value!([
+            {"name": "widget", "price": "5"},
+            {"name": "big, \"blue\" gadget", "price": "10"},
+        ]);
+        assert_eq!(unit!(ParseCsv, input).unwrap(), desired_result);
+    }
+
+    #[test]
+    fn unit_parse_csv_ragged() {
+        let input = tos!("a,b\n1\n2,3,4");
+        let desired_result = liquid_value!([{"a": "1", "b": nil}, {"a": "2", "b": "3"}]);
+        assert_eq!(unit!(ParseCsv, input).unwrap(), desired_result);
+    }
+
+    #[test]
+    fn unit_parse_csv_multiline_field() {
+        let input = tos!("a\n\"line 1\nline 2\"");
+        let desired_result = liquid_value!([{"a": "line 1\nline 2"}]);
+        assert_eq!(unit!(ParseCsv, input).unwrap(), desired_result);
+    }
+
+    #[test]
+    fn unit_parse_csv_empty() {
+        assert_eq!(unit!(ParseCsv, tos!("")).unwrap(), liquid_value!([]));
+    }
+
+    #[test]
+    fn unit_parse_csv_unterminated() {
+        assert!(unit!(ParseCsv, tos!("a\n\"b")).is_err());
+    }
+
+    #[test]
+    fn unit_to_csv_objects() {
+        let input = liquid_value!([
+            {"name": "widget", "price": 5},
+            {"name": "big, \"blue\" gadget"},
+        ]);
+        assert_eq!(
+            unit!(ToCsv, input).unwrap(),
+            tos!("name,price\r\nwidget,5\r\n\"big, \"\"blue\"\" gadget\",\r\n")
+        );
+    }
+
+    #[test]
+    fn unit_to_csv_arrays() {
+        let input = liquid_value!([["a", 1], ["b", 2]]);
+        assert_eq!(unit!(ToCsv, input).unwrap(), tos!("a,1\r\nb,2\r\n"));
+    }
+
+    #[test]
+    fn unit_to_csv_mixed() {
+        assert!(unit!(ToCsv, liquid_value!([["a"], {"b": 1}])).is_err());
+        assert!(unit!(ToCsv, tos!("a,b")).is_err());
+    }
+
+    #[test]
+    fn to_csv_options() {
+        let globals = liquid_value!({"rows": [{"a": 1, "b": 2, "c": 3}], "cols": ["c", "a"]});
+        assert_eq!(
+            render(
+                r#"{{ rows | to_csv: delimiter: ";", quote: "always", headers: cols }}"#,
+                globals
+            ),
+            "\"c\";\"a\"\r\n\"3\";\"1\"\r\n"
+        );
+    }
+
+    #[test]
+    fn parse_csv_delimiter_roundtrip() {
+        let globals = liquid_value!({"data": "a;b\n1;2\n"});
+        assert_eq!(
+            render(
+                r#"{{ data | parse_csv: delimiter: ";" | to_csv: delimiter: "|" }}"#,
+                globals
+            ),
+            "a|b\r\n1|2\r\n"
+        );
+    }
+
+    #[test]
+    fn to_csv_invalid_options() {
+        let globals = liquid_value!({"rows": [["a"]]});
+        let parser = ParserBuilder::with_liquid()
+            .extra_filters()
+            .build()
+            .unwrap();
+        let template = parser
+            .parse(r#"{{ rows | to_csv: quote: "sometimes" }}"#)
+            .unwrap();
+        assert!(template.render(globals.as_object().unwrap()).is_err());
+        let template = parser
+            .parse(r#"{{ rows | to_csv: delimiter: ",," }}"#)
+            .unwrap();
+        assert!(template.render(globals.as_object().unwrap()).is_err());
+    }
+}
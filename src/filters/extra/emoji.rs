@@ -0,0 +1,105 @@
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::Value;
+
+// liquid-rust proprietary
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+'
+}
+
+fn emojify(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find(':') {
+        result.push_str(&rest[..start]);
+        let candidate = &rest[start + 1..];
+        let end = candidate
+            .find(|c| !is_shortcode_char(c))
+            .filter(|&end| end > 0 && candidate[end..].starts_with(':'));
+        let emoji = end
+            .and_then(|end| emojis::get_by_shortcode(&candidate[..end]).map(|emoji| (end, emoji)));
+        match emoji {
+            Some((end, emoji)) => {
+                result.push_str(emoji.as_str());
+                rest = &candidate[end + 1..];
+            }
+            None => {
+                // Leave the colon in place; it may start the next shortcode.
+                result.push(':');
+                rest = candidate;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "emojify",
+    description = "Replaces `:shortcode:` emoji names with the emoji, leaving unknown names as-is.",
+    parsed(EmojifyFilter)
+)]
+pub struct Emojify;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "emojify"]
+struct EmojifyFilter;
+
+impl Filter for EmojifyFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(emojify(&input.to_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments {
+                positional,
+                keyword,
+            };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_emojify() {
+        assert_eq!(
+            unit!(Emojify, tos!("Shipped :tada: :+1:")),
+            tos!("Shipped \u{1f389} \u{1f44d}")
+        );
+        assert_eq!(
+            unit!(Emojify, tos!(":tada::tada:")),
+            tos!("\u{1f389}\u{1f389}")
+        );
+    }
+
+    #[test]
+    fn unit_emojify_unknown() {
+        assert_eq!(
+            unit!(Emojify, tos!("at 10:30 :not_an_emoji: ::")),
+            tos!("at 10:30 :not_an_emoji: ::")
+        );
+        assert_eq!(unit!(Emojify, tos!("a:zz:tada:")), tos!("a:zz\u{1f389}"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::env;
+
+use filters::invalid_input;
+use liquid_compiler::{Filter, FilterArguments, FilterReflection, ParseFilter};
+use liquid_derive::*;
+use liquid_error::{Error, Result};
+use liquid_interpreter::Context;
+use liquid_value::Value;
+
+// liquid-rust proprietary
+
+/// Reads environment variables, e.g. `{{ "API_BASE" | env }}`.
+///
+/// This is not registered by `extra_filters` since it exposes the host's environment.  Only
+/// variables matching the allowlist can be read; an entry ending in `*` allows any variable with
+/// that prefix.  Unset variables evaluate to `nil`.
+///
+/// ```rust
+/// let parser = liquid::ParserBuilder::with_liquid()
+///     .filter(liquid::filters::extra::Env::new().allow("API_BASE").allow("CI_*"))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, FilterReflection)]
+#[filter(
+    name = "env",
+    description = "Returns the value of an allowlisted environment variable, or nil if it is unset."
+)]
+pub struct Env {
+    allowlist: Vec<String>,
+}
+
+impl Env {
+    /// Create an `env` filter that denies all variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow reading `name`, or all variables starting with a prefix when `name` ends with `*`.
+    pub fn allow<S: Into<String>>(mut self, name: S) -> Self {
+        self.allowlist.push(name.into());
+        self
+    }
+}
+
+impl ParseFilter for Env {
+    fn parse(&self, mut arguments: FilterArguments) -> Result<Box<dyn Filter>> {
+        if arguments.positional.next().is_some() {
+            return Error::with_msg("Invalid number of positional arguments")
+                .context("cause", "expected at most 0 positional arguments")
+                .into_err();
+        }
+        if let Some((name, _)) = arguments.keyword.next() {
+            return Error::with_msg(format!("Unexpected named argument `{}`", name)).into_err();
+        }
+
+        Ok(Box::new(EnvFilter {
+            allowlist: self.allowlist.clone(),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+#[derive(Debug, Display_filter)]
+#[name = "env"]
+struct EnvFilter {
+    allowlist: Vec<String>,
+}
+
+impl EnvFilter {
+    fn is_allowed(&self, name: &str) -> bool {
+        self.allowlist.iter().any(|allowed| {
+            if allowed.ends_with('*') {
+                name.starts_with(&allowed[..allowed.len() - 1])
+            } else {
+                name == allowed
+            }
+        })
+    }
+}
+
+impl Filter for EnvFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let name = input
+            .as_scalar()
+            .ok_or_else(|| invalid_input("String expected"))?
+            .to_str();
+
+        if !self.is_allowed(&name) {
+            return Error::with_msg("Environment variable is not allowed")
+                .context("variable", name.into_owned())
+                .into_err();
+        }
+
+        let value = env::var(name.as_ref())
+            .map(Value::scalar)
+            .unwrap_or(Value::Nil);
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:expr, $b:expr) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments {
+                positional,
+                keyword,
+            };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_env() {
+        env::set_var("LIQUID_TEST_ENV_SET", "value");
+        let filter = Env::new().allow("LIQUID_TEST_ENV_SET");
+        assert_eq!(
+            unit!(filter, tos!("LIQUID_TEST_ENV_SET")).unwrap(),
+            tos!("value")
+        );
+    }
+
+    #[test]
+    fn unit_env_unset() {
+        let filter = Env::new().allow("LIQUID_TEST_ENV_UNSET");
+        assert_eq!(
+            unit!(filter, tos!("LIQUID_TEST_ENV_UNSET")).unwrap(),
+            Value::Nil
+        );
+    }
+
+    #[test]
+    fn unit_env_prefix() {
+        env::set_var("LIQUID_TEST_ENV_PREFIX", "value");
+        let filter = Env::new().allow("LIQUID_TEST_ENV_P*");
+        assert_eq!(
+            unit!(filter, tos!("LIQUID_TEST_ENV_PREFIX")).unwrap(),
+            tos!("value")
+        );
+    }
+
+    #[test]
+    fn unit_env_not_allowed() {
+        env::set_var("LIQUID_TEST_ENV_DENIED", "value");
+        let filter = Env::new().allow("LIQUID_TEST_ENV_SET");
+        assert!(unit!(filter, tos!("LIQUID_TEST_ENV_DENIED")).is_err());
+        assert!(unit!(Env::new(), tos!("LIQUID_TEST_ENV_DENIED")).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use std::borrow::Cow;
+
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+use regex::{Captures, Regex};
+
+use filters::invalid_argument;
+
+// liquid-rust proprietary
+
+lazy_static! {
+    static ref HTML: [Regex; 4] = [
+        Regex::new(r"(?is)<script.*?</script>").unwrap(),
+        Regex::new(r"(?is)<style.*?</style>").unwrap(),
+        Regex::new(r"(?is)<!--.*?-->").unwrap(),
+        Regex::new(r"(?is)<.*?>").unwrap(),
+    ];
+    static ref FENCE: Regex = Regex::new(r"^(```|~~~)").unwrap();
+    static ref RULE: Regex = Regex::new(r"^([-*_=]\s*){3,}$").unwrap();
+    static ref LINK_DEFINITION: Regex = Regex::new(r"^\[[^\]]+\]:\s").unwrap();
+    static ref BLOCK_MARKER: Regex =
+        Regex::new(r"^(#{1,6}\s+|>\s?|[-*+]\s+|\d{1,9}[.)]\s+)").unwrap();
+    static ref CLOSING_HASHES: Regex = Regex::new(r"\s+#+\s*$").unwrap();
+    static ref IMAGE_OR_LINK: Regex = Regex::new(r"!?\[([^\]]*)\](\([^)]*\)|\[[^\]]*\])").unwrap();
+    static ref CODE: Regex = Regex::new(r"`+([^`]+)`+").unwrap();
+    static ref STRONG_OR_EMPHASIS: Regex =
+        Regex::new(r"(\*\*|~~|\*)(\S(?:.*?\S)?)(\*\*|~~|\*)").unwrap();
+    static ref UNDERSCORES: Regex =
+        Regex::new(r"(^|[^\w])(__?)(\S(?:.*?\S)?)__?([^\w]|$)").unwrap();
+    static ref ENTITY: Regex = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+}
+
+fn decode_entity(entity: &str) -> Option<Cow<'static, str>> {
+    let decoded = match entity {
+        "amp" => "&",
+        "lt" => "<",
+        "gt" => ">",
+        "quot" => "\"",
+        "apos" => "'",
+        "nbsp" => " ",
+        "copy" => "©",
+        "reg" => "®",
+        "trade" => "™",
+        "hellip" => "…",
+        "mdash" => "—",
+        "ndash" => "–",
+        "lsquo" => "‘",
+        "rsquo" => "’",
+        "ldquo" => "“",
+        "rdquo" => "”",
+        _ => {
+            let code = if entity.starts_with("#x") || entity.starts_with("#X") {
+                u32::from_str_radix(&entity[2..], 16).ok()?
+            } else if let Some(decimal) = entity.strip_prefix('#') {
+                decimal.parse().ok()?
+            } else {
+                return None;
+            };
+            return ::std::char::from_u32(code).map(|c| Cow::Owned(c.to_string()));
+        }
+    };
+    Some(Cow::Borrowed(decoded))
+}
+
+/// Replace the HTML entities in `text` with the characters they stand for.
+pub(super) fn decode_entities(text: &str) -> Cow<'_, str> {
+    ENTITY.replace_all(text, |captures: &Captures| {
+        decode_entity(&captures[1])
+            .map(Cow::into_owned)
+            .unwrap_or_else(|| captures[0].to_owned())
+    })
+}
+
+/// The text of Markdown or HTML, without markup, with whitespace collapsed to single spaces.
+fn strip_markdown(input: &str, context: &Context) -> Result<String> {
+    let mut text = input.to_owned();
+    for matcher in HTML.iter() {
+        context.spend_filter_budget(text.len() as u64)?;
+        text = matcher.replace_all(&text, "").into_owned();
+    }
+
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if FENCE.is_match(line) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(Cow::Borrowed(line));
+            continue;
+        }
+        if RULE.is_match(line) || LINK_DEFINITION.is_match(line) {
+            continue;
+        }
+        let mut line = Cow::Borrowed(line);
+        // Markers nest, e.g. a list in a block quote.
+        while let Some(marker) = BLOCK_MARKER.find(&line) {
+            line = Cow::Owned(line[marker.end()..].to_owned());
+        }
+        let line = CLOSING_HASHES.replace(&line, "").into_owned();
+        let line = IMAGE_OR_LINK.replace_all(&line, "$1").into_owned();
+        let line = CODE.replace_all(&line, "$1").into_owned();
+        let line = STRONG_OR_EMPHASIS.replace_all(&line, "$2").into_owned();
+        let line = UNDERSCORES.replace_all(&line, "$1$3$4").into_owned();
+        lines.push(Cow::Owned(line));
+    }
+    context.spend_filter_budget(text.len() as u64)?;
+
+    let text = itertools::join(lines.iter().flat_map(|line| line.split_whitespace()), " ");
+    Ok(decode_entities(&text).into_owned())
+}
+
+/// `text` cut to at most `length` characters, including the `ellipsis`, at a word boundary when
+/// there is one.
+fn truncate_words(text: &str, length: usize, ellipsis: &str) -> String {
+    if text.chars().count() <= length {
+        return text.to_owned();
+    }
+    let room = length.saturating_sub(ellipsis.chars().count());
+    let end = text
+        .char_indices()
+        .nth(room)
+        .map(|(i, _)| i)
+        .unwrap_or_else(|| text.len());
+    let cut = &text[..end];
+    // A word that fits exactly is followed by a space, and is kept.
+    let cut = if text[end..].starts_with(' ') {
+        cut
+    } else {
+        cut.rfind(' ').map(|space| &cut[..space]).unwrap_or(cut)
+    };
+    format!("{}{}", cut.trim_end(), ellipsis)
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "strip_markdown",
+    description = "Turns Markdown or HTML into plain text, decoding entities like `&amp;` and collapsing whitespace.",
+    parsed(StripMarkdownFilter)
+)]
+pub struct StripMarkdown;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "strip_markdown"]
+struct StripMarkdownFilter;
+
+impl Filter for StripMarkdownFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let text = strip_markdown(&input.to_str(), context)?;
+        Ok(Value::scalar(text))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct ExcerptArgs {
+    #[parameter(
+        description = "The most characters of the excerpt, including the ellipsis. Defaults to 200.",
+        arg_type = "integer"
+    )]
+    length: Option<Expression>,
+    #[parameter(
+        description = "What ends an excerpt that was cut short. Defaults to \"...\".",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    ellipsis: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "excerpt",
+    description = "A plain text summary of Markdown or HTML, cut at a word boundary, like `strip_markdown` then `truncate`.",
+    parameters(ExcerptArgs),
+    parsed(ExcerptFilter)
+)]
+pub struct Excerpt;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "excerpt"]
+struct ExcerptFilter {
+    #[parameters]
+    args: ExcerptArgs,
+}
+
+impl Filter for ExcerptFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let length = args.length.unwrap_or(200);
+        if length < 0 {
+            return Err(invalid_argument("length", "Positive number expected"));
+        }
+        let ellipsis = args.ellipsis.unwrap_or_else(|| "...".into());
+
+        let text = strip_markdown(&input.to_str(), context)?;
+        Ok(Value::scalar(truncate_words(
+            &text,
+            length as usize,
+            &ellipsis,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    const ARTICLE: &str = concat!(
+        "# Hello, *World* #\n",
+        "\n",
+        "Fish &amp; chips are **great**, see [the menu](/menu) and ![a photo](fish.png).\n",
+        "\n",
+        "> - quoted `code` in a list\n",
+        "\n",
+        "---\n",
+        "```rust\n",
+        "let snake_case = 1;\n",
+        "```\n",
+        "[menu]: /menu\n",
+        "<p>Caf&eacute; &#8220;open&#x201D;<br>late_night __hours__</p>\n"
+    );
+
+    #[test]
+    fn unit_strip_markdown() {
+        assert_eq!(
+            unit!(StripMarkdown, tos!(ARTICLE)),
+            tos!(concat!(
+                "Hello, World ",
+                "Fish & chips are great, see the menu and a photo. ",
+                "quoted code in a list ",
+                "let snake_case = 1; ",
+                "Caf&eacute; “open”late_night hours"
+            ))
+        );
+        assert_eq!(unit!(StripMarkdown, tos!("")), tos!(""));
+    }
+
+    #[test]
+    fn unit_excerpt() {
+        assert_eq!(
+            unit!(Excerpt, tos!(ARTICLE), Value::scalar(30)),
+            tos!("Hello, World Fish & chips...")
+        );
+        assert_eq!(
+            unit!(Excerpt, tos!("**Short** text"), Value::scalar(30)),
+            tos!("Short text")
+        );
+        assert_eq!(
+            unit!(Excerpt, tos!("Exactly fits here"), Value::scalar(10)),
+            tos!("Exactly...")
+        );
+        assert_eq!(
+            unit!(Excerpt, tos!("Unbreakable"), Value::scalar(8)),
+            tos!("Unbre...")
+        );
+    }
+
+    #[test]
+    fn unit_excerpt_ellipsis() {
+        let template = ::liquid::ParserBuilder::with_liquid()
+            .extra_filters()
+            .build()
+            .unwrap()
+            .parse("{{ '*one* two three' | excerpt: 10, ellipsis: '…' }}")
+            .unwrap();
+        let output = template.render(&::liquid::value::Object::new()).unwrap();
+        assert_eq!(output, "one two…");
+    }
+}
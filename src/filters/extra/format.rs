@@ -0,0 +1,373 @@
+use filters::helpers::{input_float, input_number, Number};
+use filters::invalid_argument;
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_interpreter::NumberFormat;
+use liquid_value::Value;
+
+// Shopify Liquid, with separators and the default currency configured on the filters
+
+/// The symbol of a currency and how many decimals its amounts have.
+fn currency_format(code: &str) -> Option<(&'static str, usize)> {
+    let format = match code {
+        "USD" | "CAD" | "AUD" | "NZD" | "MXN" | "SGD" | "HKD" => ("$", 2),
+        "EUR" => ("€", 2),
+        "GBP" => ("£", 2),
+        "JPY" => ("¥", 0),
+        "CNY" => ("¥", 2),
+        "KRW" => ("₩", 0),
+        "INR" => ("₹", 2),
+        "BRL" => ("R$", 2),
+        "CHF" => ("CHF ", 2),
+        "SEK" | "NOK" | "DKK" => ("kr ", 2),
+        "PLN" => ("zł ", 2),
+        "RUB" => ("₽", 2),
+        "TRY" => ("₺", 2),
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Format `input`, in cents, as an amount of `currency`.
+fn format_money(
+    input: &Value,
+    currency: Option<&str>,
+    format: &NumberFormat,
+    context: &Context,
+) -> Result<String> {
+    let currency = currency.unwrap_or_else(|| format.currency());
+    let (symbol, decimals) = match currency_format(currency) {
+        Some(known) => known,
+        None if currency.len() == 3 && currency.chars().all(|c| c.is_ascii_uppercase()) => ("", 2),
+        None => {
+            return Err(invalid_argument(
+                "currency",
+                "ISO 4217 currency code, like `USD`, expected",
+            ))
+        }
+    };
+
+    let amount = input_float(input, context)? / 100.0;
+    let formatted = format.format(amount.abs(), Some(decimals));
+    let sign = if amount < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        "-"
+    } else {
+        ""
+    };
+    if symbol.is_empty() {
+        Ok(format!("{}{} {}", sign, formatted, currency))
+    } else {
+        Ok(format!("{}{}{}", sign, symbol, formatted))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct NumberWithDelimiterArgs {
+    #[parameter(
+        description = "What separates groups of thousands. Defaults to the filter's.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    delimiter: Option<Expression>,
+    #[parameter(
+        description = "The decimal separator. Defaults to the filter's.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    separator: Option<Expression>,
+}
+
+#[derive(Clone, Debug, Default, ParseFilter, FilterReflection)]
+#[filter(
+    name = "number_with_delimiter",
+    description = "Groups the thousands of a number, e.g. `1,234,567.5`.",
+    parameters(NumberWithDelimiterArgs),
+    parsed(NumberWithDelimiterFilter),
+    stateful
+)]
+pub struct NumberWithDelimiter {
+    format: NumberFormat,
+}
+
+impl NumberWithDelimiter {
+    /// Create a `number_with_delimiter` filter with the `en-US` separators.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the separators numbers are formatted with, e.g. `NumberFormat::for_locale("de-DE")`.
+    pub fn with_format(mut self, format: NumberFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "number_with_delimiter"]
+struct NumberWithDelimiterFilter {
+    #[parameters]
+    args: NumberWithDelimiterArgs,
+    #[state]
+    config: NumberWithDelimiter,
+}
+
+impl Filter for NumberWithDelimiterFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let mut format = self.config.format.clone();
+        if let Some(delimiter) = args.delimiter {
+            format = format.with_thousands_separator(delimiter.into_owned());
+        }
+        if let Some(separator) = args.separator {
+            format = format.with_decimal_separator(separator.into_owned());
+        }
+
+        let number = match input_number(input, context)? {
+            Number::Integer(whole) => whole.to_string(),
+            Number::Float(fractional) => fractional.to_string(),
+        };
+        Ok(Value::scalar(format.delimit(&number)))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct MoneyArgs {
+    #[parameter(
+        description = "The ISO 4217 code of the currency. Defaults to the filter's.",
+        arg_type = "str"
+    )]
+    currency: Option<Expression>,
+}
+
+#[derive(Clone, Debug, Default, ParseFilter, FilterReflection)]
+#[filter(
+    name = "money",
+    description = "Formats an amount in cents with its currency symbol, e.g. `$1,234.50`.",
+    parameters(MoneyArgs),
+    parsed(MoneyFilter),
+    stateful
+)]
+pub struct Money {
+    format: NumberFormat,
+}
+
+impl Money {
+    /// Create a `money` filter with the `en-US` separators, in `USD` by default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the separators and default currency amounts are formatted with, e.g.
+    /// `NumberFormat::for_locale("de-DE").with_currency("EUR")`.
+    pub fn with_format(mut self, format: NumberFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "money"]
+struct MoneyFilter {
+    #[parameters]
+    args: MoneyArgs,
+    #[state]
+    config: Money,
+}
+
+impl Filter for MoneyFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let currency = args.currency.as_ref().map(|c| c.as_ref());
+        let money = format_money(input, currency, &self.config.format, context)?;
+        Ok(Value::scalar(money))
+    }
+}
+
+#[derive(Clone, Debug, Default, ParseFilter, FilterReflection)]
+#[filter(
+    name = "money_with_currency",
+    description = "Formats an amount in cents with its currency symbol and code, e.g. `$1,234.50 USD`.",
+    parameters(MoneyArgs),
+    parsed(MoneyWithCurrencyFilter),
+    stateful
+)]
+pub struct MoneyWithCurrency {
+    format: NumberFormat,
+}
+
+impl MoneyWithCurrency {
+    /// Create a `money_with_currency` filter with the `en-US` separators, in `USD` by default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the separators and default currency amounts are formatted with.
+    pub fn with_format(mut self, format: NumberFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "money_with_currency"]
+struct MoneyWithCurrencyFilter {
+    #[parameters]
+    args: MoneyArgs,
+    #[state]
+    config: MoneyWithCurrency,
+}
+
+impl Filter for MoneyWithCurrencyFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let currency = args
+            .currency
+            .as_ref()
+            .map(|c| c.as_ref())
+            .unwrap_or_else(|| self.config.format.currency());
+        let money = format_money(input, Some(currency), &self.config.format, context)?;
+        if currency_format(currency).is_some() {
+            Ok(Value::scalar(format!("{} {}", money, currency)))
+        } else {
+            Ok(Value::scalar(money))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:expr, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:expr, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:expr, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:expr, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap_err()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_number_with_delimiter() {
+        assert_eq!(
+            unit!(NumberWithDelimiter::new(), Value::scalar(1234567)),
+            tos!("1,234,567")
+        );
+        assert_eq!(
+            unit!(NumberWithDelimiter::new(), Value::scalar(-1234.5)),
+            tos!("-1,234.5")
+        );
+        assert_eq!(unit!(NumberWithDelimiter::new(), tos!("999")), tos!("999"));
+        failed!(NumberWithDelimiter::new(), tos!("many"));
+    }
+
+    #[test]
+    fn unit_number_with_delimiter_keywords() {
+        let template = ::liquid::ParserBuilder::with_liquid()
+            .extra_filters()
+            .build()
+            .unwrap()
+            .parse(r#"{{ 1234567.5 | number_with_delimiter: delimiter: ".", separator: "," }}"#)
+            .unwrap();
+        let output = template.render(&::liquid::value::Object::new()).unwrap();
+        assert_eq!(output, "1.234.567,5");
+    }
+
+    #[test]
+    fn unit_money() {
+        assert_eq!(
+            unit!(Money::new(), Value::scalar(123450)),
+            tos!("$1,234.50")
+        );
+        assert_eq!(
+            unit!(Money::new(), Value::scalar(5), tos!("EUR")),
+            tos!("€0.05")
+        );
+        assert_eq!(
+            unit!(Money::new(), Value::scalar(-2599), tos!("GBP")),
+            tos!("-£25.99")
+        );
+        assert_eq!(
+            unit!(Money::new(), Value::scalar(150000), tos!("JPY")),
+            tos!("¥1,500")
+        );
+        assert_eq!(
+            unit!(Money::new(), Value::scalar(1000), tos!("XTS")),
+            tos!("10.00 XTS")
+        );
+        failed!(Money::new(), Value::scalar(1000), tos!("euro"));
+        failed!(Money::new(), tos!("free"));
+    }
+
+    #[test]
+    fn unit_money_with_currency() {
+        assert_eq!(
+            unit!(MoneyWithCurrency::new(), Value::scalar(123450)),
+            tos!("$1,234.50 USD")
+        );
+        assert_eq!(
+            unit!(MoneyWithCurrency::new(), Value::scalar(1000), tos!("XTS")),
+            tos!("10.00 XTS")
+        );
+    }
+
+    #[test]
+    fn unit_money_configured() {
+        let euros = NumberFormat::for_locale("de-DE").with_currency("EUR");
+        assert_eq!(
+            unit!(
+                Money::new().with_format(euros.clone()),
+                Value::scalar(123450)
+            ),
+            tos!("€1.234,50")
+        );
+        assert_eq!(
+            unit!(
+                MoneyWithCurrency::new().with_format(euros.clone()),
+                Value::scalar(123450)
+            ),
+            tos!("€1.234,50 EUR")
+        );
+        assert_eq!(
+            unit!(
+                NumberWithDelimiter::new().with_format(euros),
+                Value::scalar(1234.5)
+            ),
+            tos!("1.234,5")
+        );
+    }
+}
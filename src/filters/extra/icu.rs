@@ -0,0 +1,546 @@
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::{Object, Value};
+
+use filters::{invalid_argument, invalid_input};
+use i18n::{language, plural_category};
+
+// liquid-rust proprietary
+
+/// A piece of an ICU message.
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    /// `{name}`
+    Argument(String),
+    /// `{name, number}`, `{name, number, integer}` or `{name, number, percent}`
+    Number(String, NumberStyle),
+    /// `{name, plural, ...}` or `{name, selectordinal, ...}`
+    Plural(Plural),
+    /// `{name, select, ...}`
+    Select(String, Vec<(String, Vec<Part>)>),
+    /// `#` in a plural case, the number less the offset.
+    Hash,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum NumberStyle {
+    Plain,
+    Integer,
+    Percent,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Plural {
+    name: String,
+    ordinal: bool,
+    offset: f64,
+    cases: Vec<(String, Vec<Part>)>,
+}
+
+/// Parses the syntax of ICU messages, e.g. `{count, plural, one {# item} other {# items}}`.
+struct MessageParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl MessageParser {
+    fn new(message: &str) -> Self {
+        Self {
+            chars: message.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn error(&self, cause: &str) -> liquid_error::Error {
+        invalid_input(format!("{} at character {}", cause, self.position + 1))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("`{}` expected", expected)))
+        }
+    }
+
+    /// A name, keyword or plural selector like `=0`.
+    fn word(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !"{},#'".contains(c))
+        {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.error("Name expected"));
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    /// The parts up to the `}` closing a case, or the end of the message at the top level.
+    fn message(&mut self, nested: bool, in_plural: bool) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        loop {
+            let c = match self.peek() {
+                Some(c) => c,
+                None if nested => return Err(self.error("Unclosed `{`")),
+                None => break,
+            };
+            match c {
+                '}' if nested => break,
+                '}' => return Err(self.error("Unexpected `}`")),
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    self.position += 1;
+                    parts.push(self.argument()?);
+                }
+                '#' if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    self.position += 1;
+                    parts.push(Part::Hash);
+                }
+                '\'' => {
+                    self.position += 1;
+                    match self.peek() {
+                        // `''` is an apostrophe, in quoted text too.
+                        Some('\'') => {
+                            text.push('\'');
+                            self.position += 1;
+                        }
+                        Some(c) if c == '{' || c == '}' || (in_plural && c == '#') => {
+                            self.quoted(&mut text)
+                        }
+                        _ => text.push('\''),
+                    }
+                }
+                c => {
+                    text.push(c);
+                    self.position += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// Text between apostrophes, taken literally.
+    fn quoted(&mut self, text: &mut String) {
+        while let Some(c) = self.peek() {
+            self.position += 1;
+            if c == '\'' {
+                if self.peek() == Some('\'') {
+                    text.push('\'');
+                    self.position += 1;
+                } else {
+                    return;
+                }
+            } else {
+                text.push(c);
+            }
+        }
+    }
+
+    /// The part after an opening `{`, up to and including its closing `}`.
+    fn argument(&mut self) -> Result<Part> {
+        let name = self.word()?;
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Part::Argument(name));
+        }
+        self.expect(',')?;
+        let kind = self.word()?;
+        let part = match kind.as_str() {
+            "number" => {
+                self.skip_whitespace();
+                let style = if self.peek() == Some(',') {
+                    self.position += 1;
+                    match self.word()?.as_str() {
+                        "integer" => NumberStyle::Integer,
+                        "percent" => NumberStyle::Percent,
+                        _ => return Err(self.error("`integer` or `percent` expected")),
+                    }
+                } else {
+                    NumberStyle::Plain
+                };
+                Part::Number(name, style)
+            }
+            "plural" | "selectordinal" => {
+                self.expect(',')?;
+                self.skip_whitespace();
+                let mut offset = 0.0;
+                if self.chars[self.position..].starts_with(&['o', 'f', 'f', 's', 'e', 't', ':']) {
+                    self.position += "offset:".len();
+                    offset = self
+                        .word()?
+                        .parse()
+                        .map_err(|_| self.error("Number expected"))?;
+                }
+                Part::Plural(Plural {
+                    name,
+                    ordinal: kind == "selectordinal",
+                    offset,
+                    cases: self.cases(true)?,
+                })
+            }
+            "select" => {
+                self.expect(',')?;
+                Part::Select(name, self.cases(false)?)
+            }
+            _ => {
+                return Err(self.error("`number`, `plural`, `selectordinal` or `select` expected"));
+            }
+        };
+        self.expect('}')?;
+        Ok(part)
+    }
+
+    /// The `selector {message}` cases of a plural or select, which must include `other`.
+    fn cases(&mut self, in_plural: bool) -> Result<Vec<(String, Vec<Part>)>> {
+        let mut cases = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') || self.peek().is_none() {
+                break;
+            }
+            let selector = self.word()?;
+            self.expect('{')?;
+            let message = self.message(true, in_plural)?;
+            self.expect('}')?;
+            cases.push((selector, message));
+        }
+        if !cases.iter().any(|(selector, _)| selector == "other") {
+            return Err(self.error("`other` case expected"));
+        }
+        Ok(cases)
+    }
+}
+
+fn parse_message(message: &str) -> Result<Vec<Part>> {
+    MessageParser::new(message).message(false, false)
+}
+
+fn format_number(n: f64, style: NumberStyle) -> String {
+    match style {
+        NumberStyle::Plain if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", n as i64),
+        NumberStyle::Plain => format!("{}", n),
+        NumberStyle::Integer => format!("{}", n.round() as i64),
+        NumberStyle::Percent => format!("{}%", (n * 100.0).round() as i64),
+    }
+}
+
+struct Formatter<'a> {
+    values: &'a Object,
+    language: String,
+}
+
+impl<'a> Formatter<'a> {
+    fn value(&self, name: &str) -> Result<&'a Value> {
+        self.values
+            .get(name)
+            .ok_or_else(|| invalid_argument("values".into(), format!("No value for `{}`", name)))
+    }
+
+    fn number(&self, name: &str) -> Result<f64> {
+        self.value(name)?
+            .as_scalar()
+            .and_then(|scalar| scalar.to_float())
+            .ok_or_else(|| {
+                invalid_argument("values".into(), format!("Number expected for `{}`", name))
+            })
+    }
+
+    fn format(&self, parts: &[Part], hash: Option<f64>, output: &mut String) -> Result<()> {
+        for part in parts {
+            match *part {
+                Part::Text(ref text) => output.push_str(text),
+                Part::Argument(ref name) => output.push_str(&self.value(name)?.to_str()),
+                Part::Number(ref name, style) => {
+                    output.push_str(&format_number(self.number(name)?, style))
+                }
+                Part::Hash => {
+                    let n = hash.expect("`#` is only parsed in plural cases");
+                    output.push_str(&format_number(n, NumberStyle::Plain));
+                }
+                Part::Plural(ref plural) => {
+                    let n = self.number(&plural.name)?;
+                    let category =
+                        plural_category(&self.language, n - plural.offset, plural.ordinal);
+                    let exact = format!("={}", format_number(n, NumberStyle::Plain));
+                    let message = find_case(&plural.cases, &exact)
+                        .or_else(|| find_case(&plural.cases, category))
+                        .or_else(|| find_case(&plural.cases, "other"))
+                        .expect("`other` is checked when parsing");
+                    self.format(message, Some(n - plural.offset), output)?;
+                }
+                Part::Select(ref name, ref cases) => {
+                    let value = self.value(name)?.to_str();
+                    let message = find_case(cases, &value)
+                        .or_else(|| find_case(cases, "other"))
+                        .expect("`other` is checked when parsing");
+                    self.format(message, hash, output)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find_case<'p>(cases: &'p [(String, Vec<Part>)], selector: &str) -> Option<&'p [Part]> {
+    cases
+        .iter()
+        .find(|(case, _)| case == selector)
+        .map(|(_, message)| message.as_slice())
+}
+
+#[derive(Debug, FilterParameters)]
+struct IcuFormatArgs {
+    #[parameter(
+        description = "The values of the arguments in the message, as an object.",
+        arg_type = "any"
+    )]
+    values: Option<Expression>,
+    #[parameter(
+        description = "The locale whose plural rules are used, like `en` or `pt-BR`. Defaults to `en`.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    locale: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "icu_format",
+    description = "Formats an ICU MessageFormat message, e.g. `{count, plural, one {# item} other {# items}}`, \
+                   with `plural`, `selectordinal`, `select` and `number` arguments.",
+    parameters(IcuFormatArgs),
+    parsed(IcuFormatFilter)
+)]
+pub struct IcuFormat;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "icu_format"]
+struct IcuFormatFilter {
+    #[parameters]
+    args: IcuFormatArgs,
+}
+
+impl Filter for IcuFormatFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let empty = Object::new();
+        let values = match args.values {
+            None | Some(Value::Nil) => &empty,
+            Some(Value::Object(values)) => values,
+            Some(_) => return Err(invalid_argument("values", "Object expected")),
+        };
+        let language = language(args.locale.as_ref().map_or("en", |locale| locale.as_ref()));
+
+        let message = input.to_str();
+        context.spend_filter_budget(message.len() as u64)?;
+        let parts = parse_message(&message)?;
+
+        let mut output = String::new();
+        Formatter { values, language }.format(&parts, None, &mut output)?;
+        Ok(Value::scalar(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:ident, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+                .unwrap_err()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn values(entries: &[(&'static str, Value)]) -> Value {
+        Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| ((*key).into(), value.clone()))
+                .collect(),
+        )
+    }
+
+    fn render(text: &str, count: i32) -> String {
+        let mut globals = Object::new();
+        globals.insert("message".into(), values(&[("count", Value::scalar(count))]));
+        ::liquid::ParserBuilder::with_liquid()
+            .extra_filters()
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap()
+            .render(&globals)
+            .unwrap()
+    }
+
+    const INBOX: &str =
+        "{name} has {count, plural, =0 {no messages} one {# message} other {# messages}}.";
+
+    #[test]
+    fn unit_icu_format_plural() {
+        let format = |count: i32| {
+            unit!(
+                IcuFormat,
+                tos!(INBOX),
+                values(&[("name", tos!("Ann")), ("count", Value::scalar(count))])
+            )
+        };
+        assert_eq!(format(0), tos!("Ann has no messages."));
+        assert_eq!(format(1), tos!("Ann has 1 message."));
+        assert_eq!(format(12), tos!("Ann has 12 messages."));
+
+        let offset = "{count, plural, offset:1 =0 {nobody} =1 {you} one {you and # other} other {you and # others}}";
+        let format = |count: i32| {
+            unit!(
+                IcuFormat,
+                tos!(offset),
+                values(&[("count", Value::scalar(count))])
+            )
+        };
+        assert_eq!(format(1), tos!("you"));
+        assert_eq!(format(2), tos!("you and 1 other"));
+        assert_eq!(format(3), tos!("you and 2 others"));
+    }
+
+    #[test]
+    fn unit_icu_format_select() {
+        let message = "{gender, select, female {She} male {He} other {They}} liked {n, selectordinal, one {#st} two {#nd} few {#rd} other {#th}} post";
+        let format = |gender: &str, n: i32| {
+            unit!(
+                IcuFormat,
+                tos!(message),
+                values(&[("gender", tos!(gender)), ("n", Value::scalar(n))])
+            )
+        };
+        assert_eq!(format("female", 1), tos!("She liked 1st post"));
+        assert_eq!(format("male", 22), tos!("He liked 22nd post"));
+        assert_eq!(format("unknown", 13), tos!("They liked 13th post"));
+        assert_eq!(format("other", 103), tos!("They liked 103rd post"));
+    }
+
+    #[test]
+    fn unit_icu_format_numbers_and_quotes() {
+        let message =
+            "'{'{n, number}'}' is {share, number, percent} of {n, number, integer} - it''s '#'1";
+        assert_eq!(
+            unit!(
+                IcuFormat,
+                tos!(message),
+                values(&[("n", Value::scalar(2.5)), ("share", Value::scalar(0.25))])
+            ),
+            tos!("{2.5} is 25% of 3 - it's '#'1")
+        );
+        assert_eq!(
+            unit!(
+                IcuFormat,
+                tos!("{n, plural, other {'#'# ranked}}"),
+                values(&[("n", Value::scalar(4))])
+            ),
+            tos!("#4 ranked")
+        );
+        assert_eq!(unit!(IcuFormat, tos!("No arguments")), tos!("No arguments"));
+    }
+
+    #[test]
+    fn unit_icu_format_locale() {
+        let polish = "{{ '{count, plural, one {# plik} few {# pliki} many {# plików} other {# pliku}}' | icu_format: message, locale: 'pl' }}";
+        assert_eq!(render(polish, 1), "1 plik");
+        assert_eq!(render(polish, 22), "22 pliki");
+        assert_eq!(render(polish, 25), "25 plików");
+
+        let french = "{{ '{count, plural, one {# fichier} other {# fichiers}}' | icu_format: message, locale: 'fr_FR' }}";
+        assert_eq!(render(french, 0), "0 fichier");
+        assert_eq!(render(french, 2), "2 fichiers");
+
+        let english = "{{ '{count, plural, one {# file} other {# files}}' | icu_format: message }}";
+        assert_eq!(render(english, 0), "0 files");
+
+        assert_eq!(plural_category("ru", 21.0, false), "one");
+        assert_eq!(plural_category("ru", 1.5, false), "other");
+        assert_eq!(plural_category("cs", 3.0, false), "few");
+        assert_eq!(plural_category("ja", 1.0, false), "other");
+        assert_eq!(plural_category(&language("pt_BR"), 0.0, false), "one");
+        assert_eq!(plural_category(&language("en-GB"), 1.0, false), "one");
+        assert_eq!(plural_category(&language("xx"), 1.0, false), "one");
+    }
+
+    #[test]
+    fn unit_icu_format_errors() {
+        failed!(IcuFormat, tos!("{count, plural, one {#}}"));
+        failed!(IcuFormat, tos!("{count, plural, other {#}"));
+        failed!(IcuFormat, tos!("{count, date}"));
+        failed!(IcuFormat, tos!("}"));
+        failed!(IcuFormat, tos!("{name}"));
+        failed!(IcuFormat, tos!("{name}"), tos!("not an object"));
+        failed!(
+            IcuFormat,
+            tos!("{n, plural, other {#}}"),
+            values(&[("n", tos!("many"))])
+        );
+    }
+}
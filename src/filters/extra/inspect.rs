@@ -0,0 +1,164 @@
+use std::fmt::Write;
+
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::{Scalar, Value};
+
+// liquid-rust proprietary
+
+fn inspect_scalar(scalar: &Scalar, output: &mut String) {
+    match scalar.type_name() {
+        "string" => write!(output, "{:?}", scalar.to_str()),
+        // `Debug` keeps the `.0` telling fractional numbers apart from whole ones.
+        "fractional number" => write!(output, "{:?}", scalar.to_float().unwrap_or_default()),
+        _ => write!(output, "{}", scalar.render()),
+    }
+    .expect("writing to a `String` cannot fail");
+}
+
+fn inspect(value: &Value, output: &mut String) {
+    match *value {
+        Value::Scalar(ref scalar) => inspect_scalar(scalar, output),
+        Value::Array(ref array) => {
+            output.push('[');
+            for (i, item) in array.iter().enumerate() {
+                if i != 0 {
+                    output.push_str(", ");
+                }
+                inspect(item, output);
+            }
+            output.push(']');
+        }
+        Value::Range(ref range) => inspect(&Value::Array(range.to_array().into()), output),
+        Value::Lazy(ref array) => inspect(&Value::Array(array.to_array()), output),
+        Value::Object(ref object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            output.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i != 0 {
+                    output.push_str(", ");
+                }
+                write!(output, "{:?}: ", key).expect("writing to a `String` cannot fail");
+                inspect(value, output);
+            }
+            output.push('}');
+        }
+        Value::Nil => output.push_str("nil"),
+        Value::Empty => output.push_str("empty"),
+        Value::Blank => output.push_str("blank"),
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "inspect",
+    description = "Shows a value as it is, e.g. `[1, \"two\", nil]`, for debugging templates.",
+    parsed(InspectFilter)
+)]
+pub struct Inspect;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "inspect"]
+struct InspectFilter;
+
+impl Filter for InspectFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let mut output = String::new();
+        inspect(input, &mut output);
+        Ok(Value::scalar(output))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "type_of",
+    description = "The type of a value: `string`, `integer`, `float`, `boolean`, `date`, `array`, \
+                   `object` or `nil`.",
+    parsed(TypeOfFilter)
+)]
+pub struct TypeOf;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "type_of"]
+struct TypeOfFilter;
+
+impl Filter for TypeOfFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let type_of = match *input {
+            Value::Scalar(ref scalar) => match scalar.type_name() {
+                "whole number" => "integer",
+                "fractional number" => "float",
+                other => other,
+            },
+            Value::Array(_) | Value::Lazy(_) => "array",
+            Value::Range(_) => "range",
+            Value::Object(_) => "object",
+            Value::Nil | Value::Empty | Value::Blank => "nil",
+        };
+        Ok(Value::scalar(type_of))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_inspect() {
+        assert_eq!(unit!(Inspect, Value::scalar(1)), tos!("1"));
+        assert_eq!(unit!(Inspect, Value::scalar(1f64)), tos!("1.0"));
+        assert_eq!(unit!(Inspect, tos!("1")), tos!(r#""1""#));
+        assert_eq!(unit!(Inspect, tos!("say \"hi\"")), tos!(r#""say \"hi\"""#));
+        assert_eq!(unit!(Inspect, Value::scalar(false)), tos!("false"));
+        assert_eq!(unit!(Inspect, Value::Nil), tos!("nil"));
+        assert_eq!(unit!(Inspect, Value::array(vec![])), tos!("[]"));
+
+        let mut object = ::liquid::value::Object::new();
+        object.insert("b".into(), Value::array(vec![Value::scalar(1), Value::Nil]));
+        object.insert("a".into(), tos!("x"));
+        assert_eq!(
+            unit!(Inspect, Value::Object(object)),
+            tos!(r#"{"a": "x", "b": [1, nil]}"#)
+        );
+    }
+
+    #[test]
+    fn unit_type_of() {
+        assert_eq!(unit!(TypeOf, tos!("1")), tos!("string"));
+        assert_eq!(unit!(TypeOf, Value::scalar(1)), tos!("integer"));
+        assert_eq!(unit!(TypeOf, Value::scalar(1.5)), tos!("float"));
+        assert_eq!(unit!(TypeOf, Value::scalar(true)), tos!("boolean"));
+        assert_eq!(unit!(TypeOf, Value::array(vec![])), tos!("array"));
+        assert_eq!(
+            unit!(TypeOf, Value::Object(::liquid::value::Object::new())),
+            tos!("object")
+        );
+        assert_eq!(unit!(TypeOf, Value::Nil), tos!("nil"));
+    }
+}
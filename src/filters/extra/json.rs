@@ -0,0 +1,101 @@
+use filters::json::{to_json, JsonStyle};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+
+// liquid-rust proprietary
+
+#[derive(Debug, FilterParameters)]
+struct JsonArgs {
+    #[parameter(
+        description = "Whether to write object keys in alphabetical order, for stable output. \
+                       Defaults to false.",
+        arg_type = "bool",
+        mode = "keyword"
+    )]
+    sort_keys: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "json",
+    description = "Serializes a value to JSON.",
+    parameters(JsonArgs),
+    parsed(JsonFilter)
+)]
+pub struct Json;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "json"]
+struct JsonFilter {
+    #[parameters]
+    args: JsonArgs,
+}
+
+impl Filter for JsonFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let style = JsonStyle {
+            sort_keys: args.sort_keys.unwrap_or(false),
+            ..JsonStyle::default()
+        };
+        Ok(Value::scalar(to_json(input, style)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($k:ident : $v:expr),*) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(vec![$((stringify!($k), ::liquid::interpreter::Expression::Literal($v))),*].into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_json_scalars() {
+        assert_eq!(unit!(Json, Value::scalar(1)), tos!("1"));
+        assert_eq!(unit!(Json, Value::scalar(1.5)), tos!("1.5"));
+        assert_eq!(unit!(Json, Value::scalar(f64::NAN)), tos!("null"));
+        assert_eq!(unit!(Json, Value::scalar(true)), tos!("true"));
+        assert_eq!(unit!(Json, tos!("a \"b\"\n")), tos!(r#""a \"b\"\n""#));
+        assert_eq!(unit!(Json, tos!("12")), tos!(r#""12""#));
+        assert_eq!(unit!(Json, Value::Nil), tos!("null"));
+    }
+
+    #[test]
+    fn unit_json_sort_keys() {
+        let mut object = ::liquid::value::Object::new();
+        for key in &["c", "a", "d", "b"] {
+            object.insert(
+                (*key).into(),
+                Value::array(vec![Value::scalar(1), Value::Nil]),
+            );
+        }
+        assert_eq!(
+            unit!(Json, Value::Object(object), sort_keys: Value::scalar(true)),
+            tos!(r#"{"a":[1,null],"b":[1,null],"c":[1,null],"d":[1,null]}"#)
+        );
+    }
+}
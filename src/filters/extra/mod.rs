@@ -1,5 +1,56 @@
+mod array;
+mod asset_digest;
+mod color;
+mod compare;
+#[cfg(feature = "crypto-filters")]
+mod crypto;
+mod csv;
 mod date;
+#[cfg(feature = "emoji-filters")]
+mod emoji;
+mod env;
+mod excerpt;
+mod format;
+#[cfg(feature = "icu-filters")]
+mod icu;
+mod inspect;
+mod json;
 mod pluralize;
+#[cfg(feature = "query-filters")]
+mod query;
+mod read_file;
+#[cfg(feature = "similarity-filters")]
+mod similarity;
+mod toc;
+mod units;
+mod url;
+mod xml;
 
+pub use self::array::{Partition, PartitionExp, SlicePages};
+pub use self::asset_digest::AssetDigest;
+pub use self::color::{ColorContrast, ColorDarken, ColorLighten, ColorMix, ColorToRgb};
+pub use self::compare::{Diff, Equals};
+#[cfg(feature = "crypto-filters")]
+pub use self::crypto::{HmacSha256, Md5, Sha1, Sha256};
+pub use self::csv::{ParseCsv, ToCsv};
 pub use self::date::DateInTz;
+#[cfg(feature = "emoji-filters")]
+pub use self::emoji::Emojify;
+pub use self::env::Env;
+pub use self::excerpt::{Excerpt, StripMarkdown};
+pub use self::format::{Money, MoneyWithCurrency, NumberWithDelimiter};
+#[cfg(feature = "icu-filters")]
+pub use self::icu::IcuFormat;
+pub use self::inspect::{Inspect, TypeOf};
+pub use self::json::Json;
 pub use self::pluralize::Pluralize;
+#[cfg(feature = "query-filters")]
+pub use self::query::Query;
+pub use self::read_file::ReadFile;
+#[cfg(feature = "similarity-filters")]
+pub use self::similarity::{DiffWords, Levenshtein, Similar};
+pub use self::toc::{AnchorizeHeadings, Toc};
+pub use self::units::{ConvertUnit, WeightWithUnit};
+pub use self::url::{UrlJoin, UrlParse, UrlRemoveParam, UrlSetParam};
+pub use self::xml::{CdataEscape, Xml};
+pub use filters::escape::XmlEscape;
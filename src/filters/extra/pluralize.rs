@@ -1,10 +1,10 @@
-use filters::invalid_input;
+use filters::helpers::input_integer;
 use liquid_compiler::{Filter, FilterParameters};
 use liquid_derive::*;
 use liquid_error::Result;
 use liquid_interpreter::Context;
 use liquid_interpreter::Expression;
-use liquid_value::{Scalar, Value};
+use liquid_value::Value;
 
 // shopify-specific
 
@@ -36,10 +36,7 @@ impl Filter for PluralizeFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let n = input
-            .as_scalar()
-            .and_then(Scalar::to_integer)
-            .ok_or_else(|| invalid_input("Whole number expected"))?;
+        let n = input_integer(input, context)?;
 
         if (n as isize) == 1 {
             Ok(args.singular.clone())
@@ -0,0 +1,187 @@
+use std::borrow::Cow;
+
+use filters::invalid_argument;
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+
+// liquid-rust proprietary
+
+#[derive(Debug, FilterParameters)]
+struct QueryArgs {
+    #[parameter(
+        description = "The JSON Pointer (RFC 6901) to look up, e.g. `/items/0/name`.",
+        arg_type = "str"
+    )]
+    pointer: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "query",
+    description = "Extracts a nested value using a JSON Pointer, returning nil when it doesn't exist.",
+    parameters(QueryArgs),
+    parsed(QueryFilter)
+)]
+pub struct Query;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "query"]
+struct QueryFilter {
+    #[parameters]
+    args: QueryArgs,
+}
+
+fn unescape(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+fn lookup<'v>(value: &'v Value, token: &str) -> Option<&'v Value> {
+    match value {
+        Value::Array(array) => {
+            // Leading zeros and signs are not valid array indexes.
+            if token.is_empty()
+                || (token.len() > 1 && token.starts_with('0'))
+                || !token.bytes().all(|b| b.is_ascii_digit())
+            {
+                return None;
+            }
+            token
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| array.get(index))
+        }
+        Value::Object(object) => object.get(token),
+        _ => None,
+    }
+}
+
+impl Filter for QueryFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let pointer: &str = &args.pointer;
+
+        if pointer.is_empty() {
+            return Ok(input.clone());
+        }
+        if !pointer.starts_with('/') {
+            return Err(invalid_argument(
+                "pointer",
+                "JSON Pointer must be empty or start with `/`",
+            ));
+        }
+
+        let mut current = input;
+        for token in pointer[1..].split('/') {
+            match lookup(current, &unescape(token)) {
+                Some(value) => current = value,
+                None => return Ok(Value::Nil),
+            }
+        }
+        Ok(current.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use liquid_value::liquid_value;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:ident, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+                .unwrap_err()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn payload() -> Value {
+        liquid_value!({
+            "items": [{"name": "first"}, {"name": "second"}],
+            "a/b": 1,
+            "m~n": 2,
+            "": 3,
+        })
+    }
+
+    #[test]
+    fn unit_query() {
+        assert_eq!(
+            unit!(Query, payload(), tos!("/items/1/name")),
+            tos!("second")
+        );
+        assert_eq!(
+            unit!(Query, payload(), tos!("/items/0")),
+            liquid_value!({"name": "first"})
+        );
+    }
+
+    #[test]
+    fn unit_query_whole_document() {
+        assert_eq!(unit!(Query, payload(), tos!("")), payload());
+    }
+
+    #[test]
+    fn unit_query_escapes() {
+        assert_eq!(unit!(Query, payload(), tos!("/a~1b")), Value::scalar(1));
+        assert_eq!(unit!(Query, payload(), tos!("/m~0n")), Value::scalar(2));
+        assert_eq!(unit!(Query, payload(), tos!("/")), Value::scalar(3));
+    }
+
+    #[test]
+    fn unit_query_missing() {
+        assert_eq!(unit!(Query, payload(), tos!("/items/2/name")), Value::Nil);
+        assert_eq!(unit!(Query, payload(), tos!("/items/01")), Value::Nil);
+        assert_eq!(unit!(Query, payload(), tos!("/items/-1")), Value::Nil);
+        assert_eq!(unit!(Query, payload(), tos!("/nope/deeper")), Value::Nil);
+    }
+
+    #[test]
+    fn unit_query_invalid_pointer() {
+        failed!(Query, payload(), tos!("items"));
+    }
+
+    #[test]
+    fn unit_query_no_args() {
+        failed!(Query, payload());
+    }
+}
@@ -0,0 +1,117 @@
+use filters::invalid_input;
+use liquid_compiler::{Filter, FilterArguments, FilterReflection, ParseFilter};
+use liquid_derive::*;
+use liquid_error::{Error, Result};
+use liquid_interpreter::Context;
+use liquid_value::Value;
+use sandbox::FileSandbox;
+
+// liquid-rust proprietary
+
+/// Reads a file's content, e.g. `{{ "nginx/upstream.conf" | read_file }}`.
+///
+/// Only files within the `FileSandbox` are available, so this is not registered by
+/// `extra_filters`.
+#[derive(Clone, Debug, Default, FilterReflection)]
+#[filter(
+    name = "read_file",
+    description = "Returns the content of a file from the sandbox."
+)]
+pub struct ReadFile {
+    sandbox: FileSandbox,
+}
+
+impl ReadFile {
+    pub fn new(sandbox: FileSandbox) -> Self {
+        Self { sandbox }
+    }
+}
+
+impl ParseFilter for ReadFile {
+    fn parse(&self, mut arguments: FilterArguments) -> Result<Box<dyn Filter>> {
+        if arguments.positional.next().is_some() {
+            return Error::with_msg("Invalid number of positional arguments")
+                .context("cause", "expected at most 0 positional arguments")
+                .into_err();
+        }
+        if let Some((name, _)) = arguments.keyword.next() {
+            return Error::with_msg(format!("Unexpected named argument `{}`", name)).into_err();
+        }
+
+        Ok(Box::new(ReadFileFilter {
+            sandbox: self.sandbox.clone(),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+#[derive(Debug, Display_filter)]
+#[name = "read_file"]
+struct ReadFileFilter {
+    sandbox: FileSandbox,
+}
+
+impl Filter for ReadFileFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let path = input
+            .as_scalar()
+            .ok_or_else(|| invalid_input("String expected"))?
+            .to_str();
+
+        self.sandbox.read(&path).map(Value::scalar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:expr, $b:expr) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments {
+                positional,
+                keyword,
+            };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn filter() -> ReadFile {
+        ReadFile::new(
+            FileSandbox::new()
+                .allow_root(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/input")),
+        )
+    }
+
+    #[test]
+    fn unit_read_file() {
+        let output = unit!(filter(), tos!("example.txt")).unwrap();
+        assert!(output.to_str().starts_with("{{'whooo' | size}}"));
+    }
+
+    #[test]
+    fn unit_read_file_outside_sandbox() {
+        assert!(unit!(filter(), tos!("../../../Cargo.toml")).is_err());
+    }
+
+    #[test]
+    fn unit_read_file_not_a_string() {
+        assert!(unit!(filter(), Value::Nil).is_err());
+    }
+}
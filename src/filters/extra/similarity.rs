@@ -0,0 +1,297 @@
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::{Object, Value};
+
+use filters::invalid_argument;
+
+// liquid-rust proprietary
+
+/// How many characters have to be inserted, deleted or substituted to turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + if a == *b { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        ::std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// How alike `a` and `b` are, from `0.0` for nothing in common to `1.0` for equal.
+fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+#[derive(Debug, FilterParameters)]
+struct LevenshteinArgs {
+    #[parameter(description = "The string to compare with.", arg_type = "str")]
+    other: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "levenshtein",
+    description = "The number of characters to insert, delete or substitute to turn the input into the argument.",
+    parameters(LevenshteinArgs),
+    parsed(LevenshteinFilter)
+)]
+pub struct Levenshtein;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "levenshtein"]
+struct LevenshteinFilter {
+    #[parameters]
+    args: LevenshteinArgs,
+}
+
+impl Filter for LevenshteinFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let distance = levenshtein(&input.to_str(), &args.other);
+        Ok(Value::scalar(distance as i32))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct SimilarArgs {
+    #[parameter(description = "The string to compare with.", arg_type = "str")]
+    other: Expression,
+    #[parameter(
+        description = "How alike the strings must be, from 0.0 to 1.0. Defaults to 0.8.",
+        arg_type = "float",
+        mode = "keyword"
+    )]
+    threshold: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "similar",
+    description = "Whether the input and the argument are alike, ignoring case, e.g. for \"did you mean\" suggestions.",
+    parameters(SimilarArgs),
+    parsed(SimilarFilter)
+)]
+pub struct Similar;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "similar"]
+struct SimilarFilter {
+    #[parameters]
+    args: SimilarArgs,
+}
+
+impl Filter for SimilarFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let threshold = args.threshold.unwrap_or(0.8);
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(invalid_argument(
+                "threshold",
+                "Number from 0.0 to 1.0 expected",
+            ));
+        }
+
+        let input = input.to_str().to_lowercase();
+        let other = args.other.to_lowercase();
+        Ok(Value::scalar(similarity(&input, &other) >= threshold))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Change {
+    Equal,
+    Insert,
+    Delete,
+}
+
+impl Change {
+    fn name(self) -> &'static str {
+        match self {
+            Change::Equal => "equal",
+            Change::Insert => "insert",
+            Change::Delete => "delete",
+        }
+    }
+}
+
+/// The changes turning the words of `old` into the words of `new`, keeping as many words as
+/// possible, with runs of the same change joined.
+fn diff_words<'s>(old: &'s str, new: &'s str) -> Vec<(Change, Vec<&'s str>)> {
+    let old: Vec<_> = old.split_whitespace().collect();
+    let new: Vec<_> = new.split_whitespace().collect();
+
+    // `common[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`.
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes: Vec<(Change, Vec<&str>)> = Vec::new();
+    let mut push = |change, word| match changes.last_mut() {
+        Some(&mut (last, ref mut words)) if last == change => words.push(word),
+        _ => changes.push((change, vec![word])),
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            push(Change::Equal, old[i]);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            push(Change::Delete, old[i]);
+            i += 1;
+        } else {
+            push(Change::Insert, new[j]);
+            j += 1;
+        }
+    }
+    changes
+}
+
+#[derive(Debug, FilterParameters)]
+struct DiffWordsArgs {
+    #[parameter(description = "The new version of the input.", arg_type = "str")]
+    new: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "diff_words",
+    description = "The changes between the words of the input and the argument, as objects with `type` (`equal`, `insert` or `delete`) and `text`.",
+    parameters(DiffWordsArgs),
+    parsed(DiffWordsFilter)
+)]
+pub struct DiffWords;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "diff_words"]
+struct DiffWordsFilter {
+    #[parameters]
+    args: DiffWordsArgs,
+}
+
+impl Filter for DiffWordsFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let input = input.to_str();
+        let changes = diff_words(&input, &args.new)
+            .into_iter()
+            .map(|(change, words)| {
+                let mut object = Object::new();
+                object.insert("type".into(), Value::scalar(change.name()));
+                object.insert("text".into(), Value::scalar(words.join(" ")));
+                Value::Object(object)
+            });
+        Ok(Value::array(changes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn render(text: &str) -> String {
+        ::liquid::ParserBuilder::with_liquid()
+            .extra_filters()
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap()
+            .render(&Object::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn unit_levenshtein() {
+        assert_eq!(
+            unit!(Levenshtein, tos!("kitten"), tos!("sitting")),
+            Value::scalar(3)
+        );
+        assert_eq!(unit!(Levenshtein, tos!(""), tos!("abc")), Value::scalar(3));
+        assert_eq!(
+            unit!(Levenshtein, tos!("über"), tos!("uber")),
+            Value::scalar(1)
+        );
+        assert_eq!(
+            unit!(Levenshtein, tos!("same"), tos!("same")),
+            Value::scalar(0)
+        );
+    }
+
+    #[test]
+    fn unit_similar() {
+        assert_eq!(
+            unit!(Similar, tos!("Color"), tos!("colour")),
+            Value::scalar(true)
+        );
+        assert_eq!(
+            unit!(Similar, tos!("apple"), tos!("orange")),
+            Value::scalar(false)
+        );
+        assert_eq!(
+            render("{{ 'apple' | similar: 'apply', threshold: 0.9 }}"),
+            "false"
+        );
+        assert_eq!(
+            render("{{ 'apple' | similar: 'maple', threshold: 0.5 }}"),
+            "true"
+        );
+    }
+
+    #[test]
+    fn unit_diff_words() {
+        let text = concat!(
+            "{% assign changes = 'the quick brown fox' | diff_words: 'the slow brown dog jumps' %}",
+            "{% for change in changes %}{{ change.type }}:{{ change.text }};{% endfor %}"
+        );
+        assert_eq!(
+            render(text),
+            "equal:the;delete:quick;insert:slow;equal:brown;delete:fox;insert:dog jumps;"
+        );
+        assert_eq!(unit!(DiffWords, tos!(""), tos!("")), Value::array(vec![]));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::{Array, Object, Value};
+use regex::Regex;
+
+use super::excerpt::decode_entities;
+use filters::helpers::argument_in_range;
+
+// liquid-rust proprietary
+
+lazy_static! {
+    static ref HEADING: Regex = Regex::new(r"(?is)<h([1-6])(\s[^>]*)?>(.*?)</h[1-6]\s*>").unwrap();
+    static ref ID: Regex = Regex::new(r#"(?i)\bid\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    static ref TAG: Regex = Regex::new(r"(?s)<.*?>").unwrap();
+    static ref SLUG_INVALID_CHARS: Regex = Regex::new(r"[^\p{M}\p{L}\p{Nd}]+").unwrap();
+}
+
+#[derive(Debug)]
+struct Heading {
+    level: i32,
+    id: String,
+    text: String,
+    /// Where the `id` goes in the opening tag, if the heading doesn't have one already.
+    anchor_at: Option<usize>,
+}
+
+/// The headings of `html`, in order, with ids that are unique in the document.
+///
+/// Headings without an id get one from their text, like `slugify`, with `-1`, `-2`, ... added
+/// to repeats.
+fn headings(html: &str) -> Vec<Heading> {
+    let parsed: Vec<_> = HEADING
+        .captures_iter(html)
+        .map(|captures| {
+            let level = captures[1]
+                .parse()
+                .expect("the pattern only matches 1 to 6");
+            let inner = TAG.replace_all(&captures[3], "");
+            let text = itertools::join(decode_entities(&inner).split_whitespace(), " ");
+            let id = captures.get(2).and_then(|attributes| {
+                let id = ID.captures(attributes.as_str())?;
+                let id = id.get(1).or_else(|| id.get(2))?.as_str();
+                Some(id.to_owned())
+            });
+            let anchor_at = captures
+                .get(2)
+                .map_or_else(|| captures.get(0).unwrap().start() + 3, |a| a.end());
+            (level, text, id, anchor_at)
+        })
+        .collect();
+
+    let mut seen: HashSet<String> = parsed.iter().filter_map(|h| h.2.clone()).collect();
+    parsed
+        .into_iter()
+        .map(|(level, text, id, anchor_at)| match id {
+            Some(id) => Heading {
+                level,
+                id,
+                text,
+                anchor_at: None,
+            },
+            None => {
+                let slug = SLUG_INVALID_CHARS
+                    .replace_all(&text, "-")
+                    .trim_matches('-')
+                    .to_lowercase();
+                let slug = if slug.is_empty() {
+                    "section".to_owned()
+                } else {
+                    slug
+                };
+                let mut id = slug.clone();
+                let mut repeat = 0;
+                while seen.contains(&id) {
+                    repeat += 1;
+                    id = format!("{}-{}", slug, repeat);
+                }
+                seen.insert(id.clone());
+                Heading {
+                    level,
+                    id,
+                    text,
+                    anchor_at: Some(anchor_at),
+                }
+            }
+        })
+        .collect()
+}
+
+/// The headings as a tree, where each heading holds the deeper headings that follow it.
+fn nest(headings: &[Heading]) -> Array {
+    let mut entries = Array::new();
+    let mut rest = headings;
+    while let Some((heading, tail)) = rest.split_first() {
+        let end = tail
+            .iter()
+            .position(|next| next.level <= heading.level)
+            .unwrap_or(tail.len());
+        let mut entry = Object::new();
+        entry.insert("level".into(), Value::scalar(heading.level));
+        entry.insert("id".into(), Value::scalar(heading.id.clone()));
+        entry.insert("text".into(), Value::scalar(heading.text.clone()));
+        entry.insert("children".into(), Value::Array(nest(&tail[..end])));
+        entries.push(Value::Object(entry));
+        rest = &tail[end..];
+    }
+    entries
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "anchorize_headings",
+    description = "Gives every HTML heading without an `id` one made from its text, matching the ids of `toc`.",
+    parsed(AnchorizeHeadingsFilter)
+)]
+pub struct AnchorizeHeadings;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "anchorize_headings"]
+struct AnchorizeHeadingsFilter;
+
+impl Filter for AnchorizeHeadingsFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let html = input.to_str();
+        context.spend_filter_budget(html.len() as u64)?;
+
+        let mut result = String::with_capacity(html.len());
+        let mut copied = 0;
+        for heading in headings(&html) {
+            if let Some(anchor_at) = heading.anchor_at {
+                result.push_str(&html[copied..anchor_at]);
+                result.push_str(&format!(" id=\"{}\"", heading.id));
+                copied = anchor_at;
+            }
+        }
+        result.push_str(&html[copied..]);
+        Ok(Value::scalar(result))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct TocArgs {
+    #[parameter(
+        description = "The highest heading level to include, from 1 to 6. Defaults to 1.",
+        arg_type = "integer",
+        mode = "keyword"
+    )]
+    min_level: Option<Expression>,
+    #[parameter(
+        description = "The deepest heading level to include, from 1 to 6. Defaults to 6.",
+        arg_type = "integer",
+        mode = "keyword"
+    )]
+    max_level: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "toc",
+    description = "The table of contents of HTML, as nested objects with `level`, `id`, `text` and `children`.",
+    parameters(TocArgs),
+    parsed(TocFilter)
+)]
+pub struct Toc;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "toc"]
+struct TocFilter {
+    #[parameters]
+    args: TocArgs,
+}
+
+impl Filter for TocFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let min_level = argument_in_range("min_level", args.min_level.unwrap_or(1), 1..=6)?;
+        let max_level = argument_in_range("max_level", args.max_level.unwrap_or(6), 1..=6)?;
+
+        let html = input.to_str();
+        context.spend_filter_budget(html.len() as u64)?;
+        let headings: Vec<_> = headings(&html)
+            .into_iter()
+            .filter(|heading| min_level <= heading.level && heading.level <= max_level)
+            .collect();
+        Ok(Value::Array(nest(&headings)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    const PAGE: &str = concat!(
+        "<h1>Guide</h1>\n",
+        "<h2 class=\"x\">Getting <em>started</em></h2>\n",
+        "<h3>Install &amp; run</h3>\n",
+        "<h2 id='usage'>Usage</h2>\n",
+        "<h2>Getting started</h2>\n",
+        "<h4>Deep</h4>\n"
+    );
+
+    fn render(text: &str) -> String {
+        let mut globals = Object::new();
+        globals.insert("page".into(), Value::scalar(PAGE));
+        ::liquid::ParserBuilder::with_liquid()
+            .extra_filters()
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap()
+            .render(&globals)
+            .unwrap()
+    }
+
+    #[test]
+    fn unit_anchorize_headings() {
+        assert_eq!(
+            unit!(AnchorizeHeadings, tos!(PAGE)),
+            tos!(concat!(
+                "<h1 id=\"guide\">Guide</h1>\n",
+                "<h2 class=\"x\" id=\"getting-started\">Getting <em>started</em></h2>\n",
+                "<h3 id=\"install-run\">Install &amp; run</h3>\n",
+                "<h2 id='usage'>Usage</h2>\n",
+                "<h2 id=\"getting-started-1\">Getting started</h2>\n",
+                "<h4 id=\"deep\">Deep</h4>\n"
+            ))
+        );
+        assert_eq!(
+            unit!(AnchorizeHeadings, tos!("<p>No headings</p>")),
+            tos!("<p>No headings</p>")
+        );
+    }
+
+    #[test]
+    fn unit_toc() {
+        let text = concat!(
+            "{% assign toc = page | toc %}",
+            "{% for h1 in toc %}{{ h1.text }}({% for h2 in h1.children %}",
+            "{{ h2.level }}#{{ h2.id }}:{{ h2.text }}[{{ h2.children | map: 'text' | join: ',' }}];",
+            "{% endfor %}){% endfor %}"
+        );
+        assert_eq!(
+            render(text),
+            concat!(
+                "Guide(2#getting-started:Getting started[Install & run];",
+                "2#usage:Usage[];",
+                "2#getting-started-1:Getting started[Deep];)"
+            )
+        );
+    }
+
+    #[test]
+    fn unit_toc_levels() {
+        assert_eq!(
+            render("{{ page | toc: min_level: 2, max_level: 2 | map: 'id' | join: ',' }}"),
+            "getting-started,usage,getting-started-1"
+        );
+        assert_eq!(render("{{ page | toc: min_level: 4 | size }}"), "1");
+    }
+}
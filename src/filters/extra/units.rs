@@ -0,0 +1,250 @@
+use filters::{invalid_argument, invalid_input};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+
+// shopify, generalized for dimensions
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Dimension {
+    Weight,
+    Length,
+}
+
+const UNKNOWN_UNIT: &str = "One of `g`, `kg`, `oz`, `lb`, `mm`, `cm`, `m`, `in` or `ft` expected";
+
+/// The dimension of `unit` and how many base units (grams or millimeters) it is.
+fn unit_scale(unit: &str) -> Option<(Dimension, f64)> {
+    let scale = match unit {
+        "g" => (Dimension::Weight, 1.0),
+        "kg" => (Dimension::Weight, 1000.0),
+        "oz" => (Dimension::Weight, 28.349_523_125),
+        "lb" => (Dimension::Weight, 453.592_37),
+        "mm" => (Dimension::Length, 1.0),
+        "cm" => (Dimension::Length, 10.0),
+        "m" => (Dimension::Length, 1000.0),
+        "in" => (Dimension::Length, 25.4),
+        "ft" => (Dimension::Length, 304.8),
+        _ => return None,
+    };
+    Some(scale)
+}
+
+fn scale_of(argument: &'static str, unit: &str, dimension: Dimension) -> Result<f64> {
+    match unit_scale(unit) {
+        Some((d, scale)) if d == dimension => Ok(scale),
+        Some(_) => Err(invalid_argument(
+            argument,
+            "Units of the same dimension expected",
+        )),
+        None => Err(invalid_argument(argument, UNKNOWN_UNIT)),
+    }
+}
+
+fn input_number(input: &Value) -> Result<f64> {
+    input
+        .as_scalar()
+        .and_then(|s| s.to_float())
+        .ok_or_else(|| invalid_input("Number expected"))
+}
+
+/// Round to two decimals, dropping trailing zeros.
+fn format_amount(amount: f64) -> String {
+    let amount = format!("{:.2}", amount);
+    amount
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_owned()
+}
+
+#[derive(Debug, FilterParameters)]
+struct WeightWithUnitArgs {
+    #[parameter(
+        description = "The unit to display the weight in. Defaults to the filter's weight unit.",
+        arg_type = "str"
+    )]
+    unit: Option<Expression>,
+}
+
+/// Formats a weight in grams with its unit, e.g. `{{ 1500 | weight_with_unit }}` as `1.5 kg`.
+///
+/// ```rust
+/// let parser = liquid::ParserBuilder::with_liquid()
+///     .filter(liquid::filters::extra::WeightWithUnit::new().with_unit("lb"))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, ParseFilter, FilterReflection)]
+#[filter(
+    name = "weight_with_unit",
+    description = "Formats a weight in grams with its unit, e.g. `1.5 kg`.",
+    parameters(WeightWithUnitArgs),
+    parsed(WeightWithUnitFilter),
+    stateful
+)]
+pub struct WeightWithUnit {
+    unit: String,
+}
+
+impl WeightWithUnit {
+    /// Create a `weight_with_unit` filter displaying weights in `kg` by default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the unit weights are displayed in by default, e.g. `lb`.
+    pub fn with_unit<S: Into<String>>(mut self, unit: S) -> Self {
+        self.unit = unit.into();
+        self
+    }
+}
+
+impl Default for WeightWithUnit {
+    fn default() -> Self {
+        Self {
+            unit: "kg".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "weight_with_unit"]
+struct WeightWithUnitFilter {
+    #[parameters]
+    args: WeightWithUnitArgs,
+    #[state]
+    config: WeightWithUnit,
+}
+
+impl Filter for WeightWithUnitFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let unit = args
+            .unit
+            .as_ref()
+            .map(|u| u.as_ref())
+            .unwrap_or(self.config.unit.as_str());
+        let scale = scale_of("unit", unit, Dimension::Weight)?;
+
+        let grams = input_number(input)?;
+        Ok(Value::scalar(format!(
+            "{} {}",
+            format_amount(grams / scale),
+            unit
+        )))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct ConvertUnitArgs {
+    #[parameter(description = "The unit of the input.", arg_type = "str")]
+    from: Expression,
+    #[parameter(description = "The unit to convert to.", arg_type = "str")]
+    to: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "convert_unit",
+    description = "Converts a weight or length between units, e.g. `{{ 2 | convert_unit: \"lb\", \"kg\" }}`.",
+    parameters(ConvertUnitArgs),
+    parsed(ConvertUnitFilter)
+)]
+pub struct ConvertUnit;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "convert_unit"]
+struct ConvertUnitFilter {
+    #[parameters]
+    args: ConvertUnitArgs,
+}
+
+impl Filter for ConvertUnitFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let (dimension, from) =
+            unit_scale(&args.from).ok_or_else(|| invalid_argument("from", UNKNOWN_UNIT))?;
+        let to = scale_of("to", &args.to, dimension)?;
+
+        let amount = input_number(input)?;
+        Ok(Value::scalar(amount * from / to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:expr, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:expr, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_weight_with_unit() {
+        assert_eq!(
+            unit!(WeightWithUnit::new(), Value::scalar(1500)).unwrap(),
+            tos!("1.5 kg")
+        );
+        assert_eq!(
+            unit!(WeightWithUnit::new(), Value::scalar(1000), tos!("lb")).unwrap(),
+            tos!("2.2 lb")
+        );
+        assert_eq!(
+            unit!(WeightWithUnit::new(), Value::scalar(250), tos!("g")).unwrap(),
+            tos!("250 g")
+        );
+    }
+
+    #[test]
+    fn unit_weight_with_unit_configured() {
+        let ounces = WeightWithUnit::new().with_unit("oz");
+        assert_eq!(unit!(ounces, Value::scalar(100)).unwrap(), tos!("3.53 oz"));
+        assert_eq!(
+            unit!(ounces, Value::scalar(1000), tos!("kg")).unwrap(),
+            tos!("1 kg")
+        );
+    }
+
+    #[test]
+    fn unit_weight_with_unit_invalid() {
+        assert!(unit!(WeightWithUnit::new(), Value::scalar(1), tos!("cm")).is_err());
+        assert!(unit!(WeightWithUnit::new(), Value::scalar(1), tos!("stone")).is_err());
+        assert!(unit!(WeightWithUnit::new(), tos!("heavy")).is_err());
+    }
+
+    #[test]
+    fn unit_convert_unit() {
+        assert_eq!(
+            unit!(ConvertUnit, Value::scalar(2500), tos!("g"), tos!("kg")).unwrap(),
+            Value::scalar(2.5)
+        );
+        assert_eq!(
+            unit!(ConvertUnit, Value::scalar(3), tos!("m"), tos!("cm")).unwrap(),
+            Value::scalar(300.0)
+        );
+        assert!(unit!(ConvertUnit, Value::scalar(1), tos!("kg"), tos!("m")).is_err());
+        assert!(unit!(ConvertUnit, Value::scalar(1), tos!("st"), tos!("kg")).is_err());
+    }
+}
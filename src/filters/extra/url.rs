@@ -0,0 +1,330 @@
+use filters::{invalid_argument, invalid_input};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::{Object, Value};
+use url::Url;
+
+// liquid-rust proprietary
+
+fn parse_input(input: &Value) -> Result<Url> {
+    let input = input
+        .as_scalar()
+        .ok_or_else(|| invalid_input("Absolute URL expected"))?
+        .to_str();
+    Url::parse(&input).map_err(|e| invalid_input(format!("Invalid URL: {}", e)))
+}
+
+fn optional<S: Into<String>>(value: Option<S>) -> Value {
+    value.map(|v| Value::scalar(v.into())).unwrap_or(Value::Nil)
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "url_parse",
+    description = "Parses an absolute URL into an object with `scheme`, `username`, `password`, `host`, \
+                   `port`, `path`, `query`, `params`, `fragment` and `href`.",
+    parsed(UrlParseFilter)
+)]
+pub struct UrlParse;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "url_parse"]
+struct UrlParseFilter;
+
+impl Filter for UrlParseFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let url = parse_input(input)?;
+
+        let params: Object = url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned().into(), Value::scalar(v.into_owned())))
+            .collect();
+        let username = Some(url.username()).filter(|u| !u.is_empty());
+
+        let mut object = Object::new();
+        object.insert("scheme".into(), Value::scalar(url.scheme().to_owned()));
+        object.insert("username".into(), optional(username));
+        object.insert("password".into(), optional(url.password()));
+        object.insert("host".into(), optional(url.host_str()));
+        object.insert(
+            "port".into(),
+            url.port_or_known_default()
+                .map(|p| Value::scalar(i32::from(p)))
+                .unwrap_or(Value::Nil),
+        );
+        object.insert("path".into(), Value::scalar(url.path().to_owned()));
+        object.insert("query".into(), optional(url.query()));
+        object.insert("params".into(), Value::Object(params));
+        object.insert("fragment".into(), optional(url.fragment()));
+        object.insert("href".into(), Value::scalar(url.as_str().to_owned()));
+        Ok(Value::Object(object))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct UrlJoinArgs {
+    #[parameter(
+        description = "The URL to resolve against the input.",
+        arg_type = "str"
+    )]
+    relative: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "url_join",
+    description = "Resolves a relative URL against the input, like a browser would for a link on that page.",
+    parameters(UrlJoinArgs),
+    parsed(UrlJoinFilter)
+)]
+pub struct UrlJoin;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "url_join"]
+struct UrlJoinFilter {
+    #[parameters]
+    args: UrlJoinArgs,
+}
+
+impl Filter for UrlJoinFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let base = parse_input(input)?;
+        let url = base
+            .join(&args.relative)
+            .map_err(|e| invalid_argument("relative".to_owned(), format!("Invalid URL: {}", e)))?;
+        Ok(Value::scalar(url.into_string()))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct UrlSetParamArgs {
+    #[parameter(description = "The name of the query parameter.", arg_type = "str")]
+    name: Expression,
+    #[parameter(description = "The value of the query parameter.", arg_type = "str")]
+    value: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "url_set_param",
+    description = "Sets a query parameter, replacing any existing values for it.",
+    parameters(UrlSetParamArgs),
+    parsed(UrlSetParamFilter)
+)]
+pub struct UrlSetParam;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "url_set_param"]
+struct UrlSetParamFilter {
+    #[parameters]
+    args: UrlSetParamArgs,
+}
+
+fn retain_params<F>(url: &Url, f: F) -> Vec<(String, String)>
+where
+    F: Fn(&str) -> bool,
+{
+    url.query_pairs()
+        .filter(|(k, _)| f(k))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect()
+}
+
+fn set_params(url: &mut Url, params: &[(String, String)]) {
+    if params.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(params);
+    }
+}
+
+impl Filter for UrlSetParamFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let name: &str = &args.name;
+
+        let mut url = parse_input(input)?;
+        let mut params = retain_params(&url, |k| k != name);
+        params.push((name.to_owned(), args.value.into_owned()));
+        set_params(&mut url, &params);
+        Ok(Value::scalar(url.into_string()))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct UrlRemoveParamArgs {
+    #[parameter(description = "The name of the query parameter.", arg_type = "str")]
+    name: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "url_remove_param",
+    description = "Removes all values of a query parameter.",
+    parameters(UrlRemoveParamArgs),
+    parsed(UrlRemoveParamFilter)
+)]
+pub struct UrlRemoveParam;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "url_remove_param"]
+struct UrlRemoveParamFilter {
+    #[parameters]
+    args: UrlRemoveParamArgs,
+}
+
+impl Filter for UrlRemoveParamFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let name: &str = &args.name;
+
+        let mut url = parse_input(input)?;
+        let params = retain_params(&url, |k| k != name);
+        set_params(&mut url, &params);
+        Ok(Value::scalar(url.into_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use liquid_value::liquid_value;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:ident, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+                .unwrap_err()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_url_parse() {
+        let input = tos!("https://user@example.com:8080/a/b?x=1&y=two%20words#top");
+        let desired_result = liquid_value!({
+            "scheme": "https",
+            "username": "user",
+            "password": nil,
+            "host": "example.com",
+            "port": 8080,
+            "path": "/a/b",
+            "query": "x=1&y=two%20words",
+            "params": {"x": "1", "y": "two words"},
+            "fragment": "top",
+            "href": "https://user@example.com:8080/a/b?x=1&y=two%20words#top",
+        });
+        assert_eq!(unit!(UrlParse, input), desired_result);
+    }
+
+    #[test]
+    fn unit_url_parse_defaults() {
+        let output = unit!(UrlParse, tos!("http://example.com"));
+        let output = output.as_object().unwrap();
+        assert_eq!(output["port"], Value::scalar(80));
+        assert_eq!(output["path"], tos!("/"));
+        assert_eq!(output["query"], Value::Nil);
+        assert_eq!(output["username"], Value::Nil);
+    }
+
+    #[test]
+    fn unit_url_parse_relative() {
+        failed!(UrlParse, tos!("/a/b"));
+        failed!(UrlParse, Value::Nil);
+    }
+
+    #[test]
+    fn unit_url_join() {
+        let base = tos!("https://example.com/docs/intro.html");
+        assert_eq!(
+            unit!(UrlJoin, base, tos!("setup.html")),
+            tos!("https://example.com/docs/setup.html")
+        );
+        assert_eq!(
+            unit!(UrlJoin, base, tos!("../img/a.png")),
+            tos!("https://example.com/img/a.png")
+        );
+        assert_eq!(
+            unit!(UrlJoin, base, tos!("//cdn.example.com/a.js")),
+            tos!("https://cdn.example.com/a.js")
+        );
+    }
+
+    #[test]
+    fn unit_url_set_param() {
+        assert_eq!(
+            unit!(
+                UrlSetParam,
+                tos!("https://example.com/?page=1&sort=asc"),
+                tos!("page"),
+                tos!("2")
+            ),
+            tos!("https://example.com/?sort=asc&page=2")
+        );
+        assert_eq!(
+            unit!(
+                UrlSetParam,
+                tos!("https://example.com/search"),
+                tos!("q"),
+                tos!("a&b")
+            ),
+            tos!("https://example.com/search?q=a%26b")
+        );
+    }
+
+    #[test]
+    fn unit_url_remove_param() {
+        assert_eq!(
+            unit!(
+                UrlRemoveParam,
+                tos!("https://example.com/?page=1&sort=asc&page=3#top"),
+                tos!("page")
+            ),
+            tos!("https://example.com/?sort=asc#top")
+        );
+        assert_eq!(
+            unit!(
+                UrlRemoveParam,
+                tos!("https://example.com/?page=1"),
+                tos!("page")
+            ),
+            tos!("https://example.com/")
+        );
+    }
+}
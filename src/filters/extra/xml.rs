@@ -0,0 +1,208 @@
+use filters::escape::xml_escape;
+use filters::{invalid_argument, invalid_input};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+
+// liquid-rust proprietary
+
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':')
+}
+
+fn write_element(output: &mut String, name: &str, value: &Value) -> Result<()> {
+    match value {
+        Value::Array(array) => {
+            for value in array {
+                write_element(output, name, value)?;
+            }
+        }
+        Value::Range(range) => {
+            for value in range.to_array() {
+                write_element(output, name, &value)?;
+            }
+        }
+        Value::Lazy(array) => {
+            for value in array.iter() {
+                write_element(output, name, &value)?;
+            }
+        }
+        Value::Object(object) => {
+            let mut keys: Vec<_> = object.keys().collect();
+            keys.sort();
+            output.push_str(&format!("<{}>", name));
+            for key in keys {
+                if !is_xml_name(key) {
+                    return Err(invalid_input(format!(
+                        "Object key `{}` is not a valid element name",
+                        key
+                    )));
+                }
+                write_element(output, key, &object[key.as_ref()])?;
+            }
+            output.push_str(&format!("</{}>", name));
+        }
+        Value::Nil | Value::Empty | Value::Blank => {
+            output.push_str(&format!("<{}/>", name));
+        }
+        Value::Scalar(scalar) => {
+            output.push_str(&format!(
+                "<{}>{}</{}>",
+                name,
+                xml_escape(&scalar.to_str()),
+                name
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "cdata_escape",
+    description = "Escapes a string for use inside of a `<![CDATA[...]]>` section.",
+    parsed(CdataEscapeFilter)
+)]
+pub struct CdataEscape;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "cdata_escape"]
+struct CdataEscapeFilter;
+
+impl Filter for CdataEscapeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(
+            input.to_str().replace("]]>", "]]]]><![CDATA[>"),
+        ))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct XmlArgs {
+    #[parameter(description = "The name of the element.", arg_type = "str")]
+    name: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "xml",
+    description = "Serializes a value to an XML fragment. \
+                   Arrays repeat the element and objects become child elements.",
+    parameters(XmlArgs),
+    parsed(XmlFilter)
+)]
+pub struct Xml;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "xml"]
+struct XmlFilter {
+    #[parameters]
+    args: XmlArgs,
+}
+
+impl Filter for XmlFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        if !is_xml_name(&args.name) {
+            return Err(invalid_argument("name", "Valid element name expected"));
+        }
+
+        let mut output = String::new();
+        write_element(&mut output, &args.name, input)?;
+        Ok(Value::scalar(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use liquid_value::liquid_value;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! failed {
+        ($a:ident, $b:expr) => {{
+            failed!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+                .unwrap_err()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_cdata_escape() {
+        assert_eq!(
+            unit!(CdataEscape, tos!("a]]>b<c")),
+            tos!("a]]]]><![CDATA[>b<c")
+        );
+    }
+
+    #[test]
+    fn unit_xml_scalar() {
+        assert_eq!(
+            unit!(Xml, tos!("a & b"), tos!("title")),
+            tos!("<title>a &amp; b</title>")
+        );
+        assert_eq!(unit!(Xml, Value::Nil, tos!("title")), tos!("<title/>"));
+    }
+
+    #[test]
+    fn unit_xml_nested() {
+        let input = liquid_value!([
+            {"loc": "https://example.com/", "priority": 1.0},
+            {"loc": "https://example.com/about?a=1&b=2"},
+        ]);
+        assert_eq!(
+            unit!(Xml, input, tos!("url")),
+            tos!(concat!(
+                "<url><loc>https://example.com/</loc><priority>1</priority></url>",
+                "<url><loc>https://example.com/about?a=1&amp;b=2</loc></url>"
+            ))
+        );
+    }
+
+    #[test]
+    fn unit_xml_invalid_name() {
+        failed!(Xml, tos!("a"), tos!("1st"));
+        failed!(Xml, liquid_value!({"not valid": 1}), tos!("root"));
+        failed!(Xml, tos!("a"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Helpers for writing filters that convert values and report errors the way the standard
+//! filters do.
+//!
+//! Conversions follow the `Coercion` of the environment the template is rendered in.
+//!
+//! ```rust
+//! use liquid::filters::helpers::{argument_in_range, input_integer};
+//! use liquid::interpreter::Context;
+//! use liquid::value::Value;
+//!
+//! let context = Context::new();
+//! assert_eq!(input_integer(&Value::scalar(3), &context).unwrap(), 3);
+//! assert_eq!(input_integer(&Value::scalar("3"), &context).unwrap(), 3);
+//! assert!(input_integer(&Value::scalar("three"), &context).is_err());
+//! assert!(argument_in_range("precision", 12, 0..=10).is_err());
+//! ```
+
+use std::borrow::Cow;
+use std::ops::{Bound, RangeBounds};
+
+use liquid_error::{Error, Result};
+use liquid_interpreter::{Coercion, Context, Expression};
+use liquid_value::{Array, Date, Scalar, Value};
+
+pub use liquid_value::Number;
+
+/// An error for a filter's input.
+pub fn invalid_input<S>(cause: S) -> Error
+where
+    S: Into<Cow<'static, str>>,
+{
+    Error::with_msg("Invalid input").context("cause", cause)
+}
+
+/// An error for the filter argument called `argument`.
+pub fn invalid_argument<S>(argument: S, cause: S) -> Error
+where
+    S: Into<Cow<'static, str>>,
+{
+    Error::with_msg("Invalid argument")
+        .context("argument", argument)
+        .context("cause", cause)
+}
+
+fn is_strict(context: &Context) -> bool {
+    context.environment().coercion() == Coercion::Strict
+}
+
+/// `value` as a number.  Strings of numbers are numbers too, unless coercion is strict.
+pub fn to_number(value: &Value, context: &Context) -> Option<Number> {
+    let scalar = value.as_scalar()?;
+    if is_strict(context) {
+        scalar.as_number()
+    } else {
+        scalar.to_number()
+    }
+}
+
+/// `value` as a whole number.  Strings of whole numbers are whole numbers too, unless coercion
+/// is strict.
+pub fn to_integer(value: &Value, context: &Context) -> Option<i32> {
+    match to_number(value, context)? {
+        Number::Integer(x) => Some(x),
+        Number::Float(_) => None,
+    }
+}
+
+/// `value` as a string.  Numbers, booleans and dates are strings too, unless coercion is strict.
+pub fn to_str<'v>(value: &'v Value, context: &Context) -> Option<Cow<'v, str>> {
+    let scalar = value.as_scalar()?;
+    if is_strict(context) && scalar.type_name() != "string" {
+        return None;
+    }
+    Some(scalar.to_str())
+}
+
+/// `value` as a boolean.  Every value is true except `false` and `nil`, unless coercion is
+/// strict, where only booleans and `nil` are.
+pub fn to_bool(value: &Value, context: &Context) -> Option<bool> {
+    if !is_strict(context) {
+        return Some(value.is_truthy());
+    }
+    match *value {
+        Value::Scalar(ref scalar) => scalar.to_bool(),
+        Value::Nil => Some(false),
+        _ => None,
+    }
+}
+
+/// The input as a number, see `to_number`.
+pub fn input_number(input: &Value, context: &Context) -> Result<Number> {
+    to_number(input, context).ok_or_else(|| invalid_input("Number expected"))
+}
+
+/// The input as a fractional number, see `to_number`.
+pub fn input_float(input: &Value, context: &Context) -> Result<f64> {
+    input_number(input, context).map(Number::to_float)
+}
+
+/// The input as a whole number, see `to_integer`.
+pub fn input_integer(input: &Value, context: &Context) -> Result<i32> {
+    to_integer(input, context).ok_or_else(|| invalid_input("Whole number expected"))
+}
+
+/// The input as a string, see `to_str`.
+pub fn input_str<'v>(input: &'v Value, context: &Context) -> Result<Cow<'v, str>> {
+    to_str(input, context).ok_or_else(|| invalid_input("String expected"))
+}
+
+/// The argument called `argument` as a number, see `to_number`.
+pub fn argument_number(argument: &'static str, value: &Value, context: &Context) -> Result<Number> {
+    to_number(value, context).ok_or_else(|| invalid_argument(argument, "Number expected"))
+}
+
+/// The argument called `argument` as a whole number, see `to_integer`.
+pub fn argument_integer(argument: &'static str, value: &Value, context: &Context) -> Result<i32> {
+    to_integer(value, context).ok_or_else(|| invalid_argument(argument, "Whole number expected"))
+}
+
+/// The input as an array.
+pub fn input_array(input: &Value) -> Result<&Array> {
+    input
+        .as_array()
+        .ok_or_else(|| invalid_input("Array expected"))
+}
+
+/// The input as a date, or a string of one.
+///
+/// Unlike the `date` filter, this doesn't understand `"now"` or `"today"`.
+pub fn input_date(input: &Value) -> Result<Date> {
+    input
+        .as_scalar()
+        .and_then(Scalar::to_date)
+        .ok_or_else(|| invalid_input("Date expected"))
+}
+
+/// The literal string given for the argument called `argument`, e.g. an expression a filter
+/// parses.
+pub fn literal_str(argument: &'static str, expression: Expression) -> Result<String> {
+    match expression {
+        Expression::Literal(Value::Scalar(s)) => Ok(s.into_string()),
+        _ => Err(invalid_argument(argument, "Literal string expected")),
+    }
+}
+
+/// `value`, if it is within `range`, for the argument called `argument`.
+pub fn argument_in_range<R>(argument: &'static str, value: i32, range: R) -> Result<i32>
+where
+    R: RangeBounds<i32>,
+{
+    if range.contains(&value) {
+        return Ok(value);
+    }
+
+    let start = match range.start_bound() {
+        Bound::Included(start) => Some(*start),
+        Bound::Excluded(start) => Some(start + 1),
+        Bound::Unbounded => None,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => Some(*end),
+        Bound::Excluded(end) => Some(end - 1),
+        Bound::Unbounded => None,
+    };
+    let cause = match (start, end) {
+        (Some(start), Some(end)) => format!("Whole number from {} to {} expected", start, end),
+        (Some(start), None) => format!("Whole number of at least {} expected", start),
+        (None, Some(end)) => format!("Whole number of at most {} expected", end),
+        (None, None) => unreachable!("every number is in an unbounded range"),
+    };
+    Err(invalid_argument(argument.into(), Cow::Owned(cause)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use liquid_interpreter::{ContextBuilder, Environment};
+
+    fn strict() -> Context<'static> {
+        ContextBuilder::new()
+            .set_environment(Environment::new().with_coercion(Coercion::Strict))
+            .build()
+    }
+
+    #[test]
+    fn inputs() {
+        let context = Context::new();
+        assert_eq!(input_integer(&Value::scalar("42"), &context).unwrap(), 42);
+        assert!(input_integer(&Value::scalar(4.2), &context).is_err());
+        assert_eq!(input_float(&Value::scalar(4), &context).unwrap(), 4.0);
+        assert!(input_number(&Value::Nil, &context).is_err());
+        assert_eq!(input_str(&Value::scalar(4), &context).unwrap(), "4");
+        assert!(input_str(&Value::array(vec![]), &context).is_err());
+        assert!(input_array(&Value::array(vec![])).unwrap().is_empty());
+        assert!(input_array(&Value::scalar("a")).is_err());
+        assert!(input_date(&Value::scalar("2016-06-13 02:30:00 +0300")).is_ok());
+        assert!(input_date(&Value::scalar("June")).is_err());
+    }
+
+    #[test]
+    fn lax_coercion() {
+        let context = Context::new();
+        assert_eq!(
+            to_number(&Value::scalar("5"), &context),
+            Some(Number::Integer(5))
+        );
+        assert_eq!(
+            to_number(&Value::scalar("5.5"), &context),
+            Some(Number::Float(5.5))
+        );
+        assert_eq!(to_str(&Value::scalar(5), &context).unwrap(), "5");
+        assert_eq!(to_bool(&Value::scalar(0), &context), Some(true));
+        assert_eq!(to_bool(&Value::Nil, &context), Some(false));
+    }
+
+    #[test]
+    fn strict_coercion() {
+        let context = strict();
+        assert_eq!(to_number(&Value::scalar("5"), &context), None);
+        assert_eq!(
+            to_number(&Value::scalar(5), &context),
+            Some(Number::Integer(5))
+        );
+        assert_eq!(to_integer(&Value::scalar(5.0), &context), None);
+        assert_eq!(to_str(&Value::scalar(5), &context), None);
+        assert_eq!(to_str(&Value::scalar("5"), &context).unwrap(), "5");
+        assert_eq!(to_bool(&Value::scalar(0), &context), None);
+        assert_eq!(to_bool(&Value::scalar(true), &context), Some(true));
+        assert_eq!(to_bool(&Value::Nil, &context), Some(false));
+        assert!(argument_number("operand", &Value::scalar("1"), &context).is_err());
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(argument_in_range("n", 3, 1..=3).unwrap(), 3);
+        assert_eq!(argument_in_range("n", 3, 1..).unwrap(), 3);
+
+        let error = argument_in_range("n", 3, 1..3).unwrap_err().to_string();
+        assert!(
+            error.contains("Whole number from 1 to 2 expected"),
+            "{}",
+            error
+        );
+        let error = argument_in_range("n", 0, 1..).unwrap_err().to_string();
+        assert!(
+            error.contains("Whole number of at least 1 expected"),
+            "{}",
+            error
+        );
+        let error = argument_in_range("n", 11, ..=10).unwrap_err().to_string();
+        assert!(
+            error.contains("Whole number of at most 10 expected"),
+            "{}",
+            error
+        );
+    }
+}
@@ -1,11 +1,43 @@
-use filters::invalid_input;
+use filters::{invalid_argument, invalid_input};
 use liquid_compiler::{Filter, FilterParameters};
 use liquid_derive::*;
 use liquid_error::Result;
 use liquid_interpreter::Context;
 use liquid_interpreter::Expression;
 use liquid_value::Value;
+use std::cell::Cell;
 use std::fmt::Write;
+use std::time;
+
+/// The elements of `input`, treating `nil` as an empty array so arrays can be built up from an
+/// unassigned variable.
+fn array_or_nil(input: &Value) -> Result<Vec<Value>> {
+    match *input {
+        Value::Array(ref array) => Ok(array.clone().into_vec()),
+        Value::Nil => Ok(Vec::new()),
+        _ => Err(invalid_input("Array expected")),
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct CountArgs {
+    #[parameter(
+        description = "The number of elements to remove. Defaults to 1.",
+        arg_type = "integer"
+    )]
+    count: Option<Expression>,
+}
+
+impl CountArgs {
+    fn count(&self, context: &Context) -> Result<usize> {
+        let args = self.evaluate(context)?;
+        let count = args.count.unwrap_or(1);
+        if count < 0 {
+            return Err(invalid_argument("count", "Positive number expected"));
+        }
+        Ok(count as usize)
+    }
+}
 
 #[derive(Debug, FilterParameters)]
 struct PushArgs {
@@ -34,10 +66,7 @@ impl Filter for PushFilter {
         let args = self.args.evaluate(context)?;
 
         let element = args.element.clone();
-        let mut array = input
-            .as_array()
-            .ok_or_else(|| invalid_input("Array expected"))?
-            .clone();
+        let mut array = array_or_nil(input)?;
         array.push(element);
 
         Ok(Value::array(array))
@@ -47,22 +76,25 @@ impl Filter for PushFilter {
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
     name = "pop",
-    description = "Removes the last element of an array.",
+    description = "Removes the last elements of an array.",
+    parameters(CountArgs),
     parsed(PopFilter)
 )]
 pub struct Pop;
 
-#[derive(Debug, Default, Display_filter)]
+#[derive(Debug, FromFilterParameters, Display_filter)]
 #[name = "pop"]
-struct PopFilter;
+struct PopFilter {
+    #[parameters]
+    args: CountArgs,
+}
 
 impl Filter for PopFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        let mut array = input
-            .as_array()
-            .ok_or_else(|| invalid_input("Array expected"))?
-            .clone();
-        array.pop();
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let count = self.args.count(context)?;
+        let mut array = array_or_nil(input)?;
+        let len = array.len().saturating_sub(count);
+        array.truncate(len);
 
         Ok(Value::array(array))
     }
@@ -95,10 +127,7 @@ impl Filter for UnshiftFilter {
         let args = self.args.evaluate(context)?;
 
         let element = args.element.clone();
-        let mut array = input
-            .as_array()
-            .ok_or_else(|| invalid_input("Array expected"))?
-            .clone();
+        let mut array = array_or_nil(input)?;
         array.insert(0, element);
 
         Ok(Value::array(array))
@@ -108,27 +137,141 @@ impl Filter for UnshiftFilter {
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
     name = "shift",
-    description = "Removes the first element of an array.",
+    description = "Removes the first elements of an array.",
+    parameters(CountArgs),
     parsed(ShiftFilter)
 )]
 pub struct Shift;
 
-#[derive(Debug, Default, Display_filter)]
+#[derive(Debug, FromFilterParameters, Display_filter)]
 #[name = "shift"]
-struct ShiftFilter;
+struct ShiftFilter {
+    #[parameters]
+    args: CountArgs,
+}
 
 impl Filter for ShiftFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        let mut array = input
-            .as_array()
-            .ok_or_else(|| invalid_input("Array expected"))?
-            .clone();
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let count = self.args.count(context)?;
+        let mut array = array_or_nil(input)?;
+        let count = count.min(array.len());
+        array.drain(..count);
+
+        Ok(Value::array(array))
+    }
+}
 
-        if !array.is_empty() {
-            array.remove(0);
+/// The random number generator of `sample`.
+///
+/// Seed it through the `Context` registers for repeatable renders:
+///
+/// ```rust
+/// use liquid::filters::jekyll::Random;
+/// use liquid::interpreter::ContextBuilder;
+///
+/// let context = ContextBuilder::new().set_register(Random::seeded(42)).build();
+/// ```
+///
+/// Without one, every render is seeded from the clock.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: Cell<u64>,
+}
+
+impl Random {
+    /// A generator that always produces the same numbers for the same `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            state: Cell::new(seed),
         }
+    }
 
-        Ok(Value::array(array))
+    // splitmix64
+    fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    fn below(&self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        let seed = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::seeded(seed)
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct SampleArgs {
+    #[parameter(
+        description = "The number of elements to pick. Without it, a single element is returned.",
+        arg_type = "integer"
+    )]
+    count: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "sample",
+    description = "Picks random elements of an array.",
+    parameters(SampleArgs),
+    parsed(SampleFilter)
+)]
+pub struct Sample;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "sample"]
+struct SampleFilter {
+    #[parameters]
+    args: SampleArgs,
+}
+
+impl Filter for SampleFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let array = match input.as_array() {
+            Some(array) => array,
+            // As in Jekyll, anything else cannot be sampled and is returned as is.
+            None => return Ok(input.clone()),
+        };
+
+        let fallback;
+        let random = match context.get_register::<Random>() {
+            Some(random) => random,
+            None => {
+                fallback = Random::default();
+                &fallback
+            }
+        };
+
+        match args.count {
+            None if array.is_empty() => Ok(Value::Nil),
+            None => Ok(array[random.below(array.len())].clone()),
+            Some(count) if count < 0 => Err(invalid_argument("count", "Positive number expected")),
+            Some(count) => {
+                // A partial Fisher-Yates shuffle, picking each element at most once.
+                let count = (count as usize).min(array.len());
+                let mut picked = array.clone();
+                for i in 0..count {
+                    let j = i + random.below(picked.len() - i);
+                    picked.swap(i, j);
+                }
+                picked.truncate(count);
+                Ok(Value::array(picked))
+            }
+        }
     }
 }
 
@@ -163,27 +306,26 @@ impl Filter for ArrayToSentenceStringFilter {
 
         let connector = args.connector.unwrap_or("and".into());
 
-        let mut array = input
+        let array = input
             .as_array()
-            .ok_or_else(|| invalid_input("Array expected"))?
-            .iter();
-
-        let mut sentence = array
-            .next()
-            .map(|v| v.to_str().into_owned())
-            .unwrap_or_else(|| "".to_string());
-
-        let last = array.next_back();
-
-        for value in array {
-            write!(sentence, ", {}", value.render())
-                .expect("It should be safe to write to a string.");
-        }
-
-        if let Some(last) = last {
-            write!(sentence, ", {} {}", connector, last.render())
-                .expect("It should be safe to write to a string.");
-        }
+            .ok_or_else(|| invalid_input("Array expected"))?;
+
+        // As in Jekyll, two elements are joined without a comma: "a and b".
+        let sentence = match array.as_slice() {
+            [] => String::new(),
+            [only] => only.to_str().into_owned(),
+            [first, second] => format!("{} {} {}", first.render(), connector, second.render()),
+            [init @ .., last] => {
+                let mut sentence = String::new();
+                for value in init {
+                    write!(sentence, "{}, ", value.render())
+                        .expect("It should be safe to write to a string.");
+                }
+                write!(sentence, "{} {}", connector, last.render())
+                    .expect("It should be safe to write to a string.");
+                sentence
+            }
+        };
 
         Ok(Value::scalar(sentence))
     }
@@ -212,9 +354,9 @@ mod tests {
 
     #[test]
     fn unit_push() {
-        let input = Value::Array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
+        let input = Value::array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
         let unit_result = unit!(Push, input, Value::scalar("Spokane"));
-        let desired_result = Value::Array(vec![
+        let desired_result = Value::array(vec![
             Value::scalar("Seattle"),
             Value::scalar("Tacoma"),
             Value::scalar("Spokane"),
@@ -224,25 +366,45 @@ mod tests {
 
     #[test]
     fn unit_pop() {
-        let input = Value::Array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
+        let input = Value::array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
         let unit_result = unit!(Pop, input);
-        let desired_result = Value::Array(vec![Value::scalar("Seattle")]);
+        let desired_result = Value::array(vec![Value::scalar("Seattle")]);
         assert_eq!(unit_result, desired_result);
     }
 
     #[test]
     fn unit_pop_empty() {
-        let input = Value::Array(vec![]);
+        let input = Value::array(vec![]);
         let unit_result = unit!(Pop, input);
-        let desired_result = Value::Array(vec![]);
+        let desired_result = Value::array(vec![]);
+        assert_eq!(unit_result, desired_result);
+    }
+
+    #[test]
+    fn unit_pop_count() {
+        let input = Value::array(vec![
+            Value::scalar("Seattle"),
+            Value::scalar("Tacoma"),
+            Value::scalar("Spokane"),
+        ]);
+        let unit_result = unit!(Pop, input, Value::scalar(2));
+        let desired_result = Value::array(vec![Value::scalar("Seattle")]);
+        assert_eq!(unit_result, desired_result);
+        assert_eq!(unit!(Pop, input, Value::scalar(5)), Value::array(vec![]));
+    }
+
+    #[test]
+    fn unit_push_nil() {
+        let unit_result = unit!(Push, Value::Nil, Value::scalar("Spokane"));
+        let desired_result = Value::array(vec![Value::scalar("Spokane")]);
         assert_eq!(unit_result, desired_result);
     }
 
     #[test]
     fn unit_unshift() {
-        let input = Value::Array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
+        let input = Value::array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
         let unit_result = unit!(Unshift, input, Value::scalar("Olympia"));
-        let desired_result = Value::Array(vec![
+        let desired_result = Value::array(vec![
             Value::scalar("Olympia"),
             Value::scalar("Seattle"),
             Value::scalar("Tacoma"),
@@ -252,23 +414,88 @@ mod tests {
 
     #[test]
     fn unit_shift() {
-        let input = Value::Array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
+        let input = Value::array(vec![Value::scalar("Seattle"), Value::scalar("Tacoma")]);
         let unit_result = unit!(Shift, input);
-        let desired_result = Value::Array(vec![Value::scalar("Tacoma")]);
+        let desired_result = Value::array(vec![Value::scalar("Tacoma")]);
         assert_eq!(unit_result, desired_result);
     }
 
     #[test]
     fn unit_shift_empty() {
-        let input = Value::Array(vec![]);
+        let input = Value::array(vec![]);
         let unit_result = unit!(Shift, input);
-        let desired_result = Value::Array(vec![]);
+        let desired_result = Value::array(vec![]);
         assert_eq!(unit_result, desired_result);
     }
 
+    #[test]
+    fn unit_shift_count() {
+        let input = Value::array(vec![
+            Value::scalar("Seattle"),
+            Value::scalar("Tacoma"),
+            Value::scalar("Spokane"),
+        ]);
+        let unit_result = unit!(Shift, input, Value::scalar(2));
+        let desired_result = Value::array(vec![Value::scalar("Spokane")]);
+        assert_eq!(unit_result, desired_result);
+        assert_eq!(unit!(Shift, input, Value::scalar(5)), Value::array(vec![]));
+    }
+
+    macro_rules! seeded {
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::ContextBuilder::new()
+                .set_register(Random::seeded(7))
+                .build();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    #[test]
+    fn unit_sample() {
+        let input = Value::Array((1..=10).map(Value::scalar).collect());
+        let picked = unit!(Sample, input);
+        assert!(input.as_array().unwrap().contains(&picked));
+        assert_eq!(unit!(Sample, Value::array(vec![])), Value::Nil);
+        assert_eq!(unit!(Sample, Value::scalar("a")), Value::scalar("a"));
+    }
+
+    #[test]
+    fn unit_sample_count() {
+        let input = Value::Array((1..=10).map(Value::scalar).collect());
+        let picked = unit!(Sample, input, Value::scalar(4));
+        let mut picked: Vec<_> = picked
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_scalar().unwrap().to_integer().unwrap())
+            .collect();
+        picked.sort_unstable();
+        picked.dedup();
+        assert_eq!(picked.len(), 4);
+        assert!(picked.iter().all(|i| (1..=10).contains(i)));
+
+        let all = unit!(Sample, input, Value::scalar(20));
+        assert_eq!(all.as_array().unwrap().len(), 10);
+    }
+
+    #[test]
+    fn unit_sample_seeded() {
+        let input = Value::Array((1..=100).map(Value::scalar).collect());
+        assert_eq!(
+            seeded!(Sample, input, Value::scalar(5)),
+            seeded!(Sample, input, Value::scalar(5))
+        );
+    }
+
     #[test]
     fn unit_array_to_sentence_string() {
-        let input = Value::Array(vec![
+        let input = Value::array(vec![
             Value::scalar("foo"),
             Value::scalar("bar"),
             Value::scalar("baz"),
@@ -280,15 +507,15 @@ mod tests {
 
     #[test]
     fn unit_array_to_sentence_string_two_elements() {
-        let input = Value::Array(vec![Value::scalar("foo"), Value::scalar("bar")]);
+        let input = Value::array(vec![Value::scalar("foo"), Value::scalar("bar")]);
         let unit_result = unit!(ArrayToSentenceString, input);
-        let desired_result = Value::scalar("foo, and bar");
+        let desired_result = Value::scalar("foo and bar");
         assert_eq!(unit_result, desired_result);
     }
 
     #[test]
     fn unit_array_to_sentence_string_one_element() {
-        let input = Value::Array(vec![Value::scalar("foo")]);
+        let input = Value::array(vec![Value::scalar("foo")]);
         let unit_result = unit!(ArrayToSentenceString, input);
         let desired_result = Value::scalar("foo");
         assert_eq!(unit_result, desired_result);
@@ -296,7 +523,7 @@ mod tests {
 
     #[test]
     fn unit_array_to_sentence_string_no_elements() {
-        let input = Value::Array(vec![]);
+        let input = Value::array(vec![]);
         let unit_result = unit!(ArrayToSentenceString, input);
         let desired_result = Value::scalar("");
         assert_eq!(unit_result, desired_result);
@@ -304,7 +531,7 @@ mod tests {
 
     #[test]
     fn unit_array_to_sentence_string_custom_connector() {
-        let input = Value::Array(vec![
+        let input = Value::array(vec![
             Value::scalar("foo"),
             Value::scalar("bar"),
             Value::scalar("baz"),
@@ -312,5 +539,10 @@ mod tests {
         let unit_result = unit!(ArrayToSentenceString, input, Value::scalar("or"));
         let desired_result = Value::scalar("foo, bar, or baz");
         assert_eq!(unit_result, desired_result);
+
+        let input = Value::array(vec![Value::scalar("foo"), Value::scalar(1)]);
+        let unit_result = unit!(ArrayToSentenceString, input, Value::scalar("or"));
+        let desired_result = Value::scalar("foo or 1");
+        assert_eq!(unit_result, desired_result);
     }
 }
@@ -0,0 +1,116 @@
+use std::fmt::Write;
+
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::Value;
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~".contains(&byte)
+}
+
+fn is_reserved(byte: u8) -> bool {
+    b":/?#[]@!$&'()*+,;=".contains(&byte)
+}
+
+fn percent_encode(output: &mut String, byte: u8) {
+    write!(output, "%{:02X}", byte).expect("writing to a String");
+}
+
+/// Escapes `input` for a query string parameter, like Ruby's `CGI.escape`.
+fn cgi_escape(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for &byte in input.as_bytes() {
+        match byte {
+            b' ' => result.push('+'),
+            byte if is_unreserved(byte) => result.push(byte as char),
+            byte => percent_encode(&mut result, byte),
+        }
+    }
+    result
+}
+
+/// Escapes `input` for a URI, keeping the characters with a meaning in URIs and what is already
+/// percent-encoded.
+fn uri_escape(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut result = String::with_capacity(input.len());
+    for (i, &byte) in bytes.iter().enumerate() {
+        let encoded = byte == b'%'
+            && bytes.len() > i + 2
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit();
+        if encoded || is_unreserved(byte) || is_reserved(byte) {
+            result.push(byte as char);
+        } else {
+            percent_encode(&mut result, byte);
+        }
+    }
+    result
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "cgi_escape",
+    description = "Escapes a string for use in a URL query string, with spaces as `+`.",
+    parsed(CgiEscapeFilter)
+)]
+pub struct CgiEscape;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "cgi_escape"]
+struct CgiEscapeFilter;
+
+impl Filter for CgiEscapeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(cgi_escape(&input.to_str())))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "uri_escape",
+    description = "Percent-encodes the characters of a string that may not appear in a URI, \
+                   keeping reserved characters like `/` and `?`.",
+    parsed(UriEscapeFilter)
+)]
+pub struct UriEscape;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "uri_escape"]
+struct UriEscapeFilter;
+
+impl Filter for UriEscapeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(uri_escape(&input.to_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_cgi_escape() {
+        assert_eq!(cgi_escape("my things"), "my+things");
+        assert_eq!(cgi_escape("hey!"), "hey%21");
+        assert_eq!(cgi_escape("foo, bar; baz?"), "foo%2C+bar%3B+baz%3F");
+        assert_eq!(cgi_escape("a-b_c.d~e"), "a-b_c.d~e");
+        assert_eq!(cgi_escape("日本"), "%E6%97%A5%E6%9C%AC");
+    }
+
+    #[test]
+    fn test_uri_escape() {
+        assert_eq!(uri_escape("my things"), "my%20things");
+        assert_eq!(
+            uri_escape("foo!*'();:@&=+$,/?#[]bar"),
+            "foo!*'();:@&=+$,/?#[]bar"
+        );
+        assert_eq!(uri_escape("foo bar%25"), "foo%20bar%25");
+        assert_eq!(uri_escape("100%"), "100%25");
+        assert_eq!(uri_escape("foo, bar \\baz?"), "foo,%20bar%20%5Cbaz?");
+        assert_eq!(uri_escape("/日本/"), "/%E6%97%A5%E6%9C%AC/");
+    }
+}
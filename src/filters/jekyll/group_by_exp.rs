@@ -0,0 +1,182 @@
+use std::fmt;
+
+use filters::helpers::literal_str;
+use filters::invalid_input;
+use filters::std::group_by;
+use liquid_compiler::{
+    Filter, FilterArguments, FilterChain, FilterParameters, FilterReflection, Language,
+    ParseFilter, Tag,
+};
+use liquid_derive::*;
+use liquid_error::{Result, ResultLiquidExt};
+use liquid_interpreter::{Context, ContextBuilder, Expression};
+use liquid_value::Value;
+
+#[derive(Debug, FilterParameters)]
+struct GroupByExpArgs {
+    #[parameter(
+        description = "The name each element is bound to in the expression.",
+        arg_type = "str"
+    )]
+    variable: Expression,
+    #[parameter(
+        description = "The expression, with filters, computing the group of an element.",
+        arg_type = "str"
+    )]
+    expression: Expression,
+}
+
+fn parse_filter_chain_str(text: &str, language: &Language) -> Result<FilterChain> {
+    let source = format!("{{% group_by_exp {} %}}", text);
+    let tag = Tag::new(&source)?;
+    let mut tokens = tag.into_tokens();
+    let chain = tokens
+        .expect_next("Expression expected.")?
+        .expect_filter_chain(language)
+        .into_result()?;
+    tokens.expect_nothing()?;
+    Ok(chain)
+}
+
+#[derive(Clone, FilterReflection)]
+#[filter(
+    name = "group_by_exp",
+    description = "Group the elements of an array by the result of an expression, \
+                   e.g. `{{ posts | group_by_exp: \"post\", \"post.date | date: '%Y'\" }}`.",
+    parameters(GroupByExpArgs)
+)]
+pub struct GroupByExp;
+
+impl ParseFilter for GroupByExp {
+    // Without the language, the expression cannot use filters.
+    fn parse(&self, arguments: FilterArguments) -> Result<Box<dyn Filter>> {
+        self.parse_with_language(arguments, &Language::empty())
+    }
+
+    fn parse_with_language(
+        &self,
+        arguments: FilterArguments,
+        language: &Language,
+    ) -> Result<Box<dyn Filter>> {
+        let args = GroupByExpArgs::from_args(arguments)?;
+        let variable = literal_str("variable", args.variable)?;
+        let expression = literal_str("expression", args.expression)?;
+        let chain = parse_filter_chain_str(&expression, language)
+            .context_key("expression")
+            .value_with(|| expression.clone().into())?;
+
+        Ok(Box::new(GroupByExpFilter { variable, chain }))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct GroupByExpFilter {
+    variable: String,
+    chain: FilterChain,
+}
+
+impl fmt::Display for GroupByExpFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "group_by_exp: \"{}\", \"{}\"", self.variable, self.chain)
+    }
+}
+
+impl Filter for GroupByExpFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let items: Vec<&Value> = match *input {
+            Value::Array(ref array) => array.iter().collect(),
+            Value::Object(ref object) => object.values().collect(),
+            Value::Nil => Vec::new(),
+            _ => return Err(invalid_input("Array expected")),
+        };
+
+        // Evaluate the expression in a scope of its own, seeing every variable of the template.
+        let mut scope = ContextBuilder::new()
+            .set_globals(context.stack())
+            .set_environment(context.environment().clone())
+            .build();
+
+        group_by(items.into_iter(), |item| {
+            scope.run_in_scope(|scope| {
+                scope.stack_mut().set(self.variable.clone(), item.clone());
+                self.chain.evaluate(scope)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn post(title: &str, year: i32) -> Value {
+        let mut post = ::liquid::value::Object::new();
+        post.insert("title".into(), tos!(title));
+        post.insert("year".into(), Value::scalar(year));
+        Value::Object(post)
+    }
+
+    fn group(name: Value, items: Vec<Value>) -> Value {
+        let mut group = ::liquid::value::Object::new();
+        group.insert("name".into(), name);
+        group.insert("size".into(), Value::scalar(items.len() as i32));
+        group.insert("items".into(), Value::array(items));
+        Value::Object(group)
+    }
+
+    #[test]
+    fn unit_group_by_exp() {
+        let input = Value::array(vec![post("a", 2018), post("b", 2019), post("c", 2018)]);
+        assert_eq!(
+            unit!(GroupByExp, input, tos!("post"), tos!("post.year")).unwrap(),
+            Value::array(vec![
+                group(Value::scalar(2018), vec![post("a", 2018), post("c", 2018)]),
+                group(Value::scalar(2019), vec![post("b", 2019)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn unit_group_by_exp_nil() {
+        assert_eq!(
+            unit!(GroupByExp, Value::Nil, tos!("post"), tos!("post.year")).unwrap(),
+            Value::array(vec![])
+        );
+    }
+
+    #[test]
+    fn unit_group_by_exp_invalid() {
+        let input = Value::array(vec![]);
+        assert!(unit!(GroupByExp, input, tos!("post")).is_err());
+        assert!(unit!(GroupByExp, input, tos!("post"), tos!("post.year | unknown")).is_err());
+        assert!(unit!(GroupByExp, input, tos!("post"), tos!("post.year post")).is_err());
+        assert!(unit!(GroupByExp, tos!("text"), tos!("post"), tos!("post")).is_err());
+    }
+}
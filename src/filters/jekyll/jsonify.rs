@@ -0,0 +1,125 @@
+use filters::json::{to_json, JsonStyle};
+use liquid_compiler::{Filter, FilterParameters};
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_interpreter::Expression;
+use liquid_value::Value;
+
+#[derive(Debug, FilterParameters)]
+struct JsonifyArgs {
+    #[parameter(
+        description = "Whether to indent the output, one element per line. Defaults to false.",
+        arg_type = "bool",
+        mode = "keyword"
+    )]
+    pretty: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "jsonify",
+    description = "Convert a value into JSON, safe to embed in a `<script>` element.",
+    parameters(JsonifyArgs),
+    parsed(JsonifyFilter)
+)]
+pub struct Jsonify;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "jsonify"]
+struct JsonifyFilter {
+    #[parameters]
+    args: JsonifyArgs,
+}
+
+impl Filter for JsonifyFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let style = JsonStyle {
+            pretty: args.pretty.unwrap_or(false),
+            script_safe: true,
+            ..JsonStyle::default()
+        };
+        Ok(Value::scalar(to_json(input, style)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($k:ident : $v:expr),*) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(vec![$((stringify!($k), ::liquid::interpreter::Expression::Literal($v))),*].into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn data() -> Value {
+        let mut object = ::liquid::value::Object::new();
+        object.insert(
+            "tags".into(),
+            Value::array(vec![tos!("a"), Value::scalar(2), Value::Nil]),
+        );
+        object.insert("empty".into(), Value::array(vec![]));
+        Value::Object(object)
+    }
+
+    #[test]
+    fn unit_jsonify() {
+        assert_eq!(unit!(Jsonify, Value::Nil), tos!("null"));
+        assert_eq!(unit!(Jsonify, Value::scalar(2.5)), tos!("2.5"));
+        let json = unit!(Jsonify, data()).to_str().into_owned();
+        assert!(
+            json == r#"{"tags":["a",2,null],"empty":[]}"#
+                || json == r#"{"empty":[],"tags":["a",2,null]}"#,
+            "{}",
+            json
+        );
+    }
+
+    #[test]
+    fn unit_jsonify_escapes_script() {
+        assert_eq!(
+            unit!(Jsonify, tos!("</script><b>\"&\"\n\u{2028}")),
+            tos!(r#""\u003c/script\u003e\u003cb\u003e\"\u0026\"\n\u2028""#)
+        );
+    }
+
+    #[test]
+    fn unit_jsonify_pretty() {
+        let mut object = ::liquid::value::Object::new();
+        object.insert(
+            "tags".into(),
+            Value::array(vec![tos!("a"), Value::scalar(2)]),
+        );
+        object.insert("empty".into(), Value::Object(Default::default()));
+        let mut value = ::liquid::value::Object::new();
+        value.insert("page".into(), Value::Object(object));
+        let json = unit!(Jsonify, Value::Object(value), pretty: Value::scalar(true))
+            .to_str()
+            .into_owned();
+        assert!(
+            json == "{\n  \"page\": {\n    \"tags\": [\n      \"a\",\n      2\n    ],\n    \"empty\": {}\n  }\n}"
+                || json == "{\n  \"page\": {\n    \"empty\": {},\n    \"tags\": [\n      \"a\",\n      2\n    ]\n  }\n}",
+            "{}",
+            json
+        );
+    }
+}
@@ -1,5 +1,18 @@
 mod array;
-mod slugify;
+mod escape;
+mod group_by_exp;
+mod jsonify;
+mod smartify;
+mod url;
+mod where_exp;
 
-pub use self::array::{ArrayToSentenceString, Pop, Push, Shift, Unshift};
-pub use self::slugify::Slugify;
+pub use self::array::{ArrayToSentenceString, Pop, Push, Random, Sample, Shift, Unshift};
+pub use self::escape::{CgiEscape, UriEscape};
+pub use self::group_by_exp::GroupByExp;
+pub use self::jsonify::Jsonify;
+pub use self::smartify::Smartify;
+pub use self::url::{AbsoluteUrl, RelativeUrl};
+pub use self::where_exp::WhereExp;
+pub use filters::escape::XmlEscape;
+// `slugify` is a standard filter now, it is kept here for compatibility.
+pub use filters::std::Slugify;
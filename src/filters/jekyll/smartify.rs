@@ -0,0 +1,108 @@
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::Value;
+
+fn opens_quote(previous: Option<char>) -> bool {
+    match previous {
+        None => true,
+        Some(c) => c.is_whitespace() || matches!(c, '(' | '[' | '{' | '\u{2014}' | '\u{2013}'),
+    }
+}
+
+fn smartify(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut previous = None;
+    let mut rest = input;
+    while let Some(c) = rest.chars().next() {
+        let (replacement, len) = if rest.starts_with("---") {
+            ('\u{2014}', 3)
+        } else if rest.starts_with("--") {
+            ('\u{2013}', 2)
+        } else if rest.starts_with("...") {
+            ('\u{2026}', 3)
+        } else {
+            let replacement = match c {
+                '"' if opens_quote(previous) => '\u{201c}',
+                '"' => '\u{201d}',
+                '\'' if opens_quote(previous) => '\u{2018}',
+                '\'' => '\u{2019}',
+                c => c,
+            };
+            (replacement, c.len_utf8())
+        };
+        result.push(replacement);
+        previous = Some(replacement);
+        rest = &rest[len..];
+    }
+    result
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "smartify",
+    description = "Converts straight quotes, `--`, `---` and `...` into their typographic equivalents.",
+    parsed(SmartifyFilter)
+)]
+pub struct Smartify;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "smartify"]
+struct SmartifyFilter;
+
+impl Filter for SmartifyFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(smartify(&input.to_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments {
+                positional,
+                keyword,
+            };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_smartify_quotes() {
+        assert_eq!(
+            unit!(Smartify, tos!(r#""Hello," she said. It's 'fine' ("really")."#)),
+            tos!("\u{201c}Hello,\u{201d} she said. It\u{2019}s \u{2018}fine\u{2019} (\u{201c}really\u{201d}).")
+        );
+    }
+
+    #[test]
+    fn unit_smartify_dashes_and_ellipsis() {
+        assert_eq!(
+            unit!(Smartify, tos!("1--2 --- wait...")),
+            tos!("1\u{2013}2 \u{2014} wait\u{2026}")
+        );
+    }
+
+    #[test]
+    fn unit_smartify_plain() {
+        assert_eq!(unit!(Smartify, tos!("nothing here")), tos!("nothing here"));
+        assert_eq!(unit!(Smartify, Value::scalar(5)), tos!("5"));
+    }
+}
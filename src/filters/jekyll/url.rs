@@ -0,0 +1,251 @@
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::Value;
+use url::Url;
+
+fn is_absolute(input: &str) -> bool {
+    Url::parse(input).is_ok()
+}
+
+fn ensure_leading_slash(input: &str) -> String {
+    if input.is_empty() || input.starts_with('/') {
+        input.to_owned()
+    } else {
+        format!("/{}", input)
+    }
+}
+
+fn relative_url(input: &str, baseurl: Option<&str>) -> String {
+    let baseurl = baseurl.map(|b| b.trim_end_matches('/')).unwrap_or("");
+    let baseurl = ensure_leading_slash(baseurl);
+    let path = ensure_leading_slash(input);
+    match (baseurl.as_str(), path.as_str()) {
+        ("", "") => "/".to_owned(),
+        (baseurl, path) => format!("{}{}", baseurl, path),
+    }
+}
+
+/// Prepends the site's `baseurl` to the input, e.g. `{{ "app.css" | relative_url }}`.
+///
+/// ```rust
+/// let parser = liquid::ParserBuilder::with_liquid()
+///     .filter(liquid::filters::jekyll::RelativeUrl::new().with_baseurl("/blog"))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, ParseFilter, FilterReflection)]
+#[filter(
+    name = "relative_url",
+    description = "Prepends the site's `baseurl` to the input.",
+    parsed(RelativeUrlFilter),
+    stateful
+)]
+pub struct RelativeUrl {
+    baseurl: Option<String>,
+}
+
+impl RelativeUrl {
+    /// Create a `relative_url` filter for a site served at the root.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the path the site is served under, e.g. `/blog`.
+    pub fn with_baseurl<S: Into<String>>(mut self, baseurl: S) -> Self {
+        self.baseurl = Some(baseurl.into());
+        self
+    }
+}
+
+#[derive(Debug, Display_filter)]
+#[name = "relative_url"]
+struct RelativeUrlFilter {
+    site: RelativeUrl,
+}
+
+impl From<RelativeUrl> for RelativeUrlFilter {
+    fn from(site: RelativeUrl) -> Self {
+        Self { site }
+    }
+}
+
+impl Filter for RelativeUrlFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        if input.is_nil() {
+            return Ok(Value::Nil);
+        }
+
+        let input = input.to_str();
+        if is_absolute(&input) {
+            return Ok(Value::scalar(input.into_owned()));
+        }
+
+        Ok(Value::scalar(relative_url(
+            &input,
+            self.site.baseurl.as_deref(),
+        )))
+    }
+}
+
+/// Prepends the site's `url` and `baseurl` to the input, e.g. `{{ "about/" | absolute_url }}`.
+///
+/// ```rust
+/// let parser = liquid::ParserBuilder::with_liquid()
+///     .filter(
+///         liquid::filters::jekyll::AbsoluteUrl::new()
+///             .with_url("https://example.com")
+///             .with_baseurl("/blog"),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, ParseFilter, FilterReflection)]
+#[filter(
+    name = "absolute_url",
+    description = "Prepends the site's `url` and `baseurl` to the input.",
+    parsed(AbsoluteUrlFilter),
+    stateful
+)]
+pub struct AbsoluteUrl {
+    url: Option<String>,
+    baseurl: Option<String>,
+}
+
+impl AbsoluteUrl {
+    /// Create an `absolute_url` filter for a site without a known host, which makes URLs
+    /// relative to the root.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the site's scheme and host, e.g. `https://example.com`.
+    pub fn with_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Set the path the site is served under, e.g. `/blog`.
+    pub fn with_baseurl<S: Into<String>>(mut self, baseurl: S) -> Self {
+        self.baseurl = Some(baseurl.into());
+        self
+    }
+}
+
+#[derive(Debug, Display_filter)]
+#[name = "absolute_url"]
+struct AbsoluteUrlFilter {
+    site: AbsoluteUrl,
+}
+
+impl From<AbsoluteUrl> for AbsoluteUrlFilter {
+    fn from(site: AbsoluteUrl) -> Self {
+        Self { site }
+    }
+}
+
+impl Filter for AbsoluteUrlFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        if input.is_nil() {
+            return Ok(Value::Nil);
+        }
+
+        let input = input.to_str();
+        if is_absolute(&input) {
+            return Ok(Value::scalar(input.into_owned()));
+        }
+
+        let relative = relative_url(&input, self.site.baseurl.as_deref());
+        let url = match self.site.url {
+            Some(ref url) if !url.is_empty() => {
+                format!("{}{}", url.trim_end_matches('/'), relative)
+            }
+            _ => relative,
+        };
+        Ok(Value::scalar(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:expr, $b:expr) => {{
+            let positional = Box::new(Vec::new().into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments {
+                positional,
+                keyword,
+            };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn relative() -> RelativeUrl {
+        RelativeUrl::new().with_baseurl("/blog/")
+    }
+
+    fn absolute() -> AbsoluteUrl {
+        AbsoluteUrl::new()
+            .with_url("https://example.com/")
+            .with_baseurl("/blog/")
+    }
+
+    #[test]
+    fn unit_relative_url() {
+        assert_eq!(
+            unit!(relative(), tos!("assets/app.css")),
+            tos!("/blog/assets/app.css")
+        );
+        assert_eq!(unit!(relative(), tos!("/about/")), tos!("/blog/about/"));
+        assert_eq!(unit!(relative(), tos!("")), tos!("/blog"));
+    }
+
+    #[test]
+    fn unit_relative_url_no_baseurl() {
+        assert_eq!(unit!(RelativeUrl::new(), tos!("about")), tos!("/about"));
+        assert_eq!(unit!(RelativeUrl::new(), tos!("")), tos!("/"));
+    }
+
+    #[test]
+    fn unit_absolute_url() {
+        assert_eq!(
+            unit!(absolute(), tos!("about/")),
+            tos!("https://example.com/blog/about/")
+        );
+        assert_eq!(
+            unit!(
+                AbsoluteUrl::new().with_url("https://example.com"),
+                tos!("about/")
+            ),
+            tos!("https://example.com/about/")
+        );
+        assert_eq!(unit!(AbsoluteUrl::new(), tos!("about/")), tos!("/about/"));
+    }
+
+    #[test]
+    fn unit_url_already_absolute() {
+        let input = tos!("https://cdn.example.com/app.js");
+        assert_eq!(unit!(absolute(), input), input);
+        assert_eq!(unit!(relative(), input), input);
+    }
+
+    #[test]
+    fn unit_url_nil() {
+        assert_eq!(unit!(absolute(), Value::Nil), Value::Nil);
+        assert_eq!(unit!(relative(), Value::Nil), Value::Nil);
+    }
+}
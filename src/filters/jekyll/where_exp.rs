@@ -0,0 +1,183 @@
+use std::fmt;
+
+use filters::helpers::literal_str;
+use filters::invalid_input;
+use liquid_compiler::{
+    Filter, FilterArguments, FilterParameters, FilterReflection, Language, ParseFilter,
+};
+use liquid_derive::*;
+use liquid_error::{Result, ResultLiquidExt};
+use liquid_interpreter::{Context, ContextBuilder, Expression};
+use liquid_value::Value;
+use tags::{parse_condition_str, Condition};
+
+#[derive(Debug, FilterParameters)]
+struct WhereExpArgs {
+    #[parameter(
+        description = "The name each element is bound to in the expression.",
+        arg_type = "str"
+    )]
+    variable: Expression,
+    #[parameter(
+        description = "The condition an element must meet, as in an `if` tag.",
+        arg_type = "str"
+    )]
+    expression: Expression,
+}
+
+#[derive(Clone, FilterReflection)]
+#[filter(
+    name = "where_exp",
+    description = "Filter the elements of an array to those for which an expression is true, \
+                   e.g. `{{ items | where_exp: \"item\", \"item.price > 20\" }}`.",
+    parameters(WhereExpArgs)
+)]
+pub struct WhereExp;
+
+impl ParseFilter for WhereExp {
+    fn parse(&self, arguments: FilterArguments) -> Result<Box<dyn Filter>> {
+        self.parse_with_language(arguments, &Language::empty())
+    }
+
+    fn parse_with_language(
+        &self,
+        arguments: FilterArguments,
+        language: &Language,
+    ) -> Result<Box<dyn Filter>> {
+        let args = WhereExpArgs::from_args(arguments)?;
+        let variable = literal_str("variable", args.variable)?;
+        let expression = literal_str("expression", args.expression)?;
+        let condition = parse_condition_str(&expression, language)
+            .context_key("expression")
+            .value_with(|| expression.clone().into())?;
+
+        Ok(Box::new(WhereExpFilter {
+            variable,
+            expression,
+            condition,
+        }))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct WhereExpFilter {
+    variable: String,
+    expression: String,
+    condition: Condition,
+}
+
+impl fmt::Display for WhereExpFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "where_exp: \"{}\", \"{}\"",
+            self.variable, self.expression
+        )
+    }
+}
+
+impl Filter for WhereExpFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let items: Vec<&Value> = match *input {
+            Value::Array(ref array) => array.iter().collect(),
+            Value::Object(ref object) => object.values().collect(),
+            Value::Nil => Vec::new(),
+            _ => return Err(invalid_input("Array expected")),
+        };
+
+        // Evaluate the condition in a scope of its own, seeing every variable of the template.
+        let mut scope = ContextBuilder::new()
+            .set_globals(context.stack())
+            .set_environment(context.environment().clone())
+            .build();
+
+        let mut selected = Vec::new();
+        for item in items {
+            let keep = scope.run_in_scope(|scope| {
+                scope.stack_mut().set(self.variable.clone(), item.clone());
+                self.condition.evaluate(scope)
+            })?;
+            if keep {
+                selected.push(item.clone());
+            }
+        }
+        Ok(Value::array(selected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    fn item(price: i32, active: bool) -> Value {
+        let mut item = ::liquid::value::Object::new();
+        item.insert("price".into(), Value::scalar(price));
+        item.insert("active".into(), Value::scalar(active));
+        Value::Object(item)
+    }
+
+    #[test]
+    fn unit_where_exp() {
+        let input = Value::array(vec![item(10, true), item(30, false), item(40, true)]);
+        assert_eq!(
+            unit!(
+                WhereExp,
+                input,
+                tos!("item"),
+                tos!("item.price > 20 and item.active")
+            )
+            .unwrap(),
+            Value::array(vec![item(40, true)])
+        );
+    }
+
+    #[test]
+    fn unit_where_exp_contains() {
+        let input = Value::array(vec![tos!("apple"), tos!("banana"), tos!("cherry")]);
+        assert_eq!(
+            unit!(
+                WhereExp,
+                input,
+                tos!("fruit"),
+                tos!("fruit contains \"an\"")
+            )
+            .unwrap(),
+            Value::array(vec![tos!("banana")])
+        );
+    }
+
+    #[test]
+    fn unit_where_exp_invalid() {
+        let input = Value::array(vec![]);
+        assert!(unit!(WhereExp, input, tos!("item")).is_err());
+        assert!(unit!(WhereExp, input, tos!("item"), tos!("item >")).is_err());
+        assert!(unit!(WhereExp, tos!("text"), tos!("item"), tos!("item")).is_err());
+    }
+}
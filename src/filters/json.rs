@@ -0,0 +1,126 @@
+//! The JSON writer shared by the `json` and `jsonify` filters and the `debug` tag.
+
+use std::fmt::Write;
+
+use liquid_value::{Scalar, Value};
+
+/// How `to_json` lays out its output.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct JsonStyle {
+    /// Write object keys in alphabetical order.
+    pub(crate) sort_keys: bool,
+    /// Put each element on a line of its own, indented by two spaces per level.
+    pub(crate) pretty: bool,
+    /// Escape `<`, `>`, `&` and the JavaScript line terminators, so the output can be embedded
+    /// in a `<script>` element.
+    pub(crate) script_safe: bool,
+}
+
+struct Writer {
+    output: String,
+    style: JsonStyle,
+}
+
+impl Writer {
+    fn write_str(&mut self, s: &str) {
+        self.output.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.output.push_str("\\\""),
+                '\\' => self.output.push_str("\\\\"),
+                '\n' => self.output.push_str("\\n"),
+                '\r' => self.output.push_str("\\r"),
+                '\t' => self.output.push_str("\\t"),
+                '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if self.style.script_safe => {
+                    self.write_escaped(c);
+                }
+                c if (c as u32) < 0x20 => self.write_escaped(c),
+                c => self.output.push(c),
+            }
+        }
+        self.output.push('"');
+    }
+
+    fn write_escaped(&mut self, c: char) {
+        write!(self.output, "\\u{:04x}", c as u32).expect("writing to a String");
+    }
+
+    fn write_scalar(&mut self, scalar: &Scalar) {
+        match scalar.type_name() {
+            "whole number" | "boolean" => self.output.push_str(&scalar.to_str()),
+            "fractional number" => match scalar.as_float() {
+                Some(x) if x.is_finite() => self.output.push_str(&scalar.to_str()),
+                _ => self.output.push_str("null"),
+            },
+            _ => self.write_str(&scalar.to_str()),
+        }
+    }
+
+    /// Starts the `index`th element of a container.
+    fn separate(&mut self, index: usize, depth: usize) {
+        if index != 0 {
+            self.output.push(',');
+        }
+        if self.style.pretty {
+            self.newline(depth);
+        }
+    }
+
+    fn newline(&mut self, depth: usize) {
+        self.output.push('\n');
+        for _ in 0..depth {
+            self.output.push_str("  ");
+        }
+    }
+
+    fn write_value(&mut self, value: &Value, depth: usize) {
+        match value {
+            Value::Scalar(scalar) => self.write_scalar(scalar),
+            Value::Array(array) => {
+                self.output.push('[');
+                for (i, value) in array.iter().enumerate() {
+                    self.separate(i, depth + 1);
+                    self.write_value(value, depth + 1);
+                }
+                if self.style.pretty && !array.is_empty() {
+                    self.newline(depth);
+                }
+                self.output.push(']');
+            }
+            Value::Range(range) => self.write_value(&Value::Array(range.to_array().into()), depth),
+            Value::Lazy(array) => self.write_value(&Value::Array(array.to_array()), depth),
+            Value::Object(object) => {
+                let mut entries: Vec<_> = object.iter().collect();
+                if self.style.sort_keys {
+                    entries.sort_by(|a, b| a.0.cmp(b.0));
+                }
+                self.output.push('{');
+                let empty = entries.is_empty();
+                for (i, (key, value)) in entries.into_iter().enumerate() {
+                    self.separate(i, depth + 1);
+                    self.write_str(key);
+                    self.output
+                        .push_str(if self.style.pretty { ": " } else { ":" });
+                    self.write_value(value, depth + 1);
+                }
+                if self.style.pretty && !empty {
+                    self.newline(depth);
+                }
+                self.output.push('}');
+            }
+            Value::Nil | Value::Empty | Value::Blank => self.output.push_str("null"),
+        }
+    }
+}
+
+/// Serialize `value` as JSON.
+///
+/// Non-finite numbers become `null` and dates become strings.
+pub(crate) fn to_json(value: &Value, style: JsonStyle) -> String {
+    let mut writer = Writer {
+        output: String::new(),
+        style,
+    };
+    writer.write_value(value, 0);
+    writer.output
+}
@@ -1,6 +1,4 @@
-use liquid_error::Error;
-use std::borrow::Cow;
-
+pub mod helpers;
 pub mod std;
 
 #[cfg(feature = "jekyll-filters")]
@@ -9,18 +7,8 @@ pub mod jekyll;
 #[cfg(feature = "extra-filters")]
 pub mod extra;
 
-pub fn invalid_input<S>(cause: S) -> Error
-where
-    S: Into<Cow<'static, str>>,
-{
-    Error::with_msg("Invalid input").context("cause", cause)
-}
+#[cfg(any(feature = "extra-filters", feature = "jekyll-filters"))]
+mod escape;
+pub(crate) mod json;
 
-pub fn invalid_argument<S>(argument: S, cause: S) -> Error
-where
-    S: Into<Cow<'static, str>>,
-{
-    Error::with_msg("Invalid argument")
-        .context("argument", argument)
-        .context("cause", cause)
-}
+pub use self::helpers::{invalid_argument, invalid_input};
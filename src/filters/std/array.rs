@@ -1,11 +1,15 @@
+use filters::helpers::argument_in_range;
 use filters::{invalid_argument, invalid_input};
-use liquid_compiler::{Filter, FilterParameters};
+use liquid_compiler::{Filter, FilterParameters, Filtered};
 use liquid_derive::*;
 use liquid_error::Result;
 use liquid_interpreter::Context;
 use liquid_interpreter::Expression;
-use liquid_value::{Scalar, Value};
+use liquid_value::{Number, Object, ScalarCow, Value};
 use std::cmp;
+use std::collections::VecDeque;
+
+use super::collation::Collator;
 
 macro_rules! as_sequence {
     ($value: expr, |$c:ident| $e:expr) => {
@@ -58,12 +62,69 @@ impl Filter for JoinFilter {
     }
 }
 
-fn nil_safe_compare(a: &Value, b: &Value) -> Option<cmp::Ordering> {
+/// The kinds of values in the order `sort` puts them in: numbers, dates, strings, booleans,
+/// arrays, objects, then nil.
+fn sort_rank(value: &Value) -> u8 {
+    match value {
+        Value::Scalar(x) => match x.type_name() {
+            "whole number" | "fractional number" => 0,
+            "date" => 1,
+            "string" => 2,
+            _ => 3,
+        },
+        Value::Empty | Value::Blank => 2,
+        Value::Array(_) | Value::Range(_) | Value::Lazy(_) => 4,
+        Value::Object(_) => 5,
+        Value::Nil => 6,
+    }
+}
+
+/// The order of `sort`, a total order so that any array sorts the same way.
+///
+/// Values of different kinds go in the order of `sort_rank`, so numbers are never compared to
+/// strings of numbers.  Numbers go by value with `NaN` last, dates chronologically, strings by
+/// `compare_strings`, `false` before `true`, and arrays and objects element by element, objects
+/// by their keys in order.
+fn sort_order<S>(a: &Value, b: &Value, compare_strings: &S) -> cmp::Ordering
+where
+    S: Fn(&str, &str) -> cmp::Ordering,
+{
+    let rank = sort_rank(a).cmp(&sort_rank(b));
+    if rank != cmp::Ordering::Equal {
+        return rank;
+    }
     match (a, b) {
-        (Value::Nil, Value::Nil) => Some(cmp::Ordering::Equal),
-        (Value::Nil, _) => Some(cmp::Ordering::Greater),
-        (_, Value::Nil) => Some(cmp::Ordering::Less),
-        (a, b) => a.partial_cmp(b),
+        (Value::Scalar(x), Value::Scalar(y)) => match x.type_name() {
+            "whole number" | "fractional number" => {
+                let (x, y) = (x.to_float().unwrap_or(0.0), y.to_float().unwrap_or(0.0));
+                x.partial_cmp(&y)
+                    .unwrap_or_else(|| x.is_nan().cmp(&y.is_nan()))
+            }
+            "date" => x.to_date().cmp(&y.to_date()),
+            "string" => compare_strings(&x.to_str(), &y.to_str()),
+            _ => x.to_bool().cmp(&y.to_bool()),
+        },
+        (Value::Array(x), Value::Array(y)) => x
+            .iter()
+            .zip(y.iter())
+            .map(|(x, y)| sort_order(x, y, compare_strings))
+            .find(|ordering| *ordering != cmp::Ordering::Equal)
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Value::Object(x), Value::Object(y)) => {
+            let mut x: Vec<_> = x.iter().collect();
+            let mut y: Vec<_> = y.iter().collect();
+            x.sort_by(|a, b| a.0.cmp(b.0));
+            y.sort_by(|a, b| a.0.cmp(b.0));
+            x.iter()
+                .zip(y.iter())
+                .map(|(x, y)| {
+                    x.0.cmp(y.0)
+                        .then_with(|| sort_order(x.1, y.1, compare_strings))
+                })
+                .find(|ordering| *ordering != cmp::Ordering::Equal)
+                .unwrap_or_else(|| x.len().cmp(&y.len()))
+        }
+        (a, b) => compare_strings(&a.to_str(), &b.to_str()),
     }
 }
 
@@ -74,26 +135,78 @@ fn nil_safe_casecmp_key(value: &Value) -> Option<String> {
     }
 }
 
-fn nil_safe_casecmp(a: &Option<String>, b: &Option<String>) -> Option<cmp::Ordering> {
+fn nil_safe_casecmp<K: Ord>(a: &Option<K>, b: &Option<K>) -> cmp::Ordering {
     match (a, b) {
-        (None, None) => Some(cmp::Ordering::Equal),
-        (None, _) => Some(cmp::Ordering::Greater),
-        (_, None) => Some(cmp::Ordering::Less),
-        (a, b) => a.partial_cmp(b),
+        (None, None) => cmp::Ordering::Equal,
+        (None, _) => cmp::Ordering::Greater,
+        (_, None) => cmp::Ordering::Less,
+        (a, b) => a.cmp(b),
     }
 }
 
 #[derive(Debug, Default, FilterParameters)]
 struct PropertyArgs {
-    #[parameter(description = "The property accessed by the filter.", arg_type = "str")]
+    #[parameter(
+        description = "The property accessed by the filter, or a dotted path to a nested one.",
+        arg_type = "str"
+    )]
     property: Option<Expression>,
 }
 
+#[derive(Debug, Default, FilterParameters)]
+struct SortArgs {
+    #[parameter(description = "The property to sort by.", arg_type = "str")]
+    property: Option<Expression>,
+    #[parameter(
+        description = "The property to sort by when the previous ones are equal.",
+        arg_type = "str"
+    )]
+    second_property: Option<Expression>,
+    #[parameter(
+        description = "The property to sort by when the previous ones are equal.",
+        arg_type = "str"
+    )]
+    third_property: Option<Expression>,
+    #[parameter(
+        description = "The locale whose alphabet strings are sorted by, e.g. `sv-SE` or `liquid.locale`, \
+                       instead of by code point.",
+        arg_type = "str",
+        mode = "keyword"
+    )]
+    locale: Option<Expression>,
+}
+
+/// Compares `a` and `b` by each of `properties` in turn, using `compare`.
+fn compare_by_properties<C>(
+    a: &Value,
+    b: &Value,
+    properties: &[&str],
+    mut compare: C,
+) -> cmp::Ordering
+where
+    C: FnMut(&Value, &Value) -> cmp::Ordering,
+{
+    properties
+        .iter()
+        .map(|property| {
+            compare(
+                safe_property_getter(a, property),
+                safe_property_getter(b, property),
+            )
+        })
+        .find(|ordering| *ordering != cmp::Ordering::Equal)
+        .unwrap_or(cmp::Ordering::Equal)
+}
+
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
     name = "sort",
-    description = "Sorts items in an array. The order of the sorted array is case-sensitive.",
-    parameters(PropertyArgs),
+    description = "Sorts items in an array. The order of the sorted array is case-sensitive, \
+                   and by code point unless a `locale` is given. Numbers come first, then \
+                   dates, strings, booleans, arrays and objects, with nil last. Arrays of \
+                   objects are sorted by up to three properties, ties between elements keep \
+                   their order.",
+    parameters(SortArgs),
     parsed(SortFilter)
 )]
 pub struct Sort;
@@ -102,14 +215,25 @@ pub struct Sort;
 #[name = "sort"]
 struct SortFilter {
     #[parameters]
-    args: PropertyArgs,
+    args: SortArgs,
+}
+
+/// Resolves a property path, such as `customer.address.city`, within `value`.
+///
+/// A key matching the whole path takes precedence, so keys containing dots keep working.
+fn property_value<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if let Some(property) = value.get(&ScalarCow::new(path)) {
+        return Some(property);
+    }
+    if !path.contains('.') {
+        return None;
+    }
+    path.split('.')
+        .try_fold(value, |value, key| value.get(&ScalarCow::new(key)))
 }
 
 fn safe_property_getter<'a>(value: &'a Value, property: &str) -> &'a Value {
-    value
-        .as_object()
-        .and_then(|obj| obj.get(property))
-        .unwrap_or(&Value::Nil)
+    property_value(value, property).unwrap_or(&Value::Nil)
 }
 
 impl Filter for SortFilter {
@@ -121,18 +245,28 @@ impl Filter for SortFilter {
                 return Err(invalid_input("Array of objects expected"));
             }
 
+            let collator = args
+                .locale
+                .as_ref()
+                .map(|locale| Collator::new(locale, false));
+            let compare_strings = |a: &str, b: &str| match collator {
+                Some(collator) => collator.compare(a, b),
+                None => a.cmp(b),
+            };
+            let compare = |a: &Value, b: &Value| sort_order(a, b, &compare_strings);
+
+            // `sort_by` is stable, which the order of ties relies on.
             let mut sorted: Vec<Value> = input.cloned().collect();
-            if let Some(property) = &args.property {
-                // Using unwrap is ok since all of the elements are objects
-                sorted.sort_by(|a, b| {
-                    nil_safe_compare(
-                        safe_property_getter(a, property),
-                        safe_property_getter(b, property),
-                    )
-                    .unwrap_or(cmp::Ordering::Equal)
-                });
+            context.spend_filter_budget(sorted.len() as u64)?;
+            if args.property.is_some() {
+                let properties: Vec<&str> =
+                    [&args.property, &args.second_property, &args.third_property]
+                        .iter()
+                        .filter_map(|property| property.as_deref())
+                        .collect();
+                sorted.sort_by(|a, b| compare_by_properties(a, b, &properties, compare));
             } else {
-                sorted.sort_by(|a, b| nil_safe_compare(a, b).unwrap_or(cmp::Ordering::Equal));
+                sorted.sort_by(compare);
             }
             Ok(Value::array(sorted))
         })
@@ -142,8 +276,10 @@ impl Filter for SortFilter {
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
     name = "sort_natural",
-    description = "Sorts items in an array.",
-    parameters(PropertyArgs),
+    description = "Sorts items in an array as text, ignoring case, with nil last. Arrays of \
+                   objects are sorted by up to three properties, ties between elements keep \
+                   their order.",
+    parameters(SortArgs),
     parsed(SortNaturalFilter)
 )]
 pub struct SortNatural;
@@ -152,7 +288,7 @@ pub struct SortNatural;
 #[name = "sort_natural"]
 struct SortNaturalFilter {
     #[parameters]
-    args: PropertyArgs,
+    args: SortArgs,
 }
 
 impl Filter for SortNaturalFilter {
@@ -164,27 +300,63 @@ impl Filter for SortNaturalFilter {
                 return Err(invalid_input("Array of objects expected"));
             }
 
-            let mut sorted: Vec<_> = if let Some(property) = &args.property {
-                input
-                    .map(|v| {
-                        (
-                            nil_safe_casecmp_key(safe_property_getter(v, property)),
-                            v.clone(),
-                        )
-                    })
-                    .collect()
-            } else {
-                input
-                    .map(|v| (nil_safe_casecmp_key(v), v.clone()))
-                    .collect()
+            let properties: Vec<&str> =
+                [&args.property, &args.second_property, &args.third_property]
+                    .iter()
+                    .filter_map(|property| property.as_deref())
+                    .collect();
+            let sorted = match args.locale {
+                Some(ref locale) => {
+                    let collator = Collator::new(locale, true);
+                    sort_by_keys(input, &properties, context, |value| match value {
+                        Value::Nil => None,
+                        value => Some(collator.key(&value.to_str())),
+                    })?
+                }
+                None => sort_by_keys(input, &properties, context, nil_safe_casecmp_key)?,
             };
-            sorted.sort_by(|a, b| nil_safe_casecmp(&a.0, &b.0).unwrap_or(cmp::Ordering::Equal));
-            let result: Vec<_> = sorted.into_iter().map(|(_, v)| v).collect();
-            Ok(Value::array(result))
+            Ok(Value::array(sorted))
         })
     }
 }
 
+/// Sorts `input` by the `key` of each value, or of each of `properties` in turn, with nil last.
+fn sort_by_keys<'v, I, K, F>(
+    input: I,
+    properties: &[&str],
+    context: &Context,
+    key: F,
+) -> Result<Vec<Value>>
+where
+    I: Iterator<Item = &'v Value>,
+    K: Ord,
+    F: Fn(&Value) -> Option<K>,
+{
+    let mut sorted: Vec<_> = if properties.is_empty() {
+        input.map(|v| (vec![key(v)], v.clone())).collect()
+    } else {
+        input
+            .map(|v| {
+                let keys = properties
+                    .iter()
+                    .map(|property| key(safe_property_getter(v, property)))
+                    .collect::<Vec<_>>();
+                (keys, v.clone())
+            })
+            .collect()
+    };
+    context.spend_filter_budget(sorted.len() as u64)?;
+    // `sort_by` is stable, which the order of ties relies on.
+    sorted.sort_by(|a, b| {
+        a.0.iter()
+            .zip(b.0.iter())
+            .map(|(a, b)| nil_safe_casecmp(a, b))
+            .find(|ordering| *ordering != cmp::Ordering::Equal)
+            .unwrap_or(cmp::Ordering::Equal)
+    });
+    Ok(sorted.into_iter().map(|(_, v)| v).collect())
+}
+
 #[derive(Debug, FilterParameters)]
 struct WhereArgs {
     #[parameter(description = "The property being matched", arg_type = "str")]
@@ -200,7 +372,8 @@ struct WhereArgs {
 #[filter(
     name = "where",
     description = "Filter the elements of an array to those with a certain property value. \
-                   By default the target is any truthy value.",
+                   By default the target is any truthy value. \
+                   An array of strings or numbers is filtered to the elements equal to the property.",
     parameters(WhereArgs),
     parsed(WhereFilter)
 )]
@@ -220,6 +393,19 @@ impl Filter for WhereFilter {
         let target_value: Option<&Value> = args.target_value;
 
         match &input {
+            // Scalars have no properties, so they are compared with the property itself.
+            Value::Array(array)
+                if target_value.is_none()
+                    && !array.is_empty()
+                    && array.iter().all(Value::is_scalar) =>
+            {
+                let array: Vec<_> = array
+                    .iter()
+                    .filter(|value| value.to_str() == property)
+                    .cloned()
+                    .collect();
+                return Ok(Value::array(array));
+            }
             Value::Array(array) => {
                 if !array.iter().all(Value::is_object) {
                     return Ok(Value::Nil);
@@ -234,26 +420,175 @@ impl Filter for WhereFilter {
         };
 
         as_sequence!(input, |input| {
-            let array: Vec<_> = match target_value {
-                None => input
-                    .filter_map(Value::as_object)
-                    .filter(|object| object.get(property).map_or(false, Value::is_truthy))
-                    .map(|object| Value::Object(object.clone()))
-                    .collect(),
-                Some(target_value) => input
-                    .filter_map(Value::as_object)
-                    .filter(|object| {
-                        object
-                            .get(property)
-                            .as_ref()
-                            .map_or(false, |value| value == &target_value)
-                    })
-                    .map(|object| Value::Object(object.clone()))
-                    .collect(),
-            };
+            let array: Vec<_> = input
+                .filter(|value| value.is_object())
+                .filter(|value| has_property_value(value, property, target_value))
+                .cloned()
+                .collect();
             Ok(Value::array(array))
         })
     }
+
+    fn takes_elements(&self) -> bool {
+        true
+    }
+
+    /// Arrays of objects are filtered as their elements are read, once the array is checked.
+    ///
+    /// Lazy arrays aren't checked, so their elements that aren't objects are left out.
+    fn evaluate_elements<'a>(
+        &'a self,
+        input: Filtered<'a>,
+        context: &'a Context<'_>,
+    ) -> Result<Filtered<'a>> {
+        let input = input.into_value()?;
+        let is_objects = match input {
+            Value::Array(ref array) => !array.is_empty() && array.iter().all(Value::is_object),
+            Value::Lazy(_) => true,
+            _ => false,
+        };
+        if !is_objects {
+            return self.evaluate(&input, context).map(Filtered::Value);
+        }
+
+        let args = self.args.evaluate(context)?;
+        let elements = Filtered::Value(input)
+            .into_elements()
+            .expect("input is an array");
+        let selected = elements.filter(move |value| match value {
+            Ok(value) => {
+                value.is_object() && has_property_value(value, &args.property, args.target_value)
+            }
+            Err(_) => true,
+        });
+        Ok(Filtered::Elements(Box::new(selected)))
+    }
+}
+
+/// Whether `value`'s `property` is `target_value`, or any truthy value when there is no target.
+fn has_property_value(value: &Value, property: &str, target_value: Option<&Value>) -> bool {
+    let property = property_value(value, property);
+    match target_value {
+        None => property.is_some_and(Value::is_truthy),
+        Some(target_value) => property == Some(target_value),
+    }
+}
+
+/// The index of the first object in `input` whose `property` matches, as for `where`.
+fn find_property_value<'v, I>(
+    input: I,
+    property: &str,
+    target_value: Option<&Value>,
+) -> Option<(usize, &'v Value)>
+where
+    I: Iterator<Item = &'v Value>,
+{
+    input
+        .enumerate()
+        .find(|(_, value)| value.is_object() && has_property_value(value, property, target_value))
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "find",
+    description = "Returns the first element of an array with a certain property value. \
+                   By default the target is any truthy value.",
+    parameters(WhereArgs),
+    parsed(FindFilter)
+)]
+pub struct Find;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "find"]
+struct FindFilter {
+    #[parameters]
+    args: WhereArgs,
+}
+
+impl Filter for FindFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let property: &str = &args.property;
+        let target_value: Option<&Value> = args.target_value;
+
+        as_sequence!(input, |input| Ok(find_property_value(
+            input,
+            property,
+            target_value
+        )
+        .map_or(Value::Nil, |(_, value)| value.clone())))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "find_index",
+    description = "Returns the index of the first element of an array with a certain property \
+                   value. By default the target is any truthy value.",
+    parameters(WhereArgs),
+    parsed(FindIndexFilter)
+)]
+pub struct FindIndex;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "find_index"]
+struct FindIndexFilter {
+    #[parameters]
+    args: WhereArgs,
+}
+
+impl Filter for FindIndexFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let property: &str = &args.property;
+        let target_value: Option<&Value> = args.target_value;
+
+        as_sequence!(input, |input| Ok(find_property_value(
+            input,
+            property,
+            target_value
+        )
+        .map_or(Value::Nil, |(index, _)| Value::scalar(index as i32))))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "sum",
+    description = "Adds up the numbers in an array, or a property of its elements.",
+    parameters(PropertyArgs),
+    parsed(SumFilter)
+)]
+pub struct Sum;
+
+#[derive(Debug, Default, FromFilterParameters, Display_filter)]
+#[name = "sum"]
+struct SumFilter {
+    #[parameters]
+    args: PropertyArgs,
+}
+
+impl Filter for SumFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        as_sequence!(input, |input| {
+            let policy = context.environment().numeric_policy();
+            let mut total = Number::Integer(0);
+            for item in input {
+                let value = match args.property {
+                    Some(ref property) => safe_property_getter(item, property),
+                    None => item,
+                };
+                let value = value
+                    .as_scalar()
+                    .and_then(|value| value.to_number())
+                    .ok_or_else(|| invalid_input("Array of numbers expected"))?;
+                total = total.plus(value, policy)?;
+            }
+            Ok(Value::scalar(total))
+        })
+    }
 }
 
 /// Removes any duplicate elements in an array.
@@ -263,24 +598,37 @@ impl Filter for WhereFilter {
 #[filter(
     name = "uniq",
     description = "Removes any duplicate elements in an array.",
+    parameters(PropertyArgs),
     parsed(UniqFilter)
 )]
 pub struct Uniq;
 
-#[derive(Debug, Default, Display_filter)]
+#[derive(Debug, Default, FromFilterParameters, Display_filter)]
 #[name = "uniq"]
-struct UniqFilter;
+struct UniqFilter {
+    #[parameters]
+    args: PropertyArgs,
+}
 
 impl Filter for UniqFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        // TODO(#267) optional property parameter
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
 
         let array = input
             .as_array()
             .ok_or_else(|| invalid_input("Array expected"))?;
+        let key = |x: &'_ Value| match &args.property {
+            Some(property) => safe_property_getter(x, property).clone(),
+            None => x.clone(),
+        };
+        let mut seen: Vec<Value> = Vec::new();
         let mut deduped: Vec<Value> = Vec::new();
         for x in array.iter() {
-            if !deduped.contains(x) {
+            // Each element is compared with those kept so far.
+            context.spend_filter_budget(seen.len() as u64 + 1)?;
+            let key = key(x);
+            if !seen.contains(&key) {
+                seen.push(key);
                 deduped.push(x.clone())
             }
         }
@@ -318,6 +666,11 @@ struct MapArgs {
         arg_type = "str"
     )]
     property: Expression,
+    #[parameter(
+        description = "The value for the elements without the property. By default they are left out.",
+        arg_type = "any"
+    )]
+    default: Option<Expression>,
 }
 
 #[derive(Clone, ParseFilter, FilterReflection)]
@@ -344,14 +697,107 @@ impl Filter for MapFilter {
             .as_array()
             .ok_or_else(|| invalid_input("Array expected"))?;
 
-        let property = Scalar::new(args.property.into_owned());
+        let property: &str = &args.property;
+        context.spend_filter_budget(array.len() as u64)?;
 
         let result: Vec<_> = array
             .iter()
-            .filter_map(|v| v.get(&property).cloned())
+            .filter_map(|v| property_value(v, property).or(args.default).cloned())
             .collect();
         Ok(Value::array(result))
     }
+
+    fn takes_elements(&self) -> bool {
+        true
+    }
+
+    fn evaluate_elements<'a>(
+        &'a self,
+        input: Filtered<'a>,
+        context: &'a Context<'_>,
+    ) -> Result<Filtered<'a>> {
+        let args = self.args.evaluate(context)?;
+        let elements = input
+            .into_elements()
+            .ok_or_else(|| invalid_input("Array expected"))?;
+
+        let mapped = elements.filter_map(move |v| {
+            let v = match v.and_then(|v| context.spend_filter_budget(1).map(|_| v)) {
+                Ok(v) => v,
+                Err(error) => return Some(Err(error)),
+            };
+            property_value(&v, &args.property)
+                .or(args.default)
+                .cloned()
+                .map(Ok)
+        });
+        Ok(Filtered::Elements(Box::new(mapped)))
+    }
+}
+
+#[derive(Debug, FilterParameters)]
+struct GroupByArgs {
+    #[parameter(
+        description = "The property to group the elements by.",
+        arg_type = "str"
+    )]
+    property: Expression,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "group_by",
+    description = "Groups the elements of an array by a property, \
+                   as an array of objects with the `name` of the group and its `items`.",
+    parameters(GroupByArgs),
+    parsed(GroupByFilter)
+)]
+pub struct GroupBy;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "group_by"]
+struct GroupByFilter {
+    #[parameters]
+    args: GroupByArgs,
+}
+
+/// Groups `input` by `key`, keeping the groups in the order their first element appears.
+pub(crate) fn group_by<'v, I, F>(input: I, mut key: F) -> Result<Value>
+where
+    I: Iterator<Item = &'v Value>,
+    F: FnMut(&'v Value) -> Result<Value>,
+{
+    let mut groups: Vec<(Value, Vec<Value>)> = Vec::new();
+    for item in input {
+        let name = key(item)?;
+        match groups.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, items)) => items.push(item.clone()),
+            None => groups.push((name, vec![item.clone()])),
+        }
+    }
+
+    let groups: Vec<_> = groups
+        .into_iter()
+        .map(|(name, items)| {
+            let mut group = Object::new();
+            group.insert("name".into(), name);
+            group.insert("size".into(), Value::scalar(items.len() as i32));
+            group.insert("items".into(), Value::array(items));
+            Value::Object(group)
+        })
+        .collect();
+    Ok(Value::array(groups))
+}
+
+impl Filter for GroupByFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        let property: &str = &args.property;
+
+        as_sequence!(input, |input| group_by(input, |item| Ok(
+            safe_property_getter(item, property).clone()
+        )))
+    }
 }
 
 #[derive(Clone, ParseFilter, FilterReflection)]
@@ -385,11 +831,7 @@ impl Filter for CompactFilter {
             // Reject non objects that don't have the required property
             array
                 .iter()
-                .filter(|v| {
-                    !v.as_object()
-                        .and_then(|obj| obj.get(property.as_ref()))
-                        .map_or(true, Value::is_nil)
-                })
+                .filter(|v| !property_value(v, property).map_or(true, Value::is_nil))
                 .cloned()
                 .collect()
         } else {
@@ -398,6 +840,28 @@ impl Filter for CompactFilter {
 
         Ok(Value::array(result))
     }
+
+    fn takes_elements(&self) -> bool {
+        true
+    }
+
+    /// Without a property, nulls are removed as the elements are read.
+    fn evaluate_elements<'a>(
+        &'a self,
+        input: Filtered<'a>,
+        context: &'a Context<'_>,
+    ) -> Result<Filtered<'a>> {
+        if self.args.evaluate(context)?.property.is_some() {
+            let input = input.into_value()?.into_materialized();
+            return self.evaluate(&input, context).map(Filtered::Value);
+        }
+
+        let elements = input
+            .into_elements()
+            .ok_or_else(|| invalid_input("Array expected"))?;
+        let compacted = elements.filter(|v| v.as_ref().map_or(true, |v| !v.is_nil()));
+        Ok(Filtered::Elements(Box::new(compacted)))
+    }
 }
 
 #[derive(Debug, FilterParameters)]
@@ -443,32 +907,114 @@ impl Filter for ConcatFilter {
     }
 }
 
+#[derive(Debug, FilterParameters)]
+struct FirstLastArgs {
+    #[parameter(
+        description = "How many items to return, as an array (or string). By default only the item itself is returned.",
+        arg_type = "integer"
+    )]
+    count: Option<Expression>,
+}
+
+/// The `count` argument of `first` and `last`.
+fn count_argument(count: Option<i32>) -> Result<Option<usize>> {
+    match count {
+        Some(count) => Ok(Some(argument_in_range("count", count, 0..)? as usize)),
+        None => Ok(None),
+    }
+}
+
+/// The first or `last` item of a string or array, or so many of them when there is a `count`.
+fn first_or_last(input: &Value, count: Option<i32>, last: bool) -> Result<Value> {
+    let count = count_argument(count)?;
+    match *input {
+        Value::Scalar(ref x) => {
+            let chars: Vec<_> = x.to_str().chars().collect();
+            let count = count.unwrap_or(1).min(chars.len());
+            let chars = if last {
+                &chars[chars.len() - count..]
+            } else {
+                &chars[..count]
+            };
+            Ok(Value::scalar(chars.iter().collect::<String>()))
+        }
+        Value::Array(ref x) => match count {
+            None if last => Ok(x.last().cloned().unwrap_or(Value::Nil)),
+            None => Ok(x.first().cloned().unwrap_or(Value::Nil)),
+            Some(count) => {
+                let count = count.min(x.len());
+                let items = if last {
+                    &x[x.len() - count..]
+                } else {
+                    &x[..count]
+                };
+                Ok(Value::array(items.to_vec()))
+            }
+        },
+        Value::Range(ref x) => {
+            let number = if last { x.last() } else { x.first() };
+            match count {
+                None => Ok(number.map(Value::scalar).unwrap_or(Value::Nil)),
+                Some(count) => {
+                    let count = count.min(x.len());
+                    let offset = if last { x.len() - count } else { 0 };
+                    Ok(Value::array(x.slice(offset, Some(count)).to_array()))
+                }
+            }
+        }
+        _ => Err(invalid_input("String or Array expected")),
+    }
+}
+
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
     name = "first",
     description = "Returns the first item of an array.",
+    parameters(FirstLastArgs),
     parsed(FirstFilter)
 )]
 pub struct First;
 
-#[derive(Debug, Default, Display_filter)]
+#[derive(Debug, FromFilterParameters, Display_filter)]
 #[name = "first"]
-struct FirstFilter;
+struct FirstFilter {
+    #[parameters]
+    args: FirstLastArgs,
+}
 
 impl Filter for FirstFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        match *input {
-            Value::Scalar(ref x) => {
-                let c = x
-                    .to_str()
-                    .chars()
-                    .next()
-                    .map(|c| c.to_string())
-                    .unwrap_or_else(|| "".to_owned());
-                Ok(Value::scalar(c))
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        first_or_last(input, args.count, false)
+    }
+
+    fn takes_ranges(&self) -> bool {
+        true
+    }
+
+    fn takes_elements(&self) -> bool {
+        true
+    }
+
+    /// Only the elements returned are read.
+    fn evaluate_elements<'a>(
+        &'a self,
+        input: Filtered<'a>,
+        context: &'a Context<'_>,
+    ) -> Result<Filtered<'a>> {
+        let args = self.args.evaluate(context)?;
+        let count = count_argument(args.count)?;
+        let mut elements = match input {
+            Filtered::Value(ref input) if !input.is_lazy() => {
+                return self.evaluate(input, context).map(Filtered::Value)
             }
-            Value::Array(ref x) => Ok(x.first().cloned().unwrap_or_else(|| Value::Nil)),
-            _ => Err(invalid_input("String or Array expected")),
+            input => input.into_elements().expect("input is an array"),
+        };
+        match count {
+            None => Ok(Filtered::Value(
+                elements.next().transpose()?.unwrap_or(Value::Nil),
+            )),
+            Some(count) => Ok(Filtered::Elements(Box::new(elements.take(count)))),
         }
     }
 }
@@ -477,28 +1023,59 @@ impl Filter for FirstFilter {
 #[filter(
     name = "last",
     description = "Returns the last item of an array.",
+    parameters(FirstLastArgs),
     parsed(LastFilter)
 )]
 pub struct Last;
 
-#[derive(Debug, Default, Display_filter)]
+#[derive(Debug, FromFilterParameters, Display_filter)]
 #[name = "last"]
-struct LastFilter;
+struct LastFilter {
+    #[parameters]
+    args: FirstLastArgs,
+}
 
 impl Filter for LastFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        match *input {
-            Value::Scalar(ref x) => {
-                let c = x
-                    .to_str()
-                    .chars()
-                    .last()
-                    .map(|c| c.to_string())
-                    .unwrap_or_else(|| "".to_owned());
-                Ok(Value::scalar(c))
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+        first_or_last(input, args.count, true)
+    }
+
+    fn takes_ranges(&self) -> bool {
+        true
+    }
+
+    fn takes_elements(&self) -> bool {
+        true
+    }
+
+    /// Only the elements returned are kept while the others are read.
+    fn evaluate_elements<'a>(
+        &'a self,
+        input: Filtered<'a>,
+        context: &'a Context<'_>,
+    ) -> Result<Filtered<'a>> {
+        let args = self.args.evaluate(context)?;
+        let count = count_argument(args.count)?;
+        let elements = match input {
+            Filtered::Value(ref input) if !input.is_lazy() => {
+                return self.evaluate(input, context).map(Filtered::Value)
+            }
+            input => input.into_elements().expect("input is an array"),
+        };
+        let mut last = VecDeque::with_capacity(count.unwrap_or(1));
+        for element in elements {
+            let element = element?;
+            if last.len() == count.unwrap_or(1) {
+                last.pop_front();
             }
-            Value::Array(ref x) => Ok(x.last().cloned().unwrap_or_else(|| Value::Nil)),
-            _ => Err(invalid_input("String or Array expected")),
+            if count != Some(0) {
+                last.push_back(element);
+            }
+        }
+        match count {
+            None => Ok(Filtered::Value(last.pop_back().unwrap_or(Value::Nil))),
+            Some(_) => Ok(Filtered::Value(Value::array(last))),
         }
     }
 }
@@ -549,15 +1126,15 @@ mod tests {
 
     #[test]
     fn unit_concat_nothing() {
-        let input = Value::Array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
-        let result = Value::Array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
-        assert_eq!(unit!(Concat, input, Value::Array(vec![])), result);
+        let input = Value::array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
+        let result = Value::array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
+        assert_eq!(unit!(Concat, input, Value::array(vec![])), result);
     }
 
     #[test]
     fn unit_concat_something() {
-        let input = Value::Array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
-        let result = Value::Array(vec![
+        let input = Value::array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
+        let result = Value::array(vec![
             Value::scalar(1f64),
             Value::scalar(2f64),
             Value::scalar(3f64),
@@ -567,7 +1144,7 @@ mod tests {
             unit!(
                 Concat,
                 input,
-                Value::Array(vec![Value::scalar(3f64), Value::scalar(4f64)])
+                Value::array(vec![Value::scalar(3f64), Value::scalar(4f64)])
             ),
             result
         );
@@ -575,8 +1152,8 @@ mod tests {
 
     #[test]
     fn unit_concat_mixed() {
-        let input = Value::Array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
-        let result = Value::Array(vec![
+        let input = Value::array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
+        let result = Value::array(vec![
             Value::scalar(1f64),
             Value::scalar(2f64),
             Value::scalar(3f64),
@@ -586,7 +1163,7 @@ mod tests {
             unit!(
                 Concat,
                 input,
-                Value::Array(vec![Value::scalar(3f64), Value::scalar("a")])
+                Value::array(vec![Value::scalar(3f64), Value::scalar("a")])
             ),
             result
         );
@@ -594,23 +1171,23 @@ mod tests {
 
     #[test]
     fn unit_concat_wrong_type() {
-        let input = Value::Array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
+        let input = Value::array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
         failed!(Concat, input, Value::scalar(1f64));
     }
 
     #[test]
     fn unit_concat_no_args() {
-        let input = Value::Array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
+        let input = Value::array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
         failed!(Concat, input);
     }
 
     #[test]
     fn unit_concat_extra_args() {
-        let input = Value::Array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
+        let input = Value::array(vec![Value::scalar(1f64), Value::scalar(2f64)]);
         failed!(
             Concat,
             input,
-            Value::Array(vec![Value::scalar(3f64), Value::scalar("a")]),
+            Value::array(vec![Value::scalar(3f64), Value::scalar("a")]),
             Value::scalar(2f64)
         );
     }
@@ -620,7 +1197,7 @@ mod tests {
         assert_eq!(
             unit!(
                 First,
-                Value::Array(vec![
+                Value::array(vec![
                     Value::scalar(0f64),
                     Value::scalar(1f64),
                     Value::scalar(2f64),
@@ -631,15 +1208,55 @@ mod tests {
             Value::scalar(0f64)
         );
         assert_eq!(
-            unit!(First, Value::Array(vec![tos!("test"), tos!("two")])),
+            unit!(First, Value::array(vec![tos!("test"), tos!("two")])),
             tos!("test")
         );
-        assert_eq!(unit!(First, Value::Array(vec![])), Value::Nil);
+        assert_eq!(unit!(First, Value::array(vec![])), Value::Nil);
+    }
+
+    #[test]
+    fn unit_first_last_count() {
+        let input = &Value::array(vec![Value::scalar(1), Value::scalar(2), Value::scalar(3)]);
+        assert_eq!(
+            unit!(First, input, Value::scalar(2)),
+            Value::array(vec![Value::scalar(1), Value::scalar(2)])
+        );
+        assert_eq!(
+            unit!(Last, input, Value::scalar(2)),
+            Value::array(vec![Value::scalar(2), Value::scalar(3)])
+        );
+        assert_eq!(unit!(First, input, Value::scalar(5)), input.clone());
+        assert_eq!(unit!(Last, input, Value::scalar(0)), Value::array(vec![]));
+        assert_eq!(
+            unit!(First, Value::array(vec![]), Value::scalar(1)),
+            Value::array(vec![])
+        );
+        assert_eq!(unit!(First, tos!("héllo"), Value::scalar(2)), tos!("hé"));
+        assert_eq!(unit!(Last, tos!("héllo"), Value::scalar(3)), tos!("llo"));
+        failed!(First, input, Value::scalar(-1));
+        failed!(Last, input, tos!("two"));
+    }
+
+    #[test]
+    fn unit_map_default() {
+        let input = &Value::array(vec![
+            order(1, Some("Oslo")),
+            order(2, None),
+            order(3, Some("Bergen")),
+        ]);
+        assert_eq!(
+            unit!(Map, input, tos!("customer.address.city"), tos!("-")),
+            Value::array(vec![tos!("Oslo"), tos!("-"), tos!("Bergen")])
+        );
+        assert_eq!(
+            unit!(Map, input, tos!("customer.address.city"), Value::Nil),
+            Value::array(vec![tos!("Oslo"), Value::Nil, tos!("Bergen")])
+        );
     }
 
     #[test]
     fn unit_join() {
-        let input = Value::Array(vec![tos!("a"), tos!("b"), tos!("c")]);
+        let input = Value::array(vec![tos!("a"), tos!("b"), tos!("c")]);
         assert_eq!(unit!(Join, input, tos!(",")), tos!("a,b,c"));
     }
 
@@ -651,42 +1268,216 @@ mod tests {
 
     #[test]
     fn unit_join_bad_join_string() {
-        let input = Value::Array(vec![tos!("a"), tos!("b"), tos!("c")]);
+        let input = Value::array(vec![tos!("a"), tos!("b"), tos!("c")]);
         assert_eq!(unit!(Join, input, Value::scalar(1f64)), tos!("a1b1c"));
     }
 
     #[test]
     fn unit_join_no_args() {
-        let input = Value::Array(vec![tos!("a"), tos!("b"), tos!("c")]);
+        let input = Value::array(vec![tos!("a"), tos!("b"), tos!("c")]);
         assert_eq!(unit!(Join, input), tos!("a b c"));
     }
 
     #[test]
     fn unit_join_non_string_element() {
-        let input = Value::Array(vec![tos!("a"), Value::scalar(1f64), tos!("c")]);
+        let input = Value::array(vec![tos!("a"), Value::scalar(1f64), tos!("c")]);
         assert_eq!(unit!(Join, input, tos!(",")), tos!("a,1,c"));
     }
 
     #[test]
     fn unit_sort() {
-        let input = &Value::Array(vec![tos!("Z"), tos!("b"), tos!("c"), tos!("a")]);
-        let desired_result = Value::Array(vec![tos!("Z"), tos!("a"), tos!("b"), tos!("c")]);
+        let input = &Value::array(vec![tos!("Z"), tos!("b"), tos!("c"), tos!("a")]);
+        let desired_result = Value::array(vec![tos!("Z"), tos!("a"), tos!("b"), tos!("c")]);
         assert_eq!(unit!(Sort, input), desired_result);
     }
 
     #[test]
     fn unit_sort_natural() {
-        let input = &Value::Array(vec![tos!("Z"), tos!("b"), tos!("c"), tos!("a")]);
-        let desired_result = Value::Array(vec![tos!("a"), tos!("b"), tos!("c"), tos!("Z")]);
+        let input = &Value::array(vec![tos!("Z"), tos!("b"), tos!("c"), tos!("a")]);
+        let desired_result = Value::array(vec![tos!("a"), tos!("b"), tos!("c"), tos!("Z")]);
         assert_eq!(unit!(SortNatural, input), desired_result);
     }
 
+    fn post(category: &str, date: i32, title: &str) -> Value {
+        let mut post = ::liquid::value::Object::new();
+        post.insert("category".into(), tos!(category));
+        post.insert("date".into(), Value::scalar(date));
+        post.insert("title".into(), tos!(title));
+        Value::Object(post)
+    }
+
+    #[test]
+    fn unit_sort_properties() {
+        let input = &Value::array(vec![
+            post("rust", 2, "a"),
+            post("food", 1, "b"),
+            post("rust", 1, "c"),
+            post("rust", 1, "d"),
+        ]);
+        let desired_result = Value::array(vec![
+            post("food", 1, "b"),
+            post("rust", 1, "c"),
+            post("rust", 1, "d"),
+            post("rust", 2, "a"),
+        ]);
+        assert_eq!(
+            unit!(Sort, input, tos!("category"), tos!("date")),
+            desired_result
+        );
+        assert_eq!(
+            unit!(Sort, input, tos!("category")),
+            Value::array(vec![
+                post("food", 1, "b"),
+                post("rust", 2, "a"),
+                post("rust", 1, "c"),
+                post("rust", 1, "d"),
+            ])
+        );
+    }
+
+    #[test]
+    fn unit_sort_natural_properties() {
+        let input = &Value::array(vec![
+            post("Rust", 2, "a"),
+            post("rust", 1, "b"),
+            post("food", 3, "c"),
+        ]);
+        let desired_result = Value::array(vec![
+            post("food", 3, "c"),
+            post("rust", 1, "b"),
+            post("Rust", 2, "a"),
+        ]);
+        assert_eq!(
+            unit!(SortNatural, input, tos!("category"), tos!("date")),
+            desired_result
+        );
+    }
+
+    /// Pseudo-random arrays of values of every kind, with many ties, for checking the
+    /// properties of `sort` and `sort_natural` on many inputs.
+    fn arbitrary_arrays() -> Vec<Vec<Value>> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |n: u64| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) % n
+        };
+        let date = ::liquid::value::Scalar::new("2019-03-01 12:00:00 +0000")
+            .to_date()
+            .unwrap();
+        (0..200)
+            .map(|_| {
+                let len = next(12);
+                (0..len)
+                    .map(|_| match next(10) {
+                        0 => Value::scalar(next(5) as i32 - 2),
+                        1 => Value::scalar(next(5) as f64 / 2.0 - 1.0),
+                        2 => Value::scalar(f64::NAN),
+                        3 => Value::scalar(["a", "B", "b", "10", "9", ""][next(6) as usize]),
+                        4 => Value::scalar(next(2) == 0),
+                        5 => Value::scalar(date + ::chrono::Duration::days(next(3) as i64)),
+                        6 => Value::array(vec![Value::scalar(next(3) as i32); next(3) as usize]),
+                        7 => {
+                            let mut object = ::liquid::value::Object::new();
+                            object.insert("k".into(), Value::scalar(next(2) as i32));
+                            Value::Object(object)
+                        }
+                        _ => Value::Nil,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// `values`, each wrapped in an object with its position in `values`.
+    fn with_positions(values: &[Value]) -> Value {
+        let values = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let mut object = ::liquid::value::Object::new();
+                object.insert("v".into(), value.clone());
+                object.insert("i".into(), Value::scalar(i as i32));
+                Value::Object(object)
+            })
+            .collect();
+        Value::Array(values)
+    }
+
+    /// Checks that `sorted` is in `order`, and that the positions of equal values increase.
+    fn assert_sorted_stably<O>(sorted: &Value, order: O)
+    where
+        O: Fn(&Value, &Value) -> cmp::Ordering,
+    {
+        let sorted = sorted.as_array().unwrap();
+        for pair in sorted.windows(2) {
+            let (a, b) = (pair[0].as_object().unwrap(), pair[1].as_object().unwrap());
+            match order(&a["v"], &b["v"]) {
+                cmp::Ordering::Less => {}
+                cmp::Ordering::Equal => assert!(a["i"] < b["i"], "{:?}", sorted),
+                cmp::Ordering::Greater => panic!("{:?} is not sorted", sorted),
+            }
+        }
+    }
+
+    #[test]
+    fn sort_is_a_stable_total_order() {
+        let order = |a: &Value, b: &Value| sort_order(a, b, &|a: &str, b: &str| a.cmp(b));
+        for values in arbitrary_arrays() {
+            let sorted = unit!(Sort, with_positions(&values), tos!("v"));
+            assert_eq!(sorted.as_array().unwrap().len(), values.len());
+            assert_sorted_stably(&sorted, order);
+
+            let sorted = unit!(Sort, Value::array(values.clone()));
+            let again = unit!(Sort, sorted.clone());
+            assert_eq!(format!("{:?}", sorted), format!("{:?}", again));
+        }
+    }
+
+    #[test]
+    fn sort_natural_is_a_stable_total_order() {
+        let order = |a: &Value, b: &Value| {
+            nil_safe_casecmp(&nil_safe_casecmp_key(a), &nil_safe_casecmp_key(b))
+        };
+        for values in arbitrary_arrays() {
+            let sorted = unit!(SortNatural, with_positions(&values), tos!("v"));
+            assert_eq!(sorted.as_array().unwrap().len(), values.len());
+            assert_sorted_stably(&sorted, order);
+        }
+    }
+
+    #[test]
+    fn sort_mixed_kinds() {
+        let input = Value::array(vec![
+            Value::Nil,
+            tos!("10"),
+            Value::scalar(true),
+            Value::scalar(9),
+            tos!("9"),
+            Value::scalar(f64::NAN),
+            Value::scalar(false),
+            Value::scalar(2.5),
+        ]);
+        let sorted = unit!(Sort, input);
+        let sorted: Vec<_> = sorted
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.to_str().into_owned())
+            .collect();
+        assert_eq!(
+            sorted,
+            vec!["2.5", "9", "NaN", "10", "9", "false", "true", ""]
+        );
+    }
+
     #[test]
     fn unit_last() {
         assert_eq!(
             unit!(
                 Last,
-                Value::Array(vec![
+                Value::array(vec![
                     Value::scalar(0f64),
                     Value::scalar(1f64),
                     Value::scalar(2f64),
@@ -697,22 +1488,22 @@ mod tests {
             Value::scalar(4f64)
         );
         assert_eq!(
-            unit!(Last, Value::Array(vec![tos!("test"), tos!("last")])),
+            unit!(Last, Value::array(vec![tos!("test"), tos!("last")])),
             tos!("last")
         );
-        assert_eq!(unit!(Last, Value::Array(vec![])), Value::Nil);
+        assert_eq!(unit!(Last, Value::array(vec![])), Value::Nil);
     }
 
     #[test]
     fn unit_reverse_apples_oranges_peaches_plums() {
         // First example from https://shopify.github.io/liquid/filters/reverse/
-        let input = &Value::Array(vec![
+        let input = &Value::array(vec![
             tos!("apples"),
             tos!("oranges"),
             tos!("peaches"),
             tos!("plums"),
         ]);
-        let desired_result = Value::Array(vec![
+        let desired_result = Value::array(vec![
             tos!("plums"),
             tos!("peaches"),
             tos!("oranges"),
@@ -723,12 +1514,12 @@ mod tests {
 
     #[test]
     fn unit_reverse_array() {
-        let input = &Value::Array(vec![
+        let input = &Value::array(vec![
             Value::scalar(3f64),
             Value::scalar(1f64),
             Value::scalar(2f64),
         ]);
-        let desired_result = Value::Array(vec![
+        let desired_result = Value::array(vec![
             Value::scalar(2f64),
             Value::scalar(1f64),
             Value::scalar(3f64),
@@ -738,7 +1529,7 @@ mod tests {
 
     #[test]
     fn unit_reverse_array_extra_args() {
-        let input = &Value::Array(vec![
+        let input = &Value::array(vec![
             Value::scalar(3f64),
             Value::scalar(1f64),
             Value::scalar(2f64),
@@ -749,7 +1540,7 @@ mod tests {
     #[test]
     fn unit_reverse_ground_control_major_tom() {
         // Second example from https://shopify.github.io/liquid/filters/reverse/
-        let input = &Value::Array(vec![
+        let input = &Value::array(vec![
             tos!("G"),
             tos!("r"),
             tos!("o"),
@@ -779,7 +1570,7 @@ mod tests {
             tos!("m"),
             tos!("."),
         ]);
-        let desired_result = Value::Array(vec![
+        let desired_result = Value::array(vec![
             tos!("."),
             tos!("m"),
             tos!("o"),
@@ -820,11 +1611,215 @@ mod tests {
 
     #[test]
     fn unit_uniq() {
-        let input = &Value::Array(vec![tos!("a"), tos!("b"), tos!("a")]);
-        let desired_result = Value::Array(vec![tos!("a"), tos!("b")]);
+        let input = &Value::array(vec![tos!("a"), tos!("b"), tos!("a")]);
+        let desired_result = Value::array(vec![tos!("a"), tos!("b")]);
         assert_eq!(unit!(Uniq, input), desired_result);
     }
 
+    #[test]
+    fn unit_uniq_property() {
+        let product = |sku: &str, name: &str| {
+            let mut product = ::liquid::value::Object::new();
+            product.insert("sku".into(), tos!(sku));
+            product.insert("name".into(), tos!(name));
+            Value::Object(product)
+        };
+        let input = &Value::array(vec![
+            product("A1", "Hat"),
+            product("B2", "Scarf"),
+            product("A1", "Red hat"),
+        ]);
+        let desired_result = Value::array(vec![product("A1", "Hat"), product("B2", "Scarf")]);
+        assert_eq!(unit!(Uniq, input, tos!("sku")), desired_result);
+    }
+
+    #[test]
+    fn unit_group_by() {
+        let post = |title: &str, category: &str| {
+            let mut post = ::liquid::value::Object::new();
+            post.insert("title".into(), tos!(title));
+            post.insert("category".into(), tos!(category));
+            Value::Object(post)
+        };
+        let group = |name: &str, items: Vec<Value>| {
+            let mut group = ::liquid::value::Object::new();
+            group.insert("name".into(), tos!(name));
+            group.insert("size".into(), Value::scalar(items.len() as i32));
+            group.insert("items".into(), Value::array(items));
+            Value::Object(group)
+        };
+        let input = &Value::array(vec![
+            post("a", "rust"),
+            post("b", "food"),
+            post("c", "rust"),
+        ]);
+        let desired_result = Value::array(vec![
+            group("rust", vec![post("a", "rust"), post("c", "rust")]),
+            group("food", vec![post("b", "food")]),
+        ]);
+        assert_eq!(unit!(GroupBy, input, tos!("category")), desired_result);
+        assert_eq!(
+            unit!(GroupBy, Value::Nil, tos!("category")),
+            Value::array(vec![])
+        );
+    }
+
+    #[test]
+    fn unit_group_by_no_property() {
+        let input = &Value::array(vec![tos!("a")]);
+        failed!(GroupBy, input);
+    }
+
+    fn products() -> Value {
+        let product = |title: &str, available: bool| {
+            let mut product = ::liquid::value::Object::new();
+            product.insert("title".into(), tos!(title));
+            product.insert("available".into(), Value::scalar(available));
+            Value::Object(product)
+        };
+        Value::array(vec![
+            product("Hat", false),
+            product("Scarf", true),
+            product("Gloves", true),
+        ])
+    }
+
+    #[test]
+    fn unit_find() {
+        let input = &products();
+        let scarf = input.as_array().unwrap()[1].clone();
+        assert_eq!(unit!(Find, input, tos!("available")), scarf);
+        assert_eq!(unit!(Find, input, tos!("title"), tos!("Scarf")), scarf);
+        assert_eq!(unit!(Find, input, tos!("title"), tos!("Boots")), Value::Nil);
+        assert_eq!(unit!(Find, Value::Nil, tos!("title")), Value::Nil);
+        failed!(Find, input);
+    }
+
+    #[test]
+    fn unit_find_index() {
+        let input = &products();
+        assert_eq!(unit!(FindIndex, input, tos!("available")), Value::scalar(1));
+        assert_eq!(
+            unit!(FindIndex, input, tos!("title"), tos!("Gloves")),
+            Value::scalar(2)
+        );
+        assert_eq!(
+            unit!(FindIndex, input, tos!("title"), tos!("Boots")),
+            Value::Nil
+        );
+        failed!(FindIndex, input);
+    }
+
+    fn order(id: i32, city: Option<&str>) -> Value {
+        let mut order = ::liquid::value::Object::new();
+        order.insert("id".into(), Value::scalar(id));
+        if let Some(city) = city {
+            let mut address = ::liquid::value::Object::new();
+            address.insert("city".into(), tos!(city));
+            let mut customer = ::liquid::value::Object::new();
+            customer.insert("address".into(), Value::Object(address));
+            order.insert("customer".into(), Value::Object(customer));
+        }
+        Value::Object(order)
+    }
+
+    #[test]
+    fn unit_where_scalars() {
+        let input = &Value::array(vec![tos!("a"), tos!("b"), tos!("a"), Value::scalar(1)]);
+        assert_eq!(
+            unit!(Where, input, tos!("a")),
+            Value::array(vec![tos!("a"), tos!("a")])
+        );
+        assert_eq!(
+            unit!(Where, input, Value::scalar(1)),
+            Value::array(vec![Value::scalar(1)])
+        );
+        assert_eq!(unit!(Where, input, tos!("c")), Value::array(vec![]));
+        // Scalars have no properties to compare with a target value.
+        assert_eq!(unit!(Where, input, tos!("a"), tos!("a")), Value::Nil);
+        let mixed = &Value::array(vec![tos!("a"), Value::Object(Default::default())]);
+        assert_eq!(unit!(Where, mixed, tos!("a")), Value::Nil);
+    }
+
+    #[test]
+    fn unit_property_paths() {
+        let input = &Value::array(vec![
+            order(1, Some("Oslo")),
+            order(2, None),
+            order(3, Some("Bergen")),
+        ]);
+        let path = || tos!("customer.address.city");
+        assert_eq!(
+            unit!(Map, input, path()),
+            Value::array(vec![tos!("Oslo"), tos!("Bergen")])
+        );
+        assert_eq!(
+            unit!(Where, input, path(), tos!("Bergen")),
+            Value::array(vec![order(3, Some("Bergen"))])
+        );
+        assert_eq!(
+            unit!(Compact, input, path()),
+            Value::array(vec![order(1, Some("Oslo")), order(3, Some("Bergen"))])
+        );
+        assert_eq!(
+            unit!(Sort, input, path()),
+            Value::array(vec![
+                order(3, Some("Bergen")),
+                order(1, Some("Oslo")),
+                order(2, None),
+            ])
+        );
+        assert_eq!(
+            unit!(FindIndex, input, path(), tos!("Oslo")),
+            Value::scalar(0)
+        );
+        assert_eq!(
+            unit!(Map, input, tos!("customer.missing.city")),
+            Value::array(vec![])
+        );
+    }
+
+    #[test]
+    fn unit_property_path_dotted_key() {
+        let mut item = ::liquid::value::Object::new();
+        item.insert("a.b".into(), tos!("whole"));
+        let input = &Value::array(vec![Value::Object(item)]);
+        assert_eq!(
+            unit!(Map, input, tos!("a.b")),
+            Value::array(vec![tos!("whole")])
+        );
+    }
+
+    #[test]
+    fn unit_sum() {
+        let input = &Value::array(vec![Value::scalar(1), Value::scalar(2), tos!("3")]);
+        assert_eq!(unit!(Sum, input), Value::scalar(6));
+        let input = &Value::array(vec![Value::scalar(1), Value::scalar(2.5)]);
+        assert_eq!(unit!(Sum, input), Value::scalar(3.5));
+        assert_eq!(unit!(Sum, Value::array(vec![])), Value::scalar(0));
+        assert_eq!(unit!(Sum, Value::Nil), Value::scalar(0));
+    }
+
+    #[test]
+    fn unit_sum_property() {
+        let item = |price: Value| {
+            let mut item = ::liquid::value::Object::new();
+            item.insert("price".into(), price);
+            Value::Object(item)
+        };
+        let input = &Value::array(vec![item(Value::scalar(3)), item(Value::scalar(1.5))]);
+        assert_eq!(unit!(Sum, input, tos!("price")), Value::scalar(4.5));
+        let input = &Value::array(vec![item(Value::scalar(3)), item(tos!("free"))]);
+        failed!(Sum, input, tos!("price"));
+    }
+
+    #[test]
+    fn unit_sum_not_numbers() {
+        failed!(Sum, Value::array(vec![tos!("a")]));
+        failed!(Sum, Value::array(vec![Value::Nil]));
+        failed!(Sum, Value::array(vec![Value::array(vec![])]));
+    }
+
     #[test]
     fn unit_uniq_non_array() {
         let input = &Value::scalar(0f64);
@@ -832,22 +1827,22 @@ mod tests {
     }
 
     #[test]
-    fn unit_uniq_one_argument() {
-        let input = &Value::Array(vec![tos!("a"), tos!("b"), tos!("a")]);
-        failed!(Uniq, input, Value::scalar(0f64));
+    fn unit_uniq_two_arguments() {
+        let input = &Value::array(vec![tos!("a"), tos!("b"), tos!("a")]);
+        failed!(Uniq, input, tos!("a"), tos!("b"));
     }
 
     #[test]
     fn unit_uniq_shopify_liquid() {
         // Test from https://shopify.github.io/liquid/filters/uniq/
-        let input = &Value::Array(vec![
+        let input = &Value::array(vec![
             tos!("ants"),
             tos!("bugs"),
             tos!("bees"),
             tos!("bugs"),
             tos!("ants"),
         ]);
-        let desired_result = Value::Array(vec![tos!("ants"), tos!("bugs"), tos!("bees")]);
+        let desired_result = Value::array(vec![tos!("ants"), tos!("bugs"), tos!("bees")]);
         assert_eq!(unit!(Uniq, input), desired_result);
     }
 }
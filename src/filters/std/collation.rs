@@ -0,0 +1,195 @@
+use std::cmp;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// How strings are ordered for the readers of a locale.
+///
+/// Letters with accents sort with their base letter, with the accent only breaking ties, unless
+/// the language of the locale orders them as letters of their own, like `ä` after `z` in Swedish.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) struct Collator {
+    language: Language,
+    ignore_case: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Language {
+    Default,
+    Czech,
+    Danish,
+    Polish,
+    Spanish,
+    Swedish,
+}
+
+/// The sort key of a string, compared by letters, then accents, then case.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct CollationKey {
+    primary: Vec<u32>,
+    secondary: Vec<u32>,
+    tertiary: Vec<bool>,
+}
+
+impl Collator {
+    /// The collator of `locale`, an IETF language tag like `sv-SE` or `de_AT`.
+    ///
+    /// Languages without special rules use the default order, which suits e.g. English, French
+    /// and German.
+    pub(super) fn new(locale: &str, ignore_case: bool) -> Self {
+        let language = locale.split(['-', '_']).next().unwrap_or("").to_lowercase();
+        let language = match language.as_str() {
+            "cs" | "sk" => Language::Czech,
+            "da" | "nb" | "nn" | "no" => Language::Danish,
+            "pl" => Language::Polish,
+            "es" => Language::Spanish,
+            "sv" | "fi" => Language::Swedish,
+            _ => Language::Default,
+        };
+        Self {
+            language,
+            ignore_case,
+        }
+    }
+
+    /// The letter that a lowercase letter the language orders on its own follows, and how many
+    /// places after it it goes.
+    fn tailoring(self, letter: char) -> Option<(char, u32)> {
+        let tailored = match (self.language, letter) {
+            (Language::Swedish, 'å') => ('z', 1),
+            (Language::Swedish, 'ä') | (Language::Swedish, 'æ') => ('z', 2),
+            (Language::Swedish, 'ö') | (Language::Swedish, 'ø') => ('z', 3),
+            (Language::Danish, 'æ') | (Language::Danish, 'ä') => ('z', 1),
+            (Language::Danish, 'ø') | (Language::Danish, 'ö') => ('z', 2),
+            (Language::Danish, 'å') => ('z', 3),
+            (Language::Spanish, 'ñ') => ('n', 1),
+            (Language::Czech, 'č') => ('c', 1),
+            (Language::Czech, 'ř') => ('r', 1),
+            (Language::Czech, 'š') => ('s', 1),
+            (Language::Czech, 'ž') => ('z', 1),
+            (Language::Polish, 'ą') => ('a', 1),
+            (Language::Polish, 'ć') => ('c', 1),
+            (Language::Polish, 'ę') => ('e', 1),
+            (Language::Polish, 'ł') => ('l', 1),
+            (Language::Polish, 'ń') => ('n', 1),
+            (Language::Polish, 'ó') => ('o', 1),
+            (Language::Polish, 'ś') => ('s', 1),
+            (Language::Polish, 'ź') => ('z', 1),
+            (Language::Polish, 'ż') => ('z', 2),
+            _ => return None,
+        };
+        Some(tailored)
+    }
+
+    pub(super) fn key(self, text: &str) -> CollationKey {
+        let mut key = CollationKey {
+            primary: Vec::with_capacity(text.len()),
+            secondary: Vec::with_capacity(text.len()),
+            tertiary: Vec::new(),
+        };
+        for c in text.chars() {
+            let is_upper = c.is_uppercase();
+            for letter in c.to_lowercase() {
+                if let Some((base, position)) = self.tailoring(letter) {
+                    key.push(weight(base) + position, 0, is_upper);
+                    continue;
+                }
+                let expansion = match letter {
+                    'ß' => "ss",
+                    'æ' => "ae",
+                    'œ' => "oe",
+                    'þ' => "th",
+                    _ => "",
+                };
+                if !expansion.is_empty() {
+                    for base in expansion.chars() {
+                        key.push(weight(base), letter as u32, is_upper);
+                    }
+                    continue;
+                }
+                let (base, accent) = match letter {
+                    'ø' => ('o', letter as u32),
+                    'ł' => ('l', letter as u32),
+                    'đ' => ('d', letter as u32),
+                    _ => (letter, 0),
+                };
+                let mut decomposed = base.nfd();
+                let base = decomposed.next().unwrap_or(base);
+                // Accents only matter when the letters are the same.
+                let accents = decomposed
+                    .filter(|c| is_combining_mark(*c))
+                    .fold(accent, |accents, mark| accents * 31 + mark as u32);
+                key.push(weight(base), accents, is_upper);
+            }
+        }
+        if self.ignore_case {
+            key.tertiary.clear();
+        }
+        key
+    }
+
+    /// Compares two strings.
+    pub(super) fn compare(self, a: &str, b: &str) -> cmp::Ordering {
+        self.key(a).cmp(&self.key(b))
+    }
+}
+
+impl CollationKey {
+    fn push(&mut self, primary: u32, secondary: u32, is_upper: bool) {
+        self.primary.push(primary);
+        self.secondary.push(secondary);
+        self.tertiary.push(is_upper);
+    }
+}
+
+/// The primary weight of a base letter, leaving room for the letters tailored after it.
+fn weight(c: char) -> u32 {
+    (c as u32) << 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(locale: &str, words: &[&str]) -> Vec<String> {
+        let collator = Collator::new(locale, false);
+        let mut words: Vec<_> = words.iter().map(|w| (*w).to_owned()).collect();
+        words.sort_by_key(|word| collator.key(word));
+        words
+    }
+
+    #[test]
+    fn accents_break_ties() {
+        assert_eq!(
+            sorted("en-US", &["zebra", "émile", "eve", "Eve", "apple", "Ähre"]),
+            vec!["Ähre", "apple", "émile", "eve", "Eve", "zebra"]
+        );
+        assert_eq!(
+            sorted("de", &["Straße", "Strasse", "Strand", "Öl", "Ofen"]),
+            vec!["Ofen", "Öl", "Strand", "Strasse", "Straße"]
+        );
+    }
+
+    #[test]
+    fn tailored_letters() {
+        assert_eq!(
+            sorted("sv-SE", &["öl", "zon", "äpple", "åsna", "ost"]),
+            vec!["ost", "zon", "åsna", "äpple", "öl"]
+        );
+        assert_eq!(
+            sorted("da_DK", &["åben", "ære", "øl", "zoo"]),
+            vec!["zoo", "ære", "øl", "åben"]
+        );
+        assert_eq!(
+            sorted("es", &["ñu", "nube", "oso"]),
+            vec!["nube", "ñu", "oso"]
+        );
+    }
+
+    #[test]
+    fn ignore_case() {
+        let collator = Collator::new("en", true);
+        assert_eq!(collator.key("Émile"), collator.key("émile"));
+        assert!(collator.key("Emile") < collator.key("émile"));
+    }
+}
@@ -1,14 +1,23 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, FixedOffset};
+use filters::invalid_argument;
 use liquid_compiler::{Filter, FilterParameters};
 use liquid_derive::*;
 use liquid_error::Result;
 use liquid_interpreter::Context;
 use liquid_interpreter::Expression;
-use liquid_value::{Scalar, Value};
+use liquid_value::{Date as DateValue, Value};
 
 #[derive(Debug, FilterParameters)]
 struct DateArgs {
     #[parameter(description = "The format to return the date in.", arg_type = "str")]
     format: Expression,
+    #[parameter(
+        description = "The timezone to present the date in, as `UTC`, an offset like `+02:00` or, with the `timezone-names` feature, a name like `Europe/Amsterdam`.",
+        arg_type = "str"
+    )]
+    timezone: Option<Expression>,
 }
 
 #[derive(Clone, ParseFilter, FilterReflection)]
@@ -27,14 +36,135 @@ struct DateFilter {
     args: DateArgs,
 }
 
+/// A timezone to present a date in.
+#[derive(Debug, Copy, Clone)]
+enum Timezone {
+    Fixed(FixedOffset),
+    #[cfg(feature = "timezone-names")]
+    Named(chrono_tz::Tz),
+}
+
+impl Timezone {
+    /// The same instant as `date`, at the offset the timezone had then.
+    fn convert(&self, date: &DateValue) -> DateValue {
+        match *self {
+            Timezone::Fixed(offset) => date.with_timezone(&offset),
+            #[cfg(feature = "timezone-names")]
+            Timezone::Named(timezone) => {
+                let date = date.with_timezone(&timezone);
+                date.with_timezone(&::chrono::Offset::fix(date.offset()))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "timezone-names")]
+const TIMEZONE_EXPECTED: &str =
+    "`UTC`, an offset like `+02:00` or a name like `Europe/Amsterdam` expected";
+#[cfg(not(feature = "timezone-names"))]
+const TIMEZONE_EXPECTED: &str = "`UTC` or an offset like `+02:00` expected";
+
+/// Parse `UTC`, `GMT`, `Z`, an offset like `+02:00`, `+0200` or `-05`, or, with the
+/// `timezone-names` feature, a tz database name like `Europe/Amsterdam`.
+fn parse_timezone(timezone: &str) -> Option<Timezone> {
+    #[cfg(feature = "timezone-names")]
+    {
+        if let Ok(timezone) = timezone.parse() {
+            return Some(Timezone::Named(timezone));
+        }
+    }
+    parse_offset(timezone).map(Timezone::Fixed)
+}
+
+fn parse_offset(timezone: &str) -> Option<FixedOffset> {
+    if let "UTC" | "GMT" | "Z" = timezone {
+        return FixedOffset::east_opt(0);
+    }
+    let sign = match timezone.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return None,
+    };
+    let digits: String = timezone[1..].chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (&digits[..], "00"),
+        4 => (&digits[..2], &digits[2..]),
+        _ => return None,
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn ordinal(day: u32) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", day, suffix)
+}
+
+/// Format `date` with `strftime` directives, plus `%o` for the day of the month as an ordinal,
+/// e.g. `3rd`, and `%Z` for `UTC` or the offset.
+fn format_date(date: &DateValue, format: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(format.len());
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('o') => expanded.push_str(&ordinal(date.day())),
+            Some('Z') if date.offset().local_minus_utc() == 0 => expanded.push_str("UTC"),
+            Some('Z') => expanded.push_str(&date.format("%:z").to_string()),
+            Some(c) => {
+                expanded.push('%');
+                expanded.push(c);
+            }
+            None => expanded.push('%'),
+        }
+    }
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(&expanded))
+        .map_err(|_| invalid_argument("format", "Invalid date format directive"))?;
+    Ok(formatted)
+}
+
 impl Filter for DateFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let date = input.as_scalar().and_then(Scalar::to_date);
+        let timezone = match args.timezone {
+            Some(timezone) => Some(
+                parse_timezone(timezone.as_ref())
+                    .ok_or_else(|| invalid_argument("timezone", TIMEZONE_EXPECTED))?,
+            ),
+            None => None,
+        };
+
+        let date = match input.as_scalar() {
+            Some(s) if s.to_str() == "now" || s.to_str() == "today" => Some(context.now()),
+            Some(s) => s.to_date(),
+            None => None,
+        };
+        let date = date.map(|date| match timezone {
+            Some(timezone) => timezone.convert(&date),
+            None => date,
+        });
         match date {
             Some(date) if !args.format.is_empty() => {
-                Ok(Value::scalar(date.format(args.format.as_ref()).to_string()))
+                Ok(Value::scalar(format_date(&date, args.format.as_ref())?))
             }
             _ => Ok(input.clone()),
         }
@@ -152,7 +282,116 @@ mod tests {
             Date,
             tos!("13 Jun 2016 02:30:00 +0300"),
             Value::scalar(0f64),
+            tos!("UTC"),
             Value::scalar(1f64)
         );
     }
+
+    #[test]
+    fn unit_date_timezone() {
+        assert_eq!(
+            unit!(
+                Date,
+                tos!("2016-06-13 02:30:00 +0300"),
+                tos!("%Y-%m-%d %H:%M %:z"),
+                tos!("UTC")
+            ),
+            tos!("2016-06-12 23:30 +00:00")
+        );
+        assert_eq!(
+            unit!(
+                Date,
+                tos!("2016-06-13 02:30:00 +0000"),
+                tos!("%H:%M %Z"),
+                tos!("-0530")
+            ),
+            tos!("21:00 -05:30")
+        );
+        failed!(
+            Date,
+            tos!("2016-06-13 02:30:00 +0000"),
+            tos!("%H:%M"),
+            tos!("Mars/Olympus_Mons")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "timezone-names")]
+    fn unit_date_timezone_name() {
+        // Amsterdam is on summer time in June but not in January.
+        assert_eq!(
+            unit!(
+                Date,
+                tos!("2016-06-13 02:30:00 +0000"),
+                tos!("%Y-%m-%d %H:%M %:z"),
+                tos!("Europe/Amsterdam")
+            ),
+            tos!("2016-06-13 04:30 +02:00")
+        );
+        assert_eq!(
+            unit!(
+                Date,
+                tos!("2016-01-13 02:30:00 +0000"),
+                tos!("%Y-%m-%d %H:%M %:z"),
+                tos!("Europe/Amsterdam")
+            ),
+            tos!("2016-01-13 03:30 +01:00")
+        );
+        assert_eq!(
+            unit!(
+                Date,
+                tos!("2016-06-13 02:30:00 +0000"),
+                tos!("%H:%M %Z"),
+                tos!("America/New_York")
+            ),
+            tos!("22:30 -04:00")
+        );
+    }
+
+    #[test]
+    fn unit_date_directives() {
+        assert_eq!(
+            unit!(Date, tos!("2016-06-13 02:30:00 +0000"), tos!("%s %Z")),
+            tos!("1465785000 UTC")
+        );
+        assert_eq!(
+            unit!(
+                Date,
+                tos!("2016-06-01 02:30:00 +0000"),
+                tos!("%B %o, 100%%")
+            ),
+            tos!("June 1st, 100%")
+        );
+        assert_eq!(
+            unit!(Date, tos!("2016-06-12 02:30:00 +0000"), tos!("%o")),
+            tos!("12th")
+        );
+        assert_eq!(
+            unit!(Date, tos!("2016-06-23 02:30:00 +0000"), tos!("%o")),
+            tos!("23rd")
+        );
+        failed!(Date, tos!("2016-06-13 02:30:00 +0000"), tos!("%Q"));
+    }
+
+    #[test]
+    fn unit_date_now() {
+        let now =
+            DateValue::parse_from_str("2019-01-01 12:00:00 +0000", "%Y-%m-%d %H:%M:%S %z").unwrap();
+        let context = ::liquid::interpreter::ContextBuilder::new()
+            .set_environment(::liquid::interpreter::Environment::new().with_now(now))
+            .build();
+        let args = ::liquid::compiler::FilterArguments {
+            positional: Box::new(vec![Expression::Literal(tos!("%Y-%m-%d %H:%M"))].into_iter()),
+            keyword: Box::new(Vec::new().into_iter()),
+        };
+        let filter = ::liquid::compiler::ParseFilter::parse(&Date, args).unwrap();
+        assert_eq!(
+            filter.evaluate(&tos!("now"), &context).unwrap(),
+            tos!("2019-01-01 12:00")
+        );
+        assert_eq!(
+            filter.evaluate(&tos!("today"), &context).unwrap(),
+            tos!("2019-01-01 12:00")
+        );
+    }
 }
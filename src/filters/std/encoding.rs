@@ -0,0 +1,208 @@
+use filters::invalid_input;
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::Value;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn encode(input: &[u8], alphabet: &[u8; 64]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).cloned().unwrap_or(0),
+            chunk.get(2).cloned().unwrap_or(0),
+        ];
+        let group = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                output.push(alphabet[index as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+/// Decodes `input`, with or without its `=` padding.
+fn decode(input: &str, alphabet: &[u8; 64]) -> Result<Vec<u8>> {
+    let invalid = || invalid_input("Invalid base64");
+
+    let data = input.trim_end_matches('=');
+    if data.len() % 4 == 1 || input.len() - data.len() > 2 {
+        return Err(invalid());
+    }
+    if data.len() != input.len() && input.len() % 4 != 0 {
+        return Err(invalid());
+    }
+
+    let mut output = Vec::with_capacity(data.len() / 4 * 3 + 2);
+    for chunk in data.as_bytes().chunks(4) {
+        let mut group = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let index = alphabet.iter().position(|a| a == c).ok_or_else(invalid)?;
+            group |= (index as u32) << (18 - 6 * i);
+        }
+        let bytes = [(group >> 16) as u8, (group >> 8) as u8, group as u8];
+        output.extend_from_slice(&bytes[..chunk.len() - 1]);
+    }
+    Ok(output)
+}
+
+fn decode_str(input: &str, alphabet: &[u8; 64]) -> Result<Value> {
+    let bytes = decode(input, alphabet)?;
+    let decoded = String::from_utf8(bytes).map_err(|_| invalid_input("Malformed UTF-8"))?;
+    Ok(Value::scalar(decoded))
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "base64_encode",
+    description = "Encodes a string in Base64.",
+    parsed(Base64EncodeFilter)
+)]
+pub struct Base64Encode;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "base64_encode"]
+struct Base64EncodeFilter;
+
+impl Filter for Base64EncodeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(encode(input.to_str().as_bytes(), STANDARD)))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "base64_decode",
+    description = "Decodes a string encoded in Base64.",
+    parsed(Base64DecodeFilter)
+)]
+pub struct Base64Decode;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "base64_decode"]
+struct Base64DecodeFilter;
+
+impl Filter for Base64DecodeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        decode_str(&input.to_str(), STANDARD)
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "base64_url_safe_encode",
+    description = "Encodes a string in URL-safe Base64, using `-` and `_` instead of `+` and `/`.",
+    parsed(Base64UrlSafeEncodeFilter)
+)]
+pub struct Base64UrlSafeEncode;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "base64_url_safe_encode"]
+struct Base64UrlSafeEncodeFilter;
+
+impl Filter for Base64UrlSafeEncodeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(encode(input.to_str().as_bytes(), URL_SAFE)))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "base64_url_safe_decode",
+    description = "Decodes a string encoded in URL-safe Base64.",
+    parsed(Base64UrlSafeDecodeFilter)
+)]
+pub struct Base64UrlSafeDecode;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "base64_url_safe_decode"]
+struct Base64UrlSafeDecodeFilter;
+
+impl Filter for Base64UrlSafeDecodeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        decode_str(&input.to_str(), URL_SAFE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            ::liquid::compiler::ParseFilter::parse(&$a, args)
+                .and_then(|filter| ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context))
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_base64_encode() {
+        assert_eq!(unit!(Base64Encode, tos!("")).unwrap(), tos!(""));
+        assert_eq!(unit!(Base64Encode, tos!("f")).unwrap(), tos!("Zg=="));
+        assert_eq!(unit!(Base64Encode, tos!("fo")).unwrap(), tos!("Zm8="));
+        assert_eq!(unit!(Base64Encode, tos!("foo")).unwrap(), tos!("Zm9v"));
+        assert_eq!(
+            unit!(Base64Encode, tos!("one two three")).unwrap(),
+            tos!("b25lIHR3byB0aHJlZQ==")
+        );
+        assert_eq!(
+            unit!(Base64Encode, Value::scalar(12)).unwrap(),
+            tos!("MTI=")
+        );
+    }
+
+    #[test]
+    fn unit_base64_decode() {
+        assert_eq!(
+            unit!(Base64Decode, tos!("b25lIHR3byB0aHJlZQ==")).unwrap(),
+            tos!("one two three")
+        );
+        assert_eq!(unit!(Base64Decode, tos!("Zm8")).unwrap(), tos!("fo"));
+        assert_eq!(unit!(Base64Decode, tos!("")).unwrap(), tos!(""));
+        assert!(unit!(Base64Decode, tos!("Zm9v!")).is_err());
+        assert!(unit!(Base64Decode, tos!("Z")).is_err());
+        assert!(unit!(Base64Decode, tos!("Zm8==")).is_err());
+        assert!(unit!(Base64Decode, tos!("_w==")).is_err());
+        // A lone continuation byte is not UTF-8.
+        assert!(unit!(Base64Decode, tos!("gA==")).is_err());
+    }
+
+    #[test]
+    fn unit_base64_url_safe() {
+        let input = "~~~?ÿ";
+        assert_eq!(unit!(Base64Encode, tos!(input)).unwrap(), tos!("fn5+P8O/"));
+        assert_eq!(
+            unit!(Base64UrlSafeEncode, tos!(input)).unwrap(),
+            tos!("fn5-P8O_")
+        );
+        assert_eq!(
+            unit!(Base64UrlSafeDecode, tos!("fn5-P8O_")).unwrap(),
+            tos!(input)
+        );
+        assert!(unit!(Base64UrlSafeDecode, tos!("fn5+P8O/")).is_err());
+    }
+}
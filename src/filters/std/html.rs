@@ -2,7 +2,7 @@ use liquid_compiler::Filter;
 use liquid_derive::*;
 use liquid_error::Result;
 use liquid_interpreter::Context;
-use liquid_value::Value;
+use liquid_value::{Scalar, Value};
 use regex::Regex;
 
 /// Returns the number of already escaped characters.
@@ -57,7 +57,7 @@ fn escape(input: &Value, once_p: bool) -> Result<Value> {
     if last < s.len() {
         result.push_str(&s[last..]);
     }
-    Ok(Value::scalar(result))
+    Ok(Value::Scalar(Scalar::safe_str(result)))
 }
 
 #[derive(Clone, ParseFilter, FilterReflection)]
@@ -96,6 +96,27 @@ impl Filter for EscapeOnceFilter {
     }
 }
 
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "safe",
+    description = "Marks a string safe to output as it is, when the output is escaped automatically.",
+    parsed(SafeFilter)
+)]
+pub struct Safe;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "safe"]
+struct SafeFilter;
+
+impl Filter for SafeFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        match input.as_scalar() {
+            Some(scalar) if scalar.is_safe() => Ok(input.clone()),
+            _ => Ok(Value::Scalar(Scalar::safe_str(input.to_str()))),
+        }
+    }
+}
+
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
     name = "strip_html",
@@ -109,7 +130,7 @@ pub struct StripHtml;
 struct StripHtmlFilter;
 
 impl Filter for StripHtmlFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         lazy_static! {
             // regexps taken from https://git.io/vXbgS
             static ref MATCHERS: [Regex; 4] = [
@@ -120,11 +141,11 @@ impl Filter for StripHtmlFilter {
             ];
         }
 
-        let input = input.to_str().into_owned();
-
-        let result = MATCHERS.iter().fold(input, |acc, matcher| {
-            matcher.replace_all(&acc, "").into_owned()
-        });
+        let mut result = input.to_str().into_owned();
+        for matcher in MATCHERS.iter() {
+            context.spend_filter_budget(result.len() as u64)?;
+            result = matcher.replace_all(&result, "").into_owned();
+        }
         Ok(Value::scalar(result))
     }
 }
@@ -229,6 +250,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unit_safe() {
+        let output = unit!(Safe, tos!("<br>"));
+        assert_eq!(output, tos!("<br>"));
+        assert!(output.as_scalar().unwrap().is_safe());
+        assert!(unit!(Escape, tos!("<br>")).as_scalar().unwrap().is_safe());
+        assert!(!tos!("<br>").as_scalar().unwrap().is_safe());
+    }
+
     #[test]
     fn unit_strip_html() {
         assert_eq!(
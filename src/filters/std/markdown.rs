@@ -0,0 +1,133 @@
+use liquid_compiler::Filter;
+use liquid_derive::*;
+use liquid_error::Result;
+use liquid_interpreter::Context;
+use liquid_value::{Scalar, Value};
+use pulldown_cmark::{html, Parser};
+
+// Jekyll
+
+fn markdown_to_html(markdown: &str) -> String {
+    let parser = Parser::new(markdown);
+    let mut html = String::with_capacity(markdown.len() * 5 / 4);
+    html::push_html(&mut html, parser);
+    html
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "markdownify",
+    description = "Converts a Markdown string into HTML.",
+    parsed(MarkdownifyFilter)
+)]
+pub struct Markdownify;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "markdownify"]
+struct MarkdownifyFilter;
+
+impl Filter for MarkdownifyFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let input = input.to_str();
+        context.spend_filter_budget(input.len() as u64)?;
+        Ok(Value::Scalar(Scalar::safe_str(markdown_to_html(&input))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    macro_rules! unit {
+        ($a:ident, $b:expr) => {{
+            unit!($a, $b, )
+        }};
+        ($a:ident, $b:expr, $($c:expr),*) => {{
+            let positional = Box::new(vec![$(::liquid::interpreter::Expression::Literal($c)),*].into_iter());
+            let keyword = Box::new(Vec::new().into_iter());
+            let args = ::liquid::compiler::FilterArguments { positional, keyword };
+
+            let context = ::liquid::interpreter::Context::default();
+
+            let filter = ::liquid::compiler::ParseFilter::parse(&$a, args).unwrap();
+            ::liquid::compiler::Filter::evaluate(&*filter, &$b, &context).unwrap()
+        }};
+    }
+
+    macro_rules! tos {
+        ($a:expr) => {{
+            Value::scalar($a.to_owned())
+        }};
+    }
+
+    #[test]
+    fn unit_markdownify() {
+        assert_eq!(
+            unit!(Markdownify, tos!("Hello *world*")),
+            tos!("<p>Hello <em>world</em></p>\n")
+        );
+        assert_eq!(unit!(Markdownify, Value::Nil), tos!(""));
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            markdown_to_html("# Title #\n\nSub\n---\n\none\ntwo\n\n***\n> quoted\nlazy"),
+            "<h1>Title</h1>\n<h2>Sub</h2>\n<p>one\ntwo</p>\n<hr />\n\
+             <blockquote>\n<p>quoted\nlazy</p>\n</blockquote>\n"
+        );
+        assert_eq!(
+            markdown_to_html("```rust\nfn main() {}\n<b>\n```\n\n    indented\n"),
+            "<pre><code class=\"language-rust\">fn main() {}\n&lt;b&gt;\n</code></pre>\n\
+             <pre><code>indented\n</code></pre>\n"
+        );
+        assert_eq!(
+            markdown_to_html("<div class=\"note\">\n*raw*\n</div>\n\nafter"),
+            "<div class=\"note\">\n*raw*\n</div>\n<p>after</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(
+            markdown_to_html("- one\n- two\n  - nested\n- three"),
+            "<ul>\n<li>one</li>\n<li>two\n<ul>\n<li>nested</li>\n</ul>\n</li>\n<li>three</li>\n</ul>\n"
+        );
+        assert_eq!(
+            markdown_to_html("3. a\n\n4. b\n"),
+            "<ol start=\"3\">\n<li>\n<p>a</p>\n</li>\n<li>\n<p>b</p>\n</li>\n</ol>\n"
+        );
+        assert_eq!(
+            markdown_to_html("text\n- item"),
+            "<p>text</p>\n<ul>\n<li>item</li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn test_inline() {
+        assert_eq!(
+            markdown_to_html("**bold** _em_ ***both*** snake_case_name 2 * 3 * 4"),
+            "<p><strong>bold</strong> <em>em</em> <em><strong>both</strong></em> \
+             snake_case_name 2 * 3 * 4</p>\n"
+        );
+        assert_eq!(
+            markdown_to_html("`a < b` `` c`d `` \\*not\\*"),
+            "<p><code>a &lt; b</code> <code>c`d</code> *not*</p>\n"
+        );
+        assert_eq!(
+            markdown_to_html("[the *site*](http://example.com \"Title\") ![logo](/logo.png)"),
+            "<p><a href=\"http://example.com\" title=\"Title\">the <em>site</em></a> \
+             <img src=\"/logo.png\" alt=\"logo\" /></p>\n"
+        );
+        assert_eq!(
+            markdown_to_html("<https://example.com> <me@example.com> <span>x</span> &copy; & <"),
+            "<p><a href=\"https://example.com\">https://example.com</a> \
+             <a href=\"mailto:me@example.com\">me@example.com</a> <span>x</span> © &amp; &lt;</p>\n"
+        );
+        assert_eq!(
+            markdown_to_html("line  \nbreak\\\nagain"),
+            "<p>line<br />\nbreak<br />\nagain</p>\n"
+        );
+    }
+}
@@ -1,10 +1,18 @@
-use filters::{invalid_argument, invalid_input};
+use filters::helpers::{argument_number, input_float, input_number, Number};
+use filters::invalid_argument;
 use liquid_compiler::{Filter, FilterParameters};
 use liquid_derive::*;
 use liquid_error::Result;
 use liquid_interpreter::Context;
 use liquid_interpreter::Expression;
-use liquid_value::{Scalar, Value};
+use liquid_value::Value;
+
+/// The input and operand of an arithmetic filter.
+fn numbers(input: &Value, operand: &Value, context: &Context) -> Result<(Number, Number)> {
+    let input = input_number(input, context)?;
+    let operand = argument_number("operand", operand, context)?;
+    Ok((input, operand))
+}
 
 #[derive(Clone, ParseFilter, FilterReflection)]
 #[filter(
@@ -19,15 +27,9 @@ pub struct Abs;
 struct AbsFilter;
 
 impl Filter for AbsFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        match *input {
-            Value::Scalar(ref s) => s
-                .to_integer()
-                .map(|i| Value::scalar(i.abs()))
-                .or_else(|| s.to_float().map(|i| Value::scalar(i.abs())))
-                .ok_or_else(|| invalid_input("Number expected")),
-            _ => invalid_input("Number expected").into_err(),
-        }
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let policy = context.environment().numeric_policy();
+        input_number(input, context)?.abs(policy).map(Value::scalar)
     }
 }
 
@@ -57,26 +59,10 @@ impl Filter for AtLeastFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let input = input
-            .as_scalar()
-            .ok_or_else(|| invalid_input("Number expected"))?;
-
-        let min = args
-            .min
-            .as_scalar()
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        let result = input
-            .to_integer()
-            .and_then(|i| min.to_integer().map(|min| Value::scalar(i.max(min))))
-            .or_else(|| {
-                input
-                    .to_float()
-                    .and_then(|i| min.to_float().map(|min| Value::scalar(i.max(min))))
-            })
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        Ok(result)
+        match numbers(input, args.min, context)? {
+            (Number::Integer(i), Number::Integer(o)) => Ok(Value::scalar(i.max(o))),
+            (i, o) => Ok(Value::scalar(i.to_float().max(o.to_float()))),
+        }
     }
 }
 
@@ -106,26 +92,10 @@ impl Filter for AtMostFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let input = input
-            .as_scalar()
-            .ok_or_else(|| invalid_input("Number expected"))?;
-
-        let max = args
-            .max
-            .as_scalar()
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        let result = input
-            .to_integer()
-            .and_then(|i| max.to_integer().map(|max| Value::scalar(i.min(max))))
-            .or_else(|| {
-                input
-                    .to_float()
-                    .and_then(|i| max.to_float().map(|max| Value::scalar(i.min(max))))
-            })
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        Ok(result)
+        match numbers(input, args.max, context)? {
+            (Number::Integer(i), Number::Integer(o)) => Ok(Value::scalar(i.min(o))),
+            (i, o) => Ok(Value::scalar(i.to_float().min(o.to_float()))),
+        }
     }
 }
 
@@ -155,26 +125,9 @@ impl Filter for PlusFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let input = input
-            .as_scalar()
-            .ok_or_else(|| invalid_input("Number expected"))?;
-
-        let operand = args
-            .operand
-            .as_scalar()
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        let result = input
-            .to_integer()
-            .and_then(|i| operand.to_integer().map(|o| Value::scalar(i + o)))
-            .or_else(|| {
-                input
-                    .to_float()
-                    .and_then(|i| operand.to_float().map(|o| Value::scalar(i + o)))
-            })
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        Ok(result)
+        let (input, operand) = numbers(input, args.operand, context)?;
+        let policy = context.environment().numeric_policy();
+        input.plus(operand, policy).map(Value::scalar)
     }
 }
 
@@ -204,26 +157,9 @@ impl Filter for MinusFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let input = input
-            .as_scalar()
-            .ok_or_else(|| invalid_input("Number expected"))?;
-
-        let operand = args
-            .operand
-            .as_scalar()
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        let result = input
-            .to_integer()
-            .and_then(|i| operand.to_integer().map(|o| Value::scalar(i - o)))
-            .or_else(|| {
-                input
-                    .to_float()
-                    .and_then(|i| operand.to_float().map(|o| Value::scalar(i - o)))
-            })
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        Ok(result)
+        let (input, operand) = numbers(input, args.operand, context)?;
+        let policy = context.environment().numeric_policy();
+        input.minus(operand, policy).map(Value::scalar)
     }
 }
 
@@ -253,26 +189,9 @@ impl Filter for TimesFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let input = input
-            .as_scalar()
-            .ok_or_else(|| invalid_input("Number expected"))?;
-
-        let operand = args
-            .operand
-            .as_scalar()
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        let result = input
-            .to_integer()
-            .and_then(|i| operand.to_integer().map(|o| Value::scalar(i * o)))
-            .or_else(|| {
-                input
-                    .to_float()
-                    .and_then(|i| operand.to_float().map(|o| Value::scalar(i * o)))
-            })
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        Ok(result)
+        let (input, operand) = numbers(input, args.operand, context)?;
+        let policy = context.environment().numeric_policy();
+        input.times(operand, policy).map(Value::scalar)
     }
 }
 
@@ -302,26 +221,12 @@ impl Filter for DividedByFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let input = input
-            .as_scalar()
-            .ok_or_else(|| invalid_input("Number expected"))?;
-
-        let operand = args
-            .operand
-            .as_scalar()
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        let result = input
-            .to_integer()
-            .and_then(|i| operand.to_integer().map(|o| Value::scalar(i / o)))
-            .or_else(|| {
-                input
-                    .to_float()
-                    .and_then(|i| operand.to_float().map(|o| Value::scalar(i / o)))
-            })
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        Ok(result)
+        let (input, operand) = numbers(input, args.operand, context)?;
+        let policy = context.environment().numeric_policy();
+        input
+            .divided_by(operand, policy)
+            .map(Value::scalar)
+            .map_err(|error| error.context("argument", "operand"))
     }
 }
 
@@ -351,26 +256,12 @@ impl Filter for ModuloFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        let input = input
-            .as_scalar()
-            .ok_or_else(|| invalid_input("Number expected"))?;
-
-        let operand = args
-            .operand
-            .as_scalar()
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        let result = input
-            .to_integer()
-            .and_then(|i| operand.to_integer().map(|o| Value::scalar(i % o)))
-            .or_else(|| {
-                input
-                    .to_float()
-                    .and_then(|i| operand.to_float().map(|o| Value::scalar(i % o)))
-            })
-            .ok_or_else(|| invalid_argument("operand", "Number expected"))?;
-
-        Ok(result)
+        let (input, operand) = numbers(input, args.operand, context)?;
+        let policy = context.environment().numeric_policy();
+        input
+            .modulo(operand, policy)
+            .map(Value::scalar)
+            .map_err(|error| error.context("argument", "operand"))
     }
 }
 
@@ -405,13 +296,11 @@ impl Filter for RoundFilter {
 
         let n = args.decimal_places.unwrap_or(0);
 
-        let input = input
-            .as_scalar()
-            .and_then(Scalar::to_float)
-            .ok_or_else(|| invalid_input("Number expected"))?;
+        let input = input_float(input, context)?;
 
         if n == 0 {
-            Ok(Value::scalar(input.round() as i32))
+            let policy = context.environment().numeric_policy();
+            Number::whole(input.round(), policy).map(Value::scalar)
         } else if n < 0 {
             Err(invalid_argument(
                 "decimal_places",
@@ -437,12 +326,10 @@ pub struct Ceil;
 struct CeilFilter;
 
 impl Filter for CeilFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        let n = input
-            .as_scalar()
-            .and_then(Scalar::to_float)
-            .ok_or_else(|| invalid_input("Number expected"))?;
-        Ok(Value::scalar(n.ceil() as i32))
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let n = input_float(input, context)?;
+        let policy = context.environment().numeric_policy();
+        Number::whole(n.ceil(), policy).map(Value::scalar)
     }
 }
 
@@ -459,12 +346,10 @@ pub struct Floor;
 struct FloorFilter;
 
 impl Filter for FloorFilter {
-    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        let n = input
-            .as_scalar()
-            .and_then(Scalar::to_float)
-            .ok_or_else(|| invalid_input("Number expected"))?;
-        Ok(Value::scalar(n.floor() as i32))
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let n = input_float(input, context)?;
+        let policy = context.environment().numeric_policy();
+        Number::whole(n.floor(), policy).map(Value::scalar)
     }
 }
 
@@ -545,6 +430,15 @@ mod tests {
         assert_eq!(unit!(Abs, Value::scalar(4f64)), Value::scalar(4f64));
         assert_eq!(unit!(Abs, tos!("-19.86")), Value::scalar(19.86f64));
     }
+
+    #[test]
+    fn unit_abs_overflow() {
+        assert_eq!(
+            unit!(Abs, Value::scalar(i32::MIN + 1)),
+            Value::scalar(i32::MAX)
+        );
+        failed!(Abs, Value::scalar(i32::MIN));
+    }
     #[test]
     fn unit_at_least() {
         assert_eq!(
@@ -593,6 +487,11 @@ mod tests {
             unit!(Plus, Value::scalar(21.5), Value::scalar(2.25)),
             Value::scalar(23.75)
         );
+        assert_eq!(
+            unit!(Plus, Value::scalar(i32::MAX - 1), Value::scalar(1)),
+            Value::scalar(i32::MAX)
+        );
+        failed!(Plus, Value::scalar(i32::MAX), Value::scalar(1));
     }
 
     #[test]
@@ -640,6 +539,7 @@ mod tests {
             unit!(Modulo, Value::scalar(183.357), Value::scalar(12_f64)),
             Value::scalar(3.3569999999999993)
         );
+        failed!(Modulo, Value::scalar(3), Value::scalar(0));
     }
 
     #[test]
@@ -655,6 +555,12 @@ mod tests {
         failed!(DividedBy, Value::scalar(true), Value::scalar(8.5));
         failed!(DividedBy, Value::scalar(2.5), Value::scalar(true));
         failed!(DividedBy, Value::scalar(2.5));
+        assert_eq!(
+            unit!(DividedBy, Value::scalar(7), Value::scalar(2)),
+            Value::scalar(3)
+        );
+        failed!(DividedBy, Value::scalar(7), Value::scalar(0));
+        failed!(DividedBy, Value::scalar(7.5), Value::scalar(0.0));
     }
 
     #[test]
@@ -662,6 +568,7 @@ mod tests {
         assert_eq!(unit!(Ceil, Value::scalar(1.1f64)), Value::scalar(2f64));
         assert_eq!(unit!(Ceil, Value::scalar(1f64)), Value::scalar(1f64));
         failed!(Ceil, Value::scalar(true));
+        failed!(Ceil, Value::scalar(3e9));
     }
 
     #[test]
@@ -669,6 +576,7 @@ mod tests {
         assert_eq!(unit!(Floor, Value::scalar(1.1f64)), Value::scalar(1f64));
         assert_eq!(unit!(Floor, Value::scalar(1f64)), Value::scalar(1f64));
         failed!(Floor, Value::scalar(true));
+        failed!(Floor, Value::scalar(-3e9));
     }
 
     #[test]
@@ -677,6 +585,7 @@ mod tests {
         assert_eq!(unit!(Round, Value::scalar(1.5f64)), Value::scalar(2i32));
         assert_eq!(unit!(Round, Value::scalar(2f64)), Value::scalar(2i32));
         failed!(Round, Value::scalar(true));
+        failed!(Round, Value::scalar(3e9));
     }
 
     #[test]
@@ -1,4 +1,4 @@
-use liquid_compiler::{Filter, FilterParameters};
+use liquid_compiler::{Filter, FilterParameters, Filtered};
 use liquid_derive::*;
 use liquid_error::Result;
 use liquid_interpreter::Context;
@@ -6,25 +6,36 @@ use liquid_interpreter::Expression;
 use liquid_value::Value;
 
 mod array;
+mod collation;
 mod date;
+mod encoding;
 mod html;
+#[cfg(feature = "markdown-filters")]
+mod markdown;
 mod math;
 mod slice;
 mod string;
 mod url;
 
+#[cfg(feature = "jekyll-filters")]
+pub(crate) use self::array::group_by;
 pub use self::array::{
-    Compact, Concat, First, Join, Last, Map, Reverse, Sort, SortNatural, Uniq, Where,
+    Compact, Concat, Find, FindIndex, First, GroupBy, Join, Last, Map, Reverse, Sort, SortNatural,
+    Sum, Uniq, Where,
 };
 pub use self::date::Date;
-pub use self::html::{Escape, EscapeOnce, NewlineToBr, StripHtml};
+pub use self::encoding::{Base64Decode, Base64Encode, Base64UrlSafeDecode, Base64UrlSafeEncode};
+pub use self::html::{Escape, EscapeOnce, NewlineToBr, Safe, StripHtml};
+#[cfg(feature = "markdown-filters")]
+pub use self::markdown::Markdownify;
 pub use self::math::{
     Abs, AtLeast, AtMost, Ceil, DividedBy, Floor, Minus, Modulo, Plus, Round, Times,
 };
 pub use self::slice::Slice;
 pub use self::string::case::{Capitalize, Downcase, Upcase};
 pub use self::string::operate::{Append, Prepend, Remove, RemoveFirst, Replace, ReplaceFirst};
-pub use self::string::strip::{Lstrip, Rstrip, Strip, StripNewlines};
+pub use self::string::slugify::Slugify;
+pub use self::string::strip::{Lstrip, NormalizeWhitespace, Rstrip, Strip, StripNewlines};
 pub use self::string::truncate::{Truncate, TruncateWords};
 pub use self::string::Split;
 pub use self::url::{UrlDecode, UrlEncode};
@@ -47,15 +58,52 @@ impl Filter for SizeFilter {
             Value::Scalar(ref x) => Ok(Value::scalar(x.to_str().len() as i32)),
             Value::Array(ref x) => Ok(Value::scalar(x.len() as i32)),
             Value::Object(ref x) => Ok(Value::scalar(x.len() as i32)),
+            Value::Range(ref x) => Ok(Value::scalar(x.len() as i32)),
+            Value::Lazy(ref x) => Ok(Value::scalar(x.len() as i32)),
             _ => Ok(Value::scalar(0i32)),
         }
     }
+
+    fn takes_ranges(&self) -> bool {
+        true
+    }
+
+    fn takes_elements(&self) -> bool {
+        true
+    }
+
+    /// The elements are counted as they are read, without keeping them.
+    fn evaluate_elements<'a>(
+        &'a self,
+        input: Filtered<'a>,
+        context: &'a Context<'_>,
+    ) -> Result<Filtered<'a>> {
+        let elements = match input {
+            Filtered::Value(ref input) if !input.is_lazy() => {
+                return self.evaluate(input, context).map(Filtered::Value)
+            }
+            input => input.into_elements().expect("input is an array"),
+        };
+        let mut size = 0;
+        for element in elements {
+            element?;
+            size += 1;
+        }
+        Ok(Filtered::Value(Value::scalar(size)))
+    }
 }
 
 #[derive(Debug, FilterParameters)]
 struct DefaultArgs {
     #[parameter(description = "The default value.")]
     default: Expression,
+
+    #[parameter(
+        description = "Whether `false` is kept rather than replaced by the default.",
+        arg_type = "bool",
+        mode = "keyword"
+    )]
+    allow_false: Option<Expression>,
 }
 
 #[derive(Clone, ParseFilter, FilterReflection)]
@@ -78,7 +126,9 @@ impl Filter for DefaultFilter {
     fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
         let args = self.args.evaluate(context)?;
 
-        if input.is_default() {
+        let allow_false = args.allow_false.unwrap_or(false);
+        let is_false = input.as_scalar().and_then(|s| s.to_bool()) == Some(false);
+        if input.is_default() && !(allow_false && is_false) {
             Ok(args.default.clone())
         } else {
             Ok(input.clone())
@@ -124,7 +174,7 @@ mod tests {
         assert_eq!(
             unit!(
                 Size,
-                Value::Array(vec![
+                Value::array(vec![
                     Value::scalar(0f64),
                     Value::scalar(1f64),
                     Value::scalar(2f64),
@@ -145,12 +195,12 @@ mod tests {
             Value::scalar(0_f64)
         );
         assert_eq!(
-            unit!(Default, Value::Array(vec![]), Value::scalar(1_f64)),
+            unit!(Default, Value::array(vec![]), Value::scalar(1_f64)),
             Value::scalar(1_f64)
         );
         assert_eq!(
-            unit!(Default, Value::Array(vec![tos!("")]), Value::scalar(1_f64)),
-            Value::Array(vec![tos!("")])
+            unit!(Default, Value::array(vec![tos!("")]), Value::scalar(1_f64)),
+            Value::array(vec![tos!("")])
         );
         assert_eq!(
             unit!(Default, Value::Object(Object::new()), Value::scalar(1_f64)),
@@ -22,6 +22,10 @@ fn canonicalize_slice(
     } else {
         slice_offset
     };
+    // Counting back past the start is out of range, like Ruby's `slice`
+    if slice_offset < 0 {
+        return (0, 0);
+    }
 
     // Cap slice_length
     let slice_length = if slice_offset + slice_length > vec_length {
@@ -76,7 +80,7 @@ impl Filter for SliceFilter {
             ))
         } else {
             let input = input.to_str();
-            let (offset, length) = canonicalize_slice(offset, length, input.len());
+            let (offset, length) = canonicalize_slice(offset, length, input.chars().count());
             Ok(Value::scalar(
                 input.chars().skip(offset).take(length).collect::<String>(),
             ))
@@ -156,4 +160,33 @@ mod tests {
         failed!(Slice, input, tos!(-10), tos!(0));
         failed!(Slice, input, tos!(-10), tos!(-1));
     }
+
+    #[test]
+    fn unit_slice_array() {
+        let input = &Value::array(vec![tos!("a"), tos!("b"), tos!("c"), tos!("d")]);
+        assert_eq!(
+            unit!(Slice, input, tos!(1), tos!(2)),
+            Value::array(vec![tos!("b"), tos!("c")])
+        );
+        assert_eq!(unit!(Slice, input, tos!(-1)), Value::array(vec![tos!("d")]));
+        assert_eq!(
+            unit!(Slice, input, tos!(-2), tos!(5)),
+            Value::array(vec![tos!("c"), tos!("d")])
+        );
+        assert_eq!(unit!(Slice, input, tos!(4)), Value::array(vec![]));
+        assert_eq!(unit!(Slice, input, tos!(-5)), Value::array(vec![]));
+    }
+
+    #[test]
+    fn unit_slice_out_of_range() {
+        let input = &tos!("abc");
+        assert_eq!(unit!(Slice, input, tos!(-4), tos!(2)), tos!(""));
+        assert_eq!(unit!(Slice, input, tos!(5)), tos!(""));
+    }
+
+    #[test]
+    fn unit_slice_unicode() {
+        let input = &tos!("héllo");
+        assert_eq!(unit!(Slice, input, tos!(-4), tos!(2)), tos!("él"));
+    }
 }
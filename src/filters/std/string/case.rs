@@ -19,6 +19,9 @@ struct DowncaseFilter;
 impl Filter for DowncaseFilter {
     fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
         let s = input.to_str();
+        if s.chars().all(|c| c.to_lowercase().eq(Some(c))) {
+            return Ok(super::string_output(input, &s));
+        }
         Ok(Value::scalar(s.to_lowercase()))
     }
 }
@@ -38,6 +41,9 @@ struct UpcaseFilter;
 impl Filter for UpcaseFilter {
     fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
         let s = input.to_str();
+        if s.chars().all(|c| c.to_uppercase().eq(Some(c))) {
+            return Ok(super::string_output(input, &s));
+        }
         Ok(Value::scalar(s.to_uppercase()))
     }
 }
@@ -135,4 +141,13 @@ mod tests {
             tos!("HELLO WORLD 21")
         );
     }
+
+    #[test]
+    fn unit_case_unchanged_stays_shared() {
+        let input = Value::Scalar(::liquid::value::Scalar::shared_str("abc 21"));
+        assert!(unit!(Downcase, input).as_scalar().unwrap().is_shared_str());
+        assert!(!unit!(Upcase, input).as_scalar().unwrap().is_shared_str());
+        assert_eq!(unit!(Upcase, input), tos!("ABC 21"));
+        assert_eq!(unit!(Downcase, Value::scalar(1)), tos!("1"));
+    }
 }
@@ -7,9 +7,23 @@ use liquid_value::Value;
 
 pub mod case;
 pub mod operate;
+pub mod slugify;
 pub mod strip;
 pub mod truncate;
 
+/// The value of a filter that turned the string of `input` into `output`.
+///
+/// When the filter changed nothing, `input` is returned as is, so a shared string stays shared
+/// instead of being copied.
+fn string_output(input: &Value, output: &str) -> Value {
+    match input.as_scalar() {
+        Some(scalar) if scalar.type_name() == "string" && scalar.to_str() == output => {
+            input.clone()
+        }
+        _ => Value::scalar(output.to_owned()),
+    }
+}
+
 #[derive(Debug, FilterParameters)]
 struct SplitArgs {
     #[parameter(
@@ -99,18 +113,18 @@ mod tests {
     fn unit_split() {
         assert_eq!(
             unit!(Split, tos!("a, b, c"), tos!(", ")),
-            Value::Array(vec![tos!("a"), tos!("b"), tos!("c")])
+            Value::array(vec![tos!("a"), tos!("b"), tos!("c")])
         );
         assert_eq!(
             unit!(Split, tos!("a~b"), tos!("~")),
-            Value::Array(vec![tos!("a"), tos!("b")])
+            Value::array(vec![tos!("a"), tos!("b")])
         );
     }
 
     #[test]
     fn unit_split_bad_split_string() {
         let input = tos!("a,b,c");
-        let desired_result = Value::Array(vec![tos!("a,b,c")]);
+        let desired_result = Value::array(vec![tos!("a,b,c")]);
         assert_eq!(unit!(Split, input, Value::scalar(1f64)), desired_result);
     }
 
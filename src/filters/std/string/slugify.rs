@@ -1,4 +1,7 @@
 use deunicode;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
 use liquid_compiler::{Filter, FilterParameters};
 use liquid_derive::*;
 use liquid_error::Result;
@@ -30,11 +33,12 @@ impl SlugifyMode {
     }
 }
 
+// Jekyll keeps letters, marks and digits of any script.
 lazy_static! {
-    static ref SLUG_INVALID_CHARS_DEFAULT: Regex = Regex::new(r"([^0-9\p{Alphabetic}]+)").unwrap();
+    static ref SLUG_INVALID_CHARS_DEFAULT: Regex = Regex::new(r"([^\p{M}\p{L}\p{Nd}]+)").unwrap();
     static ref SLUG_INVALID_CHARS_RAW: Regex = Regex::new(r"([\s]+)").unwrap();
     static ref SLUG_INVALID_CHARS_PRETTY: Regex =
-        Regex::new(r"([^\p{Alphabetic}0-9\._\~!\$&'\(\)\+,;=@]+)").unwrap();
+        Regex::new(r"([^\p{M}\p{L}\p{Nd}\._\~!\$&'\(\)\+,;=@]+)").unwrap();
     static ref SLUG_INVALID_CHARS_ASCII: Regex = Regex::new(r"([^a-zA-Z0-9]+)").unwrap();
 }
 
@@ -73,12 +77,16 @@ impl Filter for SlugifyFilter {
             .map(|mode| SlugifyMode::new(mode.as_ref()))
             .unwrap_or(SlugifyMode::Def);
 
+        // Compose characters first, so `e` followed by a combining accent is one letter.
+        let s: String = s.trim().nfc().collect();
         let s = if mode == SlugifyMode::Latin {
-            deunicode::deunicode_with_tofu(&s.trim(), "-")
+            let unaccented: String = s.nfkd().filter(|c| !is_combining_mark(*c)).collect();
+            deunicode::deunicode_with_tofu(&unaccented, "-")
         } else {
-            s.trim().to_string()
+            s
         };
 
+        context.spend_filter_budget(s.len() as u64)?;
         let result = match mode {
             SlugifyMode::No => s,
             SlugifyMode::Def => SLUG_INVALID_CHARS_DEFAULT.replace_all(&s, "-").to_string(),
@@ -152,6 +160,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_slugify_pretty() {
+        assert_eq!(
+            unit!(Slugify, tos!("The _config.yml file?"), tos!("pretty")),
+            tos!("the-_config.yml-file")
+        );
+    }
+
+    #[test]
+    fn test_slugify_normalizes() {
+        // "Café" with a combining acute accent.
+        assert_eq!(
+            unit!(Slugify, tos!("Cafe\u{301} au lait")),
+            tos!("café-au-lait")
+        );
+        assert_eq!(
+            unit!(Slugify, tos!("Cafe\u{301} au lait"), tos!("latin")),
+            tos!("cafe-au-lait")
+        );
+        assert_eq!(
+            unit!(Slugify, tos!("Ærøskøbing Straße"), tos!("latin")),
+            tos!("aeroskobing-strasse")
+        );
+        assert_eq!(unit!(Slugify, tos!("हिन्दी शब्द")), tos!("हिन्दी-शब्द"));
+    }
+
     #[test]
     fn test_slugify_none() {
         assert_eq!(
@@ -159,5 +193,4 @@ mod tests {
             tos!("the _config.yml file")
         );
     }
-
 }
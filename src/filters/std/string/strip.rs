@@ -25,8 +25,8 @@ struct StripFilter;
 
 impl Filter for StripFilter {
     fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        let input = input.to_str();
-        Ok(Value::scalar(input.trim().to_owned()))
+        let output = input.to_str();
+        Ok(super::string_output(input, output.trim()))
     }
 }
 
@@ -50,8 +50,8 @@ struct LstripFilter;
 
 impl Filter for LstripFilter {
     fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        let input = input.to_str();
-        Ok(Value::scalar(input.trim_start().to_owned()))
+        let output = input.to_str();
+        Ok(super::string_output(input, output.trim_start()))
     }
 }
 
@@ -75,8 +75,8 @@ struct RstripFilter;
 
 impl Filter for RstripFilter {
     fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
-        let input = input.to_str();
-        Ok(Value::scalar(input.trim_end().to_owned()))
+        let output = input.to_str();
+        Ok(super::string_output(input, output.trim_end()))
     }
 }
 
@@ -104,6 +104,33 @@ impl Filter for StripNewlinesFilter {
     }
 }
 
+/// Collapses every run of whitespace, including newlines, into a single space, and removes it
+/// from both ends of the string.
+///
+/// Whitespace is any codepoint with the Unicode Derived Core Property `White_Space`, e.g. a
+/// non-breaking space.
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "normalize_whitespace",
+    description = "Collapses every run of whitespace, including newlines, into a single space.",
+    parsed(NormalizeWhitespaceFilter)
+)]
+pub struct NormalizeWhitespace;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "normalize_whitespace"]
+struct NormalizeWhitespaceFilter;
+
+impl Filter for NormalizeWhitespaceFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let input = input.to_str();
+        Ok(Value::scalar(itertools::join(
+            input.split_whitespace(),
+            " ",
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -239,6 +266,18 @@ mod tests {
         assert_eq!(unit!(Strip, input), desired_result);
     }
 
+    #[test]
+    fn unit_strip_unchanged_stays_shared() {
+        let input = Value::Scalar(::liquid::value::Scalar::shared_str("test"));
+        assert!(unit!(Strip, input).as_scalar().unwrap().is_shared_str());
+        assert!(unit!(Lstrip, input).as_scalar().unwrap().is_shared_str());
+        assert!(unit!(Rstrip, input).as_scalar().unwrap().is_shared_str());
+
+        let input = Value::Scalar(::liquid::value::Scalar::shared_str(" test"));
+        assert!(!unit!(Strip, input).as_scalar().unwrap().is_shared_str());
+        assert_eq!(unit!(Strip, Value::scalar(5)), tos!("5"));
+    }
+
     #[test]
     fn unit_strip_leading_sequence_only() {
         let input = &tos!(" 	 \n \r test");
@@ -322,4 +361,18 @@ mod tests {
         let desired_result = tos!("ab");
         assert_eq!(unit!(StripNewlines, input), desired_result);
     }
+
+    #[test]
+    fn unit_normalize_whitespace() {
+        let input = &tos!(" \t Hello,\r\n\n  there\u{a0}\u{2003}world \n");
+        let desired_result = tos!("Hello, there world");
+        assert_eq!(unit!(NormalizeWhitespace, input), desired_result);
+        assert_eq!(unit!(NormalizeWhitespace, tos!(" \n ")), tos!(""));
+        assert_eq!(unit!(NormalizeWhitespace, Value::scalar(12)), tos!("12"));
+    }
+
+    #[test]
+    fn unit_normalize_whitespace_one_argument() {
+        failed!(NormalizeWhitespace, tos!("a  b"), Value::scalar(0f64));
+    }
 }
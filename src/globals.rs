@@ -0,0 +1,71 @@
+//! Read globals from data files.
+//!
+//! The data is deserialized straight into an `Object`, without first building a JSON or YAML
+//! document, so large data files only take the memory of the globals themselves.
+//!
+//! ```rust
+//! # #[cfg(feature = "json-globals")]
+//! # {
+//! let globals = liquid::globals::from_json_reader(&br#"{"site": {"title": "Blog"}}"#[..]).unwrap();
+//! let template = liquid::ParserBuilder::with_liquid()
+//!     .build()
+//!     .unwrap()
+//!     .parse("{{ site.title }}")
+//!     .unwrap();
+//! assert_eq!(template.render(&globals).unwrap(), "Blog");
+//! # }
+//! ```
+
+use std::io;
+
+use liquid_error::{Error, Result};
+use liquid_value::Object;
+
+/// Read globals from a JSON object.
+#[cfg(feature = "json-globals")]
+pub fn from_json_reader<R: io::Read>(reader: R) -> Result<Object> {
+    let reader = io::BufReader::new(reader);
+    serde_json::from_reader(reader)
+        .map_err(|e| Error::with_msg("Invalid JSON globals").context("cause", e.to_string()))
+}
+
+/// Read globals from a YAML mapping.
+#[cfg(feature = "yaml-globals")]
+pub fn from_yaml_reader<R: io::Read>(reader: R) -> Result<Object> {
+    serde_yaml::from_reader(reader)
+        .map_err(|e| Error::with_msg("Invalid YAML globals").context("cause", e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_value::Value;
+
+    #[cfg(feature = "json-globals")]
+    #[test]
+    fn json() {
+        let globals = from_json_reader(&br#"{"a": [1, "b", null], "c": {"d": true}}"#[..]).unwrap();
+        assert_eq!(
+            globals["a"],
+            Value::array(vec![Value::scalar(1), Value::scalar("b"), Value::Nil])
+        );
+        assert_eq!(globals["c"].as_object().unwrap()["d"], Value::scalar(true));
+
+        assert!(from_json_reader(&b"[1, 2]"[..]).is_err());
+        assert!(from_json_reader(&b"{"[..]).is_err());
+    }
+
+    #[cfg(feature = "yaml-globals")]
+    #[test]
+    fn yaml() {
+        let globals = from_yaml_reader(&b"a: 1\nb: [x, y]\n"[..]).unwrap();
+        assert_eq!(globals["a"], Value::scalar(1));
+        assert_eq!(
+            globals["b"],
+            Value::array(vec![Value::scalar("x"), Value::scalar("y")])
+        );
+
+        assert!(from_yaml_reader(&b"- 1"[..]).is_err());
+    }
+}
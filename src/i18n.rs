@@ -0,0 +1,570 @@
+//! Translate the text of templates, like the `t` filter of Shopify themes.
+//!
+//! A `Catalog` holds the translations of each locale, as nested objects like those of a theme's
+//! `locales/*.json` files.  Registering it as a plugin adds the `t` filter and tag, which look
+//! up a key in the locale of the render's `Environment`:
+//!
+//! ```rust
+//! use liquid::i18n::Catalog;
+//! use liquid::value::liquid_value;
+//!
+//! let en = liquid_value!({
+//!     "cart": {
+//!         "items": {"one": "{{ count }} item", "other": "{{ count }} items"},
+//!         "greeting": "Hi {{ name }}",
+//!     }
+//! });
+//! let fr = liquid_value!({
+//!     "cart": {"items": {"one": "{{ count }} article", "other": "{{ count }} articles"}}
+//! });
+//! let catalog = Catalog::new()
+//!     .with_locale("en", en.into_object().unwrap())
+//!     .with_locale("fr", fr.into_object().unwrap());
+//!
+//! let template = liquid::ParserBuilder::with_liquid()
+//!     .plugin(catalog)
+//!     .build()
+//!     .unwrap()
+//!     .parse("{{ 'cart.items' | t: count: n }}, {% t 'cart.greeting', name: 'Ana' %}")
+//!     .unwrap();
+//! let globals = liquid_value!({"n": 1});
+//! let globals = globals.as_object().unwrap();
+//! assert_eq!(template.render(globals).unwrap(), "1 item, Hi Ana");
+//! assert_eq!(
+//!     template.with_locale("fr-CA").render(globals).unwrap(),
+//!     "1 article, Hi Ana"
+//! );
+//! ```
+//!
+//! Locale files load with `globals::from_json_reader` or `globals::from_yaml_reader`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use liquid_compiler::{Filter, FilterArguments, FilterReflection, ParseFilter};
+use liquid_compiler::{Language, ParseTag, TagReflection, TagTokenIter};
+use liquid_derive::*;
+use liquid_error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
+use liquid_interpreter::{Context, Expression, Output, References, Renderable};
+use liquid_value::{Object, Value};
+
+use filters::invalid_argument;
+use parser::ParserBuilder;
+use partials;
+use plugin::Plugin;
+
+/// The translations of each locale.
+///
+/// A key like `cart.items` names an entry of nested objects.  Entries are text, where
+/// `{{ name }}` is replaced by the argument `name` of the translation, or objects of plural
+/// forms, `zero`, `one`, `two`, `few`, `many` and `other`, chosen by the `count` argument with
+/// the plural rules of the locale.
+///
+/// Keys missing in a locale like `pt-BR` are looked up in `pt`, then in the default locale.
+/// Keys missing there too translate to `translation missing: <locale>.<key>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Catalog {
+    default_locale: String,
+    locales: HashMap<String, Object>,
+}
+
+impl Catalog {
+    /// Create a catalog without translations, defaulting to `en`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the translations of `locale`, as an IETF language tag, e.g. `en` or `pt-BR`.
+    pub fn with_locale<S: Into<String>>(mut self, locale: S, translations: Object) -> Self {
+        self.locales.insert(locale.into(), translations);
+        self
+    }
+
+    /// Set the locale whose translations are used when others miss a key.
+    pub fn with_default_locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.default_locale = locale.into();
+        self
+    }
+
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
+    /// Translate `key` in `locale`, with `arguments` for its interpolations and plural form.
+    pub fn translate(&self, locale: &str, key: &str, arguments: &Object) -> Result<String> {
+        let count = match arguments.get("count") {
+            None | Some(Value::Nil) => None,
+            Some(count) => Some(
+                count
+                    .as_scalar()
+                    .and_then(|count| count.to_float())
+                    .ok_or_else(|| invalid_argument("count", "Number expected"))?,
+            ),
+        };
+
+        let entry = self.fallbacks(locale).find_map(|locale| {
+            let entry = self.lookup(locale, key)?;
+            select(entry, count, locale)
+        });
+        let translation = match entry {
+            Some(translation) => interpolate(translation, arguments),
+            None => format!("translation missing: {}.{}", locale, key),
+        };
+        Ok(translation)
+    }
+
+    /// The locales whose translations are used for `locale`, in order.
+    fn fallbacks<'s>(&'s self, locale: &'s str) -> impl Iterator<Item = &'s str> {
+        let language = locale.split(['-', '_']).next().unwrap_or(locale);
+        let mut fallbacks = vec![locale];
+        for fallback in &[language, self.default_locale.as_str()] {
+            if !fallbacks.contains(fallback) {
+                fallbacks.push(fallback);
+            }
+        }
+        fallbacks.into_iter()
+    }
+
+    fn lookup(&self, locale: &str, key: &str) -> Option<&Value> {
+        let mut segments = key.split('.');
+        let first = segments.next()?;
+        let mut entry = self.locales.get(locale)?.get(first)?;
+        for segment in segments {
+            entry = entry.as_object()?.get(segment)?;
+        }
+        Some(entry)
+    }
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self {
+            default_locale: "en".to_owned(),
+            locales: HashMap::new(),
+        }
+    }
+}
+
+impl Plugin for Catalog {
+    fn register<P>(&self, builder: ParserBuilder<P>) -> ParserBuilder<P>
+    where
+        P: partials::PartialCompiler,
+    {
+        let catalog = Arc::new(self.clone());
+        builder
+            .filter(Translate {
+                catalog: catalog.clone(),
+            })
+            .tag(TranslateTag { catalog })
+    }
+}
+
+/// The text of `entry`, in the plural form of `count` when it has plural forms.
+fn select<'e>(entry: &'e Value, count: Option<f64>, locale: &str) -> Option<&'e str> {
+    let forms = match *entry {
+        Value::Scalar(ref text) => return text.as_str(),
+        Value::Object(ref forms) => forms,
+        _ => return None,
+    };
+    let count = count?;
+    let category = plural_category(&language(locale), count, false);
+    let form = match forms.get("zero") {
+        Some(zero) if count == 0.0 => zero,
+        _ => forms.get(category).or_else(|| forms.get("other"))?,
+    };
+    form.as_scalar()?.as_str()
+}
+
+/// Replace the `{{ name }}` of `text` by the arguments of the same name, leaving the others.
+fn interpolate(text: &str, arguments: &Object) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end + 2,
+            None => break,
+        };
+        output.push_str(&rest[..start]);
+        let name = rest[start + 2..end - 2].trim();
+        match arguments.get(name) {
+            Some(value) => output.push_str(&value.to_str()),
+            None => output.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The CLDR plural category of `n` in languages like `language`, for the most used languages.
+///
+/// Other languages use the English rules.
+pub(crate) fn plural_category(language: &str, n: f64, ordinal: bool) -> &'static str {
+    let is_integer = n.fract() == 0.0;
+    let i = n.abs().trunc() as u64;
+    let (i10, i100) = (i % 10, i % 100);
+    if ordinal {
+        return match language {
+            "en" => match (i10, i100) {
+                (1, _) if i100 != 11 => "one",
+                (2, _) if i100 != 12 => "two",
+                (3, _) if i100 != 13 => "few",
+                _ => "other",
+            },
+            "fr" if i == 1 => "one",
+            _ => "other",
+        };
+    }
+    match language {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "tr" => "other",
+        "fr" | "pt-br" => {
+            if i <= 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "ru" | "uk" | "be" | "sr" | "hr" | "bs" => match (is_integer, i10, i100) {
+            (false, _, _) => "other",
+            (true, 1, _) if i100 != 11 => "one",
+            (true, 2..=4, _) if !(12..=14).contains(&i100) => "few",
+            _ => "many",
+        },
+        "pl" => match (is_integer, i10, i100) {
+            (false, _, _) => "other",
+            _ if i == 1 => "one",
+            (true, 2..=4, _) if !(12..=14).contains(&i100) => "few",
+            _ => "many",
+        },
+        "cs" | "sk" => match (is_integer, i) {
+            (true, 1) => "one",
+            (true, 2..=4) => "few",
+            (false, _) => "many",
+            _ => "other",
+        },
+        _ => {
+            if is_integer && i == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    }
+}
+
+/// The language of a locale like `en-US` or `pt_BR`, as `plural_category` knows it.
+pub(crate) fn language(locale: &str) -> String {
+    let locale = locale.to_lowercase().replace('_', "-");
+    if locale == "pt-br" {
+        return locale;
+    }
+    locale.split('-').next().unwrap_or("").to_owned()
+}
+
+/// Evaluate the `name: value` arguments of a translation.
+fn evaluate_arguments(arguments: &[(String, Expression)], context: &Context) -> Result<Object> {
+    let mut values = Object::new();
+    for (name, value) in arguments {
+        values.insert(name.clone().into(), value.evaluate(context)?.clone());
+    }
+    Ok(values)
+}
+
+fn write_arguments(f: &mut fmt::Formatter, arguments: &[(String, Expression)]) -> fmt::Result {
+    for (i, (name, value)) in arguments.iter().enumerate() {
+        let separator = if i == 0 { "" } else { ", " };
+        write!(f, "{}{}: {}", separator, name, value)?;
+    }
+    Ok(())
+}
+
+/// Translates its input key, e.g. `{{ 'cart.items' | t: count: cart.item_count }}`.
+///
+/// Registered by registering its `Catalog` as a plugin.
+#[derive(Clone, Debug, FilterReflection)]
+#[filter(
+    name = "t",
+    description = "Translates a key in the locale of the render, with named arguments for its interpolations and plural form."
+)]
+pub struct Translate {
+    catalog: Arc<Catalog>,
+}
+
+impl ParseFilter for Translate {
+    fn parse(&self, mut arguments: FilterArguments) -> Result<Box<dyn Filter>> {
+        if arguments.positional.next().is_some() {
+            return Error::with_msg("Invalid number of positional arguments")
+                .context("cause", "expected at most 0 positional arguments")
+                .into_err();
+        }
+        let arguments = arguments
+            .keyword
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect();
+
+        Ok(Box::new(TranslateFilter {
+            catalog: self.catalog.clone(),
+            arguments,
+        }))
+    }
+
+    fn reflection(&self) -> &dyn FilterReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct TranslateFilter {
+    catalog: Arc<Catalog>,
+    arguments: Vec<(String, Expression)>,
+}
+
+impl fmt::Display for TranslateFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "t")?;
+        if !self.arguments.is_empty() {
+            write!(f, ": ")?;
+            write_arguments(f, &self.arguments)?;
+        }
+        Ok(())
+    }
+}
+
+impl Filter for TranslateFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let arguments = evaluate_arguments(&self.arguments, context)?;
+        let locale = context.environment().locale();
+        let translation = self
+            .catalog
+            .translate(locale, &input.to_str(), &arguments)?;
+        Ok(Value::scalar(translation))
+    }
+}
+
+#[derive(Debug)]
+struct Translation {
+    catalog: Arc<Catalog>,
+    key: Expression,
+    arguments: Vec<(String, Expression)>,
+}
+
+impl Translation {
+    fn trace(&self) -> String {
+        let mut trace = format!("{{% t {}", self.key);
+        for (name, value) in &self.arguments {
+            trace.push_str(&format!(", {}: {}", name, value));
+        }
+        trace.push_str(" %}");
+        trace
+    }
+}
+
+impl Renderable for Translation {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let key = self
+            .key
+            .evaluate(context)
+            .trace_with(|| self.trace().into())?;
+        let arguments =
+            evaluate_arguments(&self.arguments, context).trace_with(|| self.trace().into())?;
+        let locale = context.environment().locale();
+        let translation = self
+            .catalog
+            .translate(locale, &key.to_str(), &arguments)
+            .trace_with(|| self.trace().into())?;
+        let translation = Value::scalar(translation);
+        let (rendered, safe) = context.environment().render_output(&translation)?;
+        writer
+            .write_value(&rendered, safe)
+            .replace("Failed to render")?;
+        Ok(())
+    }
+
+    fn references(&self, references: &mut References) {
+        references.expression(&self.key);
+        for (_, value) in &self.arguments {
+            references.expression(value);
+        }
+    }
+}
+
+/// Translates a key like the `t` filter, e.g. `{% t 'cart.items', count: cart.item_count %}`.
+///
+/// Registered by registering its `Catalog` as a plugin.
+#[derive(Clone, Debug)]
+pub struct TranslateTag {
+    catalog: Arc<Catalog>,
+}
+
+impl TagReflection for TranslateTag {
+    fn tag(&self) -> &'static str {
+        "t"
+    }
+
+    fn description(&self) -> &'static str {
+        "Translates a key in the locale of the render, like the `t` filter."
+    }
+
+    fn example(&self) -> Option<&'static str> {
+        Some("{% t 'cart.items', count: cart.item_count %}")
+    }
+}
+
+impl ParseTag for TranslateTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let key = arguments
+            .expect_next("Key expected.")?
+            .expect_value()
+            .into_result()?;
+
+        let mut translation_arguments = Vec::new();
+        while let Some(token) = arguments.next() {
+            token
+                .expect_str(",")
+                .into_result_custom_msg("\",\" expected.")?;
+            let name = arguments
+                .expect_next("Identifier expected.")?
+                .expect_identifier()
+                .into_result()?
+                .to_owned();
+            arguments
+                .expect_next("\":\" expected.")?
+                .expect_str(":")
+                .into_result_custom_msg("\":\" expected.")?;
+            let value = arguments
+                .expect_next("Value expected.")?
+                .expect_value()
+                .into_result()?;
+            translation_arguments.push((name, value));
+        }
+
+        Ok(Box::new(Translation {
+            catalog: self.catalog.clone(),
+            key,
+            arguments: translation_arguments,
+        }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use interpreter::Environment;
+    use value::liquid_value;
+
+    fn catalog() -> Catalog {
+        let en = liquid_value!({
+            "cart": {
+                "items": {"zero": "Empty", "one": "{{ count }} item", "other": "{{ count }} items"},
+                "title": "Cart of {{ name }}",
+            },
+            "footer": "Thanks",
+        });
+        let pl = liquid_value!({
+            "cart": {
+                "items": {
+                    "one": "{{ count }} produkt",
+                    "few": "{{ count }} produkty",
+                    "many": "{{ count }} produktów",
+                },
+            },
+        });
+        Catalog::new()
+            .with_locale("en", en.into_object().unwrap())
+            .with_locale("pl", pl.into_object().unwrap())
+    }
+
+    fn translate(locale: &str, key: &str, count: Option<i32>) -> String {
+        let mut arguments = Object::new();
+        if let Some(count) = count {
+            arguments.insert("count".into(), Value::scalar(count));
+        }
+        catalog().translate(locale, key, &arguments).unwrap()
+    }
+
+    #[test]
+    fn plural_forms() {
+        assert_eq!(translate("en", "cart.items", Some(0)), "Empty");
+        assert_eq!(translate("en", "cart.items", Some(1)), "1 item");
+        assert_eq!(translate("en", "cart.items", Some(3)), "3 items");
+        assert_eq!(translate("pl", "cart.items", Some(3)), "3 produkty");
+        assert_eq!(translate("pl", "cart.items", Some(5)), "5 produktów");
+        // Without a `zero` form, 0 takes the plural form of the locale.
+        assert_eq!(translate("pl", "cart.items", Some(0)), "0 produktów");
+    }
+
+    #[test]
+    fn fallbacks() {
+        assert_eq!(translate("pl-PL", "cart.items", Some(1)), "1 produkt");
+        assert_eq!(translate("pl_PL", "footer", None), "Thanks");
+        assert_eq!(
+            translate("pl", "cart.total", None),
+            "translation missing: pl.cart.total"
+        );
+        // Plural forms need a count.
+        assert_eq!(
+            translate("en", "cart.items", None),
+            "translation missing: en.cart.items"
+        );
+        assert_eq!(
+            translate("en", "footer.text", None),
+            "translation missing: en.footer.text"
+        );
+    }
+
+    #[test]
+    fn interpolation() {
+        let mut arguments = Object::new();
+        arguments.insert("name".into(), Value::scalar("Ana"));
+        assert_eq!(
+            catalog().translate("en", "cart.title", &arguments).unwrap(),
+            "Cart of Ana"
+        );
+        assert_eq!(
+            catalog()
+                .translate("en", "cart.title", &Object::new())
+                .unwrap(),
+            "Cart of {{ name }}"
+        );
+
+        arguments.insert("count".into(), Value::scalar("many"));
+        assert!(catalog().translate("en", "cart.items", &arguments).is_err());
+    }
+
+    #[test]
+    fn filter_and_tag() {
+        let template = ParserBuilder::with_liquid()
+            .plugin(catalog())
+            .environment(Environment::new().with_locale("pl-PL"))
+            .build()
+            .unwrap()
+            .parse("{{ 'cart.items' | t: count: n }}|{% t 'cart.title', name: who %}")
+            .unwrap();
+        let globals = liquid_value!({"n": 2, "who": "Jan"});
+        let globals = globals.as_object().unwrap();
+        assert_eq!(template.render(globals).unwrap(), "2 produkty|Cart of Jan");
+        assert_eq!(
+            template.with_locale("en").render(globals).unwrap(),
+            "2 items|Cart of Jan"
+        );
+        assert_eq!(template.variables(), vec!["n".to_owned(), "who".to_owned()]);
+
+        let parser = ParserBuilder::with_liquid()
+            .plugin(catalog())
+            .build()
+            .unwrap();
+        assert!(parser.parse("{{ 'footer' | t: 1 }}").is_err());
+        assert!(parser.parse("{% t %}").is_err());
+        assert!(parser.parse("{% t 'footer' name: 1 %}").is_err());
+    }
+}
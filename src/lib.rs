@@ -21,9 +21,20 @@
 //! ```
 
 extern crate chrono;
+#[cfg(feature = "timezone-names")]
+extern crate chrono_tz;
 extern crate deunicode;
+#[cfg(feature = "emoji-filters")]
+extern crate emojis;
+#[cfg(feature = "crypto-filters")]
+extern crate hmac;
 extern crate itertools;
+#[cfg(feature = "crypto-filters")]
+extern crate md5;
+#[cfg(feature = "markdown-filters")]
+extern crate pulldown_cmark;
 extern crate regex;
+extern crate unicode_normalization;
 extern crate unicode_segmentation;
 extern crate url;
 
@@ -31,8 +42,14 @@ extern crate url;
 extern crate lazy_static;
 #[cfg(feature = "serde")]
 extern crate serde;
-#[cfg(test)]
+#[cfg(feature = "json-globals")]
+extern crate serde_json;
+#[cfg(any(test, feature = "template-tests", feature = "yaml-globals"))]
 extern crate serde_yaml;
+#[cfg(feature = "crypto-filters")]
+extern crate sha1;
+#[cfg(feature = "crypto-filters")]
+extern crate sha2;
 
 extern crate liquid_compiler;
 extern crate liquid_derive;
@@ -40,8 +57,10 @@ extern crate liquid_error;
 extern crate liquid_interpreter;
 extern crate liquid_value;
 
+mod compiled;
 mod parser;
 mod reflection;
+mod report;
 mod template;
 
 /// Allows `liquid-derive` macros to work inside this crate.
@@ -68,14 +87,34 @@ pub mod derive {
     pub use liquid_derive::*;
 }
 
+pub mod cache;
+pub mod coverage;
+pub mod differential;
 pub mod filters;
+#[cfg(any(feature = "json-globals", feature = "yaml-globals"))]
+pub mod globals;
+pub mod i18n;
 pub mod partials;
+pub mod placeholders;
+pub mod plugin;
+pub mod profiler;
+pub mod sandbox;
+pub mod schema;
+pub mod source_map;
+pub mod spill;
 pub mod tags;
+#[cfg(feature = "template-tests")]
+pub mod testing;
 
-pub use interpreter::ValueStore;
+pub use interpreter::{GlobalsProvider, LazyGlobals, ObjectSource, SharedGlobals, SourceStore};
+pub use interpreter::{ValueStore, ViewStore};
+pub use liquid_derive::{template, template_str};
 pub use liquid_error::Error;
+pub use liquid_value::{from_value, to_value};
 pub use parser::*;
+pub use plugin::Plugin;
 pub use reflection::*;
+pub use report::*;
 pub use template::*;
 
 #[macro_use]
@@ -4,12 +4,14 @@ use std::path;
 use std::sync;
 
 use liquid_compiler as compiler;
-use liquid_error::{Result, ResultLiquidExt, ResultLiquidReplaceExt};
+use liquid_error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
 use liquid_interpreter as interpreter;
 
 use super::Template;
+use compiled;
 use filters;
 use partials;
+use plugin;
 use reflection;
 use tags;
 
@@ -18,6 +20,16 @@ use tags;
 /// This is the recommended policy.  See `liquid::partials` for more options.
 pub type Partials = partials::EagerCompiler<partials::InMemorySource>;
 
+/// How to handle registering a tag, block or filter under a name that is already taken.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The last registration wins.
+    #[default]
+    Replace,
+    /// `ParserBuilder::build` fails.
+    Error,
+}
+
 pub struct ParserBuilder<P = Partials>
 where
     P: partials::PartialCompiler,
@@ -26,6 +38,15 @@ where
     tags: compiler::PluginRegistry<Box<dyn compiler::ParseTag>>,
     filters: compiler::PluginRegistry<Box<dyn compiler::ParseFilter>>,
     partials: Option<P>,
+    environment: interpreter::Environment,
+    warning_handler: Option<compiler::WarningHandler>,
+    loop_shadowing: compiler::LoopShadowing,
+    recovery: compiler::Recovery,
+    dynamic_partials: compiler::DynamicPartials,
+    arithmetic: bool,
+    instrument: Option<compiler::InstrumentHandle>,
+    conflict_policy: ConflictPolicy,
+    registration_errors: Vec<Error>,
 }
 
 impl ParserBuilder<Partials> {
@@ -54,9 +75,12 @@ where
             .tag(tags::BreakTag)
             .tag(tags::ContinueTag)
             .tag(tags::CycleTag)
+            .tag(tags::EchoTag)
             .tag(tags::IncludeTag)
             .tag(tags::IncrementTag)
             .tag(tags::DecrementTag)
+            .tag(tags::LiquidTag)
+            .tag(tags::RenderTag)
     }
 
     /// Register built-in Liquid blocks
@@ -78,6 +102,10 @@ where
             .filter(filters::std::Append)
             .filter(filters::std::AtLeast)
             .filter(filters::std::AtMost)
+            .filter(filters::std::Base64Decode)
+            .filter(filters::std::Base64Encode)
+            .filter(filters::std::Base64UrlSafeDecode)
+            .filter(filters::std::Base64UrlSafeEncode)
             .filter(filters::std::Capitalize)
             .filter(filters::std::Ceil)
             .filter(filters::std::Compact)
@@ -88,8 +116,11 @@ where
             .filter(filters::std::Downcase)
             .filter(filters::std::Escape)
             .filter(filters::std::EscapeOnce)
+            .filter(filters::std::Find)
+            .filter(filters::std::FindIndex)
             .filter(filters::std::First)
             .filter(filters::std::Floor)
+            .filter(filters::std::GroupBy)
             .filter(filters::std::Join)
             .filter(filters::std::Last)
             .filter(filters::std::Lstrip)
@@ -97,6 +128,7 @@ where
             .filter(filters::std::Minus)
             .filter(filters::std::Modulo)
             .filter(filters::std::NewlineToBr)
+            .filter(filters::std::NormalizeWhitespace)
             .filter(filters::std::Plus)
             .filter(filters::std::Prepend)
             .filter(filters::std::Remove)
@@ -106,14 +138,18 @@ where
             .filter(filters::std::Reverse)
             .filter(filters::std::Round)
             .filter(filters::std::Rstrip)
+            .filter(filters::std::Safe)
+            .filter_alias("raw", "safe")
             .filter(filters::std::Size)
             .filter(filters::std::Slice)
+            .filter(filters::std::Slugify)
             .filter(filters::std::Sort)
             .filter(filters::std::SortNatural)
             .filter(filters::std::Split)
             .filter(filters::std::Strip)
             .filter(filters::std::StripHtml)
             .filter(filters::std::StripNewlines)
+            .filter(filters::std::Sum)
             .filter(filters::std::Times)
             .filter(filters::std::Truncate)
             .filter(filters::std::TruncateWords)
@@ -121,6 +157,18 @@ where
             .filter(filters::std::Upcase)
             .filter(filters::std::UrlDecode)
             .filter(filters::std::UrlEncode)
+            .filter(filters::std::Where)
+            .markdown_filters()
+    }
+
+    #[cfg(not(feature = "markdown-filters"))]
+    fn markdown_filters(self) -> Self {
+        self
+    }
+
+    #[cfg(feature = "markdown-filters")]
+    fn markdown_filters(self) -> Self {
+        self.filter(filters::std::Markdownify)
     }
 
     /// Register non-standard filters
@@ -132,8 +180,99 @@ where
     /// Register non-standard filters
     #[cfg(feature = "extra-filters")]
     pub fn extra_filters(self) -> Self {
-        self.filter(filters::extra::DateInTz)
+        self.filter(filters::extra::AnchorizeHeadings)
+            .filter(filters::extra::CdataEscape)
+            .filter(filters::extra::ColorContrast)
+            .filter(filters::extra::ColorDarken)
+            .filter(filters::extra::ColorLighten)
+            .filter(filters::extra::ColorMix)
+            .filter(filters::extra::ColorToRgb)
+            .filter(filters::extra::ConvertUnit)
+            .filter(filters::extra::DateInTz)
+            .filter(filters::extra::Diff)
+            .filter(filters::extra::Equals)
+            .filter(filters::extra::Excerpt)
+            .filter(filters::extra::Inspect)
+            .filter(filters::extra::Json)
+            .filter(filters::extra::Money::new())
+            .filter(filters::extra::MoneyWithCurrency::new())
+            .filter(filters::extra::NumberWithDelimiter::new())
+            .filter(filters::extra::ParseCsv)
+            .filter(filters::extra::Partition)
+            .filter(filters::extra::PartitionExp)
             .filter(filters::extra::Pluralize)
+            .filter(filters::extra::SlicePages)
+            .filter(filters::extra::StripMarkdown)
+            .filter(filters::extra::ToCsv)
+            .filter(filters::extra::Toc)
+            .filter(filters::extra::TypeOf)
+            .filter(filters::extra::UrlJoin)
+            .filter(filters::extra::UrlParse)
+            .filter(filters::extra::UrlRemoveParam)
+            .filter(filters::extra::UrlSetParam)
+            .filter(filters::extra::WeightWithUnit::new())
+            .filter(filters::extra::Xml)
+            .filter(filters::extra::XmlEscape)
+            .emoji_filters()
+            .query_filters()
+            .crypto_filters()
+            .similarity_filters()
+            .icu_filters()
+    }
+
+    #[cfg(not(feature = "emoji-filters"))]
+    fn emoji_filters(self) -> Self {
+        self
+    }
+
+    #[cfg(feature = "emoji-filters")]
+    fn emoji_filters(self) -> Self {
+        self.filter(filters::extra::Emojify)
+    }
+
+    #[cfg(not(feature = "query-filters"))]
+    fn query_filters(self) -> Self {
+        self
+    }
+
+    #[cfg(feature = "query-filters")]
+    fn query_filters(self) -> Self {
+        self.filter(filters::extra::Query)
+    }
+
+    #[cfg(not(feature = "crypto-filters"))]
+    fn crypto_filters(self) -> Self {
+        self
+    }
+
+    #[cfg(feature = "crypto-filters")]
+    fn crypto_filters(self) -> Self {
+        self.filter(filters::extra::HmacSha256)
+            .filter(filters::extra::Md5)
+            .filter(filters::extra::Sha1)
+            .filter(filters::extra::Sha256)
+    }
+
+    #[cfg(not(feature = "similarity-filters"))]
+    fn similarity_filters(self) -> Self {
+        self
+    }
+
+    #[cfg(feature = "similarity-filters")]
+    fn similarity_filters(self) -> Self {
+        self.filter(filters::extra::DiffWords)
+            .filter(filters::extra::Levenshtein)
+            .filter(filters::extra::Similar)
+    }
+
+    #[cfg(not(feature = "icu-filters"))]
+    fn icu_filters(self) -> Self {
+        self
+    }
+
+    #[cfg(feature = "icu-filters")]
+    fn icu_filters(self) -> Self {
+        self.filter(filters::extra::IcuFormat)
     }
 
     /// Register non-standard filters
@@ -145,35 +284,99 @@ where
     /// Register non-standard filters
     #[cfg(feature = "jekyll-filters")]
     pub fn jekyll_filters(self) -> Self {
-        self.filter(filters::jekyll::Slugify)
-            .filter(filters::jekyll::Pop)
+        self.filter(filters::jekyll::Pop)
+            .filter(filters::jekyll::Sample)
             .filter(filters::jekyll::Push)
             .filter(filters::jekyll::Shift)
             .filter(filters::jekyll::Unshift)
             .filter(filters::jekyll::ArrayToSentenceString)
+            .filter(filters::jekyll::AbsoluteUrl::new())
+            .filter(filters::jekyll::RelativeUrl::new())
+            .filter(filters::jekyll::Smartify)
+            .filter(filters::jekyll::WhereExp)
+            .filter(filters::jekyll::GroupByExp)
+            .filter(filters::jekyll::Jsonify)
+            .filter(filters::jekyll::XmlEscape)
+            .filter(filters::jekyll::CgiEscape)
+            .filter(filters::jekyll::UriEscape)
+    }
+
+    /// Set how to handle registering a tag, block or filter under a name that is already taken.
+    ///
+    /// This only applies to later registrations.
+    pub fn on_conflict(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
     }
 
     /// Inserts a new custom block into the parser
     pub fn block<B: Into<Box<dyn compiler::ParseBlock>>>(mut self, block: B) -> Self {
         let block = block.into();
-        self.blocks.register(block.reflection().start_tag(), block);
+        let name = block.reflection().start_tag();
+        self.check_conflict("block", name, self.blocks.get(name).is_some());
+        self.blocks.register(name, block);
         self
     }
 
     /// Inserts a new custom tag into the parser
     pub fn tag<T: Into<Box<dyn compiler::ParseTag>>>(mut self, tag: T) -> Self {
         let tag = tag.into();
-        self.tags.register(tag.reflection().tag(), tag);
+        let name = tag.reflection().tag();
+        self.check_conflict("tag", name, self.tags.get(name).is_some());
+        self.tags.register(name, tag);
         self
     }
 
     /// Inserts a new custom filter into the parser
     pub fn filter<F: Into<Box<dyn compiler::ParseFilter>>>(mut self, filter: F) -> Self {
+        let filter = filter.into();
+        let name = filter.reflection().name();
+        self.check_conflict("filter", name, self.filters.get(name).is_some());
+        self.filters.register(name, filter);
+        self
+    }
+
+    /// Replaces a filter with the same name, regardless of the `ConflictPolicy`.
+    pub fn override_filter<F: Into<Box<dyn compiler::ParseFilter>>>(mut self, filter: F) -> Self {
         let filter = filter.into();
         self.filters.register(filter.reflection().name(), filter);
         self
     }
 
+    /// Makes the filter registered as `name` also available as `alias`.
+    ///
+    /// The alias refers to the filter registered at the time of the call.
+    pub fn filter_alias(mut self, alias: &'static str, name: &str) -> Self {
+        let filter = match self.filters.get(name) {
+            Some(filter) => filter.clone(),
+            None => {
+                self.registration_errors.push(
+                    Error::with_msg("Cannot alias an unknown filter")
+                        .context("alias", alias)
+                        .context("filter", name.to_owned()),
+                );
+                return self;
+            }
+        };
+        self.check_conflict("filter", alias, self.filters.get(alias).is_some());
+        self.filters.register(alias, filter);
+        self
+    }
+
+    fn check_conflict(&mut self, kind: &'static str, name: &'static str, exists: bool) {
+        if exists && self.conflict_policy == ConflictPolicy::Error {
+            self.registration_errors.push(
+                Error::with_msg(format!("A {} with this name is already registered", kind))
+                    .context(kind, name),
+            );
+        }
+    }
+
+    /// Inserts a bundle of tags, blocks and filters into the parser
+    pub fn plugin<T: plugin::Plugin>(self, plugin: T) -> Self {
+        plugin.register(self)
+    }
+
     /// Set which partial-templates will be available.
     pub fn partials<N: partials::PartialCompiler>(self, partials: N) -> ParserBuilder<N> {
         let Self {
@@ -181,15 +384,93 @@ where
             tags,
             filters,
             partials: _partials,
+            environment,
+            warning_handler,
+            loop_shadowing,
+            recovery,
+            dynamic_partials,
+            arithmetic,
+            instrument,
+            conflict_policy,
+            registration_errors,
         } = self;
         ParserBuilder {
             blocks,
             tags,
             filters,
             partials: Some(partials),
+            environment,
+            warning_handler,
+            loop_shadowing,
+            recovery,
+            dynamic_partials,
+            arithmetic,
+            instrument,
+            conflict_policy,
+            registration_errors,
         }
     }
 
+    /// Receive the warnings found while parsing, like uses of deprecated filters and tags.
+    ///
+    /// Parsing continues as normal; this is for reporting problems to template authors.
+    pub fn on_warning<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(compiler::Warning) + Send + Sync + 'static,
+    {
+        self.warning_handler = Some(sync::Arc::new(handler));
+        self
+    }
+
+    /// Check whether `for` and `tablerow` variables shadow those of enclosing loops or ones
+    /// assigned earlier, warning about them or failing the parse.
+    pub fn loop_shadowing(mut self, policy: compiler::LoopShadowing) -> Self {
+        self.loop_shadowing = policy;
+        self
+    }
+
+    /// Keep parsing templates with unknown tags and malformed outputs, as text or as HTML
+    /// comments, e.g. for templates written by the users of a CMS.
+    ///
+    /// Each recovery is reported as a warning.  Use `on_warning`, or
+    /// `liquid::compiler::collect_warnings` to get the warnings of one parse.
+    pub fn recovery(mut self, policy: compiler::Recovery) -> Self {
+        self.recovery = policy;
+        self
+    }
+
+    /// Allow `include` names to be computed from variables and filters, e.g. to dispatch on the
+    /// type of the data rendered, optionally only to an allowlist of partials.
+    pub fn dynamic_partials(mut self, policy: compiler::DynamicPartials) -> Self {
+        self.dynamic_partials = policy;
+        self
+    }
+
+    /// Allow arithmetic, like `{{ (width + 10) * 2 }}`, and parentheses grouping the conditions
+    /// of `if` and `unless`, like `{% if (a or b) and c %}`.
+    ///
+    /// Hyphens starting a variable then negate it, `-width` being `0 - width`, rather than being
+    /// part of its name.
+    ///
+    /// This goes beyond the Liquid of Shopify, so it is off by default.
+    pub fn arithmetic(mut self, enabled: bool) -> Self {
+        self.arithmetic = enabled;
+        self
+    }
+
+    /// Observe the nodes of the templates and partials parsed, e.g. with a
+    /// `coverage::Coverage`.
+    pub fn instrument<I: compiler::Instrument + 'static>(mut self, instrument: I) -> Self {
+        self.instrument = Some(sync::Arc::new(instrument));
+        self
+    }
+
+    /// Set the locale, timezone, and clock templates are rendered with.
+    pub fn environment(mut self, environment: interpreter::Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
     /// Create a parser
     pub fn build(self) -> Result<Parser> {
         let Self {
@@ -197,18 +478,41 @@ where
             tags,
             filters,
             partials,
+            environment,
+            warning_handler,
+            loop_shadowing,
+            recovery,
+            dynamic_partials,
+            arithmetic,
+            instrument,
+            conflict_policy: _conflict_policy,
+            registration_errors,
         } = self;
 
+        if let Some(error) = registration_errors.into_iter().next() {
+            return Err(error);
+        }
+
         let mut options = compiler::Language::empty();
         options.blocks = blocks;
         options.tags = tags;
         options.filters = filters;
+        options.set_warning_handler(warning_handler);
+        options.set_loop_shadowing(loop_shadowing);
+        options.set_recovery(recovery);
+        options.set_dynamic_partials(dynamic_partials);
+        options.set_arithmetic(arithmetic);
+        options.set_instrument(instrument);
         let options = sync::Arc::new(options);
         let partials = partials
             .map(|p| p.compile(options.clone()))
             .map_or(Ok(None), |r| r.map(Some))?
             .map(|p| p.into());
-        let p = Parser { options, partials };
+        let p = Parser {
+            options,
+            partials,
+            environment: sync::Arc::new(environment),
+        };
         Ok(p)
     }
 }
@@ -223,6 +527,15 @@ where
             tags: Default::default(),
             filters: Default::default(),
             partials: Default::default(),
+            environment: Default::default(),
+            warning_handler: None,
+            loop_shadowing: Default::default(),
+            recovery: Default::default(),
+            dynamic_partials: Default::default(),
+            arithmetic: false,
+            instrument: None,
+            conflict_policy: Default::default(),
+            registration_errors: Vec::new(),
         }
     }
 }
@@ -253,10 +566,91 @@ where
     }
 }
 
+/// Changes to a `Parser`'s language for some templates, e.g. restricting user-provided templates.
+///
+/// Apply them with `Parser::with_overrides`.
+#[derive(Default, Clone)]
+pub struct LanguageOverrides {
+    blocks: Vec<Box<dyn compiler::ParseBlock>>,
+    tags: Vec<Box<dyn compiler::ParseTag>>,
+    filters: Vec<Box<dyn compiler::ParseFilter>>,
+    removed_blocks: Vec<String>,
+    removed_tags: Vec<String>,
+    removed_filters: Vec<String>,
+}
+
+impl LanguageOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a block, replacing any with the same name
+    pub fn block<B: Into<Box<dyn compiler::ParseBlock>>>(mut self, block: B) -> Self {
+        self.blocks.push(block.into());
+        self
+    }
+
+    /// Add a tag, replacing any with the same name
+    pub fn tag<T: Into<Box<dyn compiler::ParseTag>>>(mut self, tag: T) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Add a filter, replacing any with the same name
+    pub fn filter<F: Into<Box<dyn compiler::ParseFilter>>>(mut self, filter: F) -> Self {
+        self.filters.push(filter.into());
+        self
+    }
+
+    /// Make a block unavailable
+    pub fn without_block<S: Into<String>>(mut self, name: S) -> Self {
+        self.removed_blocks.push(name.into());
+        self
+    }
+
+    /// Make a tag unavailable
+    pub fn without_tag<S: Into<String>>(mut self, name: S) -> Self {
+        self.removed_tags.push(name.into());
+        self
+    }
+
+    /// Make a filter unavailable
+    pub fn without_filter<S: Into<String>>(mut self, name: S) -> Self {
+        self.removed_filters.push(name.into());
+        self
+    }
+
+    fn apply(&self, options: &mut compiler::Language) {
+        for name in &self.removed_blocks {
+            options.blocks.remove(name);
+        }
+        for name in &self.removed_tags {
+            options.tags.remove(name);
+        }
+        for name in &self.removed_filters {
+            options.filters.remove(name);
+        }
+        for block in &self.blocks {
+            options
+                .blocks
+                .register(block.reflection().start_tag(), block.clone());
+        }
+        for tag in &self.tags {
+            options.tags.register(tag.reflection().tag(), tag.clone());
+        }
+        for filter in &self.filters {
+            options
+                .filters
+                .register(filter.reflection().name(), filter.clone());
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct Parser {
     options: sync::Arc<compiler::Language>,
     partials: Option<sync::Arc<dyn interpreter::PartialStore + Send + Sync>>,
+    environment: sync::Arc<interpreter::Environment>,
 }
 
 impl Parser {
@@ -264,6 +658,35 @@ impl Parser {
         Default::default()
     }
 
+    /// Create a `Parser` with a different language, sharing partials and environment.
+    ///
+    /// Partials keep being parsed with this parser's language.  Creating the `Parser` copies the
+    /// language, so keep it around rather than calling this for every template.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let trusted = liquid::ParserBuilder::with_liquid().build().unwrap();
+    /// let restricted = trusted.with_overrides(
+    ///     &liquid::LanguageOverrides::new()
+    ///         .without_tag("include")
+    ///         .without_filter("date"),
+    /// );
+    ///
+    /// assert!(trusted.parse("{% include 'header' %}").is_ok());
+    /// assert!(restricted.parse("{% include 'header' %}").is_err());
+    /// assert!(restricted.parse("{{ 'now' | date: '%Y' }}").is_err());
+    /// ```
+    pub fn with_overrides(&self, overrides: &LanguageOverrides) -> Self {
+        let mut options = (*self.options).clone();
+        overrides.apply(&mut options);
+        Self {
+            options: sync::Arc::new(options),
+            partials: self.partials.clone(),
+            environment: self.environment.clone(),
+        }
+    }
+
     /// Parses a liquid template, returning a Template object.
     /// # Examples
     ///
@@ -280,11 +703,125 @@ impl Parser {
     /// ```
     ///
     pub fn parse(&self, text: &str) -> Result<Template> {
-        let template = compiler::parse(text, &self.options).map(interpreter::Template::new)?;
-        Ok(Template {
-            template,
+        let (elements, warnings) =
+            compiler::collect_warnings(|| compiler::parse(text, &self.options));
+        Ok(self.template(text, elements?, warnings))
+    }
+
+    /// Parses a liquid template, also compiling it to bytes that `parse_compiled` loads faster
+    /// than parsing the template again, e.g. to store with the template or embed in the binary.
+    ///
+    /// ```
+    /// let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    /// let (template, compiled) = parser.compile("Hi {{ name | upcase }}").unwrap();
+    ///
+    /// let loaded = parser.parse_compiled(&compiled).unwrap();
+    /// let mut globals = liquid::value::Object::new();
+    /// globals.insert("name".into(), liquid::value::Value::scalar("ada"));
+    /// assert_eq!(loaded.render(&globals).unwrap(), template.render(&globals).unwrap());
+    /// ```
+    pub fn compile(&self, text: &str) -> Result<(Template, Vec<u8>)> {
+        let mut chunks = Vec::new();
+        let (elements, warnings) = compiler::collect_warnings(|| {
+            compiler::parse_chunked(text, &self.options, &mut chunks)
+        });
+        let template = self.template(text, elements?, warnings);
+        Ok((template, compiled::encode(text, &chunks)))
+    }
+
+    /// Loads a template compiled by `compile`.
+    ///
+    /// A compiled template is not a serialized syntax tree: each output and tag at its top level
+    /// is parsed again, by itself, and only the text between them isn't.  Loading is faster the
+    /// more of the template is text, e.g. on the benchmarks of `benches/liquid.rs` a page of
+    /// mostly markup loads about 13 times faster than `parse`, one of mostly tags about 3 times.
+    /// Blocks are parsed whole, and checking loop shadowing, unless allowed, scans the whole
+    /// template.
+    ///
+    /// Templates should be loaded by a parser with the tags, blocks and filters of the one that
+    /// compiled them.  Templates compiled by another version of liquid are an error; parse their
+    /// source again instead.
+    pub fn parse_compiled(&self, compiled: &[u8]) -> Result<Template> {
+        let (text, chunks) = compiled::decode(compiled)?;
+        let source: sync::Arc<str> = sync::Arc::from(text);
+        let (elements, warnings) =
+            compiler::collect_warnings(|| compiler::parse_chunks(&source, &chunks, &self.options));
+        Ok(self.template(text, elements?, warnings))
+    }
+
+    /// Parses a liquid template, reporting every error it can instead of only the first, e.g.
+    /// for a template editor.
+    ///
+    /// See `compiler::parse_all_errors`.
+    ///
+    /// ```
+    /// let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    /// let errors = parser
+    ///     .parse_all_errors("{% nope %}\n{{ a | nope }}")
+    ///     .err()
+    ///     .unwrap();
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[1].line(), Some(2));
+    /// ```
+    pub fn parse_all_errors(&self, text: &str) -> ::std::result::Result<Template, Vec<Error>> {
+        let (elements, warnings) =
+            compiler::collect_warnings(|| compiler::parse_all_errors(text, &self.options));
+        Ok(self.template(text, elements?, warnings))
+    }
+
+    /// Parses a liquid template to edit it, e.g. for a language server.
+    ///
+    /// See `compiler::Document`.
+    ///
+    /// ```
+    /// let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    /// let mut document = parser.document("{{ name | upcase }}");
+    /// assert!(document.diagnostics().is_empty());
+    ///
+    /// document.edit(10..16, "nope");
+    /// assert_eq!(document.diagnostics()[0].message, "Unknown filter");
+    /// ```
+    pub fn document(&self, text: &str) -> compiler::Document {
+        compiler::Document::new(text, self.options.clone())
+    }
+
+    /// The language templates are parsed with, e.g. to document its tags, blocks and filters.
+    ///
+    /// ```
+    /// let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    /// let language = parser.language();
+    ///
+    /// let slice = language.filters().find(|f| f.name() == "slice").unwrap();
+    /// let parameters: Vec<_> = slice
+    ///     .positional_parameters()
+    ///     .iter()
+    ///     .map(|p| (p.name, p.is_optional))
+    ///     .collect();
+    /// assert_eq!(parameters, vec![("offset", false), ("length", true)]);
+    ///
+    /// assert!(language.blocks().any(|b| b.start_tag() == "for" && b.end_tag() == "endfor"));
+    /// assert!(language.tags().any(|t| t.tag() == "assign"));
+    /// ```
+    pub fn language(&self) -> &compiler::Language {
+        &self.options
+    }
+
+    fn template(
+        &self,
+        text: &str,
+        elements: Vec<Box<dyn interpreter::Renderable>>,
+        warnings: Vec<compiler::Warning>,
+    ) -> Template {
+        let template = interpreter::Template::new(elements).with_source(text);
+        let metadata = template.metadata();
+        Template {
+            template: sync::Arc::new(template),
             partials: self.partials.clone(),
-        })
+            environment: self.environment.clone(),
+            bound: Default::default(),
+            metadata: sync::Arc::new(metadata),
+            warnings: sync::Arc::new(warnings),
+        }
     }
 
     /// Parse a liquid template from a file, returning a `Result<Template, Error>`.
@@ -332,15 +869,15 @@ impl Parser {
 
 impl reflection::ParserReflection for Parser {
     fn blocks<'r>(&'r self) -> Box<Iterator<Item = &dyn compiler::BlockReflection> + 'r> {
-        Box::new(self.options.blocks.plugins().map(|p| p.reflection()))
+        Box::new(self.options.blocks())
     }
 
     fn tags<'r>(&'r self) -> Box<Iterator<Item = &dyn compiler::TagReflection> + 'r> {
-        Box::new(self.options.tags.plugins().map(|p| p.reflection()))
+        Box::new(self.options.tags())
     }
 
     fn filters<'r>(&'r self) -> Box<Iterator<Item = &dyn compiler::FilterReflection> + 'r> {
-        Box::new(self.options.filters.plugins().map(|p| p.reflection()))
+        Box::new(self.options.filters())
     }
 
     fn partials<'r>(&'r self) -> Box<Iterator<Item = &str> + 'r> {
@@ -0,0 +1,103 @@
+use std::borrow;
+use std::sync;
+use std::time;
+
+use cache::CacheStore;
+
+use super::PartialSource;
+
+/// The namespace of the `CacheStore` the partial-templates are kept in.
+const NAMESPACE: &str = "partials";
+
+/// Partial-template source code from a slow `PartialSource`, e.g. one fetching them over the
+/// network, kept in a `CacheStore`.
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// let mut source = liquid::partials::InMemorySource::new();
+/// source.add("footer", "Goodbye");
+/// let store = Arc::new(liquid::cache::MemoryStore::new());
+/// let source = liquid::partials::CachedSource::new(source, store);
+/// let parser = liquid::ParserBuilder::with_liquid()
+///     .partials(liquid::partials::LazyCompiler::new(source))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct CachedSource<S: PartialSource> {
+    source: S,
+    store: sync::Arc<dyn CacheStore>,
+    ttl: Option<time::Duration>,
+}
+
+impl<S> CachedSource<S>
+where
+    S: PartialSource,
+{
+    /// Keep the partial-templates of `source` in `store`, until they are removed from it.
+    pub fn new(source: S, store: sync::Arc<dyn CacheStore>) -> Self {
+        Self {
+            source,
+            store,
+            ttl: None,
+        }
+    }
+
+    /// Only keep the partial-templates for `ttl`.
+    pub fn with_ttl(mut self, ttl: time::Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+impl<S> PartialSource for CachedSource<S>
+where
+    S: PartialSource,
+{
+    fn contains(&self, name: &str) -> bool {
+        self.store.get(NAMESPACE, name).is_some() || self.source.contains(name)
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.source.names()
+    }
+
+    fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
+        if let Some(content) = self.store.get(NAMESPACE, name) {
+            return Some(content.into());
+        }
+        let content = self.source.try_get(name)?.into_owned();
+        self.store.set(NAMESPACE, name, content.clone(), self.ttl);
+        Some(content.into())
+    }
+
+    fn modified(&self, name: &str) -> Option<time::SystemTime> {
+        self.source.modified(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cache::MemoryStore;
+    use partials::InMemorySource;
+
+    use super::*;
+
+    #[test]
+    fn keeps_partials_in_store() {
+        let mut source = InMemorySource::new();
+        source.add("footer", "Goodbye");
+        let store = sync::Arc::new(MemoryStore::new());
+        let cached = CachedSource::new(source, store.clone());
+
+        assert_eq!(store.get(NAMESPACE, "footer"), None);
+        assert_eq!(cached.try_get("footer").unwrap(), "Goodbye");
+        assert_eq!(store.get(NAMESPACE, "footer"), Some("Goodbye".to_owned()));
+
+        store.set(NAMESPACE, "footer", "Cached".to_owned(), None);
+        assert_eq!(cached.try_get("footer").unwrap(), "Cached");
+        assert!(cached.try_get("header").is_none());
+        assert!(!cached.contains("header"));
+    }
+}
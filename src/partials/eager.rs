@@ -89,7 +89,7 @@ where
             .map(|name| {
                 let source = self.source.get(name).and_then(|s| {
                     liquid_compiler::parse(s.as_ref(), &language)
-                        .map(liquid_interpreter::Template::new)
+                        .map(|t| liquid_interpreter::Template::new(t).with_source(s.as_ref()))
                         .map(|t| {
                             let t: sync::Arc<dyn liquid_interpreter::Renderable> =
                                 sync::Arc::new(t);
@@ -0,0 +1,195 @@
+use std::borrow;
+use std::fs;
+use std::path;
+use std::time;
+
+use sandbox::FileSandbox;
+
+use super::PartialSource;
+
+/// Partial-template source code read from the files of a directory.
+///
+/// A partial is found by its path within the directory, e.g. `snippets/product.liquid`, or by
+/// that path without one of the extensions, e.g. `snippets/product`.  Names may not reach
+/// outside of the directory.
+///
+/// Files are read each time a partial is requested, so wrap this in the `PartialCompiler`
+/// caching the partials the way you need.  `LazyCompiler` compiles a file again once it has
+/// been modified.
+///
+/// ```rust
+/// let source = liquid::partials::FsSource::new("templates").with_extension("liquid");
+/// let parser = liquid::ParserBuilder::with_liquid()
+///     .partials(liquid::partials::LazyCompiler::new(source))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FsSource {
+    root: path::PathBuf,
+    sandbox: FileSandbox,
+    extensions: Vec<String>,
+    names: Vec<String>,
+}
+
+impl FsSource {
+    /// Create a repository of the partial-templates within `root`.
+    pub fn new<P: Into<path::PathBuf>>(root: P) -> Self {
+        let root = root.into();
+        let mut source = Self {
+            sandbox: FileSandbox::new().allow_root(root.clone()),
+            root,
+            extensions: Vec::new(),
+            names: Vec::new(),
+        };
+        source.scan();
+        source
+    }
+
+    /// Allow leaving `extension`, e.g. `liquid`, out of the names of partials.
+    ///
+    /// Extensions are tried in the order they are added, after the name as it is.
+    pub fn with_extension<S: Into<String>>(mut self, extension: S) -> Self {
+        let extension = extension.into();
+        let extension = extension.trim_start_matches('.').to_owned();
+        self.extensions.push(extension);
+        self.scan();
+        self
+    }
+
+    /// The directory the partial-templates are in.
+    pub fn root(&self) -> &path::Path {
+        &self.root
+    }
+
+    /// The file `name` refers to, if there is one.
+    fn resolve(&self, name: &str) -> Option<path::PathBuf> {
+        if let Ok(path) = self.sandbox.resolve(name) {
+            return Some(path);
+        }
+        self.extensions.iter().find_map(|extension| {
+            self.sandbox
+                .resolve(&format!("{}.{}", name, extension))
+                .ok()
+        })
+    }
+
+    /// Lists the names of the files in `root`, without their extension when it's one of
+    /// `extensions`.
+    fn scan(&mut self) {
+        let mut names = Vec::new();
+        let mut directories = vec![self.root.clone()];
+        while let Some(directory) = directories.pop() {
+            let entries = match fs::read_dir(&directory) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if path.is_dir() {
+                    directories.push(path);
+                    continue;
+                }
+                let relative = match path.strip_prefix(&self.root) {
+                    Ok(relative) => relative,
+                    Err(_) => continue,
+                };
+                let name = itertools::join(
+                    relative
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy()),
+                    "/",
+                );
+                let name = self
+                    .extensions
+                    .iter()
+                    .find_map(|extension| name.strip_suffix(&format!(".{}", extension)))
+                    .map(str::to_owned)
+                    .unwrap_or(name);
+                names.push(name);
+            }
+        }
+        names.sort();
+        self.names = names;
+    }
+}
+
+impl PartialSource for FsSource {
+    fn contains(&self, name: &str) -> bool {
+        self.resolve(name).is_some()
+    }
+
+    /// The names of the partial-templates when the source was created.
+    fn names(&self) -> Vec<&str> {
+        self.names.iter().map(|s| s.as_str()).collect()
+    }
+
+    fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
+        let path = self.resolve(name)?;
+        fs::read_to_string(path).ok().map(borrow::Cow::Owned)
+    }
+
+    fn modified(&self, name: &str) -> Option<time::SystemTime> {
+        let path = self.resolve(name)?;
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use partials::LazyCompiler;
+    use value::Object;
+    use ParserBuilder;
+
+    use super::*;
+
+    fn source() -> FsSource {
+        FsSource::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/input"))
+    }
+
+    #[test]
+    fn get_by_path() {
+        let source = source();
+        assert!(source.contains("example.txt"));
+        assert!(!source.contains("example"));
+        assert!(source
+            .try_get("include_with_val.txt")
+            .unwrap()
+            .contains("{{content}}"));
+        assert!(source.names().contains(&"example.txt"));
+    }
+
+    #[test]
+    fn get_without_extension() {
+        let source = source().with_extension(".txt");
+        assert!(source.contains("example"));
+        assert!(source.contains("example.txt"));
+        assert!(source.names().contains(&"example"));
+        assert!(!source.contains("missing"));
+    }
+
+    #[test]
+    fn names_stay_in_root() {
+        let source = source().with_extension("toml");
+        assert!(!source.contains("../../Cargo"));
+        assert!(source.try_get("../../Cargo.toml").is_none());
+        assert!(source
+            .try_get(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .is_none());
+    }
+
+    #[test]
+    fn include_from_files() {
+        let parser = ParserBuilder::with_liquid()
+            .partials(LazyCompiler::new(source().with_extension("txt")))
+            .build()
+            .unwrap();
+        let template = parser
+            .parse("{% include 'include_with_val', content: 'Hello' %}")
+            .unwrap();
+        let output = template.render(&Object::new()).unwrap();
+        assert_eq!(output, "Hello\n");
+    }
+}
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::sync;
+use std::time;
 
 use liquid_compiler;
 use liquid_compiler::Language;
+use liquid_error::Error;
 use liquid_error::Result;
 use liquid_interpreter;
 use liquid_interpreter::PartialStore;
@@ -18,11 +20,15 @@ use super::PartialSource;
 /// - Most partial-templates aren't used
 /// - Of the used partial-templates, they are generally used many times.
 ///
+/// Partials are compiled when first used and kept until they are invalidated through `cache`,
+/// or the source reports they were modified since.
+///
 /// Note: partial-compilation error reporting is deferred to render-time so content can still be
 /// generated even when the content is in an intermediate-state.
 #[derive(Debug)]
 pub struct LazyCompiler<S: PartialSource> {
     source: S,
+    cache: LazyCache,
 }
 
 impl<S> LazyCompiler<S>
@@ -31,7 +37,15 @@ where
 {
     /// Create an on-demand compiler for `PartialSource`.
     pub fn new(source: S) -> Self {
-        LazyCompiler { source }
+        LazyCompiler {
+            source,
+            cache: LazyCache::default(),
+        }
+    }
+
+    /// The partials compiled so far, shared with the `PartialStore` this compiles into.
+    pub fn cache(&self) -> LazyCache {
+        self.cache.clone()
     }
 }
 
@@ -50,9 +64,7 @@ where
     S: PartialSource + Default,
 {
     fn default() -> Self {
-        Self {
-            source: Default::default(),
-        }
+        Self::new(Default::default())
     }
 }
 
@@ -82,9 +94,9 @@ where
 {
     fn compile(self, language: sync::Arc<Language>) -> Result<Box<dyn PartialStore + Send + Sync>> {
         let store = LazyStore {
-            language: language,
+            language,
             source: self.source,
-            cache: sync::Mutex::new(Default::default()),
+            cache: self.cache,
         };
         Ok(Box::new(store))
     }
@@ -94,43 +106,104 @@ where
     }
 }
 
+#[derive(Clone)]
+struct Compiled {
+    modified: Option<time::SystemTime>,
+    template: Result<sync::Arc<dyn Renderable>>,
+}
+
+/// The partials compiled by a `LazyCompiler`, e.g. to compile them again after they change.
+#[derive(Clone, Default)]
+pub struct LazyCache {
+    compiled: sync::Arc<sync::Mutex<HashMap<String, Compiled>>>,
+}
+
+impl LazyCache {
+    fn lock(&self) -> sync::MutexGuard<'_, HashMap<String, Compiled>> {
+        self.compiled.lock().expect("not to be poisoned and reused")
+    }
+
+    /// Have `name` compiled again the next time it's used.  Returns whether it was compiled.
+    pub fn invalidate(&self, name: &str) -> bool {
+        self.lock().remove(name).is_some()
+    }
+
+    /// Have every partial compiled again the next time it's used.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// How many partials are compiled, or failed to.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no partial has been compiled yet.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
+
+impl fmt::Debug for LazyCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let compiled = self.lock();
+        let mut names: Vec<_> = compiled.keys().collect();
+        names.sort_unstable();
+        f.debug_struct("LazyCache")
+            .field("compiled", &names)
+            .finish()
+    }
+}
+
 struct LazyStore<S: PartialSource> {
     language: sync::Arc<Language>,
     source: S,
-    cache: sync::Mutex<HashMap<String, Result<sync::Arc<dyn liquid_interpreter::Renderable>>>>,
+    cache: LazyCache,
 }
 
 impl<S> LazyStore<S>
 where
     S: PartialSource,
 {
-    fn try_get_or_create(&self, name: &str) -> Option<sync::Arc<dyn Renderable>> {
-        let cache = self.cache.lock().expect("not to be poisoned and reused");
-        if let Some(result) = cache.get(name) {
-            result.as_ref().ok().cloned()
-        } else {
-            let s = self.source.try_get(name)?;
-            let s = s.as_ref();
-            let template = liquid_compiler::parse(s, &self.language)
-                .map(liquid_interpreter::Template::new)
-                .map(sync::Arc::new)
-                .ok()?;
-            Some(template)
+    /// The compiled `name`, compiling it unless it's cached and unmodified.
+    ///
+    /// `None` when the source doesn't have `name`.
+    fn compiled(&self, name: &str) -> Option<Result<sync::Arc<dyn Renderable>>> {
+        let modified = self.source.modified(name);
+        if let Some(compiled) = self.cache.lock().get(name) {
+            if compiled.modified == modified {
+                return Some(compiled.template.clone());
+            }
         }
+
+        // Compiling without the lock, so other partials can be rendered meanwhile.
+        let s = self.source.try_get(name)?;
+        let template = liquid_compiler::parse(s.as_ref(), &self.language)
+            .map(|t| liquid_interpreter::Template::new(t).with_source(s.as_ref()))
+            .map(|template| -> sync::Arc<dyn Renderable> { sync::Arc::new(template) });
+        self.cache.lock().insert(
+            name.to_owned(),
+            Compiled {
+                modified,
+                template: template.clone(),
+            },
+        );
+        Some(template)
+    }
+
+    fn try_get_or_create(&self, name: &str) -> Option<sync::Arc<dyn Renderable>> {
+        self.compiled(name)?.ok()
     }
 
     fn get_or_create(&self, name: &str) -> Result<sync::Arc<dyn Renderable>> {
-        let cache = self.cache.lock().expect("not to be poisoned and reused");
-        if let Some(result) = cache.get(name) {
-            result.clone()
-        } else {
-            let s = self.source.get(name)?;
-            let s = s.as_ref();
-            let template = liquid_compiler::parse(s, &self.language)
-                .map(liquid_interpreter::Template::new)
-                .map(sync::Arc::new)?;
-            Ok(template)
-        }
+        self.compiled(name).unwrap_or_else(|| {
+            // The source explains what is missing.
+            let missing = self.source.get(name).err().unwrap_or_else(|| {
+                Error::with_msg("Unknown partial-template")
+                    .context("requested partial", name.to_owned())
+            });
+            Err(missing)
+        })
     }
 }
 
@@ -163,3 +236,105 @@ where
         self.source.fmt(f)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::borrow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use interpreter::Context;
+
+    use super::*;
+
+    /// Counts the partials read, with `changing` modified on each read.
+    #[derive(Debug, Default)]
+    struct TestSource {
+        reads: AtomicUsize,
+    }
+
+    impl PartialSource for TestSource {
+        fn contains(&self, name: &str) -> bool {
+            name != "missing"
+        }
+
+        fn names(&self) -> Vec<&str> {
+            vec!["unchanged", "changing", "broken"]
+        }
+
+        fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
+            let reads = self.reads.fetch_add(1, Ordering::SeqCst) + 1;
+            match name {
+                "unchanged" => Some(format!("read {}", reads).into()),
+                "changing" => Some(format!("read {}", reads).into()),
+                "broken" => Some("{% if %}".into()),
+                _ => None,
+            }
+        }
+
+        fn modified(&self, name: &str) -> Option<time::SystemTime> {
+            match name {
+                "changing" => {
+                    let reads = self.reads.load(Ordering::SeqCst) as u64;
+                    Some(time::UNIX_EPOCH + time::Duration::from_secs(reads))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    fn render(store: &dyn PartialStore, name: &str) -> String {
+        store
+            .get(name)
+            .unwrap()
+            .render(&mut Context::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn compiles_once() {
+        let compiler = LazyCompiler::new(TestSource::default());
+        let cache = compiler.cache();
+        let store = compiler
+            .compile(sync::Arc::new(Language::default()))
+            .unwrap();
+        assert!(cache.is_empty());
+
+        assert_eq!(render(&*store, "unchanged"), "read 1");
+        assert_eq!(render(&*store, "unchanged"), "read 1");
+        assert_eq!(cache.len(), 1);
+
+        assert!(store.get("broken").is_err());
+        assert!(store.try_get("broken").is_none());
+        assert!(store.get("missing").is_err());
+        assert!(store.try_get("missing").is_none());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn invalidate() {
+        let compiler = LazyCompiler::new(TestSource::default());
+        let cache = compiler.cache();
+        let store = compiler
+            .compile(sync::Arc::new(Language::default()))
+            .unwrap();
+
+        assert_eq!(render(&*store, "unchanged"), "read 1");
+        assert!(cache.invalidate("unchanged"));
+        assert!(!cache.invalidate("unchanged"));
+        assert_eq!(render(&*store, "unchanged"), "read 2");
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn recompiles_when_modified() {
+        let compiler = LazyCompiler::new(TestSource::default());
+        let store = compiler
+            .compile(sync::Arc::new(Language::default()))
+            .unwrap();
+
+        // Each read changes the modification time, so the partial is never up to date.
+        assert_eq!(render(&*store, "changing"), "read 1");
+        assert_eq!(render(&*store, "changing"), "read 2");
+    }
+}
@@ -1,18 +1,23 @@
 use std::borrow;
 use std::fmt;
 use std::sync;
+use std::time;
 
 use liquid_compiler::Language;
 use liquid_error::Error;
 use liquid_error::Result;
 use liquid_interpreter::PartialStore;
 
+mod cached;
 mod eager;
+mod fs;
 mod inmemory;
 mod lazy;
 mod ondemand;
 
+pub use self::cached::*;
 pub use self::eager::*;
+pub use self::fs::*;
 pub use self::inmemory::*;
 pub use self::lazy::*;
 pub use self::ondemand::*;
@@ -43,6 +48,11 @@ pub trait PartialSource: fmt::Debug {
     /// Access a partial-template.
     fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>>;
 
+    /// When the partial-template last changed, if known, so caches can notice edits.
+    fn modified(&self, _name: &str) -> Option<time::SystemTime> {
+        None
+    }
+
     /// Access a partial-template
     fn get<'a>(&'a self, name: &str) -> Result<borrow::Cow<'a, str>> {
         self.try_get(name).ok_or_else(|| {
@@ -112,7 +112,7 @@ where
         let s = self.source.try_get(name)?;
         let s = s.as_ref();
         let template = liquid_compiler::parse(s, &self.language)
-            .map(liquid_interpreter::Template::new)
+            .map(|t| liquid_interpreter::Template::new(t).with_source(s))
             .map(sync::Arc::new)
             .ok()?;
         Some(template)
@@ -122,7 +122,7 @@ where
         let s = self.source.get(name)?;
         let s = s.as_ref();
         let template = liquid_compiler::parse(s, &self.language)
-            .map(liquid_interpreter::Template::new)
+            .map(|t| liquid_interpreter::Template::new(t).with_source(s))
             .map(sync::Arc::new)?;
         Ok(template)
     }
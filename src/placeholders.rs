@@ -0,0 +1,144 @@
+//! Two-phase rendering, for filling in parts of a page later.
+//!
+//! Render a template once with `{% placeholder name %}` where late-bound content goes, e.g. at
+//! build time, and then fill in the placeholders of the output when the content is known, e.g.
+//! per request:
+//!
+//! ```rust
+//! use liquid::placeholders::Prerendered;
+//! use liquid::value::liquid_value;
+//!
+//! let template = liquid::ParserBuilder::with_liquid()
+//!     .tag(liquid::tags::PlaceholderTag)
+//!     .build()
+//!     .unwrap()
+//!     .parse("<h1>{{ title }}</h1> Cart: {% placeholder cart_count %}")
+//!     .unwrap();
+//!
+//! let globals = liquid_value!({"title": "Shop"});
+//! let output = template.render(globals.as_object().unwrap()).unwrap();
+//! let page = Prerendered::new(&output);
+//!
+//! let values = liquid_value!({"cart_count": 3});
+//! assert_eq!(page.fill(values.as_object().unwrap()).unwrap(), "<h1>Shop</h1> Cart: 3");
+//! ```
+
+use liquid_error::{Error, Result};
+use liquid_value::Object;
+
+/// Starts a placeholder in the first pass' output.
+pub(crate) const START: char = '\u{E000}';
+/// Ends a placeholder in the first pass' output.
+pub(crate) const END: char = '\u{E001}';
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+/// The output of the first rendering pass, ready to have its placeholders filled in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prerendered {
+    segments: Vec<Segment>,
+}
+
+impl Prerendered {
+    /// Find the placeholders in the output of the first pass.
+    pub fn new(output: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut rest = output;
+        while let Some(start) = rest.find(START) {
+            let (text, placeholder) = rest.split_at(start);
+            let placeholder = &placeholder[START.len_utf8()..];
+            let end = match placeholder.find(END) {
+                Some(end) => end,
+                None => break,
+            };
+            if !text.is_empty() {
+                segments.push(Segment::Text(text.to_owned()));
+            }
+            segments.push(Segment::Placeholder(placeholder[..end].to_owned()));
+            rest = &placeholder[end + END.len_utf8()..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_owned()));
+        }
+        Self { segments }
+    }
+
+    /// The names of the placeholders, in order of appearance.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name.as_str()),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// Substitute `values` for the placeholders.
+    ///
+    /// Every placeholder must have a value.
+    pub fn fill(&self, values: &Object) -> Result<String> {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Placeholder(name) => {
+                    let value = values.get(name.as_str()).ok_or_else(|| {
+                        Error::with_msg("Unknown placeholder").context("placeholder", name.clone())
+                    })?;
+                    output.push_str(&value.to_str());
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_value::liquid_value;
+
+    fn marker(name: &str) -> String {
+        format!("{}{}{}", START, name, END)
+    }
+
+    #[test]
+    fn names() {
+        let output = format!("a{}b{}", marker("x"), marker("y"));
+        let page = Prerendered::new(&output);
+        assert_eq!(page.names().collect::<Vec<_>>(), vec!["x", "y"]);
+    }
+
+    #[test]
+    fn fill() {
+        let output = format!(
+            "{}, {}!{}",
+            marker("greeting"),
+            marker("name"),
+            marker("name")
+        );
+        let page = Prerendered::new(&output);
+        let values = liquid_value!({"greeting": "Hello", "name": "Ann"});
+        assert_eq!(
+            page.fill(values.as_object().unwrap()).unwrap(),
+            "Hello, Ann!Ann"
+        );
+    }
+
+    #[test]
+    fn fill_missing() {
+        let page = Prerendered::new(&marker("name"));
+        assert!(page.fill(&Object::new()).is_err());
+    }
+
+    #[test]
+    fn unterminated() {
+        let output = format!("a{}b", START);
+        let page = Prerendered::new(&output);
+        assert_eq!(page.names().count(), 0);
+        assert_eq!(page.fill(&Object::new()).unwrap(), output);
+    }
+}
@@ -0,0 +1,96 @@
+//! Bundles of host-defined Liquid features.
+//!
+//! Some template features come as a family: Shopify's `font` setting objects are only useful
+//! with `font_face` and `font_modify`, which understand their fields.  A `Plugin` keeps these
+//! together so a host can enable all of them with one call:
+//!
+//! ```rust
+//! use liquid::compiler::Filter;
+//! use liquid::derive::*;
+//! use liquid::error::Result;
+//! use liquid::interpreter::Context;
+//! use liquid::value::{Object, Value};
+//! use liquid::{ParserBuilder, Plugin};
+//!
+//! /// The setting object, exposed to templates as an object value.
+//! struct Font {
+//!     family: String,
+//!     weight: i32,
+//! }
+//!
+//! impl From<Font> for Value {
+//!     fn from(font: Font) -> Value {
+//!         let mut object = Object::new();
+//!         object.insert("family".into(), Value::scalar(font.family));
+//!         object.insert("weight".into(), Value::scalar(font.weight));
+//!         Value::Object(object)
+//!     }
+//! }
+//!
+//! #[derive(Clone, ParseFilter, FilterReflection)]
+//! #[filter(
+//!     name = "font_face",
+//!     description = "Generates a CSS `@font-face` declaration for a font.",
+//!     parsed(FontFaceFilter)
+//! )]
+//! pub struct FontFace;
+//!
+//! #[derive(Debug, Default, Display_filter)]
+//! #[name = "font_face"]
+//! struct FontFaceFilter;
+//!
+//! impl Filter for FontFaceFilter {
+//!     fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+//!         let font = input.as_object().expect("font object");
+//!         Ok(Value::scalar(format!(
+//!             "@font-face {{ font-family: {}; font-weight: {}; }}",
+//!             font["family"].to_str(),
+//!             font["weight"].to_str()
+//!         )))
+//!     }
+//! }
+//!
+//! struct FontPlugin;
+//!
+//! impl Plugin for FontPlugin {
+//!     fn register<P>(&self, builder: ParserBuilder<P>) -> ParserBuilder<P>
+//!     where
+//!         P: liquid::partials::PartialCompiler,
+//!     {
+//!         builder.filter(FontFace)
+//!     }
+//! }
+//!
+//! let template = ParserBuilder::with_liquid()
+//!     .plugin(FontPlugin)
+//!     .build()
+//!     .unwrap()
+//!     .parse("{{ settings.heading_font | font_face }}")
+//!     .unwrap();
+//!
+//! let font = Font {
+//!     family: "Lato".to_owned(),
+//!     weight: 700,
+//! };
+//! let mut settings = Object::new();
+//! settings.insert("heading_font".into(), font.into());
+//! let mut globals = Object::new();
+//! globals.insert("settings".into(), Value::Object(settings));
+//!
+//! let output = template.render(&globals).unwrap();
+//! assert_eq!(output, "@font-face { font-family: Lato; font-weight: 700; }");
+//! ```
+
+use parser::ParserBuilder;
+use partials;
+
+/// A bundle of tags, blocks and filters that are registered together.
+///
+/// Register it with `ParserBuilder::plugin`.  Later registrations replace earlier ones with the
+/// same name, so a plugin can also override built-in features.
+pub trait Plugin {
+    /// Add the plugin's features to `builder`.
+    fn register<P>(&self, builder: ParserBuilder<P>) -> ParserBuilder<P>
+    where
+        P: partials::PartialCompiler;
+}
@@ -0,0 +1,241 @@
+//! Find the slow parts of templates, like the tags and partials a page spends its time in.
+//!
+//! Templates parsed by a parser instrumented with a `Profiler` time each of their tags and
+//! outputs when rendered with `Template::render_with_profile`:
+//!
+//! ```rust
+//! use liquid::profiler::Profiler;
+//!
+//! let parser = liquid::ParserBuilder::with_liquid()
+//!     .instrument(Profiler::new())
+//!     .build()
+//!     .unwrap();
+//! let template = parser
+//!     .parse("{% for i in (1..3) %}{{ i | times: 2 }}{% endfor %}")
+//!     .unwrap();
+//! let (output, profile) = template
+//!     .render_with_profile(&liquid::value::Object::new())
+//!     .unwrap();
+//! assert_eq!(output, "246");
+//!
+//! let for_loop = &profile.nodes[0];
+//! assert_eq!(for_loop.node.kind.to_string(), "{% for %}");
+//! assert_eq!(for_loop.children.len(), 3);
+//! assert!(for_loop.duration <= profile.duration);
+//! ```
+//!
+//! Other renders of the templates are not timed, so a parser can stay instrumented.  Text is not
+//! timed either.
+
+use std::fmt;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use liquid_compiler::{Instrument, NodeInfo, NodeKind};
+use liquid_error::Result;
+use liquid_interpreter::{Context, Output, References, Renderable};
+use liquid_value::Object;
+
+/// Wraps the tags and outputs of templates to time them.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Profiler;
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler
+    }
+}
+
+impl Instrument for Profiler {
+    fn instrument(&self, node: NodeInfo, renderable: Box<dyn Renderable>) -> Box<dyn Renderable> {
+        if node.kind == NodeKind::Text {
+            return renderable;
+        }
+        Box::new(Profiled { node, renderable })
+    }
+}
+
+/// The timings of a profiled render, kept in its `Context`.
+#[derive(Debug, Default)]
+pub(crate) struct Recording {
+    // The nodes being rendered, innermost last, with their children rendered so far.
+    open: Vec<ProfileNode>,
+    nodes: Vec<ProfileNode>,
+}
+
+impl Recording {
+    pub(crate) fn into_profile(self, duration: Duration) -> Profile {
+        Profile {
+            duration,
+            nodes: self.nodes,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Profiled {
+    node: NodeInfo,
+    renderable: Box<dyn Renderable>,
+}
+
+impl Renderable for Profiled {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        if context.get_register::<Recording>().is_none() {
+            return self.renderable.render_to(writer, context);
+        }
+
+        let partial = context.stack().frame_name().map(str::to_owned);
+        context
+            .get_register_mut::<Recording>()
+            .open
+            .push(ProfileNode {
+                node: self.node.clone(),
+                partial,
+                duration: Duration::default(),
+                children: Vec::new(),
+            });
+        let started = Instant::now();
+        let result = self.renderable.render_to(writer, context);
+        let duration = started.elapsed();
+
+        let recording = context.get_register_mut::<Recording>();
+        let mut node = recording.open.pop().expect("the node was pushed");
+        node.duration = duration;
+        match recording.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => recording.nodes.push(node),
+        }
+        result
+    }
+
+    fn metadata(&self) -> Option<&Object> {
+        self.renderable.metadata()
+    }
+
+    fn references(&self, references: &mut References) {
+        self.renderable.references(references)
+    }
+}
+
+/// How long a render took, node by node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Profile {
+    /// How long the whole render took.
+    pub duration: Duration,
+    /// The top-level nodes rendered, in the order they were.
+    pub nodes: Vec<ProfileNode>,
+}
+
+impl Profile {
+    /// Every node rendered, the nested ones after those containing them.
+    pub fn flatten(&self) -> Vec<&ProfileNode> {
+        let mut flattened = Vec::new();
+        let mut pending: Vec<_> = self.nodes.iter().rev().collect();
+        while let Some(node) = pending.pop() {
+            flattened.push(node);
+            pending.extend(node.children.iter().rev());
+        }
+        flattened
+    }
+}
+
+/// How long a render of a node took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileNode {
+    pub node: NodeInfo,
+    /// The partial holding `node`, when it is not part of the rendered template itself.
+    pub partial: Option<String>,
+    /// How long the node took, including its children.
+    pub duration: Duration,
+    /// The nodes rendered by this one, e.g. the body of a loop or the nodes of an included
+    /// partial.
+    pub children: Vec<ProfileNode>,
+}
+
+impl ProfileNode {
+    /// How long the node took, not counting its children.
+    pub fn self_duration(&self) -> Duration {
+        let children = self.children.iter().map(|c| c.duration).sum();
+        self.duration.checked_sub(children).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "render: {:?}", self.duration)?;
+        let mut pending: Vec<_> = self.nodes.iter().rev().map(|n| (1, n)).collect();
+        while let Some((depth, node)) = pending.pop() {
+            write!(
+                f,
+                "{:indent$}{} at {}:{}",
+                "",
+                node.node.kind,
+                node.node.line,
+                node.node.column,
+                indent = depth * 2
+            )?;
+            if let Some(ref partial) = node.partial {
+                write!(f, " of {}", partial)?;
+            }
+            writeln!(f, ": {:?}", node.duration)?;
+            pending.extend(node.children.iter().rev().map(|n| (depth + 1, n)));
+        }
+        Ok(())
+    }
+}
+
+/// Take the timings out of `context`, once it has rendered.
+pub(crate) fn take_recording(context: &mut Context) -> Recording {
+    mem::take(context.get_register_mut::<Recording>())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_value::Object;
+    use parser::ParserBuilder;
+    use partials::{InMemorySource, LazyCompiler};
+
+    #[test]
+    fn partials() {
+        let mut source = InMemorySource::new();
+        source.add("card", "{{ card | upcase }}");
+        let parser = ParserBuilder::with_liquid()
+            .instrument(Profiler::new())
+            .partials(LazyCompiler::new(source))
+            .build()
+            .unwrap();
+        let template = parser
+            .parse("Cards:\n{% include 'card' %}{% assign x = 1 %}")
+            .unwrap();
+
+        let (output, profile) = template.render_with_profile(&Object::new()).unwrap();
+        assert_eq!(output, "Cards:\n");
+        let nodes: Vec<_> = profile
+            .flatten()
+            .iter()
+            .map(|n| {
+                (
+                    n.node.kind.to_string(),
+                    n.node.line,
+                    n.node.column,
+                    n.partial.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("{% include %}".to_owned(), 2, 4, None),
+                ("output".to_owned(), 1, 1, Some("card".to_owned())),
+                ("{% assign %}".to_owned(), 2, 24, None),
+            ]
+        );
+        let include = &profile.nodes[0];
+        assert!(include.self_duration() <= include.duration);
+
+        // Renders that don't profile aren't recorded.
+        assert_eq!(template.render(&Object::new()).unwrap(), "Cards:\n");
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use liquid_compiler as compiler;
+use liquid_interpreter as interpreter;
+
+/// What a render did, from `Template::render_with_report`.
+///
+/// Renders of the same template with the same globals report the same, but for `duration`, so
+/// the report of a known set of renders can be checked in and diffed to tell when a release
+/// changes how templates behave.  With the `serde` feature, it serializes, e.g. to JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+pub struct RenderReport {
+    /// The warnings found parsing the template.
+    pub warnings: Vec<ReportedWarning>,
+    /// The paths of the variables read that were not defined, sorted.
+    pub undefined_variables: Vec<String>,
+    /// The filters run, with how many times each was.
+    pub filters: BTreeMap<String, usize>,
+    /// The partials included or rendered, sorted.
+    pub partials: Vec<String>,
+    /// The size of the output, in bytes.
+    pub output_bytes: usize,
+    /// How long the render took.
+    pub duration: Duration,
+}
+
+impl RenderReport {
+    pub(crate) fn new(
+        warnings: &[compiler::Warning],
+        usage: &interpreter::Usage,
+        output_bytes: usize,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            warnings: warnings.iter().map(ReportedWarning::new).collect(),
+            undefined_variables: usage.undefined().map(str::to_owned).collect(),
+            filters: usage
+                .filters()
+                .map(|(name, count)| (name.to_owned(), count))
+                .collect(),
+            partials: usage.partials().map(str::to_owned).collect(),
+            output_bytes,
+            duration,
+        }
+    }
+}
+
+/// A `compiler::Warning`, in a `RenderReport`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+pub struct ReportedWarning {
+    pub message: String,
+    /// The context about the warning, in order, e.g. `("filter", "date_to_string")`.
+    pub context: Vec<(String, String)>,
+}
+
+impl ReportedWarning {
+    fn new(warning: &compiler::Warning) -> Self {
+        Self {
+            message: warning.message().to_owned(),
+            context: warning
+                .contexts()
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect(),
+        }
+    }
+}
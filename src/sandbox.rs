@@ -0,0 +1,99 @@
+//! Capabilities for trusted templates to reach outside of their globals.
+
+use std::fs;
+use std::path;
+
+use liquid_error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
+
+/// Directories that `include_raw` and `read_file` may read from.
+///
+/// Relative paths are looked up in each root, in order.  Absolute paths, or paths that escape a
+/// root through `..` or symlinks, are only allowed if they resolve to within a root.
+///
+/// ```rust
+/// let sandbox = liquid::sandbox::FileSandbox::new().allow_root("snippets");
+/// let parser = liquid::ParserBuilder::with_liquid()
+///     .tag(liquid::tags::IncludeRawTag::new(sandbox.clone()))
+///     .filter(liquid::filters::extra::ReadFile::new(sandbox))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FileSandbox {
+    roots: Vec<path::PathBuf>,
+}
+
+impl FileSandbox {
+    /// Create a sandbox that denies all reads.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow reading files within `root`.
+    pub fn allow_root<P: Into<path::PathBuf>>(mut self, root: P) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    /// Resolve `name` to a file within one of the roots.
+    pub fn resolve(&self, name: &str) -> Result<path::PathBuf> {
+        for root in &self.roots {
+            let root = match root.canonicalize() {
+                Ok(root) => root,
+                Err(_) => continue,
+            };
+            let candidate = match root.join(name).canonicalize() {
+                Ok(candidate) => candidate,
+                Err(_) => continue,
+            };
+            if candidate.starts_with(&root) && candidate.is_file() {
+                return Ok(candidate);
+            }
+        }
+
+        Error::with_msg("File is not available in the sandbox")
+            .context("path", name.to_owned())
+            .into_err()
+    }
+
+    /// Read the content of `name`.
+    pub fn read(&self, name: &str) -> Result<String> {
+        let path = self.resolve(name)?;
+        fs::read_to_string(&path)
+            .replace("Cannot read file")
+            .context_key("path")
+            .value_with(|| path.to_string_lossy().into_owned().into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sandbox() -> FileSandbox {
+        FileSandbox::new().allow_root(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/input"))
+    }
+
+    #[test]
+    fn read_within_root() {
+        assert!(sandbox().read("example.txt").is_ok());
+    }
+
+    #[test]
+    fn read_missing() {
+        assert!(sandbox().read("does_not_exist.txt").is_err());
+    }
+
+    #[test]
+    fn read_escaping_root() {
+        assert!(sandbox().read("../../../Cargo.toml").is_err());
+        assert!(sandbox()
+            .read(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .is_err());
+    }
+
+    #[test]
+    fn read_without_roots() {
+        assert!(FileSandbox::new().read("example.txt").is_err());
+    }
+}
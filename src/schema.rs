@@ -0,0 +1,317 @@
+//! Declare the globals a template expects, and check them before rendering.
+//!
+//! A missing or mistyped variable otherwise renders as blank output.  Checking the globals up
+//! front reports exactly which variable is wrong:
+//!
+//! ```rust
+//! use liquid::schema::{Kind, Schema};
+//! use liquid::value::{Object, Value};
+//!
+//! let schema = Schema::new()
+//!     .required("name", Kind::Str)
+//!     .optional("orders", Kind::array(Kind::object(Schema::new().required("total", Kind::Number))));
+//!
+//! let mut globals = Object::new();
+//! globals.insert("name".into(), Value::scalar("Ann"));
+//! assert!(schema.validate(&globals).is_ok());
+//!
+//! globals.insert("name".into(), Value::scalar(7));
+//! let error = schema.validate(&globals).unwrap_err();
+//! assert!(error.to_string().contains("variable=name"));
+//! ```
+
+use std::fmt;
+
+use liquid_error::{Error, Result};
+use liquid_value::{Object, Value};
+
+/// The type of a global.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    /// Any value.
+    Any,
+    Str,
+    /// A whole number.
+    Integer,
+    /// A whole or fractional number.
+    Number,
+    Bool,
+    Date,
+    /// An array whose elements are all of a kind.
+    Array(Box<Kind>),
+    /// An object following a schema.
+    Object(Schema),
+}
+
+impl Kind {
+    /// An array whose elements are all of `kind`.
+    pub fn array(kind: Kind) -> Self {
+        Kind::Array(Box::new(kind))
+    }
+
+    /// An object following `schema`.
+    pub fn object(schema: Schema) -> Self {
+        Kind::Object(schema)
+    }
+
+    #[cfg(feature = "serde")]
+    fn infer(value: &Value) -> Self {
+        match *value {
+            Value::Scalar(ref scalar) => match scalar.type_name() {
+                "string" => Kind::Str,
+                "whole number" => Kind::Integer,
+                "fractional number" => Kind::Number,
+                "boolean" => Kind::Bool,
+                "date" => Kind::Date,
+                _ => Kind::Any,
+            },
+            Value::Array(ref array) => {
+                let mut kinds = array.iter().map(Kind::infer);
+                match kinds.next() {
+                    Some(first) if kinds.all(|k| k == first) => Kind::array(first),
+                    _ => Kind::array(Kind::Any),
+                }
+            }
+            Value::Object(ref object) => Kind::Object(Schema::infer(object)),
+            Value::Range(_) => Kind::array(Kind::Integer),
+            // The elements aren't generated just to infer their kind.
+            Value::Lazy(_) => Kind::array(Kind::Any),
+            Value::Nil | Value::Empty | Value::Blank => Kind::Any,
+        }
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        let type_name = value.type_name();
+        match *self {
+            Kind::Any => true,
+            Kind::Str => type_name == "string",
+            Kind::Integer => type_name == "whole number",
+            Kind::Number => type_name == "whole number" || type_name == "fractional number",
+            Kind::Bool => type_name == "boolean",
+            Kind::Date => type_name == "date",
+            Kind::Array(_) => value.is_array(),
+            Kind::Object(_) => value.is_object(),
+        }
+    }
+
+    fn check(&self, path: &str, value: &Value) -> Result<()> {
+        if !self.matches(value) {
+            return Err(Error::with_msg("Mistyped variable")
+                .context("variable", path.to_owned())
+                .context("expected", self.to_string())
+                .context("actual", value.type_name()));
+        }
+
+        match *self {
+            Kind::Array(ref element) => {
+                let array = value.as_array().expect("kind was checked");
+                for (index, item) in array.iter().enumerate() {
+                    element.check(&format!("{}[{}]", path, index), item)?;
+                }
+                Ok(())
+            }
+            Kind::Object(ref schema) => {
+                let object = value.as_object().expect("kind was checked");
+                schema.check(Some(path), object)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Kind::Any => write!(f, "any value"),
+            Kind::Str => write!(f, "string"),
+            Kind::Integer => write!(f, "whole number"),
+            Kind::Number => write!(f, "number"),
+            Kind::Bool => write!(f, "boolean"),
+            Kind::Date => write!(f, "date"),
+            Kind::Array(ref element) => write!(f, "array of {}", element),
+            Kind::Object(_) => write!(f, "object"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Field {
+    name: String,
+    kind: Kind,
+    required: bool,
+}
+
+/// The names and kinds of the globals a template expects.
+///
+/// Globals the schema does not mention are accepted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derive a schema from an example, such as an instance of the struct the globals are built
+    /// from.
+    ///
+    /// Every field of the example is required, except those that are `nil` which may be anything
+    /// or missing.
+    #[cfg(feature = "serde")]
+    pub fn of<T: ::serde::Serialize>(example: &T) -> Result<Self> {
+        let value = liquid_value::to_value(example)?;
+        match value {
+            Value::Object(ref object) => Ok(Self::infer(object)),
+            _ => Err(Error::with_msg("Object expected").context("actual", value.type_name())),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn infer(object: &Object) -> Self {
+        let mut fields: Vec<_> = object
+            .iter()
+            .map(|(name, value)| Field {
+                name: name.to_string(),
+                kind: Kind::infer(value),
+                required: !value.is_nil(),
+            })
+            .collect();
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { fields }
+    }
+
+    /// Expect a global that must be present.
+    pub fn required<S: Into<String>>(mut self, name: S, kind: Kind) -> Self {
+        self.field(name.into(), kind, true);
+        self
+    }
+
+    /// Expect a global that may be missing or `nil`.
+    pub fn optional<S: Into<String>>(mut self, name: S, kind: Kind) -> Self {
+        self.field(name.into(), kind, false);
+        self
+    }
+
+    fn field(&mut self, name: String, kind: Kind, required: bool) {
+        self.fields.retain(|f| f.name != name);
+        self.fields.push(Field {
+            name,
+            kind,
+            required,
+        });
+    }
+
+    /// Check `globals` follow the schema, reporting the first missing or mistyped variable.
+    pub fn validate(&self, globals: &Object) -> Result<()> {
+        self.check(None, globals)
+    }
+
+    fn check(&self, parent: Option<&str>, object: &Object) -> Result<()> {
+        for field in &self.fields {
+            let path = match parent {
+                Some(parent) => format!("{}.{}", parent, field.name),
+                None => field.name.clone(),
+            };
+            match object.get(field.name.as_str()) {
+                None | Some(Value::Nil) if field.required => {
+                    return Err(Error::with_msg("Missing variable")
+                        .context("variable", path)
+                        .context("expected", field.kind.to_string()));
+                }
+                None | Some(Value::Nil) => (),
+                Some(value) => field.kind.check(&path, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn customer() -> Schema {
+        Schema::new()
+            .required("name", Kind::Str)
+            .optional("age", Kind::Integer)
+            .required(
+                "orders",
+                Kind::array(Kind::object(Schema::new().required("total", Kind::Number))),
+            )
+    }
+
+    fn order(total: Value) -> Value {
+        let mut order = Object::new();
+        order.insert("total".into(), total);
+        Value::Object(order)
+    }
+
+    #[test]
+    fn validate_ok() {
+        let mut globals = Object::new();
+        globals.insert("name".into(), Value::scalar("Ann"));
+        globals.insert(
+            "orders".into(),
+            Value::array(vec![order(Value::scalar(3)), order(Value::scalar(2.5))]),
+        );
+        globals.insert("extra".into(), Value::scalar(true));
+        assert!(customer().validate(&globals).is_ok());
+    }
+
+    #[test]
+    fn validate_missing() {
+        let mut globals = Object::new();
+        globals.insert("orders".into(), Value::array(vec![]));
+        let error = customer().validate(&globals).unwrap_err().to_string();
+        assert!(error.contains("Missing variable"), "{}", error);
+        assert!(error.contains("variable=name"), "{}", error);
+    }
+
+    #[test]
+    fn validate_mistyped() {
+        let mut globals = Object::new();
+        globals.insert("name".into(), Value::scalar("Ann"));
+        globals.insert("age".into(), Value::scalar("old"));
+        globals.insert("orders".into(), Value::array(vec![]));
+        let error = customer().validate(&globals).unwrap_err().to_string();
+        assert!(error.contains("Mistyped variable"), "{}", error);
+        assert!(error.contains("variable=age"), "{}", error);
+        assert!(error.contains("expected=whole number"), "{}", error);
+        assert!(error.contains("actual=string"), "{}", error);
+
+        globals.insert("age".into(), Value::scalar(40));
+        globals.insert(
+            "orders".into(),
+            Value::array(vec![order(Value::scalar(1)), order(Value::scalar("x"))]),
+        );
+        let error = customer().validate(&globals).unwrap_err().to_string();
+        assert!(error.contains("variable=orders[1].total"), "{}", error);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn schema_of() {
+        #[derive(::serde::Serialize)]
+        struct Customer {
+            name: String,
+            nickname: Option<String>,
+            points: Vec<i32>,
+        }
+
+        let schema = Schema::of(&Customer {
+            name: "Ann".to_owned(),
+            nickname: None,
+            points: vec![1],
+        })
+        .unwrap();
+        assert_eq!(
+            schema,
+            Schema::new()
+                .required("name", Kind::Str)
+                .optional("nickname", Kind::Any)
+                .required("points", Kind::array(Kind::Integer))
+        );
+    }
+}
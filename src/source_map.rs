@@ -0,0 +1,251 @@
+//! Map the output of renders back to the template nodes that wrote it, e.g. to report a broken
+//! link found in rendered HTML at the line of the template it came from.
+//!
+//! Templates parsed by a parser instrumented with a `SourceMapper` record which bytes of the
+//! output each of their text, tags and outputs wrote when rendered with
+//! `Template::render_with_source_map`:
+//!
+//! ```rust
+//! use liquid::source_map::SourceMapper;
+//!
+//! let parser = liquid::ParserBuilder::with_liquid()
+//!     .instrument(SourceMapper::new())
+//!     .build()
+//!     .unwrap();
+//! let template = parser
+//!     .parse("<h1>{{ title }}</h1>\n{% if show %}<a href=\"{{ url }}\">x</a>{% endif %}")
+//!     .unwrap();
+//! let globals = liquid::value::liquid_value!({"title": "Hi", "show": true, "url": "/x"});
+//! let (output, map) = template
+//!     .render_with_source_map(globals.as_object().unwrap())
+//!     .unwrap();
+//!
+//! let offset = output.find("/x").unwrap();
+//! let mapping = map.lookup(offset).unwrap();
+//! assert_eq!(mapping.node.kind.to_string(), "output");
+//! assert_eq!((mapping.node.line, mapping.node.column), (2, 23));
+//! ```
+//!
+//! Other renders of the templates are not mapped, so a parser can stay instrumented.  The output
+//! of nodes whose output is kept rather than written, like the body of `capture`, isn't mapped.
+
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::mem;
+use std::ops::Range;
+use std::rc::Rc;
+
+use liquid_compiler::{Instrument, NodeInfo};
+use liquid_error::Result;
+use liquid_interpreter::{Context, Output, References, Renderable};
+use liquid_value::Object;
+
+/// Wraps the nodes of templates to map the output they write.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SourceMapper;
+
+impl SourceMapper {
+    pub fn new() -> Self {
+        SourceMapper
+    }
+}
+
+impl Instrument for SourceMapper {
+    fn instrument(&self, node: NodeInfo, renderable: Box<dyn Renderable>) -> Box<dyn Renderable> {
+        Box::new(Mapped { node, renderable })
+    }
+}
+
+/// The mappings of a mapped render, kept in its `Context`.
+#[derive(Debug, Default)]
+pub(crate) struct Recording {
+    // How much of the output was written, shared with the `CountedOutput` it is written through.
+    written: Rc<Cell<usize>>,
+    mappings: Vec<Mapping>,
+}
+
+impl Recording {
+    pub(crate) fn new(written: Rc<Cell<usize>>) -> Self {
+        Self {
+            written,
+            mappings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_source_map(self) -> SourceMap {
+        let mut mappings = self.mappings;
+        // Nodes are recorded once rendered, so those containing others come after them.
+        mappings.sort_by_key(|mapping| (mapping.output.start, !mapping.output.end));
+        SourceMap { mappings }
+    }
+}
+
+/// Counts the bytes written to the output of a mapped render.
+pub(crate) struct CountedOutput<'w> {
+    writer: &'w mut dyn Output,
+    written: Rc<Cell<usize>>,
+}
+
+impl<'w> CountedOutput<'w> {
+    pub(crate) fn new(writer: &'w mut dyn Output, written: Rc<Cell<usize>>) -> Self {
+        Self { writer, written }
+    }
+}
+
+impl<'w> Write for CountedOutput<'w> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.written.set(self.written.get() + written);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<'w> Output for CountedOutput<'w> {
+    fn write_value(&mut self, value: &str, safe: bool) -> io::Result<()> {
+        self.writer.write_value(value, safe)?;
+        self.written.set(self.written.get() + value.len());
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Mapped {
+    node: NodeInfo,
+    renderable: Box<dyn Renderable>,
+}
+
+impl Renderable for Mapped {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let start = match context.get_register::<Recording>() {
+            Some(recording) => recording.written.get(),
+            None => return self.renderable.render_to(writer, context),
+        };
+
+        let result = self.renderable.render_to(writer, context);
+
+        let partial = context.stack().frame_name().map(str::to_owned);
+        let recording = context.get_register_mut::<Recording>();
+        let end = recording.written.get();
+        if start < end {
+            recording.mappings.push(Mapping {
+                output: start..end,
+                node: self.node.clone(),
+                partial,
+            });
+        }
+        result
+    }
+
+    fn metadata(&self) -> Option<&Object> {
+        self.renderable.metadata()
+    }
+
+    fn references(&self, references: &mut References) {
+        self.renderable.references(references)
+    }
+}
+
+/// Which nodes of templates wrote which bytes of the output of a render.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// The nodes that wrote to the output, in the order of the output they wrote, the nodes
+    /// containing others, like blocks, before them.
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// The innermost node that wrote the byte at `offset` of the output.
+    pub fn lookup(&self, offset: usize) -> Option<&Mapping> {
+        self.mappings
+            .iter()
+            .rev()
+            .find(|mapping| mapping.output.contains(&offset))
+    }
+}
+
+/// The output a node of a template wrote.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mapping {
+    /// The bytes of the output the node wrote, including those of the nodes it contains.
+    pub output: Range<usize>,
+    pub node: NodeInfo,
+    /// The partial holding `node`, when it is not part of the rendered template itself.
+    pub partial: Option<String>,
+}
+
+/// Take the mappings out of `context`, once it has rendered.
+pub(crate) fn take_recording(context: &mut Context) -> Recording {
+    mem::take(context.get_register_mut::<Recording>())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use parser::ParserBuilder;
+    use partials::{InMemorySource, LazyCompiler};
+
+    #[test]
+    fn mappings() {
+        let mut source = InMemorySource::new();
+        let card = "[{{ card | upcase }}]";
+        source.add("card", card);
+        let parser = ParserBuilder::with_liquid()
+            .instrument(SourceMapper::new())
+            .partials(LazyCompiler::new(source))
+            .build()
+            .unwrap();
+        let text = "Cards:\n{% include 'card', card: 'a' %}{% capture x %}b{% endcapture %}!";
+        let template = parser.parse(text).unwrap();
+
+        let (output, map) = template.render_with_source_map(&Object::new()).unwrap();
+        assert_eq!(output, "Cards:\n[A]!");
+        let mappings: Vec<_> = map
+            .mappings
+            .iter()
+            .map(|m| {
+                (
+                    m.output.clone(),
+                    m.node.kind.to_string(),
+                    match m.partial {
+                        Some(_) => &card[m.node.span.clone()],
+                        None => &text[m.node.span.clone()],
+                    },
+                    m.partial.clone(),
+                )
+            })
+            .collect();
+        let partial = Some("card".to_owned());
+        assert_eq!(
+            mappings,
+            vec![
+                (0..7, "text".to_owned(), "Cards:\n", None),
+                (
+                    7..10,
+                    "{% include %}".to_owned(),
+                    "{% include 'card', card: 'a' %}",
+                    None
+                ),
+                (7..8, "text".to_owned(), "[", partial.clone()),
+                (
+                    8..9,
+                    "output".to_owned(),
+                    "{{ card | upcase }}",
+                    partial.clone()
+                ),
+                (9..10, "text".to_owned(), "]", partial),
+                (10..11, "text".to_owned(), "!", None),
+            ]
+        );
+        assert_eq!(map.lookup(8).unwrap().node.kind.to_string(), "output");
+        assert_eq!(map.lookup(9).unwrap().node.kind.to_string(), "text");
+        assert!(map.lookup(11).is_none());
+
+        // Renders that don't map aren't recorded.
+        assert_eq!(template.render(&Object::new()).unwrap(), "Cards:\n[A]!");
+    }
+}
@@ -0,0 +1,266 @@
+//! Render large outputs without holding them in memory.
+//!
+//! A `SpillOutput` keeps the output in memory up to a threshold and moves it to a temporary
+//! file beyond that, so a template rendering a multi-hundred-MB export only ever holds that
+//! much:
+//!
+//! ```rust
+//! use std::io::Read;
+//!
+//! use liquid::spill::SpillOutput;
+//!
+//! let template = liquid::ParserBuilder::with_liquid()
+//!     .build()
+//!     .unwrap()
+//!     .parse("{% for i in (1..1000) %}{{ i }},{% endfor %}")
+//!     .unwrap();
+//!
+//! let mut output = SpillOutput::new(1024);
+//! template
+//!     .render_to_output(&mut output, &liquid::value::Object::new())
+//!     .unwrap();
+//! assert!(output.is_spilled());
+//!
+//! let mut csv = String::new();
+//! output.into_reader().unwrap().read_to_string(&mut csv).unwrap();
+//! assert!(csv.starts_with("1,2,3,"));
+//! ```
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use liquid_interpreter::Output;
+
+/// Tells apart the temporary files of a process.
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// A file removed once dropped.
+#[derive(Debug)]
+struct TempFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl TempFile {
+    fn create(dir: &Path) -> io::Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        loop {
+            let name = format!(
+                "liquid-{}-{}-{}.out",
+                process::id(),
+                NEXT_FILE.fetch_add(1, Ordering::Relaxed),
+                nanos
+            );
+            let path = dir.join(name);
+            match OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => return Ok(Self { path, file }),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[derive(Debug)]
+enum Buffer {
+    Memory(Vec<u8>),
+    File(BufWriter<TempFile>),
+}
+
+/// An `Output` that moves to a temporary file once it outgrows a threshold.
+///
+/// The temporary file is removed when the `SpillOutput`, or the `SpilledReader` made from it,
+/// is dropped.
+#[derive(Debug)]
+pub struct SpillOutput {
+    threshold: usize,
+    dir: PathBuf,
+    buffer: Buffer,
+    len: u64,
+}
+
+impl SpillOutput {
+    /// Keep up to `threshold` bytes in memory, spilling to the system's temporary directory.
+    pub fn new(threshold: usize) -> Self {
+        Self::in_dir(threshold, env::temp_dir())
+    }
+
+    /// Keep up to `threshold` bytes in memory, spilling to a file in `dir`.
+    pub fn in_dir<P: Into<PathBuf>>(threshold: usize, dir: P) -> Self {
+        Self {
+            threshold,
+            dir: dir.into(),
+            buffer: Buffer::Memory(Vec::new()),
+            len: 0,
+        }
+    }
+
+    /// The number of bytes written.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether nothing was written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the output outgrew the threshold and moved to a file.
+    pub fn is_spilled(&self) -> bool {
+        match self.buffer {
+            Buffer::Memory(_) => false,
+            Buffer::File(_) => true,
+        }
+    }
+
+    /// Read back the output, from the start.
+    pub fn into_reader(self) -> io::Result<SpilledReader> {
+        let inner = match self.buffer {
+            Buffer::Memory(data) => Reader::Memory(Cursor::new(data)),
+            Buffer::File(writer) => {
+                let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+                file.file.seek(SeekFrom::Start(0))?;
+                Reader::File(file)
+            }
+        };
+        Ok(SpilledReader { inner })
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        let mut file = BufWriter::new(TempFile::create(&self.dir)?);
+        if let Buffer::Memory(ref data) = self.buffer {
+            file.write_all(data)?;
+        }
+        self.buffer = Buffer::File(file);
+        Ok(())
+    }
+}
+
+impl Write for SpillOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = match self.buffer {
+            Buffer::Memory(ref mut data) if data.len() + buf.len() <= self.threshold => {
+                data.extend_from_slice(buf);
+                buf.len()
+            }
+            Buffer::Memory(_) => {
+                self.spill()?;
+                return self.write(buf);
+            }
+            Buffer::File(ref mut file) => file.write(buf)?,
+        };
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.buffer {
+            Buffer::Memory(_) => Ok(()),
+            Buffer::File(ref mut file) => file.flush(),
+        }
+    }
+}
+
+impl Output for SpillOutput {}
+
+#[derive(Debug)]
+enum Reader {
+    Memory(Cursor<Vec<u8>>),
+    File(TempFile),
+}
+
+/// The output of a `SpillOutput`, read from memory or its temporary file.
+#[derive(Debug)]
+pub struct SpilledReader {
+    inner: Reader,
+}
+
+impl Read for SpilledReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner {
+            Reader::Memory(ref mut data) => data.read(buf),
+            Reader::File(ref mut file) => file.file.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(output: SpillOutput) -> String {
+        let mut text = String::new();
+        output
+            .into_reader()
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn below_threshold() {
+        let mut output = SpillOutput::new(8);
+        output.write_all(b"12345678").unwrap();
+        assert!(!output.is_spilled());
+        assert_eq!(output.len(), 8);
+        assert_eq!(read(output), "12345678");
+    }
+
+    #[test]
+    fn spills_and_cleans_up() {
+        let mut output = SpillOutput::new(4);
+        output.write_all(b"abc").unwrap();
+        output.write_value("defg", false).unwrap();
+        assert!(output.is_spilled());
+        assert_eq!(output.len(), 7);
+
+        let path = match output.buffer {
+            Buffer::File(ref file) => file.get_ref().path.clone(),
+            Buffer::Memory(_) => unreachable!(),
+        };
+        let mut reader = output.into_reader().unwrap();
+        assert!(path.exists());
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "abcdefg");
+
+        drop(reader);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn missing_dir() {
+        let mut output = SpillOutput::in_dir(0, "/nonexistent/liquid");
+        assert!(output.write_all(b"a").is_err());
+    }
+}
@@ -1,5 +1,3 @@
-use std::io::Write;
-
 use liquid_error::Result;
 use liquid_error::ResultLiquidExt;
 
@@ -9,11 +7,15 @@ use compiler::ParseTag;
 use compiler::TagReflection;
 use compiler::TagTokenIter;
 use interpreter::Context;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
+use interpreter::Variable;
+use value::Scalar;
 
 #[derive(Debug)]
 struct Assign {
-    dst: String,
+    dst: Variable,
     src: FilterChain,
 }
 
@@ -24,14 +26,32 @@ impl Assign {
 }
 
 impl Renderable for Assign {
-    fn render_to(&self, _writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, _writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let value = self
             .src
             .evaluate(context)
             .trace_with(|| self.trace().into())?;
-        context.stack_mut().set_global(self.dst.to_owned(), value);
+        context
+            .spend_assigned(&value)
+            .trace_with(|| self.trace().into())?;
+        let dst = self
+            .dst
+            .evaluate(context)
+            .trace_with(|| self.trace().into())?
+            .iter()
+            .map(|index| Scalar::new(index.to_str().into_owned()))
+            .collect::<Vec<_>>();
+        context
+            .stack_mut()
+            .set_global_path(&dst, value)
+            .trace_with(|| self.trace().into())?;
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        self.src.references(references);
+        references.set(&self.dst);
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -61,9 +81,8 @@ impl ParseTag for AssignTag {
     ) -> Result<Box<dyn Renderable>> {
         let dst = arguments
             .expect_next("Identifier expected.")?
-            .expect_identifier()
-            .into_result()?
-            .to_string();
+            .expect_variable()
+            .into_result()?;
 
         arguments
             .expect_next("Assignment operator \"=\" expected.")?
@@ -127,7 +146,7 @@ mod test {
         let mut context = Context::new();
         context.stack_mut().set_global(
             "tags",
-            Value::Array(vec![
+            Value::array(vec![
                 Value::scalar("alpha"),
                 Value::scalar("beta"),
                 Value::scalar("gamma"),
@@ -163,6 +182,82 @@ mod test {
         assert_eq!(output, "alpha");
     }
 
+    #[test]
+    fn assign_array_literal() {
+        let text = concat!(
+            "{% assign names = [\"a\", 'b', name, [1, 2]] %}",
+            "{% for n in names %}{{ n }};{% endfor %}",
+            "{% assign none = [] %}{{ none.size }}"
+        );
+        let options = options();
+        let template = compiler::parse(text, &options)
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        context.stack_mut().set_global("name", Value::scalar("c"));
+
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "a;b;c;12;0");
+    }
+
+    #[test]
+    fn assign_range() {
+        let text = "{% assign sizes = (1..last) %}{% for size in sizes %}{{ size }}{% endfor %}";
+        let options = options();
+        let template = compiler::parse(text, &options)
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        context.stack_mut().set_global("last", Value::scalar(4));
+
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "1234");
+    }
+
+    #[test]
+    fn assign_nested_path() {
+        let text = concat!(
+            r#"{% assign user.profile.theme = "dark" %}"#,
+            r#"{% assign user["name"] = "Ann" %}"#,
+            "{% assign tags[0] = tags[1] %}",
+            "{{ user.profile.theme }} {{ user.name }} {{ tags }}"
+        );
+        let options = options();
+        let template = compiler::parse(text, &options)
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        context.stack_mut().set_global(
+            "tags",
+            Value::array(vec![Value::scalar("alpha"), Value::scalar("beta")]),
+        );
+
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "dark Ann betabeta");
+        assert_eq!(
+            context
+                .stack()
+                .get(&[Scalar::new("user"), Scalar::new("name")])
+                .unwrap(),
+            &Value::scalar("Ann")
+        );
+    }
+
+    #[test]
+    fn assign_nested_path_into_scalar() {
+        let text = r#"{% assign title = "x" %}{% assign title.size = 1 %}"#;
+        let options = options();
+        let template = compiler::parse(text, &options)
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        assert!(template.render(&mut context).is_err());
+    }
+
     #[test]
     fn assign_in_loop_persists_on_loop_exit() {
         let text = concat!(
@@ -185,7 +280,7 @@ mod test {
             let mut context = Context::new();
             context.stack_mut().set_global(
                 "tags",
-                Value::Array(vec![
+                Value::array(vec![
                     Value::scalar("alpha"),
                     Value::scalar("beta"),
                     Value::scalar("gamma"),
@@ -205,7 +300,7 @@ mod test {
             let mut context = Context::new();
             context.stack_mut().set_global(
                 "tags",
-                Value::Array(vec![
+                Value::array(vec![
                     Value::scalar("alpha"),
                     Value::scalar("beta"),
                     Value::scalar("freestyle"),
@@ -0,0 +1,305 @@
+use std::sync::Arc;
+use std::time;
+
+use liquid_error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
+
+use cache::CacheStore;
+use cache::DependencyIndex;
+use compiler::BlockReflection;
+use compiler::Language;
+use compiler::ParseBlock;
+use compiler::TagBlock;
+use compiler::TagTokenIter;
+use compiler::TryMatchToken;
+use interpreter::Context;
+use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
+use interpreter::Renderable;
+use interpreter::Template;
+
+/// The namespace of the `CacheStore` the fragments are kept in.
+const NAMESPACE: &str = "fragments";
+
+#[derive(Debug)]
+struct Cache {
+    key: Expression,
+    ttl: Option<Expression>,
+    template: Template,
+    store: Arc<dyn CacheStore>,
+    dependencies: DependencyIndex,
+}
+
+impl Cache {
+    fn trace(&self) -> String {
+        match self.ttl {
+            Some(ref ttl) => format!("{{% cache {}, ttl: {} %}}", self.key, ttl),
+            None => format!("{{% cache {} %}}", self.key),
+        }
+    }
+
+    fn ttl(&self, context: &Context) -> Result<Option<time::Duration>> {
+        let ttl = match self.ttl {
+            Some(ref ttl) => ttl.evaluate(context)?,
+            None => return Ok(None),
+        };
+        let seconds = ttl
+            .as_scalar()
+            .and_then(|ttl| ttl.to_integer())
+            .filter(|seconds| *seconds >= 0)
+            .ok_or_else(|| {
+                Error::with_msg("Whole number of seconds expected")
+                    .context("ttl", ttl.source().to_string())
+            })?;
+        Ok(Some(time::Duration::from_secs(seconds as u64)))
+    }
+}
+
+impl Renderable for Cache {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let key = self
+            .key
+            .evaluate(context)
+            .trace_with(|| self.trace().into())?
+            .to_str()
+            .into_owned();
+        let ttl = self.ttl(context).trace_with(|| self.trace().into())?;
+
+        let content = match self.store.get(NAMESPACE, &key) {
+            Some(content) => {
+                // Enclosing fragments depend on what this one was rendered from.
+                if let Some(dependencies) = self.dependencies.get(&key) {
+                    context.add_dependencies(&dependencies);
+                }
+                content
+            }
+            None => {
+                let mut rendered = Vec::new();
+                let (result, dependencies) = context
+                    .record_dependencies(|context| self.template.render_to(&mut rendered, context));
+                result.trace_with(|| self.trace().into())?;
+                let content = String::from_utf8(rendered).expect("render only writes UTF-8");
+                self.store.set(NAMESPACE, &key, content.clone(), ttl);
+                self.dependencies.insert(key, dependencies);
+                content
+            }
+        };
+        write!(writer, "{}", content).replace("Failed to render")?;
+        Ok(())
+    }
+
+    fn references(&self, references: &mut References) {
+        references.expression(&self.key);
+        if let Some(ref ttl) = self.ttl {
+            references.expression(ttl);
+        }
+        self.template.references(references);
+    }
+}
+
+/// Renders its content once per key and reuses it from a `CacheStore` after that, e.g.
+/// `{% cache 'sidebar', ttl: 300 %}...{% endcache %}`.
+///
+/// Tags in the content, like `assign`, only have effect when it is rendered.  The store is
+/// supplied by the host, so this is not registered by `liquid_blocks`.
+#[derive(Clone, Debug)]
+pub struct CacheBlock {
+    store: Arc<dyn CacheStore>,
+    dependencies: DependencyIndex,
+}
+
+impl CacheBlock {
+    pub fn new(store: Arc<dyn CacheStore>) -> Self {
+        let dependencies = DependencyIndex::new(store.clone(), NAMESPACE);
+        Self {
+            store,
+            dependencies,
+        }
+    }
+
+    /// The globals and partials each fragment was rendered from, to remove the fragments a
+    /// change affects.
+    pub fn dependencies(&self) -> DependencyIndex {
+        self.dependencies.clone()
+    }
+}
+
+impl BlockReflection for CacheBlock {
+    fn start_tag(&self) -> &'static str {
+        "cache"
+    }
+
+    fn end_tag(&self) -> &'static str {
+        "endcache"
+    }
+
+    fn description(&self) -> &'static str {
+        "Renders its content once per key, optionally for `ttl` seconds, and reuses it after that."
+    }
+}
+
+impl ParseBlock for CacheBlock {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        mut tokens: TagBlock,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let key = arguments
+            .expect_next("Key expected.")?
+            .expect_value()
+            .into_result()?;
+
+        let mut ttl = None;
+        if let Some(token) = arguments.next() {
+            let token = match token.expect_str(",") {
+                TryMatchToken::Matches(()) => arguments.expect_next("\"ttl\" expected.")?,
+                TryMatchToken::Fails(token) => token,
+            };
+            token
+                .expect_str("ttl")
+                .into_result_custom_msg("\"ttl\" expected.")?;
+            arguments
+                .expect_next("\":\" expected.")?
+                .expect_str(":")
+                .into_result_custom_msg("\":\" expected.")?;
+            ttl = Some(
+                arguments
+                    .expect_next("Number of seconds expected.")?
+                    .expect_value()
+                    .into_result()?,
+            );
+        }
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        let template = Template::new(tokens.parse_all(options)?);
+
+        tokens.assert_empty();
+        Ok(Box::new(Cache {
+            key,
+            ttl,
+            template,
+            store: self.store.clone(),
+            dependencies: self.dependencies.clone(),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use cache::MemoryStore;
+    use compiler;
+    use interpreter;
+    use tags;
+    use value;
+    use value::Value;
+
+    fn render(text: &str, store: Arc<dyn CacheStore>, user: &str) -> Result<String> {
+        let mut options = Language::default();
+        options
+            .blocks
+            .register("cache", CacheBlock::new(store).into());
+        options.tags.register("assign", tags::AssignTag.into());
+        let template = compiler::parse(text, &options).map(interpreter::Template::new)?;
+
+        let mut context = Context::new();
+        context
+            .stack_mut()
+            .set_global("user", Value::scalar(user.to_owned()));
+        template.render(&mut context)
+    }
+
+    #[test]
+    fn cache_reuses_content() {
+        let store = Arc::new(MemoryStore::new());
+        let text = "{% cache 'greeting' %}Hi {{ user }}{% endcache %}";
+        assert_eq!(render(text, store.clone(), "Ann").unwrap(), "Hi Ann");
+        assert_eq!(render(text, store.clone(), "Bob").unwrap(), "Hi Ann");
+        assert_eq!(store.get(NAMESPACE, "greeting"), Some("Hi Ann".to_owned()));
+
+        let text = "{% cache user %}Hi {{ user }}{% endcache %}";
+        assert_eq!(render(text, store.clone(), "Bob").unwrap(), "Hi Bob");
+    }
+
+    #[test]
+    fn cache_ttl() {
+        let store = Arc::new(MemoryStore::new());
+        let text = "{% cache 'greeting', ttl: 0 %}Hi {{ user }}{% endcache %}";
+        assert_eq!(render(text, store.clone(), "Ann").unwrap(), "Hi Ann");
+        assert_eq!(render(text, store.clone(), "Bob").unwrap(), "Hi Bob");
+
+        let text = "{% cache 'greeting' ttl: -1 %}{% endcache %}";
+        assert!(render(text, store.clone(), "Ann").is_err());
+        let text = "{% cache 'greeting', expires: 1 %}{% endcache %}";
+        assert!(render(text, store, "Ann").is_err());
+    }
+
+    #[test]
+    fn cache_dependencies() {
+        let store = Arc::new(MemoryStore::new());
+        let block = CacheBlock::new(store.clone());
+        let dependencies = block.dependencies();
+        let mut options = Language::default();
+        options.blocks.register("cache", block.into());
+        let text = concat!(
+            "{% cache 'page' %}{% cache 'product' %}{{ product.title }}{% endcache %}",
+            "{{ user }}{% endcache %}{% cache 'user' %}{{ user }}{% endcache %}"
+        );
+        let template = compiler::parse(text, &options)
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut globals = value::Object::new();
+        let mut product = value::Object::new();
+        product.insert("title".into(), Value::scalar("Shirt"));
+        globals.insert("product".into(), Value::Object(product));
+        globals.insert("user".into(), Value::scalar("Ann"));
+        let render = || {
+            let mut context = interpreter::ContextBuilder::new()
+                .set_globals(&globals)
+                .build();
+            template.render(&mut context).unwrap()
+        };
+        assert_eq!(render(), "ShirtAnnAnn");
+
+        assert_eq!(
+            dependencies
+                .get("page")
+                .unwrap()
+                .globals()
+                .collect::<Vec<_>>(),
+            vec!["product.title", "user"]
+        );
+        assert_eq!(
+            dependencies.keys_depending_on_global("user"),
+            vec!["page".to_owned(), "user".to_owned()]
+        );
+        assert_eq!(
+            dependencies.invalidate_global("product"),
+            vec!["page".to_owned(), "product".to_owned()]
+        );
+        assert!(store.get(NAMESPACE, "user").is_some());
+        assert!(store.get(NAMESPACE, "page").is_none());
+
+        // Reused fragments still count for those rendered again.
+        store.set(NAMESPACE, "product", "Shirt".to_owned(), None);
+        dependencies.insert("product", {
+            let mut product = interpreter::Dependencies::new();
+            product.add_global("product.title");
+            product
+        });
+        assert_eq!(render(), "ShirtAnnAnn");
+        assert_eq!(
+            dependencies.keys_depending_on_global("product"),
+            vec!["page".to_owned(), "product".to_owned()]
+        );
+    }
+}
@@ -1,7 +1,5 @@
-use std::io::Write;
-
 use liquid_error::{Result, ResultLiquidExt};
-use liquid_value::Value;
+use liquid_value::{Scalar, Value};
 
 use compiler::BlockReflection;
 use compiler::Language;
@@ -9,6 +7,8 @@ use compiler::ParseBlock;
 use compiler::TagBlock;
 use compiler::TagTokenIter;
 use interpreter::Context;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
 use interpreter::Template;
 
@@ -25,18 +25,30 @@ impl Capture {
 }
 
 impl Renderable for Capture {
-    fn render_to(&self, _writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, _writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let mut captured = Vec::new();
         self.template
             .render_to(&mut captured, context)
             .trace_with(|| self.trace().into())?;
 
-        let output = String::from_utf8(captured).expect("render only writes UTF-8");
+        let captured = String::from_utf8(captured).expect("render only writes UTF-8");
+        // The values in it were escaped already.
+        let output = if context.environment().auto_escape() {
+            Value::Scalar(Scalar::safe_str(captured))
+        } else {
+            Value::scalar(captured)
+        };
         context
-            .stack_mut()
-            .set_global(self.id.to_owned(), Value::scalar(output));
+            .spend_assigned(&output)
+            .trace_with(|| self.trace().into())?;
+        context.stack_mut().set_global(self.id.to_owned(), output);
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        self.template.references(references);
+        references.assign(self.id.as_str());
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -1,5 +1,3 @@
-use std::io::Write;
-
 use itertools;
 use liquid_error::{Result, ResultLiquidExt};
 use liquid_value::Value;
@@ -13,6 +11,8 @@ use compiler::TagTokenIter;
 use compiler::TryMatchToken;
 use interpreter::Context;
 use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
 use interpreter::Template;
 
@@ -56,7 +56,7 @@ impl Case {
 }
 
 impl Renderable for Case {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let value = self.target.evaluate(context)?.to_owned();
         for case in &self.cases {
             if case.evaluate(&value, context)? {
@@ -81,6 +81,19 @@ impl Renderable for Case {
 
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        references.expression(&self.target);
+        for case in &self.cases {
+            for arg in &case.args {
+                references.expression(arg);
+            }
+            case.template.references(references);
+        }
+        if let Some(ref t) = self.else_block {
+            t.references(references);
+        }
+    }
 }
 
 fn parse_condition(arguments: &mut TagTokenIter) -> Result<Vec<Expression>> {
@@ -130,7 +143,7 @@ impl BlockReflection for CaseBlock {
     }
 
     fn description(&self) -> &'static str {
-        ""
+        "Renders the first `when` block with a value, in a list separated by `,` or `or`, equal to the target."
     }
 }
 
@@ -238,6 +251,47 @@ mod test {
         assert_eq!(template.render(&mut context).unwrap(), "otherwise");
     }
 
+    #[test]
+    fn when_with_value_lists() {
+        let text = concat!(
+            "{% case x %}",
+            "{% when 'a', 'b' %}",
+            "a or b",
+            "{% when 'c' or 'd', 'e' %}",
+            "c, d or e",
+            "{% when 'f','g' %}",
+            "f or g",
+            "{% else %}",
+            "otherwise",
+            "{% endcase %}"
+        );
+        let options = options();
+        let template = compiler::parse(text, &options)
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        for (x, expected) in &[
+            ("a", "a or b"),
+            ("b", "a or b"),
+            ("c", "c, d or e"),
+            ("e", "c, d or e"),
+            ("g", "f or g"),
+            ("h", "otherwise"),
+        ] {
+            context.stack_mut().set_global("x", Value::scalar(*x));
+            assert_eq!(template.render(&mut context).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn when_with_dangling_separator_is_an_error() {
+        let text = "{% case x %}{% when 'a', %}a{% endcase %}";
+        assert!(compiler::parse(text, &options()).is_err());
+        let text = "{% case x %}{% when 'a' 'b' %}a{% endcase %}";
+        assert!(compiler::parse(text, &options()).is_err());
+    }
+
     #[test]
     fn test_no_matches_returns_empty_string() {
         let text = concat!(
@@ -1,5 +1,3 @@
-use std::io::Write;
-
 use liquid_error::Result;
 
 use compiler::BlockElement;
@@ -9,13 +7,14 @@ use compiler::ParseBlock;
 use compiler::TagBlock;
 use compiler::TagTokenIter;
 use interpreter::Context;
+use interpreter::Output;
 use interpreter::Renderable;
 
 #[derive(Copy, Clone, Debug)]
 struct Comment;
 
 impl Renderable for Comment {
-    fn render_to(&self, _writer: &mut dyn Write, _context: &mut Context) -> Result<()> {
+    fn render_to(&self, _writer: &mut dyn Output, _context: &mut Context) -> Result<()> {
         Ok(())
     }
 }
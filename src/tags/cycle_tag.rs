@@ -1,5 +1,5 @@
+use std::borrow;
 use std::collections::HashMap;
-use std::io::Write;
 
 use itertools;
 use liquid_error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
@@ -9,56 +9,96 @@ use compiler::ParseTag;
 use compiler::TagReflection;
 use compiler::TagToken;
 use compiler::TagTokenIter;
-use compiler::TryMatchToken;
 use interpreter::Context;
 use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
 
+/// The group of a cycle, e.g. `rows` in `{% cycle rows: 'odd', 'even' %}`.
+///
+/// Like Ruby Liquid, a variable names the group after its value.  Unlike Ruby Liquid, an
+/// undefined variable names the group after itself.
+#[derive(Clone, Debug)]
+struct CycleGroup {
+    name: Expression,
+    source: String,
+}
+
+impl CycleGroup {
+    fn parse(token: TagToken) -> Result<Self> {
+        let source = token.as_str().to_owned();
+        let name = token.expect_value().into_result()?;
+        Ok(CycleGroup { name, source })
+    }
+
+    fn evaluate<'c>(&'c self, context: &'c Context) -> borrow::Cow<'c, str> {
+        match self.name.try_evaluate(context) {
+            Some(value) if !value.is_nil() => value.to_str(),
+            _ => borrow::Cow::Borrowed(self.source.as_str()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 struct Cycle {
+    group: Option<CycleGroup>,
+    // Cycles without a group share their state with the cycles of the same values.
     name: String,
     values: Vec<Expression>,
 }
 
 impl Cycle {
     fn trace(&self) -> String {
-        format!(
-            "{{% cycle {} %}}",
-            itertools::join(self.values.iter(), ", ")
-        )
+        let values = itertools::join(self.values.iter(), ", ");
+        match self.group {
+            Some(ref group) => format!("{{% cycle {}: {} %}}", group.source, values),
+            None => format!("{{% cycle {} %}}", values),
+        }
     }
 }
 
 impl Renderable for Cycle {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let name = match self.group {
+            Some(ref group) => group.evaluate(context).into_owned(),
+            None => self.name.clone(),
+        };
         let expr = context
-            .get_register_mut::<State>()
-            .cycle(&self.name, &self.values)
+            .get_register_mut::<CycleState>()
+            .cycle(&name, &self.values)
             .trace_with(|| self.trace().into())?;
         let value = expr.evaluate(context).trace_with(|| self.trace().into())?;
-        write!(writer, "{}", value.render()).replace("Failed to render")?;
+        let (rendered, safe) = context
+            .environment()
+            .render_output(value)
+            .trace_with(|| self.trace().into())?;
+        writer
+            .write_value(&rendered, safe)
+            .replace("Failed to render")?;
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        if let Some(ref group) = self.group {
+            references.expression(&group.name);
+        }
+        for value in &self.values {
+            references.expression(value);
+        }
+    }
 }
 
 /// Internal implementation of cycle, to allow easier testing.
 fn parse_cycle(mut arguments: TagTokenIter, _options: &Language) -> Result<Cycle> {
-    let mut name = String::new();
+    let mut group = None;
     let mut values = Vec::new();
 
     let first = arguments.expect_next("Identifier or value expected")?;
     let second = arguments.next();
     match second.as_ref().map(TagToken::as_str) {
         Some(":") => {
-            name = match first.expect_identifier() {
-                TryMatchToken::Matches(name) => name.to_string(),
-                TryMatchToken::Fails(name) => match name.expect_literal() {
-                    // This will allow non string literals such as 0 to be parsed as such.
-                    // Is this ok or should more specific functions be created?
-                    TryMatchToken::Matches(name) => name.to_str().into_owned(),
-                    TryMatchToken::Fails(name) => return name.raise_error().into_err(),
-                },
-            };
+            group = Some(CycleGroup::parse(first)?);
         }
         Some(",") | None => {
             // first argument is the first item in the cycle
@@ -92,14 +132,16 @@ fn parse_cycle(mut arguments: TagTokenIter, _options: &Language) -> Result<Cycle
         }
     }
 
-    if name.is_empty() {
-        name = itertools::join(values.iter(), "-");
-    }
+    let name = itertools::join(values.iter(), "-");
 
     // no more arguments should be supplied, trying to supply them is an error
     arguments.expect_nothing()?;
 
-    Ok(Cycle { name, values })
+    Ok(Cycle {
+        group,
+        name,
+        values,
+    })
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -131,13 +173,38 @@ impl ParseTag for CycleTag {
     }
 }
 
+/// The positions of the cycles encountered while rendering.
+///
+/// Custom tags can read or reset cycles through `Context::get_register_mut::<CycleState>()`.
+/// Cycles are named after their group, or else after their values, e.g. `"one"-"two"` for
+/// `{% cycle 'one', 'two' %}`.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
-struct State {
+pub struct CycleState {
     // The indices of all the cycles encountered during rendering.
     cycles: HashMap<String, usize>,
 }
 
-impl State {
+impl CycleState {
+    /// The index of the value the cycle `name` renders next, if it has rendered yet.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.cycles.get(name).cloned()
+    }
+
+    /// The names of the cycles that have rendered.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.cycles.keys().map(String::as_str)
+    }
+
+    /// Start the cycle `name` over from its first value.
+    pub fn reset(&mut self, name: &str) {
+        self.cycles.remove(name);
+    }
+
+    /// Start every cycle over from its first value.
+    pub fn reset_all(&mut self) {
+        self.cycles.clear();
+    }
+
     fn cycle<'e>(&mut self, name: &str, values: &'e [Expression]) -> Result<&'e Expression> {
         let index = self.cycle_index(name, values.len());
         if index >= values.len() {
@@ -160,16 +227,80 @@ impl State {
     }
 }
 
+#[derive(Clone, Debug)]
+struct ResetCycle {
+    group: Option<CycleGroup>,
+}
+
+impl Renderable for ResetCycle {
+    fn render_to(&self, _writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let name = self
+            .group
+            .as_ref()
+            .map(|group| group.evaluate(context).into_owned());
+        let state = context.get_register_mut::<CycleState>();
+        match name {
+            Some(ref name) => state.reset(name),
+            None => state.reset_all(),
+        }
+        Ok(())
+    }
+
+    fn references(&self, references: &mut References) {
+        if let Some(ref group) = self.group {
+            references.expression(&group.name);
+        }
+    }
+}
+
+/// Starts a named cycle, or all cycles, over, e.g. `{% resetcycle 'rows' %}`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ResetCycleTag;
+
+impl TagReflection for ResetCycleTag {
+    fn tag(&self) -> &'static str {
+        "resetcycle"
+    }
+
+    fn description(&self) -> &'static str {
+        "Starts a named cycle, or every cycle when no name is given, over from its first value."
+    }
+}
+
+impl ParseTag for ResetCycleTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let group = match arguments.next() {
+            Some(name) => Some(CycleGroup::parse(name)?),
+            None => None,
+        };
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(ResetCycle { group }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use compiler;
     use interpreter;
+    use tags;
     use value::Value;
 
     fn options() -> Language {
         let mut options = Language::default();
         options.tags.register("cycle", CycleTag.into());
+        options.tags.register("resetcycle", ResetCycleTag.into());
         options
     }
 
@@ -198,6 +329,33 @@ mod test {
         assert_eq!(output.unwrap(), "one\ntwo\none\ntwo\n");
     }
 
+    #[test]
+    fn groups_can_be_variables() {
+        let text = concat!(
+            "{% for row in rows %}",
+            "{% cycle row: 'odd', 'even' %}",
+            "{% cycle undefined: 'a', 'b' %}",
+            "{% cycle 'odd', 'even' %} ",
+            "{% endfor %}",
+            "{% resetcycle rows.first %}{% cycle 'x': 'odd', 'even' %}"
+        );
+        let mut options = options();
+        options.blocks.register("for", tags::ForBlock.into());
+        let template = compiler::parse(text, &options)
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let rows = vec![Value::scalar("x"), Value::scalar("y"), Value::scalar("x")];
+        context.stack_mut().set_global("rows", Value::array(rows));
+        let output = template.render(&mut context).unwrap();
+
+        assert_eq!(output, "oddaodd oddbeven evenaodd odd");
+        let state = context.get_register_mut::<CycleState>();
+        assert_eq!(state.position("undefined"), Some(1));
+        assert_eq!(state.position("y"), Some(1));
+    }
+
     #[test]
     fn values_are_cycled() {
         let text = concat!(
@@ -250,4 +408,34 @@ mod test {
         let output = template.render(&mut Default::default());
         assert!(output.is_err());
     }
+
+    #[test]
+    fn cycles_can_be_reset() {
+        let text = concat!(
+            "{% cycle 'a': 'one', 'two', 'three' %}",
+            "{% cycle 'b': 'one', 'two', 'three' %}",
+            "{% cycle 'a': 'one', 'two', 'three' %}",
+            "{% cycle 'b': 'one', 'two', 'three' %}\n",
+            "{% resetcycle 'a' %}",
+            "{% cycle 'a': 'one', 'two', 'three' %}",
+            "{% cycle 'b': 'one', 'two', 'three' %}\n",
+            "{% resetcycle %}",
+            "{% cycle 'a': 'one', 'two', 'three' %}",
+            "{% cycle 'b': 'one', 'two', 'three' %}\n",
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let output = template.render(&mut context);
+
+        assert_eq!(output.unwrap(), "oneonetwotwo\nonethree\noneone\n");
+        let state = context.get_register_mut::<CycleState>();
+        assert_eq!(state.position("a"), Some(1));
+        assert_eq!(state.position("c"), None);
+        let mut names: Vec<_> = state.names().collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["a", "b"]);
+    }
 }
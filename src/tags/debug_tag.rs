@@ -0,0 +1,195 @@
+use liquid_error::{Result, ResultLiquidReplaceExt};
+use liquid_value::{Object, Value};
+
+use compiler::Language;
+use compiler::ParseTag;
+use compiler::TagReflection;
+use compiler::TagTokenIter;
+use filters::json::{to_json, JsonStyle};
+use interpreter::Context;
+use interpreter::Output;
+use interpreter::Renderable;
+use interpreter::Variable;
+
+/// The variables of loops, in the scope of their loop.
+const LOOP_VARIABLES: &[&str] = &["forloop", "tablerowloop"];
+
+#[derive(Clone, Debug)]
+struct Debug {
+    variable: Option<Variable>,
+}
+
+impl Debug {
+    /// What the render can see: its scopes, its globals and the state of the loops it is in.
+    fn dump(context: &Context) -> Value {
+        let stack = context.stack();
+        let scopes = stack
+            .scopes()
+            .map(|(template, variables)| {
+                let mut scope = Object::new();
+                let template = template.map_or(Value::Nil, |name| Value::scalar(name.to_owned()));
+                scope.insert("template".into(), template);
+                scope.insert("variables".into(), Value::Object(variables.clone()));
+                Value::Object(scope)
+            })
+            .collect();
+
+        let mut dump = Object::new();
+        dump.insert("scopes".into(), Value::Array(scopes));
+        dump.insert("globals".into(), Value::Object(stack.global_values()));
+        for name in LOOP_VARIABLES {
+            let state = stack
+                .scopes()
+                .filter_map(|(_, variables)| variables.get(*name))
+                .last()
+                .cloned()
+                .unwrap_or(Value::Nil);
+            dump.insert((*name).into(), state);
+        }
+        Value::Object(dump)
+    }
+}
+
+impl Renderable for Debug {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let value = match self.variable {
+            Some(ref variable) => variable
+                .try_evaluate(context)
+                .and_then(|path| context.stack().try_get(&path).cloned())
+                .unwrap_or(Value::Nil),
+            None => Self::dump(context),
+        };
+        let style = JsonStyle {
+            sort_keys: true,
+            pretty: true,
+            script_safe: true,
+        };
+        write!(writer, "{}", to_json(&value, style)).replace("Failed to render")?;
+        Ok(())
+    }
+}
+
+/// Prints what the render can see as JSON, for developing templates, e.g. `{% debug %}` or
+/// `{% debug product.variants %}`.
+///
+/// Without a variable, it prints the variables of each scope, outermost first, the globals,
+/// and the `forloop` and `tablerowloop` of the innermost loops.  It is not registered by
+/// `ParserBuilder::liquid`, so it is only in the parsers it is added to.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DebugTag;
+
+impl DebugTag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TagReflection for DebugTag {
+    fn tag(&self) -> &'static str {
+        "debug"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prints the scopes, globals and loops of the render, or a variable, as JSON."
+    }
+
+    fn example(&self) -> Option<&'static str> {
+        Some("{% debug product %}")
+    }
+}
+
+impl ParseTag for DebugTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let variable = match arguments.next() {
+            Some(token) => Some(token.expect_variable().into_result()?),
+            None => None,
+        };
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(Debug { variable }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use compiler;
+    use interpreter;
+    use tags;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("debug", DebugTag.into());
+        options.tags.register("assign", tags::AssignTag.into());
+        options.blocks.register("for", tags::ForBlock.into());
+        options
+    }
+
+    fn unit_parse(text: &str, context: &mut Context) -> String {
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+        template.render(context).unwrap()
+    }
+
+    #[test]
+    fn debug_variable() {
+        let mut context = Context::new();
+        let output = unit_parse(
+            "{% assign tags = ['a', '<b>'] %}{% debug tags %}{% debug missing %}",
+            &mut context,
+        );
+        assert_eq!(output, "[\n  \"a\",\n  \"\\u003cb\\u003e\"\n]null");
+    }
+
+    #[test]
+    fn debug_dump() {
+        let mut globals = Object::new();
+        globals.insert("site".into(), Value::scalar("blog"));
+        let mut context = interpreter::ContextBuilder::new()
+            .set_globals(&globals)
+            .build();
+        let output = unit_parse("{% assign x = 1 %}{% debug %}", &mut context);
+        let expected = r#"{
+  "forloop": null,
+  "globals": {
+    "site": "blog"
+  },
+  "scopes": [
+    {
+      "template": null,
+      "variables": {
+        "x": 1
+      }
+    }
+  ],
+  "tablerowloop": null
+}"#;
+        assert_eq!(output, expected);
+
+        let output = unit_parse(
+            "{% for i in (1..2) %}{% for j in (1..1) %}{% debug %}{% endfor %}{% endfor %}",
+            &mut context,
+        );
+        assert!(output.starts_with("{\n  \"forloop\": {\n    \"cycle\": 1,"));
+        assert!(output.contains("\"parentloop\": {"));
+        assert!(output.contains("\"j\": 1"));
+    }
+
+    #[test]
+    fn debug_arguments() {
+        assert!(compiler::parse("{% debug a b %}", &options()).is_err());
+        assert!(compiler::parse("{% debug 'a' %}", &options()).is_err());
+    }
+}
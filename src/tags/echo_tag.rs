@@ -0,0 +1,84 @@
+use liquid_error::Result;
+
+use compiler::Language;
+use compiler::ParseTag;
+use compiler::TagReflection;
+use compiler::TagTokenIter;
+use interpreter::Renderable;
+
+/// Outputs a value like `{{ }}` does, for where `{{ }}` can't be used, e.g. in a `{% liquid %}`
+/// tag.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EchoTag;
+
+impl TagReflection for EchoTag {
+    fn tag(&self) -> &'static str {
+        "echo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Outputs a value, with filters, like `{{ }}`."
+    }
+}
+
+impl ParseTag for EchoTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let value = arguments
+            .expect_next("Value expected.")?
+            .expect_filter_chain(options)
+            .into_result()?;
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(value))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use compiler;
+    use filters;
+    use interpreter;
+    use interpreter::Context;
+    use value::Value;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("echo", EchoTag.into());
+        options
+            .filters
+            .register("append", Box::new(filters::std::Append));
+        options
+    }
+
+    #[test]
+    fn echo_tag() {
+        let text = "{% echo name | append: '!' %}";
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        context
+            .stack_mut()
+            .set_global("name", Value::scalar("Liquid"));
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "Liquid!");
+    }
+
+    #[test]
+    fn echo_tag_requires_one_value() {
+        assert!(compiler::parse("{% echo %}", &options()).is_err());
+        assert!(compiler::parse("{% echo a b %}", &options()).is_err());
+    }
+}
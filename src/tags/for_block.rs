@@ -1,9 +1,9 @@
+use std::collections::HashMap;
 use std::fmt;
-use std::io::Write;
 
 use itertools;
 use liquid_error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
-use liquid_value::{Object, Scalar, Value};
+use liquid_value::{Array, LazyArray, Object, Scalar, Value};
 
 use compiler::BlockElement;
 use compiler::BlockReflection;
@@ -13,6 +13,8 @@ use compiler::TagBlock;
 use compiler::TagTokenIter;
 use compiler::TryMatchToken;
 use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
 use interpreter::Template;
 use interpreter::{Context, Interrupt};
@@ -24,15 +26,14 @@ enum Range {
 }
 
 impl Range {
-    pub fn evaluate(&self, context: &Context) -> Result<Vec<Value>> {
+    pub fn evaluate(&self, context: &Context) -> Result<Items> {
         let range = match *self {
             Range::Array(ref array_id) => get_array(context, array_id)?,
 
             Range::Counted(ref start_arg, ref stop_arg) => {
                 let start = int_argument(start_arg, context, "start")?;
                 let stop = int_argument(stop_arg, context, "end")?;
-                let range = start..=stop;
-                range.map(|x| Value::scalar(x as i32)).collect()
+                Items::Numbers(liquid_value::Range::new(start as i32, stop as i32))
             }
         };
 
@@ -40,6 +41,26 @@ impl Range {
     }
 }
 
+/// What a loop goes over: the numbers of a range, and the elements of a lazy array, are only
+/// made into values as they are needed.
+enum Items {
+    Values(Array),
+    Numbers(liquid_value::Range),
+    Lazy(LazyArray),
+}
+
+impl Range {
+    fn references(&self, references: &mut References) {
+        match *self {
+            Range::Array(ref array_id) => references.expression(array_id),
+            Range::Counted(ref start, ref stop) => {
+                references.expression(start);
+                references.expression(stop);
+            }
+        }
+    }
+}
+
 impl fmt::Display for Range {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -50,23 +71,51 @@ impl fmt::Display for Range {
 }
 
 fn iter_array(
-    mut range: Vec<Value>,
+    range: Items,
     limit: Option<usize>,
     offset: usize,
     reversed: bool,
-) -> Vec<Value> {
-    let offset = ::std::cmp::min(offset, range.len());
-    let limit = limit
-        .map(|l| ::std::cmp::min(l, range.len()))
-        .unwrap_or_else(|| range.len() - offset);
-    range.drain(0..offset);
-    range.resize(limit, Value::Nil);
-
-    if reversed {
-        range.reverse();
-    };
-
-    range
+) -> (usize, Box<dyn Iterator<Item = Value>>) {
+    match range {
+        Items::Values(values) => {
+            // The values are shared with the array looped over, so only the ones looped over
+            // are copied, as they are.
+            let offset = ::std::cmp::min(offset, values.len());
+            let end = limit
+                .map(|l| ::std::cmp::min(offset + l, values.len()))
+                .unwrap_or_else(|| values.len());
+            let indexes = offset..end;
+            let len = indexes.len();
+            let indexes: Box<dyn Iterator<Item = usize>> = if reversed {
+                Box::new(indexes.rev())
+            } else {
+                Box::new(indexes)
+            };
+            (len, Box::new(indexes.map(move |i| values[i].clone())))
+        }
+        Items::Numbers(range) => {
+            let range = range.slice(offset, limit);
+            let numbers = range.iter().map(Value::scalar);
+            let numbers: Box<dyn Iterator<Item = Value>> = if reversed {
+                Box::new(numbers.rev())
+            } else {
+                Box::new(numbers)
+            };
+            (range.len(), numbers)
+        }
+        // Reversing needs every element at once.
+        Items::Lazy(array) if reversed => {
+            iter_array(Items::Values(array.to_array()), limit, offset, reversed)
+        }
+        Items::Lazy(array) => {
+            // The length, needed for `forloop`, is counted by generating the elements unless the
+            // array knows it.
+            let len = array.len();
+            let offset = ::std::cmp::min(offset, len);
+            let len = limit.map_or(len - offset, |l| ::std::cmp::min(l, len - offset));
+            (len, Box::new(array.iter().skip(offset).take(len)))
+        }
+    }
 }
 
 /// Extracts an integer value or an identifier from the token stream
@@ -82,8 +131,22 @@ fn parse_attr(arguments: &mut TagTokenIter) -> Result<Expression> {
         .into_result()
 }
 
+/// Extracts the `offset` of a `for` loop, which may `continue` a previous loop.
+fn parse_offset(arguments: &mut TagTokenIter) -> Result<Offset> {
+    arguments
+        .expect_next("\":\" expected.")?
+        .expect_str(":")
+        .into_result_custom_msg("\":\" expected.")?;
+
+    let token = arguments.expect_next("Value expected.")?;
+    if token.as_str() == "continue" {
+        return Ok(Offset::Continue);
+    }
+    token.expect_value().into_result().map(Offset::At)
+}
+
 /// Evaluates an attribute, returning Ok(None) if input is also None.
-fn evaluate_attr(attr: &Option<Expression>, context: &mut Context) -> Result<Option<usize>> {
+fn evaluate_attr(attr: Option<&Expression>, context: &mut Context) -> Result<Option<usize>> {
     match attr {
         Some(attr) => {
             let value = attr.evaluate(context)?;
@@ -98,6 +161,28 @@ fn evaluate_attr(attr: &Option<Expression>, context: &mut Context) -> Result<Opt
     }
 }
 
+#[derive(Debug)]
+enum Offset {
+    At(Expression),
+    /// Where the last loop over the same variable and collection stopped.
+    Continue,
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Offset::At(ref offset) => write!(f, "{}", offset),
+            Offset::Continue => write!(f, "continue"),
+        }
+    }
+}
+
+/// Remembers where loops stopped, for `offset: continue`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct State {
+    offsets: HashMap<String, usize>,
+}
+
 #[derive(Debug)]
 struct For {
     var_name: String,
@@ -105,7 +190,7 @@ struct For {
     item_template: Template,
     else_template: Option<Template>,
     limit: Option<Expression>,
-    offset: Option<Expression>,
+    offset: Option<Offset>,
     reversed: bool,
 }
 
@@ -119,19 +204,26 @@ impl For {
             self.reversed,
         )
     }
+
+    /// Identifies the loops that `offset: continue` continues, like Shopify does.
+    fn offset_key(&self) -> String {
+        format!("{}-{}", self.var_name, self.range)
+    }
 }
 
-fn get_array(context: &Context, array_id: &Expression) -> Result<Vec<Value>> {
+fn get_array(context: &Context, array_id: &Expression) -> Result<Items> {
     let array = array_id.evaluate(context)?;
     match array {
-        Value::Empty => Ok(vec![]),
-        Value::Array(x) => Ok(x.to_owned()),
+        Value::Empty => Ok(Items::Values(Array::new())),
+        Value::Array(x) => Ok(Items::Values(x.clone())),
+        Value::Range(x) => Ok(Items::Numbers(*x)),
+        Value::Lazy(x) => Ok(Items::Lazy(x.clone())),
         Value::Object(x) => {
             let x = x
                 .iter()
-                .map(|(k, v)| Value::Array(vec![Value::scalar(k.clone()), v.to_owned()]))
+                .map(|(k, v)| Value::array(vec![Value::scalar(k.clone()), v.to_owned()]))
                 .collect();
-            Ok(x)
+            Ok(Items::Values(x))
         }
         x => Err(unexpected_value_error("array", Some(x.type_name()))),
     }
@@ -151,16 +243,29 @@ fn int_argument(arg: &Expression, context: &Context, arg_name: &str) -> Result<i
 }
 
 impl Renderable for For {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let range = self
             .range
             .evaluate(context)
             .trace_with(|| self.trace().into())?;
-        let limit = evaluate_attr(&self.limit, context)?;
-        let offset = evaluate_attr(&self.offset, context)?.unwrap_or(0);
-        let range = iter_array(range, limit, offset, self.reversed);
+        let limit = evaluate_attr(self.limit.as_ref(), context)?;
+        let offset = match self.offset {
+            Some(Offset::At(ref offset)) => evaluate_attr(Some(offset), context)?,
+            Some(Offset::Continue) => context
+                .get_register_mut::<State>()
+                .offsets
+                .get(&self.offset_key())
+                .cloned(),
+            None => None,
+        }
+        .unwrap_or(0);
+        let (range_len, range) = iter_array(range, limit, offset, self.reversed);
+        context
+            .get_register_mut::<State>()
+            .offsets
+            .insert(self.offset_key(), offset + range_len);
 
-        match range.len() {
+        match range_len {
             0 => {
                 if let Some(ref t) = self.else_template {
                     t.render_to(writer, context)
@@ -170,11 +275,20 @@ impl Renderable for For {
             }
 
             range_len => {
+                let parentloop = context
+                    .stack()
+                    .try_get(&[Scalar::new("forloop")])
+                    .cloned()
+                    .unwrap_or(Value::Nil);
                 context.run_in_scope(|mut scope| -> Result<()> {
                     let mut helper_vars = Object::new();
                     helper_vars.insert("length".into(), Value::scalar(range_len as i32));
+                    helper_vars.insert("parentloop".into(), parentloop);
 
-                    for (i, v) in range.into_iter().enumerate() {
+                    for (i, v) in range.enumerate() {
+                        scope
+                            .spend_loop_iteration()
+                            .trace_with(|| self.trace().into())?;
                         helper_vars.insert("index0".into(), Value::scalar(i as i32));
                         helper_vars.insert("index".into(), Value::scalar((i + 1) as i32));
                         helper_vars
@@ -182,6 +296,11 @@ impl Renderable for For {
                         helper_vars.insert("rindex".into(), Value::scalar((range_len - i) as i32));
                         helper_vars.insert("first".into(), Value::scalar(i == 0));
                         helper_vars.insert("last".into(), Value::scalar(i == (range_len - 1)));
+                        // For zebra-striping, counted like `index`: the first iteration is odd.
+                        helper_vars.insert("odd".into(), Value::scalar(i % 2 == 0));
+                        helper_vars.insert("even".into(), Value::scalar(i % 2 == 1));
+                        helper_vars.insert("cycle0".into(), Value::scalar((i % 2) as i32));
+                        helper_vars.insert("cycle".into(), Value::scalar((i % 2 + 1) as i32));
 
                         scope
                             .stack_mut()
@@ -207,13 +326,29 @@ impl Renderable for For {
         }
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        self.range.references(references);
+        if let Some(ref limit) = self.limit {
+            references.expression(limit);
+        }
+        if let Some(Offset::At(ref offset)) = self.offset {
+            references.expression(offset);
+        }
+        references.scope(&[&self.var_name, "forloop"], |references| {
+            self.item_template.references(references)
+        });
+        if let Some(ref t) = self.else_template {
+            t.references(references);
+        }
+    }
 }
 
-fn trace_for_tag(
+fn trace_for_tag<O: fmt::Display>(
     var_name: &str,
     range: &Range,
     limit: &Option<Expression>,
-    offset: &Option<Expression>,
+    offset: &Option<O>,
     reversed: bool,
 ) -> String {
     let mut parameters = vec![];
@@ -292,7 +427,7 @@ impl ParseBlock for ForBlock {
         while let Some(token) = arguments.next() {
             match token.as_str() {
                 "limit" => limit = Some(parse_attr(&mut arguments)?),
-                "offset" => offset = Some(parse_attr(&mut arguments)?),
+                "offset" => offset = Some(parse_offset(&mut arguments)?),
                 "reversed" => reversed = true,
                 _ => {
                     return token
@@ -391,23 +526,25 @@ fn trace_tablerow_tag(
 }
 
 impl Renderable for TableRow {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let range = self
             .range
             .evaluate(context)
             .trace_with(|| self.trace().into())?;
-        let cols = evaluate_attr(&self.cols, context)?;
-        let limit = evaluate_attr(&self.limit, context)?;
-        let offset = evaluate_attr(&self.offset, context)?.unwrap_or(0);
-        let range = iter_array(range, limit, offset, false);
+        let cols = evaluate_attr(self.cols.as_ref(), context)?;
+        let limit = evaluate_attr(self.limit.as_ref(), context)?;
+        let offset = evaluate_attr(self.offset.as_ref(), context)?.unwrap_or(0);
+        let (range_len, range) = iter_array(range, limit, offset, false);
 
         context.run_in_scope(|mut scope| -> Result<()> {
             let mut helper_vars = Object::new();
 
-            let range_len = range.len();
             helper_vars.insert("length".into(), Value::scalar(range_len as i32));
 
-            for (i, v) in range.into_iter().enumerate() {
+            for (i, v) in range.enumerate() {
+                scope
+                    .spend_loop_iteration()
+                    .trace_with(|| self.trace().into())?;
                 let (col_index, row_index) = match cols {
                     Some(cols) => (i % cols, i / cols),
                     None => (i, 0),
@@ -428,9 +565,11 @@ impl Renderable for TableRow {
                 helper_vars.insert("col".into(), Value::scalar((col_index + 1) as i32));
                 helper_vars.insert("col_first".into(), Value::scalar(col_first));
                 helper_vars.insert("col_last".into(), Value::scalar(col_last));
-                scope
-                    .stack_mut()
-                    .set("tablerow", Value::Object(helper_vars.clone()));
+                helper_vars.insert("row".into(), Value::scalar((row_index + 1) as i32));
+                // `tablerowloop` as in Shopify Liquid, `tablerow` for existing templates.
+                let helper_vars = Value::Object(helper_vars.clone());
+                scope.stack_mut().set("tablerow", helper_vars.clone());
+                scope.stack_mut().set("tablerowloop", helper_vars);
 
                 if col_first {
                     write!(writer, "<tr class=\"row{}\">", row_index + 1)
@@ -456,6 +595,17 @@ impl Renderable for TableRow {
 
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        self.range.references(references);
+        for attr in self.cols.iter().chain(&self.limit).chain(&self.offset) {
+            references.expression(attr);
+        }
+        references.scope(
+            &[&self.var_name, "tablerow", "tablerowloop"],
+            |references| self.item_template.references(references),
+        );
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -588,7 +738,7 @@ mod test {
         let mut context: Context = Default::default();
         context.stack_mut().set_global(
             "array",
-            Value::Array(vec![
+            Value::array(vec![
                 Value::scalar(22f64),
                 Value::scalar(23f64),
                 Value::scalar(24f64),
@@ -619,6 +769,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn zebra_striping() {
+        let text = concat!(
+            "{% for name in (1..4) %}",
+            "{{forloop.odd}}/{{forloop.even}}-{{forloop.cycle0}}{{forloop.cycle}} ",
+            "{% endfor %}",
+        );
+
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Default::default();
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(
+            output,
+            "true/false-01 false/true-12 true/false-01 false/true-12 "
+        );
+    }
+
     #[test]
     fn loop_over_range_vars() {
         let text = concat!(
@@ -676,6 +846,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn nested_forloops_parentloop() {
+        let text = concat!(
+            "{% for outer in (1..2) %}",
+            "[{{ forloop.parentloop }}]",
+            "{% for inner in (1..3) %}",
+            "{{ forloop.parentloop.index }}.{{ forloop.index }}",
+            "/{{ forloop.parentloop.length }}-{{ forloop.parentloop.last }},",
+            "{% endfor %}",
+            "{% endfor %}"
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(
+            output,
+            "[]1.1/2-false,1.2/2-false,1.3/2-false,[]2.1/2-true,2.2/2-true,2.3/2-true,"
+        );
+    }
+
     #[test]
     fn nested_forloops_with_else() {
         // test that nested for loops parse their `else` blocks correctly
@@ -691,15 +884,15 @@ mod test {
             .unwrap();
 
         let mut context = Context::new();
-        context.stack_mut().set_global("i", Value::Array(vec![]));
-        context.stack_mut().set_global("j", Value::Array(vec![]));
+        context.stack_mut().set_global("i", Value::array(vec![]));
+        context.stack_mut().set_global("j", Value::array(vec![]));
         let output = template.render(&mut context).unwrap();
         assert_eq!(output, "empty outer");
 
         context
             .stack_mut()
-            .set_global("i", Value::Array(vec![Value::scalar(1i32)]));
-        context.stack_mut().set_global("j", Value::Array(vec![]));
+            .set_global("i", Value::array(vec![Value::scalar(1i32)]));
+        context.stack_mut().set_global("j", Value::array(vec![]));
         let output = template.render(&mut context).unwrap();
         assert_eq!(output, "empty inner");
     }
@@ -750,6 +943,24 @@ mod test {
         assert_eq!(output, "5 6 7 8 9 10 ");
     }
 
+    #[test]
+    fn offset_continue_loop() {
+        let text = concat!(
+            "{% for i in (1..7) limit:3 %}{{ i }} {% endfor %}| ",
+            "{% for i in (1..7) offset:continue limit:3 %}{{ i }} {% endfor %}| ",
+            "{% for j in (1..7) offset:continue limit:2 %}{{ j }} {% endfor %}| ",
+            "{% for i in (1..7) offset:continue limit:3 %}{{ i }} {% endfor %}| ",
+            "{% for i in (1..7) offset:continue %}{{ i }} {% else %}done{% endfor %}"
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "1 2 3 | 4 5 6 | 1 2 | 7 | done");
+    }
+
     #[test]
     fn offset_and_limited_loop() {
         let text = concat!(
@@ -798,6 +1009,43 @@ mod test {
         assert_eq!(output, "6 5 4 3 2 ");
     }
 
+    #[test]
+    fn loop_over_large_range() {
+        let text = concat!(
+            "{% for i in (1..1000000) reversed offset:2 limit:3 %}",
+            "{{ i }} ",
+            "{% endfor %}"
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "5 4 3 ");
+    }
+
+    #[test]
+    fn loop_over_assigned_range() {
+        let text = concat!(
+            "{% assign numbers = (start..3) %}",
+            "{% for i in numbers %}",
+            "{{ i }} ",
+            "{% endfor %}",
+            "{% for i in numbers %}",
+            "{{ forloop.length }} ",
+            "{% endfor %}"
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        context.stack_mut().set_global("start", Value::scalar(2i32));
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "2 3 2 2 ");
+    }
+
     #[test]
     fn empty_loop_invokes_else_template() {
         let text = concat!(
@@ -893,7 +1141,7 @@ mod test {
 
         context.stack_mut().set_global(
             "array",
-            Value::Array(vec![
+            Value::array(vec![
                 Value::scalar("alpha"),
                 Value::scalar("beta"),
                 Value::scalar("gamma"),
@@ -936,7 +1184,7 @@ mod test {
         let mut context: Context = Default::default();
         context.stack_mut().set_global(
             "array",
-            Value::Array(vec![
+            Value::array(vec![
                 Value::scalar(22f64),
                 Value::scalar(23f64),
                 Value::scalar(24f64),
@@ -962,7 +1210,7 @@ mod test {
         let mut context: Context = Default::default();
         context.stack_mut().set_global(
             "array",
-            Value::Array(vec![
+            Value::array(vec![
                 Value::scalar(22f64),
                 Value::scalar(23f64),
                 Value::scalar(24f64),
@@ -995,6 +1243,29 @@ mod test {
         assert_eq!(output, "<tr class=\"row1\"><td class=\"col1\">6 </td><td class=\"col2\">7 </td><td class=\"col3\">8 </td></tr><tr class=\"row2\"><td class=\"col1\">9 </td></tr>");
     }
 
+    #[test]
+    fn tablerowloop_variables() {
+        let text = concat!(
+            "{% tablerow v in (1..5) cols:2 limit:4 offset:1 %}",
+            "{{tablerowloop.row}}/{{tablerowloop.col}}/{{tablerowloop.index}}/{{v}}",
+            "{% endtablerow %}",
+        );
+
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context: Context = Default::default();
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                "<tr class=\"row1\"><td class=\"col1\">1/1/1/2</td><td class=\"col2\">1/2/2/3</td></tr>",
+                "<tr class=\"row2\"><td class=\"col1\">2/1/3/4</td><td class=\"col2\">2/2/4/5</td></tr>",
+            )
+        );
+    }
+
     #[test]
     fn tablerow_variables() {
         let text = concat!(
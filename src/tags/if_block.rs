@@ -1,6 +1,6 @@
 use std::fmt;
-use std::io::Write;
 
+use itertools;
 use liquid_error::{Error, Result, ResultLiquidExt};
 use liquid_value::Value;
 
@@ -8,11 +8,16 @@ use compiler::BlockElement;
 use compiler::BlockReflection;
 use compiler::Language;
 use compiler::ParseBlock;
+#[cfg(any(feature = "extra-filters", feature = "jekyll-filters"))]
+use compiler::Tag;
 use compiler::TagBlock;
 use compiler::TagToken;
 use compiler::TagTokenIter;
+use compiler::Warning;
 use interpreter::Context;
 use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
 use interpreter::Template;
 
@@ -58,7 +63,7 @@ impl ComparisonOperator {
 }
 
 #[derive(Clone, Debug)]
-struct BinaryCondition {
+pub(crate) struct BinaryCondition {
     lh: Expression,
     comparison: ComparisonOperator,
     rh: Expression,
@@ -90,7 +95,7 @@ impl fmt::Display for BinaryCondition {
 }
 
 #[derive(Clone, Debug)]
-struct ExistenceCondition {
+pub(crate) struct ExistenceCondition {
     lh: Expression,
 }
 
@@ -107,19 +112,132 @@ impl fmt::Display for ExistenceCondition {
     }
 }
 
+/// What `is` tests a value for, e.g. `string` in `product.title is string`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TypeTest {
+    String,
+    Number,
+    Integer,
+    Float,
+    Boolean,
+    Date,
+    Array,
+    Object,
+    Nil,
+    Empty,
+    Blank,
+    Defined,
+}
+
+impl TypeTest {
+    const NAMES: &'static [(&'static str, TypeTest)] = &[
+        ("string", TypeTest::String),
+        ("number", TypeTest::Number),
+        ("integer", TypeTest::Integer),
+        ("float", TypeTest::Float),
+        ("boolean", TypeTest::Boolean),
+        ("date", TypeTest::Date),
+        ("array", TypeTest::Array),
+        ("object", TypeTest::Object),
+        ("nil", TypeTest::Nil),
+        ("empty", TypeTest::Empty),
+        ("blank", TypeTest::Blank),
+        ("defined", TypeTest::Defined),
+    ];
+
+    fn from_str(s: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|&&(name, _)| name == s)
+            .map(|&(_, test)| test)
+    }
+
+    fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|&&(_, test)| test == self)
+            .map(|&(name, _)| name)
+            .expect("every test is named")
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        let scalar_type = value.as_scalar().map(|scalar| scalar.type_name());
+        match self {
+            TypeTest::String => scalar_type == Some("string"),
+            TypeTest::Number => {
+                scalar_type == Some("whole number") || scalar_type == Some("fractional number")
+            }
+            TypeTest::Integer => scalar_type == Some("whole number"),
+            TypeTest::Float => scalar_type == Some("fractional number"),
+            TypeTest::Boolean => scalar_type == Some("boolean"),
+            TypeTest::Date => scalar_type == Some("date"),
+            TypeTest::Array => value.is_array(),
+            TypeTest::Object => value.is_object(),
+            TypeTest::Nil => value.is_nil(),
+            TypeTest::Empty => *value == Value::Empty,
+            TypeTest::Blank => *value == Value::Blank,
+            TypeTest::Defined => true,
+        }
+    }
+}
+
+/// `is` and `is not` conditions, e.g. `product.tags is not empty`.
 #[derive(Clone, Debug)]
-enum Condition {
+pub(crate) struct TypeCondition {
+    lh: Expression,
+    negated: bool,
+    test: TypeTest,
+}
+
+impl TypeCondition {
+    pub fn evaluate(&self, context: &Context) -> Result<bool> {
+        // Undefined variables are nil to every test but `defined`.
+        let matches = match self.lh.try_evaluate(context) {
+            Some(value) => self.test.matches(value),
+            None => self.test != TypeTest::Defined && self.test.matches(&Value::Nil),
+        };
+        Ok(matches != self.negated)
+    }
+}
+
+impl fmt::Display for TypeCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let not = if self.negated { " not" } else { "" };
+        write!(f, "{} is{} {}", self.lh, not, self.test.name())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Condition {
     Binary(BinaryCondition),
     Existence(ExistenceCondition),
+    Type(TypeCondition),
     Conjunction(Box<Condition>, Box<Condition>),
     Disjunction(Box<Condition>, Box<Condition>),
 }
 
 impl Condition {
+    pub fn references(&self, references: &mut References) {
+        match *self {
+            Condition::Binary(ref c) => {
+                references.expression(&c.lh);
+                references.expression(&c.rh);
+            }
+            Condition::Existence(ref c) => references.expression(&c.lh),
+            Condition::Type(ref c) => references.expression(&c.lh),
+            Condition::Conjunction(ref left, ref right)
+            | Condition::Disjunction(ref left, ref right) => {
+                left.references(references);
+                right.references(references);
+            }
+        }
+    }
+
     pub fn evaluate(&self, context: &Context) -> Result<bool> {
         match *self {
             Condition::Binary(ref c) => c.evaluate(context),
             Condition::Existence(ref c) => c.evaluate(context),
+            Condition::Type(ref c) => c.evaluate(context),
             Condition::Conjunction(ref left, ref right) => {
                 Ok(left.evaluate(context)? && right.evaluate(context)?)
             }
@@ -130,17 +248,126 @@ impl Condition {
     }
 }
 
+impl Condition {
+    /// The value of the condition when it only involves literals, like `"a" == "b"`.
+    fn constant(&self) -> Option<bool> {
+        match *self {
+            Condition::Binary(ref c) => match (&c.lh, &c.rh) {
+                (&Expression::Literal(_), &Expression::Literal(_)) => {
+                    c.evaluate(&Context::new()).ok()
+                }
+                _ => None,
+            },
+            Condition::Existence(ref c) => match c.lh {
+                Expression::Literal(ref value) => Some(value.is_truthy()),
+                Expression::Variable(_)
+                | Expression::Range(..)
+                | Expression::Array(_)
+                | Expression::Arithmetic(..) => None,
+            },
+            Condition::Type(ref c) => match c.lh {
+                Expression::Literal(_) => c.evaluate(&Context::new()).ok(),
+                Expression::Variable(_)
+                | Expression::Range(..)
+                | Expression::Array(_)
+                | Expression::Arithmetic(..) => None,
+            },
+            Condition::Conjunction(ref left, ref right) => {
+                match (left.constant(), right.constant()) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                }
+            }
+            Condition::Disjunction(ref left, ref right) => {
+                match (left.constant(), right.constant()) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Whether the condition is just `true` or `false`, as when toggling a branch on purpose.
+    fn is_bool_literal(&self) -> bool {
+        match *self {
+            Condition::Existence(ExistenceCondition {
+                lh: Expression::Literal(Value::Scalar(ref s)),
+            }) => s.to_bool().is_some(),
+            _ => false,
+        }
+    }
+}
+
+/// Warn about a condition that never changes and the branch it makes unreachable.
+fn warn_constant_condition(
+    options: &Language,
+    tag_name: &str,
+    condition: &Condition,
+    mode: bool,
+    else_tag: Option<&str>,
+    line: usize,
+) {
+    if condition.is_bool_literal() {
+        return;
+    }
+    let value = match condition.constant() {
+        Some(value) => value,
+        None => return,
+    };
+
+    let msg = if value {
+        "Condition is always true"
+    } else {
+        "Condition is always false"
+    };
+    options.warn(
+        Warning::with_msg(msg)
+            .context("tag", tag_name.to_owned())
+            .context("condition", condition.to_string())
+            .context("line", line.to_string()),
+    );
+
+    let unreachable = if value == mode {
+        else_tag
+    } else {
+        Some(tag_name)
+    };
+    if let Some(unreachable) = unreachable {
+        options.warn(
+            Warning::with_msg("Unreachable branch")
+                .context("branch", unreachable.to_owned())
+                .context("condition", condition.to_string())
+                .context("line", line.to_string()),
+        );
+    }
+}
+
 impl fmt::Display for Condition {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Condition::Binary(ref c) => write!(f, "{}", c),
             Condition::Existence(ref c) => write!(f, "{}", c),
-            Condition::Conjunction(ref left, ref right) => write!(f, "{} and {}", left, right),
+            Condition::Type(ref c) => write!(f, "{}", c),
+            Condition::Conjunction(ref left, ref right) => {
+                write_operand(f, left)?;
+                write!(f, " and ")?;
+                write_operand(f, right)
+            }
             Condition::Disjunction(ref left, ref right) => write!(f, "{} or {}", left, right),
         }
     }
 }
 
+/// Writes an operand of `and`, in parentheses when it is an `or`.
+fn write_operand(f: &mut fmt::Formatter, condition: &Condition) -> fmt::Result {
+    match *condition {
+        Condition::Disjunction(..) => write!(f, "({})", condition),
+        _ => write!(f, "{}", condition),
+    }
+}
+
 #[derive(Debug)]
 struct Conditional {
     tag_name: &'static str,
@@ -169,8 +396,14 @@ fn contains_check(a: &Value, b: &Value) -> Result<bool> {
             }
             Ok(false)
         }
+        Value::Lazy(ref arr) => Ok(arr.iter().any(|elem| elem == *b)),
+        Value::Range(ref range) => {
+            let b = b.as_scalar().and_then(|b| b.to_integer());
+            Ok(b.map(|b| range.start() <= b && b <= range.end())
+                .unwrap_or(false))
+        }
         _ => Err(unexpected_value_error(
-            "string | array | object",
+            "string | array | object | range",
             Some(a.type_name()),
         )),
     }
@@ -189,7 +422,7 @@ impl Conditional {
 }
 
 impl Renderable for Conditional {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let condition = self.compare(context).trace_with(|| self.trace().into())?;
         if condition {
             self.if_true
@@ -204,6 +437,14 @@ impl Renderable for Conditional {
 
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        self.condition.references(references);
+        self.if_true.references(references);
+        if let Some(ref template) = self.if_false {
+            template.references(references);
+        }
+    }
 }
 
 struct PeekableTagTokenIter<'a> {
@@ -244,6 +485,21 @@ fn parse_atom_condition(arguments: &mut PeekableTagTokenIter) -> Result<Conditio
         .expect_next("Value expected.")?
         .expect_value()
         .into_result()?;
+    if let Some("is") = arguments.peek().map(TagToken::as_str) {
+        arguments.next();
+        let mut token = arguments.expect_next("Type expected.")?;
+        let negated = token.as_str() == "not";
+        if negated {
+            token = arguments.expect_next("Type expected.")?;
+        }
+        let test = TypeTest::from_str(token.as_str()).ok_or_else(|| {
+            let names = TypeTest::NAMES
+                .iter()
+                .map(|&(name, _)| format!("\"{}\"", name));
+            token.raise_custom_error(&format!("{} expected.", itertools::join(names, ", ")))
+        })?;
+        return Ok(Condition::Type(TypeCondition { lh, negated, test }));
+    }
     let cond = match arguments
         .peek()
         .map(TagToken::as_str)
@@ -267,36 +523,75 @@ fn parse_atom_condition(arguments: &mut PeekableTagTokenIter) -> Result<Conditio
     Ok(cond)
 }
 
-fn parse_conjunction_chain(arguments: &mut PeekableTagTokenIter) -> Result<Condition> {
-    let mut lh = parse_atom_condition(arguments)?;
+/// Parses a condition in parentheses when `grouping` allows them, e.g. `(a or b)`.
+fn parse_group_condition(
+    arguments: &mut PeekableTagTokenIter,
+    grouping: bool,
+) -> Result<Condition> {
+    if !grouping || arguments.peek().map(TagToken::as_str) != Some("(") {
+        return parse_atom_condition(arguments);
+    }
+
+    arguments.next();
+    let condition = parse_disjunction_chain(arguments, grouping)?;
+    let token = arguments.expect_next("\")\" expected.")?;
+    if token.as_str() != ")" {
+        return Err(token.raise_custom_error("\"and\", \"or\" or \")\" expected."));
+    }
+    Ok(condition)
+}
+
+fn parse_conjunction_chain(
+    arguments: &mut PeekableTagTokenIter,
+    grouping: bool,
+) -> Result<Condition> {
+    let mut lh = parse_group_condition(arguments, grouping)?;
 
     while let Some("and") = arguments.peek().map(TagToken::as_str) {
         arguments.next();
-        let rh = parse_atom_condition(arguments)?;
+        let rh = parse_group_condition(arguments, grouping)?;
         lh = Condition::Conjunction(Box::new(lh), Box::new(rh));
     }
 
     Ok(lh)
 }
 
+fn parse_disjunction_chain(
+    arguments: &mut PeekableTagTokenIter,
+    grouping: bool,
+) -> Result<Condition> {
+    let mut lh = parse_conjunction_chain(arguments, grouping)?;
+
+    while let Some("or") = arguments.peek().map(TagToken::as_str) {
+        arguments.next();
+        let rh = parse_conjunction_chain(arguments, grouping)?;
+        lh = Condition::Disjunction(Box::new(lh), Box::new(rh));
+    }
+
+    Ok(lh)
+}
+
+/// Parses a condition on its own, as written in an "if" tag, e.g. `item.price > 20 and item.active`.
+#[cfg(any(feature = "extra-filters", feature = "jekyll-filters"))]
+pub(crate) fn parse_condition_str(text: &str, options: &Language) -> Result<Condition> {
+    let source = format!("{{% if {} %}}", text);
+    let tag = Tag::new(&source)?;
+    parse_condition(tag.into_tokens(), options)
+}
+
 /// Common parsing for "if" and "unless" condition
-fn parse_condition(arguments: TagTokenIter) -> Result<Condition> {
+fn parse_condition(arguments: TagTokenIter, options: &Language) -> Result<Condition> {
     let mut arguments = PeekableTagTokenIter {
         iter: arguments,
         peeked: None,
     };
-    let mut lh = parse_conjunction_chain(&mut arguments)?;
-
-    while let Some(token) = arguments.next() {
-        token
-            .expect_str("or")
-            .into_result_custom_msg("\"and\" or \"or\" expected.")?;
+    let condition = parse_disjunction_chain(&mut arguments, options.arithmetic())?;
 
-        let rh = parse_conjunction_chain(&mut arguments)?;
-        lh = Condition::Disjunction(Box::new(lh), Box::new(rh));
+    if let Some(token) = arguments.next() {
+        return Err(token.raise_custom_error("\"and\" or \"or\" expected."));
     }
 
-    Ok(lh)
+    Ok(condition)
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -329,15 +624,18 @@ impl ParseBlock for UnlessBlock {
         mut tokens: TagBlock,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
-        let condition = parse_condition(arguments)?;
+        let line = arguments.line();
+        let condition = parse_condition(arguments, options)?;
 
         let mut if_true = Vec::new();
         let mut if_false = None;
+        let mut else_tag = None;
 
         while let Some(element) = tokens.next()? {
             match element {
                 BlockElement::Tag(tag) => match tag.name() {
                     "else" => {
+                        else_tag = Some("else");
                         if_false = Some(tokens.parse_all(options)?);
                         break;
                     }
@@ -347,6 +645,8 @@ impl ParseBlock for UnlessBlock {
             }
         }
 
+        warn_constant_condition(options, self.start_tag(), &condition, false, else_tag, line);
+
         let if_true = Template::new(if_true);
         let if_false = if_false.map(Template::new);
 
@@ -371,19 +671,23 @@ fn parse_if(
     tokens: &mut TagBlock,
     options: &Language,
 ) -> Result<Box<dyn Renderable>> {
-    let condition = parse_condition(arguments)?;
+    let line = arguments.line();
+    let condition = parse_condition(arguments, options)?;
 
     let mut if_true = Vec::new();
     let mut if_false = None;
+    let mut else_tag = None;
 
     while let Some(element) = tokens.next()? {
         match element {
             BlockElement::Tag(tag) => match tag.name() {
                 "else" => {
+                    else_tag = Some("else");
                     if_false = Some(tokens.parse_all(options)?);
                     break;
                 }
                 "elsif" => {
+                    else_tag = Some("elsif");
                     if_false = Some(vec![parse_if("elsif", tag.into_tokens(), tokens, options)?]);
                     break;
                 }
@@ -393,6 +697,8 @@ fn parse_if(
         }
     }
 
+    warn_constant_condition(options, tag_name, &condition, true, else_tag, line);
+
     let if_true = Template::new(if_true);
     let if_false = if_false.map(Template::new);
 
@@ -463,6 +769,7 @@ mod test {
     use compiler;
     use interpreter;
     use value::Object;
+    use value::Scalar;
     use value::Value;
 
     fn options() -> Language {
@@ -514,6 +821,53 @@ mod test {
         assert_eq!(output, "if false");
     }
 
+    #[test]
+    fn type_tests() {
+        let mut context = Context::new();
+        context
+            .stack_mut()
+            .set_global("title", Value::scalar("Shoe"));
+        context.stack_mut().set_global("price", Value::scalar(12));
+        context.stack_mut().set_global("weight", Value::scalar(1.5));
+        context.stack_mut().set_global("tags", Value::array(vec![]));
+        context.stack_mut().set_global("note", Value::scalar("  "));
+        context.stack_mut().set_global("gone", Value::Nil);
+
+        let mut render = |condition: &str| {
+            let text = format!("{{% if {} %}}yes{{% else %}}no{{% endif %}}", condition);
+            compiler::parse(&text, &options())
+                .map(interpreter::Template::new)
+                .unwrap()
+                .render(&mut context)
+                .unwrap()
+        };
+        assert_eq!(render("title is string"), "yes");
+        assert_eq!(render("price is string"), "no");
+        assert_eq!(render("price is number and weight is number"), "yes");
+        assert_eq!(render("price is integer"), "yes");
+        assert_eq!(render("weight is integer"), "no");
+        assert_eq!(render("weight is float"), "yes");
+        assert_eq!(render("tags is array"), "yes");
+        assert_eq!(render("tags is empty"), "yes");
+        assert_eq!(render("tags is not empty"), "no");
+        assert_eq!(render("title is empty"), "no");
+        assert_eq!(render("note is blank"), "yes");
+        assert_eq!(render("gone is nil"), "yes");
+        assert_eq!(render("gone is defined"), "yes");
+        assert_eq!(render("missing is defined"), "no");
+        assert_eq!(render("missing is not defined"), "yes");
+        assert_eq!(render("missing is nil"), "yes");
+        assert_eq!(render("missing.deeper is blank"), "yes");
+        assert_eq!(render("title is object or true is boolean"), "yes");
+    }
+
+    #[test]
+    fn type_tests_need_a_known_type() {
+        assert!(compiler::parse("{% if x is %}{% endif %}", &options()).is_err());
+        assert!(compiler::parse("{% if x is not %}{% endif %}", &options()).is_err());
+        assert!(compiler::parse("{% if x is strings %}{% endif %}", &options()).is_err());
+    }
+
     #[test]
     fn implicit_comparison() {
         let text = concat!(
@@ -651,6 +1005,29 @@ mod test {
         assert_eq!(output, "fourth");
     }
 
+    #[test]
+    fn compare_dates() {
+        let text = "{% if post.date > other.date %}newer{% else %}older{% endif %}\
+                    {% if post.date == \"2019-03-01T09:00:00Z\" %} same{% endif %}";
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let date = Scalar::new("2019-03-01 10:00:00 +0100").to_date().unwrap();
+        let mut post = Object::new();
+        post.insert("date".into(), Value::scalar(date));
+        let mut other = Object::new();
+        other.insert("date".into(), Value::scalar("2019-02-28 23:59:59.5 +0000"));
+
+        let mut context = Context::new();
+        context.stack_mut().set_global("post", Value::Object(post));
+        context
+            .stack_mut()
+            .set_global("other", Value::Object(other));
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "newer same");
+    }
+
     #[test]
     fn string_contains_with_literals() {
         let text = "{% if \"Star Wars\" contains \"Star\" %}if true{% endif %}";
@@ -741,7 +1118,7 @@ mod test {
             Value::scalar("Star Trek"),
             Value::scalar("Alien"),
         ];
-        context.stack_mut().set_global("movies", Value::Array(arr));
+        context.stack_mut().set_global("movies", Value::array(arr));
         let output = template.render(&mut context).unwrap();
         assert_eq!(output, "if true");
     }
@@ -755,11 +1132,42 @@ mod test {
 
         let mut context = Context::new();
         let arr = vec![Value::scalar("Alien")];
-        context.stack_mut().set_global("movies", Value::Array(arr));
+        context.stack_mut().set_global("movies", Value::array(arr));
         let output = template.render(&mut context).unwrap();
         assert_eq!(output, "if false");
     }
 
+    #[test]
+    fn contains_with_array_literal() {
+        let text = concat!(
+            "{% if [\"Star Wars\", movie] contains \"Alien\" %}if true{% endif %}",
+            "{% if [1, 2] contains 3 %}{% else %}if false{% endif %}"
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        context
+            .stack_mut()
+            .set_global("movie", Value::scalar("Alien"));
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "if trueif false");
+    }
+
+    #[test]
+    fn contains_with_range() {
+        let text =
+            "{% if (1..5) contains 3 %}if true{% endif %}{% if (1..5) contains 6 %}6{% endif %}";
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "if true");
+    }
+
     #[test]
     fn multiple_conditions_and() {
         let text = "{% if 1 == 1 and 2 == 2 %}if true{% else %}if false{% endif %}";
@@ -1,19 +1,21 @@
-use std::io::Write;
-
 use liquid_error::{Result, ResultLiquidExt, ResultLiquidReplaceExt};
 
+use compiler::BlockElement;
 use compiler::BlockReflection;
 use compiler::Language;
 use compiler::ParseBlock;
 use compiler::TagBlock;
 use compiler::TagTokenIter;
 use interpreter::Context;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
 use interpreter::Template;
 
 #[derive(Debug)]
 struct IfChanged {
     if_changed: Template,
+    else_template: Option<Template>,
 }
 
 impl IfChanged {
@@ -23,7 +25,7 @@ impl IfChanged {
 }
 
 impl Renderable for IfChanged {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let mut rendered = Vec::new();
         self.if_changed
             .render_to(&mut rendered, context)
@@ -32,10 +34,22 @@ impl Renderable for IfChanged {
         let rendered = String::from_utf8(rendered).expect("render only writes UTF-8");
         if context.get_register_mut::<State>().has_changed(&rendered) {
             write!(writer, "{}", rendered).replace("Failed to render")?;
+        } else if let Some(ref else_template) = self.else_template {
+            else_template
+                .render_to(writer, context)
+                .trace("{{% else %}}")
+                .trace_with(|| self.trace().into())?;
         }
 
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        self.if_changed.references(references);
+        if let Some(ref else_template) = self.else_template {
+            else_template.references(references);
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -71,10 +85,32 @@ impl ParseBlock for IfChangedBlock {
         // no arguments should be supplied, trying to supply them is an error
         arguments.expect_nothing()?;
 
-        let if_changed = Template::new(tokens.parse_all(options)?);
+        let mut if_changed = Vec::new();
+        let mut else_template = None;
+
+        while let Some(element) = tokens.next()? {
+            match element {
+                BlockElement::Tag(mut tag) => match tag.name() {
+                    "else" => {
+                        // no more arguments should be supplied, trying to supply them is an error
+                        tag.tokens().expect_nothing()?;
+                        else_template = Some(tokens.parse_all(options)?);
+                        break;
+                    }
+                    _ => if_changed.push(tag.parse(&mut tokens, options)?),
+                },
+                element => if_changed.push(element.parse(&mut tokens, options)?),
+            }
+        }
+
+        let if_changed = Template::new(if_changed);
+        let else_template = else_template.map(Template::new);
 
         tokens.assert_empty();
-        Ok(Box::new(IfChanged { if_changed }))
+        Ok(Box::new(IfChanged {
+            if_changed,
+            else_template,
+        }))
     }
 
     fn reflection(&self) -> &dyn BlockReflection {
@@ -138,4 +174,21 @@ mod test {
         let output = template.render(&mut context).unwrap();
         assert_eq!(output, "\nHey! \nHey! Numbers are now bigger than 5!");
     }
+
+    #[test]
+    fn test_ifchanged_else() {
+        let text = concat!(
+            "{% for a in (1..5) %}",
+            "{% ifchanged %}{% if a > 2 %}big{% else %}small{% endif %}",
+            "{% else %}ditto{% endifchanged %} ",
+            "{% endfor %}",
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let output = template.render(&mut context).unwrap();
+        assert_eq!(output, "small ditto big ditto ditto ");
+    }
 }
@@ -0,0 +1,139 @@
+use liquid_error::{Result, ResultLiquidExt, ResultLiquidReplaceExt};
+
+use compiler::Language;
+use compiler::ParseTag;
+use compiler::TagReflection;
+use compiler::TagTokenIter;
+use interpreter::Context;
+use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
+use interpreter::Renderable;
+use sandbox::FileSandbox;
+
+#[derive(Debug)]
+struct IncludeRaw {
+    path: Expression,
+    sandbox: FileSandbox,
+}
+
+impl Renderable for IncludeRaw {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let path = self.path.evaluate(context)?.to_str().into_owned();
+        let content = self
+            .sandbox
+            .read(&path)
+            .trace_with(|| format!("{{% include_raw {} %}}", self.path).into())?;
+        write!(writer, "{}", content).replace("Failed to render")?;
+        Ok(())
+    }
+
+    fn references(&self, references: &mut References) {
+        references.expression(&self.path);
+    }
+}
+
+/// Inlines a file's content without parsing it, e.g. `{% include_raw "snippet.txt" %}`.
+///
+/// Only files within the `FileSandbox` are available, so this is not registered by
+/// `liquid_tags`.
+#[derive(Clone, Debug, Default)]
+pub struct IncludeRawTag {
+    sandbox: FileSandbox,
+}
+
+impl IncludeRawTag {
+    pub fn new(sandbox: FileSandbox) -> Self {
+        Self { sandbox }
+    }
+}
+
+impl TagReflection for IncludeRawTag {
+    fn tag(&self) -> &'static str {
+        "include_raw"
+    }
+
+    fn description(&self) -> &'static str {
+        "Inlines the content of a file from the sandbox without parsing it."
+    }
+}
+
+impl ParseTag for IncludeRawTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let path = arguments
+            .expect_next("Path expected.")?
+            .expect_value()
+            .into_result()?;
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(IncludeRaw {
+            path,
+            sandbox: self.sandbox.clone(),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use compiler;
+    use interpreter;
+
+    fn options() -> Language {
+        let sandbox = FileSandbox::new()
+            .allow_root(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/input"));
+        let mut options = Language::default();
+        options
+            .tags
+            .register("include_raw", IncludeRawTag::new(sandbox).into());
+        options
+    }
+
+    #[test]
+    fn include_raw_is_not_parsed() {
+        let text = "{% include_raw 'example.txt' %}";
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        let output = template.render(&mut context).unwrap();
+        assert!(output.contains("{{"));
+    }
+
+    #[test]
+    fn include_raw_variable() {
+        let text = "{% include_raw path %}";
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        context
+            .stack_mut()
+            .set_global("path", ::value::Value::scalar("example.txt"));
+        assert!(template.render(&mut context).is_ok());
+    }
+
+    #[test]
+    fn include_raw_outside_sandbox() {
+        let text = "{% include_raw '../../../Cargo.toml' %}";
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let mut context = Context::new();
+        assert!(template.render(&mut context).is_err());
+    }
+}
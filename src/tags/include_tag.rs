@@ -1,37 +1,146 @@
-use std::io::Write;
+use std::fmt;
 
-use liquid_error::{Result, ResultLiquidExt};
+use liquid_error::{Error, Result, ResultLiquidExt};
 
+use compiler::DynamicPartials;
+use compiler::FilterChain;
 use compiler::Language;
 use compiler::ParseTag;
 use compiler::TagReflection;
 use compiler::TagTokenIter;
 use compiler::TryMatchToken;
 use interpreter::Context;
-use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
+use interpreter::Variable;
+
+use super::render_tag::{default_alias, parse_partial_arguments, PartialArguments};
+
+#[derive(Debug)]
+enum PartialName {
+    Literal(String),
+    /// A variable, or the name it spells when undefined, e.g. `footer.html`.
+    Variable(Variable, String),
+    Computed(FilterChain),
+}
+
+impl fmt::Display for PartialName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PartialName::Literal(ref name) => write!(f, "{}", name),
+            PartialName::Variable(_, ref source) => write!(f, "{}", source),
+            PartialName::Computed(ref chain) => write!(f, "{}", chain),
+        }
+    }
+}
 
 #[derive(Debug)]
 struct Include {
-    partial: Expression,
+    partial: PartialName,
+    dynamic: DynamicPartials,
+    arguments: PartialArguments,
+}
+
+impl fmt::Display for Include {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.partial, self.arguments)
+    }
+}
+
+impl Include {
+    fn trace(&self) -> String {
+        format!("{{% include {} %}}", self)
+    }
+
+    fn name(&self, context: &Context) -> Result<String> {
+        let name = match self.partial {
+            PartialName::Literal(ref name) => return Ok(name.clone()),
+            PartialName::Variable(ref variable, ref source) => {
+                let value = variable
+                    .try_evaluate(context)
+                    .and_then(|path| context.stack().try_get(&path).cloned());
+                match value {
+                    Some(value) => value.to_str().into_owned(),
+                    None => return Ok(source.clone()),
+                }
+            }
+            PartialName::Computed(ref chain) => chain.evaluate(context)?.to_str().into_owned(),
+        };
+        if !self.dynamic.allows(&name) {
+            return Error::with_msg("Partial not allowed for a computed name")
+                .context("partial", name)
+                .into_err();
+        }
+        Ok(name)
+    }
 }
 
 impl Renderable for Include {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
-        let name = self.partial.evaluate(context)?.render().to_string();
-        context.run_in_named_scope(name.clone(), |mut scope| -> Result<()> {
-            let partial = scope
-                .partials()
-                .get(&name)
-                .trace_with(|| format!("{{% include {} %}}", self.partial).into())?;
-            partial
-                .render_to(writer, &mut scope)
-                .trace_with(|| format!("{{% include {} %}}", self.partial).into())
-                .context_key_with(|| self.partial.to_string().into())
-                .value_with(|| name.to_string().into())
-        })?;
-
-        Ok(())
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        let name = self.name(context).trace_with(|| self.trace().into())?;
+        let arguments = self
+            .arguments
+            .evaluate_arguments(context)
+            .trace_with(|| self.trace().into())?;
+        let items = self
+            .arguments
+            .evaluate_items(context)
+            .trace_with(|| self.trace().into())?;
+        let alias = self
+            .arguments
+            .alias
+            .clone()
+            .unwrap_or_else(|| default_alias(&name));
+
+        context.add_partial_dependency(&name);
+        context
+            .run_in_partial(&name, |context| {
+                context.run_in_named_scope(name.clone(), |scope| -> Result<()> {
+                    let partial = scope
+                        .partials()
+                        .get(&name)
+                        .trace_with(|| self.trace().into())?;
+                    // Unlike `render`, the partial sees the caller's variables, and the arguments only
+                    // last as long as it does.
+                    for (key, value) in arguments {
+                        scope.stack_mut().set(key, value);
+                    }
+                    let items = match items {
+                        Some(items) => items,
+                        None => {
+                            return partial
+                                .render_to(writer, scope)
+                                .trace_with(|| self.trace().into())
+                                .context_key_with(|| self.partial.to_string().into())
+                                .value_with(|| name.to_string().into());
+                        }
+                    };
+                    for item in items {
+                        scope.stack_mut().set(alias.clone(), item);
+                        partial
+                            .render_to(writer, scope)
+                            .trace_with(|| self.trace().into())
+                            .context_key_with(|| self.partial.to_string().into())
+                            .value_with(|| name.to_string().into())?;
+                    }
+                    Ok(())
+                })
+            })
+            .trace_with(|| self.trace().into())
+    }
+
+    fn references(&self, references: &mut References) {
+        match self.partial {
+            PartialName::Literal(ref name) => references.partial(name.as_str()),
+            // Either the variable is defined, or the partial is named after it.
+            PartialName::Variable(ref variable, ref source) => {
+                references.variable(variable);
+                references.partial(source.as_str());
+            }
+            PartialName::Computed(ref chain) => chain.references(references),
+        }
+        self.arguments.references(references);
     }
 }
 
@@ -50,7 +159,7 @@ impl TagReflection for IncludeTag {
     }
 
     fn description(&self) -> &'static str {
-        ""
+        "Renders a partial in the caller's scope, optionally `with` a value, `for` each item of an array or with `key: value` arguments."
     }
 }
 
@@ -58,24 +167,38 @@ impl ParseTag for IncludeTag {
     fn parse(
         &self,
         mut arguments: TagTokenIter,
-        _options: &Language,
+        options: &Language,
     ) -> Result<Box<dyn Renderable>> {
         let name = arguments.expect_next("Identifier or literal expected.")?;
+        let dynamic = options.dynamic_partials().clone();
 
-        // This may accept strange inputs such as `{% include 0 %}` or `{% include filterchain | filter:0 %}`.
-        // Those inputs would fail anyway by there being not a path with those names so they are not a big concern.
-        let name = match name.expect_literal() {
+        // Without dynamic partials, this may accept strange inputs such as `{% include 0 %}` or
+        // `{% include filterchain | filter:0 %}`.  Those inputs would fail anyway by there being
+        // not a path with those names so they are not a big concern.
+        let partial = match name.expect_literal() {
             // Using `to_str()` on literals ensures `Strings` will have their quotes trimmed.
-            TryMatchToken::Matches(name) => name.to_str().to_string(),
-            TryMatchToken::Fails(name) => name.as_str().to_string(),
+            TryMatchToken::Matches(name) => PartialName::Literal(name.to_str().to_string()),
+            TryMatchToken::Fails(name) if dynamic == DynamicPartials::Deny => {
+                PartialName::Literal(name.as_str().to_string())
+            }
+            TryMatchToken::Fails(name) => {
+                let source = name.as_str().to_string();
+                match name.expect_variable() {
+                    TryMatchToken::Matches(variable) => PartialName::Variable(variable, source),
+                    TryMatchToken::Fails(name) => {
+                        PartialName::Computed(name.expect_filter_chain(options).into_result()?)
+                    }
+                }
+            }
         };
 
-        // no more arguments should be supplied, trying to supply them is an error
-        arguments.expect_nothing()?;
+        let arguments = parse_partial_arguments(&mut arguments)?;
 
-        let partial = Expression::with_literal(name);
-
-        Ok(Box::new(Include { partial }))
+        Ok(Box::new(Include {
+            partial,
+            dynamic,
+            arguments,
+        }))
     }
 
     fn reflection(&self) -> &dyn TagReflection {
@@ -90,6 +213,7 @@ mod test {
     use compiler;
     use compiler::Filter;
     use derive::*;
+    use filters;
     use interpreter;
     use interpreter::ContextBuilder;
     use partials;
@@ -114,6 +238,10 @@ mod test {
 
         fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
             match name {
+                "cards/book" => Some("Book: {{ card.title }}".into()),
+                "cards/secret" => Some("Secret".into()),
+                "banner" => Some("[{{ title }}|{{ color }}|{{ num }}]".into()),
+                "product" => Some("<{{ product.title }}>".into()),
                 "example.txt" => Some(r#"{{'whooo' | size}}{%comment%}What happens{%endcomment%} {%if num < numTwo%}wat{%else%}wot{%endif%} {%if num > numTwo%}wat{%else%}wot{%endif%}"#.into()),
                 _ => None
             }
@@ -123,6 +251,7 @@ mod test {
     fn options() -> Language {
         let mut options = Language::default();
         options.tags.register("include", IncludeTag.into());
+        options.tags.register("assign", tags::AssignTag.into());
         options
             .blocks
             .register("comment", tags::CommentBlock.into());
@@ -223,4 +352,113 @@ mod test {
         let output = template.render(&mut context);
         assert!(output.is_err());
     }
+
+    fn render_dynamic(text: &str, policy: DynamicPartials) -> Result<String> {
+        let mut options = options();
+        options.filters.register("size", Box::new(SizeFilterParser));
+        options
+            .filters
+            .register("append", Box::new(filters::std::Append));
+        options
+            .filters
+            .register("split", Box::new(filters::std::Split));
+        options.set_dynamic_partials(policy);
+        let template = compiler::parse(text, &options).map(interpreter::Template::new)?;
+
+        let partials = partials::OnDemandCompiler::<TestSource>::empty()
+            .compile(::std::sync::Arc::new(options))
+            .unwrap();
+        let mut context = ContextBuilder::new()
+            .set_partials(partials.as_ref())
+            .build();
+        let mut card = value::Object::new();
+        card.insert("type".into(), Value::scalar("book"));
+        card.insert("title".into(), Value::scalar("Dune"));
+        context.stack_mut().set_global("card", Value::Object(card));
+        context
+            .stack_mut()
+            .set_global("template_name", Value::scalar("cards/book"));
+        context
+            .stack_mut()
+            .set_global("num", value::Value::scalar(5f64));
+        context
+            .stack_mut()
+            .set_global("numTwo", value::Value::scalar(10f64));
+        template.render(&mut context)
+    }
+
+    #[test]
+    fn include_computed_name() {
+        let text = r#"{% include "cards/" | append: card.type %}"#;
+        assert_eq!(
+            render_dynamic(text, DynamicPartials::Allow).unwrap(),
+            "Book: Dune"
+        );
+        let text = "{% include template_name %}";
+        assert_eq!(
+            render_dynamic(text, DynamicPartials::Allow).unwrap(),
+            "Book: Dune"
+        );
+        let text = "{% include example.txt %}";
+        assert_eq!(
+            render_dynamic(text, DynamicPartials::Allow).unwrap(),
+            "5 wat wot"
+        );
+    }
+
+    #[test]
+    fn include_computed_name_allowlist() {
+        let allowlist = DynamicPartials::Allowlist(vec!["cards/book".to_owned()]);
+        let text = r#"{% include "cards/" | append: card.type %}"#;
+        assert_eq!(
+            render_dynamic(text, allowlist.clone()).unwrap(),
+            "Book: Dune"
+        );
+        let text = r#"{% include "cards/" | append: "secret" %}"#;
+        assert!(render_dynamic(text, allowlist.clone()).is_err());
+        // Literal names are not restricted.
+        let text = r#"{% include "cards/secret" %}"#;
+        assert_eq!(render_dynamic(text, allowlist).unwrap(), "Secret");
+    }
+
+    #[test]
+    fn include_computed_name_denied() {
+        let text = "{% include template_name %}";
+        assert!(render_dynamic(text, DynamicPartials::Deny).is_err());
+    }
+
+    #[test]
+    fn include_with() {
+        let text = "{% include 'product' with card %}{{ product }}";
+        assert_eq!(
+            render_dynamic(text, DynamicPartials::Deny).unwrap(),
+            "<Dune>"
+        );
+        let text = "{% include 'cards/book' with card as card %}";
+        assert_eq!(
+            render_dynamic(text, DynamicPartials::Deny).unwrap(),
+            "Book: Dune"
+        );
+    }
+
+    #[test]
+    fn include_for() {
+        let text =
+            "{% assign cards = 'a,b' | split: ',' %}{% include 'banner' for cards as title %}";
+        assert_eq!(
+            render_dynamic(text, DynamicPartials::Deny).unwrap(),
+            "[a||5][b||5]"
+        );
+    }
+
+    #[test]
+    fn include_arguments() {
+        let text = r#"{% include 'banner', title: "Hello", color: card.title %}[{{ title }}]"#;
+        assert_eq!(
+            render_dynamic(text, DynamicPartials::Deny).unwrap(),
+            "[Hello|Dune|5][]"
+        );
+        let text = "{% include 'banner' title: 1 title: 2 %}";
+        assert!(render_dynamic(text, DynamicPartials::Deny).is_err());
+    }
 }
@@ -1,5 +1,3 @@
-use std::io::Write;
-
 use liquid_error::{Result, ResultLiquidReplaceExt};
 
 use compiler::Language;
@@ -7,6 +5,8 @@ use compiler::ParseTag;
 use compiler::TagReflection;
 use compiler::TagTokenIter;
 use interpreter::Context;
+use interpreter::Output;
+use interpreter::References;
 use interpreter::Renderable;
 use value::Value;
 
@@ -16,7 +16,7 @@ struct Increment {
 }
 
 impl Renderable for Increment {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let mut val = context
             .stack()
             .get_index(&self.id)
@@ -31,6 +31,10 @@ impl Renderable for Increment {
             .set_index(self.id.to_owned(), Value::scalar(val));
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        references.assign(self.id.as_str());
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -81,7 +85,7 @@ struct Decrement {
 }
 
 impl Renderable for Decrement {
-    fn render_to(&self, writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         let mut val = context
             .stack()
             .get_index(&self.id)
@@ -96,6 +100,10 @@ impl Renderable for Decrement {
             .set_index(self.id.to_owned(), Value::scalar(val));
         Ok(())
     }
+
+    fn references(&self, references: &mut References) {
+        references.assign(self.id.as_str());
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -1,11 +1,10 @@
-use std::io::Write;
-
 use liquid_error::Result;
 
 use compiler::Language;
 use compiler::ParseTag;
 use compiler::TagReflection;
 use compiler::TagTokenIter;
+use interpreter::Output;
 use interpreter::Renderable;
 use interpreter::{Context, Interrupt};
 
@@ -13,7 +12,7 @@ use interpreter::{Context, Interrupt};
 struct Break;
 
 impl Renderable for Break {
-    fn render_to(&self, _writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, _writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         context.interrupt_mut().set_interrupt(Interrupt::Break);
         Ok(())
     }
@@ -58,7 +57,7 @@ impl ParseTag for BreakTag {
 struct Continue;
 
 impl Renderable for Continue {
-    fn render_to(&self, _writer: &mut dyn Write, context: &mut Context) -> Result<()> {
+    fn render_to(&self, _writer: &mut dyn Output, context: &mut Context) -> Result<()> {
         context.interrupt_mut().set_interrupt(Interrupt::Continue);
         Ok(())
     }
@@ -0,0 +1,118 @@
+use liquid_error::Result;
+
+use compiler;
+use compiler::Language;
+use compiler::ParseTag;
+use compiler::TagReflection;
+use compiler::TagTokenIter;
+use interpreter::Renderable;
+use interpreter::Template;
+
+/// Runs many tags, one per line, without the `{% %}` of each.
+///
+/// ```text
+/// {% liquid
+///   assign name = customer.name | default: "stranger"
+///   if customer
+///     echo "Welcome back, " | append: name
+///   else
+///     echo "Hello, " | append: name
+///   endif
+/// %}
+/// ```
+///
+/// Use `echo` to output values.  A block started in the tag has to end in it.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LiquidTag;
+
+impl TagReflection for LiquidTag {
+    fn tag(&self) -> &'static str {
+        "liquid"
+    }
+
+    fn description(&self) -> &'static str {
+        "Runs many tags, one per line, without the `{% %}` of each."
+    }
+}
+
+impl ParseTag for LiquidTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let statements = compiler::parse_statements(arguments.remaining_source(), options)?;
+        Ok(Box::new(Template::new(statements)))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use filters;
+    use interpreter;
+    use interpreter::Context;
+    use tags;
+    use value::Value;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("liquid", LiquidTag.into());
+        options.tags.register("echo", tags::EchoTag.into());
+        options.tags.register("assign", tags::AssignTag.into());
+        options.blocks.register("if", tags::IfBlock.into());
+        options.blocks.register("for", tags::ForBlock.into());
+        options
+            .filters
+            .register("prepend", Box::new(filters::std::Prepend));
+        options
+            .filters
+            .register("plus", Box::new(filters::std::Plus));
+        options
+    }
+
+    fn render(text: &str) -> String {
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+        let mut context = Context::new();
+        context.stack_mut().set_global(
+            "items",
+            Value::array(vec![Value::scalar(1), Value::scalar(2), Value::scalar(3)]),
+        );
+        template.render(&mut context).unwrap()
+    }
+
+    #[test]
+    fn liquid_tag() {
+        let text = concat!(
+            "<{% liquid\n",
+            "  assign total = 0\n",
+            "\n",
+            "  for item in items\n",
+            "    if item > 1\n",
+            "      echo item | prepend: ' '\n",
+            "    endif\n",
+            "    assign total = total | plus: item\n",
+            "  endfor\n",
+            "%}> {{ total }}"
+        );
+        assert_eq!(render(text), "< 2 3> 6");
+    }
+
+    #[test]
+    fn liquid_tag_on_one_line() {
+        assert_eq!(render("{% liquid echo 'a' %}b"), "ab");
+        assert_eq!(render("a{% liquid %}b"), "ab");
+    }
+
+    #[test]
+    fn liquid_tag_blocks_must_end_inside() {
+        let text = "{% liquid\nif true\n%}x{% endif %}";
+        assert!(compiler::parse(text, &options()).is_err());
+    }
+}
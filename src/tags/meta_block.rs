@@ -0,0 +1,171 @@
+use liquid_error::{Error, Result};
+
+use compiler::BlockReflection;
+use compiler::Language;
+use compiler::ParseBlock;
+use compiler::TagBlock;
+use compiler::TagTokenIter;
+use interpreter::Context;
+use interpreter::Output;
+use interpreter::Renderable;
+use value::{Object, Value};
+
+#[derive(Clone, Debug)]
+struct Meta {
+    metadata: Object,
+}
+
+impl Renderable for Meta {
+    fn render_to(&self, _writer: &mut dyn Output, _context: &mut Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn metadata(&self) -> Option<&Object> {
+        Some(&self.metadata)
+    }
+}
+
+fn parse_value(value: &str) -> Value {
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if quoted {
+        return Value::scalar(value[1..value.len() - 1].to_owned());
+    }
+    match value {
+        "" => Value::Nil,
+        "true" => Value::scalar(true),
+        "false" => Value::scalar(false),
+        _ => {
+            if let Ok(integer) = value.parse::<i32>() {
+                Value::scalar(integer)
+            } else if let Ok(float) = value.parse::<f64>() {
+                Value::scalar(float)
+            } else {
+                Value::scalar(value.to_owned())
+            }
+        }
+    }
+}
+
+fn parse_metadata(content: &str) -> Result<Object> {
+    let mut metadata = Object::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let colon = line.find(':').ok_or_else(|| {
+            Error::with_msg("`key: value` expected")
+                .context("line", (i + 1).to_string())
+                .context("found", line.to_owned())
+        })?;
+        let key = line[..colon].trim();
+        if key.is_empty() {
+            return Error::with_msg("Key expected")
+                .context("line", (i + 1).to_string())
+                .into_err();
+        }
+        let value = parse_value(line[colon + 1..].trim());
+        metadata.insert(key.to_owned().into(), value);
+    }
+    Ok(metadata)
+}
+
+/// Declares metadata for the host, e.g. to drive routing or caching, one `key: value` per line.
+///
+/// ```text
+/// {% meta %}
+/// title: Home
+/// layout: default
+/// cache_ttl: 300
+/// {% endmeta %}
+/// ```
+///
+/// Values are whole or fractional numbers, `true`, `false`, quoted strings, or else the rest of
+/// the line as a string.  The block renders nothing; its metadata is available through
+/// `Template::metadata`, for blocks at the top level of the template.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MetaBlock;
+
+impl BlockReflection for MetaBlock {
+    fn start_tag(&self) -> &'static str {
+        "meta"
+    }
+
+    fn end_tag(&self) -> &'static str {
+        "endmeta"
+    }
+
+    fn description(&self) -> &'static str {
+        "Declares `key: value` metadata about the template for the host, rendering nothing."
+    }
+}
+
+impl ParseBlock for MetaBlock {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        mut tokens: TagBlock,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        // no arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        let content = tokens.escape_liquid(false)?;
+        let metadata = parse_metadata(content)?;
+
+        tokens.assert_empty();
+        Ok(Box::new(Meta { metadata }))
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use compiler;
+    use interpreter;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.blocks.register("meta", MetaBlock.into());
+        options
+    }
+
+    #[test]
+    fn meta_block() {
+        let text = concat!(
+            "{% meta %}\n",
+            "# Routing\n",
+            "title: Home: Welcome\n",
+            "layout: 'default'\n",
+            "cache_ttl: 300\n",
+            "weight: 0.5\n",
+            "draft: false\n",
+            "{% endmeta %}Hello"
+        );
+        let template = compiler::parse(text, &options())
+            .map(interpreter::Template::new)
+            .unwrap();
+
+        let output = template.render(&mut Context::new()).unwrap();
+        assert_eq!(output, "Hello");
+
+        let metadata = template.metadata();
+        assert_eq!(metadata["title"], Value::scalar("Home: Welcome"));
+        assert_eq!(metadata["layout"], Value::scalar("default"));
+        assert_eq!(metadata["cache_ttl"], Value::scalar(300));
+        assert_eq!(metadata["weight"], Value::scalar(0.5));
+        assert_eq!(metadata["draft"], Value::scalar(false));
+    }
+
+    #[test]
+    fn meta_block_invalid_line() {
+        let text = "{% meta %}title{% endmeta %}";
+        assert!(compiler::parse(text, &options()).is_err());
+    }
+}
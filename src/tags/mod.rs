@@ -1,29 +1,47 @@
 mod assign_tag;
+mod cache_block;
 mod capture_block;
 mod case_block;
 mod comment_block;
 mod cycle_tag;
+mod debug_tag;
+mod echo_tag;
 mod for_block;
 mod if_block;
 mod ifchanged_block;
+mod include_raw_tag;
 mod include_tag;
 mod increment_tags;
 mod interrupt_tags;
+mod liquid_tag;
+mod meta_block;
+mod placeholder_tag;
 mod raw_block;
+mod render_tag;
 
 pub use self::assign_tag::AssignTag;
+pub use self::cache_block::CacheBlock;
 pub use self::capture_block::CaptureBlock;
 pub use self::case_block::CaseBlock;
 pub use self::comment_block::CommentBlock;
-pub use self::cycle_tag::CycleTag;
+pub use self::cycle_tag::{CycleState, CycleTag, ResetCycleTag};
+pub use self::debug_tag::DebugTag;
+pub use self::echo_tag::EchoTag;
 pub use self::for_block::ForBlock;
 pub use self::for_block::TableRowBlock;
 pub use self::if_block::IfBlock;
 pub use self::if_block::UnlessBlock;
+#[cfg(any(feature = "extra-filters", feature = "jekyll-filters"))]
+pub(crate) use self::if_block::{parse_condition_str, Condition};
 pub use self::ifchanged_block::IfChangedBlock;
+pub use self::include_raw_tag::IncludeRawTag;
 pub use self::include_tag::IncludeTag;
 pub use self::increment_tags::DecrementTag;
 pub use self::increment_tags::IncrementTag;
 pub use self::interrupt_tags::BreakTag;
 pub use self::interrupt_tags::ContinueTag;
+pub use self::liquid_tag::LiquidTag;
+pub use self::meta_block::MetaBlock;
+pub use self::placeholder_tag::PlaceholderTag;
 pub use self::raw_block::RawBlock;
+pub use self::render_tag::RenderTag;
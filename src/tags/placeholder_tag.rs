@@ -0,0 +1,68 @@
+use liquid_error::{Result, ResultLiquidReplaceExt};
+
+use compiler::Language;
+use compiler::ParseTag;
+use compiler::TagReflection;
+use compiler::TagTokenIter;
+use interpreter::Context;
+use interpreter::Output;
+use interpreter::Renderable;
+use placeholders;
+
+#[derive(Clone, Debug)]
+struct Placeholder {
+    name: String,
+}
+
+impl Renderable for Placeholder {
+    fn render_to(&self, writer: &mut dyn Output, _context: &mut Context) -> Result<()> {
+        write!(
+            writer,
+            "{}{}{}",
+            placeholders::START,
+            self.name,
+            placeholders::END
+        )
+        .replace("Failed to render")?;
+        Ok(())
+    }
+}
+
+/// Leaves a placeholder to fill in after rendering, e.g. `{% placeholder cart_count %}`.
+///
+/// See `liquid::placeholders`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PlaceholderTag;
+
+impl TagReflection for PlaceholderTag {
+    fn tag(&self) -> &'static str {
+        "placeholder"
+    }
+
+    fn description(&self) -> &'static str {
+        "Leaves a named placeholder in the output, to be filled in by a second pass."
+    }
+}
+
+impl ParseTag for PlaceholderTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let name = arguments
+            .expect_next("Identifier expected.")?
+            .expect_identifier()
+            .into_result()?
+            .to_string();
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(Placeholder { name }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
@@ -1,5 +1,3 @@
-use std::io::Write;
-
 use liquid_error::{Result, ResultLiquidReplaceExt};
 
 use compiler::BlockReflection;
@@ -8,6 +6,7 @@ use compiler::ParseBlock;
 use compiler::TagBlock;
 use compiler::TagTokenIter;
 use interpreter::Context;
+use interpreter::Output;
 use interpreter::Renderable;
 
 #[derive(Clone, Debug)]
@@ -16,7 +15,7 @@ struct RawT {
 }
 
 impl Renderable for RawT {
-    fn render_to(&self, writer: &mut dyn Write, _context: &mut Context) -> Result<()> {
+    fn render_to(&self, writer: &mut dyn Output, _context: &mut Context) -> Result<()> {
         write!(writer, "{}", self.content).replace("Failed to render")?;
         Ok(())
     }
@@ -0,0 +1,410 @@
+use std::fmt;
+
+use liquid_error::{Result, ResultLiquidExt};
+
+use compiler::Language;
+use compiler::ParseTag;
+use compiler::TagReflection;
+use compiler::TagTokenIter;
+use compiler::TryMatchToken;
+use interpreter::Context;
+use interpreter::Expression;
+use interpreter::Output;
+use interpreter::References;
+use interpreter::Renderable;
+use value::{Object, Value};
+
+/// How a partial is rendered: once, `with` a value or `for` each item of an array.
+#[derive(Debug)]
+pub(super) enum RenderMode {
+    Once,
+    With(Expression),
+    For(Expression),
+}
+
+/// What follows the name of a partial in `render` and `include`, e.g.
+/// `with product as item, title: 'Hi'`.
+#[derive(Debug)]
+pub(super) struct PartialArguments {
+    pub(super) mode: RenderMode,
+    /// The variable the `with` or `for` value is set as, when given with `as`.
+    pub(super) alias: Option<String>,
+    pub(super) arguments: Vec<(String, Expression)>,
+}
+
+impl PartialArguments {
+    /// The `key: value` arguments, in the caller's scope.
+    pub(super) fn evaluate_arguments(&self, context: &Context) -> Result<Vec<(String, Value)>> {
+        let mut arguments = Vec::with_capacity(self.arguments.len());
+        for (key, value) in &self.arguments {
+            arguments.push((key.clone(), value.evaluate(context)?.clone()));
+        }
+        Ok(arguments)
+    }
+
+    /// The values to render the partial with, one at a time, unless it is rendered once.
+    pub(super) fn evaluate_items(&self, context: &Context) -> Result<Option<Vec<Value>>> {
+        let items = match self.mode {
+            RenderMode::Once => None,
+            RenderMode::With(ref value) => Some(vec![value.evaluate(context)?.clone()]),
+            RenderMode::For(ref value) => match *value.evaluate(context)? {
+                Value::Array(ref items) => Some(items.clone().into_vec()),
+                ref value => Some(vec![value.clone()]),
+            },
+        };
+        Ok(items)
+    }
+
+    /// Record the globals of the `with` or `for` value and of the arguments.
+    pub(super) fn references(&self, references: &mut References) {
+        match self.mode {
+            RenderMode::Once => (),
+            RenderMode::With(ref value) | RenderMode::For(ref value) => {
+                references.expression(value)
+            }
+        }
+        for (_, value) in &self.arguments {
+            references.expression(value);
+        }
+    }
+
+    pub(super) fn is_for(&self) -> bool {
+        matches!(self.mode, RenderMode::For(_))
+    }
+}
+
+impl fmt::Display for PartialArguments {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            RenderMode::Once => (),
+            RenderMode::With(ref value) => write!(f, " with {}", value)?,
+            RenderMode::For(ref value) => write!(f, " for {}", value)?,
+        }
+        if let Some(ref alias) = self.alias {
+            write!(f, " as {}", alias)?;
+        }
+        for (key, value) in &self.arguments {
+            write!(f, ", {}: {}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse what follows the name of a partial: an optional `with` or `for` value, an optional
+/// `as` alias, then `key: value` arguments separated by commas.
+pub(super) fn parse_partial_arguments(arguments: &mut TagTokenIter) -> Result<PartialArguments> {
+    let mut mode = RenderMode::Once;
+    let mut alias = None;
+    let mut next = arguments.next();
+    if let Some(token) = next.take() {
+        let keyword = token.as_str().to_owned();
+        match keyword.as_str() {
+            "with" | "for" => {
+                let value = arguments
+                    .expect_next("Value expected.")?
+                    .expect_value()
+                    .into_result()?;
+                mode = if keyword == "with" {
+                    RenderMode::With(value)
+                } else {
+                    RenderMode::For(value)
+                };
+                next = arguments.next();
+                if let Some(token) = next.take() {
+                    match token.expect_str("as") {
+                        TryMatchToken::Matches(()) => {
+                            alias = Some(
+                                arguments
+                                    .expect_next("Identifier expected.")?
+                                    .expect_identifier()
+                                    .into_result()?
+                                    .to_owned(),
+                            );
+                            next = arguments.next();
+                        }
+                        TryMatchToken::Fails(token) => next = Some(token),
+                    }
+                }
+            }
+            _ => next = Some(token),
+        }
+    }
+
+    let mut partial_arguments = Vec::new();
+    while let Some(token) = next.take() {
+        // Arguments are separated by commas, which are optional before the first one.
+        let token = match token.expect_str(",") {
+            TryMatchToken::Matches(()) => arguments.expect_next("Identifier expected.")?,
+            TryMatchToken::Fails(token) if partial_arguments.is_empty() => token,
+            TryMatchToken::Fails(token) => {
+                return Err(token.raise_custom_error("\",\" expected."));
+            }
+        };
+        let key = token.expect_identifier().into_result()?.to_owned();
+        arguments
+            .expect_next("\":\" expected.")?
+            .expect_str(":")
+            .into_result_custom_msg("\":\" expected.")?;
+        let value = arguments
+            .expect_next("Value expected.")?
+            .expect_value()
+            .into_result()?;
+        partial_arguments.push((key, value));
+        next = arguments.next();
+    }
+
+    Ok(PartialArguments {
+        mode,
+        alias,
+        arguments: partial_arguments,
+    })
+}
+
+#[derive(Debug)]
+struct Render {
+    name: String,
+    alias: String,
+    arguments: PartialArguments,
+}
+
+impl Render {
+    fn trace(&self) -> String {
+        format!("{{% render {} %}}", self)
+    }
+}
+
+impl fmt::Display for Render {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}'{}", self.name, self.arguments)
+    }
+}
+
+fn forloop(index: usize, length: usize) -> Value {
+    let mut helper_vars = Object::new();
+    helper_vars.insert("length".into(), Value::scalar(length as i32));
+    helper_vars.insert("index0".into(), Value::scalar(index as i32));
+    helper_vars.insert("index".into(), Value::scalar((index + 1) as i32));
+    helper_vars.insert("rindex0".into(), Value::scalar((length - index - 1) as i32));
+    helper_vars.insert("rindex".into(), Value::scalar((length - index) as i32));
+    helper_vars.insert("first".into(), Value::scalar(index == 0));
+    helper_vars.insert("last".into(), Value::scalar(index == length - 1));
+    Value::Object(helper_vars)
+}
+
+impl Renderable for Render {
+    fn render_to(&self, writer: &mut dyn Output, context: &mut Context) -> Result<()> {
+        // Everything is evaluated in the caller's scope, before the partial is isolated from it.
+        let arguments = self
+            .arguments
+            .evaluate_arguments(context)
+            .trace_with(|| self.trace().into())?;
+        let items = self
+            .arguments
+            .evaluate_items(context)
+            .trace_with(|| self.trace().into())?;
+        let loop_length = match items {
+            Some(ref items) if self.arguments.is_for() => Some(items.len()),
+            _ => None,
+        };
+
+        context.add_partial_dependency(&self.name);
+        let partial = context
+            .partials()
+            .get(&self.name)
+            .trace_with(|| self.trace().into())?;
+        context
+            .run_in_partial(&self.name, |context| {
+                context.run_in_isolated_scope(self.name.clone(), |scope| -> Result<()> {
+                    for (key, value) in arguments {
+                        scope.stack_mut().set(key, value);
+                    }
+                    let items = match items {
+                        Some(items) => items,
+                        None => {
+                            return partial
+                                .render_to(writer, scope)
+                                .trace_with(|| self.trace().into());
+                        }
+                    };
+                    for (i, item) in items.into_iter().enumerate() {
+                        if let Some(length) = loop_length {
+                            scope.stack_mut().set("forloop", forloop(i, length));
+                        }
+                        scope.stack_mut().set(self.alias.clone(), item);
+                        partial
+                            .render_to(writer, scope)
+                            .trace_with(|| self.trace().into())
+                            .context_key("index")
+                            .value_with(|| format!("{}", i + 1).into())?;
+                    }
+                    Ok(())
+                })
+            })
+            .trace_with(|| self.trace().into())
+    }
+
+    fn references(&self, references: &mut References) {
+        self.arguments.references(references);
+        references.partial(self.name.as_str());
+    }
+}
+
+/// The variable a partial sees its `with` or `for` value as by default, e.g. `product` for
+/// `snippets/product.liquid`.
+pub(super) fn default_alias(name: &str) -> String {
+    let file = name.rsplit('/').next().unwrap_or(name);
+    let stem = file.split('.').next().unwrap_or(file);
+    stem.to_owned()
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RenderTag;
+
+impl TagReflection for RenderTag {
+    fn tag(&self) -> &'static str {
+        "render"
+    }
+
+    fn description(&self) -> &'static str {
+        "Renders a partial that only sees the globals and the variables passed to it."
+    }
+}
+
+impl ParseTag for RenderTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let name = arguments
+            .expect_next("Partial name expected.")?
+            .expect_literal()
+            .into_result_custom_msg("Quoted partial name expected.")?
+            .to_str()
+            .into_owned();
+
+        let arguments = parse_partial_arguments(&mut arguments)?;
+        let alias = arguments
+            .alias
+            .clone()
+            .unwrap_or_else(|| default_alias(&name));
+
+        Ok(Box::new(Render {
+            name,
+            alias,
+            arguments,
+        }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow;
+    use std::sync;
+
+    use compiler;
+    use interpreter;
+    use interpreter::ContextBuilder;
+    use partials;
+    use partials::PartialCompiler;
+    use tags;
+
+    use super::*;
+
+    #[derive(Default, Debug, Clone, Copy)]
+    struct TestSource;
+
+    impl partials::PartialSource for TestSource {
+        fn contains(&self, _name: &str) -> bool {
+            true
+        }
+
+        fn names(&self) -> Vec<&str> {
+            vec![]
+        }
+
+        fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
+            match name {
+                "card" => Some("[{{ card.title }}|{{ secret }}|{{ site }}]".into()),
+                "snippets/item.liquid" => {
+                    Some("{{ forloop.index }}/{{ forloop.length }}:{{ item }} ".into())
+                }
+                "args" => Some("{{ title }} {{ product.title }}{% assign leaked = 1 %}".into()),
+                _ => None,
+            }
+        }
+    }
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("render", RenderTag.into());
+        options.tags.register("assign", tags::AssignTag.into());
+        options
+    }
+
+    fn render(text: &str) -> Result<String> {
+        let options = options();
+        let template = compiler::parse(text, &options).map(interpreter::Template::new)?;
+
+        let partials = partials::OnDemandCompiler::<TestSource>::empty()
+            .compile(sync::Arc::new(options))
+            .unwrap();
+        let mut globals = Object::new();
+        globals.insert("site".into(), Value::scalar("example.com"));
+        let mut context = ContextBuilder::new()
+            .set_globals(&globals)
+            .set_partials(partials.as_ref())
+            .build();
+        let mut product = Object::new();
+        product.insert("title".into(), Value::scalar("Shoe"));
+        context
+            .stack_mut()
+            .set_global("product", Value::Object(product));
+        let items = vec![Value::scalar("a"), Value::scalar("b"), Value::scalar("c")];
+        context.stack_mut().set_global("items", Value::array(items));
+        template.render(&mut context)
+    }
+
+    #[test]
+    fn render_isolates_scope() {
+        let output = render("{% assign secret = 'x' %}{% render 'card' %}").unwrap();
+        assert_eq!(output, "[||example.com]");
+
+        let output = render("{% render 'args' %}{{ leaked }}").unwrap();
+        assert_eq!(output, " ");
+    }
+
+    #[test]
+    fn render_with() {
+        let output = render("{% render 'card' with product %}").unwrap();
+        assert_eq!(output, "[Shoe||example.com]");
+
+        let output = render("{% render 'args' with product as product, title: 'Hi' %}").unwrap();
+        assert_eq!(output, "Hi Shoe");
+    }
+
+    #[test]
+    fn render_arguments() {
+        let output = render("{% render 'args', title: product.title %}").unwrap();
+        assert_eq!(output, "Shoe ");
+    }
+
+    #[test]
+    fn render_for() {
+        let output = render("{% render 'snippets/item.liquid' for items %}").unwrap();
+        assert_eq!(output, "1/3:a 2/3:b 3/3:c ");
+
+        let output = render("{% render 'snippets/item.liquid' for items as other %}").unwrap();
+        assert_eq!(output, "1/3: 2/3: 3/3: ");
+    }
+
+    #[test]
+    fn render_requires_literal_name() {
+        assert!(compiler::parse("{% render card %}", &options()).is_err());
+        assert!(compiler::parse("{% render 'card' title: 1 title: 2 %}", &options()).is_err());
+    }
+}
@@ -1,14 +1,30 @@
+use std::cell;
+#[cfg(feature = "async")]
+use std::future::Future;
 use std::io::Write;
+use std::rc;
 use std::sync;
+use std::time::Instant;
 
-use liquid_error::Result;
+use liquid_compiler as compiler;
+use liquid_error::{Error, Result};
 use liquid_interpreter as interpreter;
 use liquid_interpreter::PartialStore;
 use liquid_interpreter::Renderable;
+use liquid_value::Object;
+
+use parser::Parser;
+use profiler;
+use report::RenderReport;
+use source_map;
 
 pub struct Template {
-    pub(crate) template: interpreter::Template,
+    pub(crate) template: sync::Arc<interpreter::Template>,
     pub(crate) partials: Option<sync::Arc<dyn PartialStore + Send + Sync>>,
+    pub(crate) environment: sync::Arc<interpreter::Environment>,
+    pub(crate) bound: sync::Arc<Object>,
+    pub(crate) metadata: sync::Arc<Object>,
+    pub(crate) warnings: sync::Arc<Vec<compiler::Warning>>,
 }
 
 impl Template {
@@ -16,7 +32,7 @@ impl Template {
     pub fn render(&self, globals: &dyn interpreter::ValueStore) -> Result<String> {
         const BEST_GUESS: usize = 10_000;
         let mut data = Vec::with_capacity(BEST_GUESS);
-        self.render_to(&mut data, globals)?;
+        self.render_to_output(&mut data, globals)?;
 
         Ok(convert_buffer(data))
     }
@@ -27,13 +43,364 @@ impl Template {
         writer: &mut dyn Write,
         globals: &dyn interpreter::ValueStore,
     ) -> Result<()> {
-        let context = interpreter::ContextBuilder::new().set_globals(globals);
+        self.render_to_output(&mut interpreter::WriteOutput(writer), globals)
+    }
+
+    /// Renders an instance of the Template through an `Output`, using the given globals.
+    ///
+    /// This lets an `Output` wrapper tell the values of expressions from template text.
+    pub fn render_to_output(
+        &self,
+        writer: &mut dyn interpreter::Output,
+        globals: &dyn interpreter::ValueStore,
+    ) -> Result<()> {
+        let mut context = self.context_builder(globals).build();
+        self.render_limited(writer, &mut context)
+    }
+
+    /// Renders an instance of the Template, using the given globals, reporting on the render.
+    ///
+    /// ```rust
+    /// let template = liquid::ParserBuilder::with_liquid()
+    ///     .build()
+    ///     .unwrap()
+    ///     .parse("{{ name | upcase }}{{ missing }}")
+    ///     .unwrap();
+    /// let mut globals = liquid::value::Object::new();
+    /// globals.insert("name".into(), liquid::value::Value::scalar("ada"));
+    ///
+    /// let (output, report) = template.render_with_report(&globals).unwrap();
+    /// assert_eq!(output, "ADA");
+    /// assert_eq!(report.undefined_variables, vec!["missing"]);
+    /// assert_eq!(report.filters["upcase"], 1);
+    /// assert_eq!(report.output_bytes, 3);
+    /// ```
+    ///
+    /// See `RenderReport`.
+    pub fn render_with_report(
+        &self,
+        globals: &dyn interpreter::ValueStore,
+    ) -> Result<(String, RenderReport)> {
+        let started = Instant::now();
+        let mut data = Vec::new();
+        let mut context = self.context_builder(globals).build();
+        let (result, usage) =
+            context.record_usage(|context| self.render_limited(&mut data, context));
+        result?;
+        let output = convert_buffer(data);
+        let report = RenderReport::new(&self.warnings, &usage, output.len(), started.elapsed());
+        Ok((output, report))
+    }
+
+    /// Renders an instance of the Template, using the given globals, with the errors the
+    /// environment's `ErrorPolicy` carried on after.
+    ///
+    /// ```rust
+    /// use liquid::interpreter::{Environment, ErrorPolicy};
+    ///
+    /// let policy = ErrorPolicy::placeholder(|error| format!("<!-- {} -->", error.message()));
+    /// let template = liquid::ParserBuilder::with_liquid()
+    ///     .environment(Environment::new().with_error_policy(policy))
+    ///     .build()
+    ///     .unwrap()
+    ///     .parse("a{{ 1 | divided_by: 0 }}b")
+    ///     .unwrap();
+    ///
+    /// let (output, errors) = template
+    ///     .render_with_errors(&liquid::value::Object::new())
+    ///     .unwrap();
+    /// assert_eq!(output, "a<!-- Division by zero -->b");
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    pub fn render_with_errors(
+        &self,
+        globals: &dyn interpreter::ValueStore,
+    ) -> Result<(String, Vec<Error>)> {
+        let mut data = Vec::new();
+        let mut context = self.context_builder(globals).build();
+        self.render_limited(&mut data, &mut context)?;
+        Ok((convert_buffer(data), context.take_suppressed_errors()))
+    }
+
+    /// Renders an instance of the Template, using the given globals, timing its nodes.
+    ///
+    /// Only the nodes of templates parsed by a parser instrumented with a
+    /// `profiler::Profiler` are timed.  See `profiler`.
+    pub fn render_with_profile(
+        &self,
+        globals: &dyn interpreter::ValueStore,
+    ) -> Result<(String, profiler::Profile)> {
+        let started = Instant::now();
+        let mut data = Vec::new();
+        let mut context = self
+            .context_builder(globals)
+            .set_register(profiler::Recording::default())
+            .build();
+        self.render_limited(&mut data, &mut context)?;
+        let profile = profiler::take_recording(&mut context).into_profile(started.elapsed());
+        Ok((convert_buffer(data), profile))
+    }
+
+    /// Renders an instance of the Template, using the given globals, mapping its output back to
+    /// the nodes that wrote it.
+    ///
+    /// Only the nodes of templates parsed by a parser instrumented with a
+    /// `source_map::SourceMapper` are mapped.  See `source_map`.
+    pub fn render_with_source_map(
+        &self,
+        globals: &dyn interpreter::ValueStore,
+    ) -> Result<(String, source_map::SourceMap)> {
+        let written = rc::Rc::new(cell::Cell::new(0));
+        let mut data = Vec::new();
+        let mut context = self
+            .context_builder(globals)
+            .set_register(source_map::Recording::new(written.clone()))
+            .build();
+        self.render_limited(
+            &mut source_map::CountedOutput::new(&mut data, written),
+            &mut context,
+        )?;
+        let map = source_map::take_recording(&mut context).into_source_map();
+        Ok((convert_buffer(data), map))
+    }
+
+    /// Renders an instance of the Template, with globals computed asynchronously by `provider`.
+    ///
+    /// The globals the template and the partials it names literally refer to are awaited before
+    /// rendering, so rendering doesn't wait on them.  Globals only read by partials whose names
+    /// are computed are awaited once a render reads them, rendering again.
+    ///
+    /// ```rust
+    /// use std::future::{self, Future};
+    /// use std::sync::Arc;
+    /// use std::task::{Context, Poll, Wake, Waker};
+    ///
+    /// use liquid::error::Result;
+    /// use liquid::interpreter::{AsyncGlobalsProvider, BoxFuture};
+    /// use liquid::value::Value;
+    ///
+    /// struct Site;
+    ///
+    /// struct Noop;
+    ///
+    /// impl Wake for Noop {
+    ///     fn wake(self: Arc<Self>) {}
+    /// }
+    ///
+    /// impl AsyncGlobalsProvider for Site {
+    ///     fn names(&self) -> Vec<&str> {
+    ///         vec!["title"]
+    ///     }
+    ///
+    ///     fn get<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Value>>> {
+    ///         let title = Some(Value::scalar("Blog")).filter(|_| name == "title");
+    ///         Box::pin(future::ready(Ok(title)))
+    ///     }
+    /// }
+    ///
+    /// let template = liquid::ParserBuilder::with_liquid()
+    ///     .build()
+    ///     .unwrap()
+    ///     .parse("{{ title | upcase }}")
+    ///     .unwrap();
+    /// let mut render = Box::pin(template.render_async(&Site));
+    /// let waker = Waker::from(Arc::new(Noop));
+    /// let mut context = Context::from_waker(&waker);
+    /// match render.as_mut().poll(&mut context) {
+    ///     Poll::Ready(output) => assert_eq!(output.unwrap(), "BLOG"),
+    ///     Poll::Pending => unreachable!("nothing to wait on"),
+    /// }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn render_async<'a>(
+        &'a self,
+        provider: &'a dyn interpreter::AsyncGlobalsProvider,
+    ) -> impl Future<Output = Result<String>> + Send + 'a {
+        let names: Vec<_> = self
+            .referenced_globals()
+            .variables()
+            .map(|path| path.split('.').next().unwrap_or(path).to_owned())
+            .collect();
+        interpreter::render_fetching(provider, names, move |globals| {
+            let mut data = Vec::new();
+            let mut context = self.context_builder(globals).build();
+            let (result, usage) =
+                context.record_usage(|context| self.render_limited(&mut data, context));
+            (result.map(|()| convert_buffer(data)), usage)
+        })
+    }
+
+    /// Render to `writer`, failing once the output is larger than the environment's `Limits`
+    /// allow.
+    fn render_limited(
+        &self,
+        writer: &mut dyn interpreter::Output,
+        context: &mut interpreter::Context,
+    ) -> Result<()> {
+        let mut limited = interpreter::LimitedOutput::new(writer, self.environment.limits());
+        let result = self.template.render_to(&mut limited, context);
+        limited.check()?;
+        result
+    }
+
+    fn context_builder<'g>(
+        &'g self,
+        globals: &'g dyn interpreter::ValueStore,
+    ) -> interpreter::ContextBuilder<'g> {
+        let context = interpreter::ContextBuilder::new()
+            .set_globals(globals)
+            .set_shared_globals(self.bound.clone())
+            .set_environment(self.environment.clone());
         let context = match self.partials {
             Some(ref partials) => context.set_partials(partials.as_ref()),
             None => context,
         };
-        let mut context = context.build();
-        self.template.render_to(writer, &mut context)
+        context
+    }
+
+    /// Returns a handle to the same template with some globals already bound.
+    ///
+    /// Render the handle with the remaining, per-render globals.  Those take precedence over
+    /// bound globals of the same name.  The parsed template and the bound globals are shared by
+    /// every render, not copied, and are read-only to them.
+    pub fn bind(&self, globals: Object) -> Template {
+        let mut bound = (*self.bound).clone();
+        bound.extend(globals);
+        Template {
+            template: self.template.clone(),
+            partials: self.partials.clone(),
+            environment: self.environment.clone(),
+            bound: sync::Arc::new(bound),
+            metadata: self.metadata.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
+
+    /// Returns a handle to the same template rendering in `locale`, an IETF language tag, e.g.
+    /// to render a page in the locale of each visitor.
+    ///
+    /// The locale is that of `liquid.locale` and of the translations of the `t` filter, see
+    /// `i18n::Catalog`.  Like `bind`, the parsed template is shared, not copied.
+    pub fn with_locale<S: Into<String>>(&self, locale: S) -> Template {
+        Template {
+            template: self.template.clone(),
+            partials: self.partials.clone(),
+            environment: sync::Arc::new((*self.environment).clone().with_locale(locale)),
+            bound: self.bound.clone(),
+            metadata: self.metadata.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
+
+    /// The metadata the template declares, with `{% meta %}` blocks at its top level.
+    ///
+    /// ```rust
+    /// use liquid::value::Value;
+    ///
+    /// let template = liquid::ParserBuilder::with_liquid()
+    ///     .block(liquid::tags::MetaBlock)
+    ///     .build()
+    ///     .unwrap()
+    ///     .parse("{% meta %}layout: post\ncache_ttl: 60{% endmeta %}{{ content }}")
+    ///     .unwrap();
+    /// assert_eq!(template.metadata()["layout"], Value::scalar("post"));
+    /// assert_eq!(template.metadata()["cache_ttl"], Value::scalar(60));
+    /// ```
+    ///
+    /// See `tags::MetaBlock`.
+    pub fn metadata(&self) -> &Object {
+        &self.metadata
+    }
+
+    /// The paths of the globals the template refers to, in the order it first does, without
+    /// rendering it.
+    ///
+    /// ```rust
+    /// let template = liquid::ParserBuilder::with_liquid()
+    ///     .build()
+    ///     .unwrap()
+    ///     .parse(concat!(
+    ///         "{{ user.address.city }}",
+    ///         "{% for item in cart.items %}{{ item.price | times: rate }}{% endfor %}",
+    ///     ))
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     template.variables(),
+    ///     vec!["user.address.city", "cart.items", "rate"]
+    /// );
+    /// ```
+    ///
+    /// Paths stop at the first index that isn't a literal, e.g. `products` for
+    /// `products[i].title`.  Variables the template sets, like with `assign` or a `for` loop,
+    /// aren't globals.  Partials are not looked into.
+    pub fn variables(&self) -> Vec<String> {
+        self.references().variables().map(str::to_owned).collect()
+    }
+
+    /// The names of the partials the template includes or renders, in the order it first does.
+    ///
+    /// Partials named by computed values can't be known without rendering.
+    pub fn partials(&self) -> Vec<String> {
+        self.references().partials().map(str::to_owned).collect()
+    }
+
+    fn references(&self) -> interpreter::References {
+        let mut references = interpreter::References::new();
+        self.template.references(&mut references);
+        references
+    }
+
+    /// The references of the template and of the partials it names literally, recursively.
+    #[cfg(feature = "async")]
+    fn referenced_globals(&self) -> interpreter::References {
+        let mut references = self.references();
+        let partials = match self.partials {
+            Some(ref partials) => partials,
+            None => return references,
+        };
+        let mut walked: Vec<String> = Vec::new();
+        loop {
+            let pending: Vec<String> = references
+                .partials()
+                .filter(|name| !walked.iter().any(|walked| walked == name))
+                .map(str::to_owned)
+                .collect();
+            if pending.is_empty() {
+                return references;
+            }
+            for name in pending {
+                if let Some(partial) = partials.try_get(&name) {
+                    partial.references(&mut references);
+                }
+                walked.push(name);
+            }
+        }
+    }
+}
+
+/// A template source checked at compile time by `template!` or `template_str!`.
+///
+/// The source is embedded in the binary and parsed at runtime, by a `Parser` that should have
+/// the tags, blocks and filters declared to the macro.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EmbeddedTemplate {
+    source: &'static str,
+}
+
+impl EmbeddedTemplate {
+    #[doc(hidden)]
+    pub const fn new(source: &'static str) -> Self {
+        Self { source }
+    }
+
+    /// The template source.
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /// Parses the template with `parser`.
+    pub fn parse(&self, parser: &Parser) -> Result<Template> {
+        parser.parse(self.source)
     }
 }
 
@@ -0,0 +1,302 @@
+//! Tests written by template authors, for their own templates.
+//!
+//! Test cases are YAML, either embedded in the template as a comment that starts with `test:`
+//!
+//! ```liquid
+//! Hello, {{ user.name | default: "stranger" }}!
+//! {% comment %}
+//! test: greets by name
+//! globals:
+//!   user:
+//!     name: Ann
+//! expect: Hello, Ann!
+//! {% endcomment %}
+//! ```
+//!
+//! or in a sidecar file next to it, `greeting.liquid.test.yml`, holding a list of them:
+//!
+//! ```yaml
+//! - test: greets strangers
+//!   expect:
+//!     - Hello,
+//!     - stranger
+//! - test: exact output
+//!   output: "Hello, stranger!\n"
+//! ```
+//!
+//! `expect` lists fragments the output must contain; `output` is the exact expected output.
+//!
+//! ```rust
+//! let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+//! let source = "{{ a | plus: 1 }}{% comment %}test: adds\nglobals: {a: 1}\nexpect: '2'{% endcomment %}";
+//! let outcomes = liquid::testing::TestRunner::new(&parser).run(source).unwrap();
+//! assert!(outcomes.iter().all(|o| o.passed()));
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path;
+
+use liquid_error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
+use liquid_value::Object;
+use regex::Regex;
+use serde::Deserialize;
+use serde_yaml;
+
+use parser::Parser;
+
+/// A single test of a template.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct TestCase {
+    /// What is being tested.
+    #[serde(rename = "test")]
+    pub name: String,
+    /// The globals to render the template with.
+    #[serde(default)]
+    pub globals: Object,
+    /// Fragments the output must contain.
+    #[serde(default)]
+    pub expect: Fragments,
+    /// The exact output, when it should be checked.
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// Fragments, given as a single string or a list.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(from = "FragmentsRepr")]
+pub struct Fragments(pub Vec<String>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FragmentsRepr {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<FragmentsRepr> for Fragments {
+    fn from(repr: FragmentsRepr) -> Self {
+        match repr {
+            FragmentsRepr::One(fragment) => Fragments(vec![fragment]),
+            FragmentsRepr::Many(fragments) => Fragments(fragments),
+        }
+    }
+}
+
+/// The result of running a `TestCase`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestOutcome {
+    /// The `TestCase` name.
+    pub name: String,
+    /// Why the test failed, if it did.
+    pub failure: Option<String>,
+}
+
+impl TestOutcome {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl fmt::Display for TestOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.failure {
+            Some(ref failure) => write!(f, "FAILED {}: {}", self.name, failure),
+            None => write!(f, "ok {}", self.name),
+        }
+    }
+}
+
+/// Find the test cases embedded in `{% comment %}` blocks of a template.
+pub fn embedded_cases(source: &str) -> Result<Vec<TestCase>> {
+    lazy_static! {
+        static ref COMMENT: Regex =
+            Regex::new(r"(?s)\{%-?\s*comment\s*-?%\}(.*?)\{%-?\s*endcomment\s*-?%\}").unwrap();
+    }
+
+    COMMENT
+        .captures_iter(source)
+        .map(|captures| captures.get(1).expect("always captured").as_str())
+        .filter(|body| body.trim_start().starts_with("test:"))
+        .map(|body| {
+            serde_yaml::from_str(body)
+                .map_err(|e| Error::with_msg("Invalid test case").context("cause", e.to_string()))
+        })
+        .collect()
+}
+
+/// Read the test cases from a YAML sidecar file.
+pub fn sidecar_cases<P: AsRef<path::Path>>(file: P) -> Result<Vec<TestCase>> {
+    let file = file.as_ref();
+    let content = fs::read_to_string(file)
+        .replace("Cannot read file")
+        .context_key("path")
+        .value_with(|| file.to_string_lossy().into_owned().into())?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| Error::with_msg("Invalid test case").context("cause", e.to_string()))
+        .context_key("path")
+        .value_with(|| file.to_string_lossy().into_owned().into())
+}
+
+/// The sidecar file for a template, e.g. `page.liquid.test.yml` for `page.liquid`.
+pub fn sidecar_path<P: AsRef<path::Path>>(template: P) -> path::PathBuf {
+    let mut sidecar = template.as_ref().as_os_str().to_owned();
+    sidecar.push(".test.yml");
+    sidecar.into()
+}
+
+/// Runs template tests with a configured `Parser`.
+pub struct TestRunner<'p> {
+    parser: &'p Parser,
+}
+
+impl<'p> TestRunner<'p> {
+    pub fn new(parser: &'p Parser) -> Self {
+        Self { parser }
+    }
+
+    /// Run the test cases embedded in `source`.
+    pub fn run(&self, source: &str) -> Result<Vec<TestOutcome>> {
+        let cases = embedded_cases(source)?;
+        self.run_cases(source, &cases)
+    }
+
+    /// Run the embedded and sidecar test cases of a template file.
+    pub fn run_file<P: AsRef<path::Path>>(&self, file: P) -> Result<Vec<TestOutcome>> {
+        let file = file.as_ref();
+        let source = fs::read_to_string(file)
+            .replace("Cannot read file")
+            .context_key("path")
+            .value_with(|| file.to_string_lossy().into_owned().into())?;
+
+        let mut cases = embedded_cases(&source)?;
+        let sidecar = sidecar_path(file);
+        if sidecar.is_file() {
+            cases.extend(sidecar_cases(sidecar)?);
+        }
+        self.run_cases(&source, &cases)
+    }
+
+    /// Run `cases` against `source`.
+    ///
+    /// Parse errors fail the run while render errors fail the individual test.
+    pub fn run_cases(&self, source: &str, cases: &[TestCase]) -> Result<Vec<TestOutcome>> {
+        let template = self.parser.parse(source)?;
+        let outcomes = cases
+            .iter()
+            .map(|case| {
+                let failure = match template.render(&case.globals) {
+                    Ok(output) => check(case, &output),
+                    Err(e) => Some(format!("render failed: {}", e)),
+                };
+                TestOutcome {
+                    name: case.name.clone(),
+                    failure,
+                }
+            })
+            .collect();
+        Ok(outcomes)
+    }
+}
+
+fn check(case: &TestCase, output: &str) -> Option<String> {
+    if let Some(ref expected) = case.output {
+        if expected != output {
+            return Some(format!("expected output {:?}, got {:?}", expected, output));
+        }
+    }
+    case.expect
+        .0
+        .iter()
+        .find(|fragment| !output.contains(fragment.as_str()))
+        .map(|fragment| format!("expected {:?} in output {:?}", fragment, output))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use parser::ParserBuilder;
+
+    const GREETING: &str = r#"Hello, {{ user.name | default: "stranger" }}!
+{% comment %}
+test: greets by name
+globals:
+  user:
+    name: Ann
+expect: Hello, Ann!
+{% endcomment %}
+{%- comment %}Not a test.{% endcomment -%}
+{% comment %}
+test: greets strangers
+expect: [Hello, stranger]
+{% endcomment %}"#;
+
+    #[test]
+    fn embedded() {
+        let cases = embedded_cases(GREETING).unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].name, "greets by name");
+        assert_eq!(cases[0].expect, Fragments(vec!["Hello, Ann!".to_owned()]));
+        assert_eq!(
+            cases[1].expect,
+            Fragments(vec!["Hello".to_owned(), "stranger".to_owned()])
+        );
+    }
+
+    #[test]
+    fn embedded_invalid() {
+        assert!(embedded_cases("{% comment %}test: [unclosed{% endcomment %}").is_err());
+    }
+
+    #[test]
+    fn run_passes() {
+        let parser = ParserBuilder::with_liquid().build().unwrap();
+        let outcomes = TestRunner::new(&parser).run(GREETING).unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(TestOutcome::passed), "{:?}", outcomes);
+    }
+
+    #[test]
+    fn run_fails() {
+        let parser = ParserBuilder::with_liquid().build().unwrap();
+        let cases = vec![
+            TestCase {
+                name: "fragment".to_owned(),
+                globals: Object::new(),
+                expect: Fragments(vec!["Goodbye".to_owned()]),
+                output: None,
+            },
+            TestCase {
+                name: "exact".to_owned(),
+                globals: Object::new(),
+                expect: Fragments::default(),
+                output: Some("Hi".to_owned()),
+            },
+        ];
+        let outcomes = TestRunner::new(&parser).run_cases("Hello", &cases).unwrap();
+        assert!(outcomes.iter().all(|o| !o.passed()));
+        assert_eq!(
+            outcomes[0].to_string(),
+            "FAILED fragment: expected \"Goodbye\" in output \"Hello\""
+        );
+    }
+
+    #[test]
+    fn sidecar() {
+        assert_eq!(
+            sidecar_path("site/page.liquid"),
+            path::PathBuf::from("site/page.liquid.test.yml")
+        );
+
+        let parser = ParserBuilder::with_liquid().build().unwrap();
+        let outcomes = TestRunner::new(&parser)
+            .run_file(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/testing/greeting.liquid"
+            ))
+            .unwrap();
+        assert_eq!(outcomes.len(), 3, "{:?}", outcomes);
+        assert!(outcomes.iter().all(TestOutcome::passed), "{:?}", outcomes);
+    }
+}
@@ -0,0 +1,113 @@
+extern crate liquid;
+
+use liquid::value::{Object, Value};
+
+fn parser() -> liquid::Parser {
+    liquid::ParserBuilder::with_liquid()
+        .arithmetic(true)
+        .build()
+        .unwrap()
+}
+
+fn globals() -> Object {
+    let mut globals = Object::new();
+    globals.insert("width".into(), Value::scalar(40));
+    globals.insert("price".into(), Value::scalar(2.5));
+    globals.insert("quantity".into(), Value::scalar(3));
+    globals.insert("a".into(), Value::scalar(false));
+    globals.insert("b".into(), Value::scalar(true));
+    globals.insert("c".into(), Value::scalar(false));
+    globals
+}
+
+fn render(text: &str) -> String {
+    parser().parse(text).unwrap().render(&globals()).unwrap()
+}
+
+#[test]
+fn operators_follow_precedence() {
+    assert_eq!(render("{{ width * 2 + 10 }}"), "90");
+    assert_eq!(render("{{ 10 + width * 2 }}"), "90");
+    assert_eq!(render("{{ 10 - 4 - 3 }}"), "3");
+    assert_eq!(render("{{ width / 3 % 5 }}"), "3");
+    assert_eq!(render("{{ price * quantity }}"), "7.5");
+}
+
+#[test]
+fn parentheses_group() {
+    assert_eq!(render("{{ (width + 10) * 2 }}"), "100");
+    assert_eq!(render("{{ 2 * (width - (quantity + 7)) }}"), "60");
+    assert_eq!(render("{{ (1..3) | join: ',' }}"), "1,2,3");
+}
+
+#[test]
+fn arithmetic_is_a_value() {
+    assert_eq!(render("{{ width + 2 | divided_by: 2 }}"), "21");
+    assert_eq!(render("{{ 100 | minus: width * 2 }}"), "20");
+    assert_eq!(
+        render("{% assign total = price * quantity %}{{ total }}"),
+        "7.5"
+    );
+    assert_eq!(
+        render("{% if price * quantity > 7 %}yes{% else %}no{% endif %}"),
+        "yes"
+    );
+}
+
+#[test]
+fn conditions_group() {
+    assert_eq!(
+        render("{% if (a or b) and c %}yes{% else %}no{% endif %}"),
+        "no"
+    );
+    assert_eq!(
+        render("{% if a or (b and not_there) %}yes{% else %}no{% endif %}"),
+        "no"
+    );
+    assert_eq!(
+        render("{% unless (a or c) and b %}yes{% endunless %}"),
+        "yes"
+    );
+    assert!(parser().parse("{% if (a or b %}{% endif %}").is_err());
+    assert!(parser().parse("{% if (a or b)) %}{% endif %}").is_err());
+}
+
+#[test]
+fn unary_minus() {
+    assert_eq!(render("{{ -width }}"), "-40");
+    assert_eq!(render("{{ -price }}"), "-2.5");
+    assert_eq!(render("{{ --width }}"), "40");
+    assert_eq!(render("{{ 2 * -width + 1 }}"), "-79");
+    assert_eq!(render("{{ 10 - -width }}"), "50");
+    assert_eq!(render("{{ -(width + 2) }}"), "-42");
+    assert_eq!(render("{{ 10 | plus: -width }}"), "-30");
+    assert_eq!(render("{% assign x = -quantity %}{{ x }}"), "-3");
+    assert_eq!(
+        render("{% if quantity == 3 and -quantity < 0 %}yes{% endif %}"),
+        "yes"
+    );
+    assert_eq!(render("{% if a or -1 %}yes{% endif %}"), "yes");
+}
+
+#[test]
+fn errors_render() {
+    let template = parser().parse("{{ width / (quantity - 3) }}").unwrap();
+    assert!(template.render(&globals()).is_err());
+    let template = parser().parse("{{ width + 'wide' }}").unwrap();
+    assert!(template.render(&globals()).is_err());
+}
+
+#[test]
+fn disabled_by_default() {
+    let parser = liquid::ParserBuilder::with_liquid().build().unwrap();
+    assert!(parser.parse("{{ width + 1 }}").is_err());
+    assert!(parser.parse("{{ width | plus: 1 + 2 }}").is_err());
+    assert!(parser.parse("{% if (a or b) and c %}{% endif %}").is_err());
+    assert!(parser.parse("{{ width-}}").is_ok());
+
+    // Without arithmetic, identifiers may start with hyphens.
+    let mut globals = Object::new();
+    globals.insert("-width".into(), Value::scalar(40));
+    let template = parser.parse("{{ -width }}").unwrap();
+    assert_eq!(template.render(&globals).unwrap(), "40");
+}
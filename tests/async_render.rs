@@ -0,0 +1,82 @@
+#![cfg(feature = "async")]
+
+extern crate liquid;
+
+use std::future::{self, Future};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use liquid::error::Result;
+use liquid::interpreter::{AsyncGlobalsProvider, BoxFuture};
+use liquid::value::Value;
+
+/// Wakes nothing, `block_on` polling regardless.
+struct Noop;
+
+impl Wake for Noop {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Runs `future` on this thread, polling it until it is ready.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(Noop));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Globals named after their value, recording which were fetched.
+#[derive(Default)]
+struct Provider {
+    fetched: Mutex<Vec<String>>,
+}
+
+impl AsyncGlobalsProvider for Provider {
+    fn names(&self) -> Vec<&str> {
+        vec!["title", "name", "footer", "unused", "which"]
+    }
+
+    fn get<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Value>>> {
+        self.fetched.lock().unwrap().push(name.to_owned());
+        let value = match name {
+            "which" => Value::scalar("footer"),
+            _ => Value::scalar(name.to_uppercase()),
+        };
+        Box::pin(future::ready(Ok(Some(value))))
+    }
+}
+
+fn parser() -> liquid::Parser {
+    let mut partials = liquid::Partials::empty();
+    partials.add("greeting", "Hi {{ name }}");
+    partials.add("footer", "{{ footer }}");
+    liquid::ParserBuilder::with_liquid()
+        .partials(partials)
+        .dynamic_partials(liquid::compiler::DynamicPartials::Allow)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn globals_are_awaited_before_rendering() {
+    let template = parser()
+        .parse("{{ title }} {% include 'greeting' %} {{ other }}")
+        .unwrap();
+    let provider = Provider::default();
+    let output = block_on(template.render_async(&provider)).unwrap();
+    assert_eq!(output, "TITLE Hi NAME ");
+    assert_eq!(*provider.fetched.lock().unwrap(), vec!["title", "name"]);
+}
+
+#[test]
+fn globals_of_computed_partials_are_awaited_when_read() {
+    let template = parser().parse("{% include which %}").unwrap();
+    let provider = Provider::default();
+    let output = block_on(template.render_async(&provider)).unwrap();
+    assert_eq!(output, "FOOTER");
+    assert_eq!(*provider.fetched.lock().unwrap(), vec!["which", "footer"]);
+}
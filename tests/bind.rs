@@ -0,0 +1,51 @@
+extern crate liquid;
+
+use liquid::value::Value;
+use liquid::*;
+
+#[test]
+pub fn bind_globals() {
+    let template = ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse("{{ site.title }}: {{ page }}{% for link in site.links %} {{ link }}{% endfor %}")
+        .unwrap();
+
+    let mut site = value::Object::new();
+    site.insert("title".into(), Value::scalar("Blog"));
+    site.insert(
+        "links".into(),
+        Value::array(vec![Value::scalar("home"), Value::scalar("about")]),
+    );
+    let mut config = value::Object::new();
+    config.insert("site".into(), Value::Object(site));
+    let layout = template.bind(config);
+
+    let mut globals = value::Object::new();
+    globals.insert("page".into(), Value::scalar("Hello"));
+    let output = layout.render(&globals).unwrap();
+    assert_eq!(output, "Blog: Hello home about");
+
+    assert!(template.render(&globals).is_err());
+}
+
+#[test]
+pub fn bind_globals_precedence() {
+    let template = ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse("{{ a }} {{ b }}")
+        .unwrap();
+
+    let mut bound = value::Object::new();
+    bound.insert("a".into(), Value::scalar(1));
+    bound.insert("b".into(), Value::scalar(1));
+    let mut rebound = value::Object::new();
+    rebound.insert("b".into(), Value::scalar(2));
+    let template = template.bind(bound).bind(rebound);
+    assert_eq!(template.render(&value::Object::new()).unwrap(), "1 2");
+
+    let mut globals = value::Object::new();
+    globals.insert("a".into(), Value::scalar(3));
+    assert_eq!(template.render(&globals).unwrap(), "3 2");
+}
@@ -0,0 +1,75 @@
+extern crate liquid;
+
+use liquid::value::{Object, Value};
+
+fn parser() -> liquid::Parser {
+    liquid::ParserBuilder::with_liquid().build().unwrap()
+}
+
+fn globals() -> Object {
+    let mut globals = Object::new();
+    globals.insert("name".into(), Value::scalar("ada"));
+    globals.insert(
+        "items".into(),
+        Value::array(vec![Value::scalar(1), Value::scalar(2), Value::scalar(3)]),
+    );
+    globals
+}
+
+#[test]
+fn compiled_templates_render_the_same() {
+    let text = concat!(
+        "<h1>{{ name | capitalize }}</h1>\n",
+        "<ul>\n",
+        "  {%- for item in items %}\n",
+        "  <li>{{ item | times: 2 }}</li>\n",
+        "  {%- endfor %}\n",
+        "</ul>\n",
+        "{% assign total = items | size %}{% if total > 2 -%}\n",
+        "  many {%- else %}few{% endif %} {% raw %}{{ kept }}{% endraw %}\n",
+    );
+    let parser = parser();
+    let (template, compiled) = parser.compile(text).unwrap();
+    let loaded = parser.parse_compiled(&compiled).unwrap();
+    let expected = template.render(&globals()).unwrap();
+    assert_eq!(loaded.render(&globals()).unwrap(), expected);
+    assert_eq!(loaded.variables(), template.variables());
+}
+
+#[test]
+fn errors_point_into_the_template() {
+    let text = "Hi\n{% if name %}\n  {{ name | divided_by: 0 }}{% endif %}";
+    let parser = parser();
+    let (_, compiled) = parser.compile(text).unwrap();
+    let loaded = parser.parse_compiled(&compiled).unwrap();
+    let error = loaded.render(&globals()).unwrap_err();
+    assert_eq!(error.line(), Some(3));
+}
+
+#[test]
+fn templates_of_other_versions_are_rejected() {
+    let parser = parser();
+    let (_, compiled) = parser.compile("{{ name }}").unwrap();
+    let old = String::from_utf8_lossy(&compiled).replace(env!("CARGO_PKG_VERSION"), "0.0.0");
+    let error = parser.parse_compiled(old.as_bytes()).err().unwrap();
+    assert!(error
+        .to_string()
+        .contains("Template compiled by another version of liquid"));
+
+    assert!(parser.parse_compiled(b"{{ name }}").is_err());
+}
+
+#[test]
+fn loading_checks_loop_shadowing() {
+    let text = "{% for item in items %}{% for item in item.children %}{% endfor %}{% endfor %}";
+    let (_, compiled) = parser().compile(text).unwrap();
+
+    let strict = liquid::ParserBuilder::with_liquid()
+        .loop_shadowing(liquid::compiler::LoopShadowing::Error)
+        .build()
+        .unwrap();
+    let error = strict.parse_compiled(&compiled).err().unwrap();
+    assert!(error
+        .to_string()
+        .contains("Loop variable shadows another variable"));
+}
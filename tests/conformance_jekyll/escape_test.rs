@@ -0,0 +1,35 @@
+#[test]
+fn test_xml_escape() {
+    assert_eq!(v!("AT&amp;T"), jekyll_filters!(XmlEscape, v!("AT&T")));
+    assert_eq!(
+        v!("&lt;code&gt;command &amp;lt;filename&amp;gt;&lt;/code&gt;"),
+        jekyll_filters!(XmlEscape, v!("<code>command &lt;filename&gt;</code>"))
+    );
+    assert_eq!(v!(""), jekyll_filters!(XmlEscape, v!(nil)));
+}
+
+#[test]
+fn test_cgi_escape() {
+    assert_eq!(v!("my+things"), jekyll_filters!(CgiEscape, v!("my things")));
+    assert_eq!(v!("hey%21"), jekyll_filters!(CgiEscape, v!("hey!")));
+    assert_eq!(
+        v!("foo%2C+bar%3B+baz%3F"),
+        jekyll_filters!(CgiEscape, v!("foo, bar; baz?"))
+    );
+}
+
+#[test]
+fn test_uri_escape() {
+    assert_eq!(
+        v!("my%20things"),
+        jekyll_filters!(UriEscape, v!("my things"))
+    );
+    assert_eq!(
+        v!("foo!*'();:@&=+$,/?#[]bar"),
+        jekyll_filters!(UriEscape, v!("foo!*'();:@&=+$,/?#[]bar"))
+    );
+    assert_eq!(
+        v!("foo,%20bar%20%5Cbaz?"),
+        jekyll_filters!(UriEscape, v!("foo, bar \\baz?"))
+    );
+}
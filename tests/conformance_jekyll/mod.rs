@@ -1 +1,2 @@
+mod escape_test;
 mod slugify_test;
@@ -406,7 +406,6 @@ fn test_uniq() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#267
 fn test_uniq_empty_array() {
     assert_eq!(v!([]), filters!(Uniq, v!([]), v!("a")));
 }
@@ -761,7 +760,6 @@ fn test_times() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#251
 fn test_divided_by() {
     assert_template_result!("4", r#"{{ 12 | divided_by:3 }}"#);
     assert_template_result!("4", r#"{{ 14 | divided_by:3 }}"#);
@@ -776,7 +774,6 @@ fn test_divided_by() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#251
 fn test_modulo() {
     assert_template_result!("1", r#"{{ 3 | modulo:2 }}"#);
     assert_render_error!("{{ 1 | modulo:0 }}");
@@ -785,7 +782,6 @@ fn test_modulo() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#251
 fn test_round() {
     assert_template_result!("5", r#"{{ input | round }}"#, v!({"input": 4.6}));
     assert_template_result!("4", r#"{{ "4.3" | round }}"#);
@@ -796,7 +792,6 @@ fn test_round() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#251
 fn test_ceil() {
     assert_template_result!("5", r#"{{ input | ceil }}"#, v!({"input": 4.6}));
     assert_template_result!("5", r#"{{ "4.3" | ceil }}"#);
@@ -806,7 +801,6 @@ fn test_ceil() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#251
 fn test_floor() {
     assert_template_result!("4", r#"{{ input | floor }}"#, v!({"input": 4.6}));
     assert_template_result!("4", r#"{{ "4.3" | floor }}"#);
@@ -868,6 +862,28 @@ fn test_default() {
     assert_eq!(v!("bar"), filters!(Default, v!({}), v!("bar")));
 }
 
+#[test]
+fn test_default_handle_false() {
+    let assigns = v!({ "a": "foo", "b": nil, "c": "", "d": false, "e": [], "f": {} });
+    assert_template_result!(
+        "foo bar bar false bar bar",
+        concat!(
+            r#"{{ a | default: "bar", allow_false: true }} "#,
+            r#"{{ b | default: "bar", allow_false: true }} "#,
+            r#"{{ c | default: "bar", allow_false: true }} "#,
+            r#"{{ d | default: "bar", allow_false: true }} "#,
+            r#"{{ e | default: "bar", allow_false: true }} "#,
+            r#"{{ f | default: "bar", allow_false: true }}"#,
+        ),
+        assigns.clone()
+    );
+    assert_template_result!(
+        "bar",
+        r#"{{ d | default: "bar", allow_false: false }}"#,
+        assigns
+    );
+}
+
 #[test]
 #[should_panic]
 fn test_cannot_access_private_methods() {
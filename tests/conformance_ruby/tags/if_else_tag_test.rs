@@ -117,6 +117,18 @@ fn test_comparison_of_expressions_starting_with_and_or_or() {
     );
 }
 
+#[test]
+fn test_negative_numbers_after_and_or_or_and_contains() {
+    let assigns = v!({ "a": true, "list": [-1, 2] });
+    assert_template_result!(" YES ", "{% if a and -1 < 0 %} YES {% endif %}", assigns);
+    assert_template_result!(" YES ", "{% if false or -1 %} YES {% endif %}", assigns);
+    assert_template_result!(
+        " YES ",
+        "{% if list contains -1 %} YES {% endif %}",
+        assigns
+    );
+}
+
 #[test]
 fn test_if_and() {
     assert_template_result!(" YES ", "{% if true and true %} YES {% endif %}");
@@ -49,7 +49,6 @@ fn test_include_tag_looks_for_file_system_in_registers_first() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#237
 fn test_include_tag_with() {
     assert_template_result!(
         "Product: Draft 151cm ",
@@ -70,7 +69,6 @@ fn test_include_tag_with_default_name() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#237
 fn test_include_tag_for() {
     assert_template_result!(
         "Product: Draft 151cm Product: Element 155cm ",
@@ -81,7 +79,6 @@ fn test_include_tag_for() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#237
 fn test_include_tag_with_local_variables() {
     assert_template_result!(
         "Locale: test123 ",
@@ -92,7 +89,6 @@ fn test_include_tag_with_local_variables() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#237
 fn test_include_tag_with_multiple_local_variables() {
     assert_template_result!(
         "Locale: test123 test321",
@@ -103,7 +99,6 @@ fn test_include_tag_with_multiple_local_variables() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#237
 fn test_include_tag_with_multiple_local_variables_from_context() {
     assert_template_result!(
         "Locale: test123 test321",
@@ -136,7 +131,6 @@ fn test_nested_include_tag() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#237
 fn test_nested_include_with_variable() {
     assert_template_result!(
         "Product: Draft 151cm details ",
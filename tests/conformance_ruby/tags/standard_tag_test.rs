@@ -157,7 +157,6 @@ fn test_case_with_else() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#136
 fn test_case_on_size() {
     assert_template_result!(
         "",
@@ -192,7 +191,6 @@ fn test_case_on_size() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#136
 fn test_case_on_size_with_else() {
     assert_template_result!(
         "else",
@@ -408,7 +406,6 @@ fn test_multiple_named_cycles_with_names_from_context() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#136
 fn test_size_of_array() {
     let assigns = v!({ "array": [1, 2, 3, 4] });
     assert_template_result!(
@@ -419,7 +416,6 @@ fn test_size_of_array() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#136
 fn test_size_of_hash() {
     let assigns = v!({ "hash": { "a": 1, "b": 2, "c": 3, "d": 4 } });
     assert_template_result!(
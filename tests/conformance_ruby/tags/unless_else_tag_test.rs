@@ -43,3 +43,14 @@ fn test_unless_in_loop() {
 fn test_unless_else_in_loop() {
     assert_template_result!(" TRUE  2  3 ", "{% for i in choices %}{% unless i %} {{ forloop.index }} {% else %} TRUE {% endunless %}{% endfor %}", v!({"choices": [1, nil, false]}));
 }
+
+#[test]
+fn test_unless_negative_number_after_and() {
+    let assigns = v!({ "n": 1 });
+    assert_template_result!("", "{% unless n and -1 %} YES {% endunless %}", assigns);
+    assert_template_result!(
+        " YES ",
+        "{% unless n and -1 < 0 %}{% else %} YES {% endunless %}",
+        assigns
+    );
+}
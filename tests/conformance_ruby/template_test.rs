@@ -182,7 +182,6 @@ fn test_undefined_filters_raise() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#224
 fn test_using_range_literal_works_as_expected() {
     let template = liquid::ParserBuilder::with_liquid()
         .build()
@@ -10,6 +10,7 @@ fn build_parser() -> Parser {
         .filter(derive_macros_test_filters::TestPositionalFilterParser)
         .filter(derive_macros_test_filters::TestKeywordFilterParser)
         .filter(derive_macros_test_filters::TestMixedFilterParser)
+        .filter(derive_macros_test_filters::TestNamedFilterParser)
         .filter(derive_macros_test_filters::TestParameterlessFilterParser)
         .build()
         .unwrap()
@@ -212,6 +213,84 @@ pub fn test_derive_mixed_filter_reflection() {
     assert_eq!(kw_args[2].is_optional, false);
 }
 
+#[test]
+pub fn test_derive_named_filter_ok() {
+    let parser = build_parser();
+
+    let template = parser
+        .parse(concat!(
+            "{{ 0 | thumbnail: 100, 50 }}\n",
+            "{{ 0 | thumbnail: width: 100, height: 50 }}\n",
+            "{{ 0 | thumbnail: height: 50, crop: true, width: 100 }}\n",
+            "{{ 0 | thumbnail: 100, height: 50 }}\n",
+            "{{ 0 | thumbnail: width: 100 }}"
+        ))
+        .unwrap();
+    let expected = concat!(
+        "<100x50; crop: false>\n",
+        "<100x50; crop: false>\n",
+        "<100x50; crop: true>\n",
+        "<100x50; crop: false>\n",
+        "<100xNone; crop: false>"
+    );
+
+    let globals = liquid::value::Object::new();
+    let rendered = template.render(&globals).unwrap();
+
+    assert_eq!(rendered, expected);
+}
+
+#[test]
+pub fn test_derive_named_filter_err() {
+    let parser = build_parser();
+
+    assert!(parser.parse("{{ 0 | thumbnail }}\n").is_err());
+    assert!(parser.parse("{{ 0 | thumbnail: height: 50 }}\n").is_err());
+    assert!(parser
+        .parse("{{ 0 | thumbnail: 100, width: 100 }}\n")
+        .is_err());
+    assert!(parser
+        .parse("{{ 0 | thumbnail: 100, 50, true }}\n")
+        .is_err());
+}
+
+#[test]
+pub fn test_derive_named_filter_display() {
+    let filter = derive_macros_test_filters::TestNamedFilterParser;
+    let parse = |args: Vec<(&'static str, i32)>| {
+        let positional: Vec<_> = args
+            .iter()
+            .filter(|(name, _)| name.is_empty())
+            .map(|(_, value)| {
+                liquid::interpreter::Expression::Literal(liquid::value::Value::scalar(*value))
+            })
+            .collect();
+        let keyword: Vec<_> = args
+            .iter()
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, value)| {
+                (
+                    *name,
+                    liquid::interpreter::Expression::Literal(liquid::value::Value::scalar(*value)),
+                )
+            })
+            .collect();
+        let args = liquid::compiler::FilterArguments {
+            positional: Box::new(positional.into_iter()),
+            keyword: Box::new(keyword.into_iter()),
+        };
+        liquid::compiler::ParseFilter::parse(&filter, args)
+            .unwrap()
+            .to_string()
+    };
+
+    assert_eq!(
+        parse(vec![("", 100), ("height", 50)]),
+        "thumbnail : 100, 50"
+    );
+    assert_eq!(parse(vec![("width", 100)]), "thumbnail : 100");
+}
+
 #[test]
 pub fn test_derive_parameterless_filter_ok() {
     let parser = build_parser();
@@ -280,3 +359,67 @@ pub fn test_derive_stateful_filter() {
         .unwrap();
     assert_eq!(rendered, ":-( hello :-(");
 }
+
+#[test]
+pub fn test_derive_configured_filter() {
+    let globals = liquid::value::Object::new();
+
+    let parser = ParserBuilder::new()
+        .filter(derive_macros_test_filters::TestConfiguredFilterParser::new(
+            "https://cdn.example.com",
+        ))
+        .filter(
+            derive_macros_test_filters::TestConfiguredParameterlessFilterParser {
+                greeting: "Hello".to_owned(),
+            },
+        )
+        .build()
+        .unwrap();
+    let rendered = parser
+        .parse("{{ \"a.css\" | cdn }} {{ \"b.js\" | cdn: version: 2 }} {{ \"you\" | greet }}")
+        .unwrap()
+        .render(&globals)
+        .unwrap();
+    assert_eq!(
+        rendered,
+        "https://cdn.example.com/a.css https://cdn.example.com/b.js?v=2 Hello, you"
+    );
+
+    assert!(parser.parse("{{ \"a.css\" | cdn: 2 }}").is_err());
+    assert!(parser.parse("{{ \"you\" | greet: 2 }}").is_err());
+
+    let filter = derive_macros_test_filters::TestConfiguredFilterParser::new("");
+    assert_eq!(filter.name(), "cdn");
+    assert_eq!(filter.keyword_parameters()[0].name, "version");
+}
+
+#[test]
+pub fn test_derive_deprecated_filter() {
+    let filter = derive_macros_test_filters::TestDeprecatedFilterParser;
+    let deprecation = filter.deprecated().unwrap();
+    assert_eq!(deprecation.message, "Kept for old themes.");
+    assert_eq!(deprecation.replacement, Some("no_args"));
+
+    let filter = derive_macros_test_filters::TestParameterlessFilterParser;
+    assert_eq!(filter.deprecated(), None);
+
+    let warnings = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let parser = ParserBuilder::new()
+        .filter(derive_macros_test_filters::TestDeprecatedFilterParser)
+        .filter(derive_macros_test_filters::TestParameterlessFilterParser)
+        .on_warning(move |warning| sink.lock().unwrap().push(warning))
+        .build()
+        .unwrap();
+    let rendered = parser
+        .parse("{{ 0 | no_args }}{{ 0 | old_args }}")
+        .unwrap()
+        .render(&liquid::value::Object::new())
+        .unwrap();
+    assert_eq!(rendered, "<><old>");
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].get("filter"), Some("old_args"));
+    assert_eq!(warnings[0].get("replacement"), Some("no_args"));
+}
@@ -0,0 +1,92 @@
+extern crate liquid;
+use std::sync::Arc;
+
+use liquid::compiler::{Filter, FilterParameters};
+use liquid::derive::*;
+use liquid::error::Result;
+use liquid::interpreter::Context;
+use liquid::interpreter::Expression;
+use liquid::value::Value;
+
+#[derive(Debug, FilterParameters)]
+struct TestConfiguredFilterParameters {
+    #[parameter(description = "", arg_type = "integer", mode = "keyword")]
+    version: Option<Expression>,
+}
+
+#[derive(Clone, Debug, ParseFilter, FilterReflection)]
+#[filter(
+    name = "cdn",
+    description = "Test configured filters.",
+    parameters(TestConfiguredFilterParameters),
+    parsed(TestConfiguredFilter),
+    stateful
+)]
+pub struct TestConfiguredFilterParser {
+    base_url: Arc<str>,
+}
+
+impl TestConfiguredFilterParser {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+}
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "cdn"]
+pub struct TestConfiguredFilter {
+    #[parameters]
+    args: TestConfiguredFilterParameters,
+    #[state]
+    config: TestConfiguredFilterParser,
+}
+
+impl Filter for TestConfiguredFilter {
+    fn evaluate(&self, input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let mut result = format!("{}/{}", self.config.base_url, input.to_str());
+        if let Some(version) = args.version {
+            result.push_str(&format!("?v={}", version));
+        }
+
+        Ok(Value::scalar(result))
+    }
+}
+
+#[derive(Clone, Debug, ParseFilter, FilterReflection)]
+#[filter(
+    name = "greet",
+    description = "Test configured filters without parameters.",
+    parsed(TestConfiguredParameterlessFilter),
+    stateful
+)]
+pub struct TestConfiguredParameterlessFilterParser {
+    pub greeting: String,
+}
+
+#[derive(Debug, Display_filter)]
+#[name = "greet"]
+pub struct TestConfiguredParameterlessFilter {
+    greeting: String,
+}
+
+impl From<TestConfiguredParameterlessFilterParser> for TestConfiguredParameterlessFilter {
+    fn from(parser: TestConfiguredParameterlessFilterParser) -> Self {
+        Self {
+            greeting: parser.greeting,
+        }
+    }
+}
+
+impl Filter for TestConfiguredParameterlessFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(format!(
+            "{}, {}",
+            self.greeting,
+            input.to_str()
+        )))
+    }
+}
@@ -0,0 +1,28 @@
+extern crate liquid;
+use liquid::compiler::Filter;
+use liquid::derive::*;
+use liquid::error::Result;
+use liquid::interpreter::Context;
+use liquid::value::Value;
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "old_args",
+    description = "Deprecated filter with no arguments.",
+    deprecated = "Kept for old themes.",
+    replacement = "no_args",
+    parsed(TestDeprecatedFilter)
+)]
+pub struct TestDeprecatedFilterParser;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "old_args"]
+pub struct TestDeprecatedFilter;
+
+impl Filter for TestDeprecatedFilter {
+    fn evaluate(&self, _input: &Value, _context: &Context) -> Result<Value> {
+        let result = "<old>";
+
+        Ok(Value::scalar(result))
+    }
+}
@@ -1,11 +1,17 @@
+mod configured;
+mod deprecated;
 mod keyword;
 mod mixed;
+mod named;
 mod parameterless;
 mod positional;
 mod stateful;
 
+pub use self::configured::{TestConfiguredFilterParser, TestConfiguredParameterlessFilterParser};
+pub use self::deprecated::TestDeprecatedFilterParser;
 pub use self::keyword::TestKeywordFilterParser;
 pub use self::mixed::TestMixedFilterParser;
+pub use self::named::TestNamedFilterParser;
 pub use self::parameterless::TestParameterlessFilterParser;
 pub use self::positional::TestPositionalFilterParser;
 pub use self::stateful::TestStatefulFilterParser;
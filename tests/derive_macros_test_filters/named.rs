@@ -0,0 +1,58 @@
+extern crate liquid;
+use liquid::compiler::{Filter, FilterParameters};
+use liquid::derive::*;
+use liquid::error::Result;
+use liquid::interpreter::Context;
+use liquid::interpreter::Expression;
+use liquid::value::Value;
+
+#[derive(Debug, FilterParameters)]
+struct TestNamedFilterParameters {
+    #[parameter(
+        description = "The width.",
+        arg_type = "integer",
+        mode = "positional_or_keyword"
+    )]
+    width: Expression,
+
+    #[parameter(
+        description = "The height.",
+        arg_type = "integer",
+        mode = "positional_or_keyword"
+    )]
+    height: Option<Expression>,
+
+    #[parameter(description = "Whether to crop.", arg_type = "bool", mode = "keyword")]
+    crop: Option<Expression>,
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "thumbnail",
+    description = "Filter with parameters given positionally or by name.",
+    parameters(TestNamedFilterParameters),
+    parsed(TestNamedFilter)
+)]
+pub struct TestNamedFilterParser;
+
+#[derive(Debug, FromFilterParameters, Display_filter)]
+#[name = "thumbnail"]
+pub struct TestNamedFilter {
+    #[parameters]
+    args: TestNamedFilterParameters,
+}
+
+impl Filter for TestNamedFilter {
+    fn evaluate(&self, _input: &Value, context: &Context) -> Result<Value> {
+        let args = self.args.evaluate(context)?;
+
+        let height = args
+            .height
+            .map(|i| i.to_string())
+            .unwrap_or_else(|| "None".to_owned());
+        let crop = args.crop.unwrap_or(false);
+        let result = format!("<{}x{}; crop: {}>", args.width, height, crop);
+
+        Ok(Value::scalar(result))
+    }
+}
@@ -0,0 +1,174 @@
+extern crate liquid;
+
+use liquid::compiler::{CompletionKind, Document, NodeKind, Severity};
+
+fn document(text: &str) -> Document {
+    liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .document(text)
+}
+
+fn kinds(document: &Document) -> Vec<NodeKind> {
+    document.nodes().iter().map(|n| n.kind.clone()).collect()
+}
+
+#[test]
+fn document_outline() {
+    let text = "Hi {{ name }}{% if a %}{% for i in b %}{{ i }}{% endfor %}{% else %}x{% endif %}!";
+    let document = document(text);
+    assert_eq!(
+        kinds(&document),
+        vec![
+            NodeKind::Text,
+            NodeKind::Output,
+            NodeKind::Tag("if".to_owned()),
+            NodeKind::Text,
+        ]
+    );
+    let block = &document.nodes()[2];
+    assert_eq!(&text[block.span.clone()], &text[13..text.len() - 1]);
+    assert_eq!(block.children.len(), 3);
+
+    let offset = text.find("{{ i }}").unwrap() + 3;
+    let path: Vec<_> = document
+        .nodes_at(offset)
+        .into_iter()
+        .map(|n| n.kind.clone())
+        .collect();
+    assert_eq!(
+        path,
+        vec![
+            NodeKind::Tag("if".to_owned()),
+            NodeKind::Tag("for".to_owned()),
+            NodeKind::Output,
+        ]
+    );
+    assert_eq!(document.node_at(text.len()), None);
+}
+
+#[test]
+fn document_outline_of_raw() {
+    let document =
+        document("{% raw %}{% if %}{{ a {% endraw %}{% comment %}{% if %}{% endcomment %}");
+    let raw = &document.nodes()[0];
+    assert_eq!(raw.kind, NodeKind::Tag("raw".to_owned()));
+    assert_eq!(raw.children.len(), 1);
+    assert_eq!(raw.children[0].kind, NodeKind::Text);
+    assert_eq!(document.nodes().len(), 2);
+    assert!(document.diagnostics().is_empty());
+}
+
+#[test]
+fn document_diagnostics() {
+    let text = "{{ a }}\n{% if a %}\n  {{ a | nope }}\n{% endif %}{% nope %}";
+    let document = document(text);
+    let diagnostics = document.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, "Unknown filter");
+    assert_eq!(&text[diagnostics[0].span.clone()], "nope }}");
+    assert_eq!(document.position(diagnostics[0].span.start), (3, 10));
+    assert_eq!(&text[diagnostics[1].span.clone()], "nope %}");
+}
+
+#[test]
+fn document_edit() {
+    let mut document = document("{{ a }} and {% if b %}{{ b }}{% endif %} and {{ c | nope }}");
+    assert_eq!(document.diagnostics().len(), 1);
+
+    let b = document.source().find("b }}").unwrap();
+    document.edit(b..b + 1, "b | nope");
+    assert_eq!(document.diagnostics().len(), 2);
+    assert_eq!(
+        document.source(),
+        "{{ a }} and {% if b %}{{ b | nope }}{% endif %} and {{ c | nope }}"
+    );
+    let spans: Vec<_> = document
+        .diagnostics()
+        .iter()
+        .map(|d| &document.source()[d.span.clone()])
+        .collect();
+    assert_eq!(spans, vec!["nope }}", "nope }}"]);
+    assert_eq!(
+        document.diagnostics()[1].span.start,
+        document.source().rfind("nope").unwrap()
+    );
+
+    // Removing the end of the block reparses the whole document.
+    let endif = document.source().find("{% endif %}").unwrap();
+    document.edit(endif..endif + "{% endif %}".len(), "");
+    assert_eq!(document.nodes().len(), 3);
+    assert_eq!(
+        document.nodes()[2].span.end,
+        document.source().len(),
+        "An unclosed block runs to the end"
+    );
+
+    let fresh = self::document(document.source());
+    assert_eq!(document.nodes(), fresh.nodes());
+    assert_eq!(document.diagnostics(), fresh.diagnostics());
+}
+
+#[test]
+fn document_edit_matches_parse() {
+    let text =
+        "a {{ x }} b {%- if y -%} c {{ y }} {%- endif %} d {% for i in z %}{{ i }}{% endfor %}";
+    let edits: &[(&str, &str)] = &[
+        ("c", "{{"),
+        ("{{ y }}", "{% endif %}"),
+        (" d ", " "),
+        ("{{ i }}", "{% if i %}"),
+        ("{{ x }}", "{{ x"),
+        ("b", "{% raw %}"),
+    ];
+    for &(from, to) in edits {
+        let mut document = document(text);
+        let at = text.find(from).unwrap();
+        document.edit(at..at + from.len(), to);
+        let fresh = self::document(document.source());
+        assert_eq!(document.nodes(), fresh.nodes(), "{:?} to {:?}", from, to);
+        assert_eq!(
+            document.diagnostics(),
+            fresh.diagnostics(),
+            "{:?} to {:?}",
+            from,
+            to
+        );
+    }
+}
+
+#[test]
+fn document_completions() {
+    let document = document("{% if a %}{% en");
+    let completions = document.completions(document.source().len());
+    assert_eq!(completions[0].label, "endif");
+    assert_eq!(completions[0].kind, CompletionKind::EndTag);
+    assert_eq!(completions[0].span, 13..15);
+
+    let document = self::document("{{ a | up");
+    let labels: Vec<_> = document
+        .completions(document.source().len())
+        .into_iter()
+        .map(|c| (c.label, c.kind))
+        .collect();
+    assert_eq!(labels, vec![("upcase".to_owned(), CompletionKind::Filter)]);
+
+    let document = self::document("{% as");
+    let completions = document.completions(document.source().len());
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].label, "assign");
+    assert_eq!(completions[0].kind, CompletionKind::Tag);
+
+    let document = self::document("{{ a }} a");
+    assert!(document.completions(document.source().len()).is_empty());
+}
+
+#[test]
+fn document_offsets() {
+    let document = document("é\n{{ a }}");
+    assert_eq!(document.position(3), (2, 1));
+    assert_eq!(document.offset(2, 1), Some(3));
+    assert_eq!(document.offset(1, 2), Some(2));
+    assert_eq!(document.offset(3, 1), None);
+}
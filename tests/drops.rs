@@ -0,0 +1,68 @@
+extern crate liquid;
+
+use std::cell::Cell;
+
+use liquid::error::Result;
+use liquid::value::{Object, Value};
+use liquid::{ObjectSource, SourceStore};
+
+#[derive(Debug, Default)]
+struct ProductDrop {
+    queries: Cell<usize>,
+}
+
+impl ObjectSource for ProductDrop {
+    fn keys(&self) -> Vec<&str> {
+        vec!["title", "related_items", "reviews"]
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        match key {
+            "title" => Ok(Some(Value::scalar("Hat"))),
+            "related_items" => {
+                self.queries.set(self.queries.get() + 1);
+                Ok(Some(Value::array(vec![
+                    Value::scalar("Cap"),
+                    Value::scalar("Beanie"),
+                ])))
+            }
+            "reviews" => liquid::Error::with_msg("Reviews are unavailable").into_err(),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn render(text: &str, product: &ProductDrop) -> Result<String> {
+    let mut values = Object::new();
+    values.insert("currency".into(), Value::scalar("EUR"));
+    let globals = SourceStore::new(values).with_source("product", product);
+    liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+        .render(&globals)
+}
+
+#[test]
+fn properties_are_lazy() {
+    let product = ProductDrop::default();
+    assert_eq!(
+        render("{{ product.title }} in {{ currency }}", &product).unwrap(),
+        "Hat in EUR"
+    );
+    assert_eq!(product.queries.get(), 0);
+
+    let text = "{% for item in product.related_items %}{{ item }} {% endfor %}";
+    assert_eq!(render(text, &product).unwrap(), "Cap Beanie ");
+    assert_eq!(product.queries.get(), 1);
+}
+
+#[test]
+fn property_errors_fail_the_render() {
+    let product = ProductDrop::default();
+    let error = render("{{ product.reviews | size }}", &product).unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("Reviews are unavailable"));
+    assert!(message.contains("property=product.reviews"));
+}
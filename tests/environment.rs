@@ -0,0 +1,376 @@
+extern crate chrono;
+extern crate liquid;
+
+use liquid::*;
+
+fn environment() -> interpreter::Environment {
+    let now = chrono::DateTime::parse_from_str("2019-03-01 22:30:00 +0000", "%Y-%m-%d %H:%M:%S %z")
+        .unwrap();
+    interpreter::Environment::new()
+        .with_locale("nl-NL")
+        .with_timezone(chrono::FixedOffset::east_opt(2 * 3600).unwrap())
+        .with_now(now)
+}
+
+#[test]
+pub fn environment_variables() {
+    let template = ParserBuilder::with_liquid()
+        .environment(environment())
+        .build()
+        .unwrap()
+        .parse(
+            "{{ liquid.locale }} {{ liquid.timezone }} {{ liquid.now | date: \"%Y-%m-%d %H:%M\" }}",
+        )
+        .unwrap();
+
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "nl-NL +02:00 2019-03-02 00:30");
+}
+
+#[test]
+pub fn environment_branching() {
+    let template = ParserBuilder::with_liquid()
+        .environment(environment())
+        .build()
+        .unwrap()
+        .parse("{% if liquid.locale == \"nl-NL\" %}Hallo{% else %}Hello{% endif %}")
+        .unwrap();
+
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "Hallo");
+}
+
+#[test]
+pub fn environment_shadowed_by_globals() {
+    let template = ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse("{{ liquid }}")
+        .unwrap();
+
+    let mut globals = value::Object::default();
+    globals.insert("liquid".into(), value::Value::scalar("user"));
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "user");
+}
+
+#[test]
+pub fn environment_default_locale() {
+    let template = ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse("{{ liquid.locale }} {{ liquid.timezone }}")
+        .unwrap();
+
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "en-US +00:00");
+}
+
+#[test]
+pub fn environment_float_format() {
+    let format = interpreter::FloatFormat::new()
+        .with_significant_digits(15)
+        .with_decimal_separator(',');
+    let template = ParserBuilder::with_liquid()
+        .environment(interpreter::Environment::new().with_float_format(format))
+        .build()
+        .unwrap()
+        .parse("{{ 0.1 | plus: 0.2 }} {{ 3 | times: 2 }} {{ \"1.5\" }}")
+        .unwrap();
+
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "0,3 6 1.5");
+}
+
+#[test]
+pub fn environment_overflow_policy() {
+    let text = "{{ 2147483647 | plus: 1 }} {{ -2147483647 | minus: 2 }} {{ 65536 | times: 65536 }}";
+    let render = |policy| {
+        ParserBuilder::with_liquid()
+            .environment(interpreter::Environment::new().with_overflow_policy(policy))
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap()
+            .render(&value::Object::default())
+    };
+
+    assert!(render(interpreter::OverflowPolicy::Error).is_err());
+    assert_eq!(
+        render(interpreter::OverflowPolicy::Saturate).unwrap(),
+        "2147483647 -2147483648 2147483647"
+    );
+    assert_eq!(
+        render(interpreter::OverflowPolicy::Wrap).unwrap(),
+        "-2147483648 2147483647 0"
+    );
+    assert_eq!(
+        render(interpreter::OverflowPolicy::Promote).unwrap(),
+        "2147483648 -2147483649 4294967296"
+    );
+}
+
+#[test]
+pub fn environment_numeric_policy() {
+    let text = "{{ 2147483647 | plus: 1 }} {{ 7 | divided_by: 2 }} {{ values | sum }}";
+    let policy = value::NumericPolicy::new()
+        .with_overflow(interpreter::OverflowPolicy::Saturate)
+        .with_integer_division(interpreter::IntegerDivision::Float);
+    let template = ParserBuilder::with_liquid()
+        .environment(interpreter::Environment::new().with_numeric_policy(policy))
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+
+    let mut globals = value::Object::new();
+    globals.insert(
+        "values".into(),
+        value::Value::array(vec![
+            value::Value::scalar(2147483647),
+            value::Value::scalar(1),
+        ]),
+    );
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "2147483647 3.5 2147483647");
+}
+
+#[test]
+pub fn environment_integer_division() {
+    let text = "{{ 7 | divided_by: 2 }} {{ 6 | divided_by: 3 }} {{ 7.0 | divided_by: 2 }}";
+    let render = |division| {
+        ParserBuilder::with_liquid()
+            .environment(interpreter::Environment::new().with_integer_division(division))
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap()
+            .render(&value::Object::default())
+            .unwrap()
+    };
+
+    assert_eq!(render(interpreter::IntegerDivision::Truncate), "3 2 3.5");
+    assert_eq!(render(interpreter::IntegerDivision::Float), "3.5 2 3.5");
+}
+
+#[test]
+pub fn environment_coercion() {
+    let render = |coercion, text: &str| {
+        ParserBuilder::with_liquid()
+            .environment(interpreter::Environment::new().with_coercion(coercion))
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap()
+            .render(&value::Object::default())
+    };
+
+    let text = "{{ '5' | plus: 1 }} {{ 5 | plus: '1.5' }} {{ '2.5' | round }}";
+    assert_eq!(render(interpreter::Coercion::Lax, text).unwrap(), "6 6.5 3");
+    assert!(render(interpreter::Coercion::Strict, "{{ '5' | plus: 1 }}").is_err());
+    assert!(render(interpreter::Coercion::Strict, "{{ 5 | plus: '1' }}").is_err());
+    assert!(render(interpreter::Coercion::Strict, "{{ '2.5' | round }}").is_err());
+    assert_eq!(
+        render(interpreter::Coercion::Strict, "{{ 5 | plus: 1.5 }}").unwrap(),
+        "6.5"
+    );
+}
+
+#[test]
+pub fn environment_non_finite_policy() {
+    let mut globals = value::Object::default();
+    globals.insert("big".into(), value::Value::scalar(1e308));
+    let render = |policy| {
+        ParserBuilder::with_liquid()
+            .environment(interpreter::Environment::new().with_non_finite_policy(policy))
+            .build()
+            .unwrap()
+            .parse("[{{ big | times: 10 }}]")
+            .unwrap()
+            .render(&globals)
+    };
+
+    assert_eq!(
+        render(interpreter::NonFinitePolicy::Literal).unwrap(),
+        "[inf]"
+    );
+    assert_eq!(render(interpreter::NonFinitePolicy::Empty).unwrap(), "[]");
+    assert!(render(interpreter::NonFinitePolicy::Error).is_err());
+}
+
+#[test]
+pub fn environment_filter_budget() {
+    use std::time::Duration;
+
+    let budget = interpreter::FilterBudget::new()
+        .with_iterations(100)
+        .with_filter(
+            "upcase",
+            interpreter::FilterBudget::new().with_time(Duration::from_nanos(0)),
+        );
+    let parser = ParserBuilder::with_liquid()
+        .environment(interpreter::Environment::new().with_filter_budget(budget))
+        .build()
+        .unwrap();
+
+    let mut globals = value::Object::default();
+    let items = (0..200).map(value::Value::scalar).collect();
+    globals.insert("items".into(), value::Value::Array(items));
+
+    let template = parser
+        .parse("{{ items | slice: 0, 2 | sort | join }}")
+        .unwrap();
+    assert_eq!(template.render(&globals).unwrap(), "0 1");
+
+    let template = parser
+        .parse("{% for i in (1..2) %}\n{% endfor %}\n  {{ items | sort | first }}")
+        .unwrap();
+    let error = template.render(&globals).unwrap_err().to_string();
+    assert!(error.contains("Filter exceeded its budget"), "{}", error);
+    assert!(error.contains("filter=sort"), "{}", error);
+    assert!(error.contains("budget=100 iterations"), "{}", error);
+    assert!(error.contains("line=3"), "{}", error);
+    assert!(error.contains("column=14"), "{}", error);
+
+    let template = parser.parse("{{ 'a' | upcase }}").unwrap();
+    let error = template.render(&globals).unwrap_err().to_string();
+    assert!(error.contains("filter=upcase"), "{}", error);
+}
+
+#[test]
+pub fn environment_redaction() {
+    use std::sync::{Arc, Mutex};
+
+    let redacted = Arc::new(Mutex::new(Vec::new()));
+    let log = redacted.clone();
+    let redaction = interpreter::Redaction::new()
+        .path("user.password")
+        .path("cards.*.number")
+        .with_handler(move |path| log.lock().unwrap().push(path.to_owned()));
+    let template = ParserBuilder::with_liquid()
+        .environment(interpreter::Environment::new().with_redaction(redaction))
+        .build()
+        .unwrap()
+        .parse(concat!(
+            "{{ user.name }} {{ user.password }} {{ user.password | upcase }} ",
+            "{% if user.password == 'hunter2' %}weak{% endif %} ",
+            "{{ cards[0].number }} {{ cards[0].expiry }} {{ user }}",
+        ))
+        .unwrap();
+
+    let mut user = value::Object::new();
+    user.insert("name".into(), value::Value::scalar("Tom"));
+    user.insert("password".into(), value::Value::scalar("hunter2"));
+    let mut card = value::Object::new();
+    card.insert("number".into(), value::Value::scalar("4111"));
+    card.insert("expiry".into(), value::Value::scalar("12/30"));
+    let mut globals = value::Object::new();
+    globals.insert("user".into(), value::Value::Object(user));
+    globals.insert(
+        "cards".into(),
+        value::Value::array(vec![value::Value::Object(card)]),
+    );
+
+    let output = template.render(&globals).unwrap();
+    assert_eq!(
+        output,
+        "Tom [REDACTED] [REDACTED] weak [REDACTED] 12/30 [REDACTED]"
+    );
+    assert_eq!(
+        *redacted.lock().unwrap(),
+        vec!["user.password", "user.password", "cards.0.number", "user"]
+    );
+}
+
+#[test]
+pub fn environment_strict_variables() {
+    let parser = ParserBuilder::with_liquid()
+        .environment(interpreter::Environment::new().with_strict_variables(true))
+        .build()
+        .unwrap();
+    let mut globals = value::Object::new();
+    globals.insert("name".into(), value::Value::scalar("Ann"));
+
+    let template = parser
+        .parse("{% assign greeting = 'Hi' %}{{ greeting }} {{ name }}{% for i in (1..1) %}{{ i }}{% endfor %}")
+        .unwrap();
+    assert_eq!(template.render(&globals).unwrap(), "Hi Ann1");
+
+    let template = parser.parse("Hi {{ nmae }}").unwrap();
+    let error = template.render(&globals).unwrap_err().to_string();
+    assert!(error.contains("Unknown variable"), "{}", error);
+    assert!(error.contains("nmae"), "{}", error);
+    assert!(parser.parse("{{ name | upcaes }}").is_err());
+}
+
+#[test]
+pub fn environment_auto_escape() {
+    let parser = ParserBuilder::with_liquid()
+        .environment(interpreter::Environment::new().with_auto_escape(true))
+        .build()
+        .unwrap();
+    let mut globals = value::Object::new();
+    globals.insert("html".into(), value::Value::scalar("<b>Tom & Jerry</b>"));
+
+    let template = parser
+        .parse("{{ html }}|{{ html | safe }}|{{ html | raw | upcase }}|{{ html | escape }}")
+        .unwrap();
+    assert_eq!(
+        template.render(&globals).unwrap(),
+        "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;|<b>Tom & Jerry</b>|&lt;B&gt;TOM &amp; JERRY&lt;/B&gt;|&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;"
+    );
+
+    let template = parser
+        .parse("{% capture c %}<i>{{ html }}</i>{% endcapture %}{{ c }}")
+        .unwrap();
+    assert_eq!(
+        template.render(&globals).unwrap(),
+        "<i>&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;</i>"
+    );
+}
+
+#[test]
+pub fn environment_error_policy() {
+    let text = "{% for i in (0..2) %}[{{ 6 | divided_by: i }}]{% endfor %}{% include 'missing' %}!";
+    let parse = |policy: interpreter::ErrorPolicy| {
+        ParserBuilder::with_liquid()
+            .environment(interpreter::Environment::new().with_error_policy(policy))
+            .build()
+            .unwrap()
+            .parse(text)
+            .unwrap()
+    };
+    let globals = value::Object::default();
+
+    assert!(parse(interpreter::ErrorPolicy::fail())
+        .render(&globals)
+        .is_err());
+
+    let (output, errors) = parse(interpreter::ErrorPolicy::skip())
+        .render_with_errors(&globals)
+        .unwrap();
+    assert_eq!(output, "[][6][3]!");
+    assert_eq!(errors.len(), 2);
+
+    let policy =
+        interpreter::ErrorPolicy::placeholder(|error| format!("<!-- {} -->", error.message()));
+    let (output, errors) = parse(policy).render_with_errors(&globals).unwrap();
+    assert_eq!(
+        output,
+        "[<!-- Division by zero -->][6][3]<!-- Partial does not exist -->!"
+    );
+    assert_eq!(errors[0].message(), "Division by zero");
+
+    // Going over a limit still fails the render.
+    let template = ParserBuilder::with_liquid()
+        .environment(
+            interpreter::Environment::new()
+                .with_error_policy(interpreter::ErrorPolicy::skip())
+                .with_limits(interpreter::Limits::new().with_loop_iterations(2)),
+        )
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    assert!(template.render(&globals).is_err());
+}
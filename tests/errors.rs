@@ -0,0 +1,98 @@
+extern crate liquid;
+
+use liquid::*;
+
+fn parser() -> Parser {
+    ParserBuilder::with_liquid().build().unwrap()
+}
+
+#[test]
+pub fn parse_error_position() {
+    let error = parser().parse("a\n  {% nope %}").err().unwrap();
+    assert_eq!(error.line(), Some(2));
+    assert_eq!(error.column(), Some(6));
+    assert_eq!(error.excerpt(), Some("  |\n2 |   {% nope %}\n  |      ^"));
+    let error = error.to_string();
+    assert!(
+        error.starts_with("liquid: Unknown tag.\n --> 2:6\n"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+pub fn parse_error_position_in_block() {
+    let error = parser()
+        .parse("{% if a %}\n\t{{ a | nope }}\n{% endif %}")
+        .err()
+        .unwrap();
+    assert_eq!(error.line(), Some(2));
+    assert_eq!(error.column(), Some(9));
+    assert_eq!(
+        error.excerpt(),
+        Some("  |\n2 | \t{{ a | nope }}\n  | \t       ^")
+    );
+}
+
+#[test]
+pub fn render_error_position() {
+    let template = parser()
+        .parse("{% for i in (1..2) %}\n{{ i | divided_by: 0 }}{% endfor %}")
+        .unwrap();
+    let error = template.render(&value::Object::new()).err().unwrap();
+    assert_eq!(error.line(), Some(2));
+    assert_eq!(error.column(), Some(8));
+    assert_eq!(
+        error.excerpt(),
+        Some("  |\n2 | {{ i | divided_by: 0 }}{% endfor %}\n  |        ^")
+    );
+}
+
+#[test]
+pub fn render_error_position_of_tag() {
+    let template = parser().parse("a\n{% include 'missing' %}").unwrap();
+    let error = template.render(&value::Object::new()).err().unwrap();
+    assert_eq!(error.line(), Some(2));
+    assert_eq!(error.column(), Some(4));
+}
+
+#[test]
+pub fn parse_all_errors() {
+    let errors = parser()
+        .parse_all_errors("{% nope %}\n{% if a %}{{ a | nope }}{% endif %}\n{{ b")
+        .err()
+        .unwrap();
+    let lines: Vec<_> = errors.iter().map(|e| e.line()).collect();
+    assert_eq!(lines, vec![Some(1), Some(2), Some(3)]);
+    assert!(
+        errors[0].to_string().contains("Unknown tag"),
+        "{}",
+        errors[0]
+    );
+    assert!(
+        errors[1].to_string().contains("Unknown filter"),
+        "{}",
+        errors[1]
+    );
+    assert_eq!(errors[2].excerpt(), Some("  |\n3 | {{ b\n  |    ^"));
+}
+
+#[test]
+pub fn parse_all_errors_ends_with_unrecoverable() {
+    let errors = parser()
+        .parse_all_errors("{% nope %}{% if a %}")
+        .err()
+        .unwrap();
+    assert_eq!(errors.len(), 2);
+    assert!(
+        errors[1].to_string().contains("Unclosed block"),
+        "{}",
+        errors[1]
+    );
+}
+
+#[test]
+pub fn parse_all_errors_without_errors() {
+    let template = parser().parse_all_errors("{{ 'a' | upcase }}").unwrap();
+    assert_eq!(template.render(&value::Object::new()).unwrap(), "A");
+}
@@ -484,6 +484,34 @@ pub fn split_sort_join() {
     assert_eq!(output, "Sally Snake, giraffe, octopus, zebra".to_string());
 }
 
+#[test]
+pub fn sort_with_locale() {
+    let text = concat!(
+        "{% assign names = 'Zoë,Ärla,Olof,Åsa,Émile,eric' | split: ',' %}",
+        "{{ names | sort | join: ',' }}\n",
+        "{{ names | sort: locale: 'de-DE' | join: ',' }}\n",
+        "{{ names | sort_natural: locale: 'sv-SE' | join: ',' }}\n",
+        "{{ names | sort_natural: locale: liquid.locale | join: ',' }}"
+    );
+    let globals: liquid::value::Object = Default::default();
+    let template = liquid::ParserBuilder::with_liquid()
+        .environment(liquid::interpreter::Environment::new().with_locale("fr-FR"))
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    let output = template.render(&globals).unwrap();
+    assert_eq!(
+        output,
+        concat!(
+            "Olof,Zoë,eric,Ärla,Åsa,Émile\n",
+            "Ärla,Åsa,Émile,eric,Olof,Zoë\n",
+            "Émile,eric,Olof,Zoë,Åsa,Ärla\n",
+            "Ärla,Åsa,Émile,eric,Olof,Zoë"
+        )
+    );
+}
+
 #[test]
 pub fn modulo() {
     let text = "{{ num | modulo: 2 }}";
@@ -695,3 +723,148 @@ hashes: [{"a": "A"}, {"a": null}, {"a": "C"}, {}]
     let output = template.render(&globals).unwrap();
     assert_eq!(output, "A C".to_string());
 }
+
+#[test]
+#[cfg(feature = "jekyll-filters")]
+fn test_where_exp() {
+    let text = r#"{% assign cheap = items | where_exp: "item", "item.price < limit and item.active" %}{{ cheap | map: "name" | join }}"#;
+    let globals: liquid::value::Object = serde_yaml::from_str(
+        r#"
+limit: 20
+items:
+  - {name: pen, price: 2, active: true}
+  - {name: lamp, price: 30, active: true}
+  - {name: ink, price: 5, active: false}
+  - {name: pad, price: 4, active: true}
+"#,
+    )
+    .unwrap();
+
+    let template = liquid::ParserBuilder::with_liquid()
+        .jekyll_filters()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "pen pad".to_string());
+}
+
+#[cfg(feature = "jekyll-filters")]
+#[test]
+fn test_group_by_exp() {
+    let text = r#"{% assign years = posts | group_by_exp: "post", "post.date | date: '%Y'" %}{% for year in years %}{{ year.name }}: {{ year.items | map: "title" | join: ", " }};{% endfor %}"#;
+    let globals: liquid::value::Object = serde_yaml::from_str(
+        r#"
+posts:
+  - {title: Hello, date: "2018-03-01 10:00:00 +0000"}
+  - {title: Again, date: "2019-01-12 10:00:00 +0000"}
+  - {title: More, date: "2018-11-30 10:00:00 +0000"}
+"#,
+    )
+    .unwrap();
+
+    let template = liquid::ParserBuilder::with_liquid()
+        .jekyll_filters()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "2018: Hello, More;2019: Again;".to_string());
+}
+
+#[test]
+#[cfg(feature = "jekyll-filters")]
+fn test_build_array() {
+    let text = r#"{% for name in names %}{% assign list = list | push: name %}{% endfor %}{% assign list = list | unshift: "first" | pop | shift: 2 %}{{ list | join: ", " }}"#;
+    let globals: liquid::value::Object = serde_yaml::from_str("names: [a, b, c, d]").unwrap();
+
+    let template = liquid::ParserBuilder::with_liquid()
+        .jekyll_filters()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "b, c".to_string());
+}
+
+#[test]
+fn test_property_paths() {
+    let text = r#"{{ orders | where: "meta.flags.featured", true | map: "customer.address.city" | join: ", " }}"#;
+    let globals: liquid::value::Object = serde_yaml::from_str(
+        r#"
+orders:
+  - {customer: {address: {city: Oslo}}, meta: {flags: {featured: true}}}
+  - {customer: {address: {city: Bergen}}, meta: {flags: {featured: false}}}
+  - {customer: {address: {city: Tromsø}}, meta: {flags: {featured: true}}}
+"#,
+    )
+    .unwrap();
+
+    let template = liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "Oslo, Tromsø".to_string());
+}
+
+#[test]
+fn test_ranges() {
+    let text = concat!(
+        "{% assign numbers = (1..1000000) %}",
+        "{{ numbers | size }} {{ numbers | first }} {{ numbers | last }} ",
+        "{{ numbers.size }} {{ numbers.last }} ",
+        "{{ (1..5) | reverse | join: \",\" }} {{ (3..1) | size }}",
+    );
+    let globals = liquid::value::Object::new();
+
+    let template = liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    let output = template.render(&globals).unwrap();
+    assert_eq!(
+        output,
+        "1000000 1 1000000 1000000 1000000 5,4,3,2,1 0".to_string()
+    );
+}
+
+#[test]
+fn test_array_pipelines() {
+    let text = concat!(
+        "{{ products | where: \"active\" | map: \"title\" | first }} ",
+        "{{ products | where: \"active\" | sort: \"date\" | map: \"title\" | first }} ",
+        "{{ products | map: \"title\" | last: 2 | join: \",\" }} ",
+        "{{ products | map: \"price\" | compact | size }} ",
+        "{{ products | map: \"title\" | first: 2 | reverse | join: \",\" }}",
+    );
+    let globals: liquid::value::Object = serde_yaml::from_str(
+        r#"
+products:
+  - title: Hat
+    active: true
+    date: 2019-03-01
+    price: 10
+  - title: Cap
+    active: false
+    date: 2019-01-01
+  - title: Scarf
+    active: true
+    date: 2019-02-01
+    price: 20
+"#,
+    )
+    .unwrap();
+    let template = liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap();
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "Hat Scarf Cap,Scarf 2 Cap,Hat".to_string());
+}
@@ -0,0 +1,68 @@
+extern crate liquid;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use liquid::value::{LazyArray, Object, Value};
+
+/// Rows generated as they are read, counting how many were.
+fn rows(count: i32, generated: &Arc<AtomicUsize>) -> Value {
+    let generated = generated.clone();
+    let rows = LazyArray::new(move || {
+        let generated = generated.clone();
+        (1..=count).map(move |id| {
+            generated.fetch_add(1, Ordering::SeqCst);
+            let mut row = Object::new();
+            row.insert("id".into(), Value::scalar(id));
+            row.insert("even".into(), Value::scalar(id % 2 == 0));
+            Value::Object(row)
+        })
+    });
+    Value::Lazy(rows.with_len(count as usize))
+}
+
+fn render(text: &str, globals: &Object) -> String {
+    liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+        .render(globals)
+        .unwrap()
+}
+
+#[test]
+fn for_loops() {
+    let generated = Arc::new(AtomicUsize::new(0));
+    let mut globals = Object::new();
+    globals.insert("rows".into(), rows(1_000_000, &generated));
+
+    let text = "{% for row in rows limit: 3 offset: 1 %}{{ row.id }}{% endfor %}";
+    assert_eq!(render(text, &globals), "234");
+    assert_eq!(generated.load(Ordering::SeqCst), 4);
+
+    let text =
+        "{% for row in rows %}{{ row.id }}{% if row.id == 3 %}{% break %}{% endif %}{% endfor %}";
+    assert_eq!(render(text, &globals), "123");
+
+    let text = "{% for row in rows limit: 2 reversed %}{{ row.id }}{% endfor %}";
+    assert_eq!(render(text, &globals), "21");
+    assert_eq!(generated.load(Ordering::SeqCst), 4 + 3 + 1_000_000);
+}
+
+#[test]
+fn filters() {
+    let generated = Arc::new(AtomicUsize::new(0));
+    let mut globals = Object::new();
+    globals.insert("rows".into(), rows(10, &generated));
+
+    let text = "{{ rows | where: \"even\" | map: \"id\" | first }}";
+    assert_eq!(render(text, &globals), "2");
+    assert_eq!(generated.load(Ordering::SeqCst), 2);
+
+    let text = concat!(
+        "{{ rows | size }} {{ rows.size }} {{ rows.last.id }} ",
+        "{{ rows | map: \"id\" | reverse | first }} {{ rows | map: \"id\" | last: 2 | join: \",\" }}",
+    );
+    assert_eq!(render(text, &globals), "10 10 10 10 9,10");
+}
@@ -0,0 +1,98 @@
+extern crate liquid;
+
+use std::time::Duration;
+
+use liquid::error::ErrorKind;
+use liquid::interpreter::{Environment, Limits};
+use liquid::value::Object;
+
+fn render(text: &str, limits: Limits) -> liquid::error::Result<String> {
+    liquid::ParserBuilder::with_liquid()
+        .environment(Environment::new().with_limits(limits))
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+        .render(&Object::new())
+}
+
+#[test]
+fn output_bytes() {
+    let text = "{% for i in (1..10) %}{{ i }}{% endfor %}";
+    assert_eq!(
+        render(text, Limits::new().with_output_bytes(11)).unwrap(),
+        "12345678910"
+    );
+    let error = render(text, Limits::new().with_output_bytes(10)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+    assert!(error.to_string().contains("limit=output bytes"));
+}
+
+#[test]
+fn loop_iterations() {
+    let text = "{% for i in (1..3) %}{% for j in (1..3) %}.{% endfor %}{% endfor %}";
+    assert!(render(text, Limits::new().with_loop_iterations(12)).is_ok());
+    let error = render(text, Limits::new().with_loop_iterations(11)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+    assert!(error.to_string().contains("limit=loop iterations"));
+}
+
+#[test]
+fn assigned_bytes() {
+    let text = "{% for i in (1..10) %}{% capture s %}{{ s }}xxxxxxxxxx{% endcapture %}{% endfor %}";
+    let error = render(text, Limits::new().with_assigned_bytes(200)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+    assert!(error.to_string().contains("limit=assigned bytes"));
+    assert!(render(text, Limits::new().with_assigned_bytes(1000)).is_ok());
+}
+
+#[test]
+fn render_time() {
+    let text = "{% for i in (1..100000000) %}{% endfor %}";
+    let error = render(
+        text,
+        Limits::new().with_render_time(Duration::from_millis(10)),
+    )
+    .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+    assert!(error.to_string().contains("limit=render time"));
+}
+
+#[test]
+fn other_errors() {
+    let error = render("{{ 1 | divided_by: 0 }}", Limits::new()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Other);
+}
+
+fn render_partials(text: &str, environment: Environment) -> liquid::error::Result<String> {
+    let mut partials = liquid::Partials::empty();
+    partials.add("a", "a{% include 'b' %}");
+    partials.add("b", "b{% render 'a' %}");
+    partials.add(
+        "countdown",
+        "{{ n }}{% if n > 0 %}{% assign m = n | minus: 1 %}{% include 'countdown', n: m %}{% endif %}",
+    );
+    liquid::ParserBuilder::with_liquid()
+        .partials(partials)
+        .environment(environment)
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+        .render(&Object::new())
+}
+
+#[test]
+fn partial_depth() {
+    let error = render_partials("{% include 'a' %}", Environment::new()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+    let message = error.to_string();
+    assert!(message.contains("Include depth exceeded"));
+    assert!(message.contains("partials=a > b > a"));
+    assert!(message.contains("maximum=32"));
+
+    let text = "{% include 'countdown', n: 3 %}";
+    assert_eq!(render_partials(text, Environment::new()).unwrap(), "3210");
+    let error = render_partials(text, Environment::new().with_partial_depth(3)).unwrap_err();
+    assert!(error.to_string().contains("partials=countdown > countdown"));
+}
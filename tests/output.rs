@@ -0,0 +1,85 @@
+extern crate liquid;
+
+use std::io;
+use std::io::Write;
+
+use liquid::interpreter::Output;
+use liquid::value::Value;
+use liquid::*;
+
+/// Escapes the values of expressions, leaving template text alone.
+struct Escaping<O>(O);
+
+impl<O: Output> Write for Escaping<O> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<O: Output> Output for Escaping<O> {
+    fn write_value(&mut self, value: &str, safe: bool) -> io::Result<()> {
+        if safe {
+            self.0.write_value(value, safe)
+        } else {
+            let escaped = value.replace('&', "&amp;").replace('<', "&lt;");
+            self.0.write_value(&escaped, true)
+        }
+    }
+}
+
+#[test]
+pub fn output_wrapper() {
+    let template = ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse("<b>{{ name }}</b>{% cycle '<i>', 'b' %}")
+        .unwrap();
+
+    let mut globals = value::Object::new();
+    globals.insert("name".into(), Value::scalar("Tom & <Jerry>"));
+
+    let mut output = Escaping(Vec::new());
+    template.render_to_output(&mut output, &globals).unwrap();
+    assert_eq!(
+        String::from_utf8(output.0).unwrap(),
+        "<b>Tom &amp; &lt;Jerry></b>&lt;i>"
+    );
+
+    let mut plain = Vec::new();
+    template.render_to(&mut plain, &globals).unwrap();
+    assert_eq!(String::from_utf8(plain).unwrap(), "<b>Tom & <Jerry></b><i>");
+}
+
+fn render(text: &str, globals: &value::Object) -> String {
+    ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+        .render(globals)
+        .unwrap()
+}
+
+#[test]
+pub fn negative_indexes() {
+    let globals = value::liquid_value!({"items": ["a", "b", "c"]});
+    let text = "{% assign sizes = (1..5) %}{{ items[-1] }} {{ items[-3] }} {{ sizes[-2] }}";
+    assert_eq!(render(text, globals.as_object().unwrap()), "c a 4");
+}
+
+#[test]
+pub fn indexes_of_variables() {
+    let globals = value::liquid_value!({
+        "items": ["a", "b", "c"],
+        "pointer": 1,
+        "pointers": [0, -1],
+        "products": ["shoes", "socks"],
+        "prices": {"shoes": 80, "socks": 20}
+    });
+    let text = "{{ items[pointer] }} {{ items[pointers.last] }} {{ prices[products[1]] }}";
+    assert_eq!(render(text, globals.as_object().unwrap()), "b c 20");
+}
@@ -0,0 +1,262 @@
+extern crate liquid;
+
+use liquid::compiler::{BlockReflection, Filter, Language, ParseRawBlock, RawBody, TagTokenIter};
+use liquid::derive::*;
+use liquid::error::{Result, ResultLiquidReplaceExt};
+use liquid::interpreter::{Context, Output, Renderable};
+use liquid::value::Value;
+use liquid::*;
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "font_modify",
+    description = "Returns a copy of a font with a different weight.",
+    parsed(FontModifyFilter)
+)]
+pub struct FontModify;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "font_modify"]
+struct FontModifyFilter;
+
+impl Filter for FontModifyFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        let mut font = input.as_object().cloned().unwrap_or_default();
+        font.insert("weight".into(), Value::scalar("bold"));
+        Ok(Value::Object(font))
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "upcase",
+    description = "Overrides the built-in `upcase`.",
+    parsed(ShoutFilter)
+)]
+pub struct Shout;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "upcase"]
+struct ShoutFilter;
+
+impl Filter for ShoutFilter {
+    fn evaluate(&self, input: &Value, _context: &Context) -> Result<Value> {
+        Ok(Value::scalar(format!("{}!", input.to_str().to_uppercase())))
+    }
+}
+
+#[derive(Clone)]
+struct HighlightBlock;
+
+impl BlockReflection for HighlightBlock {
+    fn start_tag(&self) -> &'static str {
+        "highlight"
+    }
+
+    fn end_tag(&self) -> &'static str {
+        "endhighlight"
+    }
+
+    fn description(&self) -> &'static str {
+        "Wraps code in a `<pre>` of its language."
+    }
+}
+
+impl ParseRawBlock for HighlightBlock {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        body: &str,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let language = arguments
+            .expect_next("Language expected.")?
+            .expect_identifier()
+            .into_result()?
+            .to_owned();
+        arguments.expect_nothing()?;
+        Ok(Box::new(Highlight {
+            language,
+            code: body.to_owned(),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Highlight {
+    language: String,
+    code: String,
+}
+
+impl Renderable for Highlight {
+    fn render_to(&self, writer: &mut dyn Output, _context: &mut Context) -> Result<()> {
+        write!(
+            writer,
+            "<pre lang=\"{}\">{}</pre>",
+            self.language, self.code
+        )
+        .replace("Failed to render")?;
+        Ok(())
+    }
+}
+
+struct FontPlugin;
+
+impl Plugin for FontPlugin {
+    fn register<P>(&self, builder: ParserBuilder<P>) -> ParserBuilder<P>
+    where
+        P: partials::PartialCompiler,
+    {
+        builder.filter(FontModify).filter(Shout)
+    }
+}
+
+#[test]
+pub fn plugin_registers_filters() {
+    let template = ParserBuilder::with_liquid()
+        .plugin(FontPlugin)
+        .build()
+        .unwrap()
+        .parse("{% assign bold = font | font_modify %}{{ bold.family }} {{ bold.weight }}")
+        .unwrap();
+
+    let globals = value::liquid_value!({"font": {"family": "Lato", "weight": "normal"}});
+    let output = template.render(globals.as_object().unwrap()).unwrap();
+    assert_eq!(output, "Lato bold");
+}
+
+#[test]
+pub fn plugin_overrides_builtins() {
+    let template = ParserBuilder::with_liquid()
+        .plugin(FontPlugin)
+        .build()
+        .unwrap()
+        .parse("{{ 'hi' | upcase }}")
+        .unwrap();
+
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "HI!");
+}
+
+#[test]
+pub fn filter_alias() {
+    let template = ParserBuilder::with_liquid()
+        .filter_alias("uppercase", "upcase")
+        .build()
+        .unwrap()
+        .parse("{{ 'hi' | uppercase }} {{ 'hi' | upcase }}")
+        .unwrap();
+
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "HI HI");
+}
+
+#[test]
+pub fn filter_alias_unknown() {
+    assert!(ParserBuilder::with_liquid()
+        .filter_alias("uppercase", "shout")
+        .build()
+        .is_err());
+}
+
+#[test]
+pub fn conflict_error() {
+    assert!(ParserBuilder::with_liquid()
+        .on_conflict(ConflictPolicy::Error)
+        .filter(Shout)
+        .build()
+        .is_err());
+    assert!(ParserBuilder::with_liquid()
+        .on_conflict(ConflictPolicy::Error)
+        .filter_alias("append", "prepend")
+        .build()
+        .is_err());
+}
+
+#[test]
+pub fn conflict_explicit_override() {
+    let template = ParserBuilder::with_liquid()
+        .on_conflict(ConflictPolicy::Error)
+        .override_filter(Shout)
+        .build()
+        .unwrap()
+        .parse("{{ 'hi' | upcase }}")
+        .unwrap();
+
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "HI!");
+}
+
+#[test]
+pub fn language_overrides() {
+    let base = ParserBuilder::with_liquid().build().unwrap();
+    let admin = base.with_overrides(&LanguageOverrides::new().filter(FontModify).filter(Shout));
+    let user = base.with_overrides(&LanguageOverrides::new().without_filter("upcase"));
+
+    let globals = value::Object::default();
+    let render =
+        |parser: &Parser, text: &str| parser.parse(text).unwrap().render(&globals).unwrap();
+    assert_eq!(render(&admin, "{{ 'hi' | upcase }}"), "HI!");
+    assert_eq!(render(&admin, "{{ f | font_modify | size }}"), "1");
+    assert_eq!(render(&base, "{{ 'hi' | upcase }}"), "HI");
+    assert!(base.parse("{{ f | font_modify }}").is_err());
+    assert!(user.parse("{{ 'hi' | upcase }}").is_err());
+    assert_eq!(render(&user, "{{ 'hi' | downcase }}"), "hi");
+}
+
+#[test]
+pub fn language_lists_plugins() {
+    let parser = ParserBuilder::new()
+        .plugin(FontPlugin)
+        .block(tags::IfBlock)
+        .tag(tags::AssignTag)
+        .build()
+        .unwrap();
+    let language = parser.language();
+
+    let filters: Vec<_> = language
+        .filters()
+        .map(|f| (f.name(), f.description()))
+        .collect();
+    assert_eq!(
+        filters,
+        vec![
+            (
+                "font_modify",
+                "Returns a copy of a font with a different weight."
+            ),
+            ("upcase", "Overrides the built-in `upcase`."),
+        ]
+    );
+    let blocks: Vec<_> = language.blocks().map(|b| b.start_tag()).collect();
+    assert_eq!(blocks, vec!["if"]);
+    let tags: Vec<_> = language.tags().map(|t| t.tag()).collect();
+    assert_eq!(tags, vec!["assign"]);
+}
+
+#[test]
+pub fn raw_body_block() {
+    let parser = ParserBuilder::with_liquid()
+        .block(RawBody(HighlightBlock))
+        .build()
+        .unwrap();
+    let globals = value::Object::default();
+    let render = |text: &str| parser.parse(text).unwrap().render(&globals).unwrap();
+
+    assert_eq!(
+        render("{% highlight rust %}{{ x }}{% if %}{{ }{% endhighlight %}"),
+        "<pre lang=\"rust\">{{ x }}{% if %}{{ }</pre>"
+    );
+    assert_eq!(
+        render("{% highlight liquid %}{% highlight rust %}a{% endhighlight %}{% endhighlight %}!"),
+        "<pre lang=\"liquid\">{% highlight rust %}a{% endhighlight %}</pre>!"
+    );
+    assert!(parser.parse("{% highlight %}a{% endhighlight %}").is_err());
+    assert!(parser
+        .parse("{% highlight rust %}{% highlight rust %}{% endhighlight %}")
+        .is_err());
+}
@@ -0,0 +1,65 @@
+extern crate liquid;
+
+fn parse(text: &str) -> liquid::Template {
+    liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+}
+
+#[test]
+fn variables_in_outputs_and_tags() {
+    let template = parse(concat!(
+        "{{ user.address.city }}",
+        "{% if user.admin and site['title'] %}{{ products[index].title }}{% endif %}",
+        "{% case shipping.method %}{% when free %}{% endcase %}",
+    ));
+    assert_eq!(
+        template.variables(),
+        vec![
+            "user.address.city",
+            "user.admin",
+            "site.title",
+            "index",
+            "products",
+            "shipping.method",
+            "free",
+        ]
+    );
+}
+
+#[test]
+fn variables_in_filter_arguments() {
+    let template = parse("{{ price | times: rate | append: currency.symbol | round }}");
+    assert_eq!(
+        template.variables(),
+        vec!["price", "rate", "currency.symbol"]
+    );
+}
+
+#[test]
+fn variables_set_by_the_template() {
+    let template = parse(concat!(
+        "{% for item in cart.items limit: max %}{{ item.title }}{{ forloop.index }}",
+        "{% assign total = item.price %}{% endfor %}",
+        "{{ total }}{{ item }}",
+        "{% capture greeting %}Hi {{ name }}{% endcapture %}{{ greeting }}",
+        "{% increment counter %}{{ counter }}",
+    ));
+    assert_eq!(
+        template.variables(),
+        vec!["cart.items", "max", "item", "name"]
+    );
+}
+
+#[test]
+fn partials() {
+    let template = parse(concat!(
+        "{% include 'header' %}",
+        "{% render 'product' with featured as item, title: page.title %}",
+        "{% include 'header' %}",
+    ));
+    assert_eq!(template.partials(), vec!["header", "product"]);
+    assert_eq!(template.variables(), vec!["featured", "page.title"]);
+}
@@ -0,0 +1,85 @@
+extern crate liquid;
+extern crate serde_json;
+
+use liquid::value::{Object, Value};
+
+fn parser() -> liquid::Parser {
+    let mut partials = liquid::Partials::empty();
+    partials.add("greeting", "Hi {{ name | capitalize }}");
+    liquid::ParserBuilder::with_liquid()
+        .partials(partials)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn report_usage() {
+    let template = parser()
+        .parse(concat!(
+            "{% include 'greeting' %}{{ user.city | default: 'nowhere' | upcase }}",
+            "{% for i in (1..2) %}{{ i | append: missing }}{% endfor %}",
+        ))
+        .unwrap();
+    let mut globals = Object::new();
+    globals.insert("name".into(), Value::scalar("ada"));
+
+    let (output, report) = template.render_with_report(&globals).unwrap();
+    assert_eq!(output, "Hi AdaNOWHERE12");
+    assert_eq!(report.undefined_variables, vec!["missing", "user.city"]);
+    let filters: Vec<_> = report
+        .filters
+        .iter()
+        .map(|(name, count)| (name.as_str(), *count))
+        .collect();
+    assert_eq!(
+        filters,
+        vec![
+            ("append", 2),
+            ("capitalize", 1),
+            ("default", 1),
+            ("upcase", 1)
+        ]
+    );
+    assert_eq!(report.partials, vec!["greeting"]);
+    assert_eq!(report.output_bytes, output.len());
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn report_warnings() {
+    let template = parser()
+        .parse("{% if 'a' == 'b' %}a{% else %}b{% endif %}")
+        .unwrap();
+    let (_, report) = template.render_with_report(&Object::new()).unwrap();
+    let messages: Vec<_> = report.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec!["Condition is always false", "Unreachable branch"]
+    );
+    assert_eq!(
+        report.warnings[1].context[0],
+        ("branch".to_owned(), "if".to_owned())
+    );
+}
+
+#[test]
+fn report_is_deterministic() {
+    let template = parser()
+        .parse("{% include 'greeting' %}{{ a | append: b }}")
+        .unwrap();
+    let globals = Object::new();
+    let (_, mut first) = template.render_with_report(&globals).unwrap();
+    let (_, mut second) = template.render_with_report(&globals).unwrap();
+    first.duration = Default::default();
+    second.duration = Default::default();
+    assert_eq!(
+        serde_json::to_string(&first).unwrap(),
+        serde_json::to_string(&second).unwrap()
+    );
+    let json = serde_json::to_value(&first).unwrap();
+    assert_eq!(
+        json["undefined_variables"],
+        serde_json::json!(["a", "b", "name"])
+    );
+    assert_eq!(json["filters"]["append"], 1);
+}
@@ -71,3 +71,40 @@ topic1-bar\
 ",
     );
 }
+
+#[test]
+pub fn whitespace_control_trims_tabs() {
+    compare("a…\t\n\t{{- 'b' -}}\t…\n…c", "abc");
+    compare("{%\tif true\t%}a{%\tendif\t%}", "a");
+}
+
+#[test]
+pub fn whitespace_control_around_blocks() {
+    compare(
+        "
+<ul>
+……{%- for i in (1..2) %}
+……<li>{{ i }}</li>
+……{%- endfor %}
+</ul>
+",
+        "
+<ul>
+……<li>1</li>
+……<li>2</li>
+</ul>
+",
+    );
+}
+
+#[test]
+pub fn whitespace_control_around_raw() {
+    compare("a…\n{%- raw -%}…{{ b }}…{%- endraw -%}\n…c", "a{{ b }}c");
+    compare("a…{%- raw %}…{{- b -}}…{% endraw -%}…c", "a…{{- b -}}…c");
+}
+
+#[test]
+pub fn whitespace_control_keeps_other_text() {
+    compare("a…b…{{- 'c' -}}…d…e", "a…bcd…e");
+    compare("-…{{- '-' -}}…-", "---");
+}
@@ -0,0 +1,67 @@
+extern crate liquid;
+
+use liquid::value::Value;
+use liquid::*;
+
+#[test]
+pub fn template_file() {
+    let cart = liquid::template!(
+        "tests/fixtures/template_macro/cart.liquid",
+        filters = ["pluralize"]
+    );
+    let parser = ParserBuilder::with_liquid()
+        .extra_filters()
+        .build()
+        .unwrap();
+    let template = cart.parse(&parser).unwrap();
+
+    let mut globals = value::Object::new();
+    globals.insert("name".into(), Value::scalar("ann"));
+    globals.insert("count".into(), Value::scalar(2));
+    let output = template.render(&globals).unwrap();
+    assert_eq!(output, "Hi ANN, you have 2 items.\n");
+}
+
+#[test]
+pub fn template_string() {
+    let greeting = liquid::template_str!(
+        "{% if name %}Hello {{ name | upcase }}{% else %}{% greet %}{% endif %}{% note %}x{% endnote %}",
+        tags = ["greet"],
+        blocks = ["note"],
+    );
+    assert!(greeting.source().starts_with("{% if name %}"));
+}
+
+#[test]
+pub fn checked_names_are_those_with_liquid_registers() {
+    fn sorted<'a, I: IntoIterator<Item = &'a str>>(names: I) -> Vec<&'a str> {
+        let mut names: Vec<_> = names.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    liquid::template_str!("{{ name | safe }}{{ name | raw }}");
+
+    let parser = ParserBuilder::with_liquid().build().unwrap();
+    let language = parser.language();
+    assert_eq!(
+        sorted(language.tags.plugin_names()),
+        sorted(compiler::LIQUID_TAGS.iter().cloned())
+    );
+    assert_eq!(
+        sorted(language.blocks.plugin_names()),
+        sorted(compiler::LIQUID_BLOCKS.iter().cloned())
+    );
+    assert_eq!(
+        sorted(language.filters.plugin_names()),
+        sorted(compiler::LIQUID_FILTERS.iter().cloned())
+    );
+}
+
+#[cfg(feature = "markdown-filters")]
+#[test]
+pub fn checked_names_follow_features() {
+    let note = liquid::template_str!("{{ note | markdownify }}");
+    let parser = ParserBuilder::with_liquid().build().unwrap();
+    assert!(note.parse(&parser).is_ok());
+}
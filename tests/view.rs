@@ -0,0 +1,104 @@
+extern crate liquid;
+
+use std::collections::HashMap;
+
+use liquid::derive::ObjectView;
+use liquid::value::{ObjectView, Value, ValueView};
+use liquid::ViewStore;
+
+#[derive(Debug, ObjectView)]
+struct Product {
+    title: String,
+    price: f64,
+    tags: Vec<String>,
+    #[object_view(rename = "type")]
+    kind: &'static str,
+    #[object_view(skip)]
+    #[allow(dead_code)]
+    cost: f64,
+    variant: Option<Variant>,
+}
+
+#[derive(Debug, ObjectView)]
+struct Variant {
+    sku: String,
+    stock: u32,
+}
+
+#[derive(Debug, ObjectView)]
+struct Page {
+    product: Product,
+    related: Vec<Product>,
+}
+
+fn product(title: &str) -> Product {
+    Product {
+        title: title.to_owned(),
+        price: 9.5,
+        tags: vec!["new".to_owned(), "sale".to_owned()],
+        kind: "hat",
+        cost: 2.0,
+        variant: None,
+    }
+}
+
+fn render(text: &str, globals: &dyn ObjectView) -> String {
+    liquid::ParserBuilder::with_liquid()
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+        .render(&ViewStore::new(globals))
+        .unwrap()
+}
+
+#[test]
+fn derived_object_view() {
+    let mut page = Page {
+        product: product("Hat"),
+        related: vec![product("Cap"), product("Beanie")],
+    };
+    page.product.variant = Some(Variant {
+        sku: "H-1".to_owned(),
+        stock: 3,
+    });
+
+    let object = page.product.as_object().unwrap();
+    let keys: Vec<_> = object.keys().collect();
+    assert_eq!(keys, vec!["title", "price", "tags", "type", "variant"]);
+    assert!(object.get("cost").is_none());
+
+    let text = concat!(
+        "{{ product.title }} ({{ product.type }}): {{ product.price | times: 2 }}, ",
+        "{{ product.tags | join: '/' }}, {{ product.tags.size }}, {{ product.variant.sku }}, ",
+        "{% for p in related %}{{ p.title }}{% unless forloop.last %}+{% endunless %}{% endfor %}, ",
+        "{{ related.last.title }}"
+    );
+    assert_eq!(
+        render(text, &page),
+        "Hat (hat): 19, new/sale, 2, H-1, Cap+Beanie, Beanie"
+    );
+}
+
+#[test]
+fn nil_fields() {
+    let page = Page {
+        product: product("Hat"),
+        related: vec![],
+    };
+    assert_eq!(
+        render(
+            "{% if product.variant %}variant{% else %}none{% endif %}{{ related.size }}",
+            &page
+        ),
+        "none0"
+    );
+    assert_eq!(page.product.variant.to_value(), Value::Nil);
+}
+
+#[test]
+fn maps_are_object_views() {
+    let mut globals = HashMap::new();
+    globals.insert("count".to_owned(), 3);
+    assert_eq!(render("{{ count | plus: 1 }}", &globals), "4");
+}
@@ -0,0 +1,274 @@
+extern crate liquid;
+
+use std::sync::{Arc, Mutex};
+
+use liquid::compiler::{
+    Deprecation, Language, LoopShadowing, ParseTag, Recovery, TagReflection, TagTokenIter, Warning,
+};
+use liquid::error::{Result, ResultLiquidReplaceExt};
+use liquid::interpreter::{Context, Output, Renderable};
+use liquid::*;
+
+#[derive(Clone, Debug)]
+struct OldTag;
+
+impl Renderable for OldTag {
+    fn render_to(&self, writer: &mut dyn Output, _context: &mut Context) -> Result<()> {
+        write!(writer, "old").replace("Failed to render")
+    }
+}
+
+impl TagReflection for OldTag {
+    fn tag(&self) -> &'static str {
+        "old"
+    }
+
+    fn description(&self) -> &'static str {
+        "A tag that is no longer recommended."
+    }
+
+    fn deprecated(&self) -> Option<Deprecation> {
+        Some(Deprecation {
+            message: "Use `new` instead.",
+            replacement: None,
+        })
+    }
+}
+
+impl ParseTag for OldTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        arguments.expect_nothing()?;
+        Ok(Box::new(OldTag))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+fn collect_warnings(source: &str) -> Vec<Warning> {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    ParserBuilder::with_liquid()
+        .tag(OldTag)
+        .on_warning(move |warning| sink.lock().unwrap().push(warning))
+        .build()
+        .unwrap()
+        .parse(source)
+        .unwrap();
+    let warnings = warnings.lock().unwrap();
+    warnings.clone()
+}
+
+#[test]
+pub fn deprecated_tag_warns() {
+    let warnings = collect_warnings("a\n{% old %}");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].message(), "Deprecated tag");
+    assert_eq!(warnings[0].get("tag"), Some("old"));
+    assert_eq!(warnings[0].get("cause"), Some("Use `new` instead."));
+    assert_eq!(warnings[0].get("line"), Some("2"));
+}
+
+#[test]
+pub fn no_warnings_without_deprecations() {
+    assert!(collect_warnings("{{ 'a' | upcase }}{% if true %}b{% endif %}").is_empty());
+}
+
+#[test]
+pub fn warnings_do_not_fail_parsing() {
+    let template = ParserBuilder::with_liquid()
+        .tag(OldTag)
+        .build()
+        .unwrap()
+        .parse("{% old %}")
+        .unwrap();
+    let output = template.render(&value::Object::default()).unwrap();
+    assert_eq!(output, "old");
+}
+
+#[test]
+pub fn warning_display() {
+    let warning = Warning::deprecated(
+        "filter",
+        "old_args",
+        Deprecation {
+            message: "Kept for old themes.",
+            replacement: Some("no_args"),
+        },
+    );
+    assert_eq!(
+        warning.to_string(),
+        "liquid: Deprecated filter\n  with:\n    filter=old_args\n    cause=Kept for old themes.\n    replacement=no_args\n"
+    );
+}
+
+fn collect_shadowing_warnings(source: &str) -> Vec<Warning> {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    ParserBuilder::with_liquid()
+        .loop_shadowing(LoopShadowing::Warn)
+        .on_warning(move |warning| sink.lock().unwrap().push(warning))
+        .build()
+        .unwrap()
+        .parse(source)
+        .unwrap();
+    let warnings = warnings.lock().unwrap();
+    warnings.clone()
+}
+
+#[test]
+pub fn loop_shadowing_warns() {
+    let warnings = collect_shadowing_warnings(
+        "{% for item in items %}\n{% for item in item.children %}{% endfor %}{% endfor %}",
+    );
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message(),
+        "Loop variable shadows another variable"
+    );
+    assert_eq!(warnings[0].get("variable"), Some("item"));
+    assert_eq!(warnings[0].get("shadowed"), Some("enclosing loop"));
+    assert_eq!(warnings[0].get("line"), Some("2"));
+
+    let warnings =
+        collect_shadowing_warnings("{% assign title = 'a' %}{% for title in titles %}{% endfor %}");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].get("shadowed"), Some("earlier assignment"));
+}
+
+#[test]
+pub fn loop_shadowing_ignores_sibling_loops() {
+    assert!(collect_shadowing_warnings(
+        "{% for item in a %}{% endfor %}{% for item in b %}{% endfor %}\
+         {% for item in c %}{% comment %}{% for item in d %}{% endfor %}{% endcomment %}\
+         {% raw %}{% for item in e %}{% endraw %}{% endfor %}"
+    )
+    .is_empty());
+}
+
+#[test]
+pub fn loop_shadowing_error() {
+    let parser = ParserBuilder::with_liquid()
+        .loop_shadowing(LoopShadowing::Error)
+        .build()
+        .unwrap();
+    let error = parser
+        .parse("{% capture row %}{% endcapture %}{% tablerow row in rows %}{% endtablerow %}")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(
+        error.contains("Loop variable shadows another variable"),
+        "{}",
+        error
+    );
+    assert!(error.contains("variable=row"), "{}", error);
+
+    let parser = ParserBuilder::with_liquid().build().unwrap();
+    assert!(parser
+        .parse("{% for item in a %}{% for item in b %}{% endfor %}{% endfor %}")
+        .is_ok());
+}
+
+#[test]
+pub fn constant_condition_warns() {
+    let warnings = collect_warnings("{% if \"a\" == \"b\" %}a{% else %}b{% endif %}");
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].message(), "Condition is always false");
+    assert_eq!(warnings[0].get("tag"), Some("if"));
+    assert_eq!(warnings[0].get("line"), Some("1"));
+    assert_eq!(warnings[1].message(), "Unreachable branch");
+    assert_eq!(warnings[1].get("branch"), Some("if"));
+
+    let warnings = collect_warnings("{% if a %}\n{% elsif 1 < 2 or b %}{% else %}{% endif %}");
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].message(), "Condition is always true");
+    assert_eq!(warnings[0].get("tag"), Some("elsif"));
+    assert_eq!(warnings[0].get("line"), Some("2"));
+    assert_eq!(warnings[1].get("branch"), Some("else"));
+
+    let warnings = collect_warnings("{% unless \"Star Wars\" contains \"War\" %}a{% endunless %}");
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].message(), "Condition is always true");
+    assert_eq!(warnings[1].get("branch"), Some("unless"));
+
+    let warnings = collect_warnings("{% if 1 == 1 %}a{% endif %}");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].message(), "Condition is always true");
+}
+
+#[test]
+pub fn constant_condition_ignores_variables() {
+    assert!(collect_warnings("{% if a == \"b\" %}{% endif %}").is_empty());
+    assert!(collect_warnings("{% if a and 1 == 1 %}{% endif %}").is_empty());
+    assert!(collect_warnings("{% if false %}disabled{% endif %}").is_empty());
+}
+
+fn parse_recovering(recovery: Recovery, source: &str) -> (String, Vec<Warning>) {
+    let parser = ParserBuilder::with_liquid()
+        .recovery(recovery)
+        .build()
+        .unwrap();
+    let (template, warnings) = compiler::collect_warnings(|| parser.parse(source));
+    let output = template.unwrap().render(&value::Object::default()).unwrap();
+    (output, warnings)
+}
+
+#[test]
+pub fn recovery_keeps_markup_as_text() {
+    let (output, warnings) = parse_recovering(
+        Recovery::Text,
+        "a{% unknown %}b{{ 'c' | no_such_filter }}d{% if true %}{% bad %}e{% endif %}{{ f",
+    );
+    assert_eq!(
+        output,
+        "a{% unknown %}b{{ 'c' | no_such_filter }}d{% bad %}e{{ f"
+    );
+    assert_eq!(warnings.len(), 4);
+    assert_eq!(
+        warnings[0].message(),
+        "Recovered from markup that can't be parsed"
+    );
+    assert_eq!(warnings[0].get("markup"), Some("{% unknown %}"));
+    assert_eq!(warnings[0].get("line"), Some("1"));
+    assert!(warnings[0].get("cause").unwrap().contains("Unknown tag"));
+    assert_eq!(
+        warnings[1].get("markup"),
+        Some("{{ 'c' | no_such_filter }}")
+    );
+    assert_eq!(warnings[3].get("markup"), Some("{"));
+}
+
+#[test]
+pub fn recovery_replaces_markup_with_comment() {
+    let (output, warnings) = parse_recovering(
+        Recovery::Comment,
+        "a\n{% assign %}b{% un--known %}{{ 'c' | nope }}",
+    );
+    assert_eq!(
+        output,
+        "a\n<!-- liquid: Invalid tag `assign` on line 2 -->b\
+         <!-- liquid: Unknown tag `un- -known` on line 2 -->\
+         <!-- liquid: Invalid output on line 2 -->"
+    );
+    assert_eq!(warnings.len(), 3);
+}
+
+#[test]
+pub fn recovery_fails_by_default() {
+    let parser = ParserBuilder::with_liquid().build().unwrap();
+    assert!(parser.parse("{% unknown %}").is_err());
+    assert!(parser.parse("{{ a | nope }}").is_err());
+    assert!(parser.parse("{{ a").is_err());
+
+    let parser = ParserBuilder::with_liquid()
+        .recovery(Recovery::Text)
+        .build()
+        .unwrap();
+    assert!(parser.parse("{% if true %}a").is_err());
+}